@@ -1,21 +1,207 @@
+use std::future::Future;
 use std::sync::Arc;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 use tracing::{info, debug, warn};
 use crate::models::{
-    VocabularyItem, Stage1Result, Stage2Result, CacheStats, CacheType, PipelineError
+    VocabularyItem, Stage1Result, Stage2Result, Stage2Diff, CacheEntry, CacheStats, CacheType, ForceRefresh, KeyNormalization, Stage2Mode,
+    PipelineError, DEFAULT_NAMESPACE, model_pinned_key, prompt_versioned_key
 };
-use crate::database::{DatabasePool, repositories::CacheRepository};
+use crate::database::{ConnectionLimiter, DatabasePool, repositories::{CacheRepository, VocabularyRepository}};
+use crate::logging::{log_cache_hit, log_cache_miss, CacheMissReason, RequestHashCheck};
+
+/// Items checked between cache warm checkpoints
+pub const DEFAULT_WARM_CHECKPOINT_INTERVAL: usize = 500;
+
+/// How [`CacheManager::warm_cache_for_batch_with_options`] runs.
+#[derive(Debug, Clone, Copy)]
+pub struct WarmupOptions {
+    /// Continue from the checkpoint an interrupted warm over the same items
+    /// left, instead of starting over
+    pub resume: bool,
+    /// Don't check Stage 2
+    pub stage1_only: bool,
+    /// Items checked between checkpoints
+    pub checkpoint_interval: usize,
+}
+
+impl Default for WarmupOptions {
+    fn default() -> Self {
+        Self {
+            resume: false,
+            stage1_only: false,
+            checkpoint_interval: DEFAULT_WARM_CHECKPOINT_INTERVAL,
+        }
+    }
+}
 
 pub struct CacheManager {
     repository: Arc<CacheRepository>,
+    vocabulary: VocabularyRepository,
+    key_normalization: KeyNormalization,
+    respect_request_hash: bool,
+    force_refresh: ForceRefresh,
+    stage2_mode: Stage2Mode,
+    namespace: String,
+    stage1_prompt_version: Option<String>,
+    stage2_prompt_version: Option<String>,
+    stage1_model: Option<String>,
+    stage2_model: Option<String>,
+    stage1_request_model: Option<String>,
+    stage2_request_model: Option<String>,
+    connection_limiter: Option<ConnectionLimiter>,
+    explain: bool,
+}
+
+/// Whether a cached entry may be served, and if not, why
+enum Freshness {
+    Fresh(RequestHashCheck),
+    Stale(CacheMissReason),
+}
+
+fn stage_name(cache_type: &CacheType) -> &'static str {
+    match cache_type {
+        CacheType::Stage1 => "stage1",
+        CacheType::Stage2 => "stage2",
+    }
 }
 
 impl CacheManager {
     pub fn new(pool: DatabasePool) -> Self {
         Self {
-            repository: Arc::new(CacheRepository::new(pool)),
+            repository: Arc::new(CacheRepository::new(pool.clone())),
+            vocabulary: VocabularyRepository::new(pool),
+            key_normalization: KeyNormalization::default(),
+            respect_request_hash: false,
+            force_refresh: ForceRefresh::default(),
+            stage2_mode: Stage2Mode::default(),
+            namespace: DEFAULT_NAMESPACE.to_string(),
+            stage1_prompt_version: None,
+            stage2_prompt_version: None,
+            stage1_model: None,
+            stage2_model: None,
+            stage1_request_model: None,
+            stage2_request_model: None,
+            connection_limiter: None,
+            explain: false,
         }
     }
 
+    /// Use `normalization` when deriving cache keys from vocabulary items.
+    pub fn with_key_normalization(mut self, normalization: KeyNormalization) -> Self {
+        self.key_normalization = normalization;
+        self
+    }
+
+    /// Treat a cached entry as a miss when the request that produced it differs
+    /// from the one that would be sent now: [`request_hash`](Self::request_hash),
+    /// or the hash given to the `*_for_request` methods.
+    ///
+    /// This trades cache hits for freshness: prompt or parameter changes that
+    /// the cache key doesn't capture cause recomputation.
+    pub fn with_respect_request_hash(mut self, respect_request_hash: bool) -> Self {
+        self.respect_request_hash = respect_request_hash;
+        self
+    }
+
+    /// Skip cache reads for the stages in `force_refresh`, recomputing and
+    /// overwriting their entries.
+    pub fn with_force_refresh(mut self, force_refresh: ForceRefresh) -> Self {
+        self.force_refresh = force_refresh;
+        self
+    }
+
+    /// Read and write only the entries of `namespace`, e.g. one customer's,
+    /// so tenants sharing a database never see each other's results.
+    pub fn with_namespace(mut self, namespace: impl Into<String>) -> Self {
+        self.namespace = namespace.into();
+        self
+    }
+
+    /// Let at most `limit` lookups and saves for items run at once, so more
+    /// items can be in flight than the pool has connections: the rest wait
+    /// for a slot here rather than timing out acquiring a connection.
+    /// Permits are only held for the query, never while computing.
+    pub fn with_connection_limit(self, limit: usize) -> Self {
+        self.with_connection_limiter(ConnectionLimiter::new(limit))
+    }
+    
+    /// [`with_connection_limit`](Self::with_connection_limit), sharing
+    /// `limiter`'s slots with other users of the pool.
+    pub fn with_connection_limiter(mut self, limiter: ConnectionLimiter) -> Self {
+        self.connection_limiter = Some(limiter);
+        self
+    }
+
+    /// Log every lookup's decision at info level with its reason: a hit
+    /// and whether its request hash matched, or a miss because nothing was
+    /// cached, the request hash changed or the stage was forced to refresh.
+    pub fn with_explain(mut self, explain: bool) -> Self {
+        self.explain = explain;
+        self
+    }
+
+    pub fn namespace(&self) -> &str {
+        &self.namespace
+    }
+
+    /// Key each stage's entries by the version of the prompt that produced
+    /// them, e.g. a hash of a custom prompt file, so switching prompts
+    /// recomputes instead of serving another prompt's results. `None` is the
+    /// built-in prompt, whose keys are unchanged. A new Stage 1 prompt also
+    /// misses Stage 2, whose keys derive from Stage 1's.
+    pub fn with_prompt_versions(mut self, stage1: Option<String>, stage2: Option<String>) -> Self {
+        self.stage1_prompt_version = stage1;
+        self.stage2_prompt_version = stage2;
+        self
+    }
+
+    /// Key each stage's entries by the model that computes them, so running
+    /// another model recomputes instead of serving the old model's results.
+    /// `None` leaves that stage's keys model-independent, the default.
+    pub fn with_pinned_models(mut self, stage1: Option<String>, stage2: Option<String>) -> Self {
+        self.stage1_model = stage1;
+        self.stage2_model = stage2;
+        self
+    }
+
+    /// The models each stage's requests are sent to, which
+    /// [`request_hash`](Self::request_hash) covers. Unlike pinned models,
+    /// they leave the keys as they are.
+    pub fn with_request_models(mut self, stage1: impl Into<String>, stage2: impl Into<String>) -> Self {
+        self.stage1_request_model = Some(stage1.into());
+        self.stage2_request_model = Some(stage2.into());
+        self
+    }
+
+    /// Hash of the request `cache_type` would send for `vocabulary_item`
+    /// now: its cache key, which covers the term, namespace, mode and prompt
+    /// version, and the model it goes to.
+    pub fn request_hash(&self, cache_type: CacheType, vocabulary_item: &VocabularyItem) -> String {
+        let (stage1_key, stage2_key) = self.cache_keys(vocabulary_item);
+        let (cache_key, model) = match cache_type {
+            CacheType::Stage1 => (stage1_key, &self.stage1_request_model),
+            CacheType::Stage2 => (stage2_key, &self.stage2_request_model),
+        };
+        
+        let mut hasher = Sha256::new();
+        hasher.update(cache_key);
+        hasher.update(b"\n");
+        hasher.update(model.as_deref().unwrap_or_default());
+        format!("{:x}", hasher.finalize())
+    }
+
+    /// Key Stage 2 entries by `mode`, so minimal and full cards are cached
+    /// separately.
+    pub fn with_stage2_mode(mut self, mode: Stage2Mode) -> Self {
+        self.stage2_mode = mode;
+        self
+    }
+
+    /// The cached Stage 1 result for `vocabulary_item`, or the one
+    /// `compute_fn` returns, which is cached. `compute_fn` should return
+    /// this item's [`request_hash`](Self::request_hash), so the entry
+    /// counts as fresh until the request changes.
     pub async fn get_or_compute_stage1<F, Fut>(
         &self,
         vocabulary_item: &VocabularyItem,
@@ -25,30 +211,59 @@ impl CacheManager {
         F: FnOnce() -> Fut,
         Fut: std::future::Future<Output = Result<(Stage1Result, String, i32, String), PipelineError>>,
     {
-        let cache_key = Stage1Result::generate_cache_key(vocabulary_item);
+        let request_hash = self.request_hash(CacheType::Stage1, vocabulary_item);
+        self.get_or_compute_stage1_for_request(vocabulary_item, Some(&request_hash), compute_fn).await
+    }
+
+    /// Like [`get_or_compute_stage1`](Self::get_or_compute_stage1), where
+    /// `request_hash` is the hash of the request that would be sent now.
+    pub async fn get_or_compute_stage1_for_request<F, Fut>(
+        &self,
+        vocabulary_item: &VocabularyItem,
+        request_hash: Option<&str>,
+        compute_fn: F,
+    ) -> Result<Stage1Result, PipelineError>
+    where
+        F: FnOnce() -> Fut,
+        Fut: std::future::Future<Output = Result<(Stage1Result, String, i32, String), PipelineError>>,
+    {
+        let cache_key = self.stage1_key(vocabulary_item);
         debug!("Checking Stage 1 cache for key: {}", cache_key);
 
         // Check cache first
-        if let Some(cached_result) = self.repository.get_stage1_cache(&cache_key).await? {
-            info!("Stage 1 cache hit for vocabulary item: {}", vocabulary_item.korean);
-            return Ok(cached_result);
-        }
+        let miss = match self.freshness(CacheType::Stage1, &cache_key, request_hash).await? {
+            Freshness::Fresh(request_hash) => {
+                if let Some(cached_result) = self.limited(self.repository.get_stage1_cache(&cache_key)).await? {
+                    info!("Stage 1 cache hit for vocabulary item: {}", vocabulary_item.korean);
+                    self.explain_hit(CacheType::Stage1, &cache_key, vocabulary_item, request_hash);
+                    return Ok(cached_result);
+                }
+                CacheMissReason::NotCached
+            }
+            Freshness::Stale(reason) => reason,
+        };
 
         // Cache miss - compute result
         info!("Stage 1 cache miss for vocabulary item: {}", vocabulary_item.korean);
-        let (result, request_hash, token_count, model_used) = compute_fn().await?;
+        self.explain_miss(CacheType::Stage1, &cache_key, vocabulary_item, miss);
+        let (mut result, request_hash, token_count, model_used) = compute_fn().await?;
 
-        // Save to cache
-        self.repository.save_stage1_cache(
+        // Saved under the key it was looked up by, which carries the namespace
+        result.cache_key = cache_key;
+        result.vocabulary_id = self.vocabulary_id(vocabulary_item).await?;
+        self.limited(self.repository.save_stage1_cache_in(
+            &self.namespace,
             &result,
             request_hash,
             token_count,
             model_used,
-        ).await?;
+        )).await?;
 
         Ok(result)
     }
 
+    /// Like [`get_or_compute_stage1`](Self::get_or_compute_stage1), for
+    /// Stage 2.
     pub async fn get_or_compute_stage2<F, Fut>(
         &self,
         vocabulary_item: &VocabularyItem,
@@ -59,39 +274,251 @@ impl CacheManager {
         F: FnOnce() -> Fut,
         Fut: std::future::Future<Output = Result<(Stage2Result, String, i32, String), PipelineError>>,
     {
-        let cache_key = Stage2Result::generate_cache_key(vocabulary_item, &stage1_result.cache_key);
+        let request_hash = self.request_hash(CacheType::Stage2, vocabulary_item);
+        self.get_or_compute_stage2_for_request(vocabulary_item, stage1_result, Some(&request_hash), compute_fn).await
+    }
+
+    /// Like [`get_or_compute_stage2`](Self::get_or_compute_stage2), where
+    /// `request_hash` is the hash of the request that would be sent now.
+    pub async fn get_or_compute_stage2_for_request<F, Fut>(
+        &self,
+        vocabulary_item: &VocabularyItem,
+        stage1_result: &Stage1Result,
+        request_hash: Option<&str>,
+        compute_fn: F,
+    ) -> Result<Stage2Result, PipelineError>
+    where
+        F: FnOnce() -> Fut,
+        Fut: std::future::Future<Output = Result<(Stage2Result, String, i32, String), PipelineError>>,
+    {
+        let cache_key = self.stage2_key(vocabulary_item, &stage1_result.cache_key);
         debug!("Checking Stage 2 cache for key: {}", cache_key);
 
         // Check cache first
-        if let Some(cached_result) = self.repository.get_stage2_cache(&cache_key).await? {
-            info!("Stage 2 cache hit for vocabulary item: {}", vocabulary_item.korean);
-            return Ok(cached_result);
-        }
+        let miss = match self.freshness(CacheType::Stage2, &cache_key, request_hash).await? {
+            Freshness::Fresh(request_hash) => {
+                if let Some(cached_result) = self.limited(self.repository.get_stage2_cache(&cache_key)).await? {
+                    info!("Stage 2 cache hit for vocabulary item: {}", vocabulary_item.korean);
+                    self.explain_hit(CacheType::Stage2, &cache_key, vocabulary_item, request_hash);
+                    return Ok(cached_result);
+                }
+                CacheMissReason::NotCached
+            }
+            Freshness::Stale(reason) => reason,
+        };
 
         // Cache miss - compute result
         info!("Stage 2 cache miss for vocabulary item: {}", vocabulary_item.korean);
-        let (result, request_hash, token_count, model_used) = compute_fn().await?;
+        self.explain_miss(CacheType::Stage2, &cache_key, vocabulary_item, miss);
+        let (mut result, request_hash, token_count, model_used) = compute_fn().await?;
 
-        // Save to cache
-        self.repository.save_stage2_cache(
+        // Saved under the key it was looked up by, which carries the namespace
+        result.stage1_cache_key = stage1_result.cache_key.clone();
+        result.cache_key = cache_key;
+        result.vocabulary_id = self.vocabulary_id(vocabulary_item).await?;
+        self.limited(self.repository.save_stage2_cache_in(
+            &self.namespace,
             &result,
             request_hash,
             token_count,
             model_used,
-        ).await?;
+        )).await?;
 
         Ok(result)
     }
 
+    /// Both cached results for `vocabulary_item`, or `None` unless both
+    /// stages would be served from the cache: neither is forced to refresh
+    /// and, with [`with_respect_request_hash`](Self::with_respect_request_hash),
+    /// neither was cached for a different request.
+    ///
+    /// The Stage 2 key derives from the Stage 1 key rather than the Stage 1
+    /// result, so the two lookups run concurrently and a fully cached item
+    /// costs one round trip instead of two.
+    pub async fn get_cached_results(
+        &self,
+        vocabulary_item: &VocabularyItem,
+    ) -> Result<Option<(Stage1Result, Stage2Result)>, PipelineError> {
+        let stage1_key = self.stage1_key(vocabulary_item);
+        let stage2_key = self.stage2_key(vocabulary_item, &stage1_key);
+        let stage1_hash = self.request_hash(CacheType::Stage1, vocabulary_item);
+        let stage2_hash = self.request_hash(CacheType::Stage2, vocabulary_item);
+        let freshness = tokio::try_join!(
+            self.freshness(CacheType::Stage1, &stage1_key, Some(&stage1_hash)),
+            self.freshness(CacheType::Stage2, &stage2_key, Some(&stage2_hash)),
+        )?;
+        let (Freshness::Fresh(stage1_check), Freshness::Fresh(stage2_check)) = freshness else {
+            return Ok(None);
+        };
+
+        let (stage1, stage2) = tokio::try_join!(
+            self.limited(self.repository.get_stage1_cache(&stage1_key)),
+            self.limited(self.repository.get_stage2_cache(&stage2_key)),
+        )?;
+        // Misses are explained by the stage lookups that follow them
+        if stage1.is_some() && stage2.is_some() {
+            self.explain_hit(CacheType::Stage1, &stage1_key, vocabulary_item, stage1_check);
+            self.explain_hit(CacheType::Stage2, &stage2_key, vocabulary_item, stage2_check);
+        }
+        Ok(stage1.zip(stage2))
+    }
+
+    /// Compute Stage 2 even when it is cached, and report how the fresh result
+    /// differs from the cached one.
+    ///
+    /// The diff is `None` when nothing was cached. The fresh result replaces
+    /// the cached entry only with `persist`, so prompt changes can be
+    /// previewed without touching the cache.
+    pub async fn compute_and_diff_stage2<F, Fut>(
+        &self,
+        vocabulary_item: &VocabularyItem,
+        stage1_result: &Stage1Result,
+        persist: bool,
+        compute_fn: F,
+    ) -> Result<(Stage2Result, Option<Stage2Diff>), PipelineError>
+    where
+        F: FnOnce() -> Fut,
+        Fut: std::future::Future<Output = Result<(Stage2Result, String, i32, String), PipelineError>>,
+    {
+        let cache_key = self.stage2_key(vocabulary_item, &stage1_result.cache_key);
+        
+        let cached = self.repository.get_stage2_cache(&cache_key).await?;
+        let (mut result, request_hash, token_count, model_used) = compute_fn().await?;
+        let diff = cached.as_ref().map(|cached| Stage2Diff::between(cached, &result));
+        
+        if persist {
+            result.stage1_cache_key = stage1_result.cache_key.clone();
+            result.cache_key = cache_key;
+            result.vocabulary_id = self.vocabulary_id(vocabulary_item).await?;
+            self.repository.save_stage2_cache_in(
+                &self.namespace,
+                &result,
+                request_hash,
+                token_count,
+                model_used,
+            ).await?;
+        }
+        
+        Ok((result, diff))
+    }
+
+    /// Whether the entry at `cache_key` must be recomputed: its stage is
+    /// forced to refresh, or it was produced by a different request.
+    async fn freshness(
+        &self,
+        cache_type: CacheType,
+        cache_key: &str,
+        request_hash: Option<&str>,
+    ) -> Result<Freshness, PipelineError> {
+        if self.force_refresh.stage(&cache_type) {
+            debug!("Refresh forced for {}; ignoring the cached entry", cache_key);
+            return Ok(Freshness::Stale(CacheMissReason::ForcedRefresh));
+        }
+        
+        let Some(request_hash) = request_hash.filter(|_| self.respect_request_hash) else {
+            return Ok(Freshness::Fresh(RequestHashCheck::Unchecked));
+        };
+
+        match self.limited(self.repository.get_request_hash(cache_type, cache_key)).await? {
+            Some(cached_hash) if cached_hash != request_hash => {
+                info!("Cached request hash for {} is stale; recomputing", cache_key);
+                Ok(Freshness::Stale(CacheMissReason::RequestHashMismatch))
+            }
+            Some(_) => Ok(Freshness::Fresh(RequestHashCheck::Match)),
+            None => Ok(Freshness::Fresh(RequestHashCheck::Unchecked)),
+        }
+    }
+
+    fn explain_hit(&self, cache_type: CacheType, cache_key: &str, item: &VocabularyItem, request_hash: RequestHashCheck) {
+        if self.explain {
+            log_cache_hit(stage_name(&cache_type), cache_key, &item.korean, request_hash);
+        }
+    }
+
+    fn explain_miss(&self, cache_type: CacheType, cache_key: &str, item: &VocabularyItem, reason: CacheMissReason) {
+        if self.explain {
+            log_cache_miss(stage_name(&cache_type), cache_key, &item.korean, reason);
+        }
+    }
+
     pub async fn get_stats(&self) -> Result<CacheStats, PipelineError> {
         self.repository.get_cache_stats().await
     }
 
+    /// Delete cached entries of `cache_type`, or both stages, in every
+    /// namespace.
     pub async fn clear_cache(&self, cache_type: Option<CacheType>) -> Result<i64, PipelineError> {
         warn!("Clearing cache: {:?}", cache_type);
         self.repository.clear_cache(cache_type).await
     }
 
+    /// Like [`clear_cache`](Self::clear_cache), leaving other namespaces'
+    /// entries alone.
+    pub async fn clear_namespace(&self, cache_type: Option<CacheType>) -> Result<i64, PipelineError> {
+        warn!("Clearing cache in namespace '{}': {:?}", self.namespace, cache_type);
+        self.repository.clear_cache_in(cache_type, Some(&self.namespace)).await
+    }
+
+    /// The row id cached results of `vocabulary_item` point at.
+    ///
+    /// Cache rows reference `vocabulary_items`, so an item that was never
+    /// saved, e.g. one read straight from a CSV, is upserted first.
+    async fn vocabulary_id(&self, vocabulary_item: &VocabularyItem) -> Result<i64, PipelineError> {
+        match vocabulary_item.id {
+            Some(id) => Ok(id),
+            None => self.limited(self.vocabulary.upsert(vocabulary_item)).await,
+        }
+    }
+
+    /// Run `query` once a connection slot is free, if a limit is set.
+    async fn limited<T>(&self, query: impl Future<Output = Result<T, PipelineError>>) -> Result<T, PipelineError> {
+        match &self.connection_limiter {
+            Some(limiter) => limiter.run(query).await,
+            None => query.await,
+        }
+    }
+
+    /// The Stage 1 and Stage 2 keys `vocabulary_item` is cached under, with
+    /// this manager's namespace, normalization, mode, prompt versions and
+    /// pinned models applied
+    pub fn cache_keys(&self, vocabulary_item: &VocabularyItem) -> (String, String) {
+        let stage1_key = self.stage1_key(vocabulary_item);
+        let stage2_key = self.stage2_key(vocabulary_item, &stage1_key);
+        (stage1_key, stage2_key)
+    }
+
+    /// Stage 1 key of `vocabulary_item` in this manager's namespace
+    fn stage1_key(&self, vocabulary_item: &VocabularyItem) -> String {
+        let key = Stage1Result::generate_cache_key_in(vocabulary_item, self.key_normalization, &self.namespace);
+        let key = match &self.stage1_prompt_version {
+            Some(version) => prompt_versioned_key(&key, version),
+            None => key,
+        };
+        match &self.stage1_model {
+            Some(model) => model_pinned_key(&key, model),
+            None => key,
+        }
+    }
+
+    /// Stage 2 key of `vocabulary_item` given its Stage 1 key, in this
+    /// manager's mode, Stage 2 prompt version and pinned model
+    fn stage2_key(&self, vocabulary_item: &VocabularyItem, stage1_key: &str) -> String {
+        let key = Stage2Result::generate_cache_key_for_mode(
+            vocabulary_item,
+            stage1_key,
+            self.key_normalization,
+            self.stage2_mode,
+        );
+        let key = match &self.stage2_prompt_version {
+            Some(version) => prompt_versioned_key(&key, version),
+            None => key,
+        };
+        match &self.stage2_model {
+            Some(model) => model_pinned_key(&key, model),
+            None => key,
+        }
+    }
+
     pub async fn get_stage1_direct(&self, cache_key: &str) -> Result<Option<Stage1Result>, PipelineError> {
         self.repository.get_stage1_cache(cache_key).await
     }
@@ -100,44 +527,128 @@ impl CacheManager {
         self.repository.get_stage2_cache(cache_key).await
     }
 
+    /// The entry stored under `cache_key`, for inspection; unlike a lookup
+    /// it doesn't count as an access
+    pub async fn get_entry(&self, cache_type: CacheType, cache_key: &str) -> Result<Option<CacheEntry>, PipelineError> {
+        self.repository.get_cache_entry(cache_type, cache_key).await
+    }
+
     pub async fn warm_cache_for_batch(&self, vocabulary_items: &[VocabularyItem]) -> Result<CacheWarmupStats, PipelineError> {
+        self.warm_cache_for_batch_with_options(vocabulary_items, WarmupOptions::default()).await
+    }
+
+    /// Check `vocabulary_items` against the cache in chunks, saving a
+    /// checkpoint after each so an interrupted run loses at most one chunk.
+    ///
+    /// With `resume`, items a previous run over the same list already
+    /// checked are skipped and its stats carried over. The checkpoint is
+    /// removed once every item has been checked.
+    pub async fn warm_cache_for_batch_with_options(
+        &self,
+        vocabulary_items: &[VocabularyItem],
+        options: WarmupOptions,
+    ) -> Result<CacheWarmupStats, PipelineError> {
         info!("Warming cache for {} vocabulary items", vocabulary_items.len());
         
+        let warm_key = self.warm_key(vocabulary_items, options.stage1_only);
+        let checkpoint = if options.resume {
+            self.repository.get_warm_checkpoint(&warm_key).await?
+        } else {
+            None
+        };
+        
+        let (start, mut stats) = match checkpoint {
+            Some((next_index, stats)) if next_index <= vocabulary_items.len() => {
+                info!("Resuming cache warm at item {} of {}", next_index, vocabulary_items.len());
+                let stats: CacheWarmupStats = crate::json::from_value(stats, "cache warm checkpoint")?;
+                (next_index, stats)
+            }
+            _ => (0, CacheWarmupStats::default()),
+        };
+        stats.resumed_items = start;
+        
+        let interval = options.checkpoint_interval.max(1);
+        for chunk_start in (start..vocabulary_items.len()).step_by(interval) {
+            let chunk_end = (chunk_start + interval).min(vocabulary_items.len());
+            let chunk = self.probe_cache(&vocabulary_items[chunk_start..chunk_end], options.stage1_only).await?;
+            stats.merge(&chunk);
+            
+            if chunk_end < vocabulary_items.len() {
+                self.repository.save_warm_checkpoint(&warm_key, chunk_end, &serde_json::to_value(&stats)?).await?;
+            }
+        }
+        self.repository.clear_warm_checkpoint(&warm_key).await?;
+
+        info!("Cache warmup complete: {} stage1 hits, {} stage2 hits", 
+              stats.stage1_cached, stats.stage2_cached);
+
+        Ok(stats)
+    }
+
+    /// Identifies a warm by the cache keys it checks, in order, so a resume
+    /// only picks up a checkpoint left by a run over the same items.
+    pub fn warm_key(&self, vocabulary_items: &[VocabularyItem], stage1_only: bool) -> String {
+        let mut hasher = Sha256::new();
+        hasher.update(if stage1_only { "stage1" } else { self.stage2_mode.as_str() });
+        for item in vocabulary_items {
+            hasher.update(b"\n");
+            hasher.update(self.stage1_key(item));
+        }
+        format!("{:x}", hasher.finalize())
+    }
+
+    /// Count cached entries for `vocabulary_items` without modifying the cache.
+    ///
+    /// Unlike the `get_*` lookups this leaves access counts untouched, so it is
+    /// safe to run as a preview. With `stage1_only` Stage 2 is not probed.
+    pub async fn probe_cache(
+        &self,
+        vocabulary_items: &[VocabularyItem],
+        stage1_only: bool,
+    ) -> Result<CacheWarmupStats, PipelineError> {
+        debug!("Probing cache for {} vocabulary items", vocabulary_items.len());
+        
         let mut stage1_hits = 0;
         let mut stage2_hits = 0;
-        let mut total_tokens_saved = 0;
+        let mut total_tokens_saved = 0i64;
 
         for item in vocabulary_items {
-            let stage1_key = Stage1Result::generate_cache_key(item);
+            let stage1_key = self.stage1_key(item);
             
-            if let Some(stage1_result) = self.repository.get_stage1_cache(&stage1_key).await? {
-                stage1_hits += 1;
-                
-                // Check Stage 2 cache
-                let stage2_key = Stage2Result::generate_cache_key(item, &stage1_result.cache_key);
-                if self.repository.get_stage2_cache(&stage2_key).await?.is_some() {
-                    stage2_hits += 1;
-                }
+            let Some(stage1_tokens) = self.repository
+                .get_cached_token_count(CacheType::Stage1, &stage1_key)
+                .await? else {
+                continue;
+            };
+            stage1_hits += 1;
+            total_tokens_saved += stage1_tokens as i64;
+            
+            if stage1_only {
+                continue;
+            }
+            
+            let stage2_key = self.stage2_key(item, &stage1_key);
+            if let Some(stage2_tokens) = self.repository
+                .get_cached_token_count(CacheType::Stage2, &stage2_key)
+                .await? {
+                stage2_hits += 1;
+                total_tokens_saved += stage2_tokens as i64;
             }
         }
 
-        let stats = CacheWarmupStats {
+        Ok(CacheWarmupStats {
             total_items: vocabulary_items.len(),
             stage1_cached: stage1_hits,
             stage2_cached: stage2_hits,
             stage1_missing: vocabulary_items.len() - stage1_hits,
             stage2_missing: vocabulary_items.len() - stage2_hits,
             estimated_tokens_saved: total_tokens_saved,
-        };
-
-        info!("Cache warmup complete: {} stage1 hits, {} stage2 hits", 
-              stage1_hits, stage2_hits);
-
-        Ok(stats)
+            resumed_items: 0,
+        })
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct CacheWarmupStats {
     pub total_items: usize,
     pub stage1_cached: usize,
@@ -145,9 +656,37 @@ pub struct CacheWarmupStats {
     pub stage1_missing: usize,
     pub stage2_missing: usize,
     pub estimated_tokens_saved: i64,
+    /// Items whose counts came from a checkpoint rather than this run
+    #[serde(default)]
+    pub resumed_items: usize,
 }
 
 impl CacheWarmupStats {
+    /// Add the counts from another chunk of items.
+    pub fn merge(&mut self, other: &CacheWarmupStats) {
+        self.total_items += other.total_items;
+        self.stage1_cached += other.stage1_cached;
+        self.stage2_cached += other.stage2_cached;
+        self.stage1_missing += other.stage1_missing;
+        self.stage2_missing += other.stage2_missing;
+        self.estimated_tokens_saved += other.estimated_tokens_saved;
+    }
+
+    /// Items with both stages cached.
+    pub fn fully_cached(&self) -> usize {
+        self.stage2_cached
+    }
+
+    /// Items with a cached Stage 1 that still need Stage 2.
+    pub fn needs_stage2_only(&self) -> usize {
+        self.stage1_cached - self.stage2_cached
+    }
+
+    /// Items that need both stages.
+    pub fn needs_both(&self) -> usize {
+        self.stage1_missing
+    }
+
     pub fn cache_hit_rate(&self) -> f64 {
         if self.total_items == 0 {
             return 0.0;
@@ -180,6 +719,35 @@ mod tests {
         
         CacheManager::new(pool)
     }
+    
+    /// A migrated database, removed once the returned file is dropped
+    async fn test_pool() -> (DatabasePool, NamedTempFile) {
+        let temp_file = NamedTempFile::new().unwrap();
+        let pool = crate::database::create_pool(temp_file.path().to_str().unwrap()).await.unwrap();
+        crate::database::migrations::run_migrations(&pool).await.unwrap();
+        (pool, temp_file)
+    }
+    
+    /// A Stage 1 analysis for `meaning`. The manager fills in its cache key
+    /// and vocabulary row when caching it.
+    fn stage1_result(vocabulary_id: i64, meaning: &str) -> Stage1Result {
+        Stage1Result {
+            vocabulary_id,
+            request_id: "test".to_string(),
+            cache_key: String::new(),
+            semantic_analysis: SemanticAnalysis {
+                primary_meaning: meaning.to_string(),
+                alternative_meanings: vec![],
+                connotations: vec![],
+                register: "neutral".to_string(),
+                usage_contexts: vec![],
+                cultural_notes: None,
+                frequency: FrequencyLevel::Common,
+                formality: FormalityLevel::Neutral,
+            },
+            created_at: chrono::Utc::now(),
+        }
+    }
 
     #[tokio::test]
     async fn test_stage1_caching() {
@@ -233,4 +801,537 @@ mod tests {
         assert_eq!(compute_count.load(std::sync::atomic::Ordering::SeqCst), 1);
         assert_eq!(result1.cache_key, result2.cache_key);
     }
-}
\ No newline at end of file
+    
+    #[tokio::test]
+    async fn test_probe_cache_counts() {
+        let (pool, _db_file) = test_pool().await;
+        let manager = CacheManager::new(pool);
+        
+        let cached = VocabularyItem::new("학교".to_string(), "school".to_string(), "places".to_string());
+        let uncached = VocabularyItem::new("병원".to_string(), "hospital".to_string(), "places".to_string());
+        manager.get_or_compute_stage1(&cached, || async {
+            Ok((stage1_result(0, "School"), "hash".to_string(), 120, "claude-3-sonnet".to_string()))
+        }).await.unwrap();
+        
+        let stats = manager.probe_cache(&[cached, uncached], false).await.unwrap();
+        assert_eq!(stats.fully_cached(), 0);
+        assert_eq!(stats.needs_stage2_only(), 1);
+        assert_eq!(stats.needs_both(), 1);
+        assert_eq!(stats.estimated_tokens_saved, 120);
+    }
+
+    #[tokio::test]
+    async fn test_stale_request_hash_recomputes() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        
+        let (pool, _db_file) = test_pool().await;
+        let manager = CacheManager::new(pool.clone()).with_respect_request_hash(true);
+        
+        let vocab_item = VocabularyItem::new("학교".to_string(), "school".to_string(), "places".to_string());
+        let computed = AtomicUsize::new(0);
+        let compute = |request_hash: &'static str| {
+            computed.fetch_add(1, Ordering::SeqCst);
+            let result = Stage1Result { request_id: request_hash.to_string(), ..stage1_result(0, "School") };
+            async move { Ok((result, request_hash.to_string(), 100, "claude-3-sonnet".to_string())) }
+        };
+        
+        manager.get_or_compute_stage1_for_request(&vocab_item, Some("v1"), || compute("v1")).await.unwrap();
+        manager.get_or_compute_stage1_for_request(&vocab_item, Some("v1"), || compute("v1")).await.unwrap();
+        assert_eq!(computed.load(Ordering::SeqCst), 1);
+        
+        // Same cache key, but the request changed since the entry was cached
+        let result = manager.get_or_compute_stage1_for_request(&vocab_item, Some("v2"), || compute("v2"))
+            .await
+            .unwrap();
+        assert_eq!(computed.load(Ordering::SeqCst), 2);
+        assert_eq!(result.request_id, "v2");
+        
+        // Without the option the stored entry is served regardless
+        let lenient = CacheManager::new(pool);
+        lenient.get_or_compute_stage1_for_request(&vocab_item, Some("v3"), || compute("v3")).await.unwrap();
+        assert_eq!(computed.load(Ordering::SeqCst), 2);
+    }
+    
+    /// Collects what a subscriber writes, to read logged events back
+    #[derive(Clone, Default)]
+    struct CapturedLogs(std::sync::Arc<std::sync::Mutex<Vec<u8>>>);
+    
+    impl std::io::Write for CapturedLogs {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+        
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+    
+    #[tokio::test]
+    async fn test_explain_logs_each_decision() {
+        let logs = CapturedLogs::default();
+        let writer = logs.clone();
+        let subscriber = tracing_subscriber::fmt()
+            .json()
+            .with_max_level(tracing::Level::INFO)
+            .with_writer(move || writer.clone())
+            .finish();
+        let _guard = tracing::subscriber::set_default(subscriber);
+        
+        let (pool, _db_file) = test_pool().await;
+        let manager = CacheManager::new(pool)
+            .with_respect_request_hash(true)
+            .with_explain(true);
+        
+        let vocab_item = VocabularyItem::new("학교".to_string(), "school".to_string(), "places".to_string());
+        let cache_key = Stage1Result::generate_cache_key(&vocab_item);
+        let compute = |request_hash: &'static str| async move {
+            Ok((stage1_result(0, "School"), request_hash.to_string(), 100, "claude-3-sonnet".to_string()))
+        };
+        
+        for request_hash in ["v1", "v1", "v2"] {
+            manager.get_or_compute_stage1_for_request(&vocab_item, Some(request_hash), || compute(request_hash))
+                .await
+                .unwrap();
+        }
+        
+        let output = String::from_utf8(logs.0.lock().unwrap().clone()).unwrap();
+        let decisions: Vec<(String, String)> = output.lines()
+            .map(|line| serde_json::from_str::<serde_json::Value>(line).unwrap())
+            .filter(|event| event["fields"]["decision"].is_string())
+            .map(|event| {
+                let fields = &event["fields"];
+                assert_eq!(fields["cache_type"], "stage1");
+                assert_eq!(fields["term"], "학교");
+                assert_eq!(fields["cache_key"], cache_key.as_str());
+                let detail = if fields["decision"] == "hit" { &fields["request_hash"] } else { &fields["reason"] };
+                (fields["decision"].as_str().unwrap().to_string(), detail.as_str().unwrap().to_string())
+            })
+            .collect();
+        assert_eq!(decisions, [
+            ("miss".to_string(), "not_cached".to_string()),
+            ("hit".to_string(), "match".to_string()),
+            ("miss".to_string(), "request_hash_mismatch".to_string()),
+        ]);
+    }
+    
+    #[tokio::test]
+    async fn test_compute_and_diff_stage2() {
+        use crate::models::{CardType, FlashcardContent};
+        
+        let (pool, _db_file) = test_pool().await;
+        let manager = CacheManager::new(pool);
+        
+        let vocab_item = VocabularyItem::new("학교".to_string(), "school".to_string(), "places".to_string());
+        let stage1 = Stage1Result {
+            cache_key: Stage1Result::generate_cache_key(&vocab_item),
+            ..stage1_result(0, "School")
+        };
+        let stage2_key = Stage2Result::generate_cache_key(&vocab_item, &stage1.cache_key);
+        
+        let card = |example: &str| {
+            let face = |primary: &str| FlashcardContent {
+                example_sentence: Some(example.to_string()),
+                ..FlashcardContent::new(primary)
+            };
+            Stage2Result {
+                vocabulary_id: 0,
+                stage1_cache_key: stage1.cache_key.clone(),
+                request_id: "diff".to_string(),
+                cache_key: stage2_key.clone(),
+                front: face("학교"),
+                back: face("school"),
+                card_type: CardType::Standard,
+                learning_order: None,
+                related_cards: vec![],
+                tsv_output: "학교\tschool".to_string(),
+                created_at: chrono::Utc::now(),
+            }
+        };
+        let compute = |example: &'static str| {
+            let result = card(example);
+            async move { Ok((result, "hash".to_string(), 100, "claude-3-sonnet".to_string())) }
+        };
+        
+        // Nothing cached yet, so nothing to compare against
+        let (_, diff) = manager.compute_and_diff_stage2(&vocab_item, &stage1, false, || compute("학교에 가요")).await.unwrap();
+        assert!(diff.is_none());
+        
+        manager.get_or_compute_stage2(&vocab_item, &stage1, || compute("학교에 가요")).await.unwrap();
+        
+        let (_, diff) = manager.compute_and_diff_stage2(&vocab_item, &stage1, false, || compute("학교가 커요")).await.unwrap();
+        let diff = diff.unwrap();
+        let paths: Vec<&str> = diff.changes.iter().map(|change| change.path.as_str()).collect();
+        assert_eq!(paths, vec!["front.example_sentence", "back.example_sentence"]);
+        assert_eq!(diff.changes[0].old, serde_json::json!("학교에 가요"));
+        assert_eq!(diff.changes[0].new, serde_json::json!("학교가 커요"));
+        
+        // Not persisted: the cache still holds the original card
+        let cached = manager.get_stage2_direct(&stage2_key).await.unwrap().unwrap();
+        assert_eq!(cached.back.example_sentence.as_deref(), Some("학교에 가요"));
+        
+        manager.compute_and_diff_stage2(&vocab_item, &stage1, true, || compute("학교가 커요")).await.unwrap();
+        let cached = manager.get_stage2_direct(&stage2_key).await.unwrap().unwrap();
+        assert_eq!(cached.back.example_sentence.as_deref(), Some("학교가 커요"));
+    }
+    
+    #[tokio::test]
+    async fn test_force_refresh_stage2_only() {
+        use crate::models::{CardType, FlashcardContent};
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        
+        let (pool, _db_file) = test_pool().await;
+        
+        let vocab_item = VocabularyItem::new("학교".to_string(), "school".to_string(), "places".to_string());
+        let stage1 = Stage1Result {
+            cache_key: Stage1Result::generate_cache_key(&vocab_item),
+            ..stage1_result(0, "School")
+        };
+        let stage2_key = Stage2Result::generate_cache_key(&vocab_item, &stage1.cache_key);
+        
+        let stage1_calls = AtomicUsize::new(0);
+        let stage2_calls = AtomicUsize::new(0);
+        let compute_stage1 = || {
+            stage1_calls.fetch_add(1, Ordering::SeqCst);
+            let result = stage1.clone();
+            async move { Ok((result, "hash".to_string(), 100, "claude-3-sonnet".to_string())) }
+        };
+        let compute_stage2 = |template: &'static str| {
+            stage2_calls.fetch_add(1, Ordering::SeqCst);
+            let face = |primary: &str| FlashcardContent {
+                usage_notes: Some(template.to_string()),
+                ..FlashcardContent::new(primary)
+            };
+            let result = Stage2Result {
+                vocabulary_id: 0,
+                stage1_cache_key: stage1.cache_key.clone(),
+                request_id: "refresh".to_string(),
+                cache_key: stage2_key.clone(),
+                front: face("학교"),
+                back: face("school"),
+                card_type: CardType::Standard,
+                learning_order: None,
+                related_cards: vec![],
+                tsv_output: "학교\tschool".to_string(),
+                created_at: chrono::Utc::now(),
+            };
+            async move { Ok((result, "hash".to_string(), 100, "claude-3-sonnet".to_string())) }
+        };
+        
+        let manager = CacheManager::new(pool.clone());
+        let stage1_result = manager.get_or_compute_stage1(&vocab_item, compute_stage1).await.unwrap();
+        manager.get_or_compute_stage2(&vocab_item, &stage1_result, || compute_stage2("old template")).await.unwrap();
+        assert!(manager.get_cached_results(&vocab_item).await.unwrap().is_some());
+        
+        let refreshing = CacheManager::new(pool).with_force_refresh(ForceRefresh { stage1: false, stage2: true });
+        let stage1_result = refreshing.get_or_compute_stage1(&vocab_item, compute_stage1).await.unwrap();
+        let card = refreshing.get_or_compute_stage2(&vocab_item, &stage1_result, || compute_stage2("new template"))
+            .await
+            .unwrap();
+        
+        // A forced stage is never served from the cache, even when both are
+        assert!(refreshing.get_cached_results(&vocab_item).await.unwrap().is_none());
+        assert_eq!(stage1_calls.load(Ordering::SeqCst), 1, "stage 1 should stay a cache hit");
+        assert_eq!(stage2_calls.load(Ordering::SeqCst), 2);
+        assert_eq!(card.front.usage_notes.as_deref(), Some("new template"));
+        
+        // The fresh card replaced the cached one
+        let cached = refreshing.get_stage2_direct(&stage2_key).await.unwrap().unwrap();
+        assert_eq!(cached.front.usage_notes.as_deref(), Some("new template"));
+    }
+
+    #[tokio::test]
+    async fn test_changed_request_model_recomputes() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        
+        let (pool, _db_file) = test_pool().await;
+        
+        let vocab_item = VocabularyItem::new("학교".to_string(), "school".to_string(), "places".to_string());
+        let computed = AtomicUsize::new(0);
+        let compute = |manager: &CacheManager, model: &'static str| {
+            computed.fetch_add(1, Ordering::SeqCst);
+            let request_hash = manager.request_hash(CacheType::Stage1, &vocab_item);
+            let result = Stage1Result { request_id: model.to_string(), ..stage1_result(0, "School") };
+            async move { Ok((result, request_hash, 100, model.to_string())) }
+        };
+        let manager = |model: &'static str| {
+            CacheManager::new(pool.clone())
+                .with_respect_request_hash(true)
+                .with_request_models(model, model)
+        };
+        
+        let old = manager("old-model");
+        old.get_or_compute_stage1(&vocab_item, || compute(&old, "old-model")).await.unwrap();
+        old.get_or_compute_stage1(&vocab_item, || compute(&old, "old-model")).await.unwrap();
+        assert_eq!(computed.load(Ordering::SeqCst), 1);
+        
+        // The key doesn't cover the model, but the request hash does
+        let new = manager("new-model");
+        let result = new.get_or_compute_stage1(&vocab_item, || compute(&new, "new-model")).await.unwrap();
+        assert_eq!(computed.load(Ordering::SeqCst), 2);
+        assert_eq!(result.request_id, "new-model");
+        new.get_or_compute_stage1(&vocab_item, || compute(&new, "new-model")).await.unwrap();
+        assert_eq!(computed.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_cached_results_skip_stale_request_hash() {
+        use crate::models::{CardType, FlashcardContent};
+        
+        let (pool, _db_file) = test_pool().await;
+        
+        let vocab_item = VocabularyItem::new("학교".to_string(), "school".to_string(), "places".to_string());
+        let old = CacheManager::new(pool.clone()).with_request_models("old-model", "old-model");
+        let stage1 = old.get_or_compute_stage1(&vocab_item, || async {
+            Ok((stage1_result(0, "School"), old.request_hash(CacheType::Stage1, &vocab_item), 100, "old-model".to_string()))
+        }).await.unwrap();
+        old.get_or_compute_stage2(&vocab_item, &stage1, || async {
+            Ok((
+                Stage2Result {
+                    vocabulary_id: 0,
+                    stage1_cache_key: String::new(),
+                    request_id: "old".to_string(),
+                    cache_key: String::new(),
+                    front: FlashcardContent::new("학교"),
+                    back: FlashcardContent::new("school"),
+                    card_type: CardType::Standard,
+                    learning_order: None,
+                    related_cards: vec![],
+                    tsv_output: "학교\tschool".to_string(),
+                    created_at: chrono::Utc::now(),
+                },
+                old.request_hash(CacheType::Stage2, &vocab_item),
+                100,
+                "old-model".to_string(),
+            ))
+        }).await.unwrap();
+        
+        let same = CacheManager::new(pool.clone())
+            .with_respect_request_hash(true)
+            .with_request_models("old-model", "old-model");
+        assert!(same.get_cached_results(&vocab_item).await.unwrap().is_some());
+        
+        // Only Stage 2 moved to another model, but both are needed
+        let changed = CacheManager::new(pool.clone())
+            .with_respect_request_hash(true)
+            .with_request_models("old-model", "new-model");
+        assert!(changed.get_cached_results(&vocab_item).await.unwrap().is_none());
+        
+        // Without the option the stored entries are served regardless
+        let lenient = CacheManager::new(pool).with_request_models("old-model", "new-model");
+        assert!(lenient.get_cached_results(&vocab_item).await.unwrap().is_some());
+    }
+
+    #[tokio::test]
+    async fn test_connection_limit_queues_items_beyond_pool() {
+        let (pool, _db_file) = test_pool().await;
+        let limiter = ConnectionLimiter::new(2);
+        let manager = Arc::new(CacheManager::new(pool).with_connection_limiter(limiter.clone()));
+        
+        // Far more items at once than the pool has connections
+        let items = crate::database::MAX_CONNECTIONS as usize * 10;
+        let mut tasks = tokio::task::JoinSet::new();
+        for index in 0..items {
+            let manager = Arc::clone(&manager);
+            tasks.spawn(async move {
+                let vocab_item = VocabularyItem::new(format!("단어{}", index), "word".to_string(), "test".to_string());
+                manager.get_or_compute_stage1(&vocab_item, || async {
+                    Ok((stage1_result(0, "Word"), "hash".to_string(), 10, "claude-3-sonnet".to_string()))
+                }).await
+            });
+        }
+        
+        let mut cached = 0;
+        while let Some(result) = tasks.join_next().await {
+            result.unwrap().unwrap();
+            cached += 1;
+        }
+        assert_eq!(cached, items);
+        assert_eq!(manager.get_stats().await.unwrap().stage1_entries, items as i64);
+        // Every lookup and save waited for one of the two slots
+        assert_eq!(limiter.peak(), 2);
+    }
+    
+    #[tokio::test]
+    async fn test_resumed_warm_checks_only_remaining_items() {
+        let (pool, _db_file) = test_pool().await;
+        let manager = CacheManager::new(pool.clone());
+        
+        let items: Vec<VocabularyItem> = (0..5)
+            .map(|i| VocabularyItem::new(format!("단어{}", i), format!("word {}", i), "warm".to_string()))
+            .collect();
+        
+        // Stage 1 is cached for the first and last items
+        let vocabulary = VocabularyRepository::new(pool.clone());
+        for item in [&items[0], &items[4]] {
+            let vocabulary_id = vocabulary.create(item).await.unwrap();
+            sqlx::query(
+                "INSERT INTO stage1_cache (vocabulary_id, cache_key, request_hash, response_json, token_count, model_used)
+                 VALUES (?, ?, 'hash', '{}', 10, 'model')"
+            )
+            .bind(vocabulary_id)
+            .bind(Stage1Result::generate_cache_key(item))
+            .execute(&pool)
+            .await
+            .unwrap();
+        }
+        
+        // An earlier run was interrupted after checking three items. Its
+        // recorded count differs from the cache so reuse is detectable.
+        let warm_key = manager.warm_key(&items, true);
+        let checkpoint = CacheWarmupStats {
+            total_items: 3,
+            stage1_cached: 2,
+            stage1_missing: 1,
+            stage2_missing: 3,
+            estimated_tokens_saved: 20,
+            ..Default::default()
+        };
+        manager.repository
+            .save_warm_checkpoint(&warm_key, 3, &serde_json::to_value(&checkpoint).unwrap())
+            .await
+            .unwrap();
+        
+        let options = WarmupOptions { resume: true, stage1_only: true, checkpoint_interval: 1 };
+        let stats = manager.warm_cache_for_batch_with_options(&items, options).await.unwrap();
+        assert_eq!(stats.resumed_items, 3);
+        assert_eq!(stats.total_items, 5);
+        // Two from the checkpoint plus the last item; items 0-2 weren't re-checked
+        assert_eq!(stats.stage1_cached, 3);
+        assert_eq!(stats.stage1_missing, 2);
+        assert_eq!(stats.estimated_tokens_saved, 30);
+        
+        // Finishing clears the checkpoint, so the next run starts over
+        assert!(manager.repository.get_warm_checkpoint(&warm_key).await.unwrap().is_none());
+        let fresh = manager.warm_cache_for_batch_with_options(&items, options).await.unwrap();
+        assert_eq!(fresh.resumed_items, 0);
+        assert_eq!(fresh.stage1_cached, 2);
+    }
+    
+    #[tokio::test]
+    async fn test_namespaces_isolate_keys_and_clears() {
+        let (pool, _db_file) = test_pool().await;
+        let acme = CacheManager::new(pool.clone()).with_namespace("acme");
+        let globex = CacheManager::new(pool.clone()).with_namespace("globex");
+        
+        let item = VocabularyItem::new("사과".to_string(), "apple".to_string(), "food".to_string());
+        assert_ne!(acme.stage1_key(&item), globex.stage1_key(&item));
+        // The default namespace keeps the keys written before namespaces existed
+        assert_eq!(
+            Stage1Result::generate_cache_key_in(&item, KeyNormalization::default(), DEFAULT_NAMESPACE),
+            Stage1Result::generate_cache_key(&item),
+        );
+        
+        let compute = |meaning: &'static str| {
+            move || async move { Ok((stage1_result(0, meaning), "hash".to_string(), 10, "test-model".to_string())) }
+        };
+        
+        let acme_result = acme.get_or_compute_stage1(&item, compute("acme apple")).await.unwrap();
+        let globex_result = globex.get_or_compute_stage1(&item, compute("globex apple")).await.unwrap();
+        assert_ne!(acme_result.cache_key, globex_result.cache_key);
+        
+        // Each tenant reads back its own entry
+        let cached = acme.get_or_compute_stage1(&item, || async { panic!("acme should hit its cache") }).await.unwrap();
+        assert_eq!(cached.semantic_analysis.primary_meaning, "acme apple");
+        
+        assert_eq!(acme.clear_namespace(Some(CacheType::Stage1)).await.unwrap(), 1);
+        assert!(acme.repository.get_stage1_cache(&acme_result.cache_key).await.unwrap().is_none());
+        let kept = globex.get_or_compute_stage1(&item, || async { panic!("globex's entry should survive") }).await.unwrap();
+        assert_eq!(kept.semantic_analysis.primary_meaning, "globex apple");
+    }
+    
+    #[tokio::test]
+    async fn test_caching_unsaved_item_links_vocabulary_row() {
+        let (pool, _db_file) = test_pool().await;
+        let manager = CacheManager::new(pool.clone());
+        
+        // As read from a CSV: never saved, so no id
+        let item = VocabularyItem::new("바다".to_string(), "sea".to_string(), "nature".to_string());
+        assert!(item.id.is_none());
+        
+        let computed = manager.get_or_compute_stage1(&item, || async {
+            Ok((stage1_result(0, "Sea"), "hash".to_string(), 10, "test-model".to_string()))
+        }).await.unwrap();
+        
+        let saved = VocabularyRepository::new(pool)
+            .find_by_content("바다", "sea", "nature")
+            .await
+            .unwrap()
+            .expect("caching should have saved the item");
+        assert_eq!(Some(computed.vocabulary_id), saved.id);
+        
+        let cached = manager.get_or_compute_stage1(&item, || async { panic!("should hit the cache") }).await.unwrap();
+        assert_eq!(cached.vocabulary_id, computed.vocabulary_id);
+        assert_eq!(cached.cache_key, computed.cache_key);
+        assert_eq!(cached.semantic_analysis.primary_meaning, "Sea");
+    }
+    
+    #[tokio::test]
+    async fn test_request_hash_covers_model_but_key_does_not() {
+        let item = VocabularyItem::new("학교".to_string(), "school".to_string(), "places".to_string());
+        let sonnet = setup_test_manager().await.with_request_models("claude-3-sonnet", "claude-3-sonnet");
+        let haiku = setup_test_manager().await.with_request_models("claude-3-sonnet", "claude-3-haiku");
+        
+        assert_eq!(sonnet.cache_keys(&item), haiku.cache_keys(&item));
+        assert_eq!(sonnet.request_hash(CacheType::Stage1, &item), haiku.request_hash(CacheType::Stage1, &item));
+        assert_ne!(sonnet.request_hash(CacheType::Stage2, &item), haiku.request_hash(CacheType::Stage2, &item));
+        assert_ne!(sonnet.request_hash(CacheType::Stage1, &item), sonnet.request_hash(CacheType::Stage2, &item));
+    }
+    
+    #[tokio::test]
+    async fn test_prompt_versions_separate_keys() {
+        let manager = setup_test_manager().await;
+        let item = VocabularyItem::new("학교".to_string(), "school".to_string(), "places".to_string());
+        let keys = |manager: &CacheManager| {
+            let stage1 = manager.stage1_key(&item);
+            let stage2 = manager.stage2_key(&item, &stage1);
+            (stage1, stage2)
+        };
+        let (stage1, stage2) = keys(&manager);
+        
+        // The built-in prompt keeps the keys from before prompt versions
+        assert_eq!(stage1, Stage1Result::generate_cache_key(&item));
+        
+        let custom_stage2 = manager.with_prompt_versions(None, Some("v2".to_string()));
+        let (same_stage1, new_stage2) = keys(&custom_stage2);
+        assert_eq!(same_stage1, stage1);
+        assert_ne!(new_stage2, stage2);
+        assert!(new_stage2.starts_with("stage2_"));
+        
+        let custom_stage1 = custom_stage2.with_prompt_versions(Some("v2".to_string()), None);
+        let (new_stage1, dependent_stage2) = keys(&custom_stage1);
+        assert_ne!(new_stage1, stage1);
+        assert!(new_stage1.starts_with("stage1_"));
+        assert_ne!(dependent_stage2, stage2);
+    }
+    
+    #[tokio::test]
+    async fn test_pinned_models_separate_keys() {
+        let (pool, _db_file) = test_pool().await;
+        let pinned = |model: &str| CacheManager::new(pool.clone())
+            .with_pinned_models(Some(model.to_string()), Some(model.to_string()));
+        let sonnet = pinned("claude-3-sonnet");
+        let haiku = pinned("claude-3-haiku");
+        
+        let item = VocabularyItem::new("바다".to_string(), "sea".to_string(), "nature".to_string());
+        let stage1_keys = (sonnet.stage1_key(&item), haiku.stage1_key(&item));
+        assert_ne!(stage1_keys.0, stage1_keys.1);
+        assert_ne!(sonnet.stage2_key(&item, "stage1_x"), haiku.stage2_key(&item, "stage1_x"));
+        // Unpinned keys don't depend on the model
+        assert_eq!(CacheManager::new(pool.clone()).stage1_key(&item), Stage1Result::generate_cache_key(&item));
+        
+        let compute = |model: &'static str| {
+            move || async move {
+                Ok((stage1_result(0, &format!("Sea ({})", model)), "hash".to_string(), 10, model.to_string()))
+            }
+        };
+        sonnet.get_or_compute_stage1(&item, compute("claude-3-sonnet")).await.unwrap();
+        haiku.get_or_compute_stage1(&item, compute("claude-3-haiku")).await.unwrap();
+        
+        // Clearing one model's entries leaves the other's
+        assert_eq!(sonnet.repository.clear_cache_by_model("claude-3-haiku").await.unwrap(), 1);
+        assert!(haiku.repository.get_stage1_cache(&stage1_keys.1).await.unwrap().is_none());
+        let kept = sonnet.get_or_compute_stage1(&item, || async { panic!("sonnet's entry should survive") }).await.unwrap();
+        assert_eq!(kept.semantic_analysis.primary_meaning, "Sea (claude-3-sonnet)");
+    }
+}
@@ -1,30 +1,242 @@
+use serde::{Deserialize, Serialize};
 use sqlx::{sqlite::{SqlitePool, SqlitePoolOptions, SqliteConnectOptions}, Pool, Sqlite};
+use std::future::Future;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::time::Duration;
+use tokio::sync::Semaphore;
 use tracing::{info, error};
 use crate::models::PipelineError;
 
 pub type DatabasePool = Pool<Sqlite>;
 
+/// Connections in the pool of a file database
+pub const MAX_CONNECTIONS: u32 = 10;
+
+/// Caps the queries in flight at once across tasks, so more tasks can run
+/// than the pool has connections: the rest wait for a slot here rather
+/// than timing out acquiring a connection. Clones share the slots.
+#[derive(Debug, Clone)]
+pub struct ConnectionLimiter {
+    semaphore: Arc<Semaphore>,
+    in_use: Arc<AtomicUsize>,
+    peak: Arc<AtomicUsize>,
+}
+
+impl ConnectionLimiter {
+    pub fn new(limit: usize) -> Self {
+        Self {
+            semaphore: Arc::new(Semaphore::new(limit.clamp(1, Semaphore::MAX_PERMITS))),
+            in_use: Arc::new(AtomicUsize::new(0)),
+            peak: Arc::new(AtomicUsize::new(0)),
+        }
+    }
+    
+    /// No cap beyond the pool itself.
+    pub fn unlimited() -> Self {
+        Self::new(Semaphore::MAX_PERMITS)
+    }
+    
+    /// Run `query` once a slot is free, holding it until `query` ends. Only
+    /// wrap the query itself, never work that waits on something else.
+    pub async fn run<F: Future>(&self, query: F) -> F::Output {
+        let _permit = self.semaphore.acquire().await.expect("connection semaphore closed");
+        let in_use = self.in_use.fetch_add(1, Ordering::SeqCst) + 1;
+        self.peak.fetch_max(in_use, Ordering::SeqCst);
+        
+        let output = query.await;
+        self.in_use.fetch_sub(1, Ordering::SeqCst);
+        output
+    }
+    
+    /// Most queries that have run at the same time.
+    pub fn peak(&self) -> usize {
+        self.peak.load(Ordering::SeqCst)
+    }
+}
+
+/// SQLite's `synchronous` setting: how often writes wait for the disk
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Synchronous {
+    Off,
+    /// Safe with WAL; a power loss can only drop the latest commits
+    #[default]
+    Normal,
+    Full,
+    Extra,
+}
+
+impl Synchronous {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Synchronous::Off => "OFF",
+            Synchronous::Normal => "NORMAL",
+            Synchronous::Full => "FULL",
+            Synchronous::Extra => "EXTRA",
+        }
+    }
+}
+
+/// SQLite's `temp_store` setting: where temporary tables and indices live
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TempStore {
+    /// Whatever SQLite was compiled with
+    Default,
+    File,
+    #[default]
+    Memory,
+}
+
+impl TempStore {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            TempStore::Default => "DEFAULT",
+            TempStore::File => "FILE",
+            TempStore::Memory => "MEMORY",
+        }
+    }
+}
+
+/// Pragmas set on every pooled connection
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SqlitePragmas {
+    /// Bytes of a file database mapped into memory; 0 turns mapping off.
+    /// Ignored for `:memory:`
+    pub mmap_size: u64,
+    /// Page cache per connection: pages if positive, KiB if negative
+    pub cache_size: i64,
+    pub synchronous: Synchronous,
+    pub temp_store: TempStore,
+}
+
+impl Default for SqlitePragmas {
+    fn default() -> Self {
+        Self {
+            // 32-bit targets can't map more than a fraction of their
+            // address space
+            mmap_size: if usize::BITS < 64 { 256 << 20 } else { 30_000_000_000 },
+            // SQLite's own default of about 2 MiB
+            cache_size: -2000,
+            synchronous: Synchronous::Normal,
+            temp_store: TempStore::Memory,
+        }
+    }
+}
+
+impl SqlitePragmas {
+    /// Reject an `mmap_size` the address space can't hold, e.g. anything
+    /// from 2 GiB up on a 32-bit target
+    pub fn validate(&self) -> Result<(), PipelineError> {
+        if isize::try_from(self.mmap_size).is_err() {
+            return Err(PipelineError::Configuration(format!(
+                "mmap_size {} is larger than this {}-bit platform can map",
+                self.mmap_size, usize::BITS
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// Where a database URL points once its scheme is stripped
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DatabaseLocation {
+    /// `:memory:`; lives as long as the pool
+    Memory,
+    File(PathBuf),
+}
+
+impl DatabaseLocation {
+    /// Parse `pipeline.db`, `sqlite:pipeline.db`, `sqlite://~/data/pipeline.db`
+    /// or `:memory:`. A leading `~` is expanded to the home directory.
+    pub fn parse(database_url: &str) -> Result<Self, PipelineError> {
+        let path = database_url.trim();
+        let path = path.strip_prefix("sqlite://")
+            .or_else(|| path.strip_prefix("sqlite:"))
+            .unwrap_or(path);
+        
+        if path.is_empty() {
+            return Err(PipelineError::Configuration(format!(
+                "Database URL has no path: {:?}", database_url
+            )));
+        }
+        if path == ":memory:" {
+            return Ok(Self::Memory);
+        }
+        
+        Ok(Self::File(expand_home(path)?))
+    }
+}
+
+fn expand_home(path: &str) -> Result<PathBuf, PipelineError> {
+    let rest = match path.strip_prefix('~') {
+        Some(rest) if rest.is_empty() || rest.starts_with('/') || rest.starts_with('\\') => rest,
+        _ => return Ok(PathBuf::from(path)),
+    };
+    
+    let home = std::env::var_os("HOME")
+        .or_else(|| std::env::var_os("USERPROFILE"))
+        .ok_or_else(|| PipelineError::Configuration(format!(
+            "Cannot expand ~ in {:?}: no home directory is set", path
+        )))?;
+    
+    Ok(PathBuf::from(home).join(rest.trim_start_matches(['/', '\\'])))
+}
+
 pub async fn create_pool(database_url: &str) -> Result<DatabasePool, PipelineError> {
+    create_pool_with(database_url, SqlitePragmas::default()).await
+}
+
+/// [`create_pool`] with `pragmas` instead of the defaults
+pub async fn create_pool_with(database_url: &str, pragmas: SqlitePragmas) -> Result<DatabasePool, PipelineError> {
     info!("Creating database connection pool for: {}", database_url);
     
+    pragmas.validate()?;
+    let location = DatabaseLocation::parse(database_url)?;
+    
     let options = SqliteConnectOptions::new()
-        .filename(database_url)
-        .create_if_missing(true)
-        .journal_mode(sqlx::sqlite::SqliteJournalMode::Wal)
         .pragma("foreign_keys", "ON")
-        .pragma("temp_store", "MEMORY")
-        .pragma("mmap_size", "30000000000")
-        .pragma("synchronous", "NORMAL");
-    
-    let pool = SqlitePoolOptions::new()
-        .max_connections(10)
-        .min_connections(2)
-        .acquire_timeout(Duration::from_secs(5))
-        .idle_timeout(Duration::from_secs(60))
-        .max_lifetime(Duration::from_secs(1800))
-        .connect_with(options)
-        .await?;
+        .pragma("temp_store", pragmas.temp_store.as_str())
+        .pragma("synchronous", pragmas.synchronous.as_str())
+        .pragma("cache_size", pragmas.cache_size.to_string());
+    
+    let pool = match location {
+        DatabaseLocation::Memory => {
+            // Every connection would open its own empty database, so keep
+            // exactly one and never let it expire
+            let options = options
+                .filename(":memory:")
+                .journal_mode(sqlx::sqlite::SqliteJournalMode::Memory);
+            
+            SqlitePoolOptions::new()
+                .max_connections(1)
+                .min_connections(1)
+                .acquire_timeout(Duration::from_secs(5))
+                .idle_timeout(None)
+                .max_lifetime(None)
+                .connect_with(options)
+                .await?
+        }
+        DatabaseLocation::File(path) => {
+            let options = options
+                .filename(path)
+                .create_if_missing(true)
+                .journal_mode(sqlx::sqlite::SqliteJournalMode::Wal)
+                .pragma("mmap_size", pragmas.mmap_size.to_string());
+            
+            SqlitePoolOptions::new()
+                .max_connections(MAX_CONNECTIONS)
+                .min_connections(2)
+                .acquire_timeout(Duration::from_secs(5))
+                .idle_timeout(Duration::from_secs(60))
+                .max_lifetime(Duration::from_secs(1800))
+                .connect_with(options)
+                .await?
+        }
+    };
     
     info!("Database connection pool created successfully");
     Ok(pool)
@@ -95,4 +307,109 @@ mod tests {
         let mode: String = sqlx::Row::get(&result, 0);
         assert_eq!(mode, "wal");
     }
+    
+    #[tokio::test]
+    async fn test_pragma_overrides() {
+        let temp_file = NamedTempFile::new().unwrap();
+        let pragmas = SqlitePragmas {
+            mmap_size: 1 << 20,
+            cache_size: -4096,
+            synchronous: Synchronous::Full,
+            temp_store: TempStore::File,
+        };
+        
+        let pool = create_pool_with(temp_file.path().to_str().unwrap(), pragmas).await.unwrap();
+        
+        for (query, expected) in [
+            ("PRAGMA mmap_size", 1 << 20),
+            ("PRAGMA cache_size", -4096),
+            // FULL and FILE as SQLite numbers them
+            ("PRAGMA synchronous", 2),
+            ("PRAGMA temp_store", 1),
+        ] {
+            let value: i64 = sqlx::query_scalar(query)
+                .fetch_one(&pool)
+                .await
+                .unwrap();
+            assert_eq!(value, expected, "{}", query);
+        }
+    }
+    
+    #[test]
+    fn test_oversized_mmap_is_rejected() {
+        let pragmas = SqlitePragmas { mmap_size: u64::MAX, ..SqlitePragmas::default() };
+        assert!(pragmas.validate().is_err());
+        assert!(SqlitePragmas::default().validate().is_ok());
+    }
+    
+    #[test]
+    fn test_parse_database_url() {
+        assert_eq!(
+            DatabaseLocation::parse("pipeline.db").unwrap(),
+            DatabaseLocation::File(PathBuf::from("pipeline.db"))
+        );
+        assert_eq!(
+            DatabaseLocation::parse("sqlite:pipeline.db").unwrap(),
+            DatabaseLocation::File(PathBuf::from("pipeline.db"))
+        );
+        assert_eq!(
+            DatabaseLocation::parse("sqlite:///tmp/pipeline.db").unwrap(),
+            DatabaseLocation::File(PathBuf::from("/tmp/pipeline.db"))
+        );
+        assert_eq!(DatabaseLocation::parse("sqlite::memory:").unwrap(), DatabaseLocation::Memory);
+        assert!(DatabaseLocation::parse("sqlite:").is_err());
+        
+        if let Some(home) = std::env::var_os("HOME") {
+            assert_eq!(
+                DatabaseLocation::parse("~/data/pipeline.db").unwrap(),
+                DatabaseLocation::File(PathBuf::from(home).join("data/pipeline.db"))
+            );
+        }
+        // Only a leading ~/ is the home directory
+        assert_eq!(
+            DatabaseLocation::parse("~backup.db").unwrap(),
+            DatabaseLocation::File(PathBuf::from("~backup.db"))
+        );
+    }
+    
+    async fn assert_pool_works(pool: &DatabasePool) {
+        sqlx::query("CREATE TABLE IF NOT EXISTS probe (id INTEGER)")
+            .execute(pool)
+            .await
+            .unwrap();
+        sqlx::query("INSERT INTO probe (id) VALUES (1)")
+            .execute(pool)
+            .await
+            .unwrap();
+        let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM probe")
+            .fetch_one(pool)
+            .await
+            .unwrap();
+        assert!(count >= 1);
+    }
+    
+    #[tokio::test]
+    async fn test_create_pool_from_each_url_form() {
+        let dir = tempfile::tempdir().unwrap();
+        
+        let plain = dir.path().join("plain.db");
+        let pool = create_pool(plain.to_str().unwrap()).await.unwrap();
+        assert_pool_works(&pool).await;
+        assert!(plain.exists());
+        
+        let prefixed = dir.path().join("prefixed.db");
+        let pool = create_pool(&format!("sqlite:{}", prefixed.display())).await.unwrap();
+        assert_pool_works(&pool).await;
+        assert!(prefixed.exists());
+        assert!(!dir.path().join("sqlite:prefixed.db").exists());
+        
+        let pool = create_pool(":memory:").await.unwrap();
+        assert_pool_works(&pool).await;
+        // Later queries see the same in-memory database
+        let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM probe")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(count, 1);
+    }
 }
\ No newline at end of file
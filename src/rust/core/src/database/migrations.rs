@@ -1,5 +1,6 @@
-use sqlx::{Sqlite, Transaction};
-use tracing::{info, error};
+use sha2::{Digest, Sha256};
+use sqlx::{Row, Sqlite, Transaction};
+use tracing::{info, warn, error};
 use crate::models::PipelineError;
 use super::DatabasePool;
 
@@ -9,15 +10,85 @@ pub struct Migration {
     pub sql: &'static str,
 }
 
+impl Migration {
+    /// Hex SHA-256 of the migration's SQL, recorded when it is applied
+    pub fn checksum(&self) -> String {
+        format!("{:x}", Sha256::digest(self.sql.as_bytes()))
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MigrationOptions {
+    /// Warn instead of failing when an applied migration's SQL has changed
+    pub allow_drift: bool,
+}
+
 const MIGRATIONS: &[Migration] = &[
     Migration {
         version: 1,
         description: "Create initial schema",
         sql: include_str!("../../../migrations/001_initial_schema.sql"),
     },
+    Migration {
+        version: 2,
+        description: "Add batch labels",
+        sql: include_str!("../../../migrations/002_batch_labels.sql"),
+    },
+    Migration {
+        version: 3,
+        description: "Add skipped queue status",
+        sql: include_str!("../../../migrations/003_skipped_status.sql"),
+    },
+    Migration {
+        version: 4,
+        description: "Add cache warm checkpoints",
+        sql: include_str!("../../../migrations/004_cache_warm_checkpoints.sql"),
+    },
+    Migration {
+        version: 5,
+        description: "Add cache namespaces",
+        sql: include_str!("../../../migrations/005_cache_namespaces.sql"),
+    },
+    Migration {
+        version: 6,
+        description: "Add API call log",
+        sql: include_str!("../../../migrations/006_api_call_log.sql"),
+    },
+    Migration {
+        version: 7,
+        description: "Add card state",
+        sql: include_str!("../../../migrations/007_card_state.sql"),
+    },
+    Migration {
+        version: 8,
+        description: "Add cache stats history",
+        sql: include_str!("../../../migrations/008_cache_stats_history.sql"),
+    },
+    Migration {
+        version: 9,
+        description: "Add queue cache keys",
+        sql: include_str!("../../../migrations/009_queue_cache_keys.sql"),
+    },
 ];
 
 pub async fn run_migrations(pool: &DatabasePool) -> Result<(), PipelineError> {
+    run_migrations_with_options(pool, MigrationOptions::default()).await
+}
+
+/// Apply pending migrations after checking that those already applied still
+/// match the embedded SQL.
+pub async fn run_migrations_with_options(
+    pool: &DatabasePool,
+    options: MigrationOptions,
+) -> Result<(), PipelineError> {
+    apply_migrations(pool, MIGRATIONS, options).await
+}
+
+async fn apply_migrations(
+    pool: &DatabasePool,
+    migrations: &[Migration],
+    options: MigrationOptions,
+) -> Result<(), PipelineError> {
     info!("Starting database migrations");
     
     // Create schema_versions table if it doesn't exist
@@ -33,10 +104,13 @@ pub async fn run_migrations(pool: &DatabasePool) -> Result<(), PipelineError> {
     .execute(pool)
     .await?;
     
+    bootstrap_checksums(pool, migrations).await?;
+    verify_checksums(pool, migrations, options).await?;
+    
     let current_version = super::get_database_version(pool).await?;
     info!("Current database version: {}", current_version);
     
-    for migration in MIGRATIONS {
+    for migration in migrations {
         if migration.version > current_version {
             apply_migration(pool, migration).await?;
         }
@@ -46,6 +120,84 @@ pub async fn run_migrations(pool: &DatabasePool) -> Result<(), PipelineError> {
     Ok(())
 }
 
+/// Add the `checksum` column to databases created before checksums were
+/// recorded, and fill it in for migrations applied back then. Their SQL is
+/// assumed to be what shipped.
+async fn bootstrap_checksums(pool: &DatabasePool, migrations: &[Migration]) -> Result<(), PipelineError> {
+    let columns = sqlx::query("PRAGMA table_info(schema_versions)")
+        .fetch_all(pool)
+        .await?;
+    let has_checksum = columns.iter()
+        .any(|column| column.get::<String, _>("name") == "checksum");
+    
+    if !has_checksum {
+        info!("Adding checksum column to schema_versions");
+        sqlx::query("ALTER TABLE schema_versions ADD COLUMN checksum TEXT")
+            .execute(pool)
+            .await?;
+    }
+    
+    for migration in migrations {
+        let backfilled = sqlx::query(
+            "UPDATE schema_versions SET checksum = ? WHERE version = ? AND checksum IS NULL"
+        )
+        .bind(migration.checksum())
+        .bind(migration.version)
+        .execute(pool)
+        .await?;
+        
+        if backfilled.rows_affected() > 0 {
+            info!("Recorded checksum for previously applied migration {}", migration.version);
+        }
+    }
+    
+    Ok(())
+}
+
+/// Fail if an applied migration's SQL no longer matches what was recorded,
+/// unless drift is allowed.
+async fn verify_checksums(
+    pool: &DatabasePool,
+    migrations: &[Migration],
+    options: MigrationOptions,
+) -> Result<(), PipelineError> {
+    let applied = sqlx::query("SELECT version, checksum FROM schema_versions ORDER BY version")
+        .fetch_all(pool)
+        .await?;
+    
+    for row in applied {
+        let version: i32 = row.get("version");
+        let Some(recorded) = row.get::<Option<String>, _>("checksum") else {
+            continue;
+        };
+        let Some(migration) = migrations.iter().find(|migration| migration.version == version) else {
+            continue;
+        };
+        
+        let embedded = migration.checksum();
+        if recorded == embedded {
+            continue;
+        }
+        
+        let message = format!(
+            "Migration {} ({}) has changed since it was applied: recorded checksum {}, embedded {}",
+            version, migration.description, recorded, embedded
+        );
+        if options.allow_drift {
+            warn!("{}; continuing because drift is allowed", message);
+        } else {
+            error!("{}", message);
+            return Err(PipelineError::Configuration(format!(
+                "{}. Shipped migrations must not be edited; add a new one instead, \
+                 or pass --allow-migration-drift to start anyway",
+                message
+            )));
+        }
+    }
+    
+    Ok(())
+}
+
 async fn apply_migration(pool: &DatabasePool, migration: &Migration) -> Result<(), PipelineError> {
     info!("Applying migration {}: {}", migration.version, migration.description);
     
@@ -62,10 +214,11 @@ async fn apply_migration(pool: &DatabasePool, migration: &Migration) -> Result<(
     
     // Record migration
     sqlx::query(
-        "INSERT INTO schema_versions (version, description) VALUES (?, ?)"
+        "INSERT INTO schema_versions (version, description, checksum) VALUES (?, ?, ?)"
     )
     .bind(migration.version)
     .bind(migration.description)
+    .bind(migration.checksum())
     .execute(&mut *tx)
     .await?;
     
@@ -111,4 +264,62 @@ mod tests {
         // Run again - should be idempotent
         run_migrations(&pool).await.unwrap();
     }
+    
+    const PROBE_V1: &str = "CREATE TABLE probe (id INTEGER PRIMARY KEY)";
+    
+    fn probe_migrations(sql: &'static str) -> [Migration; 1] {
+        [Migration { version: 1, description: "Create probe", sql }]
+    }
+    
+    #[tokio::test]
+    async fn test_edited_migration_is_detected() {
+        let temp_file = NamedTempFile::new().unwrap();
+        let pool = super::super::create_pool(temp_file.path().to_str().unwrap()).await.unwrap();
+        
+        apply_migrations(&pool, &probe_migrations(PROBE_V1), MigrationOptions::default()).await.unwrap();
+        let recorded: String = sqlx::query_scalar("SELECT checksum FROM schema_versions WHERE version = 1")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(recorded, probe_migrations(PROBE_V1)[0].checksum());
+        
+        // Same SQL is fine; edited SQL is drift
+        apply_migrations(&pool, &probe_migrations(PROBE_V1), MigrationOptions::default()).await.unwrap();
+        let edited = probe_migrations("CREATE TABLE probe (id INTEGER PRIMARY KEY, name TEXT)");
+        let err = apply_migrations(&pool, &edited, MigrationOptions::default()).await.unwrap_err();
+        assert!(matches!(&err, PipelineError::Configuration(msg) if msg.contains("Migration 1")));
+        
+        apply_migrations(&pool, &edited, MigrationOptions { allow_drift: true }).await.unwrap();
+    }
+    
+    #[tokio::test]
+    async fn test_checksums_backfilled_for_old_databases() {
+        let temp_file = NamedTempFile::new().unwrap();
+        let pool = super::super::create_pool(temp_file.path().to_str().unwrap()).await.unwrap();
+        
+        // A database migrated before checksums existed
+        sqlx::query(
+            "CREATE TABLE schema_versions (
+                version INTEGER PRIMARY KEY,
+                description TEXT NOT NULL,
+                applied_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+            )"
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+        sqlx::query(PROBE_V1).execute(&pool).await.unwrap();
+        sqlx::query("INSERT INTO schema_versions (version, description) VALUES (1, 'Create probe')")
+            .execute(&pool)
+            .await
+            .unwrap();
+        
+        apply_migrations(&pool, &probe_migrations(PROBE_V1), MigrationOptions::default()).await.unwrap();
+        
+        let recorded: Option<String> = sqlx::query_scalar("SELECT checksum FROM schema_versions WHERE version = 1")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(recorded, Some(probe_migrations(PROBE_V1)[0].checksum()));
+    }
 }
\ No newline at end of file
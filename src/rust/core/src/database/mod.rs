@@ -2,5 +2,5 @@ pub mod connection;
 pub mod repositories;
 pub mod migrations;
 
-pub use connection::{DatabasePool, create_pool};
+pub use connection::{ConnectionLimiter, DatabasePool, DatabaseLocation, MAX_CONNECTIONS, SqlitePragmas, Synchronous, TempStore, create_pool, create_pool_with};
 pub use repositories::*;
\ No newline at end of file
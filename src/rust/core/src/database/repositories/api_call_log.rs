@@ -0,0 +1,157 @@
+use sqlx::FromRow;
+use chrono::{DateTime, Utc};
+use tracing::debug;
+use crate::models::{ApiCallRecord, BatchId, CacheType, PipelineError};
+use crate::database::DatabasePool;
+
+/// Append-only record of API calls, for cost tracking and for finding out
+/// which model produced a card
+pub struct ApiCallLogRepository {
+    pool: DatabasePool,
+}
+
+#[derive(FromRow)]
+struct ApiCallRow {
+    id: i64,
+    batch_id: Option<BatchId>,
+    term: String,
+    stage: String,
+    model: String,
+    total_tokens: i64,
+    latency_ms: i64,
+    success: bool,
+    error_message: Option<String>,
+    called_at: DateTime<Utc>,
+}
+
+impl ApiCallLogRepository {
+    pub fn new(pool: DatabasePool) -> Self {
+        Self { pool }
+    }
+
+    /// Store `record`, ignoring its `id`. Returns the id it was stored under.
+    pub async fn append(&self, record: &ApiCallRecord) -> Result<i64, PipelineError> {
+        let stage = match record.stage {
+            CacheType::Stage1 => "stage1",
+            CacheType::Stage2 => "stage2",
+        };
+        
+        let result = sqlx::query(
+            r#"
+            INSERT INTO api_call_log (
+                batch_id, term, stage, model, total_tokens, latency_ms,
+                success, error_message, called_at
+            ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)
+            "#
+        )
+        .bind(&record.batch_id)
+        .bind(&record.term)
+        .bind(stage)
+        .bind(&record.model)
+        .bind(record.total_tokens)
+        .bind(record.latency_ms)
+        .bind(record.success)
+        .bind(&record.error_message)
+        .bind(record.called_at)
+        .execute(&self.pool)
+        .await?;
+        
+        Ok(result.last_insert_rowid())
+    }
+
+    /// The last `limit` calls, of `batch_id` only if given, oldest first.
+    pub async fn recent(
+        &self,
+        batch_id: Option<&BatchId>,
+        limit: i64,
+    ) -> Result<Vec<ApiCallRecord>, PipelineError> {
+        debug!("Reading the last {} API calls (batch: {:?})", limit, batch_id);
+        
+        let rows = sqlx::query_as::<_, ApiCallRow>(
+            r#"
+            SELECT id, batch_id, term, stage, model, total_tokens, latency_ms,
+                   success, error_message, called_at
+            FROM api_call_log
+            WHERE ?1 IS NULL OR batch_id = ?1
+            ORDER BY id DESC
+            LIMIT ?2
+            "#
+        )
+        .bind(batch_id)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+        
+        rows.into_iter().rev().map(Self::row_to_record).collect()
+    }
+
+    fn row_to_record(row: ApiCallRow) -> Result<ApiCallRecord, PipelineError> {
+        let stage = match row.stage.as_str() {
+            "stage1" => CacheType::Stage1,
+            "stage2" => CacheType::Stage2,
+            _ => return Err(PipelineError::Validation(
+                format!("Invalid stage: {}", row.stage)
+            )),
+        };
+        
+        Ok(ApiCallRecord {
+            id: Some(row.id),
+            batch_id: row.batch_id,
+            term: row.term,
+            stage,
+            model: row.model,
+            total_tokens: row.total_tokens,
+            latency_ms: row.latency_ms,
+            success: row.success,
+            error_message: row.error_message,
+            called_at: row.called_at,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[tokio::test]
+    async fn test_append_and_read_back_by_batch() {
+        let temp_file = tempfile::NamedTempFile::new().unwrap();
+        let pool = crate::database::create_pool(temp_file.path().to_str().unwrap()).await.unwrap();
+        crate::database::migrations::run_migrations(&pool).await.unwrap();
+        let repo = ApiCallLogRepository::new(pool);
+        
+        let call = |batch: &str, term: &str, stage: CacheType, error: Option<&str>| ApiCallRecord {
+            id: None,
+            batch_id: Some(BatchId::new(batch)),
+            term: term.to_string(),
+            stage,
+            model: "anthropic/claude-3.5-sonnet".to_string(),
+            total_tokens: if error.is_some() { 0 } else { 120 },
+            latency_ms: 850,
+            success: error.is_none(),
+            error_message: error.map(str::to_string),
+            called_at: Utc::now(),
+        };
+        
+        repo.append(&call("a", "학교", CacheType::Stage1, None)).await.unwrap();
+        repo.append(&call("b", "바다", CacheType::Stage1, None)).await.unwrap();
+        repo.append(&call("a", "학교", CacheType::Stage2, Some("HTTP 503"))).await.unwrap();
+        repo.append(&call("a", "학교", CacheType::Stage2, None)).await.unwrap();
+        
+        let batch_a = repo.recent(Some(&BatchId::new("a")), 10).await.unwrap();
+        let stages: Vec<_> = batch_a.iter().map(|call| (call.stage.clone(), call.success)).collect();
+        assert_eq!(stages, vec![
+            (CacheType::Stage1, true),
+            (CacheType::Stage2, false),
+            (CacheType::Stage2, true),
+        ]);
+        assert_eq!(batch_a[1].error_message.as_deref(), Some("HTTP 503"));
+        assert_eq!(batch_a[0].total_tokens, 120);
+        
+        // The newest calls across batches, still in call order
+        let latest = repo.recent(None, 2).await.unwrap();
+        assert_eq!(latest.len(), 2);
+        assert_eq!(latest[0].stage, CacheType::Stage2);
+        assert!(latest[1].success);
+    }
+}
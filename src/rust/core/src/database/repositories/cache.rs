@@ -1,9 +1,14 @@
 use sqlx::{FromRow, Row};
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, Duration, Utc};
+use futures::TryStreamExt;
 use serde_json;
-use tracing::{info, debug};
-use crate::models::{CacheEntry, CacheType, CacheStats, Stage1Result, Stage2Result, PipelineError};
-use crate::database::DatabasePool;
+use tracing::{info, debug, warn};
+use crate::models::{
+    BatchId, CacheEntry, CacheEntryVocabulary, CacheImportStats, CacheMigrationStats, CacheType, CacheStats, CacheStatsSnapshot, CardType, FlashcardContent,
+    KeyNormalization, LegacyCard, Stage1Result, Stage2Mode, Stage2Result, PipelineError, VocabularyItem, DEFAULT_NAMESPACE
+};
+use crate::database::{DatabasePool, repositories::VocabularyRepository};
+use std::collections::HashMap;
 
 pub struct CacheRepository {
     pool: DatabasePool,
@@ -21,6 +26,48 @@ struct CacheRow {
     created_at: DateTime<Utc>,
     accessed_at: DateTime<Utc>,
     access_count: i32,
+    namespace: String,
+}
+
+#[derive(FromRow)]
+struct StatsSnapshotRow {
+    stage1_entries: i64,
+    stage2_entries: i64,
+    total_hits: i64,
+    total_misses: i64,
+    total_tokens_saved: i64,
+    recorded_at: DateTime<Utc>,
+}
+
+impl From<StatsSnapshotRow> for CacheStatsSnapshot {
+    fn from(row: StatsSnapshotRow) -> Self {
+        let mut stats = CacheStats {
+            total_entries: row.stage1_entries + row.stage2_entries,
+            stage1_entries: row.stage1_entries,
+            stage2_entries: row.stage2_entries,
+            total_hits: row.total_hits,
+            total_misses: row.total_misses,
+            hit_rate: 0.0,
+            total_tokens_saved: row.total_tokens_saved,
+            estimated_cost_saved: 0.0,
+        };
+        stats.calculate_hit_rate();
+        stats.estimate_cost_saved();
+        
+        Self { recorded_at: row.recorded_at, stats }
+    }
+}
+
+/// The card part of a Stage 2 entry's `response_json`
+#[derive(serde::Deserialize)]
+struct StoredCard {
+    front: FlashcardContent,
+    back: FlashcardContent,
+    card_type: CardType,
+    #[serde(default)]
+    learning_order: Option<i32>,
+    #[serde(default)]
+    related_cards: Vec<String>,
 }
 
 impl CacheRepository {
@@ -34,7 +81,7 @@ impl CacheRepository {
         let row = sqlx::query_as::<_, CacheRow>(
             r#"
             SELECT id, vocabulary_id, cache_key, request_hash, response_json, 
-                   token_count, model_used, created_at, accessed_at, access_count
+                   token_count, model_used, created_at, accessed_at, access_count, namespace
             FROM stage1_cache WHERE cache_key = ?
             "#
         )
@@ -50,10 +97,11 @@ impl CacheRepository {
                 let response_data: serde_json::Value = serde_json::from_str(&row.response_json)?;
                 
                 // Reconstruct Stage1Result from cached data
-                let semantic_analysis = serde_json::from_value(
+                let semantic_analysis = crate::json::from_value(
                     response_data.get("semantic_analysis")
                         .ok_or_else(|| PipelineError::Cache("Missing semantic_analysis in cache".to_string()))?
-                        .clone()
+                        .clone(),
+                    &format!("semantic_analysis of cached entry {}", row.cache_key),
                 )?;
                 
                 let result = Stage1Result {
@@ -80,12 +128,35 @@ impl CacheRepository {
         }
     }
 
+    /// Cache `result` in the default namespace.
+    ///
+    /// `result.vocabulary_id` must be the id of a saved vocabulary item, since
+    /// cache rows reference `vocabulary_items`; save the item first.
+    /// [`CacheManager`](crate::cache_manager::CacheManager) does so for
+    /// unsaved items.
+    ///
+    /// Saving a key that is already cached replaces the entry, e.g. after a
+    /// forced refresh, and counts it as new: `created_at` is reset to now and
+    /// `access_count` to 1.
     pub async fn save_stage1_cache(
         &self, 
         result: &Stage1Result,
         request_hash: String,
         token_count: i32,
         model_used: String,
+    ) -> Result<(), PipelineError> {
+        self.save_stage1_cache_in(DEFAULT_NAMESPACE, result, request_hash, token_count, model_used).await
+    }
+
+    /// Like [`save_stage1_cache`](Self::save_stage1_cache), recording the
+    /// entry as belonging to `namespace`.
+    pub async fn save_stage1_cache_in(
+        &self,
+        namespace: &str,
+        result: &Stage1Result,
+        request_hash: String,
+        token_count: i32,
+        model_used: String,
     ) -> Result<(), PipelineError> {
         debug!("Saving Stage 1 cache for key: {}", result.cache_key);
         
@@ -97,8 +168,17 @@ impl CacheRepository {
         sqlx::query(
             r#"
             INSERT INTO stage1_cache 
-            (vocabulary_id, cache_key, request_hash, response_json, token_count, model_used)
-            VALUES (?, ?, ?, ?, ?, ?)
+            (vocabulary_id, cache_key, request_hash, response_json, token_count, model_used, namespace)
+            VALUES (?, ?, ?, ?, ?, ?, ?)
+            ON CONFLICT(cache_key) DO UPDATE SET
+                vocabulary_id = excluded.vocabulary_id,
+                request_hash = excluded.request_hash,
+                response_json = excluded.response_json,
+                token_count = excluded.token_count,
+                model_used = excluded.model_used,
+                namespace = excluded.namespace,
+                created_at = CURRENT_TIMESTAMP,
+                access_count = 1
             "#
         )
         .bind(result.vocabulary_id)
@@ -107,6 +187,7 @@ impl CacheRepository {
         .bind(response_json.to_string())
         .bind(token_count)
         .bind(&model_used)
+        .bind(namespace)
         .execute(&self.pool)
         .await?;
         
@@ -134,34 +215,8 @@ impl CacheRepository {
                 let id: i64 = row.get(0);
                 self.update_cache_access("stage2_cache", id).await?;
                 
-                let vocabulary_id: i64 = row.get(1);
-                let stage1_cache_key: String = row.get(2);
-                let cache_key: String = row.get(3);
-                let response_json: String = row.get(5);
-                let tsv_output: String = row.get(6);
                 let token_count: i32 = row.get(7);
-                let created_at: DateTime<Utc> = row.get(9);
-                
-                let response_data: serde_json::Value = serde_json::from_str(&response_json)?;
-                
-                let flashcard_content = serde_json::from_value(
-                    response_data.get("flashcard_content")
-                        .ok_or_else(|| PipelineError::Cache("Missing flashcard_content in cache".to_string()))?
-                        .clone()
-                )?;
-                
-                let result = Stage2Result {
-                    vocabulary_id,
-                    stage1_cache_key,
-                    request_id: response_data.get("request_id")
-                        .and_then(|v| v.as_str())
-                        .unwrap_or("cached")
-                        .to_string(),
-                    cache_key,
-                    flashcard_content,
-                    tsv_output,
-                    created_at,
-                };
+                let result = Self::stage2_result(&row)?;
                 
                 info!("Stage 2 cache hit for key: {}", cache_key);
                 self.increment_cache_metrics(CacheType::Stage2, true, token_count).await?;
@@ -176,26 +231,122 @@ impl CacheRepository {
         }
     }
 
+    /// The cards of a batch's completed items, in queue order, for exporting
+    /// again without reprocessing.
+    ///
+    /// Items are matched to the Stage 2 entry whose key the batch recorded
+    /// on their queue row when they finished, so two batches of the same
+    /// words each get their own cards. Rows queued before keys were
+    /// recorded fall back to the item's newest entry. Completed items
+    /// without an entry are left out.
+    pub async fn get_stage2_results_for_batch(
+        &self,
+        batch_id: &BatchId,
+    ) -> Result<Vec<(VocabularyItem, Stage2Result)>, PipelineError> {
+        self.get_stage2_results_for_batch_in(batch_id, DEFAULT_NAMESPACE).await
+    }
+
+    /// Like [`get_stage2_results_for_batch`](Self::get_stage2_results_for_batch),
+    /// reading only `namespace`'s entries.
+    pub async fn get_stage2_results_for_batch_in(
+        &self,
+        batch_id: &BatchId,
+        namespace: &str,
+    ) -> Result<Vec<(VocabularyItem, Stage2Result)>, PipelineError> {
+        debug!("Loading Stage 2 results for batch: {}", batch_id);
+        
+        let items = VocabularyRepository::new(self.pool.clone())
+            .list_completed_in_batch(batch_id)
+            .await?;
+        
+        // Entries matched by recorded key sort after the fallback ones
+        let rows = sqlx::query(
+            r#"
+            SELECT s.id, s.vocabulary_id, s.stage1_cache_key, s.cache_key, s.request_hash, 
+                   s.response_json, s.tsv_output, s.token_count, s.model_used, 
+                   s.created_at, s.accessed_at, s.access_count
+            FROM processing_queue q
+            JOIN stage2_cache s ON CASE
+                WHEN q.stage2_cache_key IS NULL THEN s.vocabulary_id = q.vocabulary_id
+                ELSE s.cache_key = q.stage2_cache_key
+            END
+            WHERE q.batch_id = ?1 AND q.status = 'completed' AND s.namespace = ?2
+            ORDER BY q.stage2_cache_key IS NOT NULL, s.created_at, s.id
+            "#
+        )
+        .bind(batch_id)
+        .bind(namespace)
+        .fetch_all(&self.pool)
+        .await?;
+        
+        // Later rows replace earlier ones, leaving each item's recorded or
+        // else newest entry
+        let mut results = HashMap::new();
+        for row in &rows {
+            let result = Self::stage2_result(row)?;
+            results.insert(result.vocabulary_id, result);
+        }
+        
+        Ok(items.into_iter()
+            .filter_map(|item| {
+                let result = results.remove(&item.id?)?;
+                Some((item, result))
+            })
+            .collect())
+    }
+
+    /// Cache `result` in the default namespace. As with
+    /// [`save_stage1_cache`](Self::save_stage1_cache), its `vocabulary_id`
+    /// must refer to a saved item, and an existing entry for its key is
+    /// replaced.
     pub async fn save_stage2_cache(
         &self,
         result: &Stage2Result,
         request_hash: String,
         token_count: i32,
         model_used: String,
+    ) -> Result<(), PipelineError> {
+        self.save_stage2_cache_in(DEFAULT_NAMESPACE, result, request_hash, token_count, model_used).await
+    }
+
+    /// Like [`save_stage2_cache`](Self::save_stage2_cache), recording the
+    /// entry as belonging to `namespace`.
+    pub async fn save_stage2_cache_in(
+        &self,
+        namespace: &str,
+        result: &Stage2Result,
+        request_hash: String,
+        token_count: i32,
+        model_used: String,
     ) -> Result<(), PipelineError> {
         debug!("Saving Stage 2 cache for key: {}", result.cache_key);
         
         let response_json = serde_json::json!({
             "request_id": &result.request_id,
-            "flashcard_content": &result.flashcard_content,
+            "front": &result.front,
+            "back": &result.back,
+            "card_type": &result.card_type,
+            "learning_order": result.learning_order,
+            "related_cards": &result.related_cards,
         });
         
         sqlx::query(
             r#"
             INSERT INTO stage2_cache 
             (vocabulary_id, stage1_cache_key, cache_key, request_hash, 
-             response_json, tsv_output, token_count, model_used)
-            VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+             response_json, tsv_output, token_count, model_used, namespace)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)
+            ON CONFLICT(cache_key) DO UPDATE SET
+                vocabulary_id = excluded.vocabulary_id,
+                stage1_cache_key = excluded.stage1_cache_key,
+                request_hash = excluded.request_hash,
+                response_json = excluded.response_json,
+                tsv_output = excluded.tsv_output,
+                token_count = excluded.token_count,
+                model_used = excluded.model_used,
+                namespace = excluded.namespace,
+                created_at = CURRENT_TIMESTAMP,
+                access_count = 1
             "#
         )
         .bind(result.vocabulary_id)
@@ -206,6 +357,7 @@ impl CacheRepository {
         .bind(&result.tsv_output)
         .bind(token_count)
         .bind(&model_used)
+        .bind(namespace)
         .execute(&self.pool)
         .await?;
         
@@ -261,35 +413,806 @@ impl CacheRepository {
         Ok(stats)
     }
 
+    /// Record the current [`get_cache_stats`](Self::get_cache_stats) in the
+    /// stats history, e.g. after each run or on a schedule, and return them.
+    pub async fn snapshot_stats(&self) -> Result<CacheStatsSnapshot, PipelineError> {
+        let snapshot = CacheStatsSnapshot {
+            recorded_at: Utc::now(),
+            stats: self.get_cache_stats().await?,
+        };
+        
+        sqlx::query(
+            r#"
+            INSERT INTO cache_stats_history (
+                stage1_entries, stage2_entries, total_hits, total_misses,
+                total_tokens_saved, recorded_at
+            ) VALUES (?, ?, ?, ?, ?, ?)
+            "#
+        )
+        .bind(snapshot.stats.stage1_entries)
+        .bind(snapshot.stats.stage2_entries)
+        .bind(snapshot.stats.total_hits)
+        .bind(snapshot.stats.total_misses)
+        .bind(snapshot.stats.total_tokens_saved)
+        .bind(snapshot.recorded_at)
+        .execute(&self.pool)
+        .await?;
+        
+        debug!("Recorded cache stats snapshot: {:?}", snapshot.stats);
+        Ok(snapshot)
+    }
+
+    /// Snapshots recorded since `since`, oldest first.
+    pub async fn stats_history(&self, since: DateTime<Utc>) -> Result<Vec<CacheStatsSnapshot>, PipelineError> {
+        let rows = sqlx::query_as::<_, StatsSnapshotRow>(
+            r#"
+            SELECT stage1_entries, stage2_entries, total_hits, total_misses,
+                   total_tokens_saved, recorded_at
+            FROM cache_stats_history
+            WHERE recorded_at >= ?
+            ORDER BY recorded_at, id
+            "#
+        )
+        .bind(since)
+        .fetch_all(&self.pool)
+        .await?;
+        
+        Ok(rows.into_iter().map(CacheStatsSnapshot::from).collect())
+    }
+
     pub async fn clear_cache(&self, cache_type: Option<CacheType>) -> Result<i64, PipelineError> {
-        let count = match cache_type {
-            Some(CacheType::Stage1) => {
-                let result = sqlx::query("DELETE FROM stage1_cache")
-                    .execute(&self.pool)
-                    .await?;
-                result.rows_affected() as i64
+        self.clear_cache_in(cache_type, None).await
+    }
+
+    /// Delete the entries of `cache_type` (or both stages) that belong to
+    /// `namespace`, or to every namespace when it is `None`.
+    pub async fn clear_cache_in(
+        &self,
+        cache_type: Option<CacheType>,
+        namespace: Option<&str>,
+    ) -> Result<i64, PipelineError> {
+        let tables: &[&str] = match cache_type {
+            Some(CacheType::Stage1) => &["stage1_cache"],
+            Some(CacheType::Stage2) => &["stage2_cache"],
+            None => &["stage1_cache", "stage2_cache"],
+        };
+        
+        let mut count = 0;
+        for table in tables {
+            let result = sqlx::query(&format!("DELETE FROM {} WHERE ?1 IS NULL OR namespace = ?1", table))
+                .bind(namespace)
+                .execute(&self.pool)
+                .await?;
+            count += result.rows_affected() as i64;
+        }
+        
+        match namespace {
+            Some(namespace) => info!("Cleared {} cache entries in namespace '{}'", count, namespace),
+            None => info!("Cleared {} cache entries", count),
+        }
+        Ok(count)
+    }
+
+    /// Delete every entry computed by `model`, e.g. after switching models
+    /// without model pinning. Returns how many were removed.
+    pub async fn clear_cache_by_model(&self, model: &str) -> Result<i64, PipelineError> {
+        self.clear_cache_by_model_in(None, model, None).await
+    }
+
+    /// Like [`clear_cache_by_model`](Self::clear_cache_by_model), limited to
+    /// `cache_type` and, unless it is `None`, `namespace`.
+    pub async fn clear_cache_by_model_in(
+        &self,
+        cache_type: Option<CacheType>,
+        model: &str,
+        namespace: Option<&str>,
+    ) -> Result<i64, PipelineError> {
+        let tables: &[&str] = match cache_type {
+            Some(CacheType::Stage1) => &["stage1_cache"],
+            Some(CacheType::Stage2) => &["stage2_cache"],
+            None => &["stage1_cache", "stage2_cache"],
+        };
+        
+        let mut count = 0;
+        for table in tables {
+            let result = sqlx::query(&format!(
+                "DELETE FROM {} WHERE model_used = ?1 AND (?2 IS NULL OR namespace = ?2)", table
+            ))
+            .bind(model)
+            .bind(namespace)
+            .execute(&self.pool)
+            .await?;
+            count += result.rows_affected() as i64;
+        }
+        
+        info!("Cleared {} cache entries from model '{}'", count, model);
+        Ok(count)
+    }
+
+    /// Hash of the request that produced a cached entry, without touching its access stats.
+    pub async fn get_request_hash(
+        &self,
+        cache_type: CacheType,
+        cache_key: &str,
+    ) -> Result<Option<String>, PipelineError> {
+        let table = match cache_type {
+            CacheType::Stage1 => "stage1_cache",
+            CacheType::Stage2 => "stage2_cache",
+        };
+        
+        let request_hash = sqlx::query_scalar::<_, String>(
+            &format!("SELECT request_hash FROM {} WHERE cache_key = ?", table)
+        )
+        .bind(cache_key)
+        .fetch_optional(&self.pool)
+        .await?;
+        
+        Ok(request_hash)
+    }
+
+    /// A single cache entry with its metadata, without touching its access stats.
+    pub async fn get_cache_entry(
+        &self,
+        cache_type: CacheType,
+        cache_key: &str,
+    ) -> Result<Option<CacheEntry>, PipelineError> {
+        match cache_type {
+            CacheType::Stage1 => {
+                let row = sqlx::query_as::<_, CacheRow>(
+                    r#"
+                    SELECT id, vocabulary_id, cache_key, request_hash, response_json, 
+                           token_count, model_used, created_at, accessed_at, access_count, namespace
+                    FROM stage1_cache WHERE cache_key = ?
+                    "#
+                )
+                .bind(cache_key)
+                .fetch_optional(&self.pool)
+                .await?;
+                
+                row.map(Self::stage1_entry).transpose()
             }
-            Some(CacheType::Stage2) => {
-                let result = sqlx::query("DELETE FROM stage2_cache")
-                    .execute(&self.pool)
-                    .await?;
-                result.rows_affected() as i64
+            CacheType::Stage2 => {
+                let row = sqlx::query(
+                    r#"
+                    SELECT id, vocabulary_id, stage1_cache_key, cache_key, request_hash, 
+                           response_json, tsv_output, token_count, model_used, 
+                           created_at, accessed_at, access_count, namespace
+                    FROM stage2_cache WHERE cache_key = ?
+                    "#
+                )
+                .bind(cache_key)
+                .fetch_optional(&self.pool)
+                .await?;
+                
+                row.as_ref().map(Self::stage2_entry).transpose()
             }
-            None => {
-                let result1 = sqlx::query("DELETE FROM stage1_cache")
-                    .execute(&self.pool)
-                    .await?;
-                let result2 = sqlx::query("DELETE FROM stage2_cache")
-                    .execute(&self.pool)
-                    .await?;
-                (result1.rows_affected() + result2.rows_affected()) as i64
+        }
+    }
+
+    /// Remove a single cache entry. Returns whether it existed.
+    pub async fn delete_cache_entry(
+        &self,
+        cache_type: CacheType,
+        cache_key: &str,
+    ) -> Result<bool, PipelineError> {
+        let table = match cache_type {
+            CacheType::Stage1 => "stage1_cache",
+            CacheType::Stage2 => "stage2_cache",
+        };
+        
+        let result = sqlx::query(&format!("DELETE FROM {} WHERE cache_key = ?", table))
+            .bind(cache_key)
+            .execute(&self.pool)
+            .await?;
+        
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Token count recorded for a cached entry, without touching its access stats.
+    pub async fn get_cached_token_count(
+        &self,
+        cache_type: CacheType,
+        cache_key: &str,
+    ) -> Result<Option<i32>, PipelineError> {
+        let table = match cache_type {
+            CacheType::Stage1 => "stage1_cache",
+            CacheType::Stage2 => "stage2_cache",
+        };
+        
+        let token_count = sqlx::query_scalar::<_, i32>(
+            &format!("SELECT token_count FROM {} WHERE cache_key = ?", table)
+        )
+        .bind(cache_key)
+        .fetch_optional(&self.pool)
+        .await?;
+        
+        Ok(token_count)
+    }
+
+    /// Where an interrupted cache warm over the items identified by
+    /// `warm_key` stopped: the index of the next item to check and the stats
+    /// gathered before it.
+    pub async fn get_warm_checkpoint(
+        &self,
+        warm_key: &str,
+    ) -> Result<Option<(usize, serde_json::Value)>, PipelineError> {
+        let row = sqlx::query("SELECT next_index, stats FROM cache_warm_checkpoints WHERE warm_key = ?")
+            .bind(warm_key)
+            .fetch_optional(&self.pool)
+            .await?;
+        
+        match row {
+            Some(row) => {
+                let next_index: i64 = row.get(0);
+                let stats: String = row.get(1);
+                Ok(Some((next_index as usize, serde_json::from_str(&stats)?)))
             }
+            None => Ok(None),
+        }
+    }
+
+    /// Record that a cache warm has checked every item before `next_index`.
+    pub async fn save_warm_checkpoint(
+        &self,
+        warm_key: &str,
+        next_index: usize,
+        stats: &serde_json::Value,
+    ) -> Result<(), PipelineError> {
+        debug!("Saving cache warm checkpoint {} at item {}", warm_key, next_index);
+        
+        sqlx::query(
+            r#"
+            INSERT INTO cache_warm_checkpoints (warm_key, next_index, stats)
+            VALUES (?, ?, ?)
+            ON CONFLICT(warm_key) DO UPDATE SET
+                next_index = excluded.next_index,
+                stats = excluded.stats,
+                updated_at = CURRENT_TIMESTAMP
+            "#
+        )
+        .bind(warm_key)
+        .bind(next_index as i64)
+        .bind(stats.to_string())
+        .execute(&self.pool)
+        .await?;
+        
+        Ok(())
+    }
+
+    /// Forget a cache warm's progress, e.g. once it has finished.
+    pub async fn clear_warm_checkpoint(&self, warm_key: &str) -> Result<bool, PipelineError> {
+        let result = sqlx::query("DELETE FROM cache_warm_checkpoints WHERE warm_key = ?")
+            .bind(warm_key)
+            .execute(&self.pool)
+            .await?;
+        
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Stream every Stage 1 and Stage 2 entry to `sink`, one row at a time.
+    ///
+    /// Rows are read from a cursor rather than loaded up front, so backing up
+    /// a large cache doesn't hold it all in memory. Returns the entry count.
+    pub async fn export_all(
+        &self,
+        sink: &mut (dyn FnMut(CacheEntry) -> Result<(), PipelineError> + Send),
+    ) -> Result<usize, PipelineError> {
+        debug!("Exporting all cache entries");
+        let mut count = 0;
+        
+        // Read up front rather than joined, so the entry mappers stay shared
+        let vocabulary: HashMap<i64, CacheEntryVocabulary> = sqlx::query(
+            r#"
+            SELECT id, korean, english, category FROM vocabulary_items
+            WHERE id IN (SELECT vocabulary_id FROM stage1_cache UNION SELECT vocabulary_id FROM stage2_cache)
+            "#
+        )
+        .fetch_all(&self.pool)
+        .await?
+        .into_iter()
+        .map(|row| (row.get(0), CacheEntryVocabulary {
+            korean: row.get(1),
+            english: row.get(2),
+            category: row.get(3),
+        }))
+        .collect();
+        let with_vocabulary = |mut entry: CacheEntry| {
+            entry.vocabulary = vocabulary.get(&entry.vocabulary_id).cloned();
+            entry
         };
         
-        info!("Cleared {} cache entries", count);
+        let mut rows = sqlx::query_as::<_, CacheRow>(
+            r#"
+            SELECT id, vocabulary_id, cache_key, request_hash, response_json, 
+                   token_count, model_used, created_at, accessed_at, access_count, namespace
+            FROM stage1_cache ORDER BY id
+            "#
+        )
+        .fetch(&self.pool);
+        
+        while let Some(row) = rows.try_next().await? {
+            sink(with_vocabulary(Self::stage1_entry(row)?))?;
+            count += 1;
+        }
+        drop(rows);
+        
+        let mut rows = sqlx::query(
+            r#"
+            SELECT id, vocabulary_id, stage1_cache_key, cache_key, request_hash, 
+                   response_json, tsv_output, token_count, model_used, 
+                   created_at, accessed_at, access_count, namespace
+            FROM stage2_cache ORDER BY id
+            "#
+        )
+        .fetch(&self.pool);
+        
+        while let Some(row) = rows.try_next().await? {
+            sink(with_vocabulary(Self::stage2_entry(&row)?))?;
+            count += 1;
+        }
+        
+        info!("Exported {} cache entries", count);
         Ok(count)
     }
 
+    /// Entries created at least `min_age` ago and read no more than
+    /// `max_access_count` times.
+    ///
+    /// Age is measured from `created_at`, not `accessed_at`: a new entry is
+    /// never cold, however rarely it has been read.
+    pub async fn find_cold_entries(
+        &self,
+        min_age: Duration,
+        max_access_count: i32,
+    ) -> Result<Vec<CacheEntry>, PipelineError> {
+        debug!("Finding cache entries older than {} with at most {} accesses", min_age, max_access_count);
+        
+        let cutoff = Self::age_modifier(min_age);
+        
+        let stage1_rows = sqlx::query_as::<_, CacheRow>(
+            r#"
+            SELECT id, vocabulary_id, cache_key, request_hash, response_json, 
+                   token_count, model_used, created_at, accessed_at, access_count, namespace
+            FROM stage1_cache
+            WHERE julianday(created_at) <= julianday('now', ?) AND access_count <= ?
+            ORDER BY id
+            "#
+        )
+        .bind(&cutoff)
+        .bind(max_access_count)
+        .fetch_all(&self.pool)
+        .await?;
+        
+        let stage2_rows = sqlx::query(
+            r#"
+            SELECT id, vocabulary_id, stage1_cache_key, cache_key, request_hash, 
+                   response_json, tsv_output, token_count, model_used, 
+                   created_at, accessed_at, access_count, namespace
+            FROM stage2_cache
+            WHERE julianday(created_at) <= julianday('now', ?) AND access_count <= ?
+            ORDER BY id
+            "#
+        )
+        .bind(&cutoff)
+        .bind(max_access_count)
+        .fetch_all(&self.pool)
+        .await?;
+        
+        let mut entries = Vec::with_capacity(stage1_rows.len() + stage2_rows.len());
+        for row in stage1_rows {
+            entries.push(Self::stage1_entry(row)?);
+        }
+        for row in &stage2_rows {
+            entries.push(Self::stage2_entry(row)?);
+        }
+        
+        Ok(entries)
+    }
+
+    /// Delete the entries [`find_cold_entries`](Self::find_cold_entries)
+    /// would return. Returns how many were removed.
+    pub async fn prune_cold_entries(
+        &self,
+        min_age: Duration,
+        max_access_count: i32,
+    ) -> Result<u64, PipelineError> {
+        let cutoff = Self::age_modifier(min_age);
+        let mut tx = self.pool.begin().await?;
+        let mut removed = 0;
+        
+        for table in ["stage1_cache", "stage2_cache"] {
+            let result = sqlx::query(&format!(
+                "DELETE FROM {} WHERE julianday(created_at) <= julianday('now', ?) AND access_count <= ?",
+                table
+            ))
+            .bind(&cutoff)
+            .bind(max_access_count)
+            .execute(&mut *tx)
+            .await?;
+            removed += result.rows_affected();
+        }
+        
+        tx.commit().await?;
+        
+        info!("Pruned {} cold cache entries", removed);
+        Ok(removed)
+    }
+
+    /// SQLite date modifier for "`age` before now", e.g. `-86400 seconds`.
+    fn age_modifier(age: Duration) -> String {
+        format!("-{} seconds", age.num_seconds().max(0))
+    }
+
+    fn stage1_entry(row: CacheRow) -> Result<CacheEntry, PipelineError> {
+        Ok(CacheEntry {
+            id: Some(row.id),
+            cache_key: row.cache_key,
+            cache_type: CacheType::Stage1,
+            vocabulary_id: row.vocabulary_id,
+            request_hash: row.request_hash,
+            response_json: serde_json::from_str(&row.response_json)?,
+            token_count: row.token_count,
+            model_used: row.model_used,
+            created_at: row.created_at,
+            accessed_at: row.accessed_at,
+            access_count: row.access_count,
+            stage1_cache_key: None,
+            tsv_output: None,
+            namespace: row.namespace,
+            vocabulary: None,
+        })
+    }
+
+    /// The [`Stage2Result`] in a row with the columns `get_stage2_cache`
+    /// selects. Entries cached with the older `flashcard_content` shape are
+    /// converted.
+    fn stage2_result(row: &sqlx::sqlite::SqliteRow) -> Result<Stage2Result, PipelineError> {
+        let cache_key: String = row.get(3);
+        let response_json: String = row.get(5);
+        let response_data: serde_json::Value = serde_json::from_str(&response_json)?;
+        let context = format!("cached entry {}", cache_key);
+        
+        let card = match response_data.get("flashcard_content") {
+            Some(legacy) => {
+                let legacy: LegacyCard = crate::json::from_value(legacy.clone(), &context)?;
+                let (front, back, card_type) = legacy.into_sides();
+                StoredCard { front, back, card_type, learning_order: None, related_cards: Vec::new() }
+            }
+            None => crate::json::from_value(response_data.clone(), &context)?,
+        };
+        
+        Ok(Stage2Result {
+            vocabulary_id: row.get(1),
+            stage1_cache_key: row.get(2),
+            request_id: response_data.get("request_id")
+                .and_then(|v| v.as_str())
+                .unwrap_or("cached")
+                .to_string(),
+            cache_key,
+            front: card.front,
+            back: card.back,
+            card_type: card.card_type,
+            learning_order: card.learning_order,
+            related_cards: card.related_cards,
+            tsv_output: row.get(6),
+            created_at: row.get::<DateTime<Utc>, _>(9),
+        })
+    }
+
+    /// Map a `stage2_cache` row selected in the column order `export_all` uses.
+    fn stage2_entry(row: &sqlx::sqlite::SqliteRow) -> Result<CacheEntry, PipelineError> {
+        Ok(CacheEntry {
+            id: Some(row.get(0)),
+            cache_key: row.get(3),
+            cache_type: CacheType::Stage2,
+            vocabulary_id: row.get(1),
+            request_hash: row.get(4),
+            response_json: serde_json::from_str(&row.get::<String, _>(5))?,
+            token_count: row.get(7),
+            model_used: row.get(8),
+            created_at: row.get(9),
+            accessed_at: row.get(10),
+            access_count: row.get(11),
+            stage1_cache_key: Some(row.get(2)),
+            tsv_output: Some(row.get(6)),
+            namespace: row.get(12),
+            vocabulary: None,
+        })
+    }
+
+    /// Insert entries from a backup in one transaction.
+    ///
+    /// Existing cache keys are left alone unless `overwrite` is set. An
+    /// entry's `vocabulary_id` refers to the source database, so each entry
+    /// is linked to the row for its [`CacheEntry::vocabulary`] here instead,
+    /// which is created if this database lacks the word. Entries that don't
+    /// say which word they belong to, as in backups from before it was
+    /// exported, are skipped.
+    pub async fn import_all(
+        &self,
+        entries: &[CacheEntry],
+        overwrite: bool,
+    ) -> Result<CacheImportStats, PipelineError> {
+        debug!("Importing {} cache entries (overwrite: {})", entries.len(), overwrite);
+        
+        let mut conn = self.pool.acquire().await?;
+        let stats = Self::import_entries(&mut conn, entries, overwrite).await?;
+        
+        info!("Imported {} cache entries, skipped {}", stats.imported, stats.skipped);
+        Ok(stats)
+    }
+    
+    /// Id of the vocabulary row for `vocabulary`, inserting a bare one if
+    /// there is none
+    async fn local_vocabulary_id(
+        conn: &mut sqlx::SqliteConnection,
+        vocabulary: &CacheEntryVocabulary,
+    ) -> Result<i64, PipelineError> {
+        let item = VocabularyItem::new(
+            vocabulary.korean.clone(),
+            vocabulary.english.clone(),
+            vocabulary.category.clone(),
+        );
+        let id = sqlx::query_scalar(
+            r#"
+            INSERT INTO vocabulary_items (korean, english, category, difficulty_level, source)
+            VALUES (?, ?, ?, ?, 'cache_import')
+            ON CONFLICT(korean, english, category) DO UPDATE SET korean = excluded.korean
+            RETURNING id
+            "#
+        )
+        .bind(&item.korean)
+        .bind(&item.english)
+        .bind(&item.category)
+        .bind(format!("{:?}", item.difficulty_level).to_lowercase())
+        .fetch_one(conn)
+        .await?;
+        Ok(id)
+    }
+
+    async fn import_entries(
+        conn: &mut sqlx::SqliteConnection,
+        entries: &[CacheEntry],
+        overwrite: bool,
+    ) -> Result<CacheImportStats, PipelineError> {
+        use sqlx::Connection;
+        
+        let conflict = if overwrite {
+            "DO UPDATE SET vocabulary_id = excluded.vocabulary_id, request_hash = excluded.request_hash, \
+             response_json = excluded.response_json, token_count = excluded.token_count, \
+             model_used = excluded.model_used, created_at = excluded.created_at, \
+             namespace = excluded.namespace"
+        } else {
+            "DO NOTHING"
+        };
+        
+        let mut stats = CacheImportStats::default();
+        let mut tx = conn.begin().await?;
+        let mut vocabulary_ids: HashMap<&CacheEntryVocabulary, i64> = HashMap::new();
+        
+        for entry in entries {
+            let Some(vocabulary) = &entry.vocabulary else {
+                warn!("Skipping cache entry {}: the backup doesn't say which word it belongs to", entry.cache_key);
+                stats.skipped += 1;
+                continue;
+            };
+            let vocabulary_id = match vocabulary_ids.get(vocabulary) {
+                Some(&id) => id,
+                None => {
+                    let id = Self::local_vocabulary_id(&mut tx, vocabulary).await?;
+                    vocabulary_ids.insert(vocabulary, id);
+                    id
+                }
+            };
+            
+            let result = match entry.cache_type {
+                CacheType::Stage1 => {
+                    sqlx::query(&format!(
+                        r#"
+                        INSERT INTO stage1_cache 
+                        (vocabulary_id, cache_key, request_hash, response_json, token_count, 
+                         model_used, created_at, namespace)
+                        VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+                        ON CONFLICT(cache_key) {}
+                        "#,
+                        conflict
+                    ))
+                    .bind(vocabulary_id)
+                    .bind(&entry.cache_key)
+                    .bind(&entry.request_hash)
+                    .bind(entry.response_json.to_string())
+                    .bind(entry.token_count)
+                    .bind(&entry.model_used)
+                    .bind(entry.created_at)
+                    .bind(&entry.namespace)
+                    .execute(&mut *tx)
+                    .await?
+                }
+                CacheType::Stage2 => {
+                    let conflict = if overwrite {
+                        format!("{}, stage1_cache_key = excluded.stage1_cache_key, tsv_output = excluded.tsv_output", conflict)
+                    } else {
+                        conflict.to_string()
+                    };
+                    
+                    sqlx::query(&format!(
+                        r#"
+                        INSERT INTO stage2_cache 
+                        (vocabulary_id, stage1_cache_key, cache_key, request_hash, 
+                         response_json, tsv_output, token_count, model_used, created_at, namespace)
+                        VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+                        ON CONFLICT(cache_key) {}
+                        "#,
+                        conflict
+                    ))
+                    .bind(vocabulary_id)
+                    .bind(entry.stage1_cache_key.as_deref().unwrap_or_default())
+                    .bind(&entry.cache_key)
+                    .bind(&entry.request_hash)
+                    .bind(entry.response_json.to_string())
+                    .bind(entry.tsv_output.as_deref().unwrap_or_default())
+                    .bind(entry.token_count)
+                    .bind(&entry.model_used)
+                    .bind(entry.created_at)
+                    .bind(&entry.namespace)
+                    .execute(&mut *tx)
+                    .await?
+                }
+            };
+            
+            if result.rows_affected() > 0 {
+                stats.imported += 1;
+            } else {
+                stats.skipped += 1;
+            }
+        }
+        
+        tx.commit().await?;
+        Ok(stats)
+    }
+
+    /// Rewrite every cache key under `normalization`, in one transaction.
+    ///
+    /// Keys are recomputed from the vocabulary row each entry points at, so
+    /// entries whose row is gone are counted as unmigratable. An entry whose
+    /// new key is already taken keeps its old key. Stage 2 rows follow their
+    /// Stage 1 entry's new key. With `dry_run` the transaction is rolled back.
+    pub async fn migrate_keys(
+        &self,
+        normalization: KeyNormalization,
+        dry_run: bool,
+    ) -> Result<CacheMigrationStats, PipelineError> {
+        use sqlx::Connection;
+        
+        debug!("Migrating cache keys to {:?} (dry run: {})", normalization, dry_run);
+        
+        let mut conn = self.pool.acquire().await?;
+        let mut tx = conn.begin().await?;
+        let mut stats = CacheMigrationStats::default();
+        
+        let stage1_rows = sqlx::query(
+            r#"
+            SELECT s.id, s.cache_key, v.korean, v.english, v.category, v.hanja, v.example_sentence,
+                   s.namespace
+            FROM stage1_cache s
+            LEFT JOIN vocabulary_items v ON v.id = s.vocabulary_id
+            ORDER BY s.id
+            "#
+        )
+        .fetch_all(&mut *tx)
+        .await?;
+        
+        for row in stage1_rows {
+            let id: i64 = row.get(0);
+            let old_key: String = row.get(1);
+            let Some(item) = Self::item_from_join(&row, 2) else {
+                stats.unmigratable += 1;
+                continue;
+            };
+            
+            let namespace: String = row.get(7);
+            let new_key = Stage1Result::generate_cache_key_in(&item, normalization, &namespace);
+            if !Self::rekey(&mut tx, "stage1_cache", id, &old_key, &new_key, &mut stats).await? {
+                continue;
+            }
+            
+            sqlx::query("UPDATE stage2_cache SET stage1_cache_key = ? WHERE stage1_cache_key = ?")
+                .bind(&new_key)
+                .bind(&old_key)
+                .execute(&mut *tx)
+                .await?;
+        }
+        
+        let stage2_rows = sqlx::query(
+            r#"
+            SELECT s.id, s.cache_key, s.stage1_cache_key,
+                   v.korean, v.english, v.category, v.hanja, v.example_sentence
+            FROM stage2_cache s
+            LEFT JOIN vocabulary_items v ON v.id = s.vocabulary_id
+            ORDER BY s.id
+            "#
+        )
+        .fetch_all(&mut *tx)
+        .await?;
+        
+        for row in stage2_rows {
+            let id: i64 = row.get(0);
+            let old_key: String = row.get(1);
+            let stage1_key: String = row.get(2);
+            let Some(item) = Self::item_from_join(&row, 3) else {
+                stats.unmigratable += 1;
+                continue;
+            };
+            
+            let new_key = Stage2Result::generate_cache_key_for_mode(
+                &item,
+                &stage1_key,
+                normalization,
+                Stage2Mode::of_cache_key(&old_key),
+            );
+            Self::rekey(&mut tx, "stage2_cache", id, &old_key, &new_key, &mut stats).await?;
+        }
+        
+        if dry_run {
+            tx.rollback().await?;
+        } else {
+            tx.commit().await?;
+        }
+        
+        info!(
+            "Cache key migration: {} migrated, {} unchanged, {} unmigratable, {} collided",
+            stats.migrated, stats.unchanged, stats.unmigratable, stats.collided
+        );
+        Ok(stats)
+    }
+
+    /// The key-relevant fields of a joined vocabulary row starting at column
+    /// `offset`, or `None` if the join found no row.
+    fn item_from_join(row: &sqlx::sqlite::SqliteRow, offset: usize) -> Option<VocabularyItem> {
+        let korean: Option<String> = row.get(offset);
+        let mut item = VocabularyItem::new(korean?, row.get(offset + 1), row.get(offset + 2));
+        item.hanja = row.get(offset + 3);
+        item.example_sentence = row.get(offset + 4);
+        Some(item)
+    }
+
+    /// Move one entry to `new_key`, tallying the outcome. Returns whether the
+    /// key changed.
+    async fn rekey(
+        tx: &mut sqlx::Transaction<'_, sqlx::Sqlite>,
+        table: &str,
+        id: i64,
+        old_key: &str,
+        new_key: &str,
+        stats: &mut CacheMigrationStats,
+    ) -> Result<bool, PipelineError> {
+        if new_key == old_key {
+            stats.unchanged += 1;
+            return Ok(false);
+        }
+        
+        let taken = sqlx::query_scalar::<_, i64>(
+            &format!("SELECT COUNT(*) FROM {} WHERE cache_key = ?", table)
+        )
+        .bind(new_key)
+        .fetch_one(&mut **tx)
+        .await?;
+        
+        if taken > 0 {
+            stats.collided += 1;
+            return Ok(false);
+        }
+        
+        sqlx::query(&format!("UPDATE {} SET cache_key = ? WHERE id = ?", table))
+            .bind(new_key)
+            .bind(id)
+            .execute(&mut **tx)
+            .await?;
+        
+        stats.migrated += 1;
+        Ok(true)
+    }
+
     async fn update_cache_access(&self, table: &str, id: i64) -> Result<(), PipelineError> {
         sqlx::query(&format!(
             "UPDATE {} SET access_count = access_count + 1 WHERE id = ?",
@@ -351,7 +1274,9 @@ mod tests {
     use super::*;
     use crate::models::{VocabularyItem, SemanticAnalysis, FrequencyLevel, FormalityLevel};
     
-    async fn setup_test_db() -> DatabasePool {
+    /// The temp file is returned so the database outlives the pool's
+    /// first connection.
+    async fn setup_test_db() -> (DatabasePool, tempfile::NamedTempFile) {
         use tempfile::NamedTempFile;
         
         let temp_file = NamedTempFile::new().unwrap();
@@ -360,13 +1285,40 @@ mod tests {
         let pool = crate::database::create_pool(db_path).await.unwrap();
         crate::database::migrations::run_migrations(&pool).await.unwrap();
         
-        pool
+        (pool, temp_file)
     }
     
     #[tokio::test]
-    async fn test_cache_operations() {
-        let pool = setup_test_db().await;
+    async fn test_stats_snapshots_read_back_in_order() {
+        let (pool, _db_file) = setup_test_db().await;
         let repo = CacheRepository::new(pool);
+        let started = Utc::now() - Duration::seconds(1);
+        
+        let first = repo.snapshot_stats().await.unwrap();
+        repo.increment_cache_metrics(CacheType::Stage1, true, 250).await.unwrap();
+        let second = repo.snapshot_stats().await.unwrap();
+        
+        let history = repo.stats_history(started).await.unwrap();
+        assert_eq!(history.len(), 2);
+        assert_eq!(history[0].recorded_at, first.recorded_at);
+        assert_eq!(history[1].recorded_at, second.recorded_at);
+        assert_eq!(history[0].stats.total_tokens_saved, 0);
+        assert_eq!(history[1].stats.total_tokens_saved, 250);
+        assert_eq!(history[1].stats.total_hits, 1);
+        assert!(history[1].stats.estimated_cost_saved > 0.0);
+        
+        // Older snapshots fall outside the window
+        assert!(repo.stats_history(Utc::now() + Duration::seconds(1)).await.unwrap().is_empty());
+    }
+    
+    #[tokio::test]
+    async fn test_cache_operations() {
+        use crate::database::repositories::VocabularyRepository;
+        
+        let (pool, _db_file) = setup_test_db().await;
+        let repo = CacheRepository::new(pool.clone());
+        let item = VocabularyItem::new("시험".to_string(), "test".to_string(), "school".to_string());
+        let vocabulary_id = VocabularyRepository::new(pool).create(&item).await.unwrap();
         
         // Test cache miss
         let result = repo.get_stage1_cache("test_key").await.unwrap();
@@ -374,7 +1326,7 @@ mod tests {
         
         // Create and save a Stage1Result
         let stage1_result = Stage1Result {
-            vocabulary_id: 1,
+            vocabulary_id,
             request_id: "test_request".to_string(),
             cache_key: "test_key".to_string(),
             semantic_analysis: SemanticAnalysis {
@@ -402,7 +1354,440 @@ mod tests {
         assert!(cached.is_some());
         
         let cached = cached.unwrap();
-        assert_eq!(cached.vocabulary_id, 1);
+        assert_eq!(cached.vocabulary_id, vocabulary_id);
         assert_eq!(cached.cache_key, "test_key");
+        
+        // Inspecting an entry doesn't count as reading it
+        let entry = repo.get_cache_entry(CacheType::Stage1, "test_key").await.unwrap().unwrap();
+        assert_eq!(entry.model_used, "claude-3-sonnet");
+        assert_eq!(entry.token_count, 100);
+        let again = repo.get_cache_entry(CacheType::Stage1, "test_key").await.unwrap().unwrap();
+        assert_eq!(again.access_count, entry.access_count);
+        assert!(repo.get_cache_entry(CacheType::Stage2, "test_key").await.unwrap().is_none());
     }
-}
\ No newline at end of file
+    
+    #[tokio::test]
+    async fn test_resaving_key_updates_entry() {
+        use crate::database::repositories::VocabularyRepository;
+        
+        let (pool, _db_file) = setup_test_db().await;
+        let repo = CacheRepository::new(pool.clone());
+        let item = VocabularyItem::new("시험".to_string(), "test".to_string(), "school".to_string());
+        let vocabulary_id = VocabularyRepository::new(pool.clone()).create(&item).await.unwrap();
+        let stage1 = |meaning: &str| Stage1Result {
+            vocabulary_id,
+            request_id: "resave".to_string(),
+            cache_key: "resave_key".to_string(),
+            semantic_analysis: SemanticAnalysis {
+                primary_meaning: meaning.to_string(),
+                alternative_meanings: vec![],
+                connotations: vec![],
+                register: "neutral".to_string(),
+                usage_contexts: vec![],
+                cultural_notes: None,
+                frequency: FrequencyLevel::Common,
+                formality: FormalityLevel::Neutral,
+            },
+            created_at: Utc::now(),
+        };
+        
+        repo.save_stage1_cache(&stage1("Exam"), "old_hash".to_string(), 100, "claude-3-haiku".to_string())
+            .await
+            .unwrap();
+        repo.get_stage1_cache("resave_key").await.unwrap();
+        repo.save_stage1_cache(&stage1("Test"), "new_hash".to_string(), 150, "claude-3-sonnet".to_string())
+            .await
+            .unwrap();
+        
+        let cached = repo.get_stage1_cache("resave_key").await.unwrap().unwrap();
+        assert_eq!(cached.semantic_analysis.primary_meaning, "Test");
+        let (count, token_count, model_used, access_count): (i64, i32, String, i32) = sqlx::query_as(
+            "SELECT COUNT(*), token_count, model_used, access_count FROM stage1_cache WHERE cache_key = 'resave_key'"
+        )
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        assert_eq!((count, token_count, model_used.as_str()), (1, 150, "claude-3-sonnet"));
+        // Reset by the re-save, then bumped by the read above
+        assert_eq!(access_count, 2);
+        assert_eq!(
+            repo.get_request_hash(CacheType::Stage1, "resave_key").await.unwrap().as_deref(),
+            Some("new_hash")
+        );
+    }
+    
+    #[tokio::test]
+    async fn test_stage2_results_for_batch() {
+        use crate::database::repositories::{QueueRepository, VocabularyRepository};
+        use crate::models::{CardType, FlashcardContent, ProcessingStatus};
+        
+        let (pool, _db_file) = setup_test_db().await;
+        let repo = CacheRepository::new(pool.clone());
+        let queue = QueueRepository::new(pool.clone());
+        let items: Vec<VocabularyItem> = ["학교", "바다", "사과"].iter()
+            .map(|korean| VocabularyItem::new(korean.to_string(), "word".to_string(), "test".to_string()))
+            .collect();
+        let ids = VocabularyRepository::new(pool).create_many(&items).await.unwrap();
+        
+        let batch_id = BatchId::new("batch-reexport");
+        queue.enqueue_batch(ids.clone(), &batch_id, 3, None).await.unwrap();
+        for status in [ProcessingStatus::Completed, ProcessingStatus::Completed, ProcessingStatus::Failed] {
+            let queued = queue.get_next_pending(Some(&batch_id)).await.unwrap().unwrap();
+            queue.update_status(queued.id.unwrap(), status, None).await.unwrap();
+        }
+        
+        let card = |vocabulary_id: i64, cache_key: &str, front: &str| Stage2Result {
+            vocabulary_id,
+            stage1_cache_key: format!("stage1-{}", vocabulary_id),
+            request_id: "test".to_string(),
+            cache_key: cache_key.to_string(),
+            front: FlashcardContent::new(front),
+            back: FlashcardContent::new("word"),
+            card_type: CardType::Standard,
+            learning_order: None,
+            related_cards: vec![],
+            tsv_output: format!("{}\tword", front),
+            created_at: Utc::now(),
+        };
+        let save = |result: Stage2Result| {
+            let repo = &repo;
+            async move {
+                repo.save_stage2_cache(&result, "hash".to_string(), 10, "test-model".to_string()).await.unwrap();
+            }
+        };
+        save(card(ids[0], "old-key", "학교 (old)")).await;
+        save(card(ids[0], "new-key", "학교")).await;
+        save(card(ids[1], "sea-key", "바다")).await;
+        // Cached, but its queue item failed
+        save(card(ids[2], "apple-key", "사과")).await;
+        
+        let results = repo.get_stage2_results_for_batch(&batch_id).await.unwrap();
+        let fronts: Vec<&str> = results.iter()
+            .map(|(_, result)| result.front.primary_field.as_str())
+            .collect();
+        assert_eq!(fronts, ["학교", "바다"]);
+        assert_eq!(results[0].0.id, Some(ids[0]));
+        assert_eq!(results[1].0.korean, "바다");
+        
+        assert!(repo.get_stage2_results_for_batch_in(&batch_id, "other").await.unwrap().is_empty());
+        
+        // A batch that recorded its keys reads its own cards, not the newest
+        let older = BatchId::new("batch-older");
+        queue.enqueue_batch(ids[..2].to_vec(), &older, 3, None).await.unwrap();
+        let queued = queue.get_incomplete_items(&older).await.unwrap();
+        let queue_ids: Vec<i64> = queued.iter().filter_map(|item| item.id).collect();
+        queue.update_status_batch(&[
+            (queue_ids[0], ProcessingStatus::Completed),
+            (queue_ids[1], ProcessingStatus::Completed),
+        ]).await.unwrap();
+        let recorded = queue.record_cache_keys(&[
+            (queue_ids[0], "old-key".to_string()),
+            (queue_ids[1], "sea-key".to_string()),
+        ]).await.unwrap();
+        assert_eq!(recorded, 2);
+        
+        let results = repo.get_stage2_results_for_batch(&older).await.unwrap();
+        let fronts: Vec<&str> = results.iter()
+            .map(|(_, result)| result.front.primary_field.as_str())
+            .collect();
+        assert_eq!(fronts, ["학교 (old)", "바다"]);
+    }
+    
+    #[tokio::test]
+    async fn test_legacy_stage2_entry_loads() {
+        use crate::database::repositories::VocabularyRepository;
+        use crate::models::{CardType, FlashcardContent};
+        
+        let (pool, _db_file) = setup_test_db().await;
+        let repo = CacheRepository::new(pool.clone());
+        let item = VocabularyItem::new("학교".to_string(), "school".to_string(), "test".to_string());
+        let vocabulary_id = VocabularyRepository::new(pool.clone()).create(&item).await.unwrap();
+        
+        let result = Stage2Result {
+            vocabulary_id,
+            stage1_cache_key: "stage1-legacy".to_string(),
+            request_id: "legacy".to_string(),
+            cache_key: "legacy-key".to_string(),
+            front: FlashcardContent::new("학교"),
+            back: FlashcardContent::new("school"),
+            card_type: CardType::Standard,
+            learning_order: None,
+            related_cards: vec![],
+            tsv_output: "학교\tschool".to_string(),
+            created_at: Utc::now(),
+        };
+        repo.save_stage2_cache(&result, "hash".to_string(), 10, "test-model".to_string()).await.unwrap();
+        
+        // Rewrite the entry as it was stored before FlashcardContent
+        let legacy = serde_json::json!({
+            "request_id": "legacy",
+            "flashcard_content": {
+                "front": {
+                    "primary_content": "학교",
+                    "secondary_content": null,
+                    "example": null,
+                    "pronunciation": null,
+                    "notes": null,
+                    "media_references": []
+                },
+                "back": {
+                    "primary_content": "school",
+                    "secondary_content": null,
+                    "example": "학교에 가요.",
+                    "pronunciation": null,
+                    "notes": null,
+                    "media_references": []
+                },
+                "tags": ["places"],
+                "deck_name": "Korean",
+                "card_type": "basic"
+            }
+        });
+        sqlx::query("UPDATE stage2_cache SET response_json = ? WHERE cache_key = ?")
+            .bind(legacy.to_string())
+            .bind("legacy-key")
+            .execute(&pool)
+            .await
+            .unwrap();
+        
+        let loaded = repo.get_stage2_cache("legacy-key").await.unwrap().unwrap();
+        assert_eq!(loaded.request_id, "legacy");
+        assert_eq!(loaded.card_type, CardType::Standard);
+        assert_eq!(loaded.front.thematic_tags, vec!["places"]);
+        assert_eq!(loaded.back.example_sentence.as_deref(), Some("학교에 가요."));
+        assert_eq!(loaded.learning_order, None);
+    }
+    
+    #[tokio::test]
+    async fn test_export_import_round_trip() {
+        use crate::database::repositories::VocabularyRepository;
+        use tempfile::NamedTempFile;
+        
+        // Keep both files alive: export streams on a fresh pool connection
+        let source_file = NamedTempFile::new().unwrap();
+        let target_file = NamedTempFile::new().unwrap();
+        let open = |file: &NamedTempFile| {
+            let path = file.path().to_str().unwrap().to_string();
+            async move {
+                let pool = crate::database::create_pool(&path).await.unwrap();
+                crate::database::migrations::run_migrations(&pool).await.unwrap();
+                pool
+            }
+        };
+        
+        let source_pool = open(&source_file).await;
+        let vocab_id = VocabularyRepository::new(source_pool.clone())
+            .create(&VocabularyItem::new("한국".to_string(), "Korea".to_string(), "places".to_string()))
+            .await
+            .unwrap();
+        
+        let source = CacheRepository::new(source_pool);
+        let stage1_result = Stage1Result {
+            vocabulary_id: vocab_id,
+            request_id: "backup".to_string(),
+            cache_key: "backup_key".to_string(),
+            semantic_analysis: SemanticAnalysis {
+                primary_meaning: "Korea".to_string(),
+                alternative_meanings: vec![],
+                connotations: vec![],
+                register: "neutral".to_string(),
+                usage_contexts: vec![],
+                cultural_notes: None,
+                frequency: FrequencyLevel::Common,
+                formality: FormalityLevel::Neutral,
+            },
+            created_at: Utc::now(),
+        };
+        source.save_stage1_cache(&stage1_result, "hash".to_string(), 50, "claude-3-sonnet".to_string())
+            .await
+            .unwrap();
+        
+        let mut entries = Vec::new();
+        let exported = source.export_all(&mut |entry| {
+            entries.push(entry);
+            Ok(())
+        }).await.unwrap();
+        assert_eq!(exported, 1);
+        
+        // Restore into a database that has never seen the vocabulary row
+        let target = CacheRepository::new(open(&target_file).await);
+        let stats = target.import_all(&entries, false).await.unwrap();
+        assert_eq!(stats.imported, 1);
+        assert_eq!(stats.skipped, 0);
+        
+        let restored = target.get_stage1_cache("backup_key").await.unwrap().unwrap();
+        assert_eq!(restored.semantic_analysis.primary_meaning, "Korea");
+        // Linked to the word's row here, created for the import
+        let korean: String = sqlx::query_scalar("SELECT korean FROM vocabulary_items WHERE id = ?")
+            .bind(restored.vocabulary_id)
+            .fetch_one(&target.pool)
+            .await
+            .unwrap();
+        assert_eq!(korean, "한국");
+        
+        // Re-importing leaves existing keys alone, and an entry that doesn't
+        // name its word can't be linked
+        let mut unlinked = entries[0].clone();
+        unlinked.cache_key = "unlinked_key".to_string();
+        unlinked.vocabulary = None;
+        entries.push(unlinked);
+        let stats = target.import_all(&entries, false).await.unwrap();
+        assert_eq!(stats.imported, 0);
+        assert_eq!(stats.skipped, 2);
+        assert!(target.get_stage1_cache("unlinked_key").await.unwrap().is_none());
+    }
+    
+    #[tokio::test]
+    async fn test_prune_cold_entries() {
+        use crate::database::repositories::VocabularyRepository;
+        use tempfile::NamedTempFile;
+        
+        let temp_file = NamedTempFile::new().unwrap();
+        let pool = crate::database::create_pool(temp_file.path().to_str().unwrap()).await.unwrap();
+        crate::database::migrations::run_migrations(&pool).await.unwrap();
+        
+        let vocabulary_id = VocabularyRepository::new(pool.clone())
+            .create(&VocabularyItem::new("학교".to_string(), "school".to_string(), "places".to_string()))
+            .await
+            .unwrap();
+        let repo = CacheRepository::new(pool.clone());
+        
+        // (key, days since created, access count)
+        let seeds = [("cold", 30, 1), ("hot", 30, 12), ("fresh", 0, 1)];
+        for (key, age_days, access_count) in seeds {
+            let result = Stage1Result {
+                vocabulary_id,
+                request_id: key.to_string(),
+                cache_key: key.to_string(),
+                semantic_analysis: SemanticAnalysis {
+                    primary_meaning: "school".to_string(),
+                    alternative_meanings: vec![],
+                    connotations: vec![],
+                    register: "neutral".to_string(),
+                    usage_contexts: vec![],
+                    cultural_notes: None,
+                    frequency: FrequencyLevel::Common,
+                    formality: FormalityLevel::Neutral,
+                },
+                created_at: Utc::now(),
+            };
+            repo.save_stage1_cache(&result, "hash".to_string(), 10, "claude-3-sonnet".to_string())
+                .await
+                .unwrap();
+            
+            sqlx::query(
+                "UPDATE stage1_cache SET created_at = datetime('now', ?), access_count = ? WHERE cache_key = ?"
+            )
+            .bind(format!("-{} days", age_days))
+            .bind(access_count)
+            .bind(key)
+            .execute(&pool)
+            .await
+            .unwrap();
+        }
+        
+        // The update trigger just touched accessed_at on every row; age must
+        // still come from created_at
+        let cold = repo.find_cold_entries(Duration::days(7), 1).await.unwrap();
+        let keys: Vec<&str> = cold.iter().map(|entry| entry.cache_key.as_str()).collect();
+        assert_eq!(keys, vec!["cold"]);
+        
+        let removed = repo.prune_cold_entries(Duration::days(7), 1).await.unwrap();
+        assert_eq!(removed, 1);
+        
+        let remaining: Vec<String> = sqlx::query_scalar("SELECT cache_key FROM stage1_cache ORDER BY id")
+            .fetch_all(&pool)
+            .await
+            .unwrap();
+        assert_eq!(remaining, vec!["hot".to_string(), "fresh".to_string()]);
+    }
+    
+    #[tokio::test]
+    async fn test_migrate_keys() {
+        use crate::database::repositories::VocabularyRepository;
+        use tempfile::NamedTempFile;
+        
+        let temp_file = NamedTempFile::new().unwrap();
+        let pool = crate::database::create_pool(temp_file.path().to_str().unwrap()).await.unwrap();
+        crate::database::migrations::run_migrations(&pool).await.unwrap();
+        
+        let vocab = VocabularyRepository::new(pool.clone());
+        let repo = CacheRepository::new(pool.clone());
+        let analysis = SemanticAnalysis {
+            primary_meaning: "apple".to_string(),
+            alternative_meanings: vec![],
+            connotations: vec![],
+            register: "neutral".to_string(),
+            usage_contexts: vec![],
+            cultural_notes: None,
+            frequency: FrequencyLevel::Common,
+            formality: FormalityLevel::Neutral,
+        };
+        
+        // Saved under byte-exact keys, before trimming was part of the scheme
+        let save = |item: VocabularyItem, normalization: KeyNormalization| {
+            let vocab = &vocab;
+            let repo = &repo;
+            let analysis = analysis.clone();
+            async move {
+                let vocabulary_id = vocab.create(&item).await.unwrap();
+                let cache_key = Stage1Result::generate_cache_key_with(&item, normalization);
+                let result = Stage1Result {
+                    vocabulary_id,
+                    request_id: "req".to_string(),
+                    cache_key: cache_key.clone(),
+                    semantic_analysis: analysis,
+                    created_at: Utc::now(),
+                };
+                repo.save_stage1_cache(&result, "hash".to_string(), 10, "claude-3-sonnet".to_string())
+                    .await
+                    .unwrap();
+                (item, vocabulary_id, cache_key)
+            }
+        };
+        
+        let apple = VocabularyItem::new("사과 ".to_string(), "apple".to_string(), "food".to_string());
+        let (apple, apple_id, old_key) = save(apple, KeyNormalization::None).await;
+        let pear = VocabularyItem::new("배".to_string(), "pear".to_string(), "food".to_string());
+        save(pear.clone(), KeyNormalization::NfcTrim).await;
+        let pear_padded = VocabularyItem::new("배 ".to_string(), "pear".to_string(), "food".to_string());
+        save(pear_padded, KeyNormalization::None).await;
+        
+        sqlx::query(
+            r#"
+            INSERT INTO stage2_cache 
+            (vocabulary_id, stage1_cache_key, cache_key, request_hash, response_json, tsv_output, model_used)
+            VALUES (?, ?, 'old_stage2', 'hash', '{}', '', 'claude-3-sonnet')
+            "#
+        )
+        .bind(apple_id)
+        .bind(&old_key)
+        .execute(&pool)
+        .await
+        .unwrap();
+        
+        let preview = repo.migrate_keys(KeyNormalization::NfcTrim, true).await.unwrap();
+        assert_eq!(preview.migrated, 2);
+        assert!(repo.get_stage1_cache(&old_key).await.unwrap().is_some());
+        
+        let stats = repo.migrate_keys(KeyNormalization::NfcTrim, false).await.unwrap();
+        assert_eq!(stats.migrated, 2);
+        assert_eq!(stats.unchanged, 1);
+        assert_eq!(stats.collided, 1);
+        assert_eq!(stats.unmigratable, 0);
+        
+        let new_key = Stage1Result::generate_cache_key_with(&apple, KeyNormalization::NfcTrim);
+        assert!(repo.get_stage1_cache(&old_key).await.unwrap().is_none());
+        assert!(repo.get_stage1_cache(&new_key).await.unwrap().is_some());
+        
+        let stage1_key: String = sqlx::query_scalar("SELECT stage1_cache_key FROM stage2_cache")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(stage1_key, new_key);
+        
+        // A second pass finds nothing left to move
+        let again = repo.migrate_keys(KeyNormalization::NfcTrim, false).await.unwrap();
+        assert_eq!(again.migrated, 0);
+    }
+}
@@ -0,0 +1,132 @@
+use sqlx::FromRow;
+use chrono::{DateTime, Utc};
+use tracing::debug;
+use crate::models::{CardState, KeyNormalization, PipelineError};
+use crate::database::DatabasePool;
+
+/// Suspension and leech marks of cards, by term, so re-exports keep them.
+/// Terms are stored NFC-normalized and trimmed, as cache keys hash them.
+pub struct CardStateRepository {
+    pool: DatabasePool,
+}
+
+#[derive(FromRow)]
+struct CardStateRow {
+    term: String,
+    suspended: bool,
+    leech: bool,
+    updated_at: DateTime<Utc>,
+}
+
+impl CardStateRepository {
+    pub fn new(pool: DatabasePool) -> Self {
+        Self { pool }
+    }
+
+    /// Mark the card of `term` suspended or not. `leech` sets the leech
+    /// mark too; `None` leaves it as it was.
+    pub async fn set_suspended(
+        &self,
+        term: &str,
+        suspended: bool,
+        leech: Option<bool>,
+    ) -> Result<CardState, PipelineError> {
+        let term = KeyNormalization::NfcTrim.apply(term);
+        if term.is_empty() {
+            return Err(PipelineError::Validation("Card state needs a term".to_string()));
+        }
+        debug!("Setting card state of {}: suspended {}, leech {:?}", term, suspended, leech);
+        
+        let row = sqlx::query_as::<_, CardStateRow>(
+            r#"
+            INSERT INTO card_state (term, suspended, leech)
+            VALUES (?1, ?2, COALESCE(?3, 0))
+            ON CONFLICT(term) DO UPDATE SET
+                suspended = excluded.suspended,
+                leech = COALESCE(?3, leech),
+                updated_at = CURRENT_TIMESTAMP
+            RETURNING term, suspended, leech, updated_at
+            "#
+        )
+        .bind(term.as_ref())
+        .bind(suspended)
+        .bind(leech)
+        .fetch_one(&self.pool)
+        .await?;
+        
+        Ok(Self::row_to_state(row))
+    }
+
+    /// The stored state of `term`'s card, if any was ever set.
+    pub async fn get(&self, term: &str) -> Result<Option<CardState>, PipelineError> {
+        let row = sqlx::query_as::<_, CardStateRow>(
+            "SELECT term, suspended, leech, updated_at FROM card_state WHERE term = ?"
+        )
+        .bind(KeyNormalization::NfcTrim.apply(term).as_ref())
+        .fetch_optional(&self.pool)
+        .await?;
+        
+        Ok(row.map(Self::row_to_state))
+    }
+
+    /// Every card that is suspended or a leech, by term.
+    pub async fn flagged(&self) -> Result<Vec<CardState>, PipelineError> {
+        let rows = sqlx::query_as::<_, CardStateRow>(
+            r#"
+            SELECT term, suspended, leech, updated_at
+            FROM card_state
+            WHERE suspended OR leech
+            ORDER BY term
+            "#
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        
+        Ok(rows.into_iter().map(Self::row_to_state).collect())
+    }
+
+    fn row_to_state(row: CardStateRow) -> CardState {
+        CardState {
+            term: row.term,
+            suspended: row.suspended,
+            leech: row.leech,
+            updated_at: row.updated_at,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[tokio::test]
+    async fn test_suspend_keeps_leech_unless_given() {
+        let temp_file = tempfile::NamedTempFile::new().unwrap();
+        let pool = crate::database::create_pool(temp_file.path().to_str().unwrap()).await.unwrap();
+        crate::database::migrations::run_migrations(&pool).await.unwrap();
+        let repo = CardStateRepository::new(pool);
+        
+        assert!(repo.get("학교").await.unwrap().is_none());
+        
+        let state = repo.set_suspended(" 학교 ", true, Some(true)).await.unwrap();
+        assert_eq!((state.term.as_str(), state.suspended, state.leech), ("학교", true, true));
+        // The same term in decomposed jamo finds the same state
+        assert!(repo.get("\u{1112}\u{1161}\u{11A8}\u{1100}\u{116D}").await.unwrap().is_some());
+        repo.set_suspended("바다", true, None).await.unwrap();
+        repo.set_suspended("사과", false, None).await.unwrap();
+        
+        // Unsuspending alone leaves the leech mark
+        let state = repo.set_suspended("학교", false, None).await.unwrap();
+        assert_eq!((state.suspended, state.leech), (false, true));
+        
+        let flagged: Vec<_> = repo.flagged().await.unwrap().into_iter()
+            .map(|state| (state.term, state.suspended, state.leech))
+            .collect();
+        assert_eq!(flagged, vec![
+            ("바다".to_string(), true, false),
+            ("학교".to_string(), false, true),
+        ]);
+        
+        assert!(repo.set_suspended("  ", true, None).await.is_err());
+    }
+}
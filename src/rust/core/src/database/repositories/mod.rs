@@ -1,7 +1,11 @@
 pub mod vocabulary;
 pub mod cache;
 pub mod queue;
+pub mod api_call_log;
+pub mod card_state;
 
 pub use vocabulary::VocabularyRepository;
 pub use cache::CacheRepository;
-pub use queue::QueueRepository;
\ No newline at end of file
+pub use queue::QueueRepository;
+pub use api_call_log::ApiCallLogRepository;
+pub use card_state::CardStateRepository;
\ No newline at end of file
@@ -1,9 +1,10 @@
-use sqlx::{FromRow, Row};
+use sqlx::{sqlite::SqliteRow, FromRow, Row};
 use chrono::{DateTime, Utc, Duration};
 use serde_json;
+use std::collections::BTreeSet;
 use tracing::{info, debug, warn};
 use crate::models::{
-    QueueItem, ProcessingStatus, ProcessingStage, BatchProgress, 
+    QueueItem, ProcessingStatus, ProcessingStage, BatchId, BatchProgress, BatchSummary,
     ProcessingCheckpoint, PipelineError
 };
 use crate::database::DatabasePool;
@@ -12,11 +13,15 @@ pub struct QueueRepository {
     pool: DatabasePool,
 }
 
+/// The columns [`QueueRow`] is read from
+const QUEUE_COLUMNS: &str = "id, vocabulary_id, batch_id, status, stage, retry_count, max_retries, \
+    error_message, created_at, updated_at, started_at, completed_at";
+
 #[derive(FromRow)]
 struct QueueRow {
     id: i64,
     vocabulary_id: i64,
-    batch_id: String,
+    batch_id: BatchId,
     status: String,
     stage: String,
     retry_count: i32,
@@ -33,8 +38,15 @@ impl QueueRepository {
         Self { pool }
     }
 
-    pub async fn enqueue_batch(&self, vocabulary_ids: Vec<i64>, batch_id: &str) -> Result<i64, PipelineError> {
-        debug!("Enqueueing batch {} with {} items", batch_id, vocabulary_ids.len());
+    pub async fn enqueue_batch(
+        &self,
+        vocabulary_ids: Vec<i64>,
+        batch_id: &BatchId,
+        max_retries: i32,
+        label: Option<&str>,
+    ) -> Result<i64, PipelineError> {
+        debug!("Enqueueing batch {} with {} items (max_retries: {}, label: {:?})", 
+               batch_id, vocabulary_ids.len(), max_retries, label);
         
         let mut tx = self.pool.begin().await?;
         let mut count = 0;
@@ -42,12 +54,13 @@ impl QueueRepository {
         // Create batch metadata
         sqlx::query(
             r#"
-            INSERT INTO batch_metadata (batch_id, total_items, status)
-            VALUES (?, ?, 'pending')
+            INSERT INTO batch_metadata (batch_id, total_items, status, label)
+            VALUES (?, ?, 'pending', ?)
             "#
         )
         .bind(batch_id)
         .bind(vocabulary_ids.len() as i32)
+        .bind(label)
         .execute(&mut *tx)
         .await?;
         
@@ -57,11 +70,12 @@ impl QueueRepository {
                 r#"
                 INSERT INTO processing_queue 
                 (vocabulary_id, batch_id, status, stage, retry_count, max_retries)
-                VALUES (?, ?, 'pending', 'stage1', 0, 3)
+                VALUES (?, ?, 'pending', 'stage1', 0, ?)
                 "#
             )
             .bind(vocab_id)
             .bind(batch_id)
+            .bind(max_retries)
             .execute(&mut *tx)
             .await?;
             
@@ -74,29 +88,24 @@ impl QueueRepository {
         Ok(count)
     }
 
-    pub async fn get_next_pending(&self, batch_id: Option<&str>) -> Result<Option<QueueItem>, PipelineError> {
+    pub async fn get_next_pending(&self, batch_id: Option<&BatchId>) -> Result<Option<QueueItem>, PipelineError> {
         debug!("Getting next pending item from queue");
         
-        let query = if let Some(batch_id) = batch_id {
-            sqlx::query_as::<_, QueueRow>(
-                r#"
-                SELECT * FROM processing_queue 
-                WHERE batch_id = ? AND status = 'pending'
-                ORDER BY created_at ASC
-                LIMIT 1
-                "#
-            )
-            .bind(batch_id)
-        } else {
-            sqlx::query_as::<_, QueueRow>(
-                r#"
-                SELECT * FROM processing_queue 
-                WHERE status = 'pending'
-                ORDER BY created_at ASC
-                LIMIT 1
-                "#
-            )
-        };
+        let filter = if batch_id.is_some() { "batch_id = ? AND " } else { "" };
+        let sql = format!(
+            r#"
+            SELECT {} FROM processing_queue 
+            WHERE {}status = 'pending'
+            ORDER BY created_at ASC
+            LIMIT 1
+            "#,
+            QUEUE_COLUMNS, filter
+        );
+        
+        let mut query = sqlx::query_as::<_, QueueRow>(&sql);
+        if let Some(batch_id) = batch_id {
+            query = query.bind(batch_id);
+        }
         
         let row = query.fetch_optional(&self.pool).await?;
         
@@ -106,6 +115,39 @@ impl QueueRepository {
         }
     }
 
+    /// Rows of `batch_id` still waiting to be processed, or cut off while in
+    /// progress, in the order they were queued.
+    pub async fn get_incomplete_items(&self, batch_id: &BatchId) -> Result<Vec<QueueItem>, PipelineError> {
+        debug!("Getting incomplete items of batch {}", batch_id);
+        
+        let rows = sqlx::query_as::<_, QueueRow>(&format!(
+            r#"
+            SELECT {} FROM processing_queue
+            WHERE batch_id = ? AND status IN ('pending', 'in_progress')
+            ORDER BY id
+            "#,
+            QUEUE_COLUMNS
+        ))
+        .bind(batch_id)
+        .fetch_all(&self.pool)
+        .await?;
+        
+        rows.into_iter()
+            .map(|row| self.row_to_item(row))
+            .collect()
+    }
+
+    /// Items waiting to be picked up, across all batches.
+    pub async fn count_pending(&self) -> Result<i64, PipelineError> {
+        let count = sqlx::query_scalar::<_, i64>(
+            "SELECT COUNT(*) FROM processing_queue WHERE status = 'pending'"
+        )
+        .fetch_one(&self.pool)
+        .await?;
+        
+        Ok(count)
+    }
+
     pub async fn update_status(
         &self, 
         item_id: i64, 
@@ -114,67 +156,88 @@ impl QueueRepository {
     ) -> Result<(), PipelineError> {
         debug!("Updating queue item {} status to {:?}", item_id, status);
         
-        let status_str = self.status_to_string(&status);
+        sqlx::query(&Self::status_update_sql(&status, true))
+            .bind(self.status_to_string(&status))
+            .bind(&error_message)
+            .bind(item_id)
+            .execute(&self.pool)
+            .await?;
         
-        let mut query = sqlx::query(
-            r#"
-            UPDATE processing_queue 
-            SET status = ?, error_message = ?, updated_at = CURRENT_TIMESTAMP
-            "#
-        );
+        // Update batch progress
+        if Self::is_terminal(&status) {
+            self.update_batch_progress(item_id).await?;
+        }
         
-        query = query.bind(&status_str).bind(&error_message);
+        Ok(())
+    }
+
+    /// Apply many status changes in one transaction, refreshing each touched
+    /// batch's progress once rather than once per item.
+    ///
+    /// Error messages are left as they are; use [`update_status`](Self::update_status)
+    /// to record one. Returns the number of rows updated.
+    pub async fn update_status_batch(
+        &self,
+        updates: &[(i64, ProcessingStatus)],
+    ) -> Result<u64, PipelineError> {
+        if updates.is_empty() {
+            return Ok(0);
+        }
+        debug!("Applying {} queue status updates", updates.len());
         
-        // Add additional fields based on status
-        match status {
-            ProcessingStatus::InProgress => {
-                query = sqlx::query(
-                    r#"
-                    UPDATE processing_queue 
-                    SET status = ?, error_message = ?, started_at = CURRENT_TIMESTAMP, 
-                        updated_at = CURRENT_TIMESTAMP
-                    WHERE id = ?
-                    "#
-                )
-                .bind(&status_str)
-                .bind(&error_message)
-                .bind(item_id);
-            }
-            ProcessingStatus::Completed => {
-                query = sqlx::query(
-                    r#"
-                    UPDATE processing_queue 
-                    SET status = ?, error_message = ?, completed_at = CURRENT_TIMESTAMP,
-                        updated_at = CURRENT_TIMESTAMP
-                    WHERE id = ?
-                    "#
-                )
-                .bind(&status_str)
-                .bind(&error_message)
-                .bind(item_id);
-            }
-            _ => {
-                query = sqlx::query(
-                    r#"
-                    UPDATE processing_queue 
-                    SET status = ?, error_message = ?, updated_at = CURRENT_TIMESTAMP
-                    WHERE id = ?
-                    "#
+        let mut tx = self.pool.begin().await?;
+        let mut updated = 0;
+        let mut finished_batches = BTreeSet::new();
+        
+        for (item_id, status) in updates {
+            updated += sqlx::query(&Self::status_update_sql(status, false))
+                .bind(self.status_to_string(status))
+                .bind(item_id)
+                .execute(&mut *tx)
+                .await?
+                .rows_affected();
+            
+            if Self::is_terminal(status) {
+                let batch_id: Option<BatchId> = sqlx::query_scalar(
+                    "SELECT batch_id FROM processing_queue WHERE id = ?"
                 )
-                .bind(&status_str)
-                .bind(&error_message)
-                .bind(item_id);
+                .bind(item_id)
+                .fetch_optional(&mut *tx)
+                .await?;
+                finished_batches.extend(batch_id);
             }
         }
         
-        query.execute(&self.pool).await?;
+        tx.commit().await?;
         
-        // Update batch progress
-        if matches!(status, ProcessingStatus::Completed | ProcessingStatus::Failed | ProcessingStatus::Quarantined) {
-            self.update_batch_progress(item_id).await?;
+        for batch_id in finished_batches {
+            self.refresh_batch_metadata(&batch_id).await?;
         }
         
-        Ok(())
+        Ok(updated)
+    }
+
+    /// Record the Stage 2 cache key each finished item's card was stored
+    /// under, in one transaction. Batches sharing a vocabulary item read
+    /// their own card by it rather than the item's newest one.
+    pub async fn record_cache_keys(&self, keys: &[(i64, String)]) -> Result<u64, PipelineError> {
+        if keys.is_empty() {
+            return Ok(0);
+        }
+        
+        let mut tx = self.pool.begin().await?;
+        let mut updated = 0;
+        for (item_id, cache_key) in keys {
+            updated += sqlx::query("UPDATE processing_queue SET stage2_cache_key = ? WHERE id = ?")
+                .bind(cache_key)
+                .bind(item_id)
+                .execute(&mut *tx)
+                .await?
+                .rows_affected();
+        }
+        tx.commit().await?;
+        
+        Ok(updated)
     }
 
     pub async fn complete_stage(&self, item_id: i64) -> Result<ProcessingStage, PipelineError> {
@@ -249,7 +312,9 @@ impl QueueRepository {
         
         let new_retry_count = retry_count + 1;
         
-        if new_retry_count >= max_retries {
+        // `max_retries` counts retries after the first attempt, as the
+        // pipeline's RetryPolicy does
+        if new_retry_count > max_retries {
             // Quarantine the item
             sqlx::query(
                 r#"
@@ -288,7 +353,64 @@ impl QueueRepository {
         }
     }
 
-    pub async fn get_batch_progress(&self, batch_id: &str) -> Result<BatchProgress, PipelineError> {
+    /// Most recent batches first, optionally only those with `label`.
+    pub async fn list_batches(
+        &self,
+        label: Option<&str>,
+        limit: i64,
+    ) -> Result<Vec<BatchSummary>, PipelineError> {
+        debug!("Listing batches (label: {:?}, limit: {})", label, limit);
+        
+        let rows = sqlx::query(
+            r#"
+            SELECT batch_id, label, total_items, completed_items, failed_items, 
+                   status, start_time, end_time
+            FROM batch_metadata
+            WHERE ?1 IS NULL OR label = ?1
+            ORDER BY start_time DESC, rowid DESC
+            LIMIT ?2
+            "#
+        )
+        .bind(label)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+        
+        Ok(rows.iter().map(Self::row_to_summary).collect())
+    }
+
+    /// Up to `limit` batches none of whose items has been picked up yet,
+    /// e.g. queued by another process, oldest first.
+    ///
+    /// An item counts as picked up once it has been in progress, so a
+    /// batch whose items are running elsewhere, or were cut off and put
+    /// back to pending, is left to that run or a resume.
+    pub async fn list_unstarted_batches(&self, limit: i64) -> Result<Vec<BatchSummary>, PipelineError> {
+        let rows = sqlx::query(
+            r#"
+            SELECT m.batch_id, m.label, m.total_items, m.completed_items, m.failed_items, 
+                   m.status, m.start_time, m.end_time
+            FROM batch_metadata m
+            WHERE EXISTS (
+                SELECT 1 FROM processing_queue q WHERE q.batch_id = m.batch_id
+            )
+            AND NOT EXISTS (
+                SELECT 1 FROM processing_queue q
+                WHERE q.batch_id = m.batch_id
+                  AND (q.status != 'pending' OR q.started_at IS NOT NULL)
+            )
+            ORDER BY m.start_time, m.rowid
+            LIMIT ?
+            "#
+        )
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await?;
+        
+        Ok(rows.iter().map(Self::row_to_summary).collect())
+    }
+
+    pub async fn get_batch_progress(&self, batch_id: &BatchId) -> Result<BatchProgress, PipelineError> {
         debug!("Getting progress for batch {}", batch_id);
         
         let metadata = sqlx::query(
@@ -324,11 +446,12 @@ impl QueueRepository {
         .await?;
         
         let mut progress = BatchProgress {
-            batch_id: batch_id.to_string(),
+            batch_id: batch_id.clone(),
             total_items,
             completed_items: 0,
             failed_items: 0,
             quarantined_items: 0,
+            skipped_items: 0,
             pending_items: 0,
             in_progress_items: 0,
             start_time,
@@ -344,6 +467,7 @@ impl QueueRepository {
                 "completed" => progress.completed_items = count,
                 "failed" => progress.failed_items = count,
                 "quarantined" => progress.quarantined_items = count,
+                "skipped" => progress.skipped_items = count,
                 "pending" => progress.pending_items = count,
                 "in_progress" => progress.in_progress_items = count,
                 _ => {}
@@ -363,7 +487,7 @@ impl QueueRepository {
 
     pub async fn save_checkpoint(
         &self,
-        batch_id: &str,
+        batch_id: &BatchId,
         last_processed_id: i64,
         stage: ProcessingStage,
         checkpoint_data: serde_json::Value,
@@ -396,7 +520,7 @@ impl QueueRepository {
 
     pub async fn get_latest_checkpoint(
         &self,
-        batch_id: &str,
+        batch_id: &BatchId,
     ) -> Result<Option<ProcessingCheckpoint>, PipelineError> {
         debug!("Getting latest checkpoint for batch {}", batch_id);
         
@@ -443,15 +567,18 @@ impl QueueRepository {
 
     async fn update_batch_progress(&self, item_id: i64) -> Result<(), PipelineError> {
         // Get batch_id for the item
-        let batch_id: String = sqlx::query_scalar(
+        let batch_id: BatchId = sqlx::query_scalar(
             "SELECT batch_id FROM processing_queue WHERE id = ?"
         )
         .bind(item_id)
         .fetch_one(&self.pool)
         .await?;
         
-        // Update batch metadata
-        let progress = self.get_batch_progress(&batch_id).await?;
+        self.refresh_batch_metadata(&batch_id).await
+    }
+
+    async fn refresh_batch_metadata(&self, batch_id: &BatchId) -> Result<(), PipelineError> {
+        let progress = self.get_batch_progress(batch_id).await?;
         
         let status = if progress.is_complete() {
             if progress.failed_items > 0 || progress.quarantined_items > 0 {
@@ -466,7 +593,7 @@ impl QueueRepository {
         sqlx::query(
             r#"
             UPDATE batch_metadata 
-            SET completed_items = ?, failed_items = ?, quarantined_items = ?, 
+            SET completed_items = ?, failed_items = ?, quarantined_items = ?, skipped_items = ?,
                 status = ?, end_time = CASE WHEN ? THEN CURRENT_TIMESTAMP ELSE end_time END
             WHERE batch_id = ?
             "#
@@ -474,9 +601,10 @@ impl QueueRepository {
         .bind(progress.completed_items)
         .bind(progress.failed_items)
         .bind(progress.quarantined_items)
+        .bind(progress.skipped_items)
         .bind(status)
         .bind(progress.is_complete())
-        .bind(&batch_id)
+        .bind(batch_id)
         .execute(&self.pool)
         .await?;
         
@@ -490,6 +618,7 @@ impl QueueRepository {
             "completed" => ProcessingStatus::Completed,
             "failed" => ProcessingStatus::Failed,
             "quarantined" => ProcessingStatus::Quarantined,
+            "skipped" => ProcessingStatus::Skipped,
             _ => return Err(PipelineError::Validation(
                 format!("Invalid status: {}", row.status)
             )),
@@ -520,6 +649,43 @@ impl QueueRepository {
         })
     }
 
+    /// A `batch_metadata` row selected in the column order `list_batches` uses
+    fn row_to_summary(row: &SqliteRow) -> BatchSummary {
+        BatchSummary {
+            batch_id: row.get(0),
+            label: row.get(1),
+            total_items: row.get(2),
+            completed_items: row.get(3),
+            failed_items: row.get(4),
+            status: row.get(5),
+            start_time: row.get(6),
+            end_time: row.get(7),
+        }
+    }
+
+    /// `UPDATE` for one item's status, binding status, the error message when
+    /// `with_error` is set, then the item id.
+    fn status_update_sql(status: &ProcessingStatus, with_error: bool) -> String {
+        let error = if with_error { "error_message = ?, " } else { "" };
+        let timestamp = match status {
+            ProcessingStatus::InProgress => "started_at = CURRENT_TIMESTAMP, ",
+            ProcessingStatus::Completed => "completed_at = CURRENT_TIMESTAMP, ",
+            _ => "",
+        };
+        format!(
+            "UPDATE processing_queue SET status = ?, {}{}updated_at = CURRENT_TIMESTAMP WHERE id = ?",
+            error, timestamp
+        )
+    }
+
+    /// Statuses that count toward a batch's progress
+    fn is_terminal(status: &ProcessingStatus) -> bool {
+        matches!(
+            status,
+            ProcessingStatus::Completed | ProcessingStatus::Failed | ProcessingStatus::Quarantined | ProcessingStatus::Skipped
+        )
+    }
+
     fn status_to_string(&self, status: &ProcessingStatus) -> String {
         match status {
             ProcessingStatus::Pending => "pending",
@@ -527,7 +693,270 @@ impl QueueRepository {
             ProcessingStatus::Completed => "completed",
             ProcessingStatus::Failed => "failed",
             ProcessingStatus::Quarantined => "quarantined",
+            ProcessingStatus::Skipped => "skipped",
         }
         .to_string()
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::VocabularyItem;
+    use crate::database::repositories::VocabularyRepository;
+    use tempfile::NamedTempFile;
+    
+    /// The temp file is returned so the database outlives the pool's
+    /// first connection.
+    async fn setup_test_db() -> (DatabasePool, NamedTempFile) {
+        let temp_file = NamedTempFile::new().unwrap();
+        let db_path = temp_file.path().to_str().unwrap();
+        
+        let pool = crate::database::create_pool(db_path).await.unwrap();
+        crate::database::migrations::run_migrations(&pool).await.unwrap();
+        
+        (pool, temp_file)
+    }
+    
+    async fn create_vocabulary(pool: &DatabasePool, count: usize) -> Vec<i64> {
+        let repo = VocabularyRepository::new(pool.clone());
+        let items: Vec<VocabularyItem> = (0..count)
+            .map(|i| VocabularyItem::new(
+                format!("단어{}", i),
+                format!("word {}", i),
+                "test".to_string(),
+            ))
+            .collect();
+        repo.create_many(&items).await.unwrap()
+    }
+    
+    #[tokio::test]
+    async fn test_configurable_max_retries() {
+        let (pool, _db_file) = setup_test_db().await;
+        let vocab_ids = create_vocabulary(&pool, 1).await;
+        let repo = QueueRepository::new(pool);
+        
+        let batch_id = BatchId::new("batch-retries");
+        repo.enqueue_batch(vocab_ids, &batch_id, 1, None).await.unwrap();
+        
+        let item = repo.get_next_pending(Some(&batch_id)).await.unwrap().unwrap();
+        assert_eq!(item.max_retries, 1);
+        
+        // One retry after the first failure, and none after the second
+        assert!(repo.increment_retry(item.id.unwrap()).await.unwrap());
+        let will_retry = repo.increment_retry(item.id.unwrap()).await.unwrap();
+        assert!(!will_retry);
+        
+        let progress = repo.get_batch_progress(&batch_id).await.unwrap();
+        assert_eq!(progress.quarantined_items, 1);
+        assert_eq!(progress.pending_items, 0);
+    }
+    
+    #[tokio::test]
+    async fn test_retry_count_survives_reopening() {
+        let (pool, db_file) = setup_test_db().await;
+        let vocab_ids = create_vocabulary(&pool, 1).await;
+        let repo = QueueRepository::new(pool);
+        let batch_id = BatchId::new("batch-restart");
+        repo.enqueue_batch(vocab_ids, &batch_id, 2, None).await.unwrap();
+        
+        let item = repo.get_next_pending(Some(&batch_id)).await.unwrap().unwrap();
+        assert!(repo.increment_retry(item.id.unwrap()).await.unwrap());
+        assert!(repo.increment_retry(item.id.unwrap()).await.unwrap());
+        drop(repo);
+        
+        // A fresh pool, as after a restart, picks up the stored count
+        let pool = crate::database::create_pool(db_file.path().to_str().unwrap()).await.unwrap();
+        let repo = QueueRepository::new(pool);
+        let item = repo.get_next_pending(Some(&batch_id)).await.unwrap().unwrap();
+        assert_eq!(item.retry_count, 2);
+        
+        assert!(!repo.increment_retry(item.id.unwrap()).await.unwrap());
+        assert!(repo.get_next_pending(Some(&batch_id)).await.unwrap().is_none());
+        let progress = repo.get_batch_progress(&batch_id).await.unwrap();
+        assert_eq!(progress.quarantined_items, 1);
+    }
+    
+    #[tokio::test]
+    async fn test_skipped_item_is_neither_completed_nor_failed() {
+        let (pool, _db_file) = setup_test_db().await;
+        let vocab_ids = create_vocabulary(&pool, 2).await;
+        let repo = QueueRepository::new(pool);
+        
+        let batch_id = BatchId::new("batch-skip");
+        repo.enqueue_batch(vocab_ids, &batch_id, crate::models::DEFAULT_MAX_RETRIES, None).await.unwrap();
+        
+        let first = repo.get_next_pending(Some(&batch_id)).await.unwrap().unwrap();
+        repo.update_status(first.id.unwrap(), ProcessingStatus::Skipped, Some("not cached".to_string()))
+            .await
+            .unwrap();
+        
+        let progress = repo.get_batch_progress(&batch_id).await.unwrap();
+        assert_eq!(progress.skipped_items, 1);
+        assert_eq!(progress.completed_items, 0);
+        assert_eq!(progress.failed_items, 0);
+        assert!(!progress.is_complete());
+        
+        let second = repo.get_next_pending(Some(&batch_id)).await.unwrap().unwrap();
+        repo.update_status(second.id.unwrap(), ProcessingStatus::Completed, None).await.unwrap();
+        
+        // Skips count toward completion without making the batch partial
+        let progress = repo.get_batch_progress(&batch_id).await.unwrap();
+        assert!(progress.is_complete());
+        let summary = repo.list_batches(None, 10).await.unwrap();
+        assert_eq!(summary[0].status, "completed");
+        assert_eq!(summary[0].failed_items, 0);
+    }
+    
+    #[tokio::test]
+    async fn test_list_batches_by_label() {
+        let (pool, _db_file) = setup_test_db().await;
+        let vocab_ids = create_vocabulary(&pool, 3).await;
+        let repo = QueueRepository::new(pool);
+        
+        repo.enqueue_batch(vec![vocab_ids[0]], &BatchId::new("acme-1"), crate::models::DEFAULT_MAX_RETRIES, Some("acme")).await.unwrap();
+        repo.enqueue_batch(vec![vocab_ids[1]], &BatchId::new("globex-1"), crate::models::DEFAULT_MAX_RETRIES, Some("globex")).await.unwrap();
+        repo.enqueue_batch(vec![vocab_ids[2]], &BatchId::new("unlabeled"), crate::models::DEFAULT_MAX_RETRIES, None).await.unwrap();
+        
+        let acme = repo.list_batches(Some("acme"), 10).await.unwrap();
+        assert_eq!(acme.len(), 1);
+        assert_eq!(acme[0].batch_id.as_str(), "acme-1");
+        assert_eq!(acme[0].label.as_deref(), Some("acme"));
+        
+        let all = repo.list_batches(None, 10).await.unwrap();
+        assert_eq!(all.len(), 3);
+    }
+    
+    #[tokio::test]
+    async fn test_unstarted_batches_exclude_picked_up_items() {
+        let (pool, _db_file) = setup_test_db().await;
+        let vocab_ids = create_vocabulary(&pool, 4).await;
+        let repo = QueueRepository::new(pool);
+        for (n, batch) in ["running", "interrupted", "queued-1", "queued-2"].iter().enumerate() {
+            repo.enqueue_batch(vec![vocab_ids[n]], &BatchId::new(*batch), 3, None).await.unwrap();
+        }
+        
+        let running = repo.get_incomplete_items(&BatchId::new("running")).await.unwrap();
+        repo.update_status(running[0].id.unwrap(), ProcessingStatus::InProgress, None).await.unwrap();
+        // Cut off mid-flight and put back for a resume
+        let interrupted = repo.get_incomplete_items(&BatchId::new("interrupted")).await.unwrap();
+        let interrupted_id = interrupted[0].id.unwrap();
+        repo.update_status(interrupted_id, ProcessingStatus::InProgress, None).await.unwrap();
+        repo.update_status(interrupted_id, ProcessingStatus::Pending, None).await.unwrap();
+        
+        let unstarted = repo.list_unstarted_batches(10).await.unwrap();
+        let ids: Vec<&str> = unstarted.iter().map(|batch| batch.batch_id.as_str()).collect();
+        assert_eq!(ids, ["queued-1", "queued-2"]);
+        assert_eq!(repo.list_unstarted_batches(1).await.unwrap().len(), 1);
+    }
+    
+    #[tokio::test]
+    async fn test_batch_id_round_trips_through_queue() {
+        let (pool, _db_file) = setup_test_db().await;
+        let vocab_ids = create_vocabulary(&pool, 2).await;
+        let repo = QueueRepository::new(pool);
+        
+        // An id as the CLI would parse it, and as a report would serialize it
+        let batch_id: BatchId = "batch_20240101_120000".parse().unwrap();
+        let json = serde_json::to_string(&batch_id).unwrap();
+        assert_eq!(json, "\"batch_20240101_120000\"");
+        assert_eq!(serde_json::from_str::<BatchId>(&json).unwrap(), batch_id);
+        assert!("".parse::<BatchId>().is_err());
+        assert!(" padded ".parse::<BatchId>().is_err());
+        
+        repo.enqueue_batch(vocab_ids, &batch_id, crate::models::DEFAULT_MAX_RETRIES, None).await.unwrap();
+        
+        let lookup: BatchId = batch_id.to_string().parse().unwrap();
+        let progress = repo.get_batch_progress(&lookup).await.unwrap();
+        assert_eq!(progress.batch_id, batch_id);
+        assert_eq!(progress.total_items, 2);
+        
+        let item = repo.get_next_pending(Some(&lookup)).await.unwrap().unwrap();
+        assert_eq!(item.batch_id, batch_id);
+        assert_eq!(repo.list_batches(None, 10).await.unwrap()[0].batch_id, batch_id);
+    }
+    
+    #[tokio::test]
+    async fn test_count_pending_across_batches() {
+        let (pool, _db_file) = setup_test_db().await;
+        let vocab_ids = create_vocabulary(&pool, 60).await;
+        let repo = QueueRepository::new(pool);
+        
+        assert_eq!(repo.count_pending().await.unwrap(), 0);
+        
+        repo.enqueue_batch(vocab_ids[..40].to_vec(), &BatchId::new("backlog-1"), crate::models::DEFAULT_MAX_RETRIES, None).await.unwrap();
+        repo.enqueue_batch(vocab_ids[40..].to_vec(), &BatchId::new("backlog-2"), crate::models::DEFAULT_MAX_RETRIES, None).await.unwrap();
+        assert_eq!(repo.count_pending().await.unwrap(), 60);
+        
+        // Picked-up items no longer count
+        let item = repo.get_next_pending(Some(&BatchId::new("backlog-1"))).await.unwrap().unwrap();
+        repo.update_status(item.id.unwrap(), ProcessingStatus::InProgress, None).await.unwrap();
+        assert_eq!(repo.count_pending().await.unwrap(), 59);
+    }
+    
+    #[tokio::test]
+    async fn test_batched_status_updates() {
+        let (pool, _db_file) = setup_test_db().await;
+        let vocab_ids = create_vocabulary(&pool, 1000).await;
+        let repo = QueueRepository::new(pool.clone());
+        
+        let batch_id = BatchId::new("batched");
+        repo.enqueue_batch(vocab_ids, &batch_id, crate::models::DEFAULT_MAX_RETRIES, None).await.unwrap();
+        let item_ids: Vec<i64> = sqlx::query_scalar("SELECT id FROM processing_queue ORDER BY id")
+            .fetch_all(&pool)
+            .await
+            .unwrap();
+        
+        let started: Vec<_> = item_ids.iter().map(|&id| (id, ProcessingStatus::InProgress)).collect();
+        assert_eq!(repo.update_status_batch(&started).await.unwrap(), 1000);
+        assert_eq!(repo.count_pending().await.unwrap(), 0);
+        
+        let mut finished: Vec<_> = item_ids.iter().map(|&id| (id, ProcessingStatus::Completed)).collect();
+        finished[0].1 = ProcessingStatus::Skipped;
+        assert_eq!(repo.update_status_batch(&finished).await.unwrap(), 1000);
+        
+        let progress = repo.get_batch_progress(&batch_id).await.unwrap();
+        assert_eq!(progress.completed_items, 999);
+        assert_eq!(progress.skipped_items, 1);
+        
+        // Batch metadata was refreshed once, after the commit
+        let summary = repo.list_batches(None, 10).await.unwrap();
+        assert_eq!(summary[0].status, "completed");
+        assert_eq!(summary[0].completed_items, 999);
+        
+        let started_at: Option<DateTime<Utc>> = sqlx::query_scalar(
+            "SELECT started_at FROM processing_queue WHERE id = ?"
+        )
+        .bind(item_ids[1])
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        assert!(started_at.is_some());
+        
+        assert_eq!(repo.update_status_batch(&[]).await.unwrap(), 0);
+    }
+    
+    #[tokio::test]
+    async fn test_incomplete_items_in_queue_order() {
+        let (pool, _db_file) = setup_test_db().await;
+        let vocab_ids = create_vocabulary(&pool, 4).await;
+        let repo = QueueRepository::new(pool.clone());
+        let batch_id = BatchId::new("incomplete");
+        repo.enqueue_batch(vocab_ids.clone(), &batch_id, 3, None).await.unwrap();
+        repo.enqueue_batch(vocab_ids.clone(), &BatchId::new("other"), 3, None).await.unwrap();
+        
+        let queued = repo.get_incomplete_items(&batch_id).await.unwrap();
+        assert_eq!(queued.iter().map(|item| item.vocabulary_id).collect::<Vec<_>>(), vocab_ids);
+        
+        repo.update_status_batch(&[
+            (queued[0].id.unwrap(), ProcessingStatus::Completed),
+            (queued[1].id.unwrap(), ProcessingStatus::InProgress),
+            (queued[2].id.unwrap(), ProcessingStatus::Failed),
+        ]).await.unwrap();
+        
+        let incomplete = repo.get_incomplete_items(&batch_id).await.unwrap();
+        let ids: Vec<Option<i64>> = incomplete.iter().map(|item| item.id).collect();
+        assert_eq!(ids, vec![queued[1].id, queued[3].id]);
+        assert_eq!(incomplete[0].status, ProcessingStatus::InProgress);
+    }
+}
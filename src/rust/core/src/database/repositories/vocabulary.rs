@@ -1,10 +1,16 @@
-use sqlx::{FromRow, Row};
+use sqlx::{FromRow, QueryBuilder, Row, Sqlite};
 use chrono::{DateTime, Utc};
 use serde_json;
 use tracing::{info, debug};
-use crate::models::{VocabularyItem, DifficultyLevel, PipelineError};
+use crate::models::{VocabularyItem, DifficultyLevel, BatchId, PipelineError};
 use crate::database::DatabasePool;
 
+/// SQLite's default limit on bound parameters per statement
+const SQLITE_MAX_VARIABLES: usize = 999;
+
+/// Columns bound per row by `create_many`
+const INSERT_COLUMNS: usize = 11;
+
 pub struct VocabularyRepository {
     pool: DatabasePool,
 }
@@ -66,6 +72,121 @@ impl VocabularyRepository {
         Ok(id)
     }
 
+    /// Insert `item`, or update the existing row with the same korean,
+    /// english and category, returning its id either way.
+    ///
+    /// The check and write are a single statement against the table's
+    /// `UNIQUE(korean, english, category)` constraint, so concurrent imports
+    /// of the same word can't both insert.
+    pub async fn upsert(&self, item: &VocabularyItem) -> Result<i64, PipelineError> {
+        debug!("Upserting vocabulary item: {} - {}", item.korean, item.english);
+        
+        let tags_json = serde_json::to_string(&item.tags)?;
+        let metadata_json = serde_json::to_string(&item.metadata)?;
+        let difficulty = format!("{:?}", item.difficulty_level).to_lowercase();
+        
+        let id: i64 = sqlx::query_scalar(
+            r#"
+            INSERT INTO vocabulary_items 
+            (korean, english, hanja, category, subcategory, difficulty_level, 
+             source, example_sentence, notes, metadata, tags)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+            ON CONFLICT(korean, english, category) DO UPDATE SET
+                hanja = excluded.hanja,
+                subcategory = excluded.subcategory,
+                difficulty_level = excluded.difficulty_level,
+                source = excluded.source,
+                example_sentence = excluded.example_sentence,
+                notes = excluded.notes,
+                metadata = excluded.metadata,
+                tags = excluded.tags,
+                updated_at = CURRENT_TIMESTAMP
+            RETURNING id
+            "#
+        )
+        .bind(&item.korean)
+        .bind(&item.english)
+        .bind(&item.hanja)
+        .bind(&item.category)
+        .bind(&item.subcategory)
+        .bind(&difficulty)
+        .bind(&item.source)
+        .bind(&item.example_sentence)
+        .bind(&item.notes)
+        .bind(&metadata_json)
+        .bind(&tags_json)
+        .fetch_one(&self.pool)
+        .await?;
+        
+        debug!("Upserted vocabulary item with id: {}", id);
+        Ok(id)
+    }
+
+    /// Insert many items in one transaction using multi-row `INSERT`s.
+    ///
+    /// Rows are chunked to stay under SQLite's bound-parameter limit. Returns
+    /// the generated ids in the same order as `items`.
+    pub async fn create_many(&self, items: &[VocabularyItem]) -> Result<Vec<i64>, PipelineError> {
+        debug!("Bulk creating {} vocabulary items", items.len());
+        
+        if items.is_empty() {
+            return Ok(Vec::new());
+        }
+        
+        // Serialize up front so a bad item fails before anything is written
+        let encoded = items.iter()
+            .map(|item| Ok((
+                serde_json::to_string(&item.tags)?,
+                serde_json::to_string(&item.metadata)?,
+                format!("{:?}", item.difficulty_level).to_lowercase(),
+            )))
+            .collect::<Result<Vec<_>, PipelineError>>()?;
+        
+        let rows_per_chunk = SQLITE_MAX_VARIABLES / INSERT_COLUMNS;
+        let mut ids = Vec::with_capacity(items.len());
+        let mut tx = self.pool.begin().await?;
+        
+        for (chunk, encoded_chunk) in items.chunks(rows_per_chunk).zip(encoded.chunks(rows_per_chunk)) {
+            let mut builder = QueryBuilder::<Sqlite>::new(
+                r#"
+                INSERT INTO vocabulary_items 
+                (korean, english, hanja, category, subcategory, difficulty_level, 
+                 source, example_sentence, notes, metadata, tags)
+                "#
+            );
+            
+            builder.push_values(
+                chunk.iter().zip(encoded_chunk),
+                |mut row, (item, (tags_json, metadata_json, difficulty))| {
+                    row.push_bind(&item.korean)
+                        .push_bind(&item.english)
+                        .push_bind(&item.hanja)
+                        .push_bind(&item.category)
+                        .push_bind(&item.subcategory)
+                        .push_bind(difficulty)
+                        .push_bind(&item.source)
+                        .push_bind(&item.example_sentence)
+                        .push_bind(&item.notes)
+                        .push_bind(metadata_json)
+                        .push_bind(tags_json);
+                },
+            );
+            
+            let result = builder.build().execute(&mut *tx).await?;
+            
+            // Rows from a single INSERT get consecutive rowids inside the
+            // transaction, ending at last_insert_rowid
+            let last_id = result.last_insert_rowid();
+            let first_id = last_id - chunk.len() as i64 + 1;
+            ids.extend(first_id..=last_id);
+        }
+        
+        tx.commit().await?;
+        
+        info!("Bulk created {} vocabulary items", ids.len());
+        Ok(ids)
+    }
+
     pub async fn get_by_id(&self, id: i64) -> Result<Option<VocabularyItem>, PipelineError> {
         debug!("Fetching vocabulary item by id: {}", id);
         
@@ -122,6 +243,54 @@ impl VocabularyRepository {
         items
     }
 
+    /// Items of `batch_id` whose processing completed, in the order they
+    /// were queued.
+    pub async fn list_completed_in_batch(&self, batch_id: &BatchId) -> Result<Vec<VocabularyItem>, PipelineError> {
+        debug!("Listing completed vocabulary items in batch: {}", batch_id);
+        
+        let rows = sqlx::query_as::<_, VocabularyRow>(
+            r#"
+            SELECT v.* FROM processing_queue q
+            JOIN vocabulary_items v ON v.id = q.vocabulary_id
+            WHERE q.batch_id = ? AND q.status = 'completed'
+            ORDER BY q.id
+            "#
+        )
+        .bind(batch_id)
+        .fetch_all(&self.pool)
+        .await?;
+        
+        rows.into_iter()
+            .map(|row| self.row_to_item(row))
+            .collect()
+    }
+
+    /// Items of `batch_id` still waiting to be processed, or cut off while
+    /// in progress, in the order they were queued. Finished, failed,
+    /// skipped and quarantined items are left out.
+    pub async fn list_incomplete_in_batch(&self, batch_id: &BatchId) -> Result<Vec<VocabularyItem>, PipelineError> {
+        debug!("Listing incomplete vocabulary items in batch: {}", batch_id);
+        
+        let rows = sqlx::query_as::<_, VocabularyRow>(
+            r#"
+            SELECT v.* FROM processing_queue q
+            JOIN vocabulary_items v ON v.id = q.vocabulary_id
+            WHERE q.batch_id = ? AND q.status IN ('pending', 'in_progress')
+            ORDER BY q.id
+            "#
+        )
+        .bind(batch_id)
+        .fetch_all(&self.pool)
+        .await?;
+        
+        rows.into_iter()
+            .map(|row| self.row_to_item(row))
+            .collect()
+    }
+
+    /// Items with no cached Stage 1 result that no batch holds: not queued or
+    /// in flight, and not failed or quarantined, which a resume of their
+    /// batch retries instead.
     pub async fn list_unprocessed(&self, limit: i32) -> Result<Vec<VocabularyItem>, PipelineError> {
         debug!("Listing unprocessed vocabulary items, limit: {}", limit);
         
@@ -130,7 +299,12 @@ impl VocabularyRepository {
             SELECT v.* FROM vocabulary_items v
             LEFT JOIN stage1_cache s1 ON v.id = s1.vocabulary_id
             WHERE s1.id IS NULL
-            ORDER BY v.created_at ASC
+              AND NOT EXISTS (
+                  SELECT 1 FROM processing_queue q
+                  WHERE q.vocabulary_id = v.id
+                    AND q.status IN ('pending', 'in_progress', 'failed', 'quarantined')
+              )
+            ORDER BY v.created_at ASC, v.id ASC
             LIMIT ?
             "#
         )
@@ -184,6 +358,9 @@ impl VocabularyRepository {
         }
     }
 
+    /// Delete an item. Its cached results and queue entries go with it
+    /// through the schema's `ON DELETE CASCADE` foreign keys, which
+    /// `create_pool` enforces on every connection.
     pub async fn delete(&self, id: i64) -> Result<bool, PipelineError> {
         debug!("Deleting vocabulary item: {}", id);
         
@@ -278,4 +455,197 @@ mod tests {
         assert_eq!(fetched.english, "Hello");
         assert_eq!(fetched.category, "greetings");
     }
-}
\ No newline at end of file
+    
+    #[tokio::test]
+    async fn test_create_many() {
+        let pool = setup_test_db().await;
+        let repo = VocabularyRepository::new(pool);
+        
+        let items: Vec<VocabularyItem> = (0..10_000)
+            .map(|i| VocabularyItem::new(
+                format!("단어{}", i),
+                format!("word {}", i),
+                "bulk".to_string(),
+            ))
+            .collect();
+        
+        let ids = repo.create_many(&items).await.unwrap();
+        assert_eq!(ids.len(), 10_000);
+        
+        let distinct: std::collections::HashSet<_> = ids.iter().collect();
+        assert_eq!(distinct.len(), 10_000);
+        
+        // Ids line up with input order
+        let fetched = repo.get_by_id(ids[1234]).await.unwrap().unwrap();
+        assert_eq!(fetched.korean, "단어1234");
+        
+        assert_eq!(repo.count().await.unwrap(), 10_000);
+    }
+    
+    #[tokio::test]
+    async fn test_concurrent_upsert_creates_one_row() {
+        let temp_file = NamedTempFile::new().unwrap();
+        let pool = crate::database::create_pool(temp_file.path().to_str().unwrap()).await.unwrap();
+        crate::database::migrations::run_migrations(&pool).await.unwrap();
+        let repo = std::sync::Arc::new(VocabularyRepository::new(pool));
+        
+        let item = VocabularyItem::new(
+            "사과".to_string(),
+            "apple".to_string(),
+            "food".to_string(),
+        );
+        
+        let tasks: Vec<_> = (0..2)
+            .map(|_| {
+                let repo = std::sync::Arc::clone(&repo);
+                let item = item.clone();
+                tokio::spawn(async move { repo.upsert(&item).await })
+            })
+            .collect();
+        
+        let mut ids = Vec::new();
+        for task in tasks {
+            ids.push(task.await.unwrap().unwrap());
+        }
+        
+        assert_eq!(ids[0], ids[1]);
+        assert_eq!(repo.count().await.unwrap(), 1);
+        
+        // A later upsert updates the row in place
+        let mut revised = item.clone();
+        revised.notes = Some("fruit".to_string());
+        assert_eq!(repo.upsert(&revised).await.unwrap(), ids[0]);
+        let stored = repo.get_by_id(ids[0]).await.unwrap().unwrap();
+        assert_eq!(stored.notes.as_deref(), Some("fruit"));
+        assert_eq!(repo.count().await.unwrap(), 1);
+    }
+    
+    #[tokio::test]
+    async fn test_delete_removes_dependent_rows() {
+        let temp_file = NamedTempFile::new().unwrap();
+        let pool = crate::database::create_pool(temp_file.path().to_str().unwrap()).await.unwrap();
+        crate::database::migrations::run_migrations(&pool).await.unwrap();
+        let repo = VocabularyRepository::new(pool.clone());
+        
+        let id = repo.create(&VocabularyItem::new(
+            "학교".to_string(),
+            "school".to_string(),
+            "places".to_string(),
+        )).await.unwrap();
+        let kept = repo.create(&VocabularyItem::new(
+            "사과".to_string(),
+            "apple".to_string(),
+            "food".to_string(),
+        )).await.unwrap();
+        
+        for vocabulary_id in [id, kept] {
+            sqlx::query(
+                "INSERT INTO stage1_cache (vocabulary_id, cache_key, request_hash, response_json, model_used)
+                 VALUES (?, ?, 'hash', '{}', 'model')"
+            )
+            .bind(vocabulary_id)
+            .bind(format!("stage1_{}", vocabulary_id))
+            .execute(&pool)
+            .await
+            .unwrap();
+            sqlx::query(
+                "INSERT INTO stage2_cache
+                 (vocabulary_id, stage1_cache_key, cache_key, request_hash, response_json, tsv_output, model_used)
+                 VALUES (?, ?, ?, 'hash', '{}', '', 'model')"
+            )
+            .bind(vocabulary_id)
+            .bind(format!("stage1_{}", vocabulary_id))
+            .bind(format!("stage2_{}", vocabulary_id))
+            .execute(&pool)
+            .await
+            .unwrap();
+        }
+        crate::database::repositories::QueueRepository::new(pool.clone())
+            .enqueue_batch(vec![id, kept], &BatchId::new("batch"), 3, None)
+            .await
+            .unwrap();
+        
+        assert!(repo.delete(id).await.unwrap());
+        
+        for table in ["stage1_cache", "stage2_cache", "processing_queue"] {
+            let remaining: Vec<i64> = sqlx::query_scalar(&format!("SELECT vocabulary_id FROM {}", table))
+                .fetch_all(&pool)
+                .await
+                .unwrap();
+            assert_eq!(remaining, vec![kept], "{} still references the deleted item", table);
+        }
+    }
+    
+    #[tokio::test]
+    async fn test_list_incomplete_in_batch() {
+        let temp_file = NamedTempFile::new().unwrap();
+        let pool = crate::database::create_pool(temp_file.path().to_str().unwrap()).await.unwrap();
+        crate::database::migrations::run_migrations(&pool).await.unwrap();
+        let repo = VocabularyRepository::new(pool.clone());
+        let queue = crate::database::repositories::QueueRepository::new(pool.clone());
+        
+        let mut ids = Vec::new();
+        for (korean, english) in [("학교", "school"), ("바다", "sea"), ("하늘", "sky"), ("나무", "tree")] {
+            ids.push(repo.create(&VocabularyItem::new(
+                korean.to_string(),
+                english.to_string(),
+                "nouns".to_string(),
+            )).await.unwrap());
+        }
+        let batch_id = BatchId::generate();
+        queue.enqueue_batch(ids, &batch_id, 3, None).await.unwrap();
+        
+        let queued: Vec<i64> = sqlx::query_scalar("SELECT id FROM processing_queue ORDER BY id")
+            .fetch_all(&pool)
+            .await
+            .unwrap();
+        queue.update_status_batch(&[
+            (queued[0], crate::models::ProcessingStatus::Completed),
+            (queued[1], crate::models::ProcessingStatus::InProgress),
+            (queued[3], crate::models::ProcessingStatus::Quarantined),
+        ]).await.unwrap();
+        
+        let incomplete = repo.list_incomplete_in_batch(&batch_id).await.unwrap();
+        let terms: Vec<&str> = incomplete.iter().map(|item| item.korean.as_str()).collect();
+        assert_eq!(terms, vec!["바다", "하늘"]);
+        
+        assert!(repo.list_incomplete_in_batch(&BatchId::generate()).await.unwrap().is_empty());
+    }
+    
+    #[tokio::test]
+    async fn test_list_unprocessed_leaves_out_items_a_batch_holds() {
+        let temp_file = NamedTempFile::new().unwrap();
+        let pool = crate::database::create_pool(temp_file.path().to_str().unwrap()).await.unwrap();
+        crate::database::migrations::run_migrations(&pool).await.unwrap();
+        let repo = VocabularyRepository::new(pool.clone());
+        let queue = crate::database::repositories::QueueRepository::new(pool.clone());
+        
+        let mut ids = Vec::new();
+        for (korean, english) in [("학교", "school"), ("바다", "sea"), ("하늘", "sky"), ("나무", "tree"), ("사과", "apple")] {
+            ids.push(repo.create(&VocabularyItem::new(
+                korean.to_string(),
+                english.to_string(),
+                "nouns".to_string(),
+            )).await.unwrap());
+        }
+        // 사과 was never queued
+        let batch_id = BatchId::generate();
+        queue.enqueue_batch(ids[..4].to_vec(), &batch_id, 3, None).await.unwrap();
+        
+        let queued: Vec<i64> = sqlx::query_scalar("SELECT id FROM processing_queue ORDER BY id")
+            .fetch_all(&pool)
+            .await
+            .unwrap();
+        queue.update_status_batch(&[
+            (queued[1], crate::models::ProcessingStatus::InProgress),
+            (queued[2], crate::models::ProcessingStatus::Failed),
+            (queued[3], crate::models::ProcessingStatus::Skipped),
+        ]).await.unwrap();
+        
+        // Pending, in flight and failed items stay with their batch; a
+        // skipped one was never attempted and is free to pick up
+        let unprocessed = repo.list_unprocessed(10).await.unwrap();
+        let terms: Vec<&str> = unprocessed.iter().map(|item| item.korean.as_str()).collect();
+        assert_eq!(terms, vec!["나무", "사과"]);
+    }
+}
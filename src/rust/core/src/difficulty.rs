@@ -0,0 +1,222 @@
+//! Estimating a difficulty level for terms whose input didn't carry one.
+
+use crate::models::{DifficultyLevel, VocabularyItem};
+use std::collections::HashMap;
+use std::path::Path;
+use unicode_normalization::UnicodeNormalization;
+
+/// Frequency ranks at or below this count as everyday vocabulary
+const COMMON_RANK: usize = 1000;
+
+/// Scores a term's difficulty from its shape: Hangul syllable count, hanja,
+/// rare jamo and, when supplied, how frequent the word is.
+///
+/// Without a frequency list only the shape is used, so short native words
+/// lean easy and long or hanja-bearing terms lean hard.
+#[derive(Debug, Clone, Default)]
+pub struct DifficultyEstimator {
+    /// Word to frequency rank, 1 being the most common
+    frequency_ranks: Option<HashMap<String, usize>>,
+}
+
+impl DifficultyEstimator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Use `ranks` (word to rank, 1 = most common) to tell common words
+    /// from rare ones.
+    pub fn with_frequency_ranks(mut self, ranks: HashMap<String, usize>) -> Self {
+        self.frequency_ranks = Some(
+            ranks.into_iter()
+                .map(|(word, rank)| (word.nfc().collect(), rank))
+                .collect(),
+        );
+        self
+    }
+
+    /// Load a frequency list with one word per line, most common first.
+    /// Blank lines and lines starting with `#` are ignored; a line holding
+    /// more than one word, e.g. a word and its count, is an `InvalidData`
+    /// error naming the line.
+    pub fn from_frequency_file(path: &Path) -> std::io::Result<Self> {
+        let text = std::fs::read_to_string(path)?;
+        let mut ranks = HashMap::new();
+        let words = text.lines()
+            .enumerate()
+            .map(|(index, line)| (index + 1, line.trim()))
+            .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'));
+        for (rank, (line_number, word)) in words.enumerate() {
+            if word.split_whitespace().nth(1).is_some() {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::InvalidData,
+                    format!("line {}: expected one word, found {:?}", line_number, word),
+                ));
+            }
+            ranks.insert(word.to_string(), rank + 1);
+        }
+
+        Ok(Self::new().with_frequency_ranks(ranks))
+    }
+
+    pub fn estimate(&self, term: &str) -> DifficultyLevel {
+        let term: String = term.trim().nfc().collect();
+        let mut score = 0i32;
+
+        score += match hangul_syllables(&term) {
+            0 | 1 => 0,
+            2 => 1,
+            3 => 2,
+            _ => 3,
+        };
+
+        if term.chars().any(is_hanja) {
+            score += 2;
+        }
+        if term.chars().any(is_rare_jamo) || term.chars().any(has_compound_final) {
+            score += 1;
+        }
+
+        if let Some(ranks) = &self.frequency_ranks {
+            score += match ranks.get(&term) {
+                Some(&rank) if rank <= COMMON_RANK => -2,
+                Some(_) => -1,
+                None => 1,
+            };
+        }
+
+        match score {
+            i32::MIN..=0 => DifficultyLevel::Beginner,
+            1 => DifficultyLevel::Elementary,
+            2 => DifficultyLevel::Intermediate,
+            3 | 4 => DifficultyLevel::Advanced,
+            _ => DifficultyLevel::Native,
+        }
+    }
+
+    /// Set `item.difficulty_level` to the input's own `label`, or to the
+    /// estimate for its Korean term when the input had none.
+    pub fn apply(&self, item: &mut VocabularyItem, label: Option<DifficultyLevel>) {
+        item.difficulty_level = match label {
+            Some(label) => label,
+            None => self.estimate(&item.korean),
+        };
+    }
+}
+
+/// [`DifficultyEstimator::estimate`] without a frequency list.
+pub fn estimate_difficulty(term: &str) -> DifficultyLevel {
+    DifficultyEstimator::new().estimate(term)
+}
+
+/// Precomposed Hangul syllable blocks in NFC text. Bytes and chars overcount:
+/// each syllable is three UTF-8 bytes, and up to three chars when decomposed.
+pub fn hangul_syllables(text: &str) -> usize {
+    text.chars().filter(|c| is_hangul_syllable(*c)).count()
+}
+
+fn is_hangul_syllable(c: char) -> bool {
+    ('\u{AC00}'..='\u{D7A3}').contains(&c)
+}
+
+fn is_hanja(c: char) -> bool {
+    ('\u{4E00}'..='\u{9FFF}').contains(&c) || ('\u{F900}'..='\u{FAFF}').contains(&c)
+}
+
+/// Jamo left over after NFC composition: archaic letters that have no
+/// precomposed syllable, or stray compatibility jamo.
+fn is_rare_jamo(c: char) -> bool {
+    ('\u{1100}'..='\u{11FF}').contains(&c)
+        || ('\u{3130}'..='\u{318F}').contains(&c)
+        || ('\u{A960}'..='\u{A97F}').contains(&c)
+        || ('\u{D7B0}'..='\u{D7FF}').contains(&c)
+}
+
+/// Syllables ending in a two-consonant cluster such as ㄺ or ㅄ
+fn has_compound_final(c: char) -> bool {
+    if !is_hangul_syllable(c) {
+        return false;
+    }
+    let final_index = (c as u32 - 0xAC00) % 28;
+    matches!(final_index, 3 | 5 | 6 | 9..=15 | 18)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn estimator() -> DifficultyEstimator {
+        let ranks = [("물", 12), ("학교", 85), ("사람", 20)]
+            .into_iter()
+            .map(|(word, rank)| (word.to_string(), rank))
+            .collect();
+        DifficultyEstimator::new().with_frequency_ranks(ranks)
+    }
+
+    #[test]
+    fn test_common_single_syllable_is_beginner() {
+        assert_eq!(estimator().estimate("물"), DifficultyLevel::Beginner);
+    }
+
+    #[test]
+    fn test_long_rare_term_is_advanced() {
+        assert_eq!(estimator().estimate("민주주의적"), DifficultyLevel::Advanced);
+        assert_eq!(estimate_difficulty("민주주의적"), DifficultyLevel::Advanced);
+    }
+
+    #[test]
+    fn test_syllables_are_counted_not_bytes_or_jamo() {
+        let decomposed: String = "학교".nfd().collect();
+        assert_eq!(decomposed.chars().count(), 5);
+        assert_eq!(hangul_syllables(&decomposed.nfc().collect::<String>()), 2);
+
+        // The frequency lookup sees through the decomposition too
+        assert_eq!(estimator().estimate(&decomposed), DifficultyLevel::Beginner);
+    }
+
+    #[test]
+    fn test_hanja_raises_difficulty() {
+        assert!(matches!(
+            estimate_difficulty("學校"),
+            DifficultyLevel::Intermediate | DifficultyLevel::Advanced
+        ));
+        assert_eq!(estimate_difficulty("학교"), DifficultyLevel::Elementary);
+    }
+
+    #[test]
+    fn test_apply_keeps_the_input_label() {
+        let mut item = VocabularyItem::new(
+            "민주주의적".to_string(),
+            "democratic".to_string(),
+            "politics".to_string(),
+        );
+
+        estimator().apply(&mut item, Some(DifficultyLevel::Beginner));
+        assert_eq!(item.difficulty_level, DifficultyLevel::Beginner);
+
+        estimator().apply(&mut item, None);
+        assert_eq!(item.difficulty_level, DifficultyLevel::Advanced);
+    }
+
+    #[test]
+    fn test_frequency_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("frequency.txt");
+        std::fs::write(&path, "# most common first\n물\n\n사람\n").unwrap();
+
+        let estimator = DifficultyEstimator::from_frequency_file(&path).unwrap();
+        assert_eq!(estimator.estimate("사람"), DifficultyLevel::Beginner);
+        assert_eq!(estimator.estimate("민주주의적"), DifficultyLevel::Advanced);
+    }
+
+    #[test]
+    fn test_malformed_frequency_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("frequency.txt");
+        std::fs::write(&path, "물\n사람\t5120\n").unwrap();
+
+        let err = DifficultyEstimator::from_frequency_file(&path).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+        assert!(err.to_string().contains("line 2"), "{}", err);
+    }
+}
@@ -0,0 +1,70 @@
+//! JSON deserialization that reports where in the document parsing failed.
+
+use crate::models::PipelineError;
+use serde::de::DeserializeOwned;
+
+/// Parse `json` as `T`. On failure the error names `context` (e.g. which
+/// term's result was being read) and the path of the offending field, such
+/// as `comparison.similar_to[0]`.
+pub fn from_str<T: DeserializeOwned>(json: &str, context: &str) -> Result<T, PipelineError> {
+    let deserializer = &mut serde_json::Deserializer::from_str(json);
+    serde_path_to_error::deserialize(deserializer)
+        .map_err(|e| deserialization_error(context, e))
+}
+
+/// [`from_str`] for an already parsed value.
+pub fn from_value<T: DeserializeOwned>(value: serde_json::Value, context: &str) -> Result<T, PipelineError> {
+    serde_path_to_error::deserialize(value)
+        .map_err(|e| deserialization_error(context, e))
+}
+
+fn deserialization_error(
+    context: &str,
+    error: serde_path_to_error::Error<serde_json::Error>,
+) -> PipelineError {
+    PipelineError::Deserialization {
+        context: context.to_string(),
+        path: error.path().to_string(),
+        message: error.into_inner().to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::SemanticAnalysis;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_error_names_the_nested_path() {
+        let json = r#"{
+            "primary_meaning": "School",
+            "alternative_meanings": [],
+            "connotations": [],
+            "register": "neutral",
+            "usage_contexts": ["daily", 3],
+            "cultural_notes": null,
+            "frequency": "common",
+            "formality": "neutral"
+        }"#;
+
+        let err = from_str::<SemanticAnalysis>(json, "Stage 1 result for '학교'").unwrap_err();
+        assert!(matches!(
+            &err,
+            PipelineError::Deserialization { path, .. } if path == "usage_contexts[1]"
+        ));
+
+        let message = err.to_string();
+        assert!(message.contains("usage_contexts[1]"), "{}", message);
+        assert!(message.contains("'학교'"), "{}", message);
+        assert!(message.contains("invalid type"), "{}", message);
+    }
+
+    #[test]
+    fn test_value_errors_keep_the_path() {
+        let value = serde_json::json!({ "similar_to": ["학생", false] });
+
+        let err = from_value::<HashMap<String, Vec<String>>>(value, "comparison").unwrap_err();
+        assert!(err.to_string().contains("similar_to[1]"), "{}", err);
+    }
+}
@@ -9,6 +9,9 @@ pub mod database;
 pub mod cache_manager;
 pub mod traits;
 pub mod logging;
+pub mod term_normalizer;
+pub mod difficulty;
+pub mod json;
 
 #[cfg(feature = "pyo3")]
 pub mod python_interop;
@@ -17,12 +20,14 @@ pub mod python_interop;
 pub use models::*;
 pub use cache_manager::CacheManager;
 pub use traits::*;
+pub use term_normalizer::TermNormalizer;
+pub use difficulty::{DifficultyEstimator, estimate_difficulty};
 
 // Re-export database types
 pub use database::{DatabasePool, create_pool};
 
 // Re-export logging utilities
-pub use logging::{init_logging, init_json_logging, LogContext};
+pub use logging::{init_logging, init_json_logging, init_file_logging, LogContext};
 
 /// Re-export commonly used external types
 pub use chrono::{DateTime, Utc};
\ No newline at end of file
@@ -1,10 +1,18 @@
 use tracing::{Level, Metadata, Subscriber};
+use tracing_appender::rolling::RollingFileAppender;
 use tracing_subscriber::{
     fmt::{self, format::FmtSpan, time::UtcTime},
     prelude::*,
     EnvFilter, Registry,
 };
 use std::io;
+use std::path::Path;
+
+pub use tracing_appender::non_blocking::WorkerGuard;
+pub use tracing_appender::rolling::Rotation;
+
+/// Log files are named `<prefix>.<date>.log`, e.g. `pipeline.2024-01-31.log`
+const LOG_FILE_PREFIX: &str = "pipeline";
 
 pub fn init_logging(log_level: Option<&str>) -> Result<(), Box<dyn std::error::Error>> {
     let env_filter = EnvFilter::try_from_default_env()
@@ -12,6 +20,7 @@ pub fn init_logging(log_level: Option<&str>) -> Result<(), Box<dyn std::error::E
         .unwrap();
 
     let fmt_layer = fmt::layer()
+        .with_writer(io::stderr)
         .with_target(true)
         .with_thread_ids(true)
         .with_thread_names(true)
@@ -39,6 +48,7 @@ pub fn init_json_logging(log_level: Option<&str>) -> Result<(), Box<dyn std::err
 
     let fmt_layer = fmt::layer()
         .json()
+        .with_writer(io::stderr)
         .with_target(true)
         .with_thread_ids(true)
         .with_thread_names(true)
@@ -58,6 +68,66 @@ pub fn init_json_logging(log_level: Option<&str>) -> Result<(), Box<dyn std::err
     Ok(())
 }
 
+/// Log to the console (stderr) and to files in `dir` that roll over on
+/// `rotation`.
+///
+/// Installs the global subscriber, so use it instead of `init_logging` or
+/// `init_json_logging`, not alongside them. File lines are written from a
+/// background thread; keep the returned guard alive until exit, since
+/// dropping it is what flushes the last buffered lines.
+pub fn init_file_logging(
+    dir: &Path,
+    log_level: Option<&str>,
+    rotation: Rotation,
+) -> Result<WorkerGuard, Box<dyn std::error::Error>> {
+    let (subscriber, guard) = file_logging_subscriber(dir, log_level, rotation)?;
+    tracing::subscriber::set_global_default(subscriber)?;
+    
+    tracing::info!("File logging initialized in {:?} with level: {}", dir, log_level.unwrap_or("info"));
+    Ok(guard)
+}
+
+fn file_logging_subscriber(
+    dir: &Path,
+    log_level: Option<&str>,
+    rotation: Rotation,
+) -> Result<(impl Subscriber + Send + Sync, WorkerGuard), Box<dyn std::error::Error>> {
+    // A bad level is the caller's to report, e.g. by logging to the console only
+    let env_filter = EnvFilter::try_from_default_env()
+        .or_else(|_| EnvFilter::try_new(log_level.unwrap_or("info")))?;
+    
+    let appender = RollingFileAppender::builder()
+        .rotation(rotation)
+        .filename_prefix(LOG_FILE_PREFIX)
+        .filename_suffix("log")
+        .build(dir)?;
+    let (writer, guard) = tracing_appender::non_blocking(appender);
+    
+    let console_layer = fmt::layer()
+        .with_writer(io::stderr)
+        .with_target(true)
+        .with_timer(UtcTime::rfc_3339())
+        .with_ansi(true)
+        .with_level(true);
+    
+    let file_layer = fmt::layer()
+        .with_writer(writer)
+        .with_target(true)
+        .with_thread_ids(true)
+        .with_file(true)
+        .with_line_number(true)
+        .with_timer(UtcTime::rfc_3339())
+        .with_ansi(false)
+        .with_level(true);
+    
+    let subscriber = Registry::default()
+        .with(env_filter)
+        .with(console_layer)
+        .with(file_layer);
+    
+    Ok((subscriber, guard))
+}
+
 pub struct LogContext {
     pub batch_id: Option<String>,
     pub vocabulary_id: Option<i64>,
@@ -137,19 +207,65 @@ pub fn log_processing_complete(batch_id: &str, completed: usize, failed: usize,
     );
 }
 
-pub fn log_cache_hit(cache_type: &str, cache_key: &str, tokens_saved: i32) {
-    tracing::debug!(
+/// Why a cache lookup missed and the stage was computed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CacheMissReason {
+    /// Nothing is cached under the key
+    NotCached,
+    /// The cached entry came from another request than the one that would
+    /// be sent now
+    RequestHashMismatch,
+    /// The stage is forced to refresh
+    ForcedRefresh,
+}
+
+impl CacheMissReason {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CacheMissReason::NotCached => "not_cached",
+            CacheMissReason::RequestHashMismatch => "request_hash_mismatch",
+            CacheMissReason::ForcedRefresh => "forced_refresh",
+        }
+    }
+}
+
+/// Whether a cache hit's request hash was compared with the current
+/// request's; a mismatch is a miss
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RequestHashCheck {
+    /// Hashes aren't compared, or there was nothing to compare against
+    Unchecked,
+    /// The entry was produced by the same request
+    Match,
+}
+
+impl RequestHashCheck {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RequestHashCheck::Unchecked => "unchecked",
+            RequestHashCheck::Match => "match",
+        }
+    }
+}
+
+pub fn log_cache_hit(cache_type: &str, cache_key: &str, term: &str, request_hash: RequestHashCheck) {
+    tracing::info!(
         cache_type = cache_type,
         cache_key = cache_key,
-        tokens_saved = tokens_saved,
+        term = term,
+        decision = "hit",
+        request_hash = request_hash.as_str(),
         "Cache hit"
     );
 }
 
-pub fn log_cache_miss(cache_type: &str, cache_key: &str) {
-    tracing::debug!(
+pub fn log_cache_miss(cache_type: &str, cache_key: &str, term: &str, reason: CacheMissReason) {
+    tracing::info!(
         cache_type = cache_type,
         cache_key = cache_key,
+        term = term,
+        decision = "miss",
+        reason = reason.as_str(),
         "Cache miss"
     );
 }
@@ -210,6 +326,26 @@ mod tests {
         tracing::error!("Error message");
     }
     
+    #[test]
+    fn test_file_logging_flushes_on_guard_drop() {
+        let dir = tempfile::tempdir().unwrap();
+        let (subscriber, guard) = file_logging_subscriber(dir.path(), Some("info"), Rotation::NEVER).unwrap();
+        
+        tracing::subscriber::with_default(subscriber, || {
+            tracing::info!("written to file");
+        });
+        drop(guard);
+        
+        let contents = std::fs::read_to_string(dir.path().join("pipeline.log")).unwrap();
+        assert!(contents.contains("written to file"));
+    }
+    
+    #[test]
+    fn test_file_logging_rejects_a_bad_level() {
+        let dir = tempfile::tempdir().unwrap();
+        assert!(file_logging_subscriber(dir.path(), Some("pipeline=loudest"), Rotation::NEVER).is_err());
+    }
+    
     #[test]
     fn test_log_context() {
         let context = LogContext::new()
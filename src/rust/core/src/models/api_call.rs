@@ -0,0 +1,21 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use crate::models::{BatchId, CacheType};
+
+/// One API call, as kept in the `api_call_log` table
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ApiCallRecord {
+    pub id: Option<i64>,
+    /// Batch the call was made for; `None` for one-off calls
+    pub batch_id: Option<BatchId>,
+    pub term: String,
+    /// Stage the call served
+    pub stage: CacheType,
+    pub model: String,
+    /// Tokens the API reported, 0 for a failed call
+    pub total_tokens: i64,
+    pub latency_ms: i64,
+    pub success: bool,
+    pub error_message: Option<String>,
+    pub called_at: DateTime<Utc>,
+}
@@ -14,6 +14,47 @@ pub struct CacheEntry {
     pub created_at: DateTime<Utc>,
     pub accessed_at: DateTime<Utc>,
     pub access_count: i32,
+    /// Stage 2 only: key of the Stage 1 entry this card was built from
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stage1_cache_key: Option<String>,
+    /// Stage 2 only: the rendered TSV row
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tsv_output: Option<String>,
+    /// Tenant the entry was cached for; see [`crate::models::DEFAULT_NAMESPACE`]
+    #[serde(default)]
+    pub namespace: String,
+    /// The vocabulary row `vocabulary_id` refers to, set on exported entries
+    /// so an import can link them to the same word in another database
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub vocabulary: Option<CacheEntryVocabulary>,
+}
+
+/// The columns that identify a vocabulary row across databases
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct CacheEntryVocabulary {
+    pub korean: String,
+    pub english: String,
+    pub category: String,
+}
+
+/// Outcome of importing cache entries from a backup
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CacheImportStats {
+    pub imported: usize,
+    pub skipped: usize,
+}
+
+/// Outcome of rewriting cache keys under a new key scheme
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CacheMigrationStats {
+    /// Entries moved to their new key
+    pub migrated: usize,
+    /// Entries whose key is the same under both schemes
+    pub unchanged: usize,
+    /// Entries whose vocabulary row is gone, so the key can't be recomputed
+    pub unmigratable: usize,
+    /// Entries left alone because their new key is already taken
+    pub collided: usize,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -23,6 +64,30 @@ pub enum CacheType {
     Stage2,
 }
 
+/// Stages whose cached results are ignored and recomputed. Fresh results
+/// still replace the cached entries.
+///
+/// Useful when only one stage's prompt changed, e.g. a new card template
+/// with the same semantic analysis.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ForceRefresh {
+    pub stage1: bool,
+    pub stage2: bool,
+}
+
+impl ForceRefresh {
+    pub fn all() -> Self {
+        Self { stage1: true, stage2: true }
+    }
+    
+    pub fn stage(&self, cache_type: &CacheType) -> bool {
+        match cache_type {
+            CacheType::Stage1 => self.stage1,
+            CacheType::Stage2 => self.stage2,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CacheStats {
     pub total_entries: i64,
@@ -58,6 +123,10 @@ impl CacheEntry {
             created_at: now,
             accessed_at: now,
             access_count: 1,
+            stage1_cache_key: None,
+            tsv_output: None,
+            namespace: String::new(),
+            vocabulary: None,
         }
     }
 
@@ -67,6 +136,13 @@ impl CacheEntry {
     }
 }
 
+/// [`CacheStats`] as they were at `recorded_at`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CacheStatsSnapshot {
+    pub recorded_at: DateTime<Utc>,
+    pub stats: CacheStats,
+}
+
 impl CacheStats {
     pub fn calculate_hit_rate(&mut self) {
         let total = self.total_hits + self.total_misses;
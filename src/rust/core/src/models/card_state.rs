@@ -0,0 +1,14 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// State of a term's card kept across exports, as in the `card_state`
+/// table, e.g. after suspending it in Anki as a leech
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CardState {
+    pub term: String,
+    /// Export the card marked as suspended
+    pub suspended: bool,
+    /// The card kept being forgotten, as Anki's leech detection means it
+    pub leech: bool,
+    pub updated_at: DateTime<Utc>,
+}
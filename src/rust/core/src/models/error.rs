@@ -8,6 +8,9 @@ pub enum PipelineError {
     #[error("Serialization error: {0}")]
     Serialization(#[from] serde_json::Error),
     
+    #[error("Could not parse {context} at `{path}`: {message}")]
+    Deserialization { context: String, path: String, message: String },
+    
     #[error("API error: {message}")]
     Api { message: String, status_code: Option<u16> },
     
@@ -2,8 +2,12 @@ pub mod vocabulary;
 pub mod cache;
 pub mod queue;
 pub mod error;
+pub mod api_call;
+pub mod card_state;
 
 pub use vocabulary::*;
 pub use cache::*;
 pub use queue::*;
-pub use error::*;
\ No newline at end of file
+pub use error::*;
+pub use api_call::*;
+pub use card_state::*;
\ No newline at end of file
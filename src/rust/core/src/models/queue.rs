@@ -1,14 +1,84 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Retry budget used when none is configured
+pub const DEFAULT_MAX_RETRIES: i32 = 3;
+
+/// Identifies a batch in the queue, batch metadata and checkpoints.
+///
+/// Stored as TEXT, so any non-empty id without surrounding whitespace works;
+/// the pipeline and CLI pass the same value through unchanged.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize, sqlx::Type)]
+#[serde(transparent)]
+#[sqlx(transparent)]
+pub struct BatchId(String);
+
+impl BatchId {
+    pub fn new(id: impl Into<String>) -> Self {
+        Self(id.into())
+    }
+
+    /// A new id for a batch about to be queued, from the time, the process
+    /// and a counter, so batches queued at once by one or several processes
+    /// don't collide.
+    pub fn generate() -> Self {
+        static NEXT: std::sync::atomic::AtomicU64 = std::sync::atomic::AtomicU64::new(0);
+        let sequence = NEXT.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+        Self(format!(
+            "batch-{}-{}-{}",
+            Utc::now().format("%Y%m%d%H%M%S%3f"),
+            std::process::id(),
+            sequence
+        ))
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for BatchId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::str::FromStr for BatchId {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.is_empty() {
+            return Err("Batch id can't be empty".to_string());
+        }
+        if s.trim() != s {
+            return Err(format!("Invalid batch id: {:?}", s));
+        }
+        Ok(Self(s.to_string()))
+    }
+}
+
+impl From<&str> for BatchId {
+    fn from(id: &str) -> Self {
+        Self::new(id)
+    }
+}
+
+impl From<String> for BatchId {
+    fn from(id: String) -> Self {
+        Self(id)
+    }
+}
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct QueueItem {
     pub id: Option<i64>,
     pub vocabulary_id: i64,
-    pub batch_id: String,
+    pub batch_id: BatchId,
     pub status: ProcessingStatus,
     pub stage: ProcessingStage,
     pub retry_count: i32,
+    /// Retries after the first attempt before the item is quarantined
     pub max_retries: i32,
     pub error_message: Option<String>,
     pub created_at: DateTime<Utc>,
@@ -25,6 +95,9 @@ pub enum ProcessingStatus {
     Completed,
     Failed,
     Quarantined,
+    /// Deliberately not processed, e.g. an offline cache miss. Counts toward
+    /// batch completion but not as a failure.
+    Skipped,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -37,11 +110,12 @@ pub enum ProcessingStage {
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BatchProgress {
-    pub batch_id: String,
+    pub batch_id: BatchId,
     pub total_items: i32,
     pub completed_items: i32,
     pub failed_items: i32,
     pub quarantined_items: i32,
+    pub skipped_items: i32,
     pub pending_items: i32,
     pub in_progress_items: i32,
     pub start_time: DateTime<Utc>,
@@ -49,10 +123,23 @@ pub struct BatchProgress {
     pub items_per_second: f64,
 }
 
+/// A row of `batch_metadata`, as shown when listing batches
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BatchSummary {
+    pub batch_id: BatchId,
+    pub label: Option<String>,
+    pub total_items: i32,
+    pub completed_items: i32,
+    pub failed_items: i32,
+    pub status: String,
+    pub start_time: DateTime<Utc>,
+    pub end_time: Option<DateTime<Utc>>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ProcessingCheckpoint {
     pub id: Option<i64>,
-    pub batch_id: String,
+    pub batch_id: BatchId,
     pub last_processed_id: i64,
     pub stage: ProcessingStage,
     pub checkpoint_data: serde_json::Value,
@@ -60,7 +147,7 @@ pub struct ProcessingCheckpoint {
 }
 
 impl QueueItem {
-    pub fn new(vocabulary_id: i64, batch_id: String) -> Self {
+    pub fn new(vocabulary_id: i64, batch_id: BatchId) -> Self {
         let now = Utc::now();
         Self {
             id: None,
@@ -69,7 +156,7 @@ impl QueueItem {
             status: ProcessingStatus::Pending,
             stage: ProcessingStage::Stage1,
             retry_count: 0,
-            max_retries: 3,
+            max_retries: DEFAULT_MAX_RETRIES,
             error_message: None,
             created_at: now,
             updated_at: now,
@@ -78,6 +165,11 @@ impl QueueItem {
         }
     }
 
+    pub fn with_max_retries(mut self, max_retries: i32) -> Self {
+        self.max_retries = max_retries;
+        self
+    }
+
     pub fn start_processing(&mut self) {
         self.status = ProcessingStatus::InProgress;
         self.started_at = Some(Utc::now());
@@ -105,7 +197,7 @@ impl QueueItem {
         self.error_message = Some(error);
         self.updated_at = Utc::now();
 
-        if self.retry_count >= self.max_retries {
+        if self.retry_count > self.max_retries {
             self.status = ProcessingStatus::Quarantined;
             false
         } else {
@@ -114,6 +206,12 @@ impl QueueItem {
         }
     }
 
+    pub fn skip(&mut self, reason: String) {
+        self.status = ProcessingStatus::Skipped;
+        self.error_message = Some(reason);
+        self.updated_at = Utc::now();
+    }
+
     pub fn quarantine(&mut self, reason: String) {
         self.status = ProcessingStatus::Quarantined;
         self.error_message = Some(reason);
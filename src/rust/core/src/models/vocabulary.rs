@@ -51,7 +51,7 @@ pub struct SemanticAnalysis {
     pub formality: FormalityLevel,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 #[serde(rename_all = "lowercase")]
 pub enum FrequencyLevel {
     VeryCommon,
@@ -77,40 +77,143 @@ pub struct Stage2Result {
     pub stage1_cache_key: String,
     pub request_id: String,
     pub cache_key: String,
-    pub flashcard_content: FlashcardContent,
+    pub front: FlashcardContent,
+    pub back: FlashcardContent,
+    pub card_type: CardType,
+    /// Where the card falls in a suggested study order, if the model gave one
+    #[serde(default)]
+    pub learning_order: Option<i32>,
+    /// Terms of cards worth studying alongside this one
+    #[serde(default)]
+    pub related_cards: Vec<String>,
     pub tsv_output: String,
     pub created_at: DateTime<Utc>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// The content of one side of a flashcard
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct FlashcardContent {
-    pub front: CardFace,
-    pub back: CardFace,
-    pub tags: Vec<String>,
-    pub deck_name: String,
-    pub card_type: CardType,
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct CardFace {
-    pub primary_content: String,
-    pub secondary_content: Option<String>,
-    pub example: Option<String>,
-    pub pronunciation: Option<String>,
-    pub notes: Option<String>,
-    pub media_references: Vec<String>,
+    pub primary_field: String,
+    pub secondary_field: Option<String>,
+    pub tertiary_field: Option<String>,
+    pub example_sentence: Option<String>,
+    /// Translation of `example_sentence`; absent in entries cached before
+    /// it existed
+    #[serde(default)]
+    pub example_translation: Option<String>,
+    pub pronunciation_guide: Option<String>,
+    pub image_prompt: Option<String>,
+    pub mnemonic_aid: Option<String>,
+    pub grammar_notes: Option<String>,
+    pub cultural_notes: Option<String>,
+    pub usage_notes: Option<String>,
+    pub difficulty_level: DifficultyLevel,
+    pub frequency_level: FrequencyLevel,
+    pub thematic_tags: Vec<String>,
+    pub grammatical_tags: Vec<String>,
+    pub style_register: Option<String>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 #[serde(rename_all = "lowercase")]
 pub enum CardType {
-    Basic,
+    /// Front to back; stored as `basic` before cards had other fields
+    #[serde(alias = "basic")]
+    Standard,
     BasicReversed,
     Cloze,
     Production,
     Recognition,
 }
 
+impl FlashcardContent {
+    /// A side showing just `primary_field`, at intermediate difficulty and
+    /// common frequency
+    pub fn new(primary_field: impl Into<String>) -> Self {
+        Self {
+            primary_field: primary_field.into(),
+            secondary_field: None,
+            tertiary_field: None,
+            example_sentence: None,
+            example_translation: None,
+            pronunciation_guide: None,
+            image_prompt: None,
+            mnemonic_aid: None,
+            grammar_notes: None,
+            cultural_notes: None,
+            usage_notes: None,
+            difficulty_level: DifficultyLevel::Intermediate,
+            frequency_level: FrequencyLevel::Common,
+            thematic_tags: Vec::new(),
+            grammatical_tags: Vec::new(),
+            style_register: None,
+        }
+    }
+
+    /// Clear every generated field except the primary field and the
+    /// pronunciation guide, which is all Stage 2 fills in
+    /// [`Stage2Mode::Minimal`]. Tags are kept.
+    pub fn keep_essential_fields(&mut self) {
+        self.secondary_field = None;
+        self.tertiary_field = None;
+        self.example_sentence = None;
+        self.example_translation = None;
+        self.image_prompt = None;
+        self.mnemonic_aid = None;
+        self.grammar_notes = None;
+        self.cultural_notes = None;
+        self.usage_notes = None;
+        self.style_register = None;
+    }
+}
+
+/// A card as cached before [`FlashcardContent`] replaced `CardFace`: both
+/// sides under `flashcard_content`, with the tags and deck on the card.
+/// Only read, so entries cached then still load.
+#[derive(Debug, Deserialize)]
+pub(crate) struct LegacyCard {
+    front: LegacyFace,
+    back: LegacyFace,
+    #[serde(default)]
+    tags: Vec<String>,
+    card_type: CardType,
+}
+
+#[derive(Debug, Deserialize)]
+struct LegacyFace {
+    primary_content: String,
+    secondary_content: Option<String>,
+    example: Option<String>,
+    #[serde(default)]
+    example_translation: Option<String>,
+    pronunciation: Option<String>,
+    notes: Option<String>,
+}
+
+impl LegacyCard {
+    /// The front and back in the current shape, with the card's tags on the
+    /// front. The deck name and media references have no counterpart and
+    /// are dropped.
+    pub(crate) fn into_sides(self) -> (FlashcardContent, FlashcardContent, CardType) {
+        let mut front = self.front.into_content();
+        front.thematic_tags = self.tags;
+        (front, self.back.into_content(), self.card_type)
+    }
+}
+
+impl LegacyFace {
+    fn into_content(self) -> FlashcardContent {
+        FlashcardContent {
+            secondary_field: self.secondary_content,
+            example_sentence: self.example,
+            example_translation: self.example_translation,
+            pronunciation_guide: self.pronunciation,
+            usage_notes: self.notes,
+            ..FlashcardContent::new(self.primary_content)
+        }
+    }
+}
+
 impl VocabularyItem {
     pub fn new(korean: String, english: String, category: String) -> Self {
         let now = Utc::now();
@@ -137,62 +240,398 @@ impl VocabularyItem {
         self
     }
 
+    /// Whether the term is a phrase of several words, e.g. `잘 지내세요`
+    pub fn is_phrase(&self) -> bool {
+        is_phrase(&self.korean)
+    }
+
     pub fn generate_cache_key(&self) -> String {
+        self.generate_cache_key_with(KeyNormalization::default())
+    }
+
+    /// Cache key for this item with its text fields normalized first.
+    pub fn generate_cache_key_with(&self, normalization: KeyNormalization) -> String {
+        self.generate_cache_key_in(normalization, DEFAULT_NAMESPACE)
+    }
+
+    /// Cache key for this item within `namespace`, so identical items
+    /// cached for different tenants never share an entry. The default
+    /// namespace isn't hashed, which keeps keys from before namespaces.
+    pub fn generate_cache_key_in(&self, normalization: KeyNormalization, namespace: &str) -> String {
         use sha2::{Sha256, Digest};
         let mut hasher = Sha256::new();
         
-        hasher.update(&self.korean);
-        hasher.update(&self.english);
-        hasher.update(&self.category);
+        if namespace != DEFAULT_NAMESPACE {
+            // The separator keeps `ab` + `c...` apart from `a` + `bc...`
+            hasher.update(namespace.as_bytes());
+            hasher.update([0u8]);
+        }
+        
+        hasher.update(normalization.apply(&self.korean).as_bytes());
+        hasher.update(normalization.apply(&self.english).as_bytes());
+        hasher.update(normalization.apply(&self.category).as_bytes());
         
         if let Some(hanja) = &self.hanja {
-            hasher.update(hanja);
+            hasher.update(normalization.apply(hanja).as_bytes());
         }
         
         if let Some(example) = &self.example_sentence {
-            hasher.update(example);
+            hasher.update(normalization.apply(example).as_bytes());
         }
         
         format!("{:x}", hasher.finalize())
     }
 }
 
+/// Cache namespace of single-tenant setups, and of every entry cached
+/// before namespaces existed
+pub const DEFAULT_NAMESPACE: &str = "";
+
+/// Grammatical tag of cards whose term is a phrase rather than one word
+pub const PHRASE_TAG: &str = "phrase";
+
+/// Whether `term` has more than one word once surrounding whitespace is
+/// ignored
+pub fn is_phrase(term: &str) -> bool {
+    term.split_whitespace().nth(1).is_some()
+}
+
+/// How text is normalized before it is hashed into a cache key.
+///
+/// Hangul can be stored precomposed (NFC) or as separate jamo (NFD), and input
+/// files often carry stray whitespace; without normalization each variant gets
+/// its own cache entry.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum KeyNormalization {
+    /// Hash the exact bytes, matching keys created before normalization existed
+    None,
+    /// Unicode NFC normalization
+    Nfc,
+    /// NFC normalization plus trimming of surrounding whitespace. Runs of
+    /// whitespace inside, e.g. between the words of a phrase, become one
+    /// space, so a phrase keeps its word breaks however it was typed.
+    /// Entries keyed before runs were collapsed move over with `cache-migrate`.
+    #[default]
+    NfcTrim,
+}
+
+impl KeyNormalization {
+    pub fn apply<'a>(&self, text: &'a str) -> std::borrow::Cow<'a, str> {
+        use std::borrow::Cow;
+        use unicode_normalization::UnicodeNormalization;
+        
+        match self {
+            KeyNormalization::None => Cow::Borrowed(text),
+            KeyNormalization::Nfc => Cow::Owned(text.nfc().collect()),
+            KeyNormalization::NfcTrim => {
+                let words: Vec<&str> = text.split_whitespace().collect();
+                Cow::Owned(words.join(" ").nfc().collect())
+            }
+        }
+    }
+}
+
+/// How much of a card Stage 2 generates.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Stage2Mode {
+    /// Every field, including notes, image prompts and register
+    #[default]
+    Full,
+    /// Front and back primary fields and pronunciation only, which costs
+    /// fewer tokens
+    Minimal,
+}
+
+impl Stage2Mode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Stage2Mode::Full => "full",
+            Stage2Mode::Minimal => "minimal",
+        }
+    }
+
+    /// The mode a Stage 2 cache key was generated for
+    pub fn of_cache_key(cache_key: &str) -> Self {
+        if cache_key.starts_with(Stage2Mode::Minimal.key_prefix()) {
+            Stage2Mode::Minimal
+        } else {
+            Stage2Mode::Full
+        }
+    }
+
+    fn key_prefix(&self) -> &'static str {
+        match self {
+            Stage2Mode::Full => "stage2_",
+            Stage2Mode::Minimal => "stage2_minimal_",
+        }
+    }
+}
+
+/// `key` for a result generated from a non-default prompt, identified by
+/// `prompt_version`, so entries from different prompts never share a key.
+/// The `stage1_`/`stage2_` prefix is kept, so the stage and Stage 2 mode
+/// can still be read off the key.
+pub fn prompt_versioned_key(key: &str, prompt_version: &str) -> String {
+    use sha2::{Sha256, Digest};
+    let (prefix, hash) = key.rsplit_once('_').unwrap_or(("", key));
+    
+    let mut hasher = Sha256::new();
+    hasher.update(hash);
+    hasher.update([0u8]);
+    hasher.update(prompt_version);
+    if prefix.is_empty() {
+        format!("{:x}", hasher.finalize())
+    } else {
+        format!("{}_{:x}", prefix, hasher.finalize())
+    }
+}
+
+/// `key` for a result from `model`, so that with model pinning entries
+/// cached under one model are never served when running another.
+pub fn model_pinned_key(key: &str, model: &str) -> String {
+    // Prompt versions are hex digests, so this can't collide with one
+    prompt_versioned_key(key, &format!("model:{}", model))
+}
+
 impl Stage1Result {
     pub fn generate_cache_key(vocab_item: &VocabularyItem) -> String {
-        format!("stage1_{}", vocab_item.generate_cache_key())
+        Self::generate_cache_key_with(vocab_item, KeyNormalization::default())
+    }
+
+    pub fn generate_cache_key_with(vocab_item: &VocabularyItem, normalization: KeyNormalization) -> String {
+        Self::generate_cache_key_in(vocab_item, normalization, DEFAULT_NAMESPACE)
+    }
+
+    /// Cache key within `namespace`. Stage 2 keys are derived from this one,
+    /// so they are namespaced too.
+    pub fn generate_cache_key_in(
+        vocab_item: &VocabularyItem,
+        normalization: KeyNormalization,
+        namespace: &str,
+    ) -> String {
+        format!("stage1_{}", vocab_item.generate_cache_key_in(normalization, namespace))
     }
 }
 
 impl Stage2Result {
     pub fn generate_cache_key(vocab_item: &VocabularyItem, stage1_key: &str) -> String {
+        Self::generate_cache_key_with(vocab_item, stage1_key, KeyNormalization::default())
+    }
+
+    pub fn generate_cache_key_with(
+        vocab_item: &VocabularyItem,
+        stage1_key: &str,
+        normalization: KeyNormalization,
+    ) -> String {
+        Self::generate_cache_key_for_mode(vocab_item, stage1_key, normalization, Stage2Mode::Full)
+    }
+
+    /// Cache key for a card generated in `mode`. Full-mode keys are the ones
+    /// used before modes existed, so existing entries still hit.
+    pub fn generate_cache_key_for_mode(
+        vocab_item: &VocabularyItem,
+        stage1_key: &str,
+        normalization: KeyNormalization,
+        mode: Stage2Mode,
+    ) -> String {
         use sha2::{Sha256, Digest};
         let mut hasher = Sha256::new();
         hasher.update(stage1_key);
-        hasher.update(vocab_item.generate_cache_key());
-        format!("stage2_{:x}", hasher.finalize())
+        hasher.update(vocab_item.generate_cache_key_with(normalization));
+        format!("{}{:x}", mode.key_prefix(), hasher.finalize())
+    }
+
+    /// Clear the fields `mode` doesn't generate from both sides, for cards
+    /// from a client that can only produce full ones.
+    pub fn restrict_to(&mut self, mode: Stage2Mode) {
+        if mode == Stage2Mode::Minimal {
+            self.front.keep_essential_fields();
+            self.back.keep_essential_fields();
+        }
+    }
+
+    /// Add [`PHRASE_TAG`] to the front's grammatical tags if `item` is a
+    /// phrase. Tagging twice adds it once.
+    pub fn tag_if_phrase(&mut self, item: &VocabularyItem) {
+        let tags = &mut self.front.grammatical_tags;
+        if item.is_phrase() && !tags.iter().any(|tag| tag == PHRASE_TAG) {
+            tags.push(PHRASE_TAG.to_string());
+        }
     }
 
+    /// The card as one tab-separated row. Tabs and line breaks inside
+    /// fields become spaces, so every row has the same columns.
     pub fn to_tsv_row(&self) -> String {
-        let front = &self.flashcard_content.front;
-        let back = &self.flashcard_content.back;
+        let front = &self.front;
+        let back = &self.back;
         
         let mut fields = vec![
-            front.primary_content.clone(),
-            back.primary_content.clone(),
+            front.primary_field.as_str(),
+            back.primary_field.as_str(),
         ];
         
-        if let Some(secondary) = &back.secondary_content {
-            fields.push(secondary.clone());
+        if let Some(secondary) = &back.secondary_field {
+            fields.push(secondary);
         }
         
-        if let Some(example) = &back.example {
-            fields.push(example.clone());
+        if let Some(example) = &back.example_sentence {
+            fields.push(example);
+        }
+        
+        let tags = front.thematic_tags.join(" ");
+        fields.push(&tags);
+        
+        fields.iter()
+            .map(|field| field.replace(['\t', '\r', '\n'], " "))
+            .collect::<Vec<_>>()
+            .join("\t")
+    }
+}
+
+/// One field that differs between two Stage 2 results
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FieldChange {
+    /// Dotted path of the field, e.g. `back.example_sentence`
+    pub path: String,
+    pub old: serde_json::Value,
+    pub new: serde_json::Value,
+}
+
+/// Field-level differences between a cached Stage 2 result and a freshly
+/// computed one for the same cache key
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Stage2Diff {
+    pub cache_key: String,
+    pub changes: Vec<FieldChange>,
+}
+
+impl Stage2Diff {
+    /// Compare the card content and TSV row of `old` against `new`.
+    /// Bookkeeping fields such as `request_id` and `created_at` always
+    /// differ between runs and are ignored.
+    pub fn between(old: &Stage2Result, new: &Stage2Result) -> Self {
+        let mut diff = Self {
+            cache_key: old.cache_key.clone(),
+            changes: Vec::new(),
+        };
+        
+        diff.compare_face("front", &old.front, &new.front);
+        diff.compare_face("back", &old.back, &new.back);
+        diff.compare("card_type", &old.card_type, &new.card_type);
+        diff.compare("learning_order", &old.learning_order, &new.learning_order);
+        diff.compare("related_cards", &old.related_cards, &new.related_cards);
+        diff.compare("tsv_output", &old.tsv_output, &new.tsv_output);
+        
+        diff
+    }
+    
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+    
+    fn compare_face(&mut self, face: &str, old: &FlashcardContent, new: &FlashcardContent) {
+        let path = |field: &str| format!("{}.{}", face, field);
+        
+        self.compare(&path("primary_field"), &old.primary_field, &new.primary_field);
+        self.compare(&path("secondary_field"), &old.secondary_field, &new.secondary_field);
+        self.compare(&path("tertiary_field"), &old.tertiary_field, &new.tertiary_field);
+        self.compare(&path("example_sentence"), &old.example_sentence, &new.example_sentence);
+        self.compare(&path("example_translation"), &old.example_translation, &new.example_translation);
+        self.compare(&path("pronunciation_guide"), &old.pronunciation_guide, &new.pronunciation_guide);
+        self.compare(&path("image_prompt"), &old.image_prompt, &new.image_prompt);
+        self.compare(&path("mnemonic_aid"), &old.mnemonic_aid, &new.mnemonic_aid);
+        self.compare(&path("grammar_notes"), &old.grammar_notes, &new.grammar_notes);
+        self.compare(&path("cultural_notes"), &old.cultural_notes, &new.cultural_notes);
+        self.compare(&path("usage_notes"), &old.usage_notes, &new.usage_notes);
+        self.compare(&path("difficulty_level"), &old.difficulty_level, &new.difficulty_level);
+        self.compare(&path("frequency_level"), &old.frequency_level, &new.frequency_level);
+        self.compare(&path("thematic_tags"), &old.thematic_tags, &new.thematic_tags);
+        self.compare(&path("grammatical_tags"), &old.grammatical_tags, &new.grammatical_tags);
+        self.compare(&path("style_register"), &old.style_register, &new.style_register);
+    }
+    
+    fn compare<T: PartialEq + Serialize>(&mut self, path: &str, old: &T, new: &T) {
+        if old != new {
+            self.changes.push(FieldChange {
+                path: path.to_string(),
+                old: serde_json::to_value(old).unwrap_or_default(),
+                new: serde_json::to_value(new).unwrap_or_default(),
+            });
         }
+    }
+}
+
+/// The changed fields of one term's card between two batches
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TermDiff {
+    pub term: String,
+    pub changes: Vec<FieldChange>,
+}
+
+/// How the cards of two batches differ, e.g. before and after a prompt
+/// change, matched by term rather than position
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BatchDiff {
+    /// Terms in both batches whose cards differ, in the new batch's order
+    pub changed: Vec<TermDiff>,
+    /// Terms in both batches whose cards are the same
+    pub unchanged: usize,
+    /// Terms only in the new batch
+    pub added: Vec<String>,
+    /// Terms only in the old batch
+    pub removed: Vec<String>,
+}
+
+impl BatchDiff {
+    /// Compare each term's card in `old` with its card in `new`, using
+    /// [`Stage2Diff::between`]. Terms are matched after trimming, and a
+    /// term repeated within a batch is compared by its first card.
+    pub fn between(old: &[(VocabularyItem, Stage2Result)], new: &[(VocabularyItem, Stage2Result)]) -> Self {
+        let (old_terms, old_results) = cards_by_term(old);
+        let (new_terms, new_results) = cards_by_term(new);
         
-        fields.push(self.flashcard_content.tags.join(" "));
+        let mut diff = Self::default();
+        for term in &new_terms {
+            match old_results.get(term) {
+                Some(old_result) => {
+                    let changes = Stage2Diff::between(old_result, new_results[term]).changes;
+                    if changes.is_empty() {
+                        diff.unchanged += 1;
+                    } else {
+                        diff.changed.push(TermDiff { term: term.to_string(), changes });
+                    }
+                }
+                None => diff.added.push(term.to_string()),
+            }
+        }
+        diff.removed = old_terms.iter()
+            .filter(|term| !new_results.contains_key(*term))
+            .map(|term| term.to_string())
+            .collect();
         
-        fields.join("\t")
+        diff
+    }
+    
+    /// Terms present in both batches
+    pub fn compared(&self) -> usize {
+        self.changed.len() + self.unchanged
+    }
+}
+
+/// The distinct trimmed terms of `cards` in order, and each one's first card
+fn cards_by_term(cards: &[(VocabularyItem, Stage2Result)]) -> (Vec<&str>, HashMap<&str, &Stage2Result>) {
+    let mut terms = Vec::new();
+    let mut results = HashMap::new();
+    for (item, result) in cards {
+        let term = item.korean.trim();
+        if !results.contains_key(term) {
+            terms.push(term);
+            results.insert(term, result);
+        }
     }
+    (terms, results)
 }
 
 #[cfg(test)]
@@ -237,4 +676,217 @@ mod tests {
         
         assert_ne!(item1.generate_cache_key(), item3.generate_cache_key());
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_cache_key_normalization() {
+        let nfc = "한국";
+        let nfd: String = {
+            use unicode_normalization::UnicodeNormalization;
+            nfc.nfd().collect()
+        };
+        assert_ne!(nfc, nfd);
+        
+        let item = |korean: &str| VocabularyItem::new(
+            korean.to_string(),
+            "Korea".to_string(),
+            "places".to_string(),
+        );
+        let composed = item(nfc);
+        let decomposed = item(&nfd);
+        let padded = item("한국 ");
+        
+        assert_eq!(composed.generate_cache_key(), decomposed.generate_cache_key());
+        assert_eq!(composed.generate_cache_key(), padded.generate_cache_key());
+        assert_eq!(
+            composed.generate_cache_key_with(KeyNormalization::Nfc),
+            decomposed.generate_cache_key_with(KeyNormalization::Nfc)
+        );
+        assert_ne!(
+            composed.generate_cache_key_with(KeyNormalization::None),
+            decomposed.generate_cache_key_with(KeyNormalization::None)
+        );
+        assert_ne!(
+            composed.generate_cache_key_with(KeyNormalization::Nfc),
+            padded.generate_cache_key_with(KeyNormalization::Nfc)
+        );
+    }
+
+    #[test]
+    fn test_phrase_keys_tags_and_tsv_row() {
+        let item = |korean: &str| VocabularyItem::new(
+            korean.to_string(),
+            "Take care".to_string(),
+            "greetings".to_string(),
+        );
+        let phrase = item("잘 지내세요");
+        
+        assert!(phrase.is_phrase());
+        assert!(!item(" 학교 ").is_phrase());
+        
+        // Word breaks are kept, however they were typed
+        let key = phrase.generate_cache_key();
+        assert_eq!(key, item(" 잘  지내세요\n").generate_cache_key());
+        assert_eq!(key, item("잘\t지내세요").generate_cache_key());
+        assert_ne!(key, item("잘지내세요").generate_cache_key());
+        
+        let mut result = Stage2Result {
+            vocabulary_id: 1,
+            stage1_cache_key: String::new(),
+            request_id: "phrase".to_string(),
+            cache_key: key,
+            front: FlashcardContent::new("잘\t지내세요"),
+            back: FlashcardContent {
+                example_sentence: Some("안녕히 가세요.\n잘 지내세요.".to_string()),
+                ..FlashcardContent::new("Take care")
+            },
+            card_type: CardType::Standard,
+            learning_order: None,
+            related_cards: vec![],
+            tsv_output: String::new(),
+            created_at: Utc::now(),
+        };
+        result.tag_if_phrase(&phrase);
+        result.tag_if_phrase(&phrase);
+        result.tag_if_phrase(&item("학교"));
+        assert_eq!(result.front.grammatical_tags, vec![PHRASE_TAG]);
+        
+        let row = result.to_tsv_row();
+        assert_eq!(row.split('\t').collect::<Vec<_>>(), [
+            "잘 지내세요",
+            "Take care",
+            "안녕히 가세요. 잘 지내세요.",
+            "",
+        ]);
+    }
+
+    #[test]
+    fn test_stage2_mode_separates_cache_keys() {
+        let item = VocabularyItem::new("학교".to_string(), "school".to_string(), "places".to_string());
+        let stage1_key = Stage1Result::generate_cache_key(&item);
+        let key = |mode| Stage2Result::generate_cache_key_for_mode(
+            &item,
+            &stage1_key,
+            KeyNormalization::default(),
+            mode,
+        );
+
+        let full = key(Stage2Mode::Full);
+        let minimal = key(Stage2Mode::Minimal);
+        assert_eq!(full, Stage2Result::generate_cache_key(&item, &stage1_key));
+        assert_ne!(full, minimal);
+
+        assert_eq!(Stage2Mode::of_cache_key(&full), Stage2Mode::Full);
+        assert_eq!(Stage2Mode::of_cache_key(&minimal), Stage2Mode::Minimal);
+    }
+
+    #[test]
+    fn test_batch_diff_matches_by_term() {
+        let card = |term: &str, back: &str| {
+            let item = VocabularyItem::new(term.to_string(), back.to_string(), "test".to_string());
+            let result = Stage2Result {
+                vocabulary_id: 1,
+                stage1_cache_key: String::new(),
+                request_id: "diff".to_string(),
+                cache_key: format!("key-{}", term),
+                front: FlashcardContent::new(term.trim()),
+                back: FlashcardContent::new(back),
+                card_type: CardType::Standard,
+                learning_order: None,
+                related_cards: vec![],
+                tsv_output: String::new(),
+                created_at: Utc::now(),
+            };
+            (item, result)
+        };
+        
+        // Same terms in a different order, one card reworded, one term
+        // dropped and one added
+        let old = vec![card("학교", "school"), card("바다", "sea"), card("사과", "apple")];
+        let new = vec![card("사과", "apple"), card("학교 ", "school building"), card("산", "mountain")];
+        
+        let diff = BatchDiff::between(&old, &new);
+        
+        assert_eq!(diff.unchanged, 1);
+        assert_eq!(diff.changed.len(), 1);
+        assert_eq!(diff.changed[0].term, "학교");
+        assert_eq!(diff.changed[0].changes[0].path, "back.primary_field");
+        assert_eq!(diff.added, vec!["산"]);
+        assert_eq!(diff.removed, vec!["바다"]);
+        assert_eq!(diff.compared(), 2);
+    }
+
+    #[test]
+    fn test_stage2_result_round_trip() {
+        let result = Stage2Result {
+            vocabulary_id: 7,
+            stage1_cache_key: "stage1_abc".to_string(),
+            request_id: "req-1".to_string(),
+            cache_key: "def".to_string(),
+            front: FlashcardContent {
+                pronunciation_guide: Some("[hak.k͈jo]".to_string()),
+                mnemonic_aid: Some("A school bell".to_string()),
+                difficulty_level: DifficultyLevel::Beginner,
+                frequency_level: FrequencyLevel::VeryCommon,
+                thematic_tags: vec!["education".to_string()],
+                grammatical_tags: vec!["noun".to_string()],
+                ..FlashcardContent::new("학교")
+            },
+            back: FlashcardContent {
+                example_sentence: Some("학교에 가요.".to_string()),
+                example_translation: Some("I go to school.".to_string()),
+                ..FlashcardContent::new("school")
+            },
+            card_type: CardType::Cloze,
+            learning_order: Some(3),
+            related_cards: vec!["학생".to_string()],
+            tsv_output: "학교\tschool".to_string(),
+            created_at: Utc::now(),
+        };
+        
+        let json = serde_json::to_string(&result).unwrap();
+        let restored: Stage2Result = serde_json::from_str(&json).unwrap();
+        
+        assert!(Stage2Diff::between(&result, &restored).is_empty());
+        assert_eq!(restored.cache_key, result.cache_key);
+        assert_eq!(restored.created_at, result.created_at);
+    }
+
+    #[test]
+    fn test_legacy_card_converts() {
+        // Shape of a card cached before FlashcardContent, without
+        // example_translation, which came later still
+        let json = serde_json::json!({
+            "front": {
+                "primary_content": "학교",
+                "secondary_content": null,
+                "example": null,
+                "pronunciation": "[hak.k͈jo]",
+                "notes": null,
+                "media_references": []
+            },
+            "back": {
+                "primary_content": "school",
+                "secondary_content": "place of learning",
+                "example": "학교에 가요.",
+                "pronunciation": null,
+                "notes": "Also the building",
+                "media_references": []
+            },
+            "tags": ["education"],
+            "deck_name": "Korean",
+            "card_type": "basic"
+        });
+        
+        let card: LegacyCard = serde_json::from_value(json).unwrap();
+        let (front, back, card_type) = card.into_sides();
+        
+        assert_eq!(card_type, CardType::Standard);
+        assert_eq!(front.primary_field, "학교");
+        assert_eq!(front.pronunciation_guide.as_deref(), Some("[hak.k͈jo]"));
+        assert_eq!(front.thematic_tags, vec!["education"]);
+        assert_eq!(back.secondary_field.as_deref(), Some("place of learning"));
+        assert_eq!(back.example_sentence.as_deref(), Some("학교에 가요."));
+        assert_eq!(back.example_translation, None);
+        assert_eq!(back.usage_notes.as_deref(), Some("Also the building"));
+    }
+}
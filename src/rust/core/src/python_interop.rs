@@ -6,7 +6,7 @@ use std::collections::HashMap;
 use crate::models::{
     VocabularyItem, DifficultyLevel, Stage1Result, Stage2Result,
     SemanticAnalysis, FrequencyLevel, FormalityLevel,
-    FlashcardContent, CardFace, CardType,
+    FlashcardContent, CardType,
 };
 
 impl ToPyObject for DifficultyLevel {
@@ -244,67 +244,30 @@ pub fn convert_stage2_result_from_py(py_obj: &PyAny) -> PyResult<Stage2Result> {
         .ok_or_else(|| PyErr::new::<pyo3::exceptions::PyKeyError, _>("Missing cache_key"))?
         .extract()?;
     
-    let flashcard_dict = dict.get_item("flashcard_content")
-        .ok_or_else(|| PyErr::new::<pyo3::exceptions::PyKeyError, _>("Missing flashcard_content"))?
-        .downcast::<PyDict>()?;
+    let front = convert_flashcard_content_from_py(
+        dict.get_item("front")
+            .ok_or_else(|| PyErr::new::<pyo3::exceptions::PyKeyError, _>("Missing front"))?
+            .downcast::<PyDict>()?,
+    )?;
     
-    let front_dict = flashcard_dict.get_item("front")
-        .ok_or_else(|| PyErr::new::<pyo3::exceptions::PyKeyError, _>("Missing front"))?
-        .downcast::<PyDict>()?;
+    let back = convert_flashcard_content_from_py(
+        dict.get_item("back")
+            .ok_or_else(|| PyErr::new::<pyo3::exceptions::PyKeyError, _>("Missing back"))?
+            .downcast::<PyDict>()?,
+    )?;
     
-    let back_dict = flashcard_dict.get_item("back")
-        .ok_or_else(|| PyErr::new::<pyo3::exceptions::PyKeyError, _>("Missing back"))?
-        .downcast::<PyDict>()?;
+    let card_type = dict.get_item("card_type")
+        .ok_or_else(|| PyErr::new::<pyo3::exceptions::PyKeyError, _>("Missing card_type"))?
+        .extract::<String>()?
+        .parse()
+        .map_err(|_| PyErr::new::<pyo3::exceptions::PyValueError, _>("Invalid card_type"))?;
     
-    let front = CardFace {
-        primary_content: front_dict.get_item("primary_content")
-            .ok_or_else(|| PyErr::new::<pyo3::exceptions::PyKeyError, _>("Missing primary_content"))?
-            .extract()?,
-        secondary_content: front_dict.get_item("secondary_content")
-            .and_then(|v| v.extract().ok()),
-        example: front_dict.get_item("example")
-            .and_then(|v| v.extract().ok()),
-        pronunciation: front_dict.get_item("pronunciation")
-            .and_then(|v| v.extract().ok()),
-        notes: front_dict.get_item("notes")
-            .and_then(|v| v.extract().ok()),
-        media_references: front_dict.get_item("media_references")
-            .unwrap_or(&PyList::empty(front_dict.py()).into())
-            .extract()?,
-    };
+    let learning_order: Option<i32> = dict.get_item("learning_order")
+        .and_then(|v| v.extract().ok());
     
-    let back = CardFace {
-        primary_content: back_dict.get_item("primary_content")
-            .ok_or_else(|| PyErr::new::<pyo3::exceptions::PyKeyError, _>("Missing primary_content"))?
-            .extract()?,
-        secondary_content: back_dict.get_item("secondary_content")
-            .and_then(|v| v.extract().ok()),
-        example: back_dict.get_item("example")
-            .and_then(|v| v.extract().ok()),
-        pronunciation: back_dict.get_item("pronunciation")
-            .and_then(|v| v.extract().ok()),
-        notes: back_dict.get_item("notes")
-            .and_then(|v| v.extract().ok()),
-        media_references: back_dict.get_item("media_references")
-            .unwrap_or(&PyList::empty(back_dict.py()).into())
-            .extract()?,
-    };
-    
-    let flashcard_content = FlashcardContent {
-        front,
-        back,
-        tags: flashcard_dict.get_item("tags")
-            .unwrap_or(&PyList::empty(flashcard_dict.py()).into())
-            .extract()?,
-        deck_name: flashcard_dict.get_item("deck_name")
-            .ok_or_else(|| PyErr::new::<pyo3::exceptions::PyKeyError, _>("Missing deck_name"))?
-            .extract()?,
-        card_type: flashcard_dict.get_item("card_type")
-            .ok_or_else(|| PyErr::new::<pyo3::exceptions::PyKeyError, _>("Missing card_type"))?
-            .extract::<String>()?
-            .parse()
-            .map_err(|_| PyErr::new::<pyo3::exceptions::PyValueError, _>("Invalid card_type"))?,
-    };
+    let related_cards: Vec<String> = dict.get_item("related_cards")
+        .unwrap_or(&PyList::empty(dict.py()).into())
+        .extract()?;
     
     let tsv_output: String = dict.get_item("tsv_output")
         .ok_or_else(|| PyErr::new::<pyo3::exceptions::PyKeyError, _>("Missing tsv_output"))?
@@ -315,12 +278,54 @@ pub fn convert_stage2_result_from_py(py_obj: &PyAny) -> PyResult<Stage2Result> {
         stage1_cache_key,
         request_id,
         cache_key,
-        flashcard_content,
+        front,
+        back,
+        card_type,
+        learning_order,
+        related_cards,
         tsv_output,
         created_at: Utc::now(),
     })
 }
 
+fn convert_flashcard_content_from_py(face_dict: &PyDict) -> PyResult<FlashcardContent> {
+    let optional = |key: &str| -> Option<String> {
+        face_dict.get_item(key).and_then(|v| v.extract().ok())
+    };
+    let tags = |key: &str| -> PyResult<Vec<String>> {
+        face_dict.get_item(key)
+            .unwrap_or(&PyList::empty(face_dict.py()).into())
+            .extract()
+    };
+    
+    Ok(FlashcardContent {
+        primary_field: face_dict.get_item("primary_field")
+            .ok_or_else(|| PyErr::new::<pyo3::exceptions::PyKeyError, _>("Missing primary_field"))?
+            .extract()?,
+        secondary_field: optional("secondary_field"),
+        tertiary_field: optional("tertiary_field"),
+        example_sentence: optional("example_sentence"),
+        example_translation: optional("example_translation"),
+        pronunciation_guide: optional("pronunciation_guide"),
+        image_prompt: optional("image_prompt"),
+        mnemonic_aid: optional("mnemonic_aid"),
+        grammar_notes: optional("grammar_notes"),
+        cultural_notes: optional("cultural_notes"),
+        usage_notes: optional("usage_notes"),
+        difficulty_level: face_dict.get_item("difficulty_level")
+            .ok_or_else(|| PyErr::new::<pyo3::exceptions::PyKeyError, _>("Missing difficulty_level"))?
+            .extract()?,
+        frequency_level: face_dict.get_item("frequency_level")
+            .ok_or_else(|| PyErr::new::<pyo3::exceptions::PyKeyError, _>("Missing frequency_level"))?
+            .extract::<String>()?
+            .parse()
+            .map_err(|_| PyErr::new::<pyo3::exceptions::PyValueError, _>("Invalid frequency_level"))?,
+        thematic_tags: tags("thematic_tags")?,
+        grammatical_tags: tags("grammatical_tags")?,
+        style_register: optional("style_register"),
+    })
+}
+
 impl std::str::FromStr for FrequencyLevel {
     type Err = String;
     
@@ -356,7 +361,7 @@ impl std::str::FromStr for CardType {
     
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         match s.to_lowercase().as_str() {
-            "basic" => Ok(CardType::Basic),
+            "standard" | "basic" => Ok(CardType::Standard),
             "basicreversed" | "basic_reversed" => Ok(CardType::BasicReversed),
             "cloze" => Ok(CardType::Cloze),
             "production" => Ok(CardType::Production),
@@ -0,0 +1,198 @@
+//! Optional cleanup of source terms before they are enriched.
+
+use std::collections::HashSet;
+
+use crate::models::{is_phrase, VocabularyItem};
+
+/// Trailing particles stripped by default, longest first so `에서` wins
+/// over `에`. Particles that commonly end ordinary nouns (`도`, `의`, `로`)
+/// are left out.
+pub const DEFAULT_PARTICLES: &[&str] = &[
+    "에서", "에게", "한테", "으로", "부터", "까지", "처럼",
+    "에", "을", "를", "이", "가", "은", "는", "와", "과",
+];
+
+/// Words that end in something particle-shaped but are whole nouns, e.g.
+/// `고양이` (cat) isn't `고양` + `이`. Left unchanged by default.
+pub const DEFAULT_EXCEPTIONS: &[&str] = &[
+    "고양이", "어린이", "원숭이", "호랑이", "지팡이", "정치가",
+];
+
+/// Shortest remainder stripping may leave by default. Two characters keeps
+/// `결과` and `휴가` whole at the cost of leaving `밥을` alone.
+pub const DEFAULT_MIN_STEM_CHARS: usize = 2;
+
+/// Metadata key under which [`TermNormalizer::apply`] keeps the original term
+pub const ORIGINAL_TERM_KEY: &str = "original_term";
+
+/// Strips particles (josa) from terms so the dictionary form is enriched,
+/// e.g. `학교에서` becomes `학교`.
+///
+/// Stripping is conservative: the term must be a single word that isn't in
+/// the exception list, the remainder must keep at least `min_stem_chars`
+/// characters, and particles that depend on the preceding sound (`을`/`를`,
+/// `이`/`가`, ...) must match it. So `사과` (apple) is kept whole, since `과`
+/// only follows a final consonant.
+#[derive(Debug, Clone)]
+pub struct TermNormalizer {
+    particles: Vec<String>,
+    exceptions: HashSet<String>,
+    min_stem_chars: usize,
+}
+
+impl Default for TermNormalizer {
+    fn default() -> Self {
+        Self::new(DEFAULT_PARTICLES.iter().copied())
+    }
+}
+
+impl TermNormalizer {
+    pub fn new<I, S>(particles: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        let mut particles: Vec<String> = particles.into_iter().map(Into::into).collect();
+        particles.sort_by_key(|particle| std::cmp::Reverse(particle.chars().count()));
+
+        Self {
+            particles,
+            exceptions: DEFAULT_EXCEPTIONS.iter().map(|word| word.to_string()).collect(),
+            min_stem_chars: DEFAULT_MIN_STEM_CHARS,
+        }
+    }
+
+    /// Add words that are never stripped.
+    pub fn with_exceptions<I, S>(mut self, words: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.exceptions.extend(words.into_iter().map(Into::into));
+        self
+    }
+
+    /// Shortest remainder, in characters, that stripping may leave.
+    pub fn with_min_stem_chars(mut self, min_stem_chars: usize) -> Self {
+        self.min_stem_chars = min_stem_chars.max(1);
+        self
+    }
+
+    /// The term with one trailing particle removed, or unchanged when no
+    /// particle can be stripped safely.
+    pub fn normalize_term(&self, term: &str) -> String {
+        let term = term.trim();
+        if is_phrase(term) || self.exceptions.contains(term) {
+            return term.to_string();
+        }
+
+        for particle in &self.particles {
+            let Some(stem) = term.strip_suffix(particle.as_str()) else {
+                continue;
+            };
+            if stem.chars().count() < self.min_stem_chars {
+                continue;
+            }
+            if let Some(last) = stem.chars().last() {
+                if attaches_to(particle, last) {
+                    return stem.to_string();
+                }
+            }
+        }
+
+        term.to_string()
+    }
+
+    /// Normalize `item.korean` in place, keeping the original in the item's
+    /// metadata. Returns whether the term changed.
+    pub fn apply(&self, item: &mut VocabularyItem) -> bool {
+        let normalized = self.normalize_term(&item.korean);
+        if normalized == item.korean {
+            return false;
+        }
+
+        let original = std::mem::replace(&mut item.korean, normalized);
+        item.metadata.insert(ORIGINAL_TERM_KEY.to_string(), serde_json::Value::String(original));
+        true
+    }
+}
+
+/// Whether `particle` can follow a syllable ending in `last`. Particles with
+/// consonant/vowel variants only follow the matching kind of syllable.
+fn attaches_to(particle: &str, last: char) -> bool {
+    match particle {
+        "을" | "은" | "이" | "과" | "으로" => final_consonant(last).is_some_and(|index| index != 0),
+        "를" | "는" | "가" | "와" => final_consonant(last) == Some(0),
+        _ => true,
+    }
+}
+
+/// Index of a Hangul syllable's final consonant (0 for none), or `None`
+/// for characters that aren't precomposed Hangul syllables.
+fn final_consonant(syllable: char) -> Option<u32> {
+    let code = syllable as u32;
+    (0xAC00..=0xD7A3).contains(&code).then(|| (code - 0xAC00) % 28)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_strips_particles() {
+        let normalizer = TermNormalizer::default();
+
+        assert_eq!(normalizer.normalize_term("학교에서"), "학교");
+        assert_eq!(normalizer.normalize_term("친구가"), "친구");
+
+        // One-syllable stems need a lower minimum
+        let lenient = TermNormalizer::default().with_min_stem_chars(1);
+        assert_eq!(lenient.normalize_term("밥을"), "밥");
+    }
+
+    #[test]
+    fn test_leaves_words_that_only_look_inflected() {
+        let normalizer = TermNormalizer::default();
+
+        // 과 follows a final consonant, so 사과 is a word, not 사 + 과
+        assert_eq!(normalizer.normalize_term("사과"), "사과");
+        // Likewise 나이 (age) is not 나 + 이
+        assert_eq!(normalizer.normalize_term("나이"), "나이");
+        // Nothing would be left
+        assert_eq!(normalizer.normalize_term("에서"), "에서");
+        // Phrases are left alone
+        assert_eq!(normalizer.normalize_term("학교에 가요"), "학교에 가요");
+
+        let strict = TermNormalizer::default().with_min_stem_chars(3);
+        assert_eq!(strict.normalize_term("친구가"), "친구가");
+    }
+
+    #[test]
+    fn test_keeps_nouns_ending_in_particle_syllables() {
+        let normalizer = TermNormalizer::default();
+
+        for word in ["고양이", "어린이", "결과", "휴가"] {
+            assert_eq!(normalizer.normalize_term(word), word);
+        }
+
+        assert_eq!(normalizer.normalize_term("멍멍이"), "멍멍");
+        let custom = TermNormalizer::default().with_exceptions(["멍멍이"]);
+        assert_eq!(custom.normalize_term("멍멍이"), "멍멍이");
+    }
+
+    #[test]
+    fn test_apply_keeps_original_in_metadata() {
+        let normalizer = TermNormalizer::default();
+        let mut item = VocabularyItem::new(
+            "학교에서".to_string(),
+            "at school".to_string(),
+            "places".to_string(),
+        );
+
+        assert!(normalizer.apply(&mut item));
+        assert_eq!(item.korean, "학교");
+        assert_eq!(item.metadata[ORIGINAL_TERM_KEY], serde_json::json!("학교에서"));
+
+        assert!(!normalizer.apply(&mut item));
+    }
+}
@@ -1,13 +1,15 @@
 use async_trait::async_trait;
 use crate::models::{
-    VocabularyItem, Stage1Result, Stage2Result, QueueItem, BatchProgress,
+    VocabularyItem, Stage1Result, Stage2Result, QueueItem, BatchId, BatchProgress, BatchSummary,
     ProcessingCheckpoint, ProcessingStatus, ProcessingStage, CacheStats,
-    CacheType, PipelineError
+    CacheType, CacheEntry, CacheImportStats, CacheMigrationStats, CacheStatsSnapshot, KeyNormalization, PipelineError
 };
 
 #[async_trait]
 pub trait VocabularyRepository: Send + Sync {
     async fn create(&self, item: &VocabularyItem) -> Result<i64, PipelineError>;
+    async fn create_many(&self, items: &[VocabularyItem]) -> Result<Vec<i64>, PipelineError>;
+    async fn upsert(&self, item: &VocabularyItem) -> Result<i64, PipelineError>;
     async fn get_by_id(&self, id: i64) -> Result<Option<VocabularyItem>, PipelineError>;
     async fn find_by_content(
         &self, 
@@ -17,6 +19,7 @@ pub trait VocabularyRepository: Send + Sync {
     ) -> Result<Option<VocabularyItem>, PipelineError>;
     async fn list_by_category(&self, category: &str) -> Result<Vec<VocabularyItem>, PipelineError>;
     async fn list_unprocessed(&self, limit: i32) -> Result<Vec<VocabularyItem>, PipelineError>;
+    async fn list_incomplete_in_batch(&self, batch_id: &BatchId) -> Result<Vec<VocabularyItem>, PipelineError>;
     async fn update(&self, item: &VocabularyItem) -> Result<(), PipelineError>;
     async fn delete(&self, id: i64) -> Result<bool, PipelineError>;
     async fn count(&self) -> Result<i64, PipelineError>;
@@ -42,31 +45,107 @@ pub trait CacheRepository: Send + Sync {
         model_used: String,
     ) -> Result<(), PipelineError>;
     
+    async fn get_stage2_results_for_batch_in(
+        &self,
+        batch_id: &BatchId,
+        namespace: &str,
+    ) -> Result<Vec<(VocabularyItem, Stage2Result)>, PipelineError>;
     async fn get_cache_stats(&self) -> Result<CacheStats, PipelineError>;
+    async fn snapshot_stats(&self) -> Result<CacheStatsSnapshot, PipelineError>;
+    async fn stats_history(
+        &self,
+        since: chrono::DateTime<chrono::Utc>,
+    ) -> Result<Vec<CacheStatsSnapshot>, PipelineError>;
     async fn clear_cache(&self, cache_type: Option<CacheType>) -> Result<i64, PipelineError>;
+    async fn clear_cache_by_model(&self, model: &str) -> Result<i64, PipelineError>;
+    async fn get_request_hash(
+        &self,
+        cache_type: CacheType,
+        cache_key: &str,
+    ) -> Result<Option<String>, PipelineError>;
+    async fn delete_cache_entry(
+        &self,
+        cache_type: CacheType,
+        cache_key: &str,
+    ) -> Result<bool, PipelineError>;
+    async fn get_cached_token_count(
+        &self,
+        cache_type: CacheType,
+        cache_key: &str,
+    ) -> Result<Option<i32>, PipelineError>;
+    async fn get_warm_checkpoint(
+        &self,
+        warm_key: &str,
+    ) -> Result<Option<(usize, serde_json::Value)>, PipelineError>;
+    async fn save_warm_checkpoint(
+        &self,
+        warm_key: &str,
+        next_index: usize,
+        stats: &serde_json::Value,
+    ) -> Result<(), PipelineError>;
+    async fn clear_warm_checkpoint(&self, warm_key: &str) -> Result<bool, PipelineError>;
+    async fn export_all(
+        &self,
+        sink: &mut (dyn FnMut(CacheEntry) -> Result<(), PipelineError> + Send),
+    ) -> Result<usize, PipelineError>;
+    async fn import_all(
+        &self,
+        entries: &[CacheEntry],
+        overwrite: bool,
+    ) -> Result<CacheImportStats, PipelineError>;
+    async fn find_cold_entries(
+        &self,
+        min_age: chrono::Duration,
+        max_access_count: i32,
+    ) -> Result<Vec<CacheEntry>, PipelineError>;
+    async fn prune_cold_entries(
+        &self,
+        min_age: chrono::Duration,
+        max_access_count: i32,
+    ) -> Result<u64, PipelineError>;
+    async fn migrate_keys(
+        &self,
+        normalization: KeyNormalization,
+        dry_run: bool,
+    ) -> Result<CacheMigrationStats, PipelineError>;
 }
 
 #[async_trait]
 pub trait QueueRepository: Send + Sync {
-    async fn enqueue_batch(&self, vocabulary_ids: Vec<i64>, batch_id: &str) -> Result<i64, PipelineError>;
-    async fn get_next_pending(&self, batch_id: Option<&str>) -> Result<Option<QueueItem>, PipelineError>;
+    async fn enqueue_batch(
+        &self,
+        vocabulary_ids: Vec<i64>,
+        batch_id: &BatchId,
+        max_retries: i32,
+        label: Option<&str>,
+    ) -> Result<i64, PipelineError>;
+    async fn get_next_pending(&self, batch_id: Option<&BatchId>) -> Result<Option<QueueItem>, PipelineError>;
+    async fn get_incomplete_items(&self, batch_id: &BatchId) -> Result<Vec<QueueItem>, PipelineError>;
+    async fn count_pending(&self) -> Result<i64, PipelineError>;
     async fn update_status(
         &self, 
         item_id: i64, 
         status: ProcessingStatus,
         error_message: Option<String>
     ) -> Result<(), PipelineError>;
+    async fn update_status_batch(
+        &self,
+        updates: &[(i64, ProcessingStatus)],
+    ) -> Result<u64, PipelineError>;
+    async fn record_cache_keys(&self, keys: &[(i64, String)]) -> Result<u64, PipelineError>;
     async fn complete_stage(&self, item_id: i64) -> Result<ProcessingStage, PipelineError>;
     async fn increment_retry(&self, item_id: i64) -> Result<bool, PipelineError>;
-    async fn get_batch_progress(&self, batch_id: &str) -> Result<BatchProgress, PipelineError>;
+    async fn list_batches(&self, label: Option<&str>, limit: i64) -> Result<Vec<BatchSummary>, PipelineError>;
+    async fn list_unstarted_batches(&self, limit: i64) -> Result<Vec<BatchSummary>, PipelineError>;
+    async fn get_batch_progress(&self, batch_id: &BatchId) -> Result<BatchProgress, PipelineError>;
     async fn save_checkpoint(
         &self,
-        batch_id: &str,
+        batch_id: &BatchId,
         last_processed_id: i64,
         stage: ProcessingStage,
         checkpoint_data: serde_json::Value,
     ) -> Result<(), PipelineError>;
-    async fn get_latest_checkpoint(&self, batch_id: &str) -> Result<Option<ProcessingCheckpoint>, PipelineError>;
+    async fn get_latest_checkpoint(&self, batch_id: &BatchId) -> Result<Option<ProcessingCheckpoint>, PipelineError>;
 }
 
 #[async_trait]
@@ -90,10 +169,10 @@ pub struct RateLimitStatus {
 
 #[async_trait]
 pub trait Pipeline: Send + Sync {
-    async fn process_batch(&self, batch_id: &str) -> Result<BatchProgress, PipelineError>;
+    async fn process_batch(&self, batch_id: &BatchId) -> Result<BatchProgress, PipelineError>;
     async fn process_single(&self, vocabulary_item: &VocabularyItem) -> Result<String, PipelineError>;
-    async fn resume_batch(&self, batch_id: &str) -> Result<BatchProgress, PipelineError>;
-    async fn get_batch_status(&self, batch_id: &str) -> Result<BatchProgress, PipelineError>;
+    async fn resume_batch(&self, batch_id: &BatchId) -> Result<BatchProgress, PipelineError>;
+    async fn get_batch_status(&self, batch_id: &BatchId) -> Result<BatchProgress, PipelineError>;
 }
 
 #[async_trait]
@@ -0,0 +1,7 @@
+-- Batch labels
+-- Version: 2
+-- Description: Tag batches with a free-form label so they can be filtered
+
+ALTER TABLE batch_metadata ADD COLUMN label TEXT;
+
+CREATE INDEX IF NOT EXISTS idx_batch_metadata_label ON batch_metadata(label);
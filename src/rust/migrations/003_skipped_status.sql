@@ -0,0 +1,37 @@
+-- Skipped queue status
+-- Version: 3
+-- Description: Allow queue items to be marked skipped and count them per batch
+
+-- SQLite can't alter a CHECK constraint, so rebuild the queue table
+CREATE TABLE processing_queue_new (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    vocabulary_id INTEGER NOT NULL,
+    batch_id TEXT NOT NULL,
+    status TEXT NOT NULL CHECK (status IN ('pending', 'in_progress', 'completed', 'failed', 'quarantined', 'skipped')),
+    stage TEXT NOT NULL CHECK (stage IN ('stage1', 'stage2', 'complete')),
+    retry_count INTEGER NOT NULL DEFAULT 0,
+    max_retries INTEGER NOT NULL DEFAULT 3,
+    error_message TEXT,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    started_at TIMESTAMP,
+    completed_at TIMESTAMP,
+    FOREIGN KEY (vocabulary_id) REFERENCES vocabulary_items(id) ON DELETE CASCADE
+);
+
+INSERT INTO processing_queue_new SELECT * FROM processing_queue;
+DROP TABLE processing_queue;
+ALTER TABLE processing_queue_new RENAME TO processing_queue;
+
+CREATE INDEX IF NOT EXISTS idx_queue_batch_id ON processing_queue(batch_id);
+CREATE INDEX IF NOT EXISTS idx_queue_status ON processing_queue(status);
+CREATE INDEX IF NOT EXISTS idx_queue_vocab_id ON processing_queue(vocabulary_id);
+CREATE INDEX IF NOT EXISTS idx_queue_stage ON processing_queue(stage);
+
+CREATE TRIGGER IF NOT EXISTS update_queue_timestamp 
+AFTER UPDATE ON processing_queue
+BEGIN
+    UPDATE processing_queue SET updated_at = CURRENT_TIMESTAMP WHERE id = NEW.id;
+END;
+
+ALTER TABLE batch_metadata ADD COLUMN skipped_items INTEGER NOT NULL DEFAULT 0;
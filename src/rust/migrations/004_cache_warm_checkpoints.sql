@@ -0,0 +1,10 @@
+-- Cache warm checkpoints
+-- Version: 4
+-- Description: Record how far a cache warm got so an interrupted run can resume
+
+CREATE TABLE IF NOT EXISTS cache_warm_checkpoints (
+    warm_key TEXT PRIMARY KEY,
+    next_index INTEGER NOT NULL,
+    stats TEXT NOT NULL, -- JSON
+    updated_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
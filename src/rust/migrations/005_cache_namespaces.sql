@@ -0,0 +1,9 @@
+-- Cache namespaces
+-- Version: 5
+-- Description: Record which tenant's namespace each cache entry belongs to so it can be cleared on its own
+
+ALTER TABLE stage1_cache ADD COLUMN namespace TEXT NOT NULL DEFAULT '';
+ALTER TABLE stage2_cache ADD COLUMN namespace TEXT NOT NULL DEFAULT '';
+
+CREATE INDEX IF NOT EXISTS idx_stage1_cache_namespace ON stage1_cache(namespace);
+CREATE INDEX IF NOT EXISTS idx_stage2_cache_namespace ON stage2_cache(namespace);
@@ -0,0 +1,18 @@
+-- API call log
+-- Version: 6
+-- Description: Record every API call with its model, tokens and latency for cost tracking and reproducibility
+
+CREATE TABLE IF NOT EXISTS api_call_log (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    batch_id TEXT,
+    term TEXT NOT NULL,
+    stage TEXT NOT NULL CHECK (stage IN ('stage1', 'stage2')),
+    model TEXT NOT NULL,
+    total_tokens INTEGER NOT NULL DEFAULT 0,
+    latency_ms INTEGER NOT NULL,
+    success BOOLEAN NOT NULL,
+    error_message TEXT,
+    called_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS idx_api_call_log_batch ON api_call_log(batch_id, id);
@@ -0,0 +1,10 @@
+-- Card state
+-- Version: 7
+-- Description: Per-term card state kept across exports, such as suspension and leech marks
+
+CREATE TABLE IF NOT EXISTS card_state (
+    term TEXT PRIMARY KEY,
+    suspended BOOLEAN NOT NULL DEFAULT 0,
+    leech BOOLEAN NOT NULL DEFAULT 0,
+    updated_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
@@ -0,0 +1,15 @@
+-- Cache statistics history
+-- Version: 8
+-- Description: Snapshots of cache statistics over time, for tracking cache growth against cost savings
+
+CREATE TABLE IF NOT EXISTS cache_stats_history (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    stage1_entries INTEGER NOT NULL,
+    stage2_entries INTEGER NOT NULL,
+    total_hits INTEGER NOT NULL,
+    total_misses INTEGER NOT NULL,
+    total_tokens_saved INTEGER NOT NULL,
+    recorded_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS idx_cache_stats_history_recorded ON cache_stats_history(recorded_at);
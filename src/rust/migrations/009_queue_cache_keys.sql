@@ -0,0 +1,5 @@
+-- Queue cache keys
+-- Version: 9
+-- Description: The Stage 2 cache key each completed queue item's card was stored under, so a batch's own cards can be told apart from a later batch's
+
+ALTER TABLE processing_queue ADD COLUMN stage2_cache_key TEXT;
@@ -0,0 +1,159 @@
+//! Anki note type presets for TSV export.
+//!
+//! Anki maps TSV columns to note fields by position, so each preset emits
+//! exactly the fields of its note type, with tags in the last column, and a
+//! file header made of the `#directive:value` lines Anki reads on import.
+
+use flashcard_core::models::{VocabularyItem, Stage2Result, CardType};
+use serde::{Deserialize, Serialize};
+
+/// Note types with a known field layout
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum AnkiNoteType {
+    Basic,
+    BasicReversed,
+    Cloze,
+    /// Term, pronunciation, meaning and example fields for vocabulary decks
+    Korean,
+}
+
+impl AnkiNoteType {
+    /// The note type name as it appears in Anki
+    pub fn name(&self) -> &'static str {
+        match self {
+            AnkiNoteType::Basic => "Basic",
+            AnkiNoteType::BasicReversed => "Basic (and reversed card)",
+            AnkiNoteType::Cloze => "Cloze",
+            AnkiNoteType::Korean => "Korean Vocabulary",
+        }
+    }
+    
+    /// Field names in the order Anki expects them
+    pub fn field_names(&self) -> &'static [&'static str] {
+        match self {
+            AnkiNoteType::Basic | AnkiNoteType::BasicReversed => &["Front", "Back"],
+            AnkiNoteType::Cloze => &["Text", "Back Extra"],
+            AnkiNoteType::Korean => &[
+                "Korean",
+                "Pronunciation",
+                "Part of Speech",
+                "Meaning",
+                "Example",
+                "Example Translation",
+                "Mnemonic",
+                "Notes",
+            ],
+        }
+    }
+    
+    /// The note type to use for `card_type`; cloze cards only work as Cloze notes.
+    pub fn for_card(self, card_type: &CardType) -> Self {
+        match card_type {
+            CardType::Cloze => AnkiNoteType::Cloze,
+            _ => self,
+        }
+    }
+    
+    /// Field values for one card, in [`field_names`](Self::field_names) order.
+    pub fn fields(&self, item: &VocabularyItem, stage2: &Stage2Result) -> Vec<String> {
+        let front = &stage2.front;
+        let back = &stage2.back;
+        
+        match self {
+            AnkiNoteType::Basic | AnkiNoteType::BasicReversed => vec![
+                front.primary_field.clone(),
+                back.primary_field.clone(),
+            ],
+            AnkiNoteType::Cloze => vec![
+                cloze_text(&item.term, front.example_sentence.as_deref()),
+                back.primary_field.clone(),
+            ],
+            AnkiNoteType::Korean => vec![
+                item.term.clone(),
+                front.pronunciation_guide.clone().unwrap_or_default(),
+                item.word_type.clone().unwrap_or_default(),
+                back.primary_field.clone(),
+                front.example_sentence.clone().unwrap_or_default(),
+                front.example_translation.clone().unwrap_or_default(),
+                front.mnemonic_aid.clone().unwrap_or_default(),
+                back.usage_notes.clone().unwrap_or_default(),
+            ],
+        }
+    }
+}
+
+/// Export settings for a target note type and, optionally, deck.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct AnkiPreset {
+    pub note_type: AnkiNoteType,
+    pub deck: Option<String>,
+}
+
+impl AnkiPreset {
+    pub fn new(note_type: AnkiNoteType) -> Self {
+        Self { note_type, deck: None }
+    }
+    
+    pub fn with_deck(mut self, deck: impl Into<String>) -> Self {
+        self.deck = Some(deck.into());
+        self
+    }
+    
+    /// Field columns per row: wide enough for the preset and for Cloze, since
+    /// cloze cards are routed there and the tags column must stay in place.
+    fn field_columns(&self) -> usize {
+        self.note_type.field_names().len()
+            .max(AnkiNoteType::Cloze.field_names().len())
+    }
+    
+    /// Header directives, one per line, written before any rows.
+    ///
+    /// Column 1 holds each row's note type, so mixed cloze/non-cloze exports
+    /// import into the right note types.
+    pub fn header_lines(&self) -> Vec<String> {
+        let mut lines = vec![
+            "#separator:tab".to_string(),
+            "#html:true".to_string(),
+            "#notetype column:1".to_string(),
+        ];
+        if let Some(deck) = &self.deck {
+            lines.push(format!("#deck:{}", deck));
+        }
+        lines.push(format!("#tags column:{}", self.field_columns() + 2));
+        lines
+    }
+    
+    /// One row: note type, fields padded to a fixed width, then tags.
+    pub fn record(&self, item: &VocabularyItem, stage2: &Stage2Result) -> Vec<String> {
+        let note_type = self.note_type.for_card(&stage2.card_type);
+        
+        let mut record = Vec::with_capacity(self.field_columns() + 2);
+        record.push(note_type.name().to_string());
+        record.extend(note_type.fields(item, stage2));
+        record.resize(self.field_columns() + 1, String::new());
+        record.push(anki_tags(stage2));
+        record
+    }
+}
+
+/// Tags as Anki reads them: space separated, so spaces within a tag become `_`.
+fn anki_tags(stage2: &Stage2Result) -> String {
+    stage2.front.thematic_tags.iter()
+        .chain(&stage2.front.grammatical_tags)
+        .map(|tag| tag.trim().replace(' ', "_"))
+        .filter(|tag| !tag.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Blank out `term` in the example sentence, or cloze the bare term if the
+/// example doesn't contain it.
+fn cloze_text(term: &str, example: Option<&str>) -> String {
+    let cloze = format!("{{{{c1::{}}}}}", term);
+    match example {
+        Some(example) if example.contains(term) => example.replacen(term, &cloze, 1),
+        _ => cloze,
+    }
+}
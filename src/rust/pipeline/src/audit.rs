@@ -0,0 +1,81 @@
+use crate::errors::Result;
+use crate::monitoring::ApiStage;
+use crate::python_bridge::ModelSelection;
+use flashcard_core::models::{ApiCallRecord, BatchId, CacheType, VocabularyItem};
+use flashcard_core::repositories::ApiCallLogRepository;
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Instant;
+use tracing::warn;
+
+/// Records every API call to the `api_call_log` table, for cost tracking
+/// and for telling which model produced a card.
+///
+/// Each call costs a database write, so this is only on with `--audit`.
+#[derive(Clone)]
+pub struct ApiAudit {
+    log: Arc<ApiCallLogRepository>,
+    models: ModelSelection,
+    batch_id: Option<BatchId>,
+}
+
+impl ApiAudit {
+    /// Record calls to `log`, as made with the model `models` picks for
+    /// each stage
+    pub fn new(log: Arc<ApiCallLogRepository>, models: ModelSelection) -> Self {
+        Self { log, models, batch_id: None }
+    }
+    
+    /// The same audit with its calls attributed to `batch_id`
+    pub fn for_batch(&self, batch_id: &BatchId) -> Self {
+        Self {
+            batch_id: Some(batch_id.clone()),
+            ..self.clone()
+        }
+    }
+    
+    /// Await `call` and record how long it took, what it used and whether
+    /// it succeeded. Failing to write the record is logged, not returned,
+    /// so auditing never fails an item.
+    pub async fn observe<T, F>(&self, item: &VocabularyItem, stage: ApiStage, call: F) -> Result<(T, usize)>
+    where
+        F: Future<Output = Result<(T, usize)>>,
+    {
+        let called_at = chrono::Utc::now();
+        let started = Instant::now();
+        let result = call.await;
+        
+        let (stage, model) = match stage {
+            ApiStage::Stage1 => (CacheType::Stage1, &self.models.stage1),
+            ApiStage::Stage2 => (CacheType::Stage2, &self.models.stage2),
+        };
+        let record = ApiCallRecord {
+            id: None,
+            batch_id: self.batch_id.clone(),
+            term: item.term.clone(),
+            stage,
+            model: model.clone(),
+            total_tokens: result.as_ref().map_or(0, |(_, tokens)| *tokens as i64),
+            latency_ms: started.elapsed().as_millis() as i64,
+            success: result.is_ok(),
+            error_message: result.as_ref().err().map(|e| e.to_string()),
+            called_at,
+        };
+        if let Err(e) = self.log.append(&record).await {
+            warn!("Failed to record API call for {}: {}", item.term, e);
+        }
+        
+        result
+    }
+}
+
+/// `call`, recorded by `audit` when auditing is on
+pub async fn audited<T, F>(audit: Option<&ApiAudit>, item: &VocabularyItem, stage: ApiStage, call: F) -> Result<(T, usize)>
+where
+    F: Future<Output = Result<(T, usize)>>,
+{
+    match audit {
+        Some(audit) => audit.observe(item, stage, call).await,
+        None => call.await,
+    }
+}
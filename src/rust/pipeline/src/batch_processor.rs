@@ -1,25 +1,213 @@
 use crate::errors::{PipelineError, Result};
-use crate::python_bridge::ApiClient;
+use crate::python_bridge::{ApiClient, ModelSelection};
+use crate::monitoring::{ApiStage, MetricsCollector};
+use crate::concurrency::{AbortOnDrop, ApiLimiter};
+use crate::retry::{with_retry_within, within, RetryPolicy};
+use crate::fallback::build_flashcard_from_stage1;
+use crate::quality::QualityGate;
+use crate::audit::{audited, ApiAudit};
 use flashcard_core::{
-    models::{VocabularyItem, Stage1Result, Stage2Result, ProcessingStatus},
+    models::{VocabularyItem, Stage1Result, Stage2Result, Stage2Mode, ProcessingStatus, ProcessingStage, BatchId, QueueItem, CacheType},
     repositories::{QueueRepository, CacheRepository},
     cache_manager::CacheManager,
+    database::ConnectionLimiter,
 };
 use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
 use tokio::sync::{Semaphore, mpsc};
 use tracing::{info, warn, error, debug, instrument};
 use indicatif::{ProgressBar, ProgressStyle, MultiProgress};
 use console::style;
 use crossbeam_channel;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::future::Future;
 use std::time::{Duration, Instant};
-use parking_lot::RwLock;
+use parking_lot::{Mutex, RwLock};
+use serde::{Deserialize, Serialize};
+use tokio_util::sync::CancellationToken;
+use futures::stream::{self, StreamExt};
+
+/// Weight given to the newest completion interval in the rate average
+pub const DEFAULT_RATE_SMOOTHING: f64 = 0.2;
+
+/// Upper bound on the final checkpoint write after a cancellation
+pub const CHECKPOINT_FLUSH_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Buffered status transitions that trigger a write
+pub const DEFAULT_STATUS_BATCH_SIZE: usize = 50;
+
+/// Longest a status transition waits in the buffer while items are in flight
+pub const DEFAULT_STATUS_FLUSH_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Cache lookups [`BatchProcessor::prefetch`] keeps in flight, on top of
+/// which the cache manager's connection limit still applies
+const PREFETCH_LOOKUPS: usize = 16;
+
+/// Fully cached results of a chunk's items by position, looked up by
+/// [`BatchProcessor::prefetch`] before the chunk starts
+pub type Prefetched = HashMap<i32, (Stage1Result, Stage2Result)>;
+
+/// Finished items needed before `max_failure_rate` is checked, so a couple
+/// of early failures can't abort a healthy batch
+pub const FAILURE_RATE_MIN_ITEMS: usize = 20;
+
+/// When to give up on a batch whose items keep failing, e.g. because the
+/// API key was revoked. Skipped items count as neither successes nor
+/// failures, and counts carry over between chunks of the same batch.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct FailureThreshold {
+    /// Abort once this many items in a row have failed
+    pub max_consecutive_failures: Option<usize>,
+    /// Abort once more than this fraction (0..=1) of finished items failed
+    pub max_failure_rate: Option<f64>,
+}
+
+impl FailureThreshold {
+    pub fn is_disabled(&self) -> bool {
+        self.max_consecutive_failures.is_none() && self.max_failure_rate.is_none()
+    }
+}
 
 pub struct BatchProcessor {
     api_client: Arc<dyn ApiClient>,
     cache_manager: Arc<CacheManager>,
     queue_repo: Arc<dyn QueueRepository>,
+    metrics_collector: Arc<MetricsCollector>,
     semaphore: Arc<Semaphore>,
+    api_limiter: ApiLimiter,
+    /// Shared with the cache manager, so queue writes and cache queries
+    /// draw on the same connection slots
+    db_limiter: ConnectionLimiter,
     progress: Arc<RwLock<ProcessingProgress>>,
+    rate_smoothing: f64,
+    retry_policy: Arc<RetryPolicy>,
+    stage1_fallback: bool,
+    quality_gate: QualityGate,
+    cancellation: CancellationToken,
+    drain: CancellationToken,
+    status_batch_size: usize,
+    status_flush_interval: Duration,
+    stage2_mode: Stage2Mode,
+    models: ModelSelection,
+    failure_threshold: FailureThreshold,
+    audit: Option<ApiAudit>,
+    max_buffered_results: Option<usize>,
+}
+
+/// Queue status transitions held back so they reach the database in a few
+/// transactions instead of one write each.
+///
+/// Transitions are keyed by queue item id, as
+/// [`QueueRepository::update_status_batch`] takes them. Only the newest
+/// status per item is kept, so an item that passes through both stages
+/// between flushes costs a single row update. The Stage 2 cache key of
+/// each generated card is held back alongside, for
+/// [`QueueRepository::record_cache_keys`].
+#[derive(Default)]
+pub struct StatusBuffer {
+    pending: Mutex<Vec<(i64, ProcessingStatus)>>,
+    cache_keys: Mutex<Vec<(i64, String)>>,
+}
+
+impl StatusBuffer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    
+    /// Record `status` for queue item `item_id`, replacing any earlier
+    /// transition that hasn't been written yet.
+    pub fn push(&self, item_id: i64, status: ProcessingStatus) {
+        let mut pending = self.pending.lock();
+        match pending.iter_mut().find(|(buffered, _)| *buffered == item_id) {
+            Some(entry) => entry.1 = status,
+            None => pending.push((item_id, status)),
+        }
+    }
+    
+    /// The transitions of one item, or of none if it has no queue row.
+    pub fn item(&self, item_id: Option<i64>) -> ItemStatus<'_> {
+        ItemStatus { buffer: self, item_id }
+    }
+    
+    pub fn len(&self) -> usize {
+        self.pending.lock().len()
+    }
+    
+    pub fn is_empty(&self) -> bool {
+        self.pending.lock().is_empty()
+    }
+    
+    /// Drop `item_id`'s transition and cache key if they haven't been
+    /// written yet.
+    pub fn discard(&self, item_id: i64) {
+        self.pending.lock().retain(|(buffered, _)| *buffered != item_id);
+        self.cache_keys.lock().retain(|(buffered, _)| *buffered != item_id);
+    }
+    
+    /// Take every buffered transition, oldest item first.
+    pub fn drain(&self) -> Vec<(i64, ProcessingStatus)> {
+        std::mem::take(&mut *self.pending.lock())
+    }
+    
+    /// Take every buffered cache key.
+    pub fn drain_cache_keys(&self) -> Vec<(i64, String)> {
+        std::mem::take(&mut *self.cache_keys.lock())
+    }
+}
+
+/// One item's transitions in a [`StatusBuffer`]. Items processed outside
+/// the queue, e.g. by a benchmark, have no row, and theirs are dropped.
+#[derive(Clone, Copy)]
+pub struct ItemStatus<'a> {
+    buffer: &'a StatusBuffer,
+    item_id: Option<i64>,
+}
+
+impl ItemStatus<'_> {
+    pub fn set(&self, status: ProcessingStatus) {
+        if let Some(item_id) = self.item_id {
+            self.buffer.push(item_id, status);
+        }
+    }
+    
+    /// Record the Stage 2 cache key the item's card is stored under.
+    pub fn set_cache_key(&self, cache_key: String) {
+        if let Some(item_id) = self.item_id {
+            self.buffer.cache_keys.lock().push((item_id, cache_key));
+        }
+    }
+}
+
+/// The queue row an item is processed under
+#[derive(Debug, Clone, Copy)]
+struct QueuedRow {
+    id: i64,
+    /// Failures counted against the item by earlier runs
+    retry_count: u32,
+}
+
+/// Queue rows of a batch's unfinished items by vocabulary id, handed out
+/// in queue order so a term queued twice gets both of its rows.
+struct QueueIds(HashMap<i64, VecDeque<QueuedRow>>);
+
+impl QueueIds {
+    fn new(rows: Vec<QueueItem>) -> Self {
+        let mut ids: HashMap<i64, VecDeque<QueuedRow>> = HashMap::new();
+        for row in rows {
+            if let Some(id) = row.id {
+                ids.entry(row.vocabulary_id).or_default().push_back(QueuedRow {
+                    id,
+                    retry_count: u32::try_from(row.retry_count).unwrap_or(0),
+                });
+            }
+        }
+        Self(ids)
+    }
+    
+    /// The queue row `item` is processed under, if it was queued.
+    fn take(&mut self, item: &VocabularyItem) -> Option<QueuedRow> {
+        self.0.get_mut(&item.id?)?.pop_front()
+    }
 }
 
 struct ProcessingProgress {
@@ -27,39 +215,241 @@ struct ProcessingProgress {
     completed: usize,
     cached: usize,
     failed: usize,
-    start_time: Instant,
+    skipped: usize,
+    last_completion: Instant,
+    /// Exponentially weighted average of seconds between completions
+    interval_ewma: Option<f64>,
+    smoothing: f64,
+    /// Failures since the last success, across chunks of the batch
+    consecutive_failures: usize,
+    /// Items of the batch that succeeded or failed, across chunks
+    batch_finished: usize,
+    batch_failed: usize,
 }
 
 impl ProcessingProgress {
-    fn new(total: usize) -> Self {
+    fn new(total: usize, smoothing: f64) -> Self {
+        let now = Instant::now();
         Self {
             total,
             completed: 0,
             cached: 0,
             failed: 0,
-            start_time: Instant::now(),
+            skipped: 0,
+            last_completion: now,
+            interval_ewma: None,
+            smoothing,
+            consecutive_failures: 0,
+            batch_finished: 0,
+            batch_failed: 0,
         }
     }
     
-    fn eta(&self) -> Option<Duration> {
-        if self.completed == 0 {
-            return None;
+    fn record_success(&mut self) {
+        self.consecutive_failures = 0;
+        self.batch_finished += 1;
+    }
+    
+    fn record_failure(&mut self) {
+        self.failed += 1;
+        self.consecutive_failures += 1;
+        self.batch_finished += 1;
+        self.batch_failed += 1;
+    }
+    
+    /// Why the batch should stop, if `threshold` has been crossed
+    fn threshold_crossed(&self, threshold: &FailureThreshold) -> Option<String> {
+        if let Some(max) = threshold.max_consecutive_failures {
+            if self.consecutive_failures >= max {
+                return Some(format!("{} items in a row failed", self.consecutive_failures));
+            }
         }
         
-        let elapsed = self.start_time.elapsed();
-        let rate = self.completed as f64 / elapsed.as_secs_f64();
-        let remaining = self.total - self.completed;
+        if let Some(max_rate) = threshold.max_failure_rate {
+            if self.batch_finished >= FAILURE_RATE_MIN_ITEMS {
+                let rate = self.batch_failed as f64 / self.batch_finished as f64;
+                if rate > max_rate {
+                    return Some(format!(
+                        "{} of {} items failed ({:.0}%, limit {:.0}%)",
+                        self.batch_failed,
+                        self.batch_finished,
+                        rate * 100.0,
+                        max_rate * 100.0
+                    ));
+                }
+            }
+        }
+        
+        None
+    }
+    
+    fn record_completion(&mut self) {
+        self.record_completion_at(Instant::now());
+    }
+    
+    /// Count one finished item and fold the time since the previous one into
+    /// the average. Intervals rather than instantaneous rates are averaged,
+    /// since concurrent items can finish in the same instant.
+    fn record_completion_at(&mut self, now: Instant) {
+        let interval = now.saturating_duration_since(self.last_completion).as_secs_f64();
+        self.last_completion = now;
+        self.completed += 1;
+        
+        self.interval_ewma = Some(match self.interval_ewma {
+            Some(average) => self.smoothing * interval + (1.0 - self.smoothing) * average,
+            None => interval,
+        });
+    }
+    
+    /// Recent items per second, weighted toward the latest completions
+    fn rate(&self) -> Option<f64> {
+        self.interval_ewma
+            .filter(|interval| *interval > 0.0)
+            .map(|interval| 1.0 / interval)
+    }
+    
+    fn eta(&self) -> Option<Duration> {
+        let rate = self.rate()?;
+        let remaining = self.total.saturating_sub(self.completed);
         
         Some(Duration::from_secs_f64(remaining as f64 / rate))
     }
 }
 
+/// Where in the per-item flow a failure happened
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FailureStage {
+    Queue,
+    Stage1,
+    Stage2,
+}
+
+/// An item-level error tagged with the stage that produced it
+#[derive(Debug)]
+pub struct ItemFailure {
+    pub stage: FailureStage,
+    pub error: PipelineError,
+}
+
+impl ItemFailure {
+    fn at(stage: FailureStage, error: PipelineError) -> Self {
+        Self { stage, error }
+    }
+    
+    /// An offline cache miss: the item was skipped, not failed
+    pub fn is_skip(&self) -> bool {
+        matches!(self.error, PipelineError::NotCached(_))
+    }
+    
+    /// Held for review by the quality gate; retrying would only produce
+    /// another analysis like it
+    pub fn is_quarantine(&self) -> bool {
+        matches!(self.error, PipelineError::LowQuality(_))
+    }
+}
+
+impl From<PipelineError> for ItemFailure {
+    fn from(error: PipelineError) -> Self {
+        Self::at(FailureStage::Queue, error)
+    }
+}
+
+/// One failed item, in the shape written to the error report
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FailureRecord {
+    pub term: String,
+    pub position: i32,
+    pub stage: FailureStage,
+    pub category: String,
+    pub message: String,
+    pub retry_count: u32,
+}
+
+impl FailureRecord {
+    /// `retry_count` is the failures counted against the item so far,
+    /// including this one if it was counted.
+    pub fn new(item: &VocabularyItem, failure: &ItemFailure, retry_count: u32) -> Self {
+        Self {
+            term: item.term.clone(),
+            position: item.position,
+            stage: failure.stage,
+            category: failure.error.category().to_string(),
+            message: failure.error.to_string(),
+            retry_count,
+        }
+    }
+}
+
+#[derive(Default)]
 pub struct BatchResult {
-    pub successful: Vec<(VocabularyItem, Stage2Result)>,
-    pub failed: Vec<(VocabularyItem, String)>,
+    /// Completed cards with the Stage 1 analysis they were built from
+    pub successful: Vec<(VocabularyItem, Stage1Result, Stage2Result)>,
+    pub failed: Vec<FailureRecord>,
+    /// Items left unprocessed because an offline run had nothing cached
+    pub skipped: usize,
     pub total_processed: usize,
     pub cache_hits: usize,
     pub processing_time: Duration,
+    /// Comparison terms by item position, kept so cards can be linked
+    /// across chunks once they are merged
+    pub references: HashMap<i32, Vec<String>>,
+    /// Why the batch stopped early once its [`FailureThreshold`] was
+    /// crossed; the unprocessed items are left pending for a resume
+    pub aborted: Option<String>,
+    /// Items never started because the batch was drained for a shutdown;
+    /// they are left pending for a resume
+    pub left_pending: usize,
+    /// Successful cards already streamed to the export and dropped from
+    /// `successful` to bound memory; see [`successful_count`](Self::successful_count)
+    pub spilled: usize,
+    /// Most successful cards held in memory at once
+    pub peak_buffered: usize,
+    /// Highest position among items that finished for good: succeeded,
+    /// skipped or quarantined. Failed items are retried, so they never count
+    pub last_completed: Option<i32>,
+}
+
+impl BatchResult {
+    /// Combine the results of consecutive chunks of one batch into one,
+    /// re-linking related cards across chunk boundaries.
+    pub fn merge(chunks: impl IntoIterator<Item = BatchResult>) -> BatchResult {
+        let mut merged = BatchResult::default();
+        
+        for chunk in chunks {
+            merged.successful.extend(chunk.successful);
+            merged.failed.extend(chunk.failed);
+            merged.skipped += chunk.skipped;
+            merged.total_processed += chunk.total_processed;
+            merged.cache_hits += chunk.cache_hits;
+            merged.left_pending += chunk.left_pending;
+            merged.spilled += chunk.spilled;
+            merged.peak_buffered = merged.peak_buffered.max(chunk.peak_buffered);
+            merged.last_completed = merged.last_completed.max(chunk.last_completed);
+            merged.processing_time += chunk.processing_time;
+            merged.references.extend(chunk.references);
+            if merged.aborted.is_none() {
+                merged.aborted = chunk.aborted;
+            }
+        }
+        
+        resolve_related_cards(&mut merged.successful, &merged.references);
+        merged
+    }
+    
+    /// Cards that succeeded, whether still held or spilled.
+    pub fn successful_count(&self) -> usize {
+        self.successful.len() + self.spilled
+    }
+    
+    /// Drop the successful cards, and the comparison terms kept to link
+    /// them, counting them as spilled. Only for cards already streamed.
+    pub fn spill(&mut self) {
+        self.spilled += self.successful.len();
+        for (item, _, _) in self.successful.drain(..) {
+            self.references.remove(&item.position);
+        }
+    }
 }
 
 impl BatchProcessor {
@@ -67,35 +457,264 @@ impl BatchProcessor {
         api_client: Arc<dyn ApiClient>,
         cache_manager: Arc<CacheManager>,
         queue_repo: Arc<dyn QueueRepository>,
+        metrics_collector: Arc<MetricsCollector>,
         max_concurrent: usize,
     ) -> Self {
         Self {
             api_client,
             cache_manager,
             queue_repo,
+            metrics_collector,
             semaphore: Arc::new(Semaphore::new(max_concurrent)),
-            progress: Arc::new(RwLock::new(ProcessingProgress::new(0))),
+            api_limiter: ApiLimiter::unlimited(),
+            db_limiter: ConnectionLimiter::unlimited(),
+            progress: Arc::new(RwLock::new(ProcessingProgress::new(0, DEFAULT_RATE_SMOOTHING))),
+            rate_smoothing: DEFAULT_RATE_SMOOTHING,
+            retry_policy: Arc::new(RetryPolicy::default()),
+            stage1_fallback: false,
+            quality_gate: QualityGate::default(),
+            cancellation: CancellationToken::new(),
+            drain: CancellationToken::new(),
+            status_batch_size: DEFAULT_STATUS_BATCH_SIZE,
+            status_flush_interval: DEFAULT_STATUS_FLUSH_INTERVAL,
+            stage2_mode: Stage2Mode::default(),
+            models: ModelSelection::default(),
+            failure_threshold: FailureThreshold::default(),
+            audit: None,
+            max_buffered_results: None,
+        }
+    }
+    
+    /// Weight (0..=1) of the newest completion in the displayed rate and ETA.
+    /// Higher values react faster to throughput changes but jitter more.
+    pub fn with_rate_smoothing(mut self, smoothing: f64) -> Self {
+        self.rate_smoothing = smoothing.clamp(0.0, 1.0);
+        self
+    }
+    
+    /// Allow at most `max_calls` live API calls, however many items are in
+    /// flight. Cache hits never take an API permit.
+    pub fn with_api_concurrency(mut self, max_calls: usize) -> Self {
+        self.api_limiter = ApiLimiter::new(max_calls);
+        self
+    }
+    
+    /// Run queue reads and writes in `limiter`'s slots, the ones the cache
+    /// manager's queries take, so together they never need more
+    /// connections than it allows.
+    pub fn with_connection_limiter(mut self, limiter: ConnectionLimiter) -> Self {
+        self.db_limiter = limiter;
+        self
+    }
+    
+    /// How failed API calls are retried, including waits for rate limits.
+    pub fn with_retry_policy(mut self, policy: RetryPolicy) -> Self {
+        self.retry_policy = Arc::new(policy);
+        self
+    }
+    
+    /// Build a basic card from Stage 1 when Stage 2 fails or isn't cached,
+    /// instead of failing or skipping the item.
+    pub fn with_stage1_fallback(mut self, enabled: bool) -> Self {
+        self.stage1_fallback = enabled;
+        self
+    }
+    
+    /// Quarantine items whose Stage 1 analysis falls short of `gate` instead
+    /// of running Stage 2 on them.
+    pub fn with_quality_gate(mut self, gate: QualityGate) -> Self {
+        self.quality_gate = gate;
+        self
+    }
+    
+    /// Record every API call to the `api_call_log` table through `audit`,
+    /// attributed to the batch it was made for.
+    pub fn with_audit(mut self, audit: ApiAudit) -> Self {
+        self.audit = Some(audit);
+        self
+    }
+    
+    /// When streaming, hold at most `max` successful cards: once that many
+    /// are buffered they are dropped and only counted, since the sink has
+    /// written them already. Without a sink every card is kept for export.
+    pub fn with_max_buffered_results(mut self, max: usize) -> Self {
+        self.max_buffered_results = Some(max);
+        self
+    }
+    
+    /// Write queue status transitions once `batch_size` are buffered or
+    /// `interval` has passed, whichever comes first.
+    pub fn with_status_batching(mut self, batch_size: usize, interval: Duration) -> Self {
+        self.status_batch_size = batch_size.max(1);
+        self.status_flush_interval = interval;
+        self
+    }
+    
+    /// Which Stage 2 fields are generated. Should match the mode the cache
+    /// manager keys Stage 2 results by.
+    pub fn with_stage2_mode(mut self, mode: Stage2Mode) -> Self {
+        self.stage2_mode = mode;
+        self
+    }
+    
+    /// The models each stage runs, recorded with the results they cache so
+    /// entries can be told apart, e.g. by `cache clear --model`.
+    pub fn with_models(mut self, models: ModelSelection) -> Self {
+        self.models = models;
+        self
+    }
+    
+    /// Stop the batch early, checkpointed, once `threshold` is crossed, so a
+    /// broken API isn't called for every remaining item.
+    pub fn with_failure_threshold(mut self, threshold: FailureThreshold) -> Self {
+        self.failure_threshold = threshold;
+        self
+    }
+    
+    /// Stop batches when `token` is cancelled, e.g. from a signal handler.
+    pub fn with_cancellation(mut self, token: CancellationToken) -> Self {
+        self.cancellation = token;
+        self
+    }
+    
+    /// Token that interrupts the running batch when cancelled.
+    pub fn cancellation_token(&self) -> CancellationToken {
+        self.cancellation.clone()
+    }
+    
+    /// Token that drains the running batch when cancelled: items not yet
+    /// started are left pending, while those in flight finish and are
+    /// returned as usual.
+    pub fn drain_token(&self) -> CancellationToken {
+        self.drain.clone()
+    }
+    
+    /// Whether a drain has begun, so no new work should start.
+    pub fn is_draining(&self) -> bool {
+        self.drain.is_cancelled()
+    }
+    
+    /// The permit pool bounding concurrent items, for runtime resizing.
+    pub fn semaphore(&self) -> Arc<Semaphore> {
+        Arc::clone(&self.semaphore)
+    }
+    
+    /// A processor for running another batch alongside this one's. It
+    /// shares the item and API permits, cache, retry policy and
+    /// cancellation, so total concurrency stays bounded, but counts progress
+    /// and failure thresholds for its own batch.
+    pub fn for_concurrent_batch(&self) -> Self {
+        Self {
+            api_client: Arc::clone(&self.api_client),
+            cache_manager: Arc::clone(&self.cache_manager),
+            queue_repo: Arc::clone(&self.queue_repo),
+            metrics_collector: Arc::clone(&self.metrics_collector),
+            semaphore: Arc::clone(&self.semaphore),
+            api_limiter: self.api_limiter.clone(),
+            db_limiter: self.db_limiter.clone(),
+            progress: Arc::new(RwLock::new(ProcessingProgress::new(0, self.rate_smoothing))),
+            rate_smoothing: self.rate_smoothing,
+            retry_policy: Arc::clone(&self.retry_policy),
+            stage1_fallback: self.stage1_fallback,
+            quality_gate: self.quality_gate,
+            cancellation: self.cancellation.clone(),
+            drain: self.drain.clone(),
+            status_batch_size: self.status_batch_size,
+            status_flush_interval: self.status_flush_interval,
+            stage2_mode: self.stage2_mode,
+            models: self.models.clone(),
+            failure_threshold: self.failure_threshold,
+            audit: self.audit.clone(),
+            max_buffered_results: self.max_buffered_results,
         }
     }
     
-    #[instrument(skip(self, items))]
     pub async fn process_batch(
         &self,
         items: Vec<VocabularyItem>,
-        batch_id: i32,
+        batch_id: &BatchId,
+    ) -> Result<BatchResult> {
+        self.process_batch_with_sink(items, batch_id, None).await
+    }
+    
+    /// Process a batch, forwarding each successful card to `sink` as soon as
+    /// it completes so a writer task can persist it before the batch ends.
+    pub async fn process_batch_with_sink(
+        &self,
+        items: Vec<VocabularyItem>,
+        batch_id: &BatchId,
+        sink: Option<mpsc::Sender<(VocabularyItem, Stage1Result, Stage2Result)>>,
+    ) -> Result<BatchResult> {
+        self.start_progress(items.len());
+        self.process_batch_prefetched(items, batch_id, sink, Prefetched::new()).await
+    }
+    
+    /// Start counting a batch of `total` items. Its chunks share the count,
+    /// so the progress bar, rate and ETA run across the whole batch, as do
+    /// the failure thresholds.
+    pub fn start_progress(&self, total: usize) {
+        *self.progress.write() = ProcessingProgress::new(total, self.rate_smoothing);
+    }
+    
+    /// Look up which of `items` are fully cached, to hand to
+    /// [`process_batch_prefetched`](Self::process_batch_prefetched) when
+    /// their chunk's turn comes.
+    ///
+    /// Only the cache is read, so no item or API permits are taken and the
+    /// running chunk only shares the cache's connection limit with it.
+    /// Failed lookups are left out; those items look themselves up again.
+    pub fn prefetch(&self, items: Vec<VocabularyItem>) -> impl Future<Output = Prefetched> + Send + 'static {
+        let cache_manager = Arc::clone(&self.cache_manager);
+        async move {
+            let cache_manager = &cache_manager;
+            stream::iter(items)
+                .map(|item| async move {
+                    let cached = cache_manager.get_cached_results(&item).await;
+                    (item, cached)
+                })
+                .buffer_unordered(PREFETCH_LOOKUPS)
+                .filter_map(|(item, cached)| async move {
+                    match cached {
+                        Ok(hit) => hit.map(|results| (item.position, results)),
+                        Err(e) => {
+                            debug!("Prefetch for {} failed: {}", item.term, e);
+                            None
+                        }
+                    }
+                })
+                .collect()
+                .await
+        }
+    }
+    
+    /// [`process_batch_with_sink`](Self::process_batch_with_sink), serving
+    /// items found in `prefetched` without looking them up again. Items
+    /// missing from it are processed as usual, so a partial or empty map
+    /// only costs the lookups it didn't save.
+    ///
+    /// Progress is added to the count [`start_progress`](Self::start_progress)
+    /// began, so a batch's chunks are shown as one run.
+    #[instrument(skip(self, items, sink, prefetched))]
+    pub async fn process_batch_prefetched(
+        &self,
+        items: Vec<VocabularyItem>,
+        batch_id: &BatchId,
+        sink: Option<mpsc::Sender<(VocabularyItem, Stage1Result, Stage2Result)>>,
+        mut prefetched: Prefetched,
     ) -> Result<BatchResult> {
         let total = items.len();
+        let started = Instant::now();
         info!("Starting batch processing for {} items", total);
         
-        // Initialize progress
-        {
-            let mut progress = self.progress.write();
-            *progress = ProcessingProgress::new(total);
-        }
-        
-        // Create progress bars
+        // Create progress bars, picking up where earlier chunks left off
+        let (batch_total, batch_completed) = {
+            let progress = self.progress.read();
+            (progress.total, progress.completed)
+        };
         let multi_progress = MultiProgress::new();
-        let main_bar = multi_progress.add(ProgressBar::new(total as u64));
+        let main_bar = multi_progress.add(
+            ProgressBar::new(batch_total as u64).with_position(batch_completed as u64),
+        );
         main_bar.set_style(
             ProgressStyle::default_bar()
                 .template("{spinner:.green} [{bar:40.cyan/blue}] {pos}/{len} ({percent}%) {msg}")
@@ -123,17 +742,18 @@ impl BatchProcessor {
                     let prog = progress.read();
                     main_bar.set_position(prog.completed as u64);
                     main_bar.set_message(format!(
-                        "✓ {} | ⚡ {} cached | ✗ {} failed",
+                        "✓ {} | ⚡ {} cached | ✗ {} failed | ⏭ {} skipped",
                         style(prog.completed).green(),
                         style(prog.cached).yellow(),
-                        style(prog.failed).red()
+                        style(prog.failed).red(),
+                        style(prog.skipped).dim()
                     ));
                     
-                    if let Some(eta) = prog.eta() {
+                    if let (Some(eta), Some(rate)) = (prog.eta(), prog.rate()) {
                         eta_bar.set_message(format!(
                             "ETA: {} | Rate: {:.1} items/sec",
                             humantime::format_duration(eta),
-                            prog.completed as f64 / prog.start_time.elapsed().as_secs_f64()
+                            rate
                         ));
                     }
                     
@@ -143,200 +763,722 @@ impl BatchProcessor {
                 }
             })
         };
+        // Whatever returns early, nothing spawned for the batch outlives it
+        let mut tasks = AbortOnDrop::default();
+        tasks.push(&progress_handle);
         
-        // Create checkpoint
-        self.queue_repo.create_checkpoint(batch_id).await?;
+        let mut queue_ids = QueueIds::new(self.db_limiter.run(self.queue_repo.get_incomplete_items(batch_id)).await?);
         
         // Process items concurrently
         let (tx, mut rx) = mpsc::channel(100);
         let mut handles = Vec::new();
+        let in_flight = Arc::new(Mutex::new(HashSet::new()));
+        let statuses = Arc::new(StatusBuffer::new());
+        let mut flush_timer = tokio::time::interval(self.status_flush_interval);
+        flush_timer.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        
+        let audit = self.audit.as_ref().map(|audit| audit.for_batch(batch_id));
         
         for item in items {
+            // Kept to fail the item with, should its task panic
+            let spawned = item.clone();
             let permit = Arc::clone(&self.semaphore);
             let api_client = Arc::clone(&self.api_client);
             let cache_manager = Arc::clone(&self.cache_manager);
-            let queue_repo = Arc::clone(&self.queue_repo);
+            let statuses = Arc::clone(&statuses);
             let progress = Arc::clone(&self.progress);
+            let metrics = Arc::clone(&self.metrics_collector);
+            let retry_policy = Arc::clone(&self.retry_policy);
+            let api_limiter = self.api_limiter.clone();
+            let audit = audit.clone();
+            let stage1_fallback = self.stage1_fallback;
+            let quality_gate = self.quality_gate;
+            let stage2_mode = self.stage2_mode;
+            let models = self.models.clone();
+            let in_flight = Arc::clone(&in_flight);
             let tx = tx.clone();
+            let prefetched = prefetched.remove(&item.position);
+            let drain = self.drain.clone();
+            let queued = queue_ids.take(&item);
+            let queue_id = queued.map(|row| row.id);
             
             let handle = tokio::spawn(async move {
-                let _permit = permit.acquire().await.unwrap();
-                let result = Self::process_single_item(
-                    &item,
-                    api_client,
-                    cache_manager,
-                    queue_repo,
-                    batch_id,
-                ).await;
+                // An item still waiting for its permit when the batch is
+                // drained never starts; it stays pending for a resume
+                let _permit = tokio::select! {
+                    biased;
+                    _ = drain.cancelled() => return,
+                    permit = permit.acquire() => permit.unwrap(),
+                };
+                in_flight.lock().extend(queue_id);
+                let started = Instant::now();
+                let result = match prefetched {
+                    Some((stage1_result, stage2_result)) if quality_gate.check(&stage1_result).is_none() => {
+                        debug!("{} prefetched from the cache; skipping both stages", item.term);
+                        Ok((stage1_result, stage2_result, true))
+                    }
+                    // A rejected analysis is looked up again and quarantined
+                    _ => Self::process_single_item(
+                        &item,
+                        api_client,
+                        cache_manager,
+                        statuses.item(queue_id),
+                        &metrics,
+                        &retry_policy,
+                        &api_limiter,
+                        audit.as_ref(),
+                        stage1_fallback,
+                        quality_gate,
+                        stage2_mode,
+                        &models,
+                    ).await,
+                };
+                // Here rather than at Stage 2, so cached and fallback cards
+                // of phrases are tagged too
+                let result = result.map(|(stage1_result, mut stage2_result, was_cached)| {
+                    stage2_result.tag_if_phrase(&item);
+                    (stage1_result, stage2_result, was_cached)
+                });
+                
+                metrics.record_item_latency(started.elapsed());
+                
+                // Buffered before the result is sent, so a streamed card's
+                // completion is flushed ahead of it
+                if result.is_ok() {
+                    statuses.item(queue_id).set(ProcessingStatus::Completed);
+                }
+                
+                // Record live so the adaptive controller sees recent behaviour
+                match &result {
+                    Ok((_, _, true)) => metrics.record_cache_hit(),
+                    Ok((_, _, false)) => metrics.record_cache_miss(),
+                    Err(failure) if failure.is_skip() => metrics.record_cache_miss(),
+                    // Stage 1 answered; its answer was just too thin
+                    Err(failure) if failure.is_quarantine() => {}
+                    // Rate limits were already counted on each attempt
+                    Err(failure) if failure.error.is_api_failure() => metrics.record_api_error(),
+                    // Not the API's doing, so no reason to back off
+                    Err(_) => {}
+                }
                 
                 // Update progress
                 {
                     let mut prog = progress.write();
-                    prog.completed += 1;
+                    prog.record_completion();
                     match &result {
-                        Ok((_, was_cached)) => {
+                        Ok((_, _, was_cached)) => {
+                            prog.record_success();
                             if *was_cached {
                                 prog.cached += 1;
                             }
                         }
+                        Err(failure) if failure.is_skip() => {
+                            prog.skipped += 1;
+                        }
                         Err(_) => {
-                            prog.failed += 1;
+                            prog.record_failure();
                         }
                     }
                 }
                 
-                tx.send((item, result)).await.ok();
+                if let Some(queue_id) = queue_id {
+                    in_flight.lock().remove(&queue_id);
+                }
+                tx.send((item, queued, result)).await.ok();
             });
             
-            handles.push(handle);
+            tasks.push(&handle);
+            handles.push((spawned, queued, handle));
         }
         
         drop(tx);
         
         // Collect results
-        let mut successful = Vec::new();
-        let mut failed = Vec::new();
-        let mut cache_hits = 0;
+        let mut batch = BatchResult::default();
+        let mut cancelled = false;
+        let mut aborted = None;
         
-        while let Some((item, result)) = rx.recv().await {
+        loop {
+            // Finished items are drained before the token is checked, so a
+            // cancellation never loses a result that already arrived
+            let (item, queued, result) = tokio::select! {
+                biased;
+                received = rx.recv() => match received {
+                    Some(received) => received,
+                    None => break,
+                },
+                _ = self.cancellation.cancelled() => {
+                    cancelled = true;
+                    break;
+                }
+                _ = flush_timer.tick() => {
+                    self.flush_statuses(batch_id, &statuses).await?;
+                    continue;
+                }
+            };
             match result {
-                Ok((stage2_result, was_cached)) => {
-                    successful.push((item, stage2_result));
+                Ok((stage1_result, stage2_result, was_cached)) => {
+                    batch.last_completed = batch.last_completed.max(Some(item.position));
+                    batch.references.insert(item.position, comparison_terms(&stage1_result));
+                    if let Some(sink) = &sink {
+                        // The writer may persist the card straight away, so
+                        // its completion has to be on disk first. Its
+                        // related_cards are still empty: the cards it links
+                        // to may not have finished yet
+                        self.flush_statuses(batch_id, &statuses).await?;
+                        let card = (item.clone(), stage1_result.clone(), stage2_result.clone());
+                        if sink.send(card).await.is_err() {
+                            warn!("Export writer closed; card for {} not streamed", item.term);
+                        }
+                    }
+                    batch.successful.push((item, stage1_result, stage2_result));
+                    batch.peak_buffered = batch.peak_buffered.max(batch.successful.len());
                     if was_cached {
-                        cache_hits += 1;
+                        batch.cache_hits += 1;
                     }
+                    // Streamed already, so they can go once the window is full
+                    let window_full = self.max_buffered_results.is_some_and(|max| batch.successful.len() >= max);
+                    if sink.is_some() && window_full {
+                        batch.spill();
+                    }
+                }
+                Err(failure) if failure.is_skip() => {
+                    debug!("Skipping {}: {}", item.term, failure.error);
+                    batch.last_completed = batch.last_completed.max(Some(item.position));
+                    batch.skipped += 1;
                 }
-                Err(e) => {
-                    failed.push((item, e.to_string()));
+                Err(failure) if failure.is_quarantine() => {
+                    // Its quarantined status is final, so no retry is counted
+                    warn!("Quarantined {} (position {}) for review: {}", item.term, item.position, failure.error);
+                    batch.last_completed = batch.last_completed.max(Some(item.position));
+                    let retry_count = queued.map_or(0, |row| row.retry_count);
+                    batch.failed.push(FailureRecord::new(&item, &failure, retry_count));
                 }
+                Err(failure) => {
+                    let retry_count = self.record_retry(&item, queued, &statuses).await?;
+                    batch.failed.push(FailureRecord::new(&item, &failure, retry_count));
+                }
+            }
+            
+            if statuses.len() >= self.status_batch_size {
+                self.flush_statuses(batch_id, &statuses).await?;
+            }
+            
+            if aborted.is_none() {
+                aborted = self.progress.read().threshold_crossed(&self.failure_threshold);
+                if aborted.is_some() {
+                    // Nothing new starts, but results already sent are still
+                    // drained until the aborted tasks drop their senders
+                    for (_, _, handle) in &handles {
+                        handle.abort();
+                    }
+                }
+            }
+        }
+        
+        if cancelled || aborted.is_some() {
+            for (_, _, handle) in &handles {
+                handle.abort();
             }
+            progress_handle.abort();
+            let headline = match &aborted {
+                Some(reason) => format!("⛔ Aborted, {}", reason),
+                None => "⏹ Interrupted".to_string(),
+            };
+            main_bar.abandon_with_message(format!(
+                "{}: {} successful, {} failed, {} skipped",
+                headline,
+                batch.successful_count(),
+                batch.failed.len(),
+                batch.skipped
+            ));
+            
+            let interrupted: Vec<i64> = in_flight.lock().drain().collect();
+            // Items never started, and those interrupted, are still pending
+            let finished = batch.successful_count() + batch.failed.len() + batch.skipped;
+            let left_pending = total - finished;
+            let stats = serde_json::json!({
+                "completed": batch.successful_count(),
+                "failed": batch.failed.len(),
+                "skipped": batch.skipped,
+                "cache_hits": batch.cache_hits,
+                "interrupted": interrupted.len(),
+                "left_pending": left_pending,
+                "aborted": aborted,
+            });
+            self.flush_checkpoint(batch_id, batch.last_completed, &interrupted, &statuses, stats).await;
+            
+            let Some(reason) = aborted else {
+                return Err(PipelineError::Interrupted);
+            };
+            
+            // Cards finished before the abort are still returned for export
+            error!("Aborted batch {}: {}", batch_id, reason);
+            resolve_related_cards(&mut batch.successful, &batch.references);
+            return Ok(BatchResult {
+                total_processed: finished,
+                processing_time: started.elapsed(),
+                aborted: Some(reason),
+                left_pending,
+                ..batch
+            });
         }
         
-        // Wait for all tasks
-        for handle in handles {
-            handle.await.map_err(|e| PipelineError::PythonError(e.to_string()))?;
+        // Wait for all tasks. One that panicked, e.g. in the Python bridge,
+        // never sent a result, so its item fails here rather than the batch
+        for (item, queued, handle) in handles {
+            let Err(e) = handle.await else {
+                continue;
+            };
+            let message = if e.is_panic() {
+                panic_message(e.into_panic())
+            } else {
+                e.to_string()
+            };
+            error!("Processing {} (position {}) panicked: {}", item.term, item.position, message);
+            
+            let failure = ItemFailure::from(PipelineError::Panicked(message));
+            {
+                let mut prog = self.progress.write();
+                prog.record_completion();
+                prog.record_failure();
+            }
+            let retry_count = self.record_retry(&item, queued, &statuses).await?;
+            batch.failed.push(FailureRecord::new(&item, &failure, retry_count));
         }
         
         // Stop progress updater
         progress_handle.abort();
         
-        // Finalize progress bars
-        main_bar.finish_with_message(format!(
-            "✅ Completed: {} successful, {} failed, {} cached",
-            style(successful.len()).green(),
-            style(failed.len()).red(),
-            style(cache_hits).yellow()
-        ));
+        // Every item has reported, so this makes each final status durable
+        // before the caller exports anything
+        self.flush_statuses(batch_id, &statuses).await?;
         
-        let processing_time = self.progress.read().start_time.elapsed();
+        let succeeded = batch.successful_count();
+        let finished = succeeded + batch.failed.len() + batch.skipped;
+        let left_pending = total - finished;
+        if left_pending > 0 {
+            let stats = serde_json::json!({
+                "completed": succeeded,
+                "failed": batch.failed.len(),
+                "skipped": batch.skipped,
+                "cache_hits": batch.cache_hits,
+                "left_pending": left_pending,
+            });
+            self.flush_checkpoint(batch_id, batch.last_completed, &[], &statuses, stats).await;
+        }
+        
+        // Streamed cards have already been written, so only the in-memory
+        // results carry cross-references
+        resolve_related_cards(&mut batch.successful, &batch.references);
+        
+        // Finalize progress bars; a later chunk's bar continues this one
+        if left_pending == 0 && self.progress.read().completed < batch_total {
+            main_bar.finish_and_clear();
+            eta_bar.finish_and_clear();
+        } else if left_pending > 0 {
+            main_bar.abandon_with_message(format!(
+                "⏸ Drained: {} successful, {} failed, {} skipped, {} left pending",
+                style(succeeded).green(),
+                style(batch.failed.len()).red(),
+                style(batch.skipped).dim(),
+                style(left_pending).yellow()
+            ));
+        } else {
+            main_bar.finish_with_message(format!(
+                "✅ Completed: {} successful, {} failed, {} skipped, {} cached",
+                style(succeeded).green(),
+                style(batch.failed.len()).red(),
+                style(batch.skipped).dim(),
+                style(batch.cache_hits).yellow()
+            ));
+        }
+        
+        let processing_time = started.elapsed();
         info!(
-            "Batch processing complete: {} successful, {} failed, {} cache hits in {:?}",
-            successful.len(),
-            failed.len(),
-            cache_hits,
+            "Batch processing complete: {} successful, {} failed, {} skipped, {} cache hits in {:?}",
+            succeeded,
+            batch.failed.len(),
+            batch.skipped,
+            batch.cache_hits,
             processing_time
         );
         
         Ok(BatchResult {
-            successful,
-            failed,
-            total_processed: total,
-            cache_hits,
+            total_processed: finished,
             processing_time,
+            aborted: None,
+            left_pending,
+            ..batch
         })
     }
     
-    async fn process_single_item(
+    /// Run both stages for `item`, buffering its in-progress and failure
+    /// transitions; the caller records completion.
+    ///
+    /// An item with both stages cached returns straight away, without any
+    /// transition or compute call.
+    pub(crate) async fn process_single_item(
         item: &VocabularyItem,
         api_client: Arc<dyn ApiClient>,
         cache_manager: Arc<CacheManager>,
-        queue_repo: Arc<dyn QueueRepository>,
-        batch_id: i32,
-    ) -> Result<(Stage2Result, bool)> {
+        statuses: ItemStatus<'_>,
+        metrics: &MetricsCollector,
+        retry_policy: &RetryPolicy,
+        api_limiter: &ApiLimiter,
+        audit: Option<&ApiAudit>,
+        stage1_fallback: bool,
+        quality_gate: QualityGate,
+        stage2_mode: Stage2Mode,
+        models: &ModelSelection,
+    ) -> std::result::Result<(Stage1Result, Stage2Result, bool), ItemFailure> {
         debug!("Processing item: {} (position {})", item.term, item.position);
+        let api_client = &api_client;
+        // Shared by both stages, so Stage 2 gets whatever Stage 1 left
+        let item_budget = retry_policy.item_budget();
+        let budget = item_budget.as_ref();
+        
+        // Both keys are known up front, so a re-run of cached items needn't
+        // wait on one lookup before starting the next
+        match cache_manager.get_cached_results(item).await {
+            Ok(Some((stage1_result, stage2_result))) if quality_gate.check(&stage1_result).is_none() => {
+                debug!("{} fully cached; skipping both stages", item.term);
+                return Ok((stage1_result, stage2_result, true));
+            }
+            // A cached analysis the gate rejects is quarantined below
+            Ok(Some(_)) => {}
+            Ok(None) => {}
+            // The stages look the entries up again and report the error there
+            Err(e) => debug!("Cache probe for {} failed: {}", item.term, e),
+        }
         
         // Update status to processing
-        queue_repo.update_item_status(
-            batch_id,
-            item.position,
-            ProcessingStatus::Processing { stage: 1 },
-        ).await?;
+        statuses.set(ProcessingStatus::Processing { stage: 1 });
         
         // Stage 1: Semantic Analysis
-        let (stage1_result, stage1_cached) = match cache_manager.get_or_compute_stage1(
+        let stage1_computed = &AtomicBool::new(false);
+        let stage1_result = match cache_manager.get_or_compute_stage1(
             item,
-            |item| api_client.process_stage1(item),
+            || async move {
+                stage1_computed.store(true, Ordering::Relaxed);
+                // The budget is applied inside the permit, so waiting for
+                // one doesn't eat into it
+                let (result, tokens) = with_retry_within(retry_policy, metrics, budget, move || {
+                    api_limiter.call(within(
+                        budget,
+                        audited(audit, item, ApiStage::Stage1, api_client.process_stage1_with_usage(item)),
+                    ))
+                }).await?;
+                metrics.record_api_call(ApiStage::Stage1, tokens);
+                let request_hash = cache_manager.request_hash(CacheType::Stage1, item);
+                Ok::<_, PipelineError>((result, request_hash, tokens as i32, models.stage1.clone()))
+            },
         ).await {
             Ok(result) => result,
             Err(e) => {
-                queue_repo.update_item_status(
-                    batch_id,
-                    item.position,
-                    ProcessingStatus::Failed {
-                        error: e.to_string(),
-                        retry_count: 0,
-                    },
-                ).await?;
-                return Err(e);
+                statuses.set(status_after_error(&e));
+                return Err(ItemFailure::at(FailureStage::Stage1, e));
             }
         };
         
+        if let Some(reason) = quality_gate.check(&stage1_result) {
+            let e = PipelineError::LowQuality(reason);
+            statuses.set(status_after_error(&e));
+            return Err(ItemFailure::at(FailureStage::Stage1, e));
+        }
+        
         // Update status to stage 2
-        queue_repo.update_item_status(
-            batch_id,
-            item.position,
-            ProcessingStatus::Processing { stage: 2 },
-        ).await?;
+        statuses.set(ProcessingStatus::Processing { stage: 2 });
         
         // Stage 2: Card Generation
+        let stage1_cached = !stage1_computed.load(Ordering::Relaxed);
+        let stage1 = &stage1_result;
+        let stage2_computed = &AtomicBool::new(false);
         let (stage2_result, stage2_cached) = match cache_manager.get_or_compute_stage2(
             item,
-            &stage1_result,
-            |item, stage1| api_client.process_stage2(item, stage1),
+            stage1,
+            || async move {
+                stage2_computed.store(true, Ordering::Relaxed);
+                let (result, tokens) = with_retry_within(retry_policy, metrics, budget, move || {
+                    api_limiter.call(within(budget, audited(
+                        audit,
+                        item,
+                        ApiStage::Stage2,
+                        api_client.process_stage2_with_usage(item, stage1, stage2_mode),
+                    )))
+                }).await?;
+                metrics.record_api_call(ApiStage::Stage2, tokens);
+                let request_hash = cache_manager.request_hash(CacheType::Stage2, item);
+                Ok::<_, PipelineError>((result, request_hash, tokens as i32, models.stage2.clone()))
+            },
         ).await {
-            Ok(result) => result,
+            Ok(result) => {
+                // Fallback cards aren't cached, so only generated ones are recorded
+                statuses.set_cache_key(cache_manager.cache_keys(item).1);
+                (result, !stage2_computed.load(Ordering::Relaxed))
+            }
+            Err(e) if stage1_fallback => {
+                warn!("Stage 2 unavailable for {} ({}); using a Stage 1 card", item.term, e);
+                // Built here rather than read from the cache, so never a hit
+                (build_flashcard_from_stage1(item, &stage1_result), false)
+            }
             Err(e) => {
-                queue_repo.update_item_status(
-                    batch_id,
-                    item.position,
-                    ProcessingStatus::Failed {
-                        error: e.to_string(),
-                        retry_count: 0,
-                    },
-                ).await?;
-                return Err(e);
+                statuses.set(status_after_error(&e));
+                return Err(ItemFailure::at(FailureStage::Stage2, e));
             }
         };
         
-        // Update status to completed
-        queue_repo.update_item_status(
+        let was_fully_cached = stage1_cached && stage2_cached;
+        Ok((stage1_result, stage2_result, was_fully_cached))
+    }
+    
+    /// Record that `batch_id` has finished every item up to `position` once
+    /// a chunk of it is done, so a crash mid-batch loses at most the chunk
+    /// in flight.
+    pub async fn checkpoint_chunk(&self, batch_id: &BatchId, position: i32, chunk: &BatchResult) -> Result<()> {
+        let stats = serde_json::json!({
+            "completed": chunk.successful_count(),
+            "failed": chunk.failed.len(),
+            "skipped": chunk.skipped,
+            "cache_hits": chunk.cache_hits,
+        });
+        self.db_limiter.run(self.queue_repo.save_checkpoint(
             batch_id,
-            item.position,
-            ProcessingStatus::Completed,
-        ).await?;
+            i64::from(position),
+            ProcessingStage::Complete,
+            stats,
+        )).await?;
+        Ok(())
+    }
+    
+    /// Record where a cancelled batch stopped, giving up after
+    /// [`CHECKPOINT_FLUSH_TIMEOUT`] so shutdown is never held up by the database.
+    ///
+    /// `last_completed` is the highest position that finished for good; queue
+    /// items that were cut off mid-flight are put back to pending so a
+    /// resume picks them up again. Buffered transitions are written in the
+    /// same flush.
+    async fn flush_checkpoint(
+        &self,
+        batch_id: &BatchId,
+        last_completed: Option<i32>,
+        interrupted: &[i64],
+        statuses: &StatusBuffer,
+        stats: serde_json::Value,
+    ) {
+        for &item_id in interrupted {
+            statuses.push(item_id, ProcessingStatus::Pending);
+        }
         
-        let was_fully_cached = stage1_cached && stage2_cached;
-        Ok((stage2_result, was_fully_cached))
+        let flush = async {
+            self.flush_statuses(batch_id, statuses).await?;
+            if let Some(position) = last_completed {
+                self.db_limiter.run(self.queue_repo.save_checkpoint(
+                    batch_id,
+                    i64::from(position),
+                    ProcessingStage::Complete,
+                    stats,
+                )).await?;
+            }
+            Ok::<_, PipelineError>(())
+        };
+        
+        match tokio::time::timeout(CHECKPOINT_FLUSH_TIMEOUT, flush).await {
+            Ok(Ok(())) => match last_completed {
+                Some(position) => info!("Checkpointed batch {} at item {}", batch_id, position),
+                None => info!("Batch {} interrupted before any item completed", batch_id),
+            },
+            Ok(Err(e)) => warn!("Failed to checkpoint interrupted batch {}: {}", batch_id, e),
+            Err(_) => warn!(
+                "Checkpoint for batch {} not written within {:?}",
+                batch_id,
+                CHECKPOINT_FLUSH_TIMEOUT
+            ),
+        }
+    }
+    
+    /// Count a failure against the retry budget of `queued`, the item's
+    /// queue row, so the count survives a restart and a resume carries on
+    /// from it. The item is put back to pending, or quarantined once the
+    /// budget is spent, and a quarantined item is never handed out by a
+    /// resume again. Returns the item's retry count, now including this
+    /// failure.
+    async fn record_retry(&self, item: &VocabularyItem, queued: Option<QueuedRow>, statuses: &StatusBuffer) -> Result<u32> {
+        let Some(queued) = queued else {
+            return Ok(0);
+        };
+        // The retry sets the row's status, which its buffered failed status
+        // would otherwise overwrite at the next flush
+        statuses.discard(queued.id);
+        if !self.db_limiter.run(self.queue_repo.increment_retry(queued.id)).await? {
+            warn!("Quarantined {} (position {}): out of retries", item.term, item.position);
+        }
+        Ok(queued.retry_count + 1)
+    }
+    
+    /// Write every buffered status transition in one transaction, and the
+    /// buffered cache keys in another.
+    async fn flush_statuses(&self, batch_id: &BatchId, statuses: &StatusBuffer) -> Result<()> {
+        let updates = statuses.drain();
+        let cache_keys = statuses.drain_cache_keys();
+        
+        if !updates.is_empty() {
+            debug!("Writing {} status updates for batch {}", updates.len(), batch_id);
+            self.db_limiter.run(self.queue_repo.update_status_batch(&updates)).await?;
+        }
+        if !cache_keys.is_empty() {
+            self.db_limiter.run(self.queue_repo.record_cache_keys(&cache_keys)).await?;
+        }
+        Ok(())
+    }
+}
+
+/// The message a task panicked with, if it was a string.
+fn panic_message(panic: Box<dyn std::any::Any + Send>) -> String {
+    match panic.downcast::<String>() {
+        Ok(message) => *message,
+        Err(panic) => match panic.downcast_ref::<&str>() {
+            Some(message) => message.to_string(),
+            None => "non-string panic payload".to_string(),
+        },
     }
+}
+
+/// Queue status for an item whose stage errored. Offline cache misses are
+/// skipped so they don't count as failures, and items the quality gate
+/// rejects are quarantined for review.
+fn status_after_error(error: &PipelineError) -> ProcessingStatus {
+    match error {
+        PipelineError::NotCached(_) => ProcessingStatus::Skipped,
+        PipelineError::LowQuality(reason) => ProcessingStatus::Quarantined {
+            reason: reason.clone(),
+        },
+        _ => ProcessingStatus::Failed {
+            error: error.to_string(),
+            retry_count: 0,
+        },
+    }
+}
+
+/// Terms Stage 1 flagged as similar to, or easily confused with, this item.
+fn comparison_terms(stage1: &Stage1Result) -> Vec<String> {
+    stage1.comparison.similar_to.iter()
+        .chain(&stage1.comparison.commonly_confused_with)
+        .cloned()
+        .collect()
+}
+
+/// Link each card to the other cards in the batch its comparison terms name.
+///
+/// `references` maps an item position to the terms it mentions. Matches are
+/// stored in `related_cards` as positions; terms outside the batch are dropped.
+pub fn resolve_related_cards(
+    results: &mut [(VocabularyItem, Stage1Result, Stage2Result)],
+    references: &HashMap<i32, Vec<String>>,
+) {
+    let positions: HashMap<&str, i32> = results.iter()
+        .map(|(item, _, _)| (item.term.trim(), item.position))
+        .collect();
+    
+    let mut resolved = Vec::with_capacity(results.len());
+    for (item, _, _) in results.iter() {
+        let mut related: Vec<String> = Vec::new();
+        for term in references.get(&item.position).into_iter().flatten() {
+            match positions.get(term.trim()) {
+                Some(&position) if position != item.position => {
+                    let position = position.to_string();
+                    if !related.contains(&position) {
+                        related.push(position);
+                    }
+                }
+                _ => {}
+            }
+        }
+        resolved.push(related);
+    }
+    
+    for ((_, _, stage2), related) in results.iter_mut().zip(resolved) {
+        stage2.related_cards = related;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::item;
+    use crate::python_bridge::{ApiClient, MockApiClient};
     
-    pub async fn resume_batch(&self, batch_id: i32) -> Result<BatchResult> {
-        info!("Resuming batch {}", batch_id);
+    #[tokio::test]
+    async fn test_resolve_related_cards_links_batch_members() {
+        let client = MockApiClient;
+        let first = item(1, "사과");
+        let second = item(2, "배");
+        let mut results = Vec::new();
+        for item in [first, second] {
+            let stage1 = client.process_stage1(&item).await.unwrap();
+            let stage2 = client.process_stage2(&item, &stage1).await.unwrap();
+            results.push((item, stage1, stage2));
+        }
         
-        // Get incomplete items
-        let incomplete = self.queue_repo.get_incomplete_items(batch_id).await?;
+        let references = HashMap::from([
+            (1, vec!["배".to_string(), "포도".to_string()]),
+            (2, vec!["사과".to_string()]),
+        ]);
         
-        if incomplete.is_empty() {
-            info!("No incomplete items found for batch {}", batch_id);
-            return Ok(BatchResult {
-                successful: vec![],
-                failed: vec![],
-                total_processed: 0,
-                cache_hits: 0,
-                processing_time: Duration::from_secs(0),
-            });
+        resolve_related_cards(&mut results, &references);
+        
+        assert_eq!(results[0].2.related_cards, vec!["2".to_string()]);
+        assert_eq!(results[1].2.related_cards, vec!["1".to_string()]);
+    }
+    
+    #[test]
+    fn test_rate_tracks_recent_throughput() {
+        let mut progress = ProcessingProgress::new(200, DEFAULT_RATE_SMOOTHING);
+        let start = progress.last_completion;
+        let mut now = start;
+        
+        // A slow API-bound stretch, then a fast cache-heavy one
+        for _ in 0..100 {
+            now += Duration::from_secs(1);
+            progress.record_completion_at(now);
+        }
+        for _ in 0..30 {
+            now += Duration::from_millis(100);
+            progress.record_completion_at(now);
         }
         
-        info!("Found {} incomplete items to process", incomplete.len());
-        self.process_batch(incomplete, batch_id).await
+        let lifetime = progress.completed as f64 / (now - start).as_secs_f64();
+        let rate = progress.rate().unwrap();
+        assert!(lifetime < 1.5);
+        assert!((rate - 10.0).abs() < 0.5, "rate {} should be near 10/s", rate);
+        
+        let eta = progress.eta().unwrap();
+        assert!((eta.as_secs_f64() - 7.0).abs() < 0.5);
+    }
+    
+    #[test]
+    fn test_offline_cache_miss_is_skipped_not_failed() {
+        let miss = ItemFailure::at(FailureStage::Stage1, PipelineError::NotCached("stage 1 for 사과".to_string()));
+        assert!(miss.is_skip());
+        assert!(matches!(status_after_error(&miss.error), ProcessingStatus::Skipped));
+        
+        let api = ItemFailure::at(FailureStage::Stage1, PipelineError::ApiError("boom".to_string()));
+        assert!(!api.is_skip());
+        assert!(matches!(status_after_error(&api.error), ProcessingStatus::Failed { .. }));
     }
-}
\ No newline at end of file
+    
+    #[test]
+    fn test_status_buffer_keeps_latest_per_item() {
+        let buffer = StatusBuffer::new();
+        buffer.push(2, ProcessingStatus::Processing { stage: 1 });
+        buffer.push(1, ProcessingStatus::Processing { stage: 1 });
+        buffer.push(2, ProcessingStatus::Completed);
+        
+        let drained = buffer.drain();
+        assert_eq!(drained.len(), 2);
+        assert_eq!(drained[0].0, 2);
+        assert!(matches!(drained[0].1, ProcessingStatus::Completed));
+        assert!(matches!(drained[1].1, ProcessingStatus::Processing { stage: 1 }));
+        assert!(buffer.is_empty());
+    }
+}
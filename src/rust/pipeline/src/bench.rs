@@ -0,0 +1,257 @@
+//! Throughput benchmarks against a simulated API.
+//!
+//! Each concurrency level gets a fresh pipeline on a throwaway database and
+//! a [`LatencyMockClient`], so runs start from a cold cache, never touch the
+//! real API and can be compared to find where more concurrency stops paying.
+
+use crate::errors::{PipelineError, Result};
+use crate::monitoring::LatencySummary;
+use crate::pipeline::{Pipeline, PipelineConfig};
+use crate::python_bridge::LatencyMockClient;
+use flashcard_core::models::VocabularyItem;
+use serde::{Deserialize, Serialize};
+use std::fmt::Write;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tracing::info;
+
+/// What to run
+#[derive(Debug, Clone)]
+pub struct BenchOptions {
+    /// Synthetic vocabulary items per run
+    pub count: usize,
+    /// Concurrency levels to run, in order
+    pub concurrency: Vec<usize>,
+    /// Simulated duration of each API call
+    pub latency: Duration,
+    /// Random extra duration of up to this much per call
+    pub jitter: Duration,
+    /// Run every level again once its cache is warm
+    pub warm_pass: bool,
+}
+
+/// Whether a run started from an empty cache
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BenchPass {
+    Cold,
+    Warm,
+}
+
+/// Results of one run at one concurrency level
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BenchRun {
+    pub concurrency: usize,
+    pub pass: BenchPass,
+    pub items: usize,
+    pub failed: usize,
+    pub elapsed_secs: f64,
+    pub items_per_sec: f64,
+    pub latency: LatencySummary,
+    pub cache_hits: usize,
+    pub cache_misses: usize,
+    pub api_calls: usize,
+}
+
+/// Run `options` on top of `base`, whose database and concurrency settings
+/// are replaced for each level.
+pub async fn run(base: &PipelineConfig, options: &BenchOptions) -> Result<Vec<BenchRun>> {
+    if options.count == 0 {
+        return Err(PipelineError::ConfigError("count must be at least 1".to_string()));
+    }
+    if options.concurrency.is_empty() || options.concurrency.contains(&0) {
+        return Err(PipelineError::ConfigError(
+            "concurrency levels must be at least 1".to_string()
+        ));
+    }
+    
+    let items = synthetic_items(options.count);
+    let mut runs = Vec::new();
+    
+    for &concurrency in &options.concurrency {
+        info!("Benchmarking {} items at concurrency {}", items.len(), concurrency);
+        
+        // Declared first so the pipeline's pool closes before it is removed
+        let database = ScratchDatabase::new(concurrency);
+        let config = PipelineConfig {
+            database_url: database.url(),
+            max_concurrent: concurrency,
+            api_concurrency: None,
+            adaptive_concurrency: false,
+            batch_size: 0,
+            enable_metrics: false,
+            cache_only: false,
+            ..base.clone()
+        };
+        let client = LatencyMockClient::new(options.latency).with_jitter(options.jitter);
+        let pipeline = Pipeline::with_api_client(config, Arc::new(client)).await?;
+        
+        runs.push(measure(&pipeline, &items, concurrency, BenchPass::Cold).await?);
+        if options.warm_pass {
+            runs.push(measure(&pipeline, &items, concurrency, BenchPass::Warm).await?);
+        }
+    }
+    
+    Ok(runs)
+}
+
+async fn measure(
+    pipeline: &Pipeline,
+    items: &[VocabularyItem],
+    concurrency: usize,
+    pass: BenchPass,
+) -> Result<BenchRun> {
+    let metrics = &pipeline.metrics_collector;
+    let before = metrics.get_metrics();
+    metrics.take_latency_summary();
+    
+    let started = Instant::now();
+    let result = pipeline.process_items(items.to_vec()).await?;
+    let elapsed = started.elapsed().as_secs_f64();
+    
+    let after = metrics.get_metrics();
+    Ok(BenchRun {
+        concurrency,
+        pass,
+        items: result.total_processed,
+        failed: result.failed.len(),
+        elapsed_secs: elapsed,
+        items_per_sec: result.total_processed as f64 / elapsed.max(f64::EPSILON),
+        latency: metrics.take_latency_summary(),
+        cache_hits: after.cache_hits - before.cache_hits,
+        cache_misses: after.cache_misses - before.cache_misses,
+        api_calls: after.api_calls - before.api_calls,
+    })
+}
+
+/// `count` items with distinct two-syllable Hangul terms
+pub fn synthetic_items(count: usize) -> Vec<VocabularyItem> {
+    let now = chrono::Utc::now();
+    (0..count)
+        .map(|index| VocabularyItem {
+            id: None,
+            position: index as i32 + 1,
+            term: synthetic_term(index),
+            word_type: Some("noun".to_string()),
+            source: "bench".to_string(),
+            created_at: now,
+            updated_at: now,
+        })
+        .collect()
+}
+
+/// Distinct for every `index` below 11172², the number of Hangul syllables squared
+fn synthetic_term(index: usize) -> String {
+    const SYLLABLES: usize = 11172;
+    let syllable = |offset: usize| char::from_u32(0xAC00 + offset as u32).unwrap_or('가');
+    [syllable(index / SYLLABLES % SYLLABLES), syllable(index % SYLLABLES)].iter().collect()
+}
+
+/// Render `runs` as a fixed-width table.
+pub fn format_table(runs: &[BenchRun]) -> String {
+    let mut table = format!(
+        "{:>11}  {:<4}  {:>6}  {:>6}  {:>9}  {:>9}  {:>9}  {:>9}  {:>6}  {:>6}\n",
+        "Concurrency", "Pass", "Items", "Failed", "Items/s", "p50 ms", "p95 ms", "p99 ms", "Hits", "Misses"
+    );
+    for run in runs {
+        let pass = match run.pass {
+            BenchPass::Cold => "cold",
+            BenchPass::Warm => "warm",
+        };
+        let _ = writeln!(
+            table,
+            "{:>11}  {:<4}  {:>6}  {:>6}  {:>9.1}  {:>9.1}  {:>9.1}  {:>9.1}  {:>6}  {:>6}",
+            run.concurrency,
+            pass,
+            run.items,
+            run.failed,
+            run.items_per_sec,
+            run.latency.p50_ms,
+            run.latency.p95_ms,
+            run.latency.p99_ms,
+            run.cache_hits,
+            run.cache_misses,
+        );
+    }
+    table
+}
+
+/// A database file in the temp directory, deleted on drop
+struct ScratchDatabase {
+    path: PathBuf,
+}
+
+impl ScratchDatabase {
+    fn new(concurrency: usize) -> Self {
+        let name = format!("flashcard-bench-{}-{}.db", std::process::id(), concurrency);
+        Self {
+            path: std::env::temp_dir().join(name),
+        }
+    }
+    
+    fn url(&self) -> String {
+        format!("sqlite:{}", self.path.display())
+    }
+}
+
+impl Drop for ScratchDatabase {
+    fn drop(&mut self) {
+        for suffix in ["", "-wal", "-shm"] {
+            let mut path = self.path.clone().into_os_string();
+            path.push(suffix);
+            let _ = std::fs::remove_file(path);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+    
+    #[test]
+    fn test_synthetic_terms_are_distinct() {
+        let items = synthetic_items(20_000);
+        let terms: HashSet<&str> = items.iter().map(|item| item.term.as_str()).collect();
+        assert_eq!(terms.len(), items.len());
+        assert_eq!(items[0].term, "가가");
+    }
+    
+    #[tokio::test]
+    async fn test_sweep_reports_each_level_and_warm_hits() {
+        let options = BenchOptions {
+            count: 12,
+            concurrency: vec![1, 4],
+            latency: Duration::from_millis(5),
+            jitter: Duration::ZERO,
+            warm_pass: true,
+        };
+        
+        let runs = run(&PipelineConfig::default(), &options).await.unwrap();
+        
+        let levels: Vec<(usize, BenchPass)> = runs.iter().map(|run| (run.concurrency, run.pass)).collect();
+        assert_eq!(levels, [
+            (1, BenchPass::Cold),
+            (1, BenchPass::Warm),
+            (4, BenchPass::Cold),
+            (4, BenchPass::Warm),
+        ]);
+        
+        for run in &runs {
+            assert_eq!(run.items, 12);
+            assert_eq!(run.failed, 0);
+            assert_eq!(run.latency.samples, 12);
+            assert!(run.items_per_sec > 0.0);
+        }
+        
+        // Cold runs wait on both simulated calls; warm ones are served from cache
+        assert!(runs[0].latency.p50_ms >= 10.0, "{:?}", runs[0].latency);
+        assert_eq!(runs[0].cache_hits, 0);
+        assert_eq!(runs[1].cache_hits, 12);
+        assert_eq!(runs[1].api_calls, 0);
+        
+        let table = format_table(&runs);
+        assert_eq!(table.lines().count(), 5);
+    }
+}
@@ -0,0 +1,315 @@
+//! A read-only web page for looking through processed batches and their
+//! cards without exporting them.
+//!
+//! Everything shown comes from the queue and the Stage 2 cache. Card text is
+//! model output, so all of it is HTML-escaped before it reaches the page.
+//! There is no authentication, so it listens on the loopback address unless
+//! told otherwise.
+
+use crate::errors::{PipelineError, Result};
+use crate::export::combined_tags;
+use crate::pipeline::BatchInfo;
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::{Html, IntoResponse, Response},
+    routing::get,
+    Router,
+};
+use flashcard_core::models::{BatchId, BatchSummary, Stage2Result, VocabularyItem};
+use flashcard_core::repositories::{CacheRepository, QueueRepository};
+use serde::Deserialize;
+use std::fmt::Write;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use tracing::{info, error};
+
+/// Batches listed per page
+pub const BATCHES_PER_PAGE: usize = 25;
+
+/// Cards shown per page of a batch
+pub const CARDS_PER_PAGE: usize = 50;
+
+#[derive(Clone)]
+pub struct BrowseState {
+    pub cache_repo: Arc<dyn CacheRepository>,
+    pub queue_repo: Arc<dyn QueueRepository>,
+    /// Cache namespace whose cards are shown
+    pub namespace: String,
+}
+
+/// `?page=N`, counted from 1
+#[derive(Debug, Default, Deserialize)]
+pub struct PageQuery {
+    page: Option<usize>,
+}
+
+impl PageQuery {
+    fn number(&self) -> usize {
+        self.page.unwrap_or(1).max(1)
+    }
+}
+
+pub fn router(state: BrowseState) -> Router {
+    Router::new()
+        .route("/", get(batches))
+        .route("/batches/{batch_id}", get(batch))
+        .with_state(state)
+}
+
+/// Serve the browser on `addr` until the task is dropped.
+pub async fn serve(addr: SocketAddr, state: BrowseState) -> Result<()> {
+    let listener = tokio::net::TcpListener::bind(addr).await?;
+    
+    info!("Card browser listening on {}", addr);
+    axum::serve(listener, router(state))
+        .await
+        .map_err(PipelineError::IoError)
+}
+
+async fn batches(State(state): State<BrowseState>, Query(query): Query<PageQuery>) -> Response {
+    let page = query.number();
+    // The queue lists batches newest first without an offset, so read up to
+    // the end of this page and skip the earlier ones. One extra row tells
+    // whether there is a next page.
+    let limit = page.saturating_mul(BATCHES_PER_PAGE).saturating_add(1);
+    match state.queue_repo.list_batches(None, i64::try_from(limit).unwrap_or(i64::MAX)).await {
+        Ok(rows) => {
+            let (batches, has_next) = batch_page(rows, page);
+            Html(render_batches(&batches, page, has_next)).into_response()
+        }
+        Err(e) => server_error(e.into()),
+    }
+}
+
+async fn batch(
+    State(state): State<BrowseState>,
+    Path(batch_id): Path<BatchId>,
+    Query(query): Query<PageQuery>,
+) -> Response {
+    match state.cache_repo.get_stage2_results_for_batch_in(&batch_id, &state.namespace).await {
+        Ok(cards) if cards.is_empty() => (
+            StatusCode::NOT_FOUND,
+            Html(page(
+                &format!("Batch {}", escape_html(batch_id.as_str())),
+                "<p>No cached cards for this batch.</p><p><a href=\"/\">All batches</a></p>",
+            )),
+        ).into_response(),
+        Ok(cards) => Html(render_cards(&batch_id, &cards, query.number())).into_response(),
+        Err(e) => server_error(e.into()),
+    }
+}
+
+fn server_error(e: PipelineError) -> Response {
+    error!("Card browser query failed: {}", e);
+    (
+        StatusCode::INTERNAL_SERVER_ERROR,
+        Html(page("Error", &format!("<p>{}</p>", escape_html(&e.to_string())))),
+    ).into_response()
+}
+
+/// Escape `text` for use in HTML element content and quoted attributes.
+pub fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// `batch_id` as a URL path segment.
+fn path_segment(batch_id: &BatchId) -> String {
+    let mut encoded = String::new();
+    for byte in batch_id.as_str().bytes() {
+        if byte.is_ascii_alphanumeric() || b"-._~".contains(&byte) {
+            encoded.push(byte as char);
+        } else {
+            let _ = write!(encoded, "%{:02X}", byte);
+        }
+    }
+    encoded
+}
+
+/// A complete page around already-escaped `title` and `body`.
+fn page(title: &str, body: &str) -> String {
+    format!(
+        "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n<title>{title}</title>\n\
+         <style>\n\
+         body {{ font-family: sans-serif; margin: 2em; }}\n\
+         table {{ border-collapse: collapse; width: 100%; }}\n\
+         th, td {{ border: 1px solid #ccc; padding: 0.4em; text-align: left; vertical-align: top; }}\n\
+         .secondary {{ color: #666; }}\n\
+         </style>\n</head>\n<body>\n<h1>{title}</h1>\n{body}\n</body>\n</html>\n"
+    )
+}
+
+/// Previous/next links for page `number`; `link` builds the URL of a page.
+fn pager(number: usize, has_next: bool, link: impl Fn(usize) -> String) -> String {
+    let mut links = Vec::new();
+    if number > 1 {
+        links.push(format!("<a href=\"{}\">&larr; Previous</a>", link(number - 1)));
+    }
+    links.push(format!("Page {}", number));
+    if has_next {
+        links.push(format!("<a href=\"{}\">Next &rarr;</a>", link(number + 1)));
+    }
+    format!("<p>{}</p>", links.join(" | "))
+}
+
+/// The batches on page `number` of `rows`, which start at the newest
+/// batch, and whether `rows` goes on past the page.
+fn batch_page(rows: Vec<BatchSummary>, number: usize) -> (Vec<BatchInfo>, bool) {
+    let offset = number.saturating_sub(1).saturating_mul(BATCHES_PER_PAGE);
+    let has_next = rows.len() > offset.saturating_add(BATCHES_PER_PAGE);
+    let batches = rows.into_iter()
+        .skip(offset)
+        .take(BATCHES_PER_PAGE)
+        .map(BatchInfo::from)
+        .collect();
+    (batches, has_next)
+}
+
+/// Page `number` of the batch list.
+pub fn render_batches(batches: &[BatchInfo], number: usize, has_next: bool) -> String {
+    if batches.is_empty() && number == 1 {
+        return page("Batches", "<p>No batches yet.</p>");
+    }
+    
+    let mut body = String::from(
+        "<table>\n<tr><th>Batch</th><th>Label</th><th>Items</th><th>Created</th></tr>\n"
+    );
+    for batch in batches {
+        let _ = writeln!(
+            body,
+            "<tr><td><a href=\"/batches/{}\">{}</a></td><td>{}</td><td>{}</td><td>{}</td></tr>",
+            path_segment(&batch.batch_id),
+            escape_html(batch.batch_id.as_str()),
+            escape_html(batch.label.as_deref().unwrap_or_default()),
+            batch.total_items,
+            batch.created_at.format("%Y-%m-%d %H:%M:%S UTC"),
+        );
+    }
+    body.push_str("</table>\n");
+    body.push_str(&pager(number, has_next, |n| format!("/?page={}", n)));
+    page("Batches", &body)
+}
+
+/// Page `number` of a batch's cards, with front, back, tags and difficulty.
+pub fn render_cards(batch_id: &BatchId, cards: &[(VocabularyItem, Stage2Result)], number: usize) -> String {
+    let title = format!("Batch {}", escape_html(batch_id.as_str()));
+    let start = number.saturating_sub(1).saturating_mul(CARDS_PER_PAGE).min(cards.len());
+    let end = (start + CARDS_PER_PAGE).min(cards.len());
+    
+    let mut body = format!(
+        "<p><a href=\"/\">All batches</a> | {} cards, showing {}&ndash;{}</p>\n",
+        cards.len(),
+        if start == end { 0 } else { start + 1 },
+        end,
+    );
+    body.push_str("<table>\n<tr><th>#</th><th>Front</th><th>Back</th><th>Tags</th><th>Difficulty</th></tr>\n");
+    for (item, card) in &cards[start..end] {
+        let _ = writeln!(
+            body,
+            "<tr><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>",
+            item.position,
+            face(&card.front.primary_field, card.front.secondary_field.as_deref(), card.front.example_sentence.as_deref()),
+            face(&card.back.primary_field, card.back.secondary_field.as_deref(), card.back.example_sentence.as_deref()),
+            escape_html(&combined_tags(&card.front)),
+            escape_html(&format!("{:?}", card.front.difficulty_level)),
+        );
+    }
+    body.push_str("</table>\n");
+    
+    let segment = path_segment(batch_id);
+    body.push_str(&pager(number, end < cards.len(), |n| format!("/batches/{}?page={}", segment, n)));
+    page(&title, &body)
+}
+
+/// One face of a card: the primary field, then the secondary field and
+/// example if present.
+fn face(primary: &str, secondary: Option<&str>, example: Option<&str>) -> String {
+    let mut html = escape_html(primary);
+    for extra in [secondary, example].into_iter().flatten().filter(|text| !text.trim().is_empty()) {
+        let _ = write!(html, "<br><span class=\"secondary\">{}</span>", escape_html(extra));
+    }
+    html
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::card;
+    
+    #[test]
+    fn test_card_content_is_escaped() {
+        let (item, mut stage2) = card(1, "<img src=x onerror=alert(1)>", "<script>alert('x')</script> & more");
+        stage2.front.thematic_tags = vec!["\"quoted\"".to_string()];
+        
+        let html = render_cards(&BatchId::new("b<1>"), &[(item, stage2)], 1);
+        
+        assert!(!html.contains("<script>"));
+        assert!(!html.contains("<img"));
+        assert!(html.contains("&lt;img src=x onerror=alert(1)&gt;"));
+        assert!(html.contains("&lt;script&gt;alert(&#39;x&#39;)&lt;/script&gt; &amp; more"));
+        assert!(html.contains("&quot;quoted&quot;"));
+        assert!(html.contains("<title>Batch b&lt;1&gt;</title>"));
+    }
+    
+    #[test]
+    fn test_large_batches_are_paginated() {
+        let cards: Vec<_> = (1..=CARDS_PER_PAGE as i32 + 5)
+            .map(|position| card(position, "학교", "school"))
+            .collect();
+        let batch_id = BatchId::new("batch 7");
+        
+        let first = render_cards(&batch_id, &cards, 1);
+        assert_eq!(first.matches("<tr><td>").count(), CARDS_PER_PAGE);
+        assert!(first.contains("showing 1&ndash;50"));
+        assert!(first.contains("href=\"/batches/batch%207?page=2\""));
+        
+        let second = render_cards(&batch_id, &cards, 2);
+        assert_eq!(second.matches("<tr><td>").count(), 5);
+        assert!(second.contains("href=\"/batches/batch%207?page=1\""));
+        assert!(!second.contains("Next"));
+        
+        // Past the end there is nothing to show, but no panic either
+        let beyond = render_cards(&batch_id, &cards, 9);
+        assert_eq!(beyond.matches("<tr><td>").count(), 0);
+    }
+    
+    #[test]
+    fn test_batch_list_pages_skip_earlier_pages() {
+        let rows = |count: usize| -> Vec<BatchSummary> {
+            (0..count).map(|n| BatchSummary {
+                batch_id: BatchId::new(format!("batch-{}", n)),
+                label: None,
+                total_items: 2,
+                completed_items: 2,
+                failed_items: 0,
+                status: "completed".to_string(),
+                start_time: chrono::Utc::now(),
+                end_time: None,
+            }).collect()
+        };
+        
+        // As read for page 2: two pages and the row that tells of a third
+        let (second, has_next) = batch_page(rows(2 * BATCHES_PER_PAGE + 1), 2);
+        assert_eq!(second.len(), BATCHES_PER_PAGE);
+        assert_eq!(second[0].batch_id, BatchId::new(format!("batch-{}", BATCHES_PER_PAGE)));
+        assert!(has_next);
+        
+        let (last, has_next) = batch_page(rows(BATCHES_PER_PAGE + 3), 2);
+        assert_eq!(last.len(), 3);
+        assert!(!has_next);
+        
+        let (beyond, has_next) = batch_page(rows(3), 5);
+        assert!(beyond.is_empty() && !has_next);
+    }
+}
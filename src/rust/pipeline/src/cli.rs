@@ -1,5 +1,15 @@
-use clap::{Parser, Subcommand};
+use clap::{ArgMatches, Args, Parser, Subcommand, ValueEnum};
+use flashcard_core::database::{SqlitePragmas, Synchronous, TempStore};
+use flashcard_core::logging::{Rotation, WorkerGuard};
+use flashcard_core::models::{BatchId, Stage2Mode};
+use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
+use crate::anki::AnkiNoteType;
+use crate::batch_processor::DEFAULT_RATE_SMOOTHING;
+use crate::config::{ConfigFile, ExplicitArgs};
+use crate::export::{ExportFormat, MemoryPalaceColumns, SortOrder};
+use crate::input::InputFormat;
+use crate::python_bridge::DEFAULT_MODEL;
 
 #[derive(Parser)]
 #[command(name = "flashcard-pipeline")]
@@ -9,11 +19,18 @@ pub struct Cli {
     #[command(subcommand)]
     pub command: Commands,
     
-    /// Database URL
+    /// Read settings from this TOML file; flags given on the command line
+    /// take precedence over it
+    #[arg(long, value_name = "PATH", env = "FLASHCARD_CONFIG")]
+    pub config: Option<PathBuf>,
+    
+    /// Database path or URL, e.g. sqlite:pipeline.db, ~/data/pipeline.db or
+    /// :memory: for a throwaway run
     #[arg(long, env = "DATABASE_URL", default_value = "sqlite:pipeline.db")]
     pub database_url: String,
     
-    /// Cache directory
+    /// Directory for the Python client's on-disk response cache
+    /// (created if missing)
     #[arg(long, env = "CACHE_DIR", default_value = ".cache")]
     pub cache_dir: PathBuf,
     
@@ -24,39 +41,508 @@ pub struct Cli {
     /// Disable colored output
     #[arg(long)]
     pub no_color: bool,
+    
+    /// Also write logs to rotating files in this directory
+    #[arg(long, value_name = "DIR")]
+    pub log_file: Option<PathBuf>,
+    
+    /// How often --log-file starts a new file
+    #[arg(long, value_enum, default_value_t = LogRotation::Daily)]
+    pub log_rotation: LogRotation,
+    
+    /// Start even if an already-applied database migration was edited
+    #[arg(long)]
+    pub allow_migration_drift: bool,
+    
+    /// Keep cached results under this namespace, e.g. a customer name, apart
+    /// from other tenants' sharing the database
+    #[arg(long, env = "FLASHCARD_NAMESPACE")]
+    pub namespace: Option<String>,
+    
+    /// Bytes of the database file SQLite maps into memory; 0 turns
+    /// mapping off (default: 30 GB, 256 MiB on 32-bit platforms)
+    #[arg(long, value_name = "BYTES")]
+    pub sqlite_mmap_size: Option<u64>,
+    
+    /// SQLite page cache per connection, in pages, or in KiB if negative
+    /// (default: -2000)
+    #[arg(long, value_name = "N", allow_negative_numbers = true)]
+    pub sqlite_cache_size: Option<i64>,
+    
+    /// How often SQLite waits for writes to reach the disk (default: normal)
+    #[arg(long, value_enum)]
+    pub sqlite_synchronous: Option<SynchronousArg>,
+    
+    /// Where SQLite keeps temporary tables (default: memory)
+    #[arg(long, value_enum)]
+    pub sqlite_temp_store: Option<TempStoreArg>,
+}
+
+/// Rollover schedule for `--log-file`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogRotation {
+    #[default]
+    Daily,
+    Hourly,
+}
+
+impl LogRotation {
+    fn rotation(self) -> Rotation {
+        match self {
+            LogRotation::Daily => Rotation::DAILY,
+            LogRotation::Hourly => Rotation::HOURLY,
+        }
+    }
+}
+
+/// Which card fields `--stage2-mode` asks Stage 2 to generate
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Stage2ModeArg {
+    /// Every field: examples, mnemonic, notes and tags
+    Full,
+    /// Front, back, pronunciation and tags only, for fewer tokens per card
+    Minimal,
+}
+
+impl Stage2ModeArg {
+    pub fn mode(self) -> Stage2Mode {
+        match self {
+            Stage2ModeArg::Full => Stage2Mode::Full,
+            Stage2ModeArg::Minimal => Stage2Mode::Minimal,
+        }
+    }
+}
+
+/// `--sqlite-synchronous` values
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum SynchronousArg {
+    Off,
+    Normal,
+    Full,
+    Extra,
+}
+
+impl SynchronousArg {
+    pub fn synchronous(self) -> Synchronous {
+        match self {
+            SynchronousArg::Off => Synchronous::Off,
+            SynchronousArg::Normal => Synchronous::Normal,
+            SynchronousArg::Full => Synchronous::Full,
+            SynchronousArg::Extra => Synchronous::Extra,
+        }
+    }
+}
+
+/// `--sqlite-temp-store` values
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum TempStoreArg {
+    /// Whatever SQLite was compiled with
+    Default,
+    File,
+    Memory,
+}
+
+impl TempStoreArg {
+    pub fn temp_store(self) -> TempStore {
+        match self {
+            TempStoreArg::Default => TempStore::Default,
+            TempStoreArg::File => TempStore::File,
+            TempStoreArg::Memory => TempStore::Memory,
+        }
+    }
+}
+
+/// Options `process` and `process-pending` share: how items are processed
+/// and how their cards are exported
+#[derive(Args)]
+pub struct RunArgs {
+    /// Output TSV file path; `-` writes to stdout and, with the `s3`
+    /// feature, `s3://bucket/key` uploads to S3. A directory (existing,
+    /// or ending in `/`) gets a new `batch_<id>_<timestamp>.tsv` per run
+    #[arg(short, long, default_value = "output.tsv")]
+    pub output: PathBuf,
+    
+    /// Maximum items processed concurrently
+    #[arg(long, default_value_t = 5)]
+    pub max_concurrent: usize,
+    
+    /// Maximum live API calls, separate from --max-concurrent so cache
+    /// hits aren't held back (default: no separate limit)
+    #[arg(long)]
+    pub api_concurrency: Option<usize>,
+    
+    /// Maximum cache queries and queue writes in flight at once, so
+    /// --max-concurrent can exceed the database pool (default: the pool
+    /// size less 2)
+    #[arg(long)]
+    pub db_concurrency: Option<usize>,
+    
+    /// Adjust concurrency at runtime from cache-hit and error rates
+    #[arg(long)]
+    pub adaptive: bool,
+    
+    /// Lowest concurrency the adaptive controller will use
+    #[arg(long, default_value_t = 2)]
+    pub min_concurrency: usize,
+    
+    /// Error rate above which the adaptive controller backs off
+    #[arg(long, default_value_t = 0.05)]
+    pub target_error_rate: f64,
+    
+    /// Weight of the latest completion in the progress rate and ETA, from
+    /// 0 to 1; higher follows changes in throughput sooner but jumps more
+    #[arg(long, default_value_t = DEFAULT_RATE_SMOOTHING)]
+    pub rate_smoothing: f64,
+    
+    /// Items processed and checkpointed together before the next
+    /// chunk starts (0 = all at once)
+    #[arg(long, default_value_t = 10)]
+    pub batch_size: usize,
+    
+    /// Chunks ahead of the running one to look up in the cache in the
+    /// background (0 = none)
+    #[arg(long, default_value_t = 0)]
+    pub prefetch_chunks: usize,
+    
+    /// Label stored with the batch, e.g. a customer name
+    #[arg(long)]
+    pub label: Option<String>,
+    
+    /// Retries after an item's first attempt before it is quarantined
+    #[arg(long, default_value_t = 3)]
+    pub max_retries: u32,
+    
+    /// Total time one item may spend on API attempts and retry waits,
+    /// e.g. "2m"; an item out of time fails even with retries left
+    #[arg(long, value_parser = humantime::parse_duration)]
+    pub item_budget: Option<std::time::Duration>,
+    
+    /// After a first SIGINT or SIGTERM, how long items in flight may take
+    /// to finish, e.g. "1m", before they are interrupted (default: 30s)
+    #[arg(long, value_parser = humantime::parse_duration)]
+    pub shutdown_grace: Option<std::time::Duration>,
+    
+    /// Abort the batch after this many items in a row fail; finished
+    /// cards are still exported and the batch can be resumed
+    #[arg(long, value_name = "N")]
+    pub max_failures: Option<usize>,
+    
+    /// Abort the batch on the first failed item (--max-failures 1)
+    #[arg(long, conflicts_with = "max_failures")]
+    pub fail_fast: bool,
+    
+    /// Abort the batch once more than this fraction of its items
+    /// failed, e.g. 0.5, checked from the 20th item on
+    #[arg(long)]
+    pub max_failure_rate: Option<f64>,
+    
+    /// Write failed items to this CSV (default: <OUTPUT>.errors.csv)
+    #[arg(long)]
+    pub error_report: Option<PathBuf>,
+    
+    /// Write a JSON summary of the run to this file
+    #[arg(long)]
+    pub report: Option<PathBuf>,
+    
+    /// Write card statistics (counts by difficulty, frequency and card
+    /// type, average field lengths) as JSON to this file, or to
+    /// <OUTPUT>.stats.json if no path is given
+    #[arg(long, value_name = "PATH")]
+    pub stats: Option<Option<PathBuf>>,
+    
+    /// Skip cards that fail to export, listing them at the end, instead
+    /// of aborting the export
+    #[arg(long)]
+    pub skip_export_errors: bool,
+    
+    /// Prefix fields starting with =, +, - or @ so spreadsheets show
+    /// them as text instead of running them as formulas; the guard
+    /// defaults to '
+    #[arg(long, value_name = "GUARD")]
+    pub sanitize_formulas: Option<Option<String>>,
+    
+    /// Key cached results by the model that produced them, so changing
+    /// models recomputes instead of reusing another model's results
+    #[arg(long)]
+    pub pin_model_in_key: bool,
+    
+    /// Recompute cached results whose request, e.g. the model it was
+    /// sent to, differs from the one that would be sent now
+    #[arg(long)]
+    pub respect_request_hash: bool,
+    
+    /// Log why each item's stages were served from the cache or
+    /// computed: hit or miss, request hash match, forced refresh
+    #[arg(long)]
+    pub explain_cache: bool,
+    
+    /// Record every API call, with its model, tokens and latency, for
+    /// `api-log`; costs a database write per call
+    #[arg(long)]
+    pub audit: bool,
+    
+    /// Skip export (useful for testing)
+    #[arg(long)]
+    pub no_export: bool,
+    
+    /// Export as CSV instead of TSV
+    #[arg(long)]
+    pub csv: bool,
+    
+    /// Omit the header row (for positional Anki field mapping)
+    #[arg(long)]
+    pub no_headers: bool,
+    
+    /// End exported lines with CRLF, as Windows tools expect
+    #[arg(long)]
+    pub crlf: bool,
+    
+    /// Start the export with a UTF-8 byte order mark, so Excel shows
+    /// Korean text correctly
+    #[arg(long)]
+    pub bom: bool,
+    
+    /// Lay out columns for an Anki note type, with Anki header directives
+    #[arg(long, value_enum)]
+    pub anki: Option<AnkiNoteType>,
+    
+    /// Anki deck to import into (with --anki)
+    #[arg(long, requires = "anki")]
+    pub deck: Option<String>,
+    
+    /// Export only term, meaning, mnemonic and metaphor, leaving out
+    /// cards without a mnemonic
+    #[arg(long, conflicts_with_all = ["anki", "stream"])]
+    pub mnemonics_only: bool,
+    
+    /// Write each card to the output as soon as it completes. Streamed
+    /// cards leave the Related column empty, since the cards they would
+    /// link to may not have finished yet
+    #[arg(long)]
+    pub stream: bool,
+    
+    /// With --stream, the most finished cards kept in memory; beyond
+    /// that they are only counted (default: no limit)
+    #[arg(long, requires = "stream")]
+    pub max_buffered_results: Option<usize>,
+    
+    /// Threads used to format export rows (0 = one per core)
+    #[arg(long, default_value_t = 1)]
+    pub export_threads: usize,
+    
+    /// Model for both stages, e.g. anthropic/claude-3.5-sonnet
+    #[arg(long, default_value = DEFAULT_MODEL)]
+    pub model: String,
+    
+    /// Model for Stage 1 only (overrides --model)
+    #[arg(long)]
+    pub stage1_model: Option<String>,
+    
+    /// Model for Stage 2 only (overrides --model)
+    #[arg(long)]
+    pub stage2_model: Option<String>,
+    
+    /// Stage 1 prompt template to use instead of the built-in one
+    #[arg(long, value_name = "PATH")]
+    pub stage1_prompt: Option<PathBuf>,
+    
+    /// Stage 2 prompt template to use instead of the built-in one
+    #[arg(long, value_name = "PATH")]
+    pub stage2_prompt: Option<PathBuf>,
+    
+    /// Hash terms byte-for-byte instead of NFC-normalized and trimmed
+    /// (matches cache keys created by older versions)
+    #[arg(long)]
+    pub exact_cache_keys: bool,
+    
+    /// Ignore cached Stage 1 results, recomputing and re-caching them.
+    /// Cached cards are still served unless --refresh-stage2 is also set
+    #[arg(long, conflicts_with = "offline")]
+    pub refresh_stage1: bool,
+    
+    /// Ignore cached Stage 2 cards, e.g. after changing the card template
+    #[arg(long, conflicts_with = "offline")]
+    pub refresh_stage2: bool,
+    
+    /// Ignore the cache for both stages
+    #[arg(long, conflicts_with = "offline")]
+    pub refresh_all: bool,
+    
+    /// Fields Stage 2 generates. Minimal cards are cached separately
+    /// and exported with fewer columns
+    #[arg(long, value_enum, default_value_t = Stage2ModeArg::Full)]
+    pub stage2_mode: Stage2ModeArg,
+    
+    /// Build cards from the cache only, skipping uncached items and the
+    /// API health check
+    #[arg(long)]
+    pub offline: bool,
+    
+    /// Seconds the startup health check may take
+    #[arg(long, default_value_t = 30)]
+    pub health_timeout: u64,
+    
+    /// Back off exponentially on rate limits instead of waiting for the
+    /// server's retry-after
+    #[arg(long)]
+    pub ignore_retry_after: bool,
+    
+    /// Build basic cards from Stage 1 alone when Stage 2 fails or, with
+    /// --offline, isn't cached
+    #[arg(long)]
+    pub fallback_cards: bool,
+    
+    /// Word list, one word per line and most common first, used to estimate
+    /// the difficulty of input terms
+    #[arg(long, value_name = "PATH")]
+    pub frequency_list: Option<PathBuf>,
+    
+    /// Send every Stage 1 analysis on to Stage 2, instead of quarantining
+    /// ones without a meaning, keywords or IPA for review
+    #[arg(long)]
+    pub no_quality_gate: bool,
+    
+    /// Remove HTML markup and surrounding whitespace from card fields
+    /// before export
+    #[arg(long)]
+    pub strip_html: bool,
+    
+    /// Add this tag to every exported card (repeatable)
+    #[arg(long = "tag", value_name = "TAG")]
+    pub tags: Vec<String>,
+    
+    /// Also tag every card with its batch id and label, today's date and
+    /// its source file name
+    #[arg(long)]
+    pub auto_tags: bool,
+    
+    /// Add Homonyms, Similar To, Different From and Confused With columns
+    #[arg(long)]
+    pub comparison_columns: bool,
+    
+    /// Add Stage 1's memory palace (location, anchor and metaphor), as
+    /// one sentence or as separate columns
+    #[arg(long, value_enum, value_name = "COLUMNS")]
+    pub memory_palace: Option<MemoryPalaceColumns>,
+    
+    /// Order of the exported cards: by input position (the default), or
+    /// by learning order for a structured curriculum
+    #[arg(long, value_enum, value_name = "ORDER")]
+    pub sort: Option<SortOrder>,
+    
+    /// Cut exported columns longer than this many characters, ending
+    /// them with an ellipsis
+    #[arg(long)]
+    pub max_field_chars: Option<usize>,
+    
+    /// Limit one card field, e.g. `cultural_notes=200` (repeatable)
+    #[arg(long = "field-limit", value_name = "FIELD=CHARS", value_parser = parse_field_limit)]
+    pub field_limits: Vec<(String, usize)>,
 }
 
 #[derive(Subcommand)]
 pub enum Commands {
-    /// Process vocabulary from CSV file
+    /// Process vocabulary from CSV or JSONL files
     Process {
-        /// Input CSV file path
-        #[arg(value_name = "INPUT")]
-        input: PathBuf,
+        /// Input files, merged into one batch with positions numbered
+        /// across files in the order given
+        #[arg(value_name = "INPUT", required = true, num_args = 1..)]
+        input: Vec<PathBuf>,
         
-        /// Output TSV file path
-        #[arg(short, long, default_value = "output.tsv")]
-        output: PathBuf,
+        /// Layout of the input files
+        #[arg(long, value_enum, default_value_t = InputFormat::Csv)]
+        input_format: InputFormat,
+        
+        #[command(flatten)]
+        run: RunArgs,
+        
+        /// Resume from a specific batch ID
+        #[arg(long)]
+        resume: Option<BatchId>,
         
-        /// Maximum concurrent API requests
+        /// Strip trailing particles so the dictionary form is processed,
+        /// e.g. 학교에서 -> 학교
+        #[arg(long)]
+        strip_particles: bool,
+        
+        /// Skip input lines starting with this character
+        #[arg(long, default_value = "#", value_parser = parse_comment_char)]
+        comment_char: u8,
+    },
+    
+    /// Process vocabulary already in the database that has no cached results
+    ProcessPending {
+        /// Process at most this many items
+        #[arg(long)]
+        limit: Option<usize>,
+        
+        #[command(flatten)]
+        run: RunArgs,
+    },
+    
+    /// Run queued batches that haven't been started, e.g. enqueued by
+    /// other processes, several at once and each to its own file
+    RunQueue {
+        /// Directory to write each batch's `batch_<id>_<timestamp>.tsv` to
+        #[arg(short, long, default_value = "exports")]
+        output_dir: PathBuf,
+        
+        /// Batches processed at once; they share the --max-concurrent item
+        /// slots rather than getting their own
+        #[arg(long, default_value_t = 1)]
+        max_concurrent_batches: usize,
+        
+        /// Maximum items processed concurrently, across all batches
         #[arg(long, default_value_t = 5)]
         max_concurrent: usize,
         
-        /// Batch size for processing
-        #[arg(long, default_value_t = 10)]
-        batch_size: usize,
-        
-        /// Resume from a specific batch ID
+        /// Maximum live API calls, across all batches (default: no separate
+        /// limit)
         #[arg(long)]
-        resume: Option<i32>,
+        api_concurrency: Option<usize>,
         
-        /// Skip export (useful for testing)
+        /// Keep running, checking for new batches this often (e.g. "30s"),
+        /// until interrupted
+        #[arg(long, value_parser = humantime::parse_duration)]
+        watch: Option<std::time::Duration>,
+        
+        /// Record every API call, with its model, tokens and latency, for
+        /// `api-log`; costs a database write per call
         #[arg(long)]
-        no_export: bool,
+        audit: bool,
+        
+        /// After a first SIGINT or SIGTERM, how long items in flight may take
+        /// to finish, e.g. "1m", before they are interrupted (default: 30s)
+        #[arg(long, value_parser = humantime::parse_duration)]
+        shutdown_grace: Option<std::time::Duration>,
+    },
+    
+    /// Suspend a term: its cards are exported with the "suspended" tag
+    /// until it is unsuspended
+    Suspend {
+        /// The term to suspend
+        term: String,
         
-        /// Export as CSV instead of TSV
+        /// Also mark the term as a leech, tagged "leech" on export
         #[arg(long)]
-        csv: bool,
+        leech: bool,
+    },
+    
+    /// Lift a term's suspension and its leech mark
+    Unsuspend {
+        /// The term to unsuspend
+        term: String,
+    },
+    
+    /// Show API calls recorded with --audit
+    ApiLog {
+        /// Only calls made for this batch
+        batch_id: Option<BatchId>,
+        
+        /// Show at most this many of the latest calls
+        #[arg(long, default_value_t = 50)]
+        limit: usize,
     },
     
     /// Show cache statistics
@@ -66,6 +552,18 @@ pub enum Commands {
         detailed: bool,
     },
     
+    /// Show how the cache grew over time, from recorded stats snapshots,
+    /// next to the savings it brought
+    CacheGrowth {
+        /// Show snapshots recorded in this many last days
+        #[arg(long, default_value_t = 30)]
+        days: u32,
+        
+        /// Record a snapshot first, e.g. when run on a schedule
+        #[arg(long)]
+        record: bool,
+    },
+    
     /// Clear cache
     ClearCache {
         /// Clear only stage 1 cache
@@ -73,14 +571,34 @@ pub enum Commands {
         stage1_only: bool,
         
         /// Clear only stage 2 cache
-        #[arg(long)]
+        #[arg(long, conflicts_with = "stage1_only")]
         stage2_only: bool,
         
+        /// Clear only entries computed by this model, e.g.
+        /// anthropic/claude-3-sonnet
+        #[arg(long, value_name = "NAME")]
+        clear_model: Option<String>,
+        
         /// Force clear without confirmation
         #[arg(long, short)]
         force: bool,
     },
     
+    /// Delete cache entries that were created long ago and rarely read
+    CachePrune {
+        /// Minimum age since creation, e.g. "30d" or "12h"
+        #[arg(long, value_parser = humantime::parse_duration, default_value = "30d")]
+        older_than: std::time::Duration,
+        
+        /// Highest access count still considered cold (1 = never re-read)
+        #[arg(long, default_value_t = 1)]
+        max_access: i32,
+        
+        /// Count cold entries without deleting them
+        #[arg(long)]
+        dry_run: bool,
+    },
+    
     /// Test API connection
     TestConnection {
         /// Test with a sample term
@@ -93,6 +611,14 @@ pub enum Commands {
         /// Output in JSON format
         #[arg(long)]
         json: bool,
+        
+        /// Report the queue as degraded above this many pending items
+        #[arg(long)]
+        queue_warn_depth: Option<i64>,
+        
+        /// Report unhealthy above this many pending items
+        #[arg(long)]
+        queue_fail_depth: Option<i64>,
     },
     
     /// List processing batches
@@ -101,6 +627,10 @@ pub enum Commands {
         #[arg(long, default_value_t = 10)]
         limit: usize,
         
+        /// Only show batches with this label
+        #[arg(long)]
+        label: Option<String>,
+        
         /// Show detailed information
         #[arg(long, short)]
         detailed: bool,
@@ -109,7 +639,7 @@ pub enum Commands {
     /// Show batch status
     BatchStatus {
         /// Batch ID to check
-        batch_id: i32,
+        batch_id: BatchId,
         
         /// Show failed items
         #[arg(long)]
@@ -123,6 +653,43 @@ pub enum Commands {
         output: Option<PathBuf>,
     },
     
+    /// Serve health, readiness and metrics over HTTP
+    #[cfg(feature = "server")]
+    Serve {
+        /// Port to listen on
+        #[arg(long, default_value_t = 8080)]
+        port: u16,
+        
+        /// Address to listen on. Only this machine can reach the endpoints
+        /// unless another address, e.g. 0.0.0.0 for a container's probes,
+        /// is given
+        #[arg(long, default_value = "127.0.0.1")]
+        host: std::net::IpAddr,
+        
+        /// Report the queue as degraded above this many pending items
+        #[arg(long)]
+        queue_warn_depth: Option<i64>,
+        
+        /// Answer /health with 503 above this many pending items
+        #[arg(long)]
+        queue_fail_depth: Option<i64>,
+    },
+    
+    /// Serve a read-only web page for browsing processed batches and their
+    /// cards
+    #[cfg(feature = "server")]
+    Browse {
+        /// Port to listen on
+        #[arg(long, default_value_t = 8080)]
+        port: u16,
+        
+        /// Address to listen on. The page has no authentication, so only
+        /// this machine can reach it unless another address, e.g. 0.0.0.0,
+        /// is given
+        #[arg(long, default_value = "127.0.0.1")]
+        host: std::net::IpAddr,
+    },
+    
     /// Warm cache with vocabulary items
     WarmCache {
         /// Input CSV file path
@@ -131,13 +698,234 @@ pub enum Commands {
         /// Only warm stage 1 cache
         #[arg(long)]
         stage1_only: bool,
+        
+        /// Print the cache coverage report and exit without warming
+        #[arg(long)]
+        report_only: bool,
+        
+        /// Continue warming where an interrupted run over the same input
+        /// stopped, rather than looking every item up again
+        #[arg(long)]
+        resume_warm: bool,
+        
+        /// Key cached results by the model that produced them, as
+        /// `process --pin-model-in-key` looks them up
+        #[arg(long)]
+        pin_model_in_key: bool,
+        
+        /// Skip input lines starting with this character
+        #[arg(long, default_value = "#", value_parser = parse_comment_char)]
+        comment_char: u8,
+    },
+    
+    /// Check an input CSV for malformed rows, invalid characters and
+    /// duplicate terms, without processing it or opening the database
+    Validate {
+        /// Input CSV file path
+        input: PathBuf,
+        
+        /// Report invalid rows without failing
+        #[arg(long)]
+        warn_only: bool,
+        
+        /// Count terms as duplicates only when byte-for-byte equal, as
+        /// `process --exact-cache-keys` keys them
+        #[arg(long)]
+        exact_cache_keys: bool,
+        
+        /// Skip input lines starting with this character
+        #[arg(long, default_value = "#", value_parser = parse_comment_char)]
+        comment_char: u8,
     },
+    
+    /// Export all cache entries to a JSON lines backup
+    CacheExport {
+        /// Output JSONL file path
+        output: PathBuf,
+    },
+    
+    /// Export a finished batch again from the cache, e.g. in another format,
+    /// without reprocessing it
+    Reexport {
+        /// Batch to export
+        batch_id: BatchId,
+        
+        /// Output file path; `-` writes to stdout and, with the `s3`
+        /// feature, `s3://bucket/key` uploads to S3
+        #[arg(short, long)]
+        output: PathBuf,
+        
+        /// Layout to write the cards in
+        #[arg(long, value_enum, default_value_t = ExportFormat::Tsv)]
+        format: ExportFormat,
+        
+        /// Order of the exported cards: by input position (the default), or
+        /// by learning order
+        #[arg(long, value_enum, value_name = "ORDER")]
+        sort: Option<SortOrder>,
+        
+        /// End exported lines with CRLF, as Windows tools expect
+        #[arg(long)]
+        crlf: bool,
+        
+        /// Start the export with a UTF-8 byte order mark, so Excel shows
+        /// Korean text correctly
+        #[arg(long)]
+        bom: bool,
+    },
+    
+    /// Compare the cards of two batches term by term, e.g. before and after
+    /// a prompt change
+    DiffBatches {
+        /// Batch to compare against
+        old: BatchId,
+        
+        /// Batch to compare
+        new: BatchId,
+        
+        /// Output in JSON format
+        #[arg(long)]
+        json: bool,
+    },
+    
+    /// Import cache entries from a JSON lines backup
+    CacheImport {
+        /// Input JSONL file path
+        input: PathBuf,
+        
+        /// Replace entries whose cache key already exists
+        #[arg(long)]
+        overwrite: bool,
+    },
+    
+    /// Show the cached card for a single term, without calling the API
+    Inspect {
+        /// Korean term to look up
+        term: String,
+        
+        /// Regenerate Stage 2 and show which fields differ from the cached card
+        #[arg(long)]
+        diff: bool,
+        
+        /// With --diff, replace the cached card with the regenerated one
+        #[arg(long, requires = "diff")]
+        save: bool,
+    },
+    
+    /// Print the cache keys a term maps to and what is stored under them
+    CacheKey {
+        /// Korean term to look up
+        term: String,
+        
+        /// Word type the term was listed with, e.g. noun, which is part of
+        /// the key
+        #[arg(long)]
+        word_type: Option<String>,
+    },
+    
+    /// Rewrite cache keys under the current key scheme
+    CacheMigrate {
+        /// Migrate to byte-exact keys instead of NFC-normalized and trimmed
+        #[arg(long)]
+        exact_cache_keys: bool,
+        
+        /// Report what would change without writing it
+        #[arg(long)]
+        dry_run: bool,
+    },
+    
+    /// Measure throughput against a simulated API, without calling the real one
+    #[cfg(feature = "mock")]
+    Bench {
+        /// Synthetic vocabulary items per run
+        #[arg(long, default_value = "200")]
+        count: usize,
+        
+        /// Concurrency levels to sweep, e.g. 1,4,8,16
+        #[arg(long, value_delimiter = ',', default_value = "5")]
+        concurrency: Vec<usize>,
+        
+        /// Simulated duration of each API call
+        #[arg(long, value_parser = humantime::parse_duration, default_value = "200ms")]
+        latency: std::time::Duration,
+        
+        /// Random extra duration of up to this much per call
+        #[arg(long, value_parser = humantime::parse_duration, default_value = "50ms")]
+        jitter: std::time::Duration,
+        
+        /// Run each level again once its cache is warm
+        #[arg(long)]
+        warm_pass: bool,
+        
+        /// Output in JSON format
+        #[arg(long)]
+        json: bool,
+    },
+}
+
+/// A single ASCII character, as the CSV reader compares bytes
+fn parse_comment_char(value: &str) -> Result<u8, String> {
+    let mut chars = value.chars();
+    match (chars.next(), chars.next()) {
+        (Some(c), None) if c.is_ascii() => Ok(c as u8),
+        _ => Err(format!("expected a single ASCII character, got {:?}", value)),
+    }
+}
+
+/// `FIELD=CHARS`, e.g. `cultural_notes=200`
+fn parse_field_limit(value: &str) -> Result<(String, usize), String> {
+    let (field, chars) = value.split_once('=')
+        .ok_or_else(|| format!("expected FIELD=CHARS, got {:?}", value))?;
+    let chars = chars.trim().parse()
+        .map_err(|e| format!("invalid character count {:?}: {}", chars, e))?;
+    Ok((field.trim().to_string(), chars))
 }
 
 impl Cli {
-    pub fn init_logging(&self) {
+    /// Fill in global settings from `file` wherever the matching flag was
+    /// left at its default. `matches` are the top-level matches `self` was
+    /// parsed from.
+    pub fn apply_config(&mut self, file: &ConfigFile, matches: &ArgMatches) {
+        let args = ExplicitArgs::new(Some(matches));
+        let pipeline = &file.pipeline;
+        let logging = &file.logging;
+        
+        self.database_url = args.pick("database_url", self.database_url.clone(), pipeline.database_url.clone());
+        self.cache_dir = args.pick("cache_dir", self.cache_dir.clone(), pipeline.cache_dir.clone());
+        self.allow_migration_drift |= pipeline.allow_migration_drift;
+        self.debug |= logging.debug;
+        self.no_color |= logging.no_color;
+        if self.log_file.is_none() {
+            self.log_file = logging.log_file.clone();
+        }
+        self.log_rotation = args.pick("log_rotation", self.log_rotation, logging.log_rotation);
+    }
+    
+    /// `base` with whichever `--sqlite-*` flags were given applied over it
+    pub fn sqlite_pragmas(&self, base: SqlitePragmas) -> SqlitePragmas {
+        SqlitePragmas {
+            mmap_size: self.sqlite_mmap_size.unwrap_or(base.mmap_size),
+            cache_size: self.sqlite_cache_size.unwrap_or(base.cache_size),
+            synchronous: self.sqlite_synchronous.map_or(base.synchronous, SynchronousArg::synchronous),
+            temp_store: self.sqlite_temp_store.map_or(base.temp_store, TempStoreArg::temp_store),
+        }
+    }
+    
+    /// Install the global subscriber. Console logs go to stderr, leaving
+    /// stdout to output such as cards exported with `--output -`. With
+    /// `--log-file`, logs go to both the console and the file, and the
+    /// returned guard must be held until exit so buffered lines are flushed.
+    pub fn init_logging(&self) -> Option<WorkerGuard> {
         use tracing_subscriber::{fmt, EnvFilter};
         
+        if let Some(dir) = &self.log_file {
+            let level = if self.debug { "debug" } else { "info" };
+            match flashcard_core::logging::init_file_logging(dir, Some(level), self.log_rotation.rotation()) {
+                Ok(guard) => return Some(guard),
+                Err(e) => eprintln!("Could not log to {}: {}; logging to the console only", dir.display(), e),
+            }
+        }
+        
         let filter = if self.debug {
             EnvFilter::new("debug")
         } else {
@@ -147,6 +935,7 @@ impl Cli {
         
         let subscriber = fmt()
             .with_env_filter(filter)
+            .with_writer(std::io::stderr)
             .with_ansi(!self.no_color);
             
         if self.debug {
@@ -154,5 +943,7 @@ impl Cli {
         } else {
             subscriber.init();
         }
+        
+        None
     }
 }
\ No newline at end of file
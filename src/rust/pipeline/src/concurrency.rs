@@ -0,0 +1,331 @@
+use crate::monitoring::{MetricsCollector, PipelineMetrics};
+use parking_lot::Mutex;
+use std::future::Future;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Semaphore;
+use tokio::task::{AbortHandle, JoinHandle};
+use tracing::{info, debug};
+
+#[derive(Debug, Clone)]
+pub struct AdaptiveConcurrencyConfig {
+    /// Permits the controller starts with and never drops below
+    pub min_concurrency: usize,
+    /// Ceiling the controller never exceeds
+    pub max_concurrency: usize,
+    /// API error rate above which concurrency is reduced
+    pub target_error_rate: f64,
+    /// Cache-hit rate above which concurrency is raised
+    pub high_hit_rate: f64,
+    /// How often metrics are sampled
+    pub interval: Duration,
+}
+
+impl Default for AdaptiveConcurrencyConfig {
+    fn default() -> Self {
+        Self {
+            min_concurrency: 2,
+            max_concurrency: 20,
+            target_error_rate: 0.05,
+            high_hit_rate: 0.5,
+            interval: Duration::from_secs(2),
+        }
+    }
+}
+
+/// Counter deltas observed between two controller ticks.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MetricsWindow {
+    pub cache_hits: usize,
+    pub cache_misses: usize,
+    pub api_errors: usize,
+    pub rate_limit_hits: usize,
+}
+
+impl MetricsWindow {
+    fn between(previous: &PipelineMetrics, current: &PipelineMetrics) -> Self {
+        Self {
+            cache_hits: current.cache_hits.saturating_sub(previous.cache_hits),
+            cache_misses: current.cache_misses.saturating_sub(previous.cache_misses),
+            api_errors: current.api_errors.saturating_sub(previous.api_errors),
+            rate_limit_hits: current.rate_limit_hits.saturating_sub(previous.rate_limit_hits),
+        }
+    }
+    
+    fn total(&self) -> usize {
+        self.cache_hits + self.cache_misses + self.api_errors
+    }
+    
+    fn hit_rate(&self) -> f64 {
+        let lookups = self.cache_hits + self.cache_misses;
+        if lookups == 0 {
+            0.0
+        } else {
+            self.cache_hits as f64 / lookups as f64
+        }
+    }
+    
+    fn error_rate(&self) -> f64 {
+        if self.total() == 0 {
+            0.0
+        } else {
+            self.api_errors as f64 / self.total() as f64
+        }
+    }
+}
+
+/// Caps live API calls separately from the number of items in flight, so
+/// items answered from the cache never wait behind API calls.
+#[derive(Clone)]
+pub struct ApiLimiter {
+    semaphore: Arc<Semaphore>,
+    in_use: Arc<AtomicUsize>,
+    peak: Arc<AtomicUsize>,
+}
+
+impl ApiLimiter {
+    pub fn new(max_calls: usize) -> Self {
+        Self {
+            semaphore: Arc::new(Semaphore::new(max_calls.clamp(1, Semaphore::MAX_PERMITS))),
+            in_use: Arc::new(AtomicUsize::new(0)),
+            peak: Arc::new(AtomicUsize::new(0)),
+        }
+    }
+    
+    /// No cap beyond the item concurrency itself.
+    pub fn unlimited() -> Self {
+        Self::new(Semaphore::MAX_PERMITS)
+    }
+    
+    /// Run `call` once an API permit is free, holding it until `call` ends.
+    pub async fn call<F: Future>(&self, call: F) -> F::Output {
+        let _permit = self.semaphore.acquire().await.expect("API semaphore closed");
+        let in_use = self.in_use.fetch_add(1, Ordering::SeqCst) + 1;
+        self.peak.fetch_max(in_use, Ordering::SeqCst);
+        
+        let output = call.await;
+        self.in_use.fetch_sub(1, Ordering::SeqCst);
+        output
+    }
+    
+    /// Most API calls that have run at the same time.
+    pub fn peak(&self) -> usize {
+        self.peak.load(Ordering::SeqCst)
+    }
+}
+
+/// Resizes the `BatchProcessor` semaphore from recent `MetricsCollector` readings.
+///
+/// Additive increase while the window is mostly cache hits with few errors,
+/// multiplicative decrease as soon as a rate limit is seen.
+pub struct AdaptiveConcurrencyController {
+    semaphore: Arc<Semaphore>,
+    metrics: Arc<MetricsCollector>,
+    config: AdaptiveConcurrencyConfig,
+    limit: Mutex<usize>,
+    last_sample: Mutex<PipelineMetrics>,
+}
+
+impl AdaptiveConcurrencyController {
+    /// `semaphore` must currently hold `config.min_concurrency` permits.
+    pub fn new(
+        semaphore: Arc<Semaphore>,
+        metrics: Arc<MetricsCollector>,
+        config: AdaptiveConcurrencyConfig,
+    ) -> Self {
+        let last_sample = metrics.get_metrics();
+        Self {
+            semaphore,
+            metrics,
+            limit: Mutex::new(config.min_concurrency),
+            config,
+            last_sample: Mutex::new(last_sample),
+        }
+    }
+    
+    pub fn current_limit(&self) -> usize {
+        *self.limit.lock()
+    }
+    
+    pub fn spawn(self: Arc<Self>) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            loop {
+                tokio::time::sleep(self.config.interval).await;
+                self.tick();
+            }
+        })
+    }
+    
+    /// Sample metrics and resize the semaphore, returning the new limit.
+    pub fn tick(&self) -> usize {
+        let current = self.metrics.get_metrics();
+        let window = {
+            let mut last = self.last_sample.lock();
+            let window = MetricsWindow::between(&last, &current);
+            *last = current;
+            window
+        };
+        
+        let mut limit = self.limit.lock();
+        let next = self.next_limit(*limit, &window);
+        if next != *limit {
+            debug!("Adaptive concurrency window: {:?}", window);
+            info!("Adjusting concurrency {} -> {}", *limit, next);
+            self.resize(*limit, next);
+            *limit = next;
+        }
+        next
+    }
+    
+    pub fn next_limit(&self, current: usize, window: &MetricsWindow) -> usize {
+        let min = self.config.min_concurrency;
+        let max = self.config.max_concurrency.max(min);
+        
+        if window.rate_limit_hits > 0 {
+            return (current / 2).max(min);
+        }
+        
+        if window.total() == 0 {
+            return current;
+        }
+        
+        if window.error_rate() > self.config.target_error_rate {
+            current.saturating_sub(1).max(min)
+        } else if window.hit_rate() >= self.config.high_hit_rate {
+            (current + 1).min(max)
+        } else {
+            current
+        }
+    }
+    
+    fn resize(&self, from: usize, to: usize) {
+        if to > from {
+            self.semaphore.add_permits(to - from);
+            return;
+        }
+        
+        // Take idle permits away now; the rest are retired as in-flight
+        // items release them
+        let wanted = from - to;
+        let forgotten = self.semaphore.forget_permits(wanted);
+        let remaining = wanted - forgotten;
+        if remaining > 0 {
+            let semaphore = Arc::clone(&self.semaphore);
+            tokio::spawn(async move {
+                if let Ok(permits) = semaphore.acquire_many_owned(remaining as u32).await {
+                    permits.forget();
+                }
+            });
+        }
+    }
+}
+
+/// Aborts the tasks it holds when dropped, so a `?` or early return can't
+/// leave them running. Aborting a task that already finished does nothing.
+#[derive(Default)]
+pub struct AbortOnDrop(Vec<AbortHandle>);
+
+impl AbortOnDrop {
+    pub fn push<T>(&mut self, handle: &JoinHandle<T>) {
+        self.0.push(handle.abort_handle());
+    }
+}
+
+impl Drop for AbortOnDrop {
+    fn drop(&mut self) {
+        for handle in &self.0 {
+            handle.abort();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn controller() -> AdaptiveConcurrencyController {
+        let config = AdaptiveConcurrencyConfig {
+            min_concurrency: 2,
+            max_concurrency: 16,
+            ..Default::default()
+        };
+        AdaptiveConcurrencyController::new(
+            Arc::new(Semaphore::new(config.min_concurrency)),
+            Arc::new(MetricsCollector::new()),
+            config,
+        )
+    }
+    
+    #[test]
+    fn test_raises_on_high_hit_rate() {
+        let controller = controller();
+        let window = MetricsWindow { cache_hits: 9, cache_misses: 1, ..Default::default() };
+        assert_eq!(controller.next_limit(4, &window), 5);
+        assert_eq!(controller.next_limit(16, &window), 16);
+    }
+    
+    #[test]
+    fn test_halves_on_rate_limit() {
+        let controller = controller();
+        let window = MetricsWindow { cache_hits: 9, rate_limit_hits: 1, ..Default::default() };
+        assert_eq!(controller.next_limit(16, &window), 8);
+        assert_eq!(controller.next_limit(3, &window), 2);
+    }
+    
+    #[test]
+    fn test_lowers_on_error_rate() {
+        let controller = controller();
+        let window = MetricsWindow { cache_hits: 5, cache_misses: 3, api_errors: 2, ..Default::default() };
+        assert_eq!(controller.next_limit(6, &window), 5);
+    }
+    
+    #[tokio::test]
+    async fn test_api_limiter_holds_calls_to_its_limit() {
+        let api = ApiLimiter::new(5);
+        let calls: Vec<_> = (0..20)
+            .map(|_| {
+                let api = api.clone();
+                tokio::spawn(async move {
+                    api.call(tokio::time::sleep(Duration::from_millis(10))).await
+                })
+            })
+            .collect();
+        for call in calls {
+            call.await.unwrap();
+        }
+        assert_eq!(api.peak(), 5);
+    }
+    
+    #[tokio::test]
+    async fn test_tick_resizes_semaphore() {
+        let controller = controller();
+        for _ in 0..10 {
+            controller.metrics.record_cache_hit();
+        }
+        
+        assert_eq!(controller.tick(), 3);
+        assert_eq!(controller.semaphore.available_permits(), 3);
+        
+        controller.metrics.record_rate_limit();
+        assert_eq!(controller.tick(), 2);
+        assert_eq!(controller.semaphore.available_permits(), 2);
+    }
+    
+    #[tokio::test]
+    async fn test_abort_on_drop_stops_pending_tasks() {
+        let pending = tokio::spawn(std::future::pending::<()>());
+        let done = tokio::spawn(async {});
+        while !done.is_finished() {
+            tokio::task::yield_now().await;
+        }
+        {
+            let mut tasks = AbortOnDrop::default();
+            tasks.push(&pending);
+            tasks.push(&done);
+        }
+        
+        assert!(pending.await.unwrap_err().is_cancelled());
+        assert!(done.await.is_ok());
+    }
+}
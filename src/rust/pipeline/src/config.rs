@@ -0,0 +1,285 @@
+//! Loading settings from a `flashcard.toml` file.
+//!
+//! The file has a `[pipeline]` table holding [`PipelineConfig`] fields and a
+//! `[logging]` table for the console and file log settings. Keys left out
+//! keep their defaults, and unknown keys are rejected so a typo doesn't
+//! silently fall back to a default:
+//!
+//! ```toml
+//! [pipeline]
+//! database_url = "sqlite:cards.db"
+//! max_concurrent = 8
+//! max_retries = 5
+//! health_check_timeout = "1m"
+//! anki_preset = { note_type = "korean", deck = "Korean::Vocabulary" }
+//!
+//! [logging]
+//! log_file = "logs"
+//! log_rotation = "hourly"
+//! ```
+//!
+//! Flags given on the command line (or through their environment variables)
+//! override the file; see [`ExplicitArgs`].
+
+use crate::errors::{PipelineError, Result};
+use crate::pipeline::PipelineConfig;
+use crate::cli::LogRotation;
+use clap::ArgMatches;
+use clap::parser::ValueSource;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// The contents of a config file
+#[derive(Clone, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ConfigFile {
+    pub pipeline: PipelineConfig,
+    pub logging: LoggingConfig,
+}
+
+/// The `[logging]` table, mirroring the global logging flags
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct LoggingConfig {
+    pub debug: bool,
+    pub no_color: bool,
+    /// Also write logs to rotating files in this directory
+    pub log_file: Option<PathBuf>,
+    pub log_rotation: LogRotation,
+}
+
+impl ConfigFile {
+    /// Read and validate the file at `path`.
+    pub fn load(path: &Path) -> Result<Self> {
+        let text = std::fs::read_to_string(path).map_err(|e| PipelineError::ConfigError(
+            format!("Could not read {}: {}", path.display(), e)
+        ))?;
+        
+        Self::parse(&text).map_err(|e| match e {
+            PipelineError::ConfigError(message) => {
+                PipelineError::ConfigError(format!("{}: {}", path.display(), message))
+            }
+            other => other,
+        })
+    }
+    
+    /// Parse and validate config file contents.
+    pub fn parse(text: &str) -> Result<Self> {
+        // toml's message names the offending key and its line, including
+        // unknown keys with the list of accepted ones
+        let file: Self = toml::from_str(text)
+            .map_err(|e| PipelineError::ConfigError(e.to_string()))?;
+        file.pipeline.validate()?;
+        Ok(file)
+    }
+    
+    /// Render as TOML, e.g. to write out the settings a run used.
+    pub fn to_toml(&self) -> Result<String> {
+        toml::to_string_pretty(self).map_err(|e| PipelineError::ConfigError(e.to_string()))
+    }
+}
+
+/// Tells which flags were given explicitly, so they take precedence over a
+/// config file while flags left at their defaults give way to it.
+///
+/// Without a config file every flag wins, defaults included, so runs
+/// without `--config` behave exactly as before.
+pub struct ExplicitArgs<'a> {
+    matches: Option<&'a ArgMatches>,
+}
+
+impl<'a> ExplicitArgs<'a> {
+    /// `matches` are those of the command whose flags are being layered, or
+    /// `None` when no config file was loaded.
+    pub fn new(matches: Option<&'a ArgMatches>) -> Self {
+        Self { matches }
+    }
+    
+    /// Whether the flag `id` was set on the command line or from its
+    /// environment variable rather than its default.
+    pub fn is_explicit(&self, id: &str) -> bool {
+        match self.matches {
+            None => true,
+            Some(matches) => matches!(
+                matches.value_source(id),
+                Some(ValueSource::CommandLine | ValueSource::EnvVariable)
+            ),
+        }
+    }
+    
+    /// `flag` if it was given explicitly, otherwise the config file's value.
+    pub fn pick<T>(&self, id: &str, flag: T, file: T) -> T {
+        if self.is_explicit(id) {
+            flag
+        } else {
+            file
+        }
+    }
+}
+
+/// `Duration`s as humantime strings such as `"30s"` or `"2m"`
+pub(crate) mod humantime_duration {
+    use serde::{Deserialize, Deserializer, Serializer};
+    use std::time::Duration;
+    
+    pub fn serialize<S: Serializer>(duration: &Duration, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(&humantime::format_duration(*duration))
+    }
+    
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Duration, D::Error> {
+        let text = String::deserialize(deserializer)?;
+        humantime::parse_duration(&text).map_err(serde::de::Error::custom)
+    }
+}
+
+/// Like [`humantime_duration`], for settings that can be left unset
+pub(crate) mod optional_humantime_duration {
+    use serde::{Deserialize, Deserializer, Serializer};
+    use std::time::Duration;
+    
+    pub fn serialize<S: Serializer>(duration: &Option<Duration>, serializer: S) -> Result<S::Ok, S::Error> {
+        match duration {
+            Some(duration) => super::humantime_duration::serialize(duration, serializer),
+            None => serializer.serialize_none(),
+        }
+    }
+    
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<Duration>, D::Error> {
+        Option::<String>::deserialize(deserializer)?
+            .map(|text| humantime::parse_duration(&text).map_err(serde::de::Error::custom))
+            .transpose()
+    }
+}
+
+/// The CSV comment byte as a one-character string; `""` disables comments
+pub(crate) mod comment_char {
+    use serde::{Deserialize, Deserializer, Serializer};
+    
+    pub fn serialize<S: Serializer>(comment: &Option<u8>, serializer: S) -> Result<S::Ok, S::Error> {
+        match comment {
+            Some(byte) => serializer.serialize_str(&char::from(*byte).to_string()),
+            None => serializer.serialize_str(""),
+        }
+    }
+    
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<u8>, D::Error> {
+        let text = String::deserialize(deserializer)?;
+        let mut chars = text.chars();
+        match (chars.next(), chars.next()) {
+            (None, _) => Ok(None),
+            (Some(c), None) if c.is_ascii() => Ok(Some(c as u8)),
+            _ => Err(serde::de::Error::custom(format!(
+                "expected a single ASCII character or \"\", got {:?}", text
+            ))),
+        }
+    }
+}
+
+/// The particle stripper as a plain on/off switch
+pub(crate) mod strip_particles {
+    use flashcard_core::term_normalizer::TermNormalizer;
+    use serde::{Deserialize, Deserializer, Serializer};
+    
+    pub fn serialize<S: Serializer>(normalizer: &Option<TermNormalizer>, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_bool(normalizer.is_some())
+    }
+    
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<TermNormalizer>, D::Error> {
+        Ok(bool::deserialize(deserializer)?.then(TermNormalizer::default))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::anki::{AnkiNoteType, AnkiPreset};
+    use crate::cli::Cli;
+    use clap::{CommandFactory, FromArgMatches};
+    use flashcard_core::models::{ForceRefresh, KeyNormalization, Stage2Mode};
+    use std::time::Duration;
+    
+    #[test]
+    fn test_config_file_round_trips() {
+        let mut file = ConfigFile::default();
+        file.pipeline.database_url = "sqlite:cards.db".to_string();
+        file.pipeline.max_concurrent = 12;
+        file.pipeline.api_concurrency = Some(4);
+        file.pipeline.max_retries = 7;
+        file.pipeline.target_error_rate = 0.1;
+        file.pipeline.key_normalization = KeyNormalization::None;
+        file.pipeline.anki_preset = Some(AnkiPreset::new(AnkiNoteType::BasicReversed).with_deck("Korean"));
+        file.pipeline.health_check_timeout = Duration::from_secs(90);
+        file.pipeline.term_normalizer = Some(Default::default());
+        file.pipeline.csv_comment = None;
+        file.pipeline.force_refresh = ForceRefresh { stage1: false, stage2: true };
+        file.pipeline.stage2_mode = Stage2Mode::Minimal;
+        file.logging.log_file = Some(PathBuf::from("logs"));
+        file.logging.log_rotation = LogRotation::Hourly;
+        
+        let text = file.to_toml().unwrap();
+        let parsed = ConfigFile::parse(&text).unwrap();
+        
+        // Compare through the serialized form, since PipelineConfig holds
+        // transforms that can't be compared directly
+        assert_eq!(parsed.to_toml().unwrap(), text);
+        assert_eq!(parsed.pipeline.max_concurrent, 12);
+        assert_eq!(parsed.pipeline.csv_comment, None);
+        assert!(parsed.pipeline.term_normalizer.is_some());
+        assert_eq!(parsed.pipeline.health_check_timeout, Duration::from_secs(90));
+        assert_eq!(parsed.logging, file.logging);
+    }
+    
+    #[test]
+    fn test_unknown_keys_are_reported() {
+        let err = ConfigFile::parse("[pipeline]\nmax_concurent = 3\n").err().unwrap();
+        assert!(err.to_string().contains("max_concurent"), "{}", err);
+        
+        let err = ConfigFile::parse("[pipline]\n").err().unwrap();
+        assert!(err.to_string().contains("pipline"), "{}", err);
+    }
+    
+    #[test]
+    fn test_invalid_values_are_rejected() {
+        let err = ConfigFile::parse("[pipeline]\nmin_concurrency = 8\nmax_concurrency = 4\n").err().unwrap();
+        assert!(err.to_string().contains("min_concurrency"), "{}", err);
+        
+        let err = ConfigFile::parse("[pipeline]\nmax_retries = -1\n").err().unwrap();
+        assert!(err.to_string().contains("max_retries"), "{}", err);
+    }
+    
+    #[test]
+    fn test_flags_override_file_and_file_overrides_defaults() {
+        let file = ConfigFile::parse(
+            "[pipeline]\nmax_concurrent = 3\nbatch_size = 25\ndatabase_url = \"sqlite:file.db\"\n\n[logging]\nlog_rotation = \"hourly\"\n"
+        ).unwrap();
+        
+        let matches = Cli::command().get_matches_from([
+            "flashcard-pipeline",
+            "--database-url", "sqlite:flag.db",
+            "process", "words.csv",
+            "--max-concurrent", "9",
+        ]);
+        let mut cli = Cli::from_arg_matches(&matches).unwrap();
+        cli.apply_config(&file, &matches);
+        
+        assert_eq!(cli.database_url, "sqlite:flag.db");
+        assert_eq!(cli.log_rotation, LogRotation::Hourly);
+        
+        let (_, process) = matches.subcommand().unwrap();
+        let args = ExplicitArgs::new(Some(process));
+        let defaults = PipelineConfig::default();
+        
+        // Given on the command line
+        assert_eq!(args.pick("max_concurrent", 9, file.pipeline.max_concurrent), 9);
+        // Only in the file
+        assert_eq!(args.pick("batch_size", 10, file.pipeline.batch_size), 25);
+        // In neither
+        assert_eq!(
+            args.pick("min_concurrency", 2, file.pipeline.min_concurrency),
+            defaults.min_concurrency
+        );
+        
+        // Without a config file every flag wins
+        assert_eq!(ExplicitArgs::new(None).pick("batch_size", 10, 25), 10);
+    }
+}
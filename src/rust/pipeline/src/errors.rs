@@ -1,5 +1,6 @@
 use thiserror::Error;
 use std::path::PathBuf;
+use std::time::Duration;
 
 pub type Result<T> = std::result::Result<T, PipelineError>;
 
@@ -49,18 +50,90 @@ pub enum PipelineError {
     
     #[error("Cache error: {0}")]
     CacheError(String),
+    
+    #[error("Not cached (offline mode): {0}")]
+    NotCached(String),
+    
+    #[error("Gave up after spending the {budget:?} per-item time budget")]
+    Timeout { budget: Duration },
+    
+    #[error("Stage 1 result failed the quality gate: {0}")]
+    LowQuality(String),
+    
+    #[error("Processing panicked: {0}")]
+    Panicked(String),
+    
+    #[error("Card transform '{transform}' failed on '{term}': {message}")]
+    TransformFailed { transform: String, term: String, message: String },
 }
 
 impl PipelineError {
     pub fn is_retryable(&self) -> bool {
+        match self {
+            PipelineError::Core(e) => e.is_retryable(),
+            _ => matches!(
+                self,
+                PipelineError::RateLimitExceeded(_) 
+                | PipelineError::ApiError(_)
+                | PipelineError::IoError(_)
+            ),
+        }
+    }
+    
+    /// The API call itself failed or timed out, as opposed to the item
+    /// failing on our side; rate limits are told apart by
+    /// [`is_rate_limit`](Self::is_rate_limit)
+    pub fn is_api_failure(&self) -> bool {
+        matches!(self.category(), "api" | "timeout" | "python")
+    }
+    
+    pub fn is_rate_limit(&self) -> bool {
         matches!(
             self,
-            PipelineError::RateLimitExceeded(_) 
-            | PipelineError::ApiError(_)
-            | PipelineError::IoError(_)
+            PipelineError::RateLimitExceeded(_)
+            | PipelineError::Core(flashcard_core::errors::PipelineError::RateLimit { .. })
         )
     }
     
+    /// How long the server asked us to wait, for rate limits that say
+    pub fn retry_after(&self) -> Option<Duration> {
+        match self {
+            PipelineError::RateLimitExceeded(seconds)
+            | PipelineError::Core(flashcard_core::errors::PipelineError::RateLimit { retry_after: seconds }) => {
+                Some(Duration::from_secs(*seconds))
+            }
+            _ => None,
+        }
+    }
+    
+    /// Coarse grouping used when summarising failures
+    pub fn category(&self) -> &'static str {
+        use flashcard_core::errors::PipelineError as CoreError;
+        
+        match self {
+            PipelineError::RateLimitExceeded(_)
+            | PipelineError::Core(CoreError::RateLimit { .. }) => "rate_limit",
+            PipelineError::ApiError(_)
+            | PipelineError::Core(CoreError::Api { .. }) => "api",
+            PipelineError::Timeout { .. }
+            | PipelineError::Core(CoreError::Timeout { .. }) => "timeout",
+            PipelineError::LowQuality(_) => "quality",
+            PipelineError::Panicked(_) => "panic",
+            PipelineError::Core(CoreError::Validation(_))
+            | PipelineError::InvalidFormat(_) => "validation",
+            PipelineError::PythonError(_)
+            | PipelineError::Core(CoreError::PythonInterop(_)) => "python",
+            PipelineError::CacheError(_)
+            | PipelineError::Core(CoreError::Cache(_)) => "cache",
+            PipelineError::Core(CoreError::Database(_)) => "database",
+            PipelineError::SerializationError(_)
+            | PipelineError::Core(CoreError::Serialization(_)) => "serialization",
+            PipelineError::IoError(_)
+            | PipelineError::Core(CoreError::Io(_)) => "io",
+            _ => "other",
+        }
+    }
+    
     pub fn exit_code(&self) -> i32 {
         match self {
             PipelineError::Interrupted => 130, // Standard SIGINT exit code
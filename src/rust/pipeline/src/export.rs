@@ -1,14 +1,529 @@
 use crate::errors::{PipelineError, Result};
-use flashcard_core::models::{VocabularyItem, Stage2Result, FlashcardContent};
-use std::path::Path;
-use std::fs::File;
-use std::io::{Write, BufWriter};
-use tracing::{info, debug, instrument};
-use csv::Writer;
+use crate::batch_processor::FailureRecord;
+use crate::anki::AnkiPreset;
+use std::borrow::Cow;
+use std::collections::{BTreeMap, HashMap};
+use flashcard_core::models::{
+    VocabularyItem, Stage1Result, Stage2Result, Stage2Mode, FlashcardContent, BatchId, CardState, KeyNormalization,
+};
+use crate::sink::{self, OutputSink};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tracing::{info, debug, warn, instrument};
+use csv::{QuoteStyle, Terminator, Writer, WriterBuilder};
+use serde::{Deserialize, Serialize};
+use rayon::prelude::*;
+use unicode_segmentation::UnicodeSegmentation;
+
+/// Cards formatted per parallel round before their rows are written, which
+/// bounds how many formatted records are held at once.
+const EXPORT_CHUNK_SIZE: usize = 1024;
+
+/// Tag of cards suspended in the pipeline's card state. Anki can't suspend
+/// cards on import, but `tag:suspended` finds the ones to suspend.
+pub const SUSPENDED_TAG: &str = "suspended";
+
+/// Tag of cards marked as leeches, the same one Anki's leech detection adds
+pub const LEECH_TAG: &str = "leech";
+
+const HEADERS: &[&str] = &[
+    "Position",
+    "Term",
+    "IPA",
+    "Part of Speech",
+    "Front Primary",
+    "Front Secondary",
+    "Front Example",
+    "Front Example Translation",
+    "Back Primary",
+    "Back Secondary",
+    "Back Example",
+    "Back Example Translation",
+    "Mnemonic",
+    "Difficulty",
+    "Frequency",
+    "Tags",
+    "Notes",
+    "Related",
+];
+
+/// Replaces [`HEADERS`] for cards generated in [`Stage2Mode::Minimal`],
+/// which leaves the other columns empty
+const MINIMAL_HEADERS: &[&str] = &[
+    "Position",
+    "Term",
+    "Pronunciation",
+    "Front",
+    "Back",
+    "Tags",
+];
+
+/// Columns written by [`MnemonicExporter`]
+const MNEMONIC_HEADERS: &[&str] = &[
+    "Term",
+    "Meaning",
+    "Mnemonic",
+    "Metaphor",
+    "Metaphor Noun",
+    "Metaphor Action",
+    "Tags",
+];
+
+/// Appended to [`HEADERS`] by [`TsvExporter::with_comparison_columns`]
+const COMPARISON_HEADERS: &[&str] = &[
+    "Homonyms",
+    "Similar To",
+    "Different From",
+    "Confused With",
+];
+
+/// Appended by [`TsvExporter::with_memory_palace`] with
+/// [`MemoryPalaceColumns::Separate`]
+const MEMORY_PALACE_HEADERS: &[&str] = &[
+    "Location",
+    "Anchor Object",
+    "Anchor Sensory",
+    "Metaphor",
+    "Metaphor Noun",
+    "Metaphor Action",
+];
+
+/// Written first by [`TsvExporter::with_bom`], so Excel reads the file as
+/// UTF-8 instead of the system code page
+pub const UTF8_BOM: &[u8] = b"\xEF\xBB\xBF";
+
+/// How exported rows end
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum LineEnding {
+    #[default]
+    Lf,
+    /// `\r\n`, as Windows tools expect
+    CrLf,
+}
+
+impl LineEnding {
+    fn as_bytes(self) -> &'static [u8] {
+        match self {
+            LineEnding::Lf => b"\n",
+            LineEnding::CrLf => b"\r\n",
+        }
+    }
+    
+    fn terminator(self) -> Terminator {
+        match self {
+            LineEnding::Lf => Terminator::Any(b'\n'),
+            LineEnding::CrLf => Terminator::CRLF,
+        }
+    }
+}
+
+/// How Stage 1's memory-palace fields are exported
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum MemoryPalaceColumns {
+    /// One "Memory Palace" column reading as a sentence; see
+    /// [`memory_palace_sentence`]
+    Combined,
+    /// One column per field, in [`MEMORY_PALACE_HEADERS`] order
+    Separate,
+}
+
+impl MemoryPalaceColumns {
+    fn headers(self) -> &'static [&'static str] {
+        match self {
+            MemoryPalaceColumns::Combined => &["Memory Palace"],
+            MemoryPalaceColumns::Separate => MEMORY_PALACE_HEADERS,
+        }
+    }
+    
+    fn record(self, stage1: &Stage1Result) -> Vec<String> {
+        match self {
+            MemoryPalaceColumns::Combined => vec![memory_palace_sentence(stage1)],
+            MemoryPalaceColumns::Separate => vec![
+                stage1.suggested_location.clone(),
+                stage1.anchor_object.clone(),
+                stage1.anchor_sensory.clone(),
+                stage1.metaphor.clone(),
+                stage1.metaphor_noun.clone(),
+                stage1.metaphor_action.clone(),
+            ],
+        }
+    }
+}
+
+/// The order exported rows are written in
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum SortOrder {
+    /// By the term's position in the input
+    #[default]
+    Position,
+    /// By the card's learning order, for a structured curriculum. Cards
+    /// without one follow, by position.
+    LearningOrder,
+}
+
+impl SortOrder {
+    /// `results` in this order, borrowed if they already are. The sort is
+    /// stable, so ties keep their input order.
+    fn apply(self, results: &[(VocabularyItem, Stage1Result, Stage2Result)]) -> Cow<'_, [(VocabularyItem, Stage1Result, Stage2Result)]> {
+        let key = |(item, _, stage2): &(VocabularyItem, Stage1Result, Stage2Result)| match self {
+            SortOrder::Position => (false, None, item.position),
+            SortOrder::LearningOrder => (stage2.learning_order.is_none(), stage2.learning_order, item.position),
+        };
+        if results.windows(2).all(|pair| key(&pair[0]) <= key(&pair[1])) {
+            return Cow::Borrowed(results);
+        }
+        
+        let mut sorted = results.to_vec();
+        sorted.sort_by_key(key);
+        Cow::Owned(sorted)
+    }
+}
 
 pub struct TsvExporter {
     delimiter: u8,
     include_headers: bool,
+    quote_style: QuoteStyle,
+    line_ending: LineEnding,
+    /// Start the output with [`UTF8_BOM`]
+    write_bom: bool,
+    layout: RowLayout,
+    threads: usize,
+    stream: Option<StreamState>,
+    /// Where to write the [`CardStatistics`] sidecar, if anywhere
+    stats_path: Option<PathBuf>,
+    /// Skip cards that fail to export instead of failing the export
+    skip_on_error: bool,
+    /// Order of the rows of [`export`](Self::export); streamed cards are
+    /// written as they arrive
+    sort_by: SortOrder,
+}
+
+/// How a card becomes a row, cloned into the blocking formatting tasks
+#[derive(Clone, Default)]
+struct RowLayout {
+    sanitizer: FieldSanitizer,
+    limits: FieldLimits,
+    tags: RunTags,
+    anki: Option<AnkiPreset>,
+    comparison_columns: bool,
+    memory_palace: Option<MemoryPalaceColumns>,
+    stage2_mode: Stage2Mode,
+}
+
+impl RowLayout {
+    /// The card's sanitized row and how many of its fields were truncated.
+    fn format(&self, item: &VocabularyItem, stage1: &Stage1Result, stage2: &Stage2Result) -> (Vec<String>, usize) {
+        let (mut stage2, mut truncated) = self.limits.apply_per_field(stage2);
+        self.tags.apply(item, &mut stage2);
+        let mut record = layout_record(self.anki.as_ref(), self.stage2_mode, item, &stage2);
+        if self.comparison_columns && self.anki.is_none() {
+            record.extend(comparison_record(stage1));
+        }
+        if let Some(columns) = self.memory_palace.filter(|_| self.anki.is_none()) {
+            record.extend(columns.record(stage1));
+        }
+        
+        // After sanitizing, since a newline replacement changes the length
+        let mut record = self.sanitizer.sanitize(record);
+        if let Some(max) = self.limits.max_field_chars {
+            for field in &mut record {
+                if let Some(shortened) = truncate_graphemes(field, max) {
+                    *field = shortened;
+                    truncated += 1;
+                }
+            }
+        }
+        (record, truncated)
+    }
+    
+    /// The card's encoded row and how many of its fields were truncated, or
+    /// why it can't be written.
+    fn encode(
+        &self,
+        delimiter: u8,
+        quote_style: QuoteStyle,
+        line_ending: LineEnding,
+        item: &VocabularyItem,
+        stage1: &Stage1Result,
+        stage2: &Stage2Result,
+    ) -> Result<(Vec<u8>, usize)> {
+        let (record, truncated) = self.format(item, stage1, stage2);
+        // Unquoted, these would split the field or the row on import
+        if matches!(quote_style, QuoteStyle::Never) {
+            let breaks_row = |field: &String| field.bytes().any(|b| b == delimiter || b == b'"' || b == b'\n' || b == b'\r');
+            if let Some(column) = record.iter().position(breaks_row) {
+                return Err(PipelineError::ExportError(format!(
+                    "column {} contains a delimiter, quote or line break but quoting is off", column + 1
+                )));
+            }
+        }
+        Ok((encode_rows(delimiter, quote_style, line_ending, &[record])?, truncated))
+    }
+}
+
+/// Tags added to every card of a run on top of its generated ones
+#[derive(Debug, Clone, Default)]
+struct RunTags {
+    extra: Vec<String>,
+    batch: Option<BatchTags>,
+    /// Suspension and leech marks by term
+    states: Arc<HashMap<String, CardState>>,
+}
+
+/// Where a run's cards came from, for filtering them later
+#[derive(Debug, Clone)]
+struct BatchTags {
+    batch_id: BatchId,
+    label: Option<String>,
+    date: chrono::NaiveDate,
+}
+
+impl RunTags {
+    /// Merge the generated tags with these into the front's thematic tags,
+    /// dropping duplicates but keeping the first-seen order. The
+    /// grammatical tags are folded in too, since every layout writes both
+    /// lists as one.
+    fn apply(&self, item: &VocabularyItem, stage2: &mut Cow<'_, Stage2Result>) {
+        let state_tags = self.state_tags(item);
+        if self.extra.is_empty() && self.batch.is_none() && state_tags.is_empty() {
+            return;
+        }
+        
+        let front = &stage2.front;
+        let mut tags: Vec<String> = Vec::new();
+        let generated = front.thematic_tags.iter().chain(&front.grammatical_tags).cloned();
+        let run = self.extra.iter().cloned().chain(self.batch_tags(item)).chain(state_tags);
+        for tag in generated.chain(run) {
+            if !tags.contains(&tag) {
+                tags.push(tag);
+            }
+        }
+        
+        let front = &mut stage2.to_mut().front;
+        front.thematic_tags = tags;
+        front.grammatical_tags.clear();
+    }
+    
+    /// `batch::<id>`, `label::<label>`, `date::<YYYY-MM-DD>` and
+    /// `source::<file name>`, using Anki's `::` tag hierarchy.
+    fn batch_tags(&self, item: &VocabularyItem) -> Vec<String> {
+        let Some(batch) = &self.batch else {
+            return Vec::new();
+        };
+        
+        let mut tags = vec![format!("batch::{}", batch.batch_id)];
+        if let Some(label) = &batch.label {
+            tags.push(format!("label::{}", label));
+        }
+        tags.push(format!("date::{}", batch.date.format("%Y-%m-%d")));
+        
+        let source = Path::new(&item.source).file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        if !source.is_empty() {
+            tags.push(format!("source::{}", source));
+        }
+        tags
+    }
+    
+    /// Look card states up by their terms from now on.
+    fn set_card_states(&mut self, states: impl IntoIterator<Item = CardState>) {
+        let states = states.into_iter().map(|state| (card_state_key(&state.term), state)).collect();
+        self.states = Arc::new(states);
+    }
+    
+    /// [`SUSPENDED_TAG`] and [`LEECH_TAG`], as the card state of the item's
+    /// term calls for.
+    fn state_tags(&self, item: &VocabularyItem) -> Vec<String> {
+        let Some(state) = self.states.get(&card_state_key(&item.term)) else {
+            return Vec::new();
+        };
+        
+        let mut tags = Vec::new();
+        if state.suspended {
+            tags.push(SUSPENDED_TAG.to_string());
+        }
+        if state.leech {
+            tags.push(LEECH_TAG.to_string());
+        }
+        tags
+    }
+}
+
+/// [`FlashcardContent`] fields a per-field limit can name
+pub const LIMITABLE_FIELDS: &[&str] = &[
+    "primary_field",
+    "secondary_field",
+    "tertiary_field",
+    "example_sentence",
+    "example_translation",
+    "pronunciation_guide",
+    "image_prompt",
+    "mnemonic_aid",
+    "grammar_notes",
+    "cultural_notes",
+    "usage_notes",
+];
+
+/// Appended to truncated fields
+const ELLIPSIS: char = '…';
+
+/// Length limits in grapheme clusters, i.e. characters as a reader sees
+/// them: a Hangul syllable or an emoji with modifiers counts once.
+#[derive(Debug, Clone, Default)]
+struct FieldLimits {
+    /// Applies to every exported column
+    max_field_chars: Option<usize>,
+    /// Keyed by a [`LIMITABLE_FIELDS`] name, applied to both faces before
+    /// the row is laid out
+    per_field: BTreeMap<String, usize>,
+}
+
+impl FieldLimits {
+    /// `stage2` with the per-field limits applied, and how many fields that
+    /// shortened. Borrows when there is nothing to apply.
+    fn apply_per_field<'a>(&self, stage2: &'a Stage2Result) -> (Cow<'a, Stage2Result>, usize) {
+        if self.per_field.is_empty() {
+            return (Cow::Borrowed(stage2), 0);
+        }
+        
+        let mut limited = stage2.clone();
+        let mut truncated = 0;
+        for content in [&mut limited.front, &mut limited.back] {
+            for (name, &max) in &self.per_field {
+                let Some(field) = content_field_mut(content, name) else { continue };
+                if let Some(shortened) = truncate_graphemes(field, max) {
+                    *field = shortened;
+                    truncated += 1;
+                }
+            }
+        }
+        (Cow::Owned(limited), truncated)
+    }
+}
+
+/// The [`LIMITABLE_FIELDS`] field called `name`, if the card has it.
+fn content_field<'a>(content: &'a FlashcardContent, name: &str) -> Option<&'a str> {
+    match name {
+        "primary_field" => Some(&content.primary_field),
+        "secondary_field" => content.secondary_field.as_deref(),
+        "tertiary_field" => content.tertiary_field.as_deref(),
+        "example_sentence" => content.example_sentence.as_deref(),
+        "example_translation" => content.example_translation.as_deref(),
+        "pronunciation_guide" => content.pronunciation_guide.as_deref(),
+        "image_prompt" => content.image_prompt.as_deref(),
+        "mnemonic_aid" => content.mnemonic_aid.as_deref(),
+        "grammar_notes" => content.grammar_notes.as_deref(),
+        "cultural_notes" => content.cultural_notes.as_deref(),
+        "usage_notes" => content.usage_notes.as_deref(),
+        _ => None,
+    }
+}
+
+/// Mutable [`content_field`].
+fn content_field_mut<'a>(content: &'a mut FlashcardContent, name: &str) -> Option<&'a mut String> {
+    match name {
+        "primary_field" => Some(&mut content.primary_field),
+        "secondary_field" => content.secondary_field.as_mut(),
+        "tertiary_field" => content.tertiary_field.as_mut(),
+        "example_sentence" => content.example_sentence.as_mut(),
+        "example_translation" => content.example_translation.as_mut(),
+        "pronunciation_guide" => content.pronunciation_guide.as_mut(),
+        "image_prompt" => content.image_prompt.as_mut(),
+        "mnemonic_aid" => content.mnemonic_aid.as_mut(),
+        "grammar_notes" => content.grammar_notes.as_mut(),
+        "cultural_notes" => content.cultural_notes.as_mut(),
+        "usage_notes" => content.usage_notes.as_mut(),
+        _ => None,
+    }
+}
+
+/// Shorten `text` to at most `max_graphemes` grapheme clusters, the last
+/// being an ellipsis, or `None` if it already fits.
+///
+/// Cutting between clusters rather than at a byte or `char` count keeps
+/// Hangul syllables (including decomposed jamo), combining accents and
+/// emoji sequences whole. `max_graphemes` should be at least 1.
+pub fn truncate_graphemes(text: &str, max_graphemes: usize) -> Option<String> {
+    let mut starts = text.grapheme_indices(true).map(|(start, _)| start);
+    let end = starts.nth(max_graphemes.saturating_sub(1))?;
+    starts.next()?;
+    
+    let kept = text[..end].trim_end();
+    let mut shortened = String::with_capacity(kept.len() + ELLIPSIS.len_utf8());
+    shortened.push_str(kept);
+    shortened.push(ELLIPSIS);
+    Some(shortened)
+}
+
+/// What to do with control characters (tabs, carriage returns, ...) in a field.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub enum ControlCharPolicy {
+    /// Leave them alone and rely on quoting
+    #[default]
+    Keep,
+    /// Remove them
+    Strip,
+    /// Replace each one with the given text
+    Replace(String),
+}
+
+/// Prefix that makes a spreadsheet show a formula-like field as text
+pub const DEFAULT_FORMULA_GUARD: &str = "'";
+
+/// Characters that start a formula in Excel, Sheets and LibreOffice
+const FORMULA_PREFIXES: &[char] = &['=', '+', '-', '@'];
+
+/// Per-field cleanup applied before a record is written.
+#[derive(Debug, Clone, Default)]
+struct FieldSanitizer {
+    newline_replacement: Option<String>,
+    control_chars: ControlCharPolicy,
+    /// Prefixed to fields starting with one of [`FORMULA_PREFIXES`]
+    formula_guard: Option<String>,
+}
+
+impl FieldSanitizer {
+    fn sanitize_field(&self, field: String) -> String {
+        let field = match &self.newline_replacement {
+            Some(replacement) => field.replace("\r\n", "\n").replace('\n', replacement),
+            None => field,
+        };
+        let field = self.strip_control_chars(field);
+        
+        match &self.formula_guard {
+            Some(guard) if field.starts_with(FORMULA_PREFIXES) => format!("{}{}", guard, field),
+            _ => field,
+        }
+    }
+    
+    fn strip_control_chars(&self, field: String) -> String {
+        if self.control_chars == ControlCharPolicy::Keep || !field.chars().any(char::is_control) {
+            return field;
+        }
+        
+        let mut sanitized = String::with_capacity(field.len());
+        for c in field.chars() {
+            if !c.is_control() {
+                sanitized.push(c);
+            } else if let ControlCharPolicy::Replace(replacement) = &self.control_chars {
+                sanitized.push_str(replacement);
+            }
+        }
+        sanitized
+    }
+    
+    fn sanitize(&self, record: Vec<String>) -> Vec<String> {
+        record.into_iter().map(|field| self.sanitize_field(field)).collect()
+    }
+}
+
+struct StreamState {
+    sink: Box<dyn OutputSink>,
+    stats: ExportStats,
 }
 
 impl Default for TsvExporter {
@@ -16,6 +531,15 @@ impl Default for TsvExporter {
         Self {
             delimiter: b'\t',
             include_headers: true,
+            quote_style: QuoteStyle::Necessary,
+            line_ending: LineEnding::default(),
+            write_bom: false,
+            layout: RowLayout::default(),
+            threads: 1,
+            stream: None,
+            stats_path: None,
+            skip_on_error: false,
+            sort_by: SortOrder::default(),
         }
     }
 }
@@ -25,137 +549,368 @@ impl TsvExporter {
         Self::default()
     }
     
-    #[instrument(skip(self, results))]
+    /// Whether to write the header row. Anki's positional field mapping wants none.
+    pub fn with_headers(mut self, include_headers: bool) -> Self {
+        self.include_headers = include_headers;
+        self
+    }
+    
+    /// Lay rows out for an Anki note type instead of the default columns.
+    ///
+    /// The header row is replaced by Anki's `#directive` lines, and embedded
+    /// newlines become `<br>` unless a replacement was already set.
+    pub fn with_anki_preset(mut self, preset: AnkiPreset) -> Self {
+        if self.layout.sanitizer.newline_replacement.is_none() {
+            self.layout.sanitizer.newline_replacement = Some("<br>".to_string());
+        }
+        self.layout.anki = Some(preset);
+        self
+    }
+    
+    /// Add Homonyms, Similar To, Different From and Confused With columns
+    /// from each card's Stage 1 analysis. Ignored with an Anki preset.
+    pub fn with_comparison_columns(mut self, comparison_columns: bool) -> Self {
+        self.layout.comparison_columns = comparison_columns;
+        self
+    }
+    
+    /// Add Stage 1's memory-palace fields (location, anchor, metaphor) as
+    /// one sentence or as separate columns, after any comparison columns.
+    /// Ignored with an Anki preset.
+    pub fn with_memory_palace(mut self, columns: Option<MemoryPalaceColumns>) -> Self {
+        self.layout.memory_palace = columns;
+        self
+    }
+    
+    /// Write the leaner [`MINIMAL_HEADERS`] columns for cards generated in
+    /// [`Stage2Mode::Minimal`]. Ignored with an Anki preset.
+    pub fn with_stage2_mode(mut self, mode: Stage2Mode) -> Self {
+        self.layout.stage2_mode = mode;
+        self
+    }
+    
+    /// Set when fields are quoted. Defaults to [`QuoteStyle::Necessary`].
+    pub fn with_quote_style(mut self, quote_style: QuoteStyle) -> Self {
+        self.quote_style = quote_style;
+        self
+    }
+    
+    /// End rows, header and Anki directive lines with `line_ending`.
+    /// Line breaks inside fields are left as they are.
+    pub fn with_line_ending(mut self, line_ending: LineEnding) -> Self {
+        self.line_ending = line_ending;
+        self
+    }
+    
+    /// Start the output with a UTF-8 byte order mark, which Excel needs to
+    /// show Korean text correctly.
+    pub fn with_bom(mut self, write_bom: bool) -> Self {
+        self.write_bom = write_bom;
+        self
+    }
+    
+    /// Quote every field, not just those containing delimiters or quotes.
+    pub fn quote_all(self) -> Self {
+        self.with_quote_style(QuoteStyle::Always)
+    }
+    
+    /// Replace embedded newlines, e.g. with `<br>` for Anki.
+    pub fn with_newline_replacement(mut self, replacement: impl Into<String>) -> Self {
+        self.layout.sanitizer.newline_replacement = Some(replacement.into());
+        self
+    }
+    
+    /// Strip or replace control characters left after newline replacement.
+    pub fn with_control_chars(mut self, policy: ControlCharPolicy) -> Self {
+        self.layout.sanitizer.control_chars = policy;
+        self
+    }
+    
+    /// Prefix every field a spreadsheet would run as a formula (one starting
+    /// with `=`, `+`, `-` or `@`) with `guard`, e.g. [`DEFAULT_FORMULA_GUARD`],
+    /// so generated content opens as text. Off by default for TSV, which is
+    /// mostly imported into Anki rather than opened in a spreadsheet.
+    pub fn with_formula_guard(mut self, guard: Option<String>) -> Self {
+        self.layout.sanitizer.formula_guard = guard;
+        self
+    }
+    
+    /// Add `tags` to every card, merged with its generated tags.
+    pub fn with_extra_tags(mut self, tags: Vec<String>) -> Self {
+        self.layout.tags.extra = tags;
+        self
+    }
+    
+    /// Also tag every card with the batch it came from, the batch's label,
+    /// `date` and the file name of the card's source, e.g.
+    /// `batch::12 label::spring date::2026-10-16 source::words.csv`.
+    pub fn with_batch_tags(mut self, batch_id: BatchId, label: Option<String>, date: chrono::NaiveDate) -> Self {
+        self.layout.tags.batch = Some(BatchTags { batch_id, label, date });
+        self
+    }
+    
+    /// Tag the cards of terms suspended or marked as leeches in `states` with
+    /// [`SUSPENDED_TAG`] and [`LEECH_TAG`], so the marks survive re-exports.
+    pub fn with_card_states(mut self, states: impl IntoIterator<Item = CardState>) -> Self {
+        self.layout.tags.set_card_states(states);
+        self
+    }
+    
+    /// Cut every column longer than `max` grapheme clusters down to `max`,
+    /// ending with an ellipsis; see [`truncate_graphemes`].
+    pub fn with_max_field_chars(mut self, max: Option<usize>) -> Self {
+        self.layout.limits.max_field_chars = max;
+        self
+    }
+    
+    /// Limit one card field on both faces, e.g. `cultural_notes`, before the
+    /// row is laid out. `field` is one of [`LIMITABLE_FIELDS`].
+    pub fn with_field_char_limit(mut self, field: impl Into<String>, max: usize) -> Self {
+        self.layout.limits.per_field.insert(field.into(), max);
+        self
+    }
+    
+    /// Format records on `threads` worker threads; 0 uses one per core and 1
+    /// (the default) formats on the writing thread. Rows are still written
+    /// in input order, so the output is identical either way.
+    pub fn with_threads(mut self, threads: usize) -> Self {
+        self.threads = threads;
+        self
+    }
+    
+    /// Once the export is done, write its [`CardStatistics`] as JSON to
+    /// `path`, e.g. [`default_stats_path`].
+    pub fn with_stats_path(mut self, path: Option<PathBuf>) -> Self {
+        self.stats_path = path;
+        self
+    }
+    
+    /// Log and skip cards that can't be written, recording them in
+    /// [`ExportStats::skipped_cards`], instead of failing the whole export
+    /// on the first one.
+    pub fn with_skip_on_error(mut self, skip_on_error: bool) -> Self {
+        self.skip_on_error = skip_on_error;
+        self
+    }
+    
+    /// Write the cards of [`export`](Self::export) in `sort_by` order.
+    /// Streamed cards can't be reordered and are written as they arrive.
+    pub fn with_sort(mut self, sort_by: SortOrder) -> Self {
+        self.sort_by = sort_by;
+        self
+    }
+    
+    /// Keep going past a card that failed with `error` if skipping is on,
+    /// otherwise fail with it.
+    fn skip_or_fail(&self, stats: &mut ExportStats, item: &VocabularyItem, error: PipelineError) -> Result<()> {
+        if !self.skip_on_error {
+            return Err(error);
+        }
+        warn!("Skipping card '{}' at position {}: {}", item.term, item.position, error);
+        stats.skipped_cards.push(SkippedCard {
+            position: item.position,
+            term: item.term.clone(),
+            reason: error.to_string(),
+        });
+        stats.cards_skipped_errors += 1;
+        Ok(())
+    }
+    
+    /// Whatever precedes the rows: the byte order mark if enabled, then Anki
+    /// directives for a preset, otherwise the header row if enabled.
+    fn preamble(&self) -> Result<Vec<u8>> {
+        let mut out = Vec::new();
+        if self.write_bom {
+            out.extend(UTF8_BOM);
+        }
+        if let Some(preset) = &self.layout.anki {
+            for line in preset.header_lines() {
+                out.extend(line.as_bytes());
+                out.extend(self.line_ending.as_bytes());
+            }
+        }
+        
+        if self.include_headers && self.layout.anki.is_none() {
+            let headers = match self.layout.stage2_mode {
+                Stage2Mode::Full => HEADERS,
+                Stage2Mode::Minimal => MINIMAL_HEADERS,
+            };
+            let comparison: &[&str] = if self.layout.comparison_columns { COMPARISON_HEADERS } else { &[] };
+            let memory_palace: &[&str] = self.layout.memory_palace.map_or(&[], MemoryPalaceColumns::headers);
+            let headers: Vec<&str> = headers.iter()
+                .chain(comparison)
+                .chain(memory_palace)
+                .copied()
+                .collect();
+            out.extend(encode_rows(self.delimiter, self.quote_style, self.line_ending, &[headers])?);
+        }
+        Ok(out)
+    }
+    
+    #[instrument(skip(self, results, sink))]
     pub async fn export(
         &self,
-        results: &[(VocabularyItem, Stage2Result)],
-        output_path: &Path,
+        results: &[(VocabularyItem, Stage1Result, Stage2Result)],
+        sink: &mut dyn OutputSink,
     ) -> Result<ExportStats> {
-        info!("Exporting {} flashcards to {:?}", results.len(), output_path);
+        info!("Exporting {} flashcards", results.len());
+        let results = self.sort_by.apply(results);
         
-        // Create parent directory if needed
-        if let Some(parent) = output_path.parent() {
-            tokio::fs::create_dir_all(parent).await?;
-        }
+        let pool = if self.threads == 1 {
+            None
+        } else {
+            Some(Arc::new(rayon::ThreadPoolBuilder::new()
+                .num_threads(self.threads)
+                .build()
+                .map_err(|e| PipelineError::ExportError(format!("Thread pool error: {}", e)))?))
+        };
         
-        // Use blocking task for file I/O
-        let results = results.to_vec();
-        let delimiter = self.delimiter;
-        let include_headers = self.include_headers;
-        let output_path = output_path.to_owned();
+        sink.write_all(&self.preamble()?).await?;
         
-        tokio::task::spawn_blocking(move || {
-            let file = File::create(&output_path)?;
-            let mut writer = Writer::from_writer(BufWriter::new(file));
-            writer.set_delimiter(delimiter);
+        let mut stats = ExportStats::default();
+        for chunk in results.chunks(EXPORT_CHUNK_SIZE) {
+            let cards = chunk.to_vec();
+            let layout = self.layout.clone();
+            let pool = pool.clone();
+            let delimiter = self.delimiter;
+            let quote_style = self.quote_style;
+            let line_ending = self.line_ending;
             
-            // Write headers if requested
-            if include_headers {
-                writer.write_record(&[
-                    "Position",
-                    "Term",
-                    "IPA",
-                    "Part of Speech",
-                    "Front Primary",
-                    "Front Secondary",
-                    "Front Example",
-                    "Back Primary",
-                    "Back Secondary",
-                    "Back Example",
-                    "Mnemonic",
-                    "Difficulty",
-                    "Frequency",
-                    "Tags",
-                    "Notes",
-                ])?;
-            }
-            
-            let mut stats = ExportStats::default();
-            
-            for (item, stage2) in &results {
-                let front = &stage2.front;
-                let back = &stage2.back;
-                
-                // Combine all tags
-                let mut tags = Vec::new();
-                tags.extend(front.thematic_tags.iter().cloned());
-                tags.extend(front.grammatical_tags.iter().cloned());
-                let tags_str = tags.join(", ");
-                
-                // Combine notes
-                let mut notes = Vec::new();
-                if let Some(ref usage) = front.usage_notes {
-                    notes.push(format!("Usage: {}", usage));
-                }
-                if let Some(ref grammar) = front.grammar_notes {
-                    notes.push(format!("Grammar: {}", grammar));
-                }
-                if let Some(ref cultural) = front.cultural_notes {
-                    notes.push(format!("Cultural: {}", cultural));
-                }
-                let notes_str = notes.join(" | ");
-                
-                writer.write_record(&[
-                    &item.position.to_string(),
-                    &item.term,
-                    &front.pronunciation_guide.as_deref().unwrap_or(""),
-                    &item.word_type.as_deref().unwrap_or(""),
-                    &front.primary_field,
-                    &front.secondary_field.as_deref().unwrap_or(""),
-                    &front.example_sentence.as_deref().unwrap_or(""),
-                    &back.primary_field,
-                    &back.secondary_field.as_deref().unwrap_or(""),
-                    &back.example_sentence.as_deref().unwrap_or(""),
-                    &front.mnemonic_aid.as_deref().unwrap_or(""),
-                    &format!("{:?}", front.difficulty_level),
-                    &format!("{:?}", front.frequency_level),
-                    &tags_str,
-                    &notes_str,
-                ])?;
-                
-                stats.cards_exported += 1;
-                
-                // Count by difficulty
-                match front.difficulty_level {
-                    flashcard_core::models::DifficultyLevel::Beginner => stats.beginner_cards += 1,
-                    flashcard_core::models::DifficultyLevel::Intermediate => stats.intermediate_cards += 1,
-                    flashcard_core::models::DifficultyLevel::Advanced => stats.advanced_cards += 1,
-                    flashcard_core::models::DifficultyLevel::Native => stats.native_cards += 1,
-                }
-                
-                // Count special features
-                if front.mnemonic_aid.is_some() {
-                    stats.cards_with_mnemonics += 1;
+            // Formatting is CPU-bound, so it runs off the async threads.
+            // par_iter().collect() keeps input order, so rows are written in
+            // order below however many threads formatted them
+            let rows = tokio::task::spawn_blocking(move || {
+                let format = |(item, stage1, stage2): &(VocabularyItem, Stage1Result, Stage2Result)| {
+                    layout.encode(delimiter, quote_style, line_ending, item, stage1, stage2)
+                };
+                match &pool {
+                    Some(pool) => pool.install(|| cards.par_iter().map(format).collect::<Vec<_>>()),
+                    None => cards.iter().map(format).collect(),
                 }
-                if front.example_sentence.is_some() {
-                    stats.cards_with_examples += 1;
-                }
-                if !notes.is_empty() {
-                    stats.cards_with_notes += 1;
+            })
+            .await
+            .map_err(|e| PipelineError::ExportError(format!("Task join error: {}", e)))?;
+            
+            let mut data = Vec::new();
+            for ((item, _, stage2), row) in chunk.iter().zip(rows) {
+                match row {
+                    Ok((row, truncated)) => {
+                        data.extend(row);
+                        stats.record(stage2);
+                        stats.fields_truncated += truncated;
+                    }
+                    Err(e) => self.skip_or_fail(&mut stats, item, e)?,
                 }
             }
-            
-            writer.flush()?;
-            
-            debug!("Export complete: {:?}", stats);
-            Ok::<ExportStats, PipelineError>(stats)
-        })
-        .await
-        .map_err(|e| PipelineError::ExportError(format!("Task join error: {}", e)))?
+            sink.write_all(&data).await?;
+        }
+        
+        stats.log_skipped();
+        if let Some(path) = &self.stats_path {
+            write_stats_file(&stats, path)?;
+        }
+        
+        debug!("Export complete: {:?}", stats);
+        Ok(stats)
+    }
+    
+    /// Start an incremental export to `sink` and write the header row.
+    ///
+    /// Cards are then appended one at a time with [`write_one`](Self::write_one),
+    /// each handed to the sink as it is written, so a crash late in a batch
+    /// keeps everything written so far. Call [`finish`](Self::finish) to
+    /// close the sink.
+    pub async fn begin(&mut self, mut sink: Box<dyn OutputSink>) -> Result<()> {
+        if self.stream.is_some() {
+            return Err(PipelineError::ExportError(
+                "Streaming export already in progress".to_string()
+            ));
+        }
+        
+        sink.write_all(&self.preamble()?).await?;
+        
+        info!("Streaming flashcards");
+        self.stream = Some(StreamState {
+            sink,
+            stats: ExportStats::default(),
+        });
+        Ok(())
+    }
+    
+    /// Append a single card to the sink passed to [`begin`](Self::begin).
+    pub async fn write_one(
+        &mut self,
+        item: &VocabularyItem,
+        stage1: &Stage1Result,
+        stage2: &Stage2Result,
+    ) -> Result<()> {
+        let mut stream = self.stream.take().ok_or_else(|| PipelineError::ExportError(
+            "write_one called before begin".to_string()
+        ))?;
+        
+        let written = match self.layout.encode(self.delimiter, self.quote_style, self.line_ending, item, stage1, stage2) {
+            Ok((data, truncated)) => stream.sink.write_all(&data).await.map(|()| {
+                stream.stats.record(stage2);
+                stream.stats.fields_truncated += truncated;
+            }),
+            Err(e) => self.skip_or_fail(&mut stream.stats, item, e),
+        };
+        self.stream = Some(stream);
+        written
+    }
+    
+    /// Finish the sink and return the stats accumulated while streaming.
+    pub async fn finish(&mut self) -> Result<ExportStats> {
+        let mut stream = self.stream.take().ok_or_else(|| PipelineError::ExportError(
+            "finish called before begin".to_string()
+        ))?;
+        
+        stream.sink.finish().await?;
+        stream.stats.log_skipped();
+        if let Some(path) = &self.stats_path {
+            write_stats_file(&stream.stats, path)?;
+        }
+        debug!("Streaming export complete: {:?}", stream.stats);
+        Ok(stream.stats)
     }
     
+    /// Export as comma-separated values, with formula-like fields guarded
+    /// by [`DEFAULT_FORMULA_GUARD`] since CSV files tend to be opened in a
+    /// spreadsheet. Keeps this exporter's line ending and byte order mark.
     pub async fn export_csv(
         &self,
-        results: &[(VocabularyItem, Stage2Result)],
-        output_path: &Path,
+        results: &[(VocabularyItem, Stage1Result, Stage2Result)],
+        sink: &mut dyn OutputSink,
     ) -> Result<ExportStats> {
-        let mut exporter = Self::new();
+        let mut exporter = Self::new()
+            .with_formula_guard(Some(DEFAULT_FORMULA_GUARD.to_string()))
+            .with_line_ending(self.line_ending)
+            .with_bom(self.write_bom)
+            .with_sort(self.sort_by);
         exporter.delimiter = b',';
-        exporter.export(results, output_path).await
+        exporter.export(results, sink).await
     }
 }
 
-#[derive(Debug, Default, Clone)]
+/// Encode `records` as delimited rows.
+fn encode_rows<R: AsRef<[T]>, T: AsRef<[u8]>>(
+    delimiter: u8,
+    quote_style: QuoteStyle,
+    line_ending: LineEnding,
+    records: &[R],
+) -> Result<Vec<u8>> {
+    let mut writer = WriterBuilder::new()
+        .delimiter(delimiter)
+        .quote_style(quote_style)
+        .terminator(line_ending.terminator())
+        .from_writer(Vec::new());
+    for record in records {
+        writer.write_record(record.as_ref())?;
+    }
+    writer.into_inner().map_err(|e| PipelineError::ExportError(e.to_string()))
+}
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
 pub struct ExportStats {
     pub cards_exported: usize,
     pub beginner_cards: usize,
@@ -165,11 +920,97 @@ pub struct ExportStats {
     pub cards_with_mnemonics: usize,
     pub cards_with_examples: usize,
     pub cards_with_notes: usize,
+    /// Left out of a [`MnemonicExporter`] deck for having no mnemonic
+    #[serde(default)]
+    pub cards_skipped_no_mnemonic: usize,
+    /// Fields cut short by a length limit
+    #[serde(default)]
+    pub fields_truncated: usize,
+    /// Left out for failing to export, with
+    /// [`TsvExporter::with_skip_on_error`]
+    #[serde(default)]
+    pub cards_skipped_errors: usize,
+    /// Which cards those were and why
+    #[serde(default)]
+    pub skipped_cards: Vec<SkippedCard>,
+    /// Cards per frequency level
+    #[serde(default)]
+    pub by_frequency: BTreeMap<String, usize>,
+    /// Cards per card type
+    #[serde(default)]
+    pub by_card_type: BTreeMap<String, usize>,
+    /// Lengths of the [`LIMITABLE_FIELDS`] cards had, keyed `front.<field>`
+    /// or `back.<field>` and measured before any length limit
+    #[serde(default)]
+    pub field_lengths: BTreeMap<String, FieldLength>,
+}
+
+/// A card left out of an export because it couldn't be written
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SkippedCard {
+    pub position: i32,
+    pub term: String,
+    pub reason: String,
+}
+
+/// Total length of one field over the cards that had it
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct FieldLength {
+    pub cards: usize,
+    /// In grapheme clusters, as for length limits
+    pub total_chars: usize,
+}
+
+impl FieldLength {
+    pub fn average(&self) -> f64 {
+        if self.cards == 0 {
+            return 0.0;
+        }
+        self.total_chars as f64 / self.cards as f64
+    }
 }
 
 impl ExportStats {
+    fn record(&mut self, stage2: &Stage2Result) {
+        let front = &stage2.front;
+        self.cards_exported += 1;
+        
+        *self.by_frequency.entry(format!("{:?}", front.frequency_level)).or_default() += 1;
+        *self.by_card_type.entry(format!("{:?}", stage2.card_type)).or_default() += 1;
+        
+        for (face, content) in [("front", &stage2.front), ("back", &stage2.back)] {
+            for name in LIMITABLE_FIELDS {
+                let Some(field) = content_field(content, name).filter(|field| !field.is_empty()) else {
+                    continue;
+                };
+                let length = self.field_lengths.entry(format!("{}.{}", face, name)).or_default();
+                length.cards += 1;
+                length.total_chars += field.graphemes(true).count();
+            }
+        }
+        
+        // Count by difficulty
+        match front.difficulty_level {
+            flashcard_core::models::DifficultyLevel::Beginner => self.beginner_cards += 1,
+            flashcard_core::models::DifficultyLevel::Intermediate => self.intermediate_cards += 1,
+            flashcard_core::models::DifficultyLevel::Advanced => self.advanced_cards += 1,
+            flashcard_core::models::DifficultyLevel::Native => self.native_cards += 1,
+        }
+        
+        // Count special features
+        if front.mnemonic_aid.is_some() {
+            self.cards_with_mnemonics += 1;
+        }
+        if front.example_sentence.is_some() {
+            self.cards_with_examples += 1;
+        }
+        if !combined_notes(front).is_empty() {
+            self.cards_with_notes += 1;
+        }
+    }
+    
     pub fn summary(&self) -> String {
-        format!(
+        let mut summary = format!(
             "Exported {} cards:\n  \
              - Beginner: {}\n  \
              - Intermediate: {}\n  \
@@ -186,46 +1027,1098 @@ impl ExportStats {
             self.cards_with_mnemonics,
             self.cards_with_examples,
             self.cards_with_notes
-        )
+        );
+        
+        if self.cards_skipped_no_mnemonic > 0 {
+            summary.push_str(&format!("\n  - Skipped without a mnemonic: {}", self.cards_skipped_no_mnemonic));
+        }
+        if self.fields_truncated > 0 {
+            summary.push_str(&format!("\n  - Fields truncated: {}", self.fields_truncated));
+        }
+        if self.cards_skipped_errors > 0 {
+            summary.push_str(&format!("\n  - Skipped after errors: {}", self.cards_skipped_errors));
+        }
+        summary
+    }
+    
+    /// Log every card skipped for an error, once the export is done.
+    fn log_skipped(&self) {
+        if self.skipped_cards.is_empty() {
+            return;
+        }
+        warn!("Skipped {} cards that failed to export:", self.skipped_cards.len());
+        for card in &self.skipped_cards {
+            warn!("  {} (position {}): {}", card.term, card.position, card.reason);
+        }
+    }
+    
+    /// Percentage of exported cards that `count` is
+    fn percent(&self, count: usize) -> f64 {
+        if self.cards_exported == 0 {
+            return 0.0;
+        }
+        count as f64 * 100.0 / self.cards_exported as f64
+    }
+    
+    /// The counters as shares and averages, as written to the sidecar.
+    pub fn card_statistics(&self) -> CardStatistics {
+        CardStatistics {
+            cards: self.cards_exported,
+            by_difficulty: BTreeMap::from([
+                ("Beginner".to_string(), self.beginner_cards),
+                ("Intermediate".to_string(), self.intermediate_cards),
+                ("Advanced".to_string(), self.advanced_cards),
+                ("Native".to_string(), self.native_cards),
+            ]),
+            by_frequency: self.by_frequency.clone(),
+            by_card_type: self.by_card_type.clone(),
+            average_field_chars: self.field_lengths.iter()
+                .map(|(field, length)| (field.clone(), length.average()))
+                .collect(),
+            percent_with_examples: self.percent(self.cards_with_examples),
+            percent_with_mnemonics: self.percent(self.cards_with_mnemonics),
+            percent_with_notes: self.percent(self.cards_with_notes),
+            cards_skipped_no_mnemonic: self.cards_skipped_no_mnemonic,
+            cards_skipped_errors: self.cards_skipped_errors,
+            fields_truncated: self.fields_truncated,
+        }
+    }
+}
+
+/// Aggregate statistics for one export, written next to the output by
+/// [`write_stats_file`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CardStatistics {
+    pub cards: usize,
+    pub by_difficulty: BTreeMap<String, usize>,
+    pub by_frequency: BTreeMap<String, usize>,
+    pub by_card_type: BTreeMap<String, usize>,
+    /// Average grapheme clusters per field, over the cards that had it
+    pub average_field_chars: BTreeMap<String, f64>,
+    pub percent_with_examples: f64,
+    pub percent_with_mnemonics: f64,
+    pub percent_with_notes: f64,
+    pub cards_skipped_no_mnemonic: usize,
+    pub cards_skipped_errors: usize,
+    pub fields_truncated: usize,
+}
+
+/// Path for the statistics sidecar next to `output_path`, e.g.
+/// `output.stats.json`. Output to stdout uses `output.stats.json` in the
+/// working directory.
+pub fn default_stats_path(output_path: &Path) -> PathBuf {
+    if sink::is_stdout(output_path) {
+        return PathBuf::from("output.stats.json");
+    }
+    output_path.with_extension("stats.json")
+}
+
+/// Write the [`CardStatistics`] for `stats` to `path` as pretty-printed JSON.
+pub fn write_stats_file(stats: &ExportStats, path: &Path) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    
+    let json = serde_json::to_string_pretty(&stats.card_statistics())?;
+    std::fs::write(path, json)?;
+    info!("Wrote card statistics to {:?}", path);
+    Ok(())
+}
+
+pub(crate) fn combined_tags(front: &FlashcardContent) -> String {
+    let mut tags = Vec::new();
+    tags.extend(front.thematic_tags.iter().cloned());
+    tags.extend(front.grammatical_tags.iter().cloned());
+    tags.join(", ")
+}
+
+fn combined_notes(front: &FlashcardContent) -> Vec<String> {
+    let mut notes = Vec::new();
+    if let Some(ref usage) = front.usage_notes {
+        notes.push(format!("Usage: {}", usage));
+    }
+    if let Some(ref grammar) = front.grammar_notes {
+        notes.push(format!("Grammar: {}", grammar));
+    }
+    if let Some(ref cultural) = front.cultural_notes {
+        notes.push(format!("Cultural: {}", cultural));
+    }
+    notes
+}
+
+fn layout_record(
+    anki: Option<&AnkiPreset>,
+    stage2_mode: Stage2Mode,
+    item: &VocabularyItem,
+    stage2: &Stage2Result,
+) -> Vec<String> {
+    match (anki, stage2_mode) {
+        (Some(preset), _) => preset.record(item, stage2),
+        (None, Stage2Mode::Full) => format_record(item, stage2),
+        (None, Stage2Mode::Minimal) => minimal_record(item, stage2),
+    }
+}
+
+/// The [`MINIMAL_HEADERS`] columns
+fn minimal_record(item: &VocabularyItem, stage2: &Stage2Result) -> Vec<String> {
+    vec![
+        item.position.to_string(),
+        item.term.clone(),
+        stage2.front.pronunciation_guide.clone().unwrap_or_default(),
+        stage2.front.primary_field.clone(),
+        stage2.back.primary_field.clone(),
+        combined_tags(&stage2.front),
+    ]
+}
+
+fn format_record(item: &VocabularyItem, stage2: &Stage2Result) -> Vec<String> {
+    let front = &stage2.front;
+    let back = &stage2.back;
+    
+    vec![
+        item.position.to_string(),
+        item.term.clone(),
+        front.pronunciation_guide.clone().unwrap_or_default(),
+        item.word_type.clone().unwrap_or_default(),
+        front.primary_field.clone(),
+        front.secondary_field.clone().unwrap_or_default(),
+        front.example_sentence.clone().unwrap_or_default(),
+        front.example_translation.clone().unwrap_or_default(),
+        back.primary_field.clone(),
+        back.secondary_field.clone().unwrap_or_default(),
+        back.example_sentence.clone().unwrap_or_default(),
+        back.example_translation.clone().unwrap_or_default(),
+        front.mnemonic_aid.clone().unwrap_or_default(),
+        format!("{:?}", front.difficulty_level),
+        format!("{:?}", front.frequency_level),
+        combined_tags(front),
+        combined_notes(front).join(" | "),
+        stage2.related_cards.join(", "),
+    ]
+}
+
+/// The [`COMPARISON_HEADERS`] columns. Homonyms render as
+/// `reading (hanja): meaning` joined with `; `; empty lists give empty columns.
+fn comparison_record(stage1: &Stage1Result) -> Vec<String> {
+    let homonyms = stage1.homonyms.iter()
+        .map(|homonym| {
+            if homonym.hanja.trim().is_empty() {
+                format!("{}: {}", homonym.reading, homonym.meaning)
+            } else {
+                format!("{} ({}): {}", homonym.reading, homonym.hanja, homonym.meaning)
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("; ");
+    
+    vec![
+        homonyms,
+        stage1.comparison.similar_to.join(", "),
+        stage1.comparison.different_from.join(", "),
+        stage1.comparison.commonly_confused_with.join(", "),
+    ]
+}
+
+/// Stage 1's memory palace as one sentence, e.g. "In the kitchen, the kettle
+/// (whistling) — a teacher blowing a whistle". Empty fields are left out
+/// along with the words around them, so a result without an anchor reads
+/// "In the kitchen — ...", and one with nothing filled in gives "".
+pub fn memory_palace_sentence(stage1: &Stage1Result) -> String {
+    let field = |value: &str| Some(value.trim()).filter(|value| !value.is_empty());
+    
+    let mut scene = match (field(&stage1.suggested_location), field(&stage1.anchor_object)) {
+        (Some(location), Some(anchor)) => format!("In the {}, the {}", location, anchor),
+        (Some(location), None) => format!("In the {}", location),
+        (None, Some(anchor)) => format!("The {}", anchor),
+        (None, None) => String::new(),
+    };
+    if let Some(sensory) = field(&stage1.anchor_sensory) {
+        if scene.is_empty() {
+            scene = sensory.to_string();
+        } else {
+            scene = format!("{} ({})", scene, sensory);
+        }
+    }
+    
+    match field(&stage1.metaphor) {
+        Some(metaphor) if scene.is_empty() => metaphor.to_string(),
+        Some(metaphor) => format!("{} — {}", scene, metaphor),
+        None => scene,
+    }
+}
+
+/// Path for the failure report next to `output_path`, e.g. `output.errors.csv`.
+/// Output to stdout reports to `output.errors.csv` in the working directory.
+pub fn default_error_report_path(output_path: &Path) -> std::path::PathBuf {
+    if sink::is_stdout(output_path) {
+        return std::path::PathBuf::from("output.errors.csv");
+    }
+    output_path.with_extension("errors.csv")
+}
+
+/// Write failed items to a CSV so they can be fed back in as a re-run.
+///
+/// Nothing is written when there are no failures. Returns the count of
+/// failures per error category.
+pub fn write_error_report(
+    failures: &[FailureRecord],
+    report_path: &Path,
+) -> Result<BTreeMap<String, usize>> {
+    let mut by_category = BTreeMap::new();
+    if failures.is_empty() {
+        return Ok(by_category);
+    }
+    
+    if let Some(parent) = report_path.parent() {
+        std::fs::create_dir_all(parent)?;
     }
+    
+    let mut writer = Writer::from_path(report_path)?;
+    for failure in failures {
+        writer.serialize(failure)?;
+        *by_category.entry(failure.category.clone()).or_insert(0) += 1;
+    }
+    writer.flush()?;
+    
+    info!("Wrote {} failures to {:?}", failures.len(), report_path);
+    Ok(by_category)
 }
 
 // Additional export formats for future extension
 pub trait Exporter {
     async fn export(
         &self,
-        results: &[(VocabularyItem, Stage2Result)],
-        output_path: &Path,
+        results: &[(VocabularyItem, Stage1Result, Stage2Result)],
+        sink: &mut dyn OutputSink,
     ) -> Result<ExportStats>;
+    
+    /// Export to the sink [`sink::open_sink`] picks for `output_path`, so
+    /// `-` writes to stdout.
+    async fn export_to(
+        &self,
+        results: &[(VocabularyItem, Stage1Result, Stage2Result)],
+        output_path: &Path,
+    ) -> Result<ExportStats> {
+        let mut sink = sink::open_sink(output_path).await?;
+        let stats = self.export(results, sink.as_mut()).await?;
+        sink.finish().await?;
+        Ok(stats)
+    }
 }
 
 impl Exporter for TsvExporter {
     async fn export(
         &self,
-        results: &[(VocabularyItem, Stage2Result)],
-        output_path: &Path,
+        results: &[(VocabularyItem, Stage1Result, Stage2Result)],
+        sink: &mut dyn OutputSink,
     ) -> Result<ExportStats> {
-        self.export(results, output_path).await
+        self.export(results, sink).await
+    }
+}
+
+/// Output formats a finished batch can be exported in again
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    /// The [`TsvExporter`] layout the batch would normally get
+    #[default]
+    Tsv,
+    /// Every card with its Stage 1 analysis, as a JSON array
+    Json,
+    /// The [`MnemonicExporter`] deck
+    Mnemonics,
+}
+
+impl ExportFormat {
+    /// File extension for output in this format
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ExportFormat::Tsv | ExportFormat::Mnemonics => "tsv",
+            ExportFormat::Json => "json",
+        }
     }
 }
 
 // JSON export for future use
-pub struct JsonExporter;
+#[derive(Default)]
+pub struct JsonExporter {
+    tags: RunTags,
+}
 
 impl JsonExporter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    
+    /// See [`TsvExporter::with_card_states`].
+    pub fn with_card_states(mut self, states: impl IntoIterator<Item = CardState>) -> Self {
+        self.tags.set_card_states(states);
+        self
+    }
+    
     pub async fn export(
         &self,
-        results: &[(VocabularyItem, Stage2Result)],
-        output_path: &Path,
+        results: &[(VocabularyItem, Stage1Result, Stage2Result)],
+        sink: &mut dyn OutputSink,
     ) -> Result<ExportStats> {
-        info!("Exporting {} flashcards to JSON at {:?}", results.len(), output_path);
+        info!("Exporting {} flashcards to JSON", results.len());
         
-        let json_data = serde_json::to_string_pretty(results)?;
-        tokio::fs::write(output_path, json_data).await?;
+        let tagged: Vec<_> = results.iter()
+            .map(|(item, stage1, stage2)| {
+                let mut stage2 = Cow::Borrowed(stage2);
+                self.tags.apply(item, &mut stage2);
+                (item, stage1, stage2)
+            })
+            .collect();
+        let json_data = serde_json::to_vec_pretty(&tagged)?;
+        sink.write_all(&json_data).await?;
         
         Ok(ExportStats {
             cards_exported: results.len(),
             ..Default::default()
         })
     }
-}
\ No newline at end of file
+}
+
+impl Exporter for JsonExporter {
+    async fn export(
+        &self,
+        results: &[(VocabularyItem, Stage1Result, Stage2Result)],
+        sink: &mut dyn OutputSink,
+    ) -> Result<ExportStats> {
+        self.export(results, sink).await
+    }
+}
+
+/// A minimal deck for reviewing mnemonics: the term, its meaning, the
+/// mnemonic aid, Stage 1's metaphor fields and the card's tags.
+///
+/// Cards without a mnemonic on either face are left out and counted in
+/// [`ExportStats::cards_skipped_no_mnemonic`].
+pub struct MnemonicExporter {
+    delimiter: u8,
+    include_headers: bool,
+    stats_path: Option<PathBuf>,
+    tags: RunTags,
+}
+
+impl Default for MnemonicExporter {
+    fn default() -> Self {
+        Self {
+            delimiter: b'\t',
+            include_headers: true,
+            stats_path: None,
+            tags: RunTags::default(),
+        }
+    }
+}
+
+impl MnemonicExporter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    
+    pub fn with_headers(mut self, include_headers: bool) -> Self {
+        self.include_headers = include_headers;
+        self
+    }
+    
+    /// See [`TsvExporter::with_stats_path`].
+    pub fn with_stats_path(mut self, path: Option<PathBuf>) -> Self {
+        self.stats_path = path;
+        self
+    }
+    
+    /// See [`TsvExporter::with_extra_tags`].
+    pub fn with_extra_tags(mut self, tags: Vec<String>) -> Self {
+        self.tags.extra = tags;
+        self
+    }
+    
+    /// See [`TsvExporter::with_batch_tags`].
+    pub fn with_batch_tags(mut self, batch_id: BatchId, label: Option<String>, date: chrono::NaiveDate) -> Self {
+        self.tags.batch = Some(BatchTags { batch_id, label, date });
+        self
+    }
+    
+    /// See [`TsvExporter::with_card_states`].
+    pub fn with_card_states(mut self, states: impl IntoIterator<Item = CardState>) -> Self {
+        self.tags.set_card_states(states);
+        self
+    }
+    
+    pub async fn export(
+        &self,
+        results: &[(VocabularyItem, Stage1Result, Stage2Result)],
+        sink: &mut dyn OutputSink,
+    ) -> Result<ExportStats> {
+        info!("Exporting mnemonics for {} flashcards", results.len());
+        
+        let mut writer = WriterBuilder::new()
+            .delimiter(self.delimiter)
+            .from_writer(Vec::new());
+        if self.include_headers {
+            writer.write_record(MNEMONIC_HEADERS)?;
+        }
+        
+        let mut stats = ExportStats::default();
+        for (item, stage1, stage2) in results {
+            let mut stage2 = Cow::Borrowed(stage2);
+            self.tags.apply(item, &mut stage2);
+            let stage2 = stage2.as_ref();
+            match mnemonic_record(item, stage1, stage2) {
+                Some(record) => {
+                    writer.write_record(&record)?;
+                    stats.record(stage2);
+                }
+                None => stats.cards_skipped_no_mnemonic += 1,
+            }
+        }
+        
+        let data = writer.into_inner()
+            .map_err(|e| PipelineError::ExportError(e.to_string()))?;
+        sink.write_all(&data).await?;
+        
+        if let Some(path) = &self.stats_path {
+            write_stats_file(&stats, path)?;
+        }
+        
+        debug!("Mnemonic export complete: {:?}", stats);
+        Ok(stats)
+    }
+}
+
+impl Exporter for MnemonicExporter {
+    async fn export(
+        &self,
+        results: &[(VocabularyItem, Stage1Result, Stage2Result)],
+        sink: &mut dyn OutputSink,
+    ) -> Result<ExportStats> {
+        self.export(results, sink).await
+    }
+}
+
+/// The [`MNEMONIC_HEADERS`] columns, or `None` when neither face has a mnemonic.
+fn mnemonic_record(item: &VocabularyItem, stage1: &Stage1Result, stage2: &Stage2Result) -> Option<Vec<String>> {
+    let mnemonic = [&stage2.front.mnemonic_aid, &stage2.back.mnemonic_aid]
+        .into_iter()
+        .flatten()
+        .find(|mnemonic| !mnemonic.trim().is_empty())?;
+    
+    Some(vec![
+        item.term.clone(),
+        stage2.front.primary_field.clone(),
+        mnemonic.clone(),
+        stage1.metaphor.clone(),
+        stage1.metaphor_noun.clone(),
+        stage1.metaphor_action.clone(),
+        combined_tags(&stage2.front),
+    ])
+}
+
+/// Card states are matched by term the way cache keys are: NFC-normalized
+/// and trimmed, so a term typed in decomposed jamo still finds its state.
+fn card_state_key(term: &str) -> String {
+    KeyNormalization::NfcTrim.apply(term).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures;
+    use flashcard_core::models::{CardType, Comparison, DifficultyLevel, Homonym};
+    
+    fn stage1(term: &str) -> Stage1Result {
+        Stage1Result {
+            term_number: 1,
+            term: term.to_string(),
+            ipa: String::new(),
+            pos: "noun".to_string(),
+            primary_meaning: "school".to_string(),
+            other_meanings: String::new(),
+            metaphor: String::new(),
+            metaphor_noun: String::new(),
+            metaphor_action: String::new(),
+            suggested_location: String::new(),
+            anchor_object: String::new(),
+            anchor_sensory: String::new(),
+            explanation: String::new(),
+            usage_context: None,
+            comparison: Comparison {
+                similar_to: vec![],
+                different_from: vec![],
+                commonly_confused_with: vec![],
+            },
+            homonyms: vec![],
+            korean_keywords: vec![],
+        }
+    }
+    
+    fn card(example: &str) -> (VocabularyItem, Stage1Result, Stage2Result) {
+        let (mut item, mut stage2) = fixtures::card(1, "학교", "school");
+        item.word_type = Some("noun".to_string());
+        stage2.front.example_sentence = Some(example.to_string());
+        stage2.learning_order = Some(1);
+        (item, stage1("학교"), stage2)
+    }
+    
+    fn read_back(path: &Path) -> Vec<csv::StringRecord> {
+        csv::ReaderBuilder::new()
+            .delimiter(b'\t')
+            .from_path(path)
+            .unwrap()
+            .records()
+            .map(|record| record.unwrap())
+            .collect()
+    }
+    
+    #[tokio::test]
+    async fn test_embedded_tab_and_newline_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("cards.tsv");
+        let example = "학교에\t가요.\n매일 가요.";
+        
+        TsvExporter::new().export_to(&[card(example)], &path).await.unwrap();
+        
+        let records = read_back(&path);
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].len(), HEADERS.len());
+        assert_eq!(&records[0][6], example);
+    }
+    
+    #[tokio::test]
+    async fn test_parallel_export_matches_sequential() {
+        let dir = tempfile::tempdir().unwrap();
+        let sequential = dir.path().join("sequential.tsv");
+        let parallel = dir.path().join("parallel.tsv");
+        
+        // More than one chunk, so ordering across chunk boundaries is covered
+        let cards: Vec<_> = (0..EXPORT_CHUNK_SIZE as i32 + 37)
+            .map(|position| {
+                let (mut item, stage1, stage2) = card(&format!("예문 {}", position));
+                item.position = position;
+                (item, stage1, stage2)
+            })
+            .collect();
+        
+        TsvExporter::new().export_to(&cards, &sequential).await.unwrap();
+        TsvExporter::new().with_threads(4).export_to(&cards, &parallel).await.unwrap();
+        
+        assert_eq!(std::fs::read(&sequential).unwrap(), std::fs::read(&parallel).unwrap());
+    }
+    
+    #[tokio::test]
+    async fn test_learning_order_sorts_rows() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("cards.tsv");
+        let cards: Vec<_> = [(1, Some(3)), (2, None), (3, Some(1)), (4, Some(3)), (5, None), (6, Some(2))]
+            .into_iter()
+            .map(|(position, learning_order)| {
+                let (mut item, stage1, mut stage2) = card("학교에 가요.");
+                item.position = position;
+                stage2.learning_order = learning_order;
+                (item, stage1, stage2)
+            })
+            .rev()
+            .collect();
+        let positions = |path: &Path| -> Vec<String> {
+            read_back(path).iter().map(|record| record[0].to_string()).collect()
+        };
+        
+        TsvExporter::new().export_to(&cards, &path).await.unwrap();
+        assert_eq!(positions(&path), ["1", "2", "3", "4", "5", "6"]);
+        
+        // Ties on learning order by position, cards without one last
+        TsvExporter::new().with_sort(SortOrder::LearningOrder).export_to(&cards, &path).await.unwrap();
+        assert_eq!(positions(&path), ["3", "6", "1", "4", "2", "5"]);
+    }
+    
+    #[tokio::test]
+    async fn test_memory_sink_matches_file_output() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("cards.tsv");
+        let cards = [card("학교에\t가요.\n매일 가요."), card("학교에 가요.")];
+        let exporter = TsvExporter::new().with_comparison_columns(true);
+        
+        exporter.export_to(&cards, &path).await.unwrap();
+        
+        let mut memory = Vec::new();
+        let stats = Exporter::export(&exporter, &cards, &mut memory).await.unwrap();
+        
+        assert_eq!(stats.cards_exported, 2);
+        assert_eq!(memory, std::fs::read(&path).unwrap());
+    }
+    
+    #[tokio::test]
+    async fn test_stats_file_counts_match_exported_cards() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("cards.tsv");
+        let stats_path = default_stats_path(&path);
+        
+        let mut advanced = card("학교에 가요.");
+        advanced.2.front.difficulty_level = DifficultyLevel::Advanced;
+        advanced.2.front.mnemonic_aid = Some("학 sounds like a crane".to_string());
+        let mut cloze = card("학교");
+        cloze.2.card_type = CardType::Cloze;
+        cloze.2.front.example_sentence = None;
+        let cards = [card("학교에 갔어요."), advanced, cloze];
+        
+        let stats = TsvExporter::new()
+            .with_stats_path(Some(stats_path.clone()))
+            .export_to(&cards, &path)
+            .await
+            .unwrap();
+        
+        assert_eq!(stats_path, dir.path().join("cards.stats.json"));
+        let written: CardStatistics = serde_json::from_str(&std::fs::read_to_string(&stats_path).unwrap()).unwrap();
+        
+        assert_eq!(written.cards, read_back(&path).len());
+        assert_eq!(written.cards, stats.cards_exported);
+        assert_eq!(written.by_difficulty["Beginner"], 2);
+        assert_eq!(written.by_difficulty["Advanced"], 1);
+        assert_eq!(written.by_frequency, BTreeMap::from([("Common".to_string(), 3)]));
+        assert_eq!(written.by_card_type, BTreeMap::from([
+            ("Cloze".to_string(), 1),
+            ("Standard".to_string(), 2),
+        ]));
+        assert!((written.percent_with_examples - 200.0 / 3.0).abs() < 1e-9);
+        assert!((written.percent_with_mnemonics - 100.0 / 3.0).abs() < 1e-9);
+        
+        // "학교에 갔어요." and "학교에 가요." are 8 and 7 characters
+        assert_eq!(written.average_field_chars["front.example_sentence"], 7.5);
+        assert_eq!(written.average_field_chars["back.primary_field"], 6.0);
+    }
+    
+    #[tokio::test]
+    async fn test_formula_guard_neutralizes_formulas() {
+        let cards = [card("=SUM(A1:A9)"), card("-다 ending"), card("학교에 가요.")];
+        
+        let mut output = Vec::new();
+        TsvExporter::new().with_formula_guard(Some(DEFAULT_FORMULA_GUARD.to_string()))
+            .export(&cards, &mut output).await.unwrap();
+        
+        let mut reader = csv::ReaderBuilder::new().delimiter(b'\t').from_reader(output.as_slice());
+        let examples: Vec<String> = reader.records()
+            .map(|record| record.unwrap()[6].to_string())
+            .collect();
+        assert_eq!(examples, ["'=SUM(A1:A9)", "'-다 ending", "학교에 가요."]);
+        
+        // Off by default for TSV, on for CSV
+        let mut output = Vec::new();
+        TsvExporter::new().export(&cards[..1], &mut output).await.unwrap();
+        assert!(String::from_utf8(output).unwrap().contains("\t=SUM(A1:A9)"));
+        
+        let mut output = Vec::new();
+        TsvExporter::new().export_csv(&cards[..1], &mut output).await.unwrap();
+        assert!(String::from_utf8(output).unwrap().contains("'=SUM(A1:A9)"));
+    }
+    
+    #[tokio::test]
+    async fn test_crlf_with_bom() {
+        let cards = [card("학교에 가요."), card("바다가 넓어요.")];
+        
+        let mut output = Vec::new();
+        TsvExporter::new()
+            .with_line_ending(LineEnding::CrLf)
+            .with_bom(true)
+            .export(&cards, &mut output).await.unwrap();
+        
+        assert!(output.starts_with(UTF8_BOM));
+        let text = String::from_utf8(output[UTF8_BOM.len()..].to_vec()).unwrap();
+        // Header and both cards, each ending in CRLF and nothing else
+        assert!(text.ends_with("\r\n"));
+        assert_eq!(text.matches("\r\n").count(), 3);
+        assert_eq!(text.matches('\n').count(), 3);
+        assert!(text.starts_with("Position\t"));
+        
+        // Neither by default
+        let mut output = Vec::new();
+        TsvExporter::new().export(&cards, &mut output).await.unwrap();
+        assert!(output.starts_with(b"Position\t"));
+        assert!(!output.contains(&b'\r'));
+    }
+    
+    #[tokio::test]
+    async fn test_skip_on_error_exports_the_other_cards() {
+        // Unquoted, the tab would shift every later column of its row
+        let mut broken = card("학교에\t가요.");
+        broken.0.position = 2;
+        let cards = [card("학교에 가요."), broken, card("매일 가요.")];
+        let exporter = TsvExporter::new().with_quote_style(QuoteStyle::Never);
+        
+        let mut output = Vec::new();
+        let error = Exporter::export(&exporter, &cards, &mut output).await.unwrap_err();
+        assert!(matches!(error, PipelineError::ExportError(_)));
+        
+        let mut output = Vec::new();
+        let stats = Exporter::export(&exporter.with_skip_on_error(true), &cards, &mut output).await.unwrap();
+        
+        assert_eq!(stats.cards_exported, 2);
+        assert_eq!(stats.cards_skipped_errors, 1);
+        assert_eq!(stats.skipped_cards[0].position, 2);
+        assert!(stats.skipped_cards[0].reason.contains("column 7"), "{}", stats.skipped_cards[0].reason);
+        
+        let text = String::from_utf8(output).unwrap();
+        let rows: Vec<&str> = text.lines().skip(1).collect();
+        assert_eq!(rows.len(), 2);
+        assert!(rows[0].contains("학교에 가요."));
+        assert!(rows[1].contains("매일 가요."));
+    }
+    
+    #[tokio::test]
+    async fn test_without_headers_starts_with_data() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("cards.tsv");
+        
+        TsvExporter::new()
+            .with_headers(false)
+            .export_to(&[card("학교에 가요.")], &path)
+            .await
+            .unwrap();
+        
+        let contents = std::fs::read_to_string(&path).unwrap();
+        let first_line = contents.lines().next().unwrap();
+        assert!(first_line.starts_with("1\t학교\t"));
+    }
+    
+    #[tokio::test]
+    async fn test_anki_preset_routes_cloze_cards() {
+        use crate::anki::{AnkiNoteType, AnkiPreset};
+        
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("cards.tsv");
+        let standard = card("학교에 가요.");
+        let mut cloze = card("학교에 가요.");
+        cloze.2.card_type = CardType::Cloze;
+        cloze.2.front.thematic_tags = vec!["daily life".to_string()];
+        
+        TsvExporter::new()
+            .with_anki_preset(AnkiPreset::new(AnkiNoteType::Korean).with_deck("Korean::Places"))
+            .export_to(&[standard, cloze], &path)
+            .await
+            .unwrap();
+        
+        let contents = std::fs::read_to_string(&path).unwrap();
+        let lines: Vec<&str> = contents.lines().collect();
+        assert_eq!(&lines[..5], &[
+            "#separator:tab",
+            "#html:true",
+            "#notetype column:1",
+            "#deck:Korean::Places",
+            "#tags column:10",
+        ]);
+        
+        let korean: Vec<&str> = lines[5].split('\t').collect();
+        assert_eq!(korean.len(), 10);
+        assert_eq!(korean[0], "Korean Vocabulary");
+        assert_eq!(korean[1], "학교");
+        
+        let cloze: Vec<&str> = lines[6].split('\t').collect();
+        assert_eq!(cloze.len(), 10);
+        assert_eq!(cloze[0], "Cloze");
+        assert_eq!(cloze[1], "{{c1::학교}}에 가요.");
+        assert_eq!(cloze[9], "daily_life");
+    }
+    
+    #[tokio::test]
+    async fn test_run_tags_merge_with_generated_tags() {
+        use crate::anki::{AnkiNoteType, AnkiPreset};
+        
+        let cards: Vec<_> = ["/data/greetings.csv", "food.csv"].iter()
+            .map(|source| {
+                let (mut item, stage1, mut stage2) = card("학교에 가요.");
+                item.source = source.to_string();
+                stage2.front.thematic_tags = vec!["daily life".to_string(), "korean".to_string()];
+                stage2.front.grammatical_tags = vec!["noun".to_string(), "korean".to_string()];
+                (item, stage1, stage2)
+            })
+            .collect();
+        let date = chrono::NaiveDate::from_ymd_opt(2026, 10, 16).unwrap();
+        let exporter = || TsvExporter::new()
+            .with_headers(false)
+            .with_extra_tags(vec!["korean".to_string(), "spring term".to_string()])
+            .with_batch_tags(BatchId::new("7"), Some("class-a".to_string()), date);
+        
+        let mut memory = Vec::new();
+        exporter().export(&cards, &mut memory).await.unwrap();
+        let contents = String::from_utf8(memory).unwrap();
+        let tags: Vec<&str> = contents.lines()
+            .map(|line| line.split('\t').nth(15).unwrap())
+            .collect();
+        assert_eq!(tags, [
+            "daily life, korean, noun, spring term, batch::7, label::class-a, date::2026-10-16, source::greetings.csv",
+            "daily life, korean, noun, spring term, batch::7, label::class-a, date::2026-10-16, source::food.csv",
+        ]);
+        
+        let mut memory = Vec::new();
+        exporter()
+            .with_anki_preset(AnkiPreset::new(AnkiNoteType::Basic))
+            .export(&cards[..1], &mut memory)
+            .await
+            .unwrap();
+        let contents = String::from_utf8(memory).unwrap();
+        let row = contents.lines().last().unwrap();
+        assert_eq!(
+            row.rsplit('\t').next().unwrap(),
+            "daily_life korean noun spring_term batch::7 label::class-a date::2026-10-16 source::greetings.csv"
+        );
+        
+        let mut with_mnemonics = cards.clone();
+        for (_, _, stage2) in &mut with_mnemonics {
+            stage2.front.mnemonic_aid = Some("Hak-gyo: hack your way to school".to_string());
+        }
+        let mut memory = Vec::new();
+        MnemonicExporter::new()
+            .with_headers(false)
+            .with_extra_tags(vec!["korean".to_string(), "spring term".to_string()])
+            .with_batch_tags(BatchId::new("7"), Some("class-a".to_string()), date)
+            .export(&with_mnemonics, &mut memory)
+            .await
+            .unwrap();
+        let contents = String::from_utf8(memory).unwrap();
+        let tags: Vec<&str> = contents.lines()
+            .map(|line| line.rsplit('\t').next().unwrap())
+            .collect();
+        assert_eq!(tags[1], "daily life, korean, noun, spring term, batch::7, label::class-a, date::2026-10-16, source::food.csv");
+    }
+    
+    #[tokio::test]
+    async fn test_anki_sanitizing() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("cards.tsv");
+        
+        TsvExporter::new()
+            .quote_all()
+            .with_newline_replacement("<br>")
+            .with_control_chars(ControlCharPolicy::Replace(" ".to_string()))
+            .export_to(&[card("학교에\t가요.\r\n매일 가요.")], &path)
+            .await
+            .unwrap();
+        
+        let records = read_back(&path);
+        assert_eq!(records[0].len(), HEADERS.len());
+        assert_eq!(&records[0][6], "학교에 가요.<br>매일 가요.");
+    }
+    
+    #[test]
+    fn test_truncation_keeps_grapheme_clusters_whole() {
+        // Precomposed and decomposed Hangul, a combining accent, and emoji
+        // sequences of several code points each: seven clusters per repeat
+        let text = "학교\u{1112}\u{1161}\u{11AB}e\u{301}👩\u{200D}👩\u{200D}👧🇰🇷👍🏽".repeat(20);
+        let graphemes: Vec<&str> = text.graphemes(true).collect();
+        assert_eq!(graphemes.len(), 140);
+        
+        let truncated = truncate_graphemes(&text, 10).unwrap();
+        let kept: Vec<&str> = truncated.graphemes(true).collect();
+        assert_eq!(kept.len(), 10);
+        assert_eq!(&kept[..9], &graphemes[..9]);
+        assert_eq!(kept[9], "…");
+        
+        // Every cut lands on a cluster boundary
+        for max in 1..graphemes.len() {
+            let truncated = truncate_graphemes(&text, max).unwrap();
+            let prefix = truncated.strip_suffix('…').unwrap();
+            assert_eq!(prefix, graphemes[..max - 1].concat());
+        }
+        assert_eq!(truncate_graphemes(&text, 140), None);
+    }
+    
+    #[tokio::test]
+    async fn test_field_limits_truncate_and_count() {
+        let (item, stage1, mut stage2) = card(&"가".repeat(100));
+        stage2.front.cultural_notes = Some("설날에는 떡국을 먹어요.".to_string());
+        let cards = [(item, stage1, stage2)];
+        
+        let mut memory = Vec::new();
+        let stats = TsvExporter::new()
+            .with_headers(false)
+            .with_max_field_chars(Some(20))
+            .with_field_char_limit("cultural_notes", 5)
+            .export(&cards, &mut memory)
+            .await
+            .unwrap();
+        
+        let contents = String::from_utf8(memory).unwrap();
+        let row: Vec<&str> = contents.trim_end().split('\t').collect();
+        assert_eq!(row[6], format!("{}…", "가".repeat(19)));
+        assert_eq!(row[16], "Cultural: 설날에는…");
+        assert_eq!(stats.fields_truncated, 2);
+        assert!(stats.summary().contains("Fields truncated: 2"));
+    }
+    
+    #[tokio::test]
+    async fn test_comparison_columns_render_homonyms() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("cards.tsv");
+        
+        let (item, mut stage1, stage2) = card("학교에 가요.");
+        stage1.homonyms = vec![
+            Homonym {
+                hanja: "學校".to_string(),
+                reading: "학교".to_string(),
+                meaning: "school".to_string(),
+                differentiator: "education".to_string(),
+            },
+            Homonym {
+                hanja: String::new(),
+                reading: "학교".to_string(),
+                meaning: "crane bridge".to_string(),
+                differentiator: "rare".to_string(),
+            },
+        ];
+        stage1.comparison.similar_to = vec!["학원".to_string(), "대학".to_string()];
+        
+        TsvExporter::new()
+            .with_comparison_columns(true)
+            .export_to(&[(item, stage1, stage2)], &path)
+            .await
+            .unwrap();
+        
+        let mut reader = csv::ReaderBuilder::new().delimiter(b'\t').from_path(&path).unwrap();
+        let headers = reader.headers().unwrap().clone();
+        assert_eq!(headers.len(), HEADERS.len() + COMPARISON_HEADERS.len());
+        assert_eq!(&headers[HEADERS.len()], "Homonyms");
+        
+        let records = read_back(&path);
+        let extra: Vec<&str> = records[0].iter().skip(HEADERS.len()).collect();
+        assert_eq!(extra, ["학교 (學校): school; 학교: crane bridge", "학원, 대학", "", ""]);
+    }
+    
+    #[tokio::test]
+    async fn test_memory_palace_sentence() {
+        let (item, mut stage1, stage2) = card("학교에 가요.");
+        stage1.suggested_location = "school gate".to_string();
+        stage1.anchor_object = "brass bell".to_string();
+        stage1.anchor_sensory = "ringing loudly".to_string();
+        stage1.metaphor = "a hacker sneaking into class".to_string();
+        stage1.metaphor_noun = "hacker".to_string();
+        stage1.metaphor_action = "sneaking".to_string();
+        
+        assert_eq!(
+            memory_palace_sentence(&stage1),
+            "In the school gate, the brass bell (ringing loudly) — a hacker sneaking into class"
+        );
+        
+        // Missing anchor fields drop out without leaving stray punctuation
+        let mut no_anchor = stage1.clone();
+        no_anchor.anchor_object = String::new();
+        no_anchor.anchor_sensory = "  ".to_string();
+        assert_eq!(memory_palace_sentence(&no_anchor), "In the school gate — a hacker sneaking into class");
+        let mut metaphor_only = no_anchor.clone();
+        metaphor_only.suggested_location = String::new();
+        assert_eq!(memory_palace_sentence(&metaphor_only), "a hacker sneaking into class");
+        metaphor_only.metaphor = String::new();
+        assert_eq!(memory_palace_sentence(&metaphor_only), "");
+        
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("cards.tsv");
+        TsvExporter::new()
+            .with_memory_palace(Some(MemoryPalaceColumns::Combined))
+            .export_to(&[(item, stage1, stage2)], &path)
+            .await
+            .unwrap();
+        
+        let mut reader = csv::ReaderBuilder::new().delimiter(b'\t').from_path(&path).unwrap();
+        let headers = reader.headers().unwrap().clone();
+        assert_eq!(headers.len(), HEADERS.len() + 1);
+        assert_eq!(&headers[HEADERS.len()], "Memory Palace");
+        let records = read_back(&path);
+        assert_eq!(
+            &records[0][HEADERS.len()],
+            "In the school gate, the brass bell (ringing loudly) — a hacker sneaking into class"
+        );
+    }
+    
+    #[tokio::test]
+    async fn test_mnemonic_export_skips_cards_without_one() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("mnemonics.tsv");
+        
+        let (item, mut stage1, mut stage2) = card("학교에 가요.");
+        stage1.metaphor = "a bell ringing at the gate".to_string();
+        stage2.back.mnemonic_aid = Some("Hak-gyo: hack your way to school".to_string());
+        let without = card("학교에 가요.");
+        let (blank_item, blank_stage1, mut blank) = card("학교에 가요.");
+        blank.front.mnemonic_aid = Some("  ".to_string());
+        
+        let stats = MnemonicExporter::new()
+            .export_to(&[(item, stage1, stage2), without, (blank_item, blank_stage1, blank)], &path)
+            .await
+            .unwrap();
+        
+        assert_eq!(stats.cards_exported, 1);
+        assert_eq!(stats.cards_skipped_no_mnemonic, 2);
+        assert!(stats.summary().contains("Skipped without a mnemonic: 2"));
+        
+        let records = read_back(&path);
+        assert_eq!(records.len(), 1);
+        assert_eq!(
+            records[0].iter().collect::<Vec<_>>(),
+            ["학교", "학교", "Hak-gyo: hack your way to school", "a bell ringing at the gate", "", "", ""]
+        );
+    }
+    
+    #[tokio::test]
+    async fn test_card_states_tag_json_and_mnemonic_exports() {
+        let dir = tempfile::tempdir().unwrap();
+        let (item, stage1, mut stage2) = card("학교에 가요.");
+        stage2.front.mnemonic_aid = Some("Hak-gyo: hack your way to school".to_string());
+        let results = [(item, stage1, stage2)];
+        // Stored decomposed, e.g. by an older version, and still matched
+        let states = || vec![CardState {
+            term: "\u{1112}\u{1161}\u{11A8}\u{1100}\u{116D}".to_string(),
+            suspended: true,
+            leech: false,
+            updated_at: chrono::Utc::now(),
+        }];
+        
+        let path = dir.path().join("mnemonics.tsv");
+        MnemonicExporter::new().with_card_states(states()).export_to(&results, &path).await.unwrap();
+        let records = read_back(&path);
+        assert_eq!(records[0].get(MNEMONIC_HEADERS.len() - 1), Some(SUSPENDED_TAG));
+        
+        let path = dir.path().join("cards.json");
+        JsonExporter::new().with_card_states(states()).export_to(&results, &path).await.unwrap();
+        let cards: Vec<(VocabularyItem, Stage1Result, Stage2Result)> =
+            serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(cards[0].2.front.thematic_tags, [SUSPENDED_TAG]);
+    }
+    
+    #[test]
+    fn test_error_report_lists_failures_with_retry_counts() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("reports").join("cards.errors.csv");
+        let failure = |term: &str, position: i32, category: &str, retry_count: u32| FailureRecord {
+            term: term.to_string(),
+            position,
+            stage: crate::batch_processor::FailureStage::Stage1,
+            category: category.to_string(),
+            message: format!("{} failed", term),
+            retry_count,
+        };
+        
+        let by_category = write_error_report(&[
+            failure("학교", 1, "api", 2),
+            failure("바다", 2, "api", 3),
+            failure("하늘", 3, "validation", 0),
+        ], &path).unwrap();
+        
+        assert_eq!(by_category, BTreeMap::from([("api".to_string(), 2), ("validation".to_string(), 1)]));
+        let mut reader = csv::Reader::from_path(&path).unwrap();
+        assert_eq!(
+            reader.headers().unwrap().iter().collect::<Vec<_>>(),
+            ["term", "position", "stage", "category", "message", "retry_count"]
+        );
+        let rows: Vec<FailureRecord> = reader.deserialize().collect::<std::result::Result<_, _>>().unwrap();
+        let written: Vec<(&str, i32, u32)> = rows.iter()
+            .map(|row| (row.term.as_str(), row.position, row.retry_count))
+            .collect();
+        assert_eq!(written, vec![("학교", 1, 2), ("바다", 2, 3), ("하늘", 3, 0)]);
+        assert_eq!(rows[0].message, "학교 failed");
+        
+        // Nothing failed, nothing written
+        let empty = dir.path().join("none.errors.csv");
+        assert!(write_error_report(&[], &empty).unwrap().is_empty());
+        assert!(!empty.exists());
+    }
+}
@@ -0,0 +1,91 @@
+use flashcard_core::models::{
+    CardType, FlashcardContent, Stage1Result, Stage2Result, VocabularyItem,
+};
+
+/// Tag carried by every card built without Stage 2, so they can be found
+/// and regenerated later
+pub const FALLBACK_TAG: &str = "stage1-fallback";
+
+/// Build a basic card from Stage 1 alone, for items whose Stage 2 failed or
+/// was skipped (e.g. an offline run where only Stage 1 is cached).
+///
+/// The mapping is deterministic: the term, IPA and part of speech go on the
+/// front along with the metaphor as the mnemonic; the meanings go on the back.
+/// Fields Stage 1 doesn't produce, such as example sentences, are left empty,
+/// and the difficulty is the item's own, estimated when it was loaded if the
+/// input had no label.
+pub fn build_flashcard_from_stage1(item: &VocabularyItem, stage1: &Stage1Result) -> Stage2Result {
+    let difficulty_level = item.difficulty_level.clone();
+    let mut thematic_tags = stage1.korean_keywords.clone();
+    thematic_tags.push(FALLBACK_TAG.to_string());
+    
+    let front = FlashcardContent {
+        secondary_field: non_empty(&stage1.pos),
+        pronunciation_guide: non_empty(&stage1.ipa),
+        mnemonic_aid: non_empty(&stage1.metaphor),
+        usage_notes: stage1.usage_context.as_deref().and_then(non_empty),
+        thematic_tags,
+        grammatical_tags: non_empty(&stage1.pos).into_iter().collect(),
+        difficulty_level: difficulty_level.clone(),
+        ..FlashcardContent::new(item.term.clone())
+    };
+    
+    let back = FlashcardContent {
+        secondary_field: non_empty(&stage1.other_meanings),
+        tertiary_field: non_empty(&stage1.explanation),
+        difficulty_level,
+        ..FlashcardContent::new(stage1.primary_meaning.clone())
+    };
+    
+    Stage2Result {
+        front,
+        back,
+        card_type: CardType::Standard,
+        learning_order: None,
+        related_cards: vec![],
+    }
+}
+
+fn non_empty(value: &str) -> Option<String> {
+    let value = value.trim();
+    (!value.is_empty()).then(|| value.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::python_bridge::{ApiClient, MockApiClient};
+    use flashcard_core::models::DifficultyLevel;
+    
+    #[tokio::test]
+    async fn test_fallback_card_maps_stage1_fields() {
+        let item = VocabularyItem {
+            id: None,
+            position: 7,
+            term: "사과".to_string(),
+            word_type: Some("noun".to_string()),
+            difficulty_level: DifficultyLevel::Beginner,
+            source: String::new(),
+            created_at: chrono::Utc::now(),
+            updated_at: chrono::Utc::now(),
+        };
+        let mut stage1 = MockApiClient.process_stage1(&item).await.unwrap();
+        stage1.other_meanings = "  ".to_string();
+        
+        let card = build_flashcard_from_stage1(&item, &stage1);
+        
+        assert_eq!(card.front.primary_field, "사과");
+        assert_eq!(card.front.pronunciation_guide.as_deref(), Some("[mock-ipa]"));
+        assert_eq!(card.front.mnemonic_aid.as_deref(), Some("Mock metaphor"));
+        assert_eq!(card.front.difficulty_level, DifficultyLevel::Beginner);
+        assert!(card.front.thematic_tags.contains(&FALLBACK_TAG.to_string()));
+        assert_eq!(card.back.primary_field, "Mock primary meaning");
+        assert_eq!(card.back.secondary_field, None);
+        assert_eq!(card.back.example_sentence, None);
+        
+        // Same input, same card
+        let again = build_flashcard_from_stage1(&item, &stage1);
+        assert_eq!(again.front.primary_field, card.front.primary_field);
+        assert_eq!(again.back.tertiary_field, card.back.tertiary_field);
+    }
+}
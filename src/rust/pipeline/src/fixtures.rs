@@ -0,0 +1,85 @@
+//! Cards and pipelines for tests to build on.
+
+use crate::pipeline::{Pipeline, PipelineConfig};
+use crate::python_bridge::{ApiClient, MockApiClient};
+use flashcard_core::models::{
+    CardType, DifficultyLevel, FlashcardContent, FrequencyLevel, Stage2Result, VocabularyItem,
+};
+use std::path::Path;
+use std::sync::Arc;
+use tempfile::TempDir;
+
+/// A face with only `primary_field` filled in
+pub fn content(primary_field: &str) -> FlashcardContent {
+    FlashcardContent {
+        primary_field: primary_field.to_string(),
+        secondary_field: None,
+        tertiary_field: None,
+        example_sentence: None,
+        example_translation: None,
+        pronunciation_guide: None,
+        image_prompt: None,
+        mnemonic_aid: None,
+        grammar_notes: None,
+        cultural_notes: None,
+        usage_notes: None,
+        difficulty_level: DifficultyLevel::Beginner,
+        frequency_level: FrequencyLevel::Common,
+        thematic_tags: vec![],
+        grammatical_tags: vec![],
+        style_register: None,
+    }
+}
+
+/// An unsaved item for `term` at `position`
+pub fn item(position: i32, term: &str) -> VocabularyItem {
+    VocabularyItem {
+        id: None,
+        position,
+        term: term.to_string(),
+        word_type: None,
+        source: String::new(),
+        created_at: chrono::Utc::now(),
+        updated_at: chrono::Utc::now(),
+    }
+}
+
+/// A standard card with `front` and `back` as its primary fields, for the
+/// item `front` at `position`
+pub fn card(position: i32, front: &str, back: &str) -> (VocabularyItem, Stage2Result) {
+    let stage2 = Stage2Result {
+        front: content(front),
+        back: content(back),
+        card_type: CardType::Standard,
+        learning_order: None,
+        related_cards: vec![],
+    };
+    (item(position, front), stage2)
+}
+
+/// The defaults with the database in `dir` and metrics off
+pub fn test_config(dir: &Path) -> PipelineConfig {
+    PipelineConfig {
+        database_url: format!("sqlite:{}", dir.join("pipeline.db").display()),
+        enable_metrics: false,
+        ..Default::default()
+    }
+}
+
+/// A pipeline answering like the mock client, on a database of its own,
+/// configured by `configure` from [`test_config`]. The database lasts as
+/// long as the returned directory.
+pub async fn test_pipeline(configure: impl FnOnce(PipelineConfig) -> PipelineConfig) -> (TempDir, Pipeline) {
+    test_pipeline_with(Arc::new(MockApiClient), configure).await
+}
+
+/// [`test_pipeline`], calling `client` instead of the mock
+pub async fn test_pipeline_with(
+    client: Arc<dyn ApiClient>,
+    configure: impl FnOnce(PipelineConfig) -> PipelineConfig,
+) -> (TempDir, Pipeline) {
+    let dir = tempfile::tempdir().unwrap();
+    let config = configure(test_config(dir.path()));
+    let pipeline = Pipeline::with_api_client(config, client).await.unwrap();
+    (dir, pipeline)
+}
@@ -0,0 +1,549 @@
+//! Reading vocabulary input files.
+
+use crate::errors::{PipelineError, Result};
+use flashcard_core::models::{KeyNormalization, VocabularyItem};
+use csv::{ReaderBuilder, StringRecord};
+use encoding_rs::Encoding;
+use serde::{Deserialize, Serialize};
+use std::collections::hash_map::{Entry, HashMap};
+use std::path::{Path, PathBuf};
+use tracing::{debug, error};
+
+/// Decode raw file contents to UTF-8 text.
+///
+/// A byte order mark selects the encoding (UTF-8, UTF-16LE or UTF-16BE) and
+/// is stripped; without one the input must already be UTF-8. Spreadsheet
+/// exports commonly carry a BOM, which would otherwise end up in the first
+/// field.
+pub fn decode_input(bytes: &[u8]) -> Result<String> {
+    match Encoding::for_bom(bytes) {
+        Some((encoding, bom_length)) => {
+            debug!("Detected {} byte order mark", encoding.name());
+            encoding
+                .decode_without_bom_handling_and_without_replacement(&bytes[bom_length..])
+                .map(|text| text.into_owned())
+                .ok_or_else(|| PipelineError::InvalidFormat(
+                    format!("Input is not valid {} despite its byte order mark", encoding.name())
+                ))
+        }
+        None => String::from_utf8(bytes.to_vec()).map_err(|e| PipelineError::InvalidFormat(
+            format!("Input is not valid UTF-8 (no byte order mark, assumed UTF-8): {}", e)
+        )),
+    }
+}
+
+/// Comment marker used unless the caller picks another
+pub const DEFAULT_COMMENT_CHAR: u8 = b'#';
+
+/// How vocabulary input files are laid out
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum InputFormat {
+    /// `position,term,type` rows under a header
+    #[default]
+    Csv,
+    /// One JSON object per line with `term` and optional `position` and
+    /// `word_type` (or `type`)
+    Jsonl,
+}
+
+impl InputFormat {
+    /// Load `path` in this format. `comment` only applies to CSV.
+    pub fn read(self, path: &Path, comment: Option<u8>) -> Result<Vec<VocabularyItem>> {
+        match self {
+            InputFormat::Csv => read_vocabulary_csv(path, comment),
+            InputFormat::Jsonl => read_vocabulary_jsonl(path),
+        }
+    }
+}
+
+/// Load vocabulary items from a `position,term,type` CSV file.
+///
+/// Lines starting with `comment` (e.g. `#`) and records whose fields are all
+/// blank are skipped, and don't count toward the positions assigned to rows
+/// that leave `position` empty. The first row [`validate_vocabulary_csv`]
+/// would report as invalid fails the whole file.
+pub fn read_vocabulary_csv(path: &Path, comment: Option<u8>) -> Result<Vec<VocabularyItem>> {
+    let bytes = std::fs::read(path)
+        .map_err(|_| PipelineError::FileNotFound(path.to_path_buf()))?;
+    let text = decode_input(&bytes)?;
+    
+    // Flexible, so a bare "," separator line doesn't fail on its field count
+    let mut reader = ReaderBuilder::new()
+        .has_headers(true)
+        .flexible(true)
+        .comment(comment)
+        .from_reader(text.as_bytes());
+    
+    let mut items = Vec::new();
+    
+    for result in reader.records() {
+        let record = result?;
+        
+        if record.iter().all(|field| field.trim().is_empty()) {
+            continue;
+        }
+        let line = record.position().map_or(0, |position| position.line());
+        if let Some(reason) = row_problem(&record) {
+            return Err(PipelineError::InvalidFormat(format!("Invalid row at line {}: {}", line, reason)));
+        }
+        
+        // Expected format: position,term,type (optional)
+        let position: i32 = record.get(0)
+            .and_then(|s| s.trim().parse().ok())
+            .unwrap_or((items.len() + 1) as i32);
+            
+        let term = record.get(1).unwrap_or_default().to_string();
+        let word_type = record.get(2).map(|s| s.to_string());
+        
+        items.push(VocabularyItem {
+            id: None,
+            position,
+            term,
+            word_type,
+            source: path.display().to_string(),
+            created_at: chrono::Utc::now(),
+            updated_at: chrono::Utc::now(),
+        });
+    }
+    
+    if items.is_empty() {
+        return Err(PipelineError::InvalidFormat(
+            "CSV file contains no valid vocabulary items".to_string()
+        ));
+    }
+    
+    Ok(items)
+}
+
+/// A row [`validate_vocabulary_csv`] found unloadable
+#[derive(Debug, Clone, Serialize)]
+pub struct InvalidRow {
+    pub line: u64,
+    pub reason: String,
+}
+
+/// A row whose term an earlier row already has
+#[derive(Debug, Clone, Serialize)]
+pub struct DuplicateRow {
+    pub line: u64,
+    pub term: String,
+    /// Line of the row that has the term first
+    pub first_line: u64,
+}
+
+/// What [`validate_vocabulary_csv`] found in an input file
+#[derive(Debug, Default, Serialize)]
+pub struct ValidationReport {
+    /// Records checked, not counting comments and blank lines
+    pub total_rows: usize,
+    pub valid: usize,
+    pub invalid: Vec<InvalidRow>,
+    /// Valid rows repeating an earlier term. They load, but only the first
+    /// costs an API call; the rest are served from its cache entry
+    pub duplicates: Vec<DuplicateRow>,
+}
+
+impl ValidationReport {
+    /// Whether the file would load: every row is valid, and there is at
+    /// least one
+    pub fn is_valid(&self) -> bool {
+        self.invalid.is_empty() && self.valid > 0
+    }
+}
+
+/// Check every row of a `position,term,type` CSV without loading it, so a
+/// long run doesn't start on a broken file.
+///
+/// Rows are checked as [`read_vocabulary_csv`] checks them, but instead of
+/// stopping at the first problem every one is collected: records the CSV
+/// parser rejects, positions that aren't whole numbers, blank terms and
+/// terms with control or replacement characters. Terms that are the same
+/// once normalized by `key_normalization` count as duplicates, as they
+/// share a cache entry.
+pub fn validate_vocabulary_csv(
+    path: &Path,
+    comment: Option<u8>,
+    key_normalization: KeyNormalization,
+) -> Result<ValidationReport> {
+    let bytes = std::fs::read(path)
+        .map_err(|_| PipelineError::FileNotFound(path.to_path_buf()))?;
+    let text = decode_input(&bytes)?;
+    
+    let mut reader = ReaderBuilder::new()
+        .has_headers(true)
+        .flexible(true)
+        .comment(comment)
+        .from_reader(text.as_bytes());
+    
+    let mut report = ValidationReport::default();
+    let mut first_lines = HashMap::new();
+    
+    for result in reader.records() {
+        let record = match result {
+            Ok(record) => record,
+            Err(e) => {
+                report.total_rows += 1;
+                report.invalid.push(InvalidRow {
+                    line: e.position().map_or(0, |position| position.line()),
+                    reason: e.to_string(),
+                });
+                continue;
+            }
+        };
+        
+        if record.iter().all(|field| field.trim().is_empty()) {
+            continue;
+        }
+        report.total_rows += 1;
+        let line = record.position().map_or(0, |position| position.line());
+        
+        if let Some(reason) = row_problem(&record) {
+            report.invalid.push(InvalidRow { line, reason });
+            continue;
+        }
+        report.valid += 1;
+        
+        let term = record.get(1).unwrap_or_default();
+        match first_lines.entry(key_normalization.apply(term).into_owned()) {
+            Entry::Occupied(first) => report.duplicates.push(DuplicateRow {
+                line,
+                term: term.to_string(),
+                first_line: *first.get(),
+            }),
+            Entry::Vacant(entry) => {
+                entry.insert(line);
+            }
+        }
+    }
+    
+    Ok(report)
+}
+
+/// Why `record` can't be loaded as a vocabulary item, if it can't
+fn row_problem(record: &StringRecord) -> Option<String> {
+    let position = record.get(0).unwrap_or_default().trim();
+    if !position.is_empty() && position.parse::<i32>().is_err() {
+        return Some(format!("position {:?} is not a whole number", position));
+    }
+    
+    let term = record.get(1).unwrap_or_default().trim();
+    if term.is_empty() {
+        return Some("missing term".to_string());
+    }
+    // Left by a bad encoding conversion, or pasted in from elsewhere
+    if let Some(c) = term.chars().find(|c| c.is_control() || *c == char::REPLACEMENT_CHARACTER) {
+        return Some(format!("term contains invalid character U+{:04X}", u32::from(c)));
+    }
+    None
+}
+
+/// One line of a JSONL input. Other keys, such as the `id` and timestamps of
+/// a serialized [`VocabularyItem`], are ignored.
+#[derive(Deserialize)]
+struct JsonlRecord {
+    position: Option<i32>,
+    term: String,
+    #[serde(alias = "type")]
+    word_type: Option<String>,
+}
+
+/// Load vocabulary items from a file with one JSON object per line.
+///
+/// Blank lines are skipped, and records without a `position` are numbered
+/// like CSV rows that leave it empty. A malformed line fails the whole file
+/// with its line number.
+pub fn read_vocabulary_jsonl(path: &Path) -> Result<Vec<VocabularyItem>> {
+    let bytes = std::fs::read(path)
+        .map_err(|_| PipelineError::FileNotFound(path.to_path_buf()))?;
+    let text = decode_input(&bytes)?;
+    
+    let mut items = Vec::new();
+    
+    for (index, line) in text.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        
+        let record: JsonlRecord = serde_json::from_str(line).map_err(|e| {
+            // Each line is parsed alone, so serde_json's own "at line 1"
+            // suffix would only mislead; report the file's line instead
+            let message = e.to_string();
+            let message = message.split(" at line ").next().unwrap_or(&message);
+            PipelineError::InvalidFormat(format!(
+                "Invalid JSON record at line {}, column {}: {}",
+                index + 1, e.column(), message
+            ))
+        })?;
+        
+        items.push(VocabularyItem {
+            id: None,
+            position: record.position.unwrap_or((items.len() + 1) as i32),
+            term: record.term,
+            word_type: record.word_type,
+            source: path.display().to_string(),
+            created_at: chrono::Utc::now(),
+            updated_at: chrono::Utc::now(),
+        });
+    }
+    
+    if items.is_empty() {
+        return Err(PipelineError::InvalidFormat(
+            "JSONL file contains no valid vocabulary items".to_string()
+        ));
+    }
+    
+    Ok(items)
+}
+
+/// How one of several input files fared when they were merged
+#[derive(Debug, Clone, Serialize)]
+pub struct InputFileSummary {
+    pub path: PathBuf,
+    pub items: usize,
+    /// Why the file contributed nothing, e.g. it doesn't exist
+    pub error: Option<String>,
+}
+
+/// Items from several input files, merged into one batch.
+#[derive(Debug, Default)]
+pub struct MergedInput {
+    pub items: Vec<VocabularyItem>,
+    /// One entry per input path, in the order given
+    pub files: Vec<InputFileSummary>,
+}
+
+/// Load and concatenate several vocabulary CSVs; see [`read_vocabulary_files`].
+pub fn read_vocabulary_csvs(paths: &[PathBuf], comment: Option<u8>) -> Result<MergedInput> {
+    read_vocabulary_files(paths, InputFormat::Csv, comment)
+}
+
+/// Load and concatenate several vocabulary files of the same format.
+///
+/// Each file's positions are offset by the highest position before it, so
+/// positions stay unique across files and files numbered from 1 merge into
+/// one contiguous run. Every item's `source` names its file.
+///
+/// A file that can't be read is logged and recorded in
+/// [`MergedInput::files`] while the others are still loaded; only when
+/// nothing loads is its error returned.
+pub fn read_vocabulary_files(
+    paths: &[PathBuf],
+    format: InputFormat,
+    comment: Option<u8>,
+) -> Result<MergedInput> {
+    let mut merged = MergedInput::default();
+    let mut first_error = None;
+    let mut offset = 0;
+    
+    for path in paths {
+        match format.read(path, comment) {
+            Ok(mut items) => {
+                for item in &mut items {
+                    item.position += offset;
+                }
+                offset = items.iter().map(|item| item.position).max().unwrap_or(offset);
+                
+                merged.files.push(InputFileSummary {
+                    path: path.clone(),
+                    items: items.len(),
+                    error: None,
+                });
+                merged.items.extend(items);
+            }
+            Err(e) => {
+                error!("Skipping input {}: {}", path.display(), e);
+                merged.files.push(InputFileSummary {
+                    path: path.clone(),
+                    items: 0,
+                    error: Some(e.to_string()),
+                });
+                first_error.get_or_insert(e);
+            }
+        }
+    }
+    
+    match first_error {
+        Some(e) if merged.items.is_empty() => Err(e),
+        _ => Ok(merged),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_utf8_bom_is_stripped() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("vocab.csv");
+        std::fs::write(&path, "\u{feff}position,term,type\n1,한국,noun\n").unwrap();
+        
+        let items = read_vocabulary_csv(&path, Some(DEFAULT_COMMENT_CHAR)).unwrap();
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0].term, "한국");
+    }
+    
+    #[test]
+    fn test_comments_and_blank_lines_are_skipped() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("vocab.csv");
+        std::fs::write(&path, concat!(
+            "position,term,type\n",
+            "# Week 1: greetings\n",
+            ",안녕하세요,phrase\n",
+            "\n",
+            ",,\n",
+            "# Week 2: food\n",
+            ",사과,noun\n",
+            "   \n",
+            ",밥,noun\n",
+        )).unwrap();
+        
+        let items = read_vocabulary_csv(&path, Some(DEFAULT_COMMENT_CHAR)).unwrap();
+        let loaded: Vec<(i32, &str)> = items.iter()
+            .map(|item| (item.position, item.term.as_str()))
+            .collect();
+        assert_eq!(loaded, vec![(1, "안녕하세요"), (2, "사과"), (3, "밥")]);
+        
+        // With comments off, a marker line is a record with no term
+        let err = read_vocabulary_csv(&path, None).unwrap_err();
+        assert!(matches!(&err, PipelineError::InvalidFormat(msg) if msg.contains("line 2")));
+    }
+    
+    #[test]
+    fn test_validation_reports_invalid_and_duplicate_rows() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("vocab.csv");
+        std::fs::write(&path, concat!(
+            "position,term,type\n",
+            "1,학교,noun\n",
+            "# Week 2\n",
+            "2,바다,noun\n",
+            "3,,noun\n",
+            "4, 학교 ,noun\n",
+            "five,사과,noun\n",
+            "6,밥,noun\n",
+        )).unwrap();
+        
+        let report = validate_vocabulary_csv(&path, Some(DEFAULT_COMMENT_CHAR), KeyNormalization::default()).unwrap();
+        
+        assert_eq!(report.total_rows, 6);
+        assert_eq!(report.valid, 4);
+        assert!(!report.is_valid());
+        let invalid: Vec<(u64, &str)> = report.invalid.iter()
+            .map(|row| (row.line, row.reason.as_str()))
+            .collect();
+        assert_eq!(invalid, vec![
+            (5, "missing term"),
+            (7, "position \"five\" is not a whole number"),
+        ]);
+        assert_eq!(report.duplicates.len(), 1);
+        assert_eq!((report.duplicates[0].line, report.duplicates[0].first_line), (6, 2));
+        
+        // Byte-exact keys keep " 학교 " apart from "학교"
+        let report = validate_vocabulary_csv(&path, Some(DEFAULT_COMMENT_CHAR), KeyNormalization::None).unwrap();
+        assert!(report.duplicates.is_empty());
+        
+        // Every problem the report lists also stops the loader
+        let err = read_vocabulary_csv(&path, Some(DEFAULT_COMMENT_CHAR)).unwrap_err();
+        assert!(matches!(&err, PipelineError::InvalidFormat(msg) if msg.contains("line 5: missing term")));
+        
+        // Control characters, e.g. from a bad paste, make a row invalid too
+        std::fs::write(&path, "position,term,type\n1,학교,noun\n2,사\u{7}과,noun\n").unwrap();
+        let report = validate_vocabulary_csv(&path, None, KeyNormalization::default()).unwrap();
+        assert_eq!(report.invalid[0].reason, "term contains invalid character U+0007");
+        assert!(report.duplicates.is_empty());
+        
+        // A header alone has nothing to load
+        std::fs::write(&path, "position,term,type\n").unwrap();
+        let report = validate_vocabulary_csv(&path, None, KeyNormalization::default()).unwrap();
+        assert!(report.invalid.is_empty());
+        assert!(!report.is_valid());
+        assert!(read_vocabulary_csv(&path, None).is_err());
+    }
+    
+    #[test]
+    fn test_utf16le_is_transcoded() {
+        let mut bytes = vec![0xFF, 0xFE];
+        for unit in "position,term\n1,한국\n".encode_utf16() {
+            bytes.extend_from_slice(&unit.to_le_bytes());
+        }
+        
+        assert_eq!(decode_input(&bytes).unwrap(), "position,term\n1,한국\n");
+    }
+    
+    #[test]
+    fn test_invalid_utf8_names_encoding() {
+        let err = decode_input(&[b'a', 0xC3, 0x28]).unwrap_err();
+        assert!(matches!(&err, PipelineError::InvalidFormat(msg) if msg.contains("UTF-8")));
+    }
+    
+    #[test]
+    fn test_multiple_inputs_merge_with_contiguous_positions() {
+        let dir = tempfile::tempdir().unwrap();
+        let greetings = dir.path().join("greetings.csv");
+        let food = dir.path().join("food.csv");
+        let missing = dir.path().join("missing.csv");
+        std::fs::write(&greetings, "position,term,type\n1,안녕하세요,phrase\n2,감사합니다,phrase\n").unwrap();
+        std::fs::write(&food, "position,term,type\n1,사과,noun\n2,밥,noun\n3,김치,noun\n").unwrap();
+        
+        let merged = read_vocabulary_csvs(
+            &[greetings.clone(), missing.clone(), food.clone()],
+            Some(DEFAULT_COMMENT_CHAR),
+        ).unwrap();
+        
+        let positions: Vec<i32> = merged.items.iter().map(|item| item.position).collect();
+        assert_eq!(positions, vec![1, 2, 3, 4, 5]);
+        assert_eq!(merged.items[1].source, greetings.display().to_string());
+        assert_eq!(merged.items[2].source, food.display().to_string());
+        assert_eq!(merged.items[2].term, "사과");
+        
+        let counts: Vec<usize> = merged.files.iter().map(|file| file.items).collect();
+        assert_eq!(counts, vec![2, 0, 3]);
+        assert_eq!(merged.files[1].path, missing);
+        assert!(merged.files[1].error.as_deref().unwrap().contains("missing.csv"));
+        
+        // With nothing loadable, the missing file's error comes back
+        let err = read_vocabulary_csvs(&[missing.clone()], None).unwrap_err();
+        assert!(matches!(err, PipelineError::FileNotFound(path) if path == missing));
+    }
+    
+    #[test]
+    fn test_jsonl_reports_the_malformed_line() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("vocab.jsonl");
+        std::fs::write(&path, concat!(
+            "{\"position\": 1, \"term\": \"안녕하세요\", \"type\": \"phrase\"}\n",
+            "{\"position\": 2, \"term\": \"사과\",}\n",
+            "{\"position\": 3, \"term\": \"밥\", \"word_type\": \"noun\"}\n",
+        )).unwrap();
+        
+        let err = read_vocabulary_jsonl(&path).unwrap_err();
+        match err {
+            PipelineError::InvalidFormat(message) => assert_eq!(
+                message,
+                "Invalid JSON record at line 2, column 34: trailing comma"
+            ),
+            other => panic!("unexpected error: {}", other),
+        }
+        
+        // Fixed, the same file loads like its CSV equivalent
+        std::fs::write(&path, concat!(
+            "{\"position\": 1, \"term\": \"안녕하세요\", \"type\": \"phrase\"}\n",
+            "\n",
+            "{\"term\": \"사과\"}\n",
+            "{\"position\": 3, \"term\": \"밥\", \"word_type\": \"noun\"}\n",
+        )).unwrap();
+        
+        let items = InputFormat::Jsonl.read(&path, None).unwrap();
+        let loaded: Vec<(i32, &str, Option<&str>)> = items.iter()
+            .map(|item| (item.position, item.term.as_str(), item.word_type.as_deref()))
+            .collect();
+        assert_eq!(loaded, vec![
+            (1, "안녕하세요", Some("phrase")),
+            (2, "사과", None),
+            (3, "밥", Some("noun")),
+        ]);
+    }
+}
@@ -1,10 +1,33 @@
 pub mod python_bridge;
 pub mod pipeline;
 pub mod batch_processor;
+pub mod concurrency;
+pub mod retry;
 pub mod export;
+pub mod sink;
+pub mod fallback;
+pub mod quality;
+pub mod audit;
+pub mod transform;
+pub mod input;
+pub mod anki;
+pub mod report;
 pub mod monitoring;
+#[cfg(any(test, feature = "mock"))]
+pub mod bench;
 pub mod cli;
+pub mod config;
 pub mod errors;
+pub mod shutdown;
+pub mod tokens;
+
+#[cfg(test)]
+mod fixtures;
+
+#[cfg(feature = "server")]
+pub mod server;
+#[cfg(feature = "server")]
+pub mod browse;
 
 pub use pipeline::Pipeline;
 pub use batch_processor::BatchProcessor;
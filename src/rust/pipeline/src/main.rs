@@ -1,13 +1,27 @@
 use flashcard_pipeline::{
-    cli::{Cli, Commands},
-    pipeline::{Pipeline, PipelineConfig},
-    monitoring::HealthStatus,
+    anki::AnkiPreset,
+    cli::{Cli, Commands, RunArgs},
+    config::{ConfigFile, ExplicitArgs},
+    pipeline::{Pipeline, PipelineConfig, ProcessingResult},
+    quality::QualityGate,
+    monitoring::{HealthStatus, QueueDepthThresholds},
     errors::PipelineError,
+    export::{LineEnding, DEFAULT_FORMULA_GUARD},
+    input::validate_vocabulary_csv,
+    report::format_percentage,
+    transform::{HtmlStrip, TransformChain, TrimWhitespace},
 };
-use clap::Parser;
-use tracing::{info, error, warn};
+use flashcard_core::models::{CacheType, ForceRefresh, KeyNormalization};
+use flashcard_core::term_normalizer::TermNormalizer;
+use flashcard_core::cache_manager::WarmupOptions;
+use clap::{CommandFactory, FromArgMatches};
+use tracing::{info, error};
 use console::{style, Emoji};
+use flashcard_pipeline::sink::is_stdout;
+use std::io::Write;
+use std::path::Path;
 use std::process;
+use std::time::Duration;
 
 static SPARKLE: Emoji<'_, '_> = Emoji("✨ ", "* ");
 static ROCKET: Emoji<'_, '_> = Emoji("🚀 ", "=> ");
@@ -19,66 +33,213 @@ static HEALTH: Emoji<'_, '_> = Emoji("🏥 ", "[HEALTH] ");
 
 #[tokio::main]
 async fn main() {
-    let cli = Cli::parse();
-    cli.init_logging();
+    let matches = Cli::command().get_matches();
+    let mut cli = Cli::from_arg_matches(&matches).unwrap_or_else(|e| e.exit());
     
-    if let Err(e) = run(cli).await {
+    // Load before logging starts, since the file can configure it
+    let file = match cli.config.as_deref().map(ConfigFile::load).transpose() {
+        Ok(file) => file,
+        Err(e) => {
+            eprintln!("{} {}", CROSS, style(&e).red());
+            process::exit(e.exit_code());
+        }
+    };
+    if let Some(file) = &file {
+        cli.apply_config(file, &matches);
+    }
+    let log_guard = cli.init_logging();
+    
+    // Subcommand flags override the file only where given explicitly
+    let args = ExplicitArgs::new(file.as_ref().and(matches.subcommand().map(|(_, sub)| sub)));
+    let mut base = file.map(|file| file.pipeline).unwrap_or_default();
+    // Every subcommand builds its config from `base`, so set it once here
+    if let Some(namespace) = cli.namespace.take() {
+        base.namespace = namespace;
+    }
+    base.sqlite = cli.sqlite_pragmas(base.sqlite);
+    base.database_url = cli.database_url.clone();
+    base.cache_dir = cli.cache_dir.clone();
+    base.allow_migration_drift = cli.allow_migration_drift;
+    
+    if let Err(e) = run(cli, base, args).await {
         error!("{} {}", CROSS, style(e).red());
+        // process::exit skips destructors, so flush file logs first
+        drop(log_guard);
         process::exit(e.exit_code());
     }
 }
 
-async fn run(cli: Cli) -> Result<(), PipelineError> {
+/// `base` holds the config file's pipeline settings (or the defaults), which
+/// each command's flags are layered over.
+async fn run(cli: Cli, base: PipelineConfig, args: ExplicitArgs<'_>) -> Result<(), PipelineError> {
     match cli.command {
-        Commands::Process {
-            input,
-            output,
-            max_concurrent,
-            batch_size,
-            resume,
-            no_export,
-            csv,
-        } => {
-            println!("{} {}Korean Language Flashcard Pipeline", SPARKLE, style("Starting ").bold());
+        Commands::Process { input, input_format, run, resume, strip_particles, comment_char } => {
+            let output = run.output.clone();
+            let report = run.report.clone();
+            let exported = !run.no_export;
+            status_line(&output, format_args!("{} {}Korean Language Flashcard Pipeline", SPARKLE, style("Starting ").bold()));
+            
+            let base = PipelineConfig {
+                input_format: args.pick("input_format", input_format, base.input_format),
+                csv_comment: args.pick("comment_char", Some(comment_char), base.csv_comment),
+                term_normalizer: if strip_particles {
+                    Some(TermNormalizer::default())
+                } else {
+                    base.term_normalizer
+                },
+                ..base
+            };
+            let config = run_config(run, base, &args);
+            config.validate()?;
             
+            let pipeline = Pipeline::new(config).await?;
+            pipeline.shutdown_coordinator().install();
+            
+            let result = pipeline.process_csv_files(&input, &output, resume.as_ref()).await?;
+            
+            print_processing_result(&result, &output, exported)?;
+            
+            if let Some(report) = report {
+                pipeline.write_report(&result, &report)?;
+                status_line(&output, format_args!("Run report written to: {}", style(report.display()).cyan()));
+            }
+        }
+        
+        Commands::ProcessPending { limit, run } => {
+            let output = run.output.clone();
+            let report = run.report.clone();
+            let exported = !run.no_export;
+            let config = run_config(run, base, &args);
+            config.validate()?;
+            
+            let pipeline = Pipeline::new(config).await?;
+            pipeline.shutdown_coordinator().install();
+            
+            match pipeline.process_pending(limit, &output).await? {
+                Some(result) => {
+                    print_processing_result(&result, &output, exported)?;
+                    
+                    if let Some(report) = report {
+                        pipeline.write_report(&result, &report)?;
+                        status_line(&output, format_args!("Run report written to: {}", style(report.display()).cyan()));
+                    }
+                }
+                None => status_line(&output, format_args!("{} Nothing to process: every vocabulary item is already cached", CHECK)),
+            }
+        }
+        
+        Commands::RunQueue { output_dir, max_concurrent_batches, max_concurrent, api_concurrency, watch, audit, shutdown_grace } => {
             let config = PipelineConfig {
-                database_url: cli.database_url,
-                cache_dir: cli.cache_dir,
-                max_concurrent,
-                batch_size,
-                enable_metrics: true,
-                checkpoint_interval: 10,
+                max_concurrent_batches: args.pick("max_concurrent_batches", max_concurrent_batches, base.max_concurrent_batches),
+                max_concurrent: args.pick("max_concurrent", max_concurrent, base.max_concurrent),
+                api_concurrency: api_concurrency.or(base.api_concurrency),
+                audit_api_calls: audit || base.audit_api_calls,
+                shutdown_grace_period: shutdown_grace.unwrap_or(base.shutdown_grace_period),
+                ..base
             };
+            config.validate()?;
             
             let pipeline = Pipeline::new(config).await?;
+            pipeline.shutdown_coordinator().install();
             
-            let result = pipeline.process_csv_file(&input, &output, resume).await?;
+            if let Some(poll_interval) = watch {
+                println!("{} Watching the queue every {}", SPARKLE, humantime::format_duration(poll_interval));
+                pipeline.run_batch_scheduler(&output_dir, poll_interval).await?;
+                return Ok(());
+            }
             
-            println!("\n{} {}!", CHECK, style("Processing complete").green().bold());
-            println!("  Total items: {}", style(result.total_items).cyan());
-            println!("  Successful: {}", style(result.successful_items).green());
-            println!("  Failed: {}", style(result.failed_items).red());
-            println!("  Cache hits: {} ({:.1}%)", 
-                style(result.cache_hits).yellow(),
-                (result.cache_hits as f64 / result.total_items as f64) * 100.0
+            let outcomes = pipeline.process_queued_batches(&output_dir).await?;
+            if outcomes.is_empty() {
+                println!("{} No queued batches waiting to run", CHECK);
+            }
+            for (batch_id, outcome) in &outcomes {
+                match outcome {
+                    Ok(result) => println!(
+                        "{} Batch {}: {} succeeded, {} failed -> {}",
+                        CHECK,
+                        style(batch_id).cyan(),
+                        style(result.successful_items).green(),
+                        style(result.failed_items).red(),
+                        result.output_path.as_deref().unwrap_or(&output_dir).display()
+                    ),
+                    Err(e) => println!("{} Batch {}: {}", CROSS, style(batch_id).cyan(), style(e).red()),
+                }
+            }
+            // Exit as the first failed batch would have on its own
+            if let Some(e) = outcomes.into_iter().find_map(|(_, outcome)| outcome.err()) {
+                return Err(e);
+            }
+        }
+        
+        Commands::Suspend { term, leech } => {
+            let config = PipelineConfig {
+                cache_only: true,
+                ..base
+            };
+            
+            let pipeline = Pipeline::new(config).await?;
+            // Without --leech an existing leech mark is left as it is
+            let state = pipeline.set_suspended(&term, true, leech.then_some(true)).await?;
+            println!("{} Suspended {}{}; re-export to tag its cards",
+                CHECK,
+                style(&state.term).bold(),
+                if state.leech { " (leech)" } else { "" }
             );
-            println!("  Processing time: {:?}", result.processing_time);
+        }
+        
+        Commands::Unsuspend { term } => {
+            let config = PipelineConfig {
+                cache_only: true,
+                ..base
+            };
             
-            if !no_export && result.successful_items > 0 {
-                println!("\n{} Export statistics:", SPARKLE);
-                println!("{}", result.export_stats.summary());
-                println!("\nOutput written to: {}", style(output.display()).cyan());
-            }
+            let pipeline = Pipeline::new(config).await?;
+            let state = pipeline.set_suspended(&term, false, Some(false)).await?;
+            println!("{} Unsuspended {}; re-export to untag its cards", CHECK, style(&state.term).bold());
         }
         
-        Commands::CacheStats { detailed } => {
+        Commands::ApiLog { batch_id, limit } => {
             let config = PipelineConfig {
-                database_url: cli.database_url,
-                cache_dir: cli.cache_dir,
-                ..Default::default()
+                cache_only: true,
+                ..base
             };
             
             let pipeline = Pipeline::new(config).await?;
+            let calls = pipeline.api_calls(batch_id.as_ref(), limit).await?;
+            
+            if calls.is_empty() {
+                println!("{} No API calls recorded; run with --audit to record them", CHECK);
+                return Ok(());
+            }
+            
+            println!("{} Last {} API call(s):", SPARKLE, calls.len());
+            for call in &calls {
+                let stage = match call.stage {
+                    CacheType::Stage1 => "stage1",
+                    CacheType::Stage2 => "stage2",
+                };
+                let outcome = match &call.error_message {
+                    None => style("ok".to_string()).green(),
+                    Some(error) => style(error.clone()).red(),
+                };
+                println!("  {} {} {} {} {} tokens {}ms {}",
+                    call.called_at.format("%Y-%m-%d %H:%M:%S"),
+                    style(&call.term).bold(),
+                    stage,
+                    style(&call.model).dim(),
+                    call.total_tokens,
+                    call.latency_ms,
+                    outcome
+                );
+            }
+            
+            let tokens: i64 = calls.iter().map(|call| call.total_tokens).sum();
+            let failed = calls.iter().filter(|call| !call.success).count();
+            println!("\n{} {} tokens, {} failed call(s)", CHECK, tokens, style(failed).red());
+        }
+        
+        Commands::CacheStats { detailed } => {
+            let pipeline = Pipeline::new(base).await?;
             let stats = pipeline.get_cache_stats().await?;
             
             println!("{} {}:", CACHE, style("Cache Statistics").bold());
@@ -93,35 +254,87 @@ async fn run(cli: Cli) -> Result<(), PipelineError> {
             }
         }
         
-        Commands::ClearCache { stage1_only, stage2_only, force } => {
+        Commands::CacheGrowth { days, record } => {
+            let config = PipelineConfig {
+                cache_only: true,
+                ..base
+            };
+            
+            let pipeline = Pipeline::new(config).await?;
+            if record {
+                pipeline.snapshot_cache_stats().await?;
+            }
+            let history = pipeline.cache_growth(days).await?;
+            
+            let (Some(first), Some(last)) = (history.first(), history.last()) else {
+                println!("{} No cache stats recorded in the last {} days; record them with --record or record_cache_stats", CHECK, days);
+                return Ok(());
+            };
+            
+            println!("{} {} ({} snapshots, last {} days):", CACHE, style("Cache Growth").bold(), history.len(), days);
+            println!("  {:<17} {:>9} {:>8} {:>9} {:>12} {:>10}", "Recorded", "Entries", "Change", "Hit rate", "Tokens saved", "Saved");
+            let mut previous = None;
+            for snapshot in &history {
+                let stats = &snapshot.stats;
+                let change = previous.map_or(0, |previous| stats.total_entries - previous);
+                previous = Some(stats.total_entries);
+                println!("  {:<17} {:>9} {:>+8} {:>8.1}% {:>12} {:>10}",
+                    snapshot.recorded_at.format("%Y-%m-%d %H:%M"),
+                    stats.total_entries,
+                    change,
+                    stats.hit_rate * 100.0,
+                    stats.total_tokens_saved,
+                    format!("${:.2}", stats.estimated_cost_saved)
+                );
+            }
+            
+            println!("  Entries grew by {} while savings grew by {}",
+                style(format!("{:+}", last.stats.total_entries - first.stats.total_entries)).cyan(),
+                style(format!("${:.2}", last.stats.estimated_cost_saved - first.stats.estimated_cost_saved)).green()
+            );
+        }
+        
+        Commands::ClearCache { stage1_only, stage2_only, clear_model, force } => {
             if !force {
                 println!("{} Are you sure you want to clear the cache? This cannot be undone.", THINKING);
                 println!("Use --force to skip this confirmation.");
                 return Ok(());
             }
             
-            let config = PipelineConfig {
-                database_url: cli.database_url,
-                cache_dir: cli.cache_dir,
-                ..Default::default()
+            let pipeline = Pipeline::new(base).await?;
+            let cache_type = match (stage1_only, stage2_only) {
+                (true, false) => Some(CacheType::Stage1),
+                (false, true) => Some(CacheType::Stage2),
+                _ => None,
             };
             
-            let pipeline = Pipeline::new(config).await?;
+            let removed = match &clear_model {
+                Some(model) => pipeline.clear_cache_by_model(cache_type, model).await?,
+                None => pipeline.clear_cache(cache_type).await?,
+            };
+            println!("{} Cache cleared successfully ({} entries removed)", CHECK, style(removed).green());
+        }
+        
+        Commands::CachePrune { older_than, max_access, dry_run } => {
+            let pipeline = Pipeline::new(base).await?;
             
-            // TODO: Implement cache clearing
-            println!("{} Cache cleared successfully", CHECK);
+            if dry_run {
+                let cold = pipeline.find_cold_cache_entries(older_than, max_access).await?;
+                println!("{} {} cold cache entries older than {}",
+                    SPARKLE,
+                    style(cold.len()).yellow(),
+                    humantime::format_duration(older_than)
+                );
+            } else {
+                let removed = pipeline.prune_cold_cache_entries(older_than, max_access).await?;
+                println!("{} Pruned {} cold cache entries", CHECK, style(removed).green());
+            }
         }
         
         Commands::TestConnection { test_term } => {
             println!("{} Testing API connection...", ROCKET);
             
-            let config = PipelineConfig {
-                database_url: cli.database_url,
-                cache_dir: cli.cache_dir,
-                ..Default::default()
-            };
-            
-            let pipeline = Pipeline::new(config).await?;
+            let pipeline = Pipeline::new(base).await?;
             
             // Create test item
             let test_item = flashcard_core::models::VocabularyItem {
@@ -129,6 +342,7 @@ async fn run(cli: Cli) -> Result<(), PipelineError> {
                 position: 1,
                 term: test_term.clone(),
                 word_type: None,
+                source: String::new(),
                 created_at: chrono::Utc::now(),
                 updated_at: chrono::Utc::now(),
             };
@@ -151,11 +365,13 @@ async fn run(cli: Cli) -> Result<(), PipelineError> {
             }
         }
         
-        Commands::Health { json } => {
+        Commands::Health { json, queue_warn_depth, queue_fail_depth } => {
             let config = PipelineConfig {
-                database_url: cli.database_url,
-                cache_dir: cli.cache_dir,
-                ..Default::default()
+                queue_thresholds: QueueDepthThresholds {
+                    warn_depth: queue_warn_depth.or(base.queue_thresholds.warn_depth),
+                    fail_depth: queue_fail_depth.or(base.queue_thresholds.fail_depth),
+                },
+                ..base
             };
             
             let pipeline = Pipeline::new(config).await?;
@@ -177,20 +393,18 @@ async fn run(cli: Cli) -> Result<(), PipelineError> {
                 print_service_status("Cache", &health.cache_status);
                 print_service_status("API", &health.api_status);
                 print_service_status("Python Bridge", &health.python_bridge_status);
+                print_service_status(
+                    &format!("Queue ({} pending)", health.queue_depth.map_or("?".to_string(), |depth| depth.to_string())),
+                    &health.queue_status,
+                );
                 
                 println!("  Last check: {}", health.last_check.format("%Y-%m-%d %H:%M:%S UTC"));
             }
         }
         
-        Commands::ListBatches { limit, detailed } => {
-            let config = PipelineConfig {
-                database_url: cli.database_url,
-                cache_dir: cli.cache_dir,
-                ..Default::default()
-            };
-            
-            let pipeline = Pipeline::new(config).await?;
-            let batches = pipeline.list_batches().await?;
+        Commands::ListBatches { limit, label, detailed } => {
+            let pipeline = Pipeline::new(base).await?;
+            let batches = pipeline.list_batches(label.as_deref(), limit).await?;
             
             if batches.is_empty() {
                 println!("No batches found.");
@@ -198,11 +412,14 @@ async fn run(cli: Cli) -> Result<(), PipelineError> {
             }
             
             println!("{} {}:", SPARKLE, style("Processing Batches").bold());
-            for batch in batches.iter().take(limit) {
-                println!("  Batch #{}: {} items (created: {})",
-                    style(batch.batch_id).cyan(),
+            for batch in &batches {
+                println!("  Batch #{}: {} items (created: {}){}",
+                    style(&batch.batch_id).cyan(),
                     batch.total_items,
-                    batch.created_at.format("%Y-%m-%d %H:%M:%S UTC")
+                    batch.created_at.format("%Y-%m-%d %H:%M:%S UTC"),
+                    batch.label.as_deref()
+                        .map(|label| format!(" [{}]", style(label).yellow()))
+                        .unwrap_or_default()
                 );
                 
                 if detailed {
@@ -212,22 +429,17 @@ async fn run(cli: Cli) -> Result<(), PipelineError> {
         }
         
         Commands::BatchStatus { batch_id, show_failed } => {
-            let config = PipelineConfig {
-                database_url: cli.database_url,
-                cache_dir: cli.cache_dir,
-                ..Default::default()
-            };
-            
-            let pipeline = Pipeline::new(config).await?;
-            let status = pipeline.get_batch_status(batch_id).await?;
+            let pipeline = Pipeline::new(base).await?;
+            let status = pipeline.get_batch_status(&batch_id).await?;
             
-            println!("{} Batch #{} Status:", SPARKLE, style(batch_id).cyan());
+            println!("{} Batch #{} Status:", SPARKLE, style(&batch_id).cyan());
             println!("  Total items: {}", status.total_items);
-            println!("  Completed: {} ({:.1}%)", 
+            println!("  Completed: {} ({})", 
                 style(status.completed_items).green(),
-                (status.completed_items as f64 / status.total_items as f64) * 100.0
+                format_percentage(status.completed_items, status.total_items)
             );
             println!("  Failed: {}", style(status.failed_items).red());
+            println!("  Skipped: {}", style(status.skipped_items).dim());
             println!("  In progress: {}", 
                 if status.in_progress { 
                     style("Yes").yellow() 
@@ -242,13 +454,7 @@ async fn run(cli: Cli) -> Result<(), PipelineError> {
         }
         
         Commands::Metrics { output } => {
-            let config = PipelineConfig {
-                database_url: cli.database_url,
-                cache_dir: cli.cache_dir,
-                ..Default::default()
-            };
-            
-            let pipeline = Pipeline::new(config).await?;
+            let pipeline = Pipeline::new(base).await?;
             let metrics = pipeline.metrics_collector.get_metrics();
             let prometheus_format = metrics.to_prometheus_format();
             
@@ -260,28 +466,538 @@ async fn run(cli: Cli) -> Result<(), PipelineError> {
             }
         }
         
-        Commands::WarmCache { input, stage1_only } => {
-            println!("{} Warming cache...", CACHE);
+        #[cfg(feature = "server")]
+        Commands::Serve { port, host, queue_warn_depth, queue_fail_depth } => {
+            let config = PipelineConfig {
+                queue_thresholds: QueueDepthThresholds {
+                    warn_depth: queue_warn_depth.or(base.queue_thresholds.warn_depth),
+                    fail_depth: queue_fail_depth.or(base.queue_thresholds.fail_depth),
+                },
+                ..base
+            };
+            
+            let pipeline = Pipeline::new(config).await?;
+            
+            let addr = std::net::SocketAddr::new(host, port);
+            println!("{} Serving health and metrics on {}", HEALTH, style(addr).cyan());
+            pipeline.serve(addr).await?;
+        }
+        
+        #[cfg(feature = "server")]
+        Commands::Browse { port, host } => {
+            let pipeline = Pipeline::new(base).await?;
             
+            let addr = std::net::SocketAddr::new(host, port);
+            println!("{} Browse cards at http://{}/", SPARKLE, style(addr).cyan());
+            pipeline.browse(addr).await?;
+        }
+        
+        Commands::WarmCache { input, stage1_only, report_only, resume_warm, pin_model_in_key, comment_char } => {
             let config = PipelineConfig {
-                database_url: cli.database_url,
-                cache_dir: cli.cache_dir,
-                ..Default::default()
+                csv_comment: args.pick("comment_char", Some(comment_char), base.csv_comment),
+                pin_model_in_key: pin_model_in_key || base.pin_model_in_key,
+                ..base
             };
             
             let pipeline = Pipeline::new(config).await?;
             
             // Load items from CSV
             let items = pipeline.load_csv(&input).await?;
-            let warmed = pipeline.warm_cache(&items).await?;
+            // Read-only, so a report never leaves anything behind
+            let report = pipeline.probe_cache(&items, stage1_only).await?;
+            
+            println!("{} {}:", CACHE, style("Cache Coverage").bold());
+            println!("  Total items: {}", style(report.total_items).cyan());
+            if stage1_only {
+                println!("  Stage 1 cached: {}", style(report.stage1_cached).green());
+                println!("  Need stage 1: {}", style(report.stage1_missing).yellow());
+            } else {
+                println!("  Fully cached: {}", style(report.fully_cached()).green());
+                println!("  Need stage 2 only: {}", style(report.needs_stage2_only()).yellow());
+                println!("  Need both stages: {}", style(report.needs_both()).red());
+            }
+            println!("  Estimated tokens saved: {}", style(report.estimated_tokens_saved).cyan());
+            println!("  Estimated cost saved: ${:.2}", report.estimated_cost_saved());
+            // An upper bound: cached stages are counted too
+            let tokens = pipeline.estimate_tokens(&items);
+            let prompt_tokens = if stage1_only { tokens.stage1 } else { tokens.total() };
+            println!("  Prompt tokens, uncached: {}", style(prompt_tokens).cyan());
+            
+            if report_only {
+                return Ok(());
+            }
+            
+            println!("\n{} Warming cache...", CACHE);
+            let options = WarmupOptions {
+                resume: resume_warm,
+                stage1_only,
+                ..Default::default()
+            };
+            let warmed = pipeline.warm_cache(&items, options).await?;
+            
+            println!("{} Cache warmed for {} items", CHECK, style(warmed).cyan());
+        }
+        
+        Commands::Validate { input, warn_only, exact_cache_keys, comment_char } => {
+            let comment = args.pick("comment_char", Some(comment_char), base.csv_comment);
+            let key_normalization = if exact_cache_keys {
+                KeyNormalization::None
+            } else {
+                base.key_normalization
+            };
+            let report = validate_vocabulary_csv(&input, comment, key_normalization)?;
+            
+            println!("{} {} {}:", THINKING, style("Validated").bold(), input.display());
+            println!("  Total rows: {}", style(report.total_rows).cyan());
+            println!("  Valid: {}", style(report.valid).green());
+            println!("  Invalid: {}", style(report.invalid.len()).red());
+            println!("  Duplicates: {}", style(report.duplicates.len()).yellow());
+            
+            if !report.invalid.is_empty() {
+                println!("\n{} Invalid rows:", CROSS);
+                for row in &report.invalid {
+                    println!("  line {}: {}", row.line, style(&row.reason).red());
+                }
+            }
+            if !report.duplicates.is_empty() {
+                println!("\n{} Duplicate terms:", CACHE);
+                for row in &report.duplicates {
+                    println!("  line {}: {} (first on line {})", row.line, row.term, row.first_line);
+                }
+            }
+            
+            if !report.is_valid() && !warn_only {
+                // Loading a file with no rows fails just like one with bad rows
+                if report.invalid.is_empty() {
+                    return Err(PipelineError::InvalidFormat(format!(
+                        "{} contains no vocabulary rows",
+                        input.display()
+                    )));
+                }
+                return Err(PipelineError::InvalidFormat(format!(
+                    "{} of {} rows in {} are invalid",
+                    report.invalid.len(),
+                    report.total_rows,
+                    input.display()
+                )));
+            }
+            if report.is_valid() {
+                println!("\n{} Every row is valid", CHECK);
+            }
+        }
+        
+        Commands::CacheExport { output } => {
+            let pipeline = Pipeline::new(base).await?;
+            let exported = pipeline.export_cache(&output).await?;
+            
+            println!("{} Exported {} cache entries to {}", 
+                CHECK, 
+                style(exported).cyan(), 
+                style(output.display()).cyan()
+            );
+        }
+        
+        Commands::Reexport { batch_id, output, format, sort, crlf, bom } => {
+            // Everything needed is cached; never fall back to the API
+            let config = PipelineConfig {
+                cache_only: true,
+                sort_by: sort.unwrap_or(base.sort_by),
+                line_ending: if crlf { LineEnding::CrLf } else { base.line_ending },
+                write_bom: bom || base.write_bom,
+                ..base
+            };
+            
+            let pipeline = Pipeline::new(config).await?;
+            let stats = pipeline.reexport_batch(&batch_id, format, &output).await?;
+            
+            if !is_stdout(&output) {
+                println!("{} Exported {} cards from batch {} to {}",
+                    CHECK,
+                    style(stats.cards_exported).green(),
+                    batch_id,
+                    style(output.display()).cyan()
+                );
+            }
+        }
+        
+        Commands::DiffBatches { old, new, json } => {
+            let config = PipelineConfig {
+                cache_only: true,
+                ..base
+            };
+            
+            let pipeline = Pipeline::new(config).await?;
+            let diff = pipeline.diff_batches(&old, &new).await?;
+            
+            if json {
+                println!("{}", serde_json::to_string_pretty(&diff)?);
+                return Ok(());
+            }
+            
+            println!("{} Batch {} vs {}", CACHE, style(&old).cyan(), style(&new).cyan());
+            for term in &diff.changed {
+                println!("\n  {} ({} field(s) changed)", style(&term.term).bold(), term.changes.len());
+                for change in &term.changes {
+                    println!("    {}", style(&change.path).dim());
+                    println!("      {} {}", style("-").red(), change.old);
+                    println!("      {} {}", style("+").green(), change.new);
+                }
+            }
+            if !diff.added.is_empty() {
+                println!("\n  {} {}", style("Only in new:").green(), diff.added.join(", "));
+            }
+            if !diff.removed.is_empty() {
+                println!("\n  {} {}", style("Only in old:").red(), diff.removed.join(", "));
+            }
+            
+            println!("\n{} {} of {} shared cards changed, {} added, {} removed",
+                CHECK,
+                style(diff.changed.len()).yellow(),
+                diff.compared(),
+                style(diff.added.len()).green(),
+                style(diff.removed.len()).red()
+            );
+        }
+        
+        Commands::CacheImport { input, overwrite } => {
+            let pipeline = Pipeline::new(base).await?;
+            let stats = pipeline.import_cache(&input, overwrite).await?;
+            
+            println!("{} Imported {} cache entries ({} skipped)", 
+                CHECK, 
+                style(stats.imported).green(), 
+                style(stats.skipped).yellow()
+            );
+        }
+        
+        Commands::Inspect { term, diff, save } => {
+            let pipeline = Pipeline::new(base).await?;
+            let (card, changes) = pipeline.inspect_term(&term, diff, save).await?;
+            
+            println!("{}", serde_json::to_string_pretty(&card)?);
+            
+            match changes {
+                Some(changes) if changes.is_empty() => {
+                    println!("\n{} Regenerated card matches the cache", CHECK);
+                }
+                Some(changes) => {
+                    println!("\n{} {} field(s) changed:", CACHE, changes.changes.len());
+                    for change in &changes.changes {
+                        println!("  {}", style(&change.path).bold());
+                        println!("    {} {}", style("-").red(), change.old);
+                        println!("    {} {}", style("+").green(), change.new);
+                    }
+                }
+                None if diff => println!("\n{} Nothing cached for {}; nothing to compare", CACHE, term),
+                None => {}
+            }
             
-            println!("{} Cache warmed with {} entries", CHECK, style(warmed).cyan());
+            if save {
+                println!("{} Regenerated card saved to the cache", CHECK);
+            }
+        }
+        
+        Commands::CacheKey { term, word_type } => {
+            let config = PipelineConfig {
+                cache_only: true,
+                ..base
+            };
+            
+            let pipeline = Pipeline::new(config).await?;
+            let report = pipeline.cache_keys(&term, word_type).await?;
+            
+            println!("{} Cache keys for {}", CACHE, style(&term).cyan());
+            for (stage, key, entry) in [
+                ("Stage 1", &report.stage1_key, &report.stage1_entry),
+                ("Stage 2", &report.stage2_key, &report.stage2_entry),
+            ] {
+                println!("\n  {} {}", style(stage).bold(), key);
+                match entry {
+                    Some(entry) => println!(
+                        "    {}, {} tokens, created {}, read {} time(s)",
+                        entry.model_used,
+                        entry.token_count,
+                        entry.created_at.format("%Y-%m-%d %H:%M:%S UTC"),
+                        entry.access_count
+                    ),
+                    None => println!("    {}", style("not cached").dim()),
+                }
+            }
+        }
+        
+        Commands::CacheMigrate { exact_cache_keys, dry_run } => {
+            let config = PipelineConfig {
+                key_normalization: if exact_cache_keys {
+                    KeyNormalization::None
+                } else {
+                    base.key_normalization
+                },
+                ..base
+            };
+            
+            let pipeline = Pipeline::new(config).await?;
+            let stats = pipeline.migrate_cache_keys(dry_run).await?;
+            
+            let verb = if dry_run { "Would migrate" } else { "Migrated" };
+            println!("{} {} {} cache entries", CHECK, verb, style(stats.migrated).green());
+            println!("  Unchanged: {}", style(stats.unchanged).cyan());
+            println!("  Unmigratable (vocabulary missing): {}", style(stats.unmigratable).yellow());
+            println!("  Collided (new key taken): {}", style(stats.collided).red());
+        }
+        
+        #[cfg(feature = "mock")]
+        Commands::Bench { count, concurrency, latency, jitter, warm_pass, json } => {
+            use flashcard_pipeline::bench::{self, BenchOptions};
+            
+            let options = BenchOptions { count, concurrency, latency, jitter, warm_pass };
+            let runs = bench::run(&base, &options).await?;
+            
+            if json {
+                println!("{}", serde_json::to_string_pretty(&runs)?);
+            } else {
+                println!("{} {}:", ROCKET, style("Benchmark").bold());
+                print!("{}", bench::format_table(&runs));
+            }
         }
     }
     
     Ok(())
 }
 
+/// Layers the options `process` and `process-pending` share over `base`.
+fn run_config(run: RunArgs, base: PipelineConfig, args: &ExplicitArgs<'_>) -> PipelineConfig {
+    let RunArgs {
+        max_concurrent,
+        api_concurrency,
+        db_concurrency,
+        adaptive,
+        min_concurrency,
+        target_error_rate,
+        rate_smoothing,
+        batch_size,
+        prefetch_chunks,
+        label,
+        max_retries,
+        item_budget,
+        shutdown_grace,
+        max_failures,
+        fail_fast,
+        max_failure_rate,
+        error_report,
+        stats,
+        skip_export_errors,
+        sanitize_formulas,
+        pin_model_in_key,
+        respect_request_hash,
+        explain_cache,
+        audit,
+        no_headers,
+        crlf,
+        bom,
+        anki,
+        deck,
+        mnemonics_only,
+        stream,
+        max_buffered_results,
+        export_threads,
+        model,
+        stage1_model,
+        stage2_model,
+        stage1_prompt,
+        stage2_prompt,
+        exact_cache_keys,
+        refresh_stage1,
+        refresh_stage2,
+        refresh_all,
+        stage2_mode,
+        offline,
+        health_timeout,
+        ignore_retry_after,
+        fallback_cards,
+        frequency_list,
+        no_quality_gate,
+        strip_html,
+        tags,
+        auto_tags,
+        comparison_columns,
+        memory_palace,
+        sort,
+        max_field_chars,
+        field_limits,
+        // Read by the caller, which prints and reports the run
+        ..
+    } = run;
+    
+    PipelineConfig {
+        max_concurrent: args.pick("max_concurrent", max_concurrent, base.max_concurrent),
+        api_concurrency: api_concurrency.or(base.api_concurrency),
+        db_concurrency: db_concurrency.or(base.db_concurrency),
+        batch_size: args.pick("batch_size", batch_size, base.batch_size),
+        prefetch_chunks: args.pick("prefetch_chunks", prefetch_chunks, base.prefetch_chunks),
+        max_retries: args.pick("max_retries", max_retries, base.max_retries),
+        per_item_budget: item_budget.or(base.per_item_budget),
+        shutdown_grace_period: shutdown_grace.unwrap_or(base.shutdown_grace_period),
+        error_report_path: error_report.or(base.error_report_path),
+        // A bare --stats writes beside the output, once it is resolved
+        write_stats: stats.is_some() || base.write_stats,
+        stats_path: stats.flatten().or(base.stats_path),
+        adaptive_concurrency: adaptive || base.adaptive_concurrency,
+        min_concurrency: args.pick("min_concurrency", min_concurrency, base.min_concurrency),
+        // With --adaptive, --max-concurrent becomes the ceiling
+        max_concurrency: args.pick("max_concurrent", max_concurrent, base.max_concurrency),
+        target_error_rate: args.pick("target_error_rate", target_error_rate, base.target_error_rate),
+        rate_smoothing: args.pick("rate_smoothing", rate_smoothing, base.rate_smoothing),
+        stream_export: stream || base.stream_export,
+        max_buffered_results: max_buffered_results.or(base.max_buffered_results),
+        key_normalization: if exact_cache_keys {
+            KeyNormalization::None
+        } else {
+            base.key_normalization
+        },
+        batch_label: label.or(base.batch_label),
+        include_headers: !no_headers && base.include_headers,
+        respect_request_hash: respect_request_hash || base.respect_request_hash,
+        anki_preset: anki.map(|note_type| {
+            let preset = AnkiPreset::new(note_type);
+            match deck {
+                Some(deck) => preset.with_deck(deck),
+                None => preset,
+            }
+        }).or(base.anki_preset),
+        model: args.pick("model", model, base.model),
+        stage1_model: stage1_model.or(base.stage1_model),
+        stage2_model: stage2_model.or(base.stage2_model),
+        stage1_prompt_path: stage1_prompt.or(base.stage1_prompt_path),
+        stage2_prompt_path: stage2_prompt.or(base.stage2_prompt_path),
+        export_threads: args.pick("export_threads", export_threads, base.export_threads),
+        cache_only: offline || base.cache_only,
+        health_check_timeout: args.pick(
+            "health_timeout",
+            Duration::from_secs(health_timeout),
+            base.health_check_timeout,
+        ),
+        honor_retry_after: !ignore_retry_after && base.honor_retry_after,
+        stage1_fallback: fallback_cards || base.stage1_fallback,
+        frequency_list: frequency_list.or(base.frequency_list),
+        quality_gate: QualityGate {
+            enabled: !no_quality_gate && base.quality_gate.enabled,
+            ..base.quality_gate
+        },
+        transforms: card_transforms(strip_html),
+        comparison_columns: comparison_columns || base.comparison_columns,
+        memory_palace: memory_palace.or(base.memory_palace),
+        sort_by: sort.unwrap_or(base.sort_by),
+        force_refresh: ForceRefresh {
+            stage1: refresh_stage1 || refresh_all || base.force_refresh.stage1,
+            stage2: refresh_stage2 || refresh_all || base.force_refresh.stage2,
+        },
+        mnemonics_only: mnemonics_only || base.mnemonics_only,
+        stage2_mode: args.pick("stage2_mode", stage2_mode.mode(), base.stage2_mode),
+        max_field_chars: max_field_chars.or(base.max_field_chars),
+        field_char_limits: base.field_char_limits.into_iter().chain(field_limits).collect(),
+        extra_tags: base.extra_tags.into_iter().chain(tags).collect(),
+        auto_tags: auto_tags || base.auto_tags,
+        max_consecutive_failures: if fail_fast {
+            Some(1)
+        } else {
+            max_failures.or(base.max_consecutive_failures)
+        },
+        max_failure_rate: max_failure_rate.or(base.max_failure_rate),
+        skip_export_errors: skip_export_errors || base.skip_export_errors,
+        formula_guard: sanitize_formulas
+            .map(|guard| guard.unwrap_or_else(|| DEFAULT_FORMULA_GUARD.to_string()))
+            .or(base.formula_guard),
+        line_ending: if crlf { LineEnding::CrLf } else { base.line_ending },
+        write_bom: bom || base.write_bom,
+        pin_model_in_key: pin_model_in_key || base.pin_model_in_key,
+        explain_cache: explain_cache || base.explain_cache,
+        audit_api_calls: audit || base.audit_api_calls,
+        ..base
+    }
+}
+
+/// Transforms selected on the command line. Tags from `--tag` are added
+/// by the exporter instead.
+fn card_transforms(strip_html: bool) -> TransformChain {
+    let mut chain = TransformChain::new();
+    if strip_html {
+        chain = chain.with(HtmlStrip).with(TrimWhitespace);
+    }
+    chain
+}
+
+/// Print a line about the run, on stderr when the cards themselves are
+/// going to stdout
+fn status_line(output: &Path, message: std::fmt::Arguments<'_>) {
+    if is_stdout(output) {
+        eprintln!("{}", message);
+    } else {
+        println!("{}", message);
+    }
+}
+
+/// With output going to stdout the summary goes to stderr, keeping it out
+/// of the exported cards.
+fn print_processing_result(result: &ProcessingResult, output: &Path, exported: bool) -> std::io::Result<()> {
+    let mut out: Box<dyn Write> = if is_stdout(output) {
+        Box::new(std::io::stderr())
+    } else {
+        Box::new(std::io::stdout())
+    };
+    
+    if result.nothing_to_do() {
+        writeln!(out, "{} Nothing to do (all {} items already processed)", CHECK, result.already_processed)?;
+        return Ok(());
+    }
+    
+    writeln!(out, "\n{} {}!", CHECK, style("Processing complete").green().bold())?;
+    writeln!(out, "  Total items: {}", style(result.total_items).cyan())?;
+    writeln!(out, "  Successful: {}", style(result.successful_items).green())?;
+    writeln!(out, "  Failed: {}", style(result.failed_items).red())?;
+    if result.skipped_items > 0 {
+        writeln!(out, "  Skipped (not cached): {}", style(result.skipped_items).dim())?;
+    }
+    if result.left_pending > 0 {
+        writeln!(
+            out,
+            "  Left pending: {} (resume with --resume {})",
+            style(result.left_pending).yellow(),
+            result.batch_id
+        )?;
+    }
+    writeln!(out, "  Cache hits: {} ({})", 
+        style(result.cache_hits).yellow(),
+        format_percentage(result.cache_hits, result.total_items)
+    )?;
+    writeln!(out, "  Processing time: {:?}", result.processing_time)?;
+    
+    if result.input_files.len() > 1 || result.input_files.iter().any(|file| file.error.is_some()) {
+        writeln!(out, "\n{} Input files:", SPARKLE)?;
+        for file in &result.input_files {
+            match &file.error {
+                None => writeln!(out, "  {}: {}", file.path.display(), style(file.items).cyan())?,
+                Some(error) => writeln!(out, "  {}: {} {}", file.path.display(), CROSS, style(error).red())?,
+            }
+        }
+    }
+    
+    if let Some(report) = &result.error_report {
+        writeln!(out, "\n{} Failures by category:", CROSS)?;
+        for (category, count) in &result.failures_by_category {
+            writeln!(out, "  {}: {}", category, style(count).red())?;
+        }
+        writeln!(out, "Error report written to: {}", style(report.display()).cyan())?;
+    }
+    
+    if exported && result.successful_items > 0 {
+        writeln!(out, "\n{} Export statistics:", SPARKLE)?;
+        writeln!(out, "{}", result.export_stats.summary())?;
+        let written = result.output_path.as_deref().unwrap_or(output);
+        writeln!(out, "\nOutput written to: {}", style(written.display()).cyan())?;
+    }
+    Ok(())
+}
+
 fn print_service_status(name: &str, status: &flashcard_pipeline::monitoring::ServiceStatus) {
     use flashcard_pipeline::monitoring::ServiceStatus;
     
@@ -1,7 +1,9 @@
 use crate::errors::{PipelineError, Result};
+use crate::python_bridge::ModelSelection;
 use flashcard_core::repositories::{CacheRepository, QueueRepository};
 use std::sync::Arc;
-use parking_lot::RwLock;
+use parking_lot::{Mutex, RwLock};
+use hdrhistogram::Histogram;
 use std::time::{Duration, Instant};
 use tracing::{info, debug, instrument};
 use serde::{Serialize, Deserialize};
@@ -17,11 +19,17 @@ pub struct PipelineMetrics {
     pub cache_misses: usize,
     pub api_calls: usize,
     pub api_tokens_used: usize,
+    pub stage1_calls: usize,
+    pub stage1_tokens: usize,
+    pub stage2_calls: usize,
+    pub stage2_tokens: usize,
     pub api_errors: usize,
     pub rate_limit_hits: usize,
     pub average_processing_time_ms: f64,
     pub total_processing_time: Duration,
     pub estimated_cost: f64,
+    #[serde(default)]
+    pub batches_completed: usize,
 }
 
 impl Default for PipelineMetrics {
@@ -35,25 +43,133 @@ impl Default for PipelineMetrics {
             cache_misses: 0,
             api_calls: 0,
             api_tokens_used: 0,
+            stage1_calls: 0,
+            stage1_tokens: 0,
+            stage2_calls: 0,
+            stage2_tokens: 0,
             api_errors: 0,
             rate_limit_hits: 0,
             average_processing_time_ms: 0.0,
             total_processing_time: Duration::from_secs(0),
             estimated_cost: 0.0,
+            batches_completed: 0,
         }
     }
 }
 
+impl PipelineMetrics {
+    /// What was recorded between `earlier`, a snapshot of the same
+    /// collector, and this one; `start_time` is when `earlier` was taken.
+    pub fn since(&self, earlier: &PipelineMetrics) -> PipelineMetrics {
+        let items_processed = self.items_processed.saturating_sub(earlier.items_processed);
+        let total_processing_time = self.total_processing_time.saturating_sub(earlier.total_processing_time);
+        let average_processing_time_ms = if items_processed == 0 {
+            0.0
+        } else {
+            total_processing_time.as_millis() as f64 / items_processed as f64
+        };
+        
+        PipelineMetrics {
+            start_time: earlier.start_time,
+            items_processed,
+            items_succeeded: self.items_succeeded.saturating_sub(earlier.items_succeeded),
+            items_failed: self.items_failed.saturating_sub(earlier.items_failed),
+            cache_hits: self.cache_hits.saturating_sub(earlier.cache_hits),
+            cache_misses: self.cache_misses.saturating_sub(earlier.cache_misses),
+            api_calls: self.api_calls.saturating_sub(earlier.api_calls),
+            api_tokens_used: self.api_tokens_used.saturating_sub(earlier.api_tokens_used),
+            stage1_calls: self.stage1_calls.saturating_sub(earlier.stage1_calls),
+            stage1_tokens: self.stage1_tokens.saturating_sub(earlier.stage1_tokens),
+            stage2_calls: self.stage2_calls.saturating_sub(earlier.stage2_calls),
+            stage2_tokens: self.stage2_tokens.saturating_sub(earlier.stage2_tokens),
+            api_errors: self.api_errors.saturating_sub(earlier.api_errors),
+            rate_limit_hits: self.rate_limit_hits.saturating_sub(earlier.rate_limit_hits),
+            average_processing_time_ms,
+            total_processing_time,
+            estimated_cost: (self.estimated_cost - earlier.estimated_cost).max(0.0),
+            batches_completed: self.batches_completed.saturating_sub(earlier.batches_completed),
+        }
+    }
+}
+
+/// Which pipeline stage an API call served
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ApiStage {
+    Stage1,
+    Stage2,
+}
+
+impl ApiStage {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ApiStage::Stage1 => "stage1",
+            ApiStage::Stage2 => "stage2",
+        }
+    }
+}
+
+/// Blended input/output price in dollars per million tokens for `model`.
+///
+/// Matches on the model family so provider prefixes and version suffixes
+/// don't matter. Unknown models are priced like Sonnet.
+pub fn price_per_million_tokens(model: &str) -> f64 {
+    let model = model.to_ascii_lowercase();
+    if model.contains("haiku") {
+        // $0.80 input, $4 output
+        2.4
+    } else if model.contains("opus") {
+        // $15 input, $75 output
+        45.0
+    } else {
+        // Sonnet: $3 input, $15 output
+        10.0
+    }
+}
+
+/// Longest item latency the histogram tells apart; slower items are
+/// recorded as this
+const MAX_TRACKED_LATENCY: Duration = Duration::from_secs(3600);
+
+/// Per-item latency percentiles, in milliseconds
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct LatencySummary {
+    pub samples: u64,
+    pub p50_ms: f64,
+    pub p95_ms: f64,
+    pub p99_ms: f64,
+    pub max_ms: f64,
+}
+
 pub struct MetricsCollector {
     metrics: Arc<RwLock<PipelineMetrics>>,
     item_timings: Arc<RwLock<Vec<Duration>>>,
+    /// Microseconds from an item starting work to its result
+    item_latency: Arc<Mutex<Histogram<u64>>>,
+    stage1_price: f64,
+    stage2_price: f64,
+    /// The running totals as of the last [`snapshot_delta`](MetricsCollector::snapshot_delta)
+    delta_base: Arc<Mutex<PipelineMetrics>>,
 }
 
 impl MetricsCollector {
     pub fn new() -> Self {
+        Self::with_models(&ModelSelection::default())
+    }
+    
+    /// Price each stage's tokens for the model that stage runs.
+    pub fn with_models(models: &ModelSelection) -> Self {
+        let metrics = PipelineMetrics::default();
         Self {
-            metrics: Arc::new(RwLock::new(PipelineMetrics::default())),
+            delta_base: Arc::new(Mutex::new(metrics.clone())),
+            metrics: Arc::new(RwLock::new(metrics)),
             item_timings: Arc::new(RwLock::new(Vec::new())),
+            item_latency: Arc::new(Mutex::new(
+                Histogram::new_with_bounds(1, MAX_TRACKED_LATENCY.as_micros() as u64, 3)
+                    .expect("latency histogram bounds are valid")
+            )),
+            stage1_price: price_per_million_tokens(&models.stage1),
+            stage2_price: price_per_million_tokens(&models.stage2),
         }
     }
     
@@ -77,6 +193,27 @@ impl MetricsCollector {
         metrics.total_processing_time = Duration::from_millis(total_ms as u64);
     }
     
+    /// Record how long one item took once it held a concurrency permit.
+    pub fn record_item_latency(&self, latency: Duration) {
+        self.item_latency.lock().saturating_record(latency.as_micros() as u64);
+    }
+    
+    /// Percentiles of the latencies recorded since the last call, which
+    /// starts a fresh histogram.
+    pub fn take_latency_summary(&self) -> LatencySummary {
+        let mut histogram = self.item_latency.lock();
+        let to_ms = |micros: u64| micros as f64 / 1000.0;
+        let summary = LatencySummary {
+            samples: histogram.len(),
+            p50_ms: to_ms(histogram.value_at_quantile(0.50)),
+            p95_ms: to_ms(histogram.value_at_quantile(0.95)),
+            p99_ms: to_ms(histogram.value_at_quantile(0.99)),
+            max_ms: to_ms(histogram.max()),
+        };
+        histogram.reset();
+        summary
+    }
+    
     pub fn record_cache_hit(&self) {
         self.metrics.write().cache_hits += 1;
     }
@@ -85,16 +222,24 @@ impl MetricsCollector {
         self.metrics.write().cache_misses += 1;
     }
     
-    pub fn record_api_call(&self, tokens_used: usize) {
+    pub fn record_api_call(&self, stage: ApiStage, tokens_used: usize) {
         let mut metrics = self.metrics.write();
         metrics.api_calls += 1;
         metrics.api_tokens_used += tokens_used;
         
-        // Estimate cost (Claude Sonnet 4 pricing)
-        // Input: $3 per million tokens
-        // Output: $15 per million tokens
-        // Rough estimate: average $10 per million tokens
-        metrics.estimated_cost = (metrics.api_tokens_used as f64 / 1_000_000.0) * 10.0;
+        match stage {
+            ApiStage::Stage1 => {
+                metrics.stage1_calls += 1;
+                metrics.stage1_tokens += tokens_used;
+            }
+            ApiStage::Stage2 => {
+                metrics.stage2_calls += 1;
+                metrics.stage2_tokens += tokens_used;
+            }
+        }
+        
+        metrics.estimated_cost = (metrics.stage1_tokens as f64 * self.stage1_price
+            + metrics.stage2_tokens as f64 * self.stage2_price) / 1_000_000.0;
     }
     
     pub fn record_api_error(&self) {
@@ -105,6 +250,36 @@ impl MetricsCollector {
         self.metrics.write().rate_limit_hits += 1;
     }
     
+    pub fn record_batch_completed(&self) {
+        self.metrics.write().batches_completed += 1;
+    }
+    
+    /// Metrics recorded since the previous call, or since the collector was
+    /// created or reset, e.g. for one batch. The running totals are left
+    /// alone. While batches run concurrently, each delta also holds whatever
+    /// the others recorded in the meantime, but nothing is counted twice or
+    /// lost between deltas.
+    pub fn snapshot_delta(&self) -> PipelineMetrics {
+        // Recorders wait on the read lock until the base has moved on
+        let metrics = self.metrics.read();
+        let mut base = self.delta_base.lock();
+        let delta = metrics.since(&base);
+        *base = PipelineMetrics {
+            start_time: Utc::now(),
+            ..metrics.clone()
+        };
+        delta
+    }
+    
+    /// Zero every metric, as if the collector had just been created.
+    pub fn reset(&self) {
+        let mut metrics = self.metrics.write();
+        *metrics = PipelineMetrics::default();
+        self.item_timings.write().clear();
+        self.item_latency.lock().reset();
+        *self.delta_base.lock() = metrics.clone();
+    }
+    
     pub fn get_metrics(&self) -> PipelineMetrics {
         self.metrics.read().clone()
     }
@@ -139,7 +314,8 @@ impl MetricsCollector {
         info!("  Success rate: {:.1}%", success_rate);
         info!("  Cache hit rate: {:.1}%", cache_hit_rate);
         info!("  API calls made: {}", metrics.api_calls);
-        info!("  Tokens used: {}", metrics.api_tokens_used);
+        info!("  Tokens used: {} (stage 1: {}, stage 2: {})", 
+              metrics.api_tokens_used, metrics.stage1_tokens, metrics.stage2_tokens);
         info!("  Estimated cost: ${:.2}", metrics.estimated_cost);
         info!("  Average processing time: {:.0}ms", metrics.average_processing_time_ms);
         info!("  Total processing time: {:?}", metrics.total_processing_time);
@@ -160,6 +336,9 @@ pub struct HealthStatus {
     pub cache_status: ServiceStatus,
     pub api_status: ServiceStatus,
     pub python_bridge_status: ServiceStatus,
+    /// Items waiting in the processing queue, when the count could be read
+    pub queue_depth: Option<i64>,
+    pub queue_status: ServiceStatus,
     pub last_check: DateTime<Utc>,
 }
 
@@ -176,9 +355,36 @@ impl ServiceStatus {
     }
 }
 
+/// Queue depths at which the health check starts to complain, so an
+/// autoscaler can react to a backlog. Unset thresholds never trigger.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct QueueDepthThresholds {
+    /// Above this many pending items the queue is `Degraded`
+    pub warn_depth: Option<i64>,
+    /// Above this many pending items the queue is `Unhealthy`
+    pub fail_depth: Option<i64>,
+}
+
+impl QueueDepthThresholds {
+    pub fn status(&self, depth: i64) -> ServiceStatus {
+        match (self.warn_depth, self.fail_depth) {
+            (_, Some(fail)) if depth > fail => ServiceStatus::Unhealthy(
+                format!("{} items pending (fail above {})", depth, fail)
+            ),
+            (Some(warn), _) if depth > warn => ServiceStatus::Degraded(
+                format!("{} items pending (warn above {})", depth, warn)
+            ),
+            _ => ServiceStatus::Healthy,
+        }
+    }
+}
+
 pub struct HealthChecker {
     cache_repo: Arc<dyn CacheRepository>,
     queue_repo: Arc<dyn QueueRepository>,
+    check_api: bool,
+    queue_thresholds: QueueDepthThresholds,
 }
 
 impl HealthChecker {
@@ -189,9 +395,24 @@ impl HealthChecker {
         Self {
             cache_repo,
             queue_repo,
+            check_api: true,
+            queue_thresholds: QueueDepthThresholds::default(),
         }
     }
     
+    /// Report the queue as degraded or unhealthy once it backs up.
+    pub fn with_queue_thresholds(mut self, thresholds: QueueDepthThresholds) -> Self {
+        self.queue_thresholds = thresholds;
+        self
+    }
+    
+    /// Only probe the database and cache, for offline runs that never
+    /// reach the API.
+    pub fn without_api_checks(mut self) -> Self {
+        self.check_api = false;
+        self
+    }
+    
     #[instrument(skip(self))]
     pub async fn check_health(&self) -> Result<HealthStatus> {
         debug!("Running health check");
@@ -202,6 +423,8 @@ impl HealthChecker {
             cache_status: ServiceStatus::Healthy,
             api_status: ServiceStatus::Healthy,
             python_bridge_status: ServiceStatus::Healthy,
+            queue_depth: None,
+            queue_status: ServiceStatus::Healthy,
             last_check: Utc::now(),
         };
         
@@ -227,9 +450,30 @@ impl HealthChecker {
             }
         }
         
+        // Check queue backlog
+        match self.queue_depth().await {
+            Ok(depth) => {
+                debug!("Queue depth: {}", depth);
+                status.queue_depth = Some(depth);
+                status.queue_status = self.queue_thresholds.status(depth);
+                if matches!(status.queue_status, ServiceStatus::Unhealthy(_)) {
+                    status.healthy = false;
+                }
+            }
+            Err(e) => {
+                status.queue_status = ServiceStatus::Unhealthy(e.to_string());
+                status.healthy = false;
+            }
+        }
+        
         // Check Python bridge
+        if !self.check_api {
+            status.python_bridge_status = ServiceStatus::Degraded("skipped in offline mode".to_string());
+            status.api_status = ServiceStatus::Degraded("skipped in offline mode".to_string());
+        }
+        
         #[cfg(feature = "python")]
-        {
+        if self.check_api {
             match self.check_python_bridge().await {
                 Ok(_) => {
                     debug!("Python bridge health check passed");
@@ -253,6 +497,11 @@ impl HealthChecker {
         Ok(())
     }
     
+    /// Items waiting in the processing queue, across all batches
+    pub async fn queue_depth(&self) -> Result<i64> {
+        Ok(self.queue_repo.count_pending().await?)
+    }
+    
     async fn check_cache(&self) -> Result<()> {
         // Try to get cache stats
         self.cache_repo.get_cache_stats().await?;
@@ -286,17 +535,23 @@ impl PipelineMetrics {
         output.push_str("# TYPE pipeline_items_failed counter\n");
         output.push_str(&format!("pipeline_items_failed {}\n", self.items_failed));
         
+        output.push_str("# HELP pipeline_batches_total Total number of batches processed\n");
+        output.push_str("# TYPE pipeline_batches_total counter\n");
+        output.push_str(&format!("pipeline_batches_total {}\n", self.batches_completed));
+        
         output.push_str("# HELP pipeline_cache_hits Total number of cache hits\n");
         output.push_str("# TYPE pipeline_cache_hits counter\n");
         output.push_str(&format!("pipeline_cache_hits {}\n", self.cache_hits));
         
         output.push_str("# HELP pipeline_api_calls Total number of API calls made\n");
         output.push_str("# TYPE pipeline_api_calls counter\n");
-        output.push_str(&format!("pipeline_api_calls {}\n", self.api_calls));
+        output.push_str(&format!("pipeline_api_calls{{stage=\"stage1\"}} {}\n", self.stage1_calls));
+        output.push_str(&format!("pipeline_api_calls{{stage=\"stage2\"}} {}\n", self.stage2_calls));
         
         output.push_str("# HELP pipeline_api_tokens_used Total number of tokens used\n");
         output.push_str("# TYPE pipeline_api_tokens_used counter\n");
-        output.push_str(&format!("pipeline_api_tokens_used {}\n", self.api_tokens_used));
+        output.push_str(&format!("pipeline_api_tokens_used{{stage=\"stage1\"}} {}\n", self.stage1_tokens));
+        output.push_str(&format!("pipeline_api_tokens_used{{stage=\"stage2\"}} {}\n", self.stage2_tokens));
         
         output.push_str("# HELP pipeline_estimated_cost_dollars Estimated cost in dollars\n");
         output.push_str("# TYPE pipeline_estimated_cost_dollars gauge\n");
@@ -308,4 +563,98 @@ impl PipelineMetrics {
         
         output
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_api_calls_tracked_per_stage() {
+        let collector = MetricsCollector::new();
+        collector.record_api_call(ApiStage::Stage1, 300);
+        collector.record_api_call(ApiStage::Stage2, 1200);
+        collector.record_api_call(ApiStage::Stage2, 800);
+        
+        let metrics = collector.get_metrics();
+        assert_eq!(metrics.api_calls, 3);
+        assert_eq!(metrics.api_tokens_used, 2300);
+        assert_eq!((metrics.stage1_calls, metrics.stage1_tokens), (1, 300));
+        assert_eq!((metrics.stage2_calls, metrics.stage2_tokens), (2, 2000));
+        
+        let output = metrics.to_prometheus_format();
+        assert!(output.contains("pipeline_api_tokens_used{stage=\"stage1\"} 300\n"));
+        assert!(output.contains("pipeline_api_tokens_used{stage=\"stage2\"} 2000\n"));
+    }
+    
+    #[test]
+    fn test_batch_deltas_sum_to_running_totals() {
+        let collector = MetricsCollector::new();
+        let run_batch = |items: usize, failed: usize, tokens: usize| {
+            for i in 0..items {
+                collector.record_item_processed(i >= failed, Duration::from_millis(100));
+                collector.record_api_call(ApiStage::Stage1, tokens);
+            }
+            collector.record_cache_hit();
+            collector.record_batch_completed();
+            collector.snapshot_delta()
+        };
+        
+        let first = run_batch(3, 1, 100);
+        let second = run_batch(5, 0, 200);
+        
+        assert_eq!((first.items_processed, first.items_failed, first.api_tokens_used), (3, 1, 300));
+        assert_eq!((second.items_processed, second.items_failed, second.api_tokens_used), (5, 0, 1000));
+        assert_eq!((first.batches_completed, second.batches_completed), (1, 1));
+        
+        let total = collector.get_metrics();
+        assert_eq!(total.items_processed, first.items_processed + second.items_processed);
+        assert_eq!(total.items_succeeded, first.items_succeeded + second.items_succeeded);
+        assert_eq!(total.api_calls, first.api_calls + second.api_calls);
+        assert_eq!(total.api_tokens_used, 1300);
+        assert_eq!(total.cache_hits, 2);
+        assert!((total.estimated_cost - (first.estimated_cost + second.estimated_cost)).abs() < 1e-9);
+        assert!(total.to_prometheus_format().contains("pipeline_batches_total 2\n"));
+        
+        // Nothing recorded since the last delta
+        assert_eq!(collector.snapshot_delta().items_processed, 0);
+        
+        collector.reset();
+        let total = collector.get_metrics();
+        assert_eq!((total.items_processed, total.batches_completed), (0, 0));
+        assert_eq!(total.average_processing_time_ms, 0.0);
+    }
+    
+    #[test]
+    fn test_cost_uses_each_stage_model() {
+        let collector = MetricsCollector::with_models(&ModelSelection {
+            stage1: "anthropic/claude-3.5-haiku".to_string(),
+            stage2: "anthropic/claude-3-opus".to_string(),
+        });
+        collector.record_api_call(ApiStage::Stage1, 1_000_000);
+        collector.record_api_call(ApiStage::Stage2, 1_000_000);
+        
+        let metrics = collector.get_metrics();
+        assert!((metrics.estimated_cost - (2.4 + 45.0)).abs() < 1e-9);
+    }
+    
+    #[test]
+    fn test_queue_depth_thresholds() {
+        let thresholds = QueueDepthThresholds {
+            warn_depth: Some(100),
+            fail_depth: Some(1000),
+        };
+        
+        assert!(thresholds.status(0).is_healthy());
+        assert!(thresholds.status(100).is_healthy());
+        assert!(matches!(thresholds.status(101), ServiceStatus::Degraded(_)));
+        assert!(matches!(thresholds.status(5000), ServiceStatus::Unhealthy(_)));
+        
+        // Without thresholds a backlog is only reported
+        assert!(QueueDepthThresholds::default().status(5000).is_healthy());
+        
+        let fail_only = QueueDepthThresholds { fail_depth: Some(10), ..Default::default() };
+        assert!(fail_only.status(10).is_healthy());
+        assert!(matches!(fail_only.status(11), ServiceStatus::Unhealthy(_)));
+    }
+}
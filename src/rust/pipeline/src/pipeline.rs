@@ -1,20 +1,56 @@
 use crate::errors::{PipelineError, Result};
-use crate::batch_processor::{BatchProcessor, BatchResult};
-use crate::export::{TsvExporter, ExportStats};
-use crate::monitoring::{MetricsCollector, HealthChecker};
-use crate::python_bridge::{ApiClient, create_api_client};
+use crate::batch_processor::{BatchProcessor, BatchResult, FailureRecord, FailureThreshold, Prefetched, DEFAULT_RATE_SMOOTHING};
+use crate::report::RunReport;
+use crate::anki::AnkiPreset;
+use crate::export::{
+    Exporter, ExportFormat, TsvExporter, JsonExporter, MnemonicExporter, ExportStats, MemoryPalaceColumns, LineEnding,
+    SortOrder,
+    LIMITABLE_FIELDS,
+    write_error_report, default_error_report_path, default_stats_path,
+};
+use crate::sink::{batch_output_path, is_output_dir, open_sink};
+use crate::monitoring::{MetricsCollector, HealthChecker, PipelineMetrics, QueueDepthThresholds};
+use crate::concurrency::{AbortOnDrop, AdaptiveConcurrencyController, AdaptiveConcurrencyConfig};
+use crate::retry::RetryPolicy;
+use crate::quality::QualityGate;
+use crate::audit::ApiAudit;
+use crate::config::ConfigFile;
+use crate::transform::TransformChain;
+use crate::shutdown::{ShutdownCoordinator, DEFAULT_SHUTDOWN_GRACE_PERIOD};
+use crate::tokens::{TokenEstimate, TokenEstimator};
+use crate::input::{
+    read_vocabulary_csv, read_vocabulary_jsonl, read_vocabulary_files, InputFileSummary, InputFormat, MergedInput,
+};
+use crate::python_bridge::{ApiClient, ModelSelection, OfflineApiClient, PromptPaths, DEFAULT_MODEL, create_configured_api_client};
 use flashcard_core::{
-    models::VocabularyItem,
-    database::DatabasePool,
-    repositories::{VocabularyRepository, CacheRepository, QueueRepository},
-    cache_manager::CacheManager,
+    models::{
+        VocabularyItem, Stage1Result, Stage2Result, Stage2Diff, CacheEntry, CacheImportStats, CacheMigrationStats,
+        KeyNormalization, ForceRefresh, Stage2Mode, CacheType, BatchId, BatchDiff, ApiCallRecord, CardState,
+        CacheStatsSnapshot,
+    },
+    database::{ConnectionLimiter, SqlitePragmas, MAX_CONNECTIONS, create_pool_with},
+    database::migrations::{run_migrations_with_options, MigrationOptions},
+    repositories::{VocabularyRepository, CacheRepository, QueueRepository, ApiCallLogRepository, CardStateRepository},
+    cache_manager::{CacheManager, CacheWarmupStats, WarmupOptions},
+    term_normalizer::TermNormalizer,
+    difficulty::DifficultyEstimator,
 };
 use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::collections::{BTreeMap, HashSet, VecDeque};
 use std::path::{Path, PathBuf};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
 use tracing::{info, warn, error, instrument};
-use csv::ReaderBuilder;
 use std::fs::File;
 use parking_lot::RwLock;
+use tokio::sync::mpsc;
+use futures::stream::{FuturesUnordered, StreamExt};
+use tokio_util::sync::CancellationToken;
+
+/// Pool connections left free of batch processing by default, for queries
+/// outside it such as health checks and exports
+pub const DB_CONNECTION_HEADROOM: usize = 2;
 
 pub struct Pipeline {
     api_client: Arc<dyn ApiClient>,
@@ -22,74 +58,528 @@ pub struct Pipeline {
     vocab_repo: Arc<dyn VocabularyRepository>,
     cache_repo: Arc<dyn CacheRepository>,
     queue_repo: Arc<dyn QueueRepository>,
+    api_call_log: Arc<ApiCallLogRepository>,
+    card_states: Arc<CardStateRepository>,
     batch_processor: Arc<BatchProcessor>,
     pub metrics_collector: Arc<MetricsCollector>,
     pub health_checker: Arc<HealthChecker>,
+    /// The adaptive concurrency controller, with
+    /// [`PipelineConfig::adaptive_concurrency`]; one for every batch this
+    /// pipeline runs, stopped when the pipeline is dropped
+    adaptive_concurrency: AbortOnDrop,
+    /// Connection slots the cache manager and batch processor share
+    db_limiter: ConnectionLimiter,
+    /// Fills in the difficulty of loaded items, with
+    /// [`PipelineConfig::frequency_list`] when one is set
+    difficulty: DifficultyEstimator,
+    /// Prompt token estimates for the configured models and templates
+    tokens: TokenEstimator,
     config: PipelineConfig,
 }
 
-#[derive(Clone)]
+/// Settings for a [`Pipeline`]. Also the `[pipeline]` table of a config
+/// file; see [`crate::config`].
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
 pub struct PipelineConfig {
     pub database_url: String,
+    /// Pragmas set on each database connection, e.g. a smaller `mmap_size`
+    /// on a memory-constrained host
+    pub sqlite: SqlitePragmas,
     pub cache_dir: PathBuf,
+    /// Items in flight at once, including cache lookups and DB writes
     pub max_concurrent: usize,
+    /// Live API calls allowed at once; `None` leaves only `max_concurrent`
+    pub api_concurrency: Option<usize>,
+    /// Cache queries and queue writes in flight at once across items, so
+    /// `max_concurrent` can exceed the connection pool; `None` uses the pool
+    /// size less [`DB_CONNECTION_HEADROOM`]
+    pub db_concurrency: Option<usize>,
+    /// Queued batches [`Pipeline::process_queued_batches`] runs at once.
+    /// They share the item and API permits, so running more batches doesn't
+    /// raise total concurrency
+    pub max_concurrent_batches: usize,
+    /// Items per chunk; each chunk finishes and is checkpointed before the
+    /// next starts (0 processes everything at once)
     pub batch_size: usize,
+    /// Chunks after the running one whose cache lookups start in the
+    /// background, so cached items of the next chunk are ready when it
+    /// starts (0 looks items up only once their chunk runs)
+    pub prefetch_chunks: usize,
     pub enable_metrics: bool,
     pub checkpoint_interval: usize,
+    /// Retries after an item's first attempt before it is quarantined
+    pub max_retries: u32,
+    /// Wall-clock time one item may spend on API attempts and retry waits
+    /// before it fails, whatever retries it has left
+    #[serde(with = "crate::config::optional_humantime_duration")]
+    pub per_item_budget: Option<Duration>,
+    /// Where to write failed items; defaults to `<output>.errors.csv`
+    pub error_report_path: Option<PathBuf>,
+    /// Where to write aggregate card statistics as JSON after each export
+    pub stats_path: Option<PathBuf>,
+    /// Without `stats_path`, write the statistics beside each run's output,
+    /// as `<output>.stats.json`
+    pub write_stats: bool,
+    /// Resize concurrency at runtime from cache-hit and error rates
+    pub adaptive_concurrency: bool,
+    pub min_concurrency: usize,
+    pub max_concurrency: usize,
+    pub target_error_rate: f64,
+    /// Append cards to the output file as they complete instead of
+    /// exporting once the whole batch has finished. Streamed cards aren't
+    /// linked to each other, so their `related_cards` stay empty
+    pub stream_export: bool,
+    /// With `stream_export`, the most finished cards held in memory; once
+    /// that many are held they are dropped, having been written already,
+    /// and only counted. Unbounded when unset
+    pub max_buffered_results: Option<usize>,
+    /// Text normalization applied before hashing cache keys
+    pub key_normalization: KeyNormalization,
+    /// Free-form tag stored with new batches, e.g. a customer name
+    pub batch_label: Option<String>,
+    /// Write a header row at the top of the export
+    pub include_headers: bool,
+    /// Recompute cached entries whose stored request hash no longer matches
+    pub respect_request_hash: bool,
+    /// Lay the export out for this Anki note type
+    pub anki_preset: Option<AnkiPreset>,
+    /// Model the orchestrator runs unless a stage overrides it
+    pub model: String,
+    /// Model for Stage 1 semantic analysis, e.g. a cheaper one
+    pub stage1_model: Option<String>,
+    /// Model for Stage 2 card generation
+    pub stage2_model: Option<String>,
+    /// Stage 1 prompt template to use instead of the orchestrator's own
+    pub stage1_prompt_path: Option<PathBuf>,
+    /// Stage 2 prompt template to use instead of the orchestrator's own
+    pub stage2_prompt_path: Option<PathBuf>,
+    /// Weight of the newest completion in the progress rate and ETA
+    pub rate_smoothing: f64,
+    /// Threads used to format export rows; 0 means one per core
+    pub export_threads: usize,
+    /// Build cards from the cache only; uncached items are skipped and the
+    /// API is never contacted
+    pub cache_only: bool,
+    /// How long the startup health check may take before the run aborts
+    #[serde(with = "crate::config::humantime_duration")]
+    pub health_check_timeout: Duration,
+    /// Wait for the `retry_after` a rate limit names instead of backing off
+    pub honor_retry_after: bool,
+    /// Emit a basic card from Stage 1 when Stage 2 fails or isn't cached
+    pub stage1_fallback: bool,
+    /// Minimums a Stage 1 analysis must meet; items falling short are
+    /// quarantined for review instead of reaching Stage 2
+    pub quality_gate: QualityGate,
+    /// Strip trailing particles from input terms before processing
+    #[serde(rename = "strip_particles", with = "crate::config::strip_particles")]
+    pub term_normalizer: Option<TermNormalizer>,
+    /// Word list, one per line and most common first, that difficulty
+    /// estimates for unlabeled items weigh common words against
+    pub frequency_list: Option<PathBuf>,
+    /// Rewrites applied to each card, in order, before it is exported.
+    /// Built from flags, so not read from config files
+    #[serde(skip)]
+    pub transforms: TransformChain,
+    /// Pending-queue sizes at which health checks degrade or fail
+    pub queue_thresholds: QueueDepthThresholds,
+    /// Layout of the input files passed to `process_csv_files`
+    pub input_format: InputFormat,
+    /// Lines of input CSVs starting with this byte are skipped
+    #[serde(with = "crate::config::comment_char")]
+    pub csv_comment: Option<u8>,
+    /// Export homonyms and comparison terms from Stage 1 as extra columns
+    pub comparison_columns: bool,
+    /// Export Stage 1's memory-palace fields as extra columns
+    pub memory_palace: Option<MemoryPalaceColumns>,
+    /// Order exported rows are written in; streamed exports are written in
+    /// the order cards finish
+    pub sort_by: SortOrder,
+    /// Start even if an applied migration's SQL has changed since
+    pub allow_migration_drift: bool,
+    /// Stages whose cached results are recomputed instead of read
+    pub force_refresh: ForceRefresh,
+    /// Export only term, meaning, mnemonic and metaphor, skipping cards
+    /// without a mnemonic. Not supported with streaming export.
+    pub mnemonics_only: bool,
+    /// Whether Stage 2 generates every card field or only the essentials
+    pub stage2_mode: Stage2Mode,
+    /// Record every API call, with its model, tokens and latency, to the
+    /// `api_call_log` table. Costs a database write per call
+    pub audit_api_calls: bool,
+    /// Longest exported column, in grapheme clusters; longer ones are cut
+    /// and end with an ellipsis
+    pub max_field_chars: Option<usize>,
+    /// Per card field limits, e.g. `cultural_notes = 200`, applied before
+    /// `max_field_chars`
+    pub field_char_limits: BTreeMap<String, usize>,
+    /// Tags added to every exported card
+    pub extra_tags: Vec<String>,
+    /// Also tag every card with its batch id and label, the export date and
+    /// its source file name
+    pub auto_tags: bool,
+    /// Abort the batch once this many items in a row have failed
+    pub max_consecutive_failures: Option<usize>,
+    /// Abort the batch once more than this fraction of its items failed,
+    /// checked from the 20th finished item on
+    pub max_failure_rate: Option<f64>,
+    /// Cache namespace, e.g. a customer name, so runs for different tenants
+    /// sharing one database never read each other's cached results
+    pub namespace: String,
+    /// Log and skip cards that fail to export instead of failing the
+    /// export; they are listed in [`ExportStats::skipped_cards`]
+    pub skip_export_errors: bool,
+    /// Prefix exported fields a spreadsheet would run as formulas with
+    /// this, e.g. `'`; see [`TsvExporter::with_formula_guard`]
+    pub formula_guard: Option<String>,
+    /// How exported lines end
+    pub line_ending: LineEnding,
+    /// Start exports with a UTF-8 byte order mark
+    pub write_bom: bool,
+    /// Key cache entries by the model each stage runs, so switching models
+    /// recomputes instead of serving another model's results
+    pub pin_model_in_key: bool,
+    /// Log each cache lookup's decision and its reason, e.g. a Stage 2 miss
+    /// because its request hash changed
+    pub explain_cache: bool,
+    /// Record a cache stats snapshot after each run, for `cache-growth`
+    pub record_cache_stats: bool,
+    /// How long items in flight may take to finish after a shutdown signal
+    /// before they are interrupted; see [`ShutdownCoordinator`]
+    #[serde(with = "crate::config::humantime_duration")]
+    pub shutdown_grace_period: Duration,
 }
 
 impl Default for PipelineConfig {
     fn default() -> Self {
         Self {
             database_url: "sqlite:pipeline.db".to_string(),
+            sqlite: SqlitePragmas::default(),
             cache_dir: PathBuf::from(".cache"),
             max_concurrent: 5,
+            api_concurrency: None,
+            db_concurrency: None,
+            max_concurrent_batches: 1,
             batch_size: 10,
+            prefetch_chunks: 0,
             enable_metrics: true,
             checkpoint_interval: 10,
+            max_retries: flashcard_core::models::DEFAULT_MAX_RETRIES as u32,
+            per_item_budget: None,
+            error_report_path: None,
+            stats_path: None,
+            write_stats: false,
+            adaptive_concurrency: false,
+            min_concurrency: 2,
+            max_concurrency: 20,
+            target_error_rate: 0.05,
+            stream_export: false,
+            max_buffered_results: None,
+            key_normalization: KeyNormalization::default(),
+            batch_label: None,
+            include_headers: true,
+            respect_request_hash: false,
+            anki_preset: None,
+            model: DEFAULT_MODEL.to_string(),
+            stage1_model: None,
+            stage2_model: None,
+            stage1_prompt_path: None,
+            stage2_prompt_path: None,
+            rate_smoothing: DEFAULT_RATE_SMOOTHING,
+            export_threads: 1,
+            cache_only: false,
+            health_check_timeout: Duration::from_secs(30),
+            honor_retry_after: true,
+            stage1_fallback: false,
+            quality_gate: QualityGate::default(),
+            term_normalizer: None,
+            frequency_list: None,
+            transforms: TransformChain::new(),
+            queue_thresholds: QueueDepthThresholds::default(),
+            input_format: InputFormat::default(),
+            csv_comment: Some(crate::input::DEFAULT_COMMENT_CHAR),
+            comparison_columns: false,
+            memory_palace: None,
+            sort_by: SortOrder::default(),
+            allow_migration_drift: false,
+            force_refresh: ForceRefresh::default(),
+            mnemonics_only: false,
+            stage2_mode: Stage2Mode::default(),
+            audit_api_calls: false,
+            max_field_chars: None,
+            field_char_limits: BTreeMap::new(),
+            extra_tags: Vec::new(),
+            auto_tags: false,
+            max_consecutive_failures: None,
+            max_failure_rate: None,
+            namespace: flashcard_core::models::DEFAULT_NAMESPACE.to_string(),
+            skip_export_errors: false,
+            formula_guard: None,
+            line_ending: LineEnding::default(),
+            write_bom: false,
+            pin_model_in_key: false,
+            explain_cache: false,
+            record_cache_stats: false,
+            shutdown_grace_period: DEFAULT_SHUTDOWN_GRACE_PERIOD,
+        }
+    }
+}
+
+impl PipelineConfig {
+    /// Read the `[pipeline]` table of the config file at `path`; settings it
+    /// leaves out keep their defaults.
+    pub fn from_file(path: &Path) -> Result<Self> {
+        Ok(ConfigFile::load(path)?.pipeline)
+    }
+    
+    /// Reject settings that can't work together or are out of range.
+    pub fn validate(&self) -> Result<()> {
+        let invalid = |message: &str| Err(PipelineError::ConfigError(message.to_string()));
+        
+        if self.max_concurrent == 0 {
+            return invalid("max_concurrent must be at least 1");
+        }
+        if self.api_concurrency == Some(0) {
+            return invalid("api_concurrency must be at least 1");
+        }
+        if let Some(limit) = self.db_concurrency {
+            if limit == 0 || limit > MAX_CONNECTIONS as usize {
+                return Err(PipelineError::ConfigError(format!(
+                    "db_concurrency must be between 1 and the pool's {} connections", MAX_CONNECTIONS
+                )));
+            }
+        }
+        self.sqlite.validate().map_err(PipelineError::Core)?;
+        if self.max_concurrent_batches == 0 {
+            return invalid("max_concurrent_batches must be at least 1");
+        }
+        if self.adaptive_concurrency && self.max_concurrent_batches > 1 {
+            return invalid("adaptive_concurrency can't be combined with max_concurrent_batches above 1");
+        }
+        if self.min_concurrency == 0 || self.min_concurrency > self.max_concurrency {
+            return invalid("min_concurrency must be between 1 and max_concurrency");
+        }
+        if !(0.0..=1.0).contains(&self.target_error_rate) {
+            return invalid("target_error_rate must be between 0 and 1");
+        }
+        if !(0.0..=1.0).contains(&self.rate_smoothing) {
+            return invalid("rate_smoothing must be between 0 and 1");
+        }
+        // The queue stores it as a signed integer
+        if i32::try_from(self.max_retries).is_err() {
+            return invalid("max_retries is too large");
+        }
+        if self.per_item_budget == Some(Duration::ZERO) {
+            return invalid("per_item_budget must be longer than zero");
+        }
+        if self.mnemonics_only && self.stream_export {
+            return invalid("mnemonics_only can't be combined with stream_export");
+        }
+        if self.sort_by == SortOrder::LearningOrder && self.stream_export {
+            return invalid("sort_by learning-order can't be combined with stream_export");
+        }
+        if let Some(max) = self.max_buffered_results {
+            if max == 0 {
+                return invalid("max_buffered_results must be at least 1");
+            }
+            if !self.stream_export {
+                return invalid("max_buffered_results needs stream_export, since cards are otherwise exported from memory");
+            }
+        }
+        if self.cache_only && (self.force_refresh.stage1 || self.force_refresh.stage2) {
+            return invalid("force_refresh needs the API, so it can't be combined with cache_only");
+        }
+        if self.extra_tags.iter().any(|tag| tag.trim().is_empty()) {
+            return invalid("extra_tags can't contain an empty tag");
+        }
+        if self.max_consecutive_failures == Some(0) {
+            return invalid("max_consecutive_failures must be at least 1");
+        }
+        if self.max_failure_rate.is_some_and(|rate| !(0.0..1.0).contains(&rate)) {
+            return invalid("max_failure_rate must be at least 0 and below 1");
+        }
+        if self.namespace.contains('\0') {
+            return invalid("namespace must not contain NUL characters");
+        }
+        if self.max_field_chars == Some(0) {
+            return invalid("max_field_chars must be at least 1");
+        }
+        for (field, &max) in &self.field_char_limits {
+            if !LIMITABLE_FIELDS.contains(&field.as_str()) {
+                return Err(PipelineError::ConfigError(format!(
+                    "Unknown field '{}' in field_char_limits; expected one of: {}",
+                    field,
+                    LIMITABLE_FIELDS.join(", ")
+                )));
+            }
+            if max == 0 {
+                return Err(PipelineError::ConfigError(format!(
+                    "The limit for {} must be at least 1", field
+                )));
+            }
+        }
+        Ok(())
+    }
+    
+    /// Cache lookups and saves allowed in flight at once across items.
+    pub fn db_concurrency(&self) -> usize {
+        self.db_concurrency
+            .unwrap_or(MAX_CONNECTIONS as usize - DB_CONNECTION_HEADROOM)
+    }
+    
+    /// Retry schedule for API calls, sharing the per-item retry budget.
+    pub fn retry_policy(&self) -> RetryPolicy {
+        RetryPolicy {
+            max_retries: self.max_retries,
+            honor_retry_after: self.honor_retry_after,
+            per_item_budget: self.per_item_budget,
+            ..Default::default()
+        }
+    }
+    
+    /// When a batch gives up on a failing API.
+    pub fn failure_threshold(&self) -> FailureThreshold {
+        FailureThreshold {
+            max_consecutive_failures: self.max_consecutive_failures,
+            max_failure_rate: self.max_failure_rate,
+        }
+    }
+    
+    /// The model each stage runs, after per-stage overrides.
+    pub fn models(&self) -> ModelSelection {
+        ModelSelection {
+            stage1: self.stage1_model.clone().unwrap_or_else(|| self.model.clone()),
+            stage2: self.stage2_model.clone().unwrap_or_else(|| self.model.clone()),
+        }
+    }
+    
+    /// The prompt templates chosen for each stage.
+    pub fn prompts(&self) -> PromptPaths {
+        PromptPaths {
+            stage1: self.stage1_prompt_path.clone(),
+            stage2: self.stage2_prompt_path.clone(),
         }
     }
 }
 
 impl Pipeline {
     pub async fn new(config: PipelineConfig) -> Result<Self> {
+        // Create API client; its on-disk response cache lives in cache_dir
+        let api_client: Arc<dyn ApiClient> = if config.cache_only {
+            info!("Offline mode: serving from cache only");
+            Arc::new(OfflineApiClient)
+        } else {
+            prepare_cache_dir(&config.cache_dir)?;
+            Arc::from(create_configured_api_client(&config.cache_dir, config.models(), config.prompts())?)
+        };
+        
+        Self::with_api_client(config, api_client).await
+    }
+    
+    /// Build a pipeline that calls `api_client` instead of the one `config`
+    /// selects, e.g. a mock for benchmarks.
+    pub async fn with_api_client(config: PipelineConfig, api_client: Arc<dyn ApiClient>) -> Result<Self> {
         info!("Initializing pipeline with config");
         
         // Create database pool
-        let pool = DatabasePool::new(&config.database_url).await
+        let pool = create_pool_with(&config.database_url, config.sqlite).await
             .map_err(|e| PipelineError::Core(e))?;
         
-        // Run migrations
-        pool.run_migrations().await
+        // Run migrations, refusing to start if a shipped one was edited
+        let migration_options = MigrationOptions {
+            allow_drift: config.allow_migration_drift,
+        };
+        run_migrations_with_options(&pool, migration_options).await
             .map_err(|e| PipelineError::Core(e))?;
         
         // Create repositories
         let vocab_repo = Arc::new(flashcard_core::database::repositories::SqliteVocabularyRepository::new(pool.clone()));
         let cache_repo = Arc::new(flashcard_core::database::repositories::SqliteCacheRepository::new(pool.clone()));
         let queue_repo = Arc::new(flashcard_core::database::repositories::SqliteQueueRepository::new(pool.clone()));
+        let api_call_log = Arc::new(ApiCallLogRepository::new(pool.clone()));
+        let card_states = Arc::new(CardStateRepository::new(pool.clone()));
         
-        // Create cache manager
-        let cache_manager = Arc::new(CacheManager::new(
-            cache_repo.clone(),
-            config.cache_dir.clone(),
-        ));
-        
-        // Create API client
-        let api_client = create_api_client()?;
+        // Create cache manager; results from a custom prompt are kept apart
+        // from those of the built-in one and of other versions of it
+        let (stage1_prompt, stage2_prompt) = config.prompts().versions()?;
+        let (stage1_model, stage2_model) = if config.pin_model_in_key {
+            let models = config.models();
+            (Some(models.stage1), Some(models.stage2))
+        } else {
+            (None, None)
+        };
+        let db_limiter = ConnectionLimiter::new(config.db_concurrency());
+        let cache_manager = Arc::new(CacheManager::new(cache_repo.clone())
+            .with_key_normalization(config.key_normalization)
+            .with_respect_request_hash(config.respect_request_hash)
+            .with_force_refresh(config.force_refresh)
+            .with_stage2_mode(config.stage2_mode)
+            .with_namespace(config.namespace.clone())
+            .with_prompt_versions(stage1_prompt, stage2_prompt)
+            .with_pinned_models(stage1_model, stage2_model)
+            .with_request_models(config.models().stage1, config.models().stage2)
+            .with_connection_limiter(db_limiter.clone())
+            .with_explain(config.explain_cache));
         
         // Create components
-        let metrics_collector = Arc::new(MetricsCollector::new());
-        let health_checker = Arc::new(HealthChecker::new(
+        let metrics_collector = Arc::new(MetricsCollector::with_models(&config.models()));
+        let health_checker = HealthChecker::new(
             cache_repo.clone(),
             queue_repo.clone(),
-        ));
+        )
+        .with_queue_thresholds(config.queue_thresholds);
+        let health_checker = Arc::new(if config.cache_only {
+            health_checker.without_api_checks()
+        } else {
+            health_checker
+        });
+        
+        // Adaptive runs start low and let the controller raise the ceiling
+        let initial_concurrency = if config.adaptive_concurrency {
+            config.min_concurrency
+        } else {
+            config.max_concurrent
+        };
         
-        let batch_processor = Arc::new(BatchProcessor::new(
+        let mut batch_processor = BatchProcessor::new(
             api_client.clone(),
             cache_manager.clone(),
             queue_repo.clone(),
-            config.max_concurrent,
-        ));
+            metrics_collector.clone(),
+            initial_concurrency,
+        )
+        .with_rate_smoothing(config.rate_smoothing)
+        .with_retry_policy(config.retry_policy())
+        .with_stage1_fallback(config.stage1_fallback)
+        .with_quality_gate(config.quality_gate)
+        .with_stage2_mode(config.stage2_mode)
+        .with_models(config.models())
+        .with_failure_threshold(config.failure_threshold())
+        .with_connection_limiter(db_limiter.clone());
+        if let Some(max_calls) = config.api_concurrency {
+            batch_processor = batch_processor.with_api_concurrency(max_calls);
+        }
+        if config.audit_api_calls {
+            batch_processor = batch_processor.with_audit(ApiAudit::new(Arc::clone(&api_call_log), config.models()));
+        }
+        if let Some(max) = config.max_buffered_results {
+            batch_processor = batch_processor.with_max_buffered_results(max);
+        }
+        let batch_processor = Arc::new(batch_processor);
+        
+        let difficulty = match &config.frequency_list {
+            Some(path) => DifficultyEstimator::from_frequency_file(path).map_err(|e| match e.kind() {
+                std::io::ErrorKind::NotFound => PipelineError::FileNotFound(path.clone()),
+                _ => PipelineError::ConfigError(format!("frequency list {}: {}", path.display(), e)),
+            })?,
+            None => DifficultyEstimator::new(),
+        };
+        let tokens = TokenEstimator::from_config(&config)?;
+        
+        let mut adaptive_concurrency = AbortOnDrop::default();
+        if config.adaptive_concurrency {
+            adaptive_concurrency.push(&start_adaptive_concurrency(&config, &batch_processor, &metrics_collector));
+        }
         
         Ok(Self {
             api_client,
@@ -97,131 +587,593 @@ impl Pipeline {
             vocab_repo,
             cache_repo,
             queue_repo,
+            api_call_log,
+            card_states,
             batch_processor,
             metrics_collector,
             health_checker,
+            adaptive_concurrency,
+            db_limiter,
+            difficulty,
+            tokens,
             config,
         })
     }
     
-    #[instrument(skip(self))]
     pub async fn process_csv_file(
         &self,
         input_path: &Path,
         output_path: &Path,
-        resume_batch_id: Option<i32>,
+        resume_batch_id: Option<&BatchId>,
+    ) -> Result<ProcessingResult> {
+        self.process_csv_files(&[input_path.to_path_buf()], output_path, resume_batch_id).await
+    }
+    
+    /// Process several input files as one batch, with positions offset per
+    /// file (see [`read_vocabulary_files`]). They are read as CSV or JSONL
+    /// according to [`PipelineConfig::input_format`]. Files that can't be
+    /// read are listed in [`ProcessingResult::input_files`] with their error.
+    ///
+    /// When `output_path` is a directory (see [`is_output_dir`]) the cards go
+    /// to a new file in it named after the batch and the time; the file used
+    /// is [`ProcessingResult::output_path`].
+    ///
+    /// Input with no items is an error, but resuming a batch that has
+    /// nothing left is not: it returns a result with zero counts and the
+    /// batch's size in [`ProcessingResult::already_processed`], and leaves
+    /// `output_path` alone.
+    #[instrument(skip(self))]
+    pub async fn process_csv_files(
+        &self,
+        input_paths: &[PathBuf],
+        output_path: &Path,
+        resume_batch_id: Option<&BatchId>,
     ) -> Result<ProcessingResult> {
-        info!("Processing CSV file: {:?}", input_path);
+        info!("Processing {:?} files: {:?}", self.config.input_format, input_paths);
         
-        // Check health first
-        let health = self.health_checker.check_health().await?;
-        if !health.healthy {
-            return Err(PipelineError::HealthCheckFailed(
-                "System health check failed".to_string()
-            ));
-        }
+        self.ensure_healthy().await?;
         
         let start_time = std::time::Instant::now();
         
         // Load vocabulary items or resume
-        let (items, batch_id) = if let Some(batch_id) = resume_batch_id {
+        let (items, batch_id, input_files) = if let Some(batch_id) = resume_batch_id {
             info!("Resuming batch {}", batch_id);
-            let items = self.queue_repo.get_incomplete_items(batch_id).await?;
-            (items, batch_id)
+            if let Some(checkpoint) = self.queue_repo.get_latest_checkpoint(batch_id).await? {
+                info!("Last checkpoint at item {}", checkpoint.last_processed_id);
+            }
+            // Quarantined items aren't incomplete, and the rest keep the
+            // retry counts earlier runs stored
+            let items = self.vocab_repo.list_incomplete_in_batch(batch_id).await?;
+            if items.is_empty() {
+                let status = self.get_batch_status(batch_id).await?;
+                info!("Batch {} has nothing left to process", batch_id);
+                return Ok(ProcessingResult::already_processed(batch_id.clone(), status.total_items, start_time));
+            }
+            (items, batch_id.clone(), Vec::new())
         } else {
-            let items = self.load_csv(input_path).await?;
-            let batch_id = self.queue_repo.create_batch(items.len()).await?;
+            let mut merged = self.load_csvs(input_paths).await?;
+            let batch_id = self.enqueue(&mut merged.items).await?;
+            (merged.items, batch_id, merged.files)
+        };
+        
+        let mut result = self.run_batch(&self.batch_processor, items, batch_id, output_path, start_time).await?;
+        result.input_files = input_files;
+        Ok(result)
+    }
+    
+    /// Process vocabulary already in the database that has no cached
+    /// results yet, oldest first, up to `limit` items.
+    ///
+    /// Uses the same concurrency and export settings as
+    /// [`process_csv_file`](Self::process_csv_file). Returns `None` without
+    /// creating a batch when nothing is waiting.
+    #[instrument(skip(self))]
+    pub async fn process_pending(
+        &self,
+        limit: Option<usize>,
+        output_path: &Path,
+    ) -> Result<Option<ProcessingResult>> {
+        self.ensure_healthy().await?;
+        
+        let start_time = std::time::Instant::now();
+        
+        let limit = limit.map_or(i32::MAX, |limit| i32::try_from(limit).unwrap_or(i32::MAX));
+        let mut items = self.vocab_repo.list_unprocessed(limit).await?;
+        if items.is_empty() {
+            info!("No unprocessed vocabulary items");
+            return Ok(None);
+        }
+        
+        info!("Found {} unprocessed vocabulary items", items.len());
+        let batch_id = self.enqueue(&mut items).await?;
+        
+        self.run_batch(&self.batch_processor, items, batch_id, output_path, start_time).await.map(Some)
+    }
+    
+    /// Run the queued batches that haven't been started, e.g. enqueued by
+    /// other processes, oldest first, writing each one's cards to a new file
+    /// in `output_dir` (see [`batch_output_path`]).
+    ///
+    /// Up to [`PipelineConfig::max_concurrent_batches`] run at once, sharing
+    /// the item and API permits, cache and connection pool; each keeps its
+    /// own progress, checkpoints and failure thresholds. Batches queued while
+    /// this runs are picked up too. Returns each batch's outcome in the order
+    /// they finished, once no pending batch is left; one failing doesn't stop
+    /// the others.
+    pub async fn process_queued_batches(&self, output_dir: &Path) -> Result<Vec<(BatchId, Result<ProcessingResult>)>> {
+        self.schedule_batches(output_dir, None).await
+    }
+    
+    /// Like [`process_queued_batches`](Self::process_queued_batches), but
+    /// keeps looking for new batches every `poll_interval` until the
+    /// [`cancellation_token`](Self::cancellation_token) is cancelled, which
+    /// also interrupts the batches running then. Outcomes are logged.
+    pub async fn run_batch_scheduler(&self, output_dir: &Path, poll_interval: Duration) -> Result<()> {
+        self.schedule_batches(output_dir, Some(poll_interval)).await.map(|_| ())
+    }
+    
+    /// Keep up to `max_concurrent_batches` pending batches running, until
+    /// none is left or, when polling, until cancelled. Only finished batches'
+    /// outcomes are kept when not polling.
+    async fn schedule_batches(
+        &self,
+        output_dir: &Path,
+        poll_interval: Option<Duration>,
+    ) -> Result<Vec<(BatchId, Result<ProcessingResult>)>> {
+        self.ensure_healthy().await?;
+        std::fs::create_dir_all(output_dir)?;
+        
+        let cancellation = self.cancellation_token();
+        let drain = self.batch_processor.drain_token();
+        let mut started = HashSet::new();
+        let mut running = FuturesUnordered::new();
+        let mut outcomes = Vec::new();
+        
+        loop {
+            let free = self.config.max_concurrent_batches - running.len();
+            if free > 0 && !cancellation.is_cancelled() && !self.batch_processor.is_draining() {
+                for (batch_id, items) in self.pending_batches(&started, free).await? {
+                    info!("Starting queued batch {} ({} items)", batch_id, items.len());
+                    started.insert(batch_id.clone());
+                    running.push(async move {
+                        // Its own processor, so progress isn't shared with
+                        // the batches running beside it
+                        let processor = self.batch_processor.for_concurrent_batch();
+                        let start_time = std::time::Instant::now();
+                        let result = self.run_batch(&processor, items, batch_id.clone(), output_dir, start_time).await;
+                        (batch_id, result)
+                    });
+                }
+            }
             
-            // Add items to queue
-            for item in &items {
-                self.queue_repo.add_item_to_batch(batch_id, item).await?;
+            let stopping = cancellation.is_cancelled() || self.batch_processor.is_draining();
+            if running.is_empty() && (poll_interval.is_none() || stopping) {
+                break;
             }
             
-            (items, batch_id)
-        };
+            let poll = poll_interval.unwrap_or_default();
+            tokio::select! {
+                Some((batch_id, result)) = running.next(), if !running.is_empty() => {
+                    match &result {
+                        Ok(result) => info!(
+                            "Batch {} finished: {} succeeded, {} failed",
+                            batch_id, result.successful_items, result.failed_items
+                        ),
+                        Err(e) => error!("Batch {} failed: {}", batch_id, e),
+                    }
+                    if poll_interval.is_none() {
+                        outcomes.push((batch_id, result));
+                    }
+                }
+                _ = tokio::time::sleep(poll), if poll_interval.is_some() && !stopping => {}
+                _ = cancellation.cancelled(), if !cancellation.is_cancelled() => {}
+                _ = drain.cancelled(), if !drain.is_cancelled() => {}
+            }
+        }
+        
+        Ok(outcomes)
+    }
+    
+    /// Up to `limit` queued batches not in `started` that no item has been
+    /// picked up from yet, oldest first, with their items.
+    async fn pending_batches(
+        &self,
+        started: &HashSet<BatchId>,
+        limit: usize,
+    ) -> Result<Vec<(BatchId, Vec<VocabularyItem>)>> {
+        // Batches this run started may not have picked an item up yet
+        let fetch = i64::try_from(limit + started.len()).unwrap_or(i64::MAX);
+        
+        let mut pending = Vec::new();
+        for batch in self.queue_repo.list_unstarted_batches(fetch).await? {
+            if pending.len() == limit {
+                break;
+            }
+            if started.contains(&batch.batch_id) {
+                continue;
+            }
+            let items = self.vocab_repo.list_incomplete_in_batch(&batch.batch_id).await?;
+            if !items.is_empty() {
+                pending.push((batch.batch_id, items));
+            }
+        }
+        Ok(pending)
+    }
+    
+    /// Token that stops the running batch when cancelled. The batch writes a
+    /// checkpoint on the way out and returns [`PipelineError::Interrupted`].
+    pub fn cancellation_token(&self) -> CancellationToken {
+        self.batch_processor.cancellation_token()
+    }
+    
+    /// Token that drains the running batches when cancelled: nothing new
+    /// starts, items in flight finish and are exported, and the rest is left
+    /// pending. The run then returns normally, with
+    /// [`ProcessingResult::left_pending`] counting what was left.
+    pub fn drain_token(&self) -> CancellationToken {
+        self.batch_processor.drain_token()
+    }
+    
+    /// A coordinator that drains this pipeline on the first shutdown signal
+    /// and interrupts it on the second or after the configured grace period.
+    pub fn shutdown_coordinator(&self) -> ShutdownCoordinator {
+        ShutdownCoordinator::new(
+            self.drain_token(),
+            self.cancellation_token(),
+            self.config.shutdown_grace_period,
+        )
+    }
+    
+    /// Write a JSON summary of a finished run to `path`.
+    pub fn write_report(&self, result: &ProcessingResult, path: &Path) -> Result<()> {
+        let report = RunReport::new(result, &result.metrics, self.config.batch_label.clone());
+        report.write(path)?;
+        info!("Wrote run report to {:?}", path);
+        Ok(())
+    }
+    
+    /// Run the startup health check, failing if it is unhealthy or slow.
+    async fn ensure_healthy(&self) -> Result<()> {
+        let health = tokio::time::timeout(
+            self.config.health_check_timeout,
+            self.health_checker.check_health(),
+        ).await
+            .map_err(|_| PipelineError::HealthCheckFailed(format!(
+                "Health check timed out after {:?}",
+                self.config.health_check_timeout
+            )))??;
+        if !health.healthy {
+            return Err(PipelineError::HealthCheckFailed(
+                "System health check failed".to_string()
+            ));
+        }
+        Ok(())
+    }
+    
+    /// Save `items` and queue them as a new batch, returning its id. Each
+    /// item's `id` is set to its saved row, so its queue entry can be found.
+    pub(crate) async fn enqueue(&self, items: &mut [VocabularyItem]) -> Result<BatchId> {
+        let mut vocabulary_ids = Vec::with_capacity(items.len());
+        for item in items.iter_mut() {
+            let id = self.vocab_repo.upsert(item).await?;
+            item.id = Some(id);
+            vocabulary_ids.push(id);
+        }
+        
+        let batch_id = BatchId::generate();
+        self.queue_repo.enqueue_batch(
+            vocabulary_ids,
+            &batch_id,
+            i32::try_from(self.config.max_retries).unwrap_or(i32::MAX),
+            self.config.batch_label.as_deref(),
+        ).await?;
+        
+        Ok(batch_id)
+    }
+    
+    /// Process a queued batch, export the cards and write the error report,
+    /// then record the cache stats if configured to. Runs that fail or are
+    /// aborted used the cache too, so they are recorded as well.
+    async fn run_batch(
+        &self,
+        processor: &BatchProcessor,
+        items: Vec<VocabularyItem>,
+        batch_id: BatchId,
+        output_path: &Path,
+        start_time: std::time::Instant,
+    ) -> Result<ProcessingResult> {
+        let result = self.process_and_export(processor, items, batch_id, output_path, start_time).await;
         
+        if self.config.record_cache_stats {
+            // A gap in the history isn't worth failing a run over
+            if let Err(e) = self.cache_repo.snapshot_stats().await {
+                warn!("Failed to record cache stats: {}", e);
+            }
+        }
+        
+        result
+    }
+    
+    async fn process_and_export(
+        &self,
+        processor: &BatchProcessor,
+        items: Vec<VocabularyItem>,
+        batch_id: BatchId,
+        output_path: &Path,
+        start_time: std::time::Instant,
+    ) -> Result<ProcessingResult> {
         info!("Processing {} items in batch {}", items.len(), batch_id);
         
-        // Process batch
-        let batch_result = self.batch_processor.process_batch(items, batch_id).await?;
+        let output_path = &self.resolve_output_path(output_path, &batch_id)?;
         
-        // Export results
-        let export_stats = if !batch_result.successful.is_empty() {
-            let exporter = TsvExporter::new();
-            exporter.export(&batch_result.successful, output_path).await?
+        // Process batch and export results
+        let (batch_result, export_stats) = if self.config.stream_export {
+            self.process_streaming(processor, items, &batch_id, output_path).await?
         } else {
-            ExportStats::default()
+            let mut batch_result = self.process_chunks(processor, items, &batch_id, None).await?;
+            self.config.transforms.apply_all(&mut batch_result.successful)?;
+            
+            let export_stats = if batch_result.successful.is_empty() {
+                ExportStats::default()
+            } else if self.config.mnemonics_only {
+                let exporter = self.mnemonic_exporter(&batch_id, output_path).await?;
+                exporter.export_to(&batch_result.successful, output_path).await?
+            } else {
+                let exporter = self.exporter(&batch_id, output_path).await?;
+                exporter.export_to(&batch_result.successful, output_path).await?
+            };
+            
+            (batch_result, export_stats)
         };
         
+        // Report failures so they can be re-run
+        let report_path = self.config.error_report_path.clone()
+            .unwrap_or_else(|| default_error_report_path(output_path));
+        let failures_by_category = write_error_report(&batch_result.failed, &report_path)?;
+        let error_report = (!batch_result.failed.is_empty()).then_some(report_path);
+        
         // Update metrics
         if self.config.enable_metrics {
             self.update_metrics(&batch_result).await;
             self.metrics_collector.print_summary();
         }
+        let metrics = self.metrics_collector.snapshot_delta();
+        
+        // Completed cards are exported and the rest left pending, so the
+        // batch can be resumed once whatever broke the API is fixed
+        if let Some(reason) = &batch_result.aborted {
+            return Err(PipelineError::ApiError(format!(
+                "Batch {} aborted: {}. {} cards were exported to {}; resume with --resume {}",
+                batch_id,
+                reason,
+                batch_result.successful_count(),
+                output_path.display(),
+                batch_id
+            )));
+        }
         
         let processing_time = start_time.elapsed();
+        let finished_at = chrono::Utc::now();
+        let started_at = finished_at - chrono::Duration::from_std(processing_time).unwrap_or_default();
         
         Ok(ProcessingResult {
             batch_id,
             total_items: batch_result.total_processed,
-            successful_items: batch_result.successful.len(),
+            successful_items: batch_result.successful_count(),
             failed_items: batch_result.failed.len(),
+            skipped_items: batch_result.skipped,
             cache_hits: batch_result.cache_hits,
+            left_pending: batch_result.left_pending,
             export_stats,
+            error_report,
+            failures_by_category,
+            failures: batch_result.failed,
+            input_files: Vec::new(),
+            already_processed: 0,
+            output_path: Some(output_path.clone()),
+            metrics,
+            started_at,
+            finished_at,
             processing_time,
         })
     }
     
-    pub async fn load_csv(&self, path: &Path) -> Result<Vec<VocabularyItem>> {
-        info!("Loading vocabulary from CSV: {:?}", path);
+    /// `output_path`, or if it names a directory, a new file there for
+    /// `batch_id`, creating the directory if needed.
+    fn resolve_output_path(&self, output_path: &Path, batch_id: &BatchId) -> Result<PathBuf> {
+        if !is_output_dir(output_path) {
+            return Ok(output_path.to_path_buf());
+        }
         
-        let file = File::open(path)
-            .map_err(|_| PipelineError::FileNotFound(path.to_path_buf()))?;
+        std::fs::create_dir_all(output_path)?;
+        let resolved = batch_output_path(output_path, batch_id, chrono::Utc::now(), ExportFormat::Tsv.extension());
+        info!("Writing batch {} to {:?}", batch_id, resolved);
+        Ok(resolved)
+    }
+    
+    /// Run a batch while a single writer task appends each completed card to
+    /// `output_path`, so output survives a crash late in the batch.
+    async fn process_streaming(
+        &self,
+        processor: &BatchProcessor,
+        items: Vec<VocabularyItem>,
+        batch_id: &BatchId,
+        output_path: &Path,
+    ) -> Result<(BatchResult, ExportStats)> {
+        let (tx, mut rx) = mpsc::channel::<(VocabularyItem, Stage1Result, Stage2Result)>(100);
         
-        let mut reader = ReaderBuilder::new()
-            .has_headers(true)
-            .from_reader(file);
+        let mut exporter = self.exporter(batch_id, output_path).await?;
+        exporter.begin(open_sink(output_path).await?).await?;
+        let transforms = self.config.transforms.clone();
         
-        let mut items = Vec::new();
+        // All completions funnel through this one task, so writes never interleave
+        let writer = tokio::spawn(async move {
+            while let Some((item, stage1, mut stage2)) = rx.recv().await {
+                transforms.apply(&item, &mut stage2)?;
+                exporter.write_one(&item, &stage1, &stage2).await?;
+            }
+            exporter.finish().await
+        });
         
-        for (index, result) in reader.records().enumerate() {
-            let record = result?;
-            
-            // Expected format: position,term,type (optional)
-            let position: i32 = record.get(0)
-                .and_then(|s| s.parse().ok())
-                .unwrap_or((index + 1) as i32);
-                
-            let term = record.get(1)
-                .ok_or_else(|| PipelineError::InvalidFormat(
-                    format!("Missing term at row {}", index + 1)
-                ))?
-                .to_string();
-                
-            let word_type = record.get(2).map(|s| s.to_string());
+        let batch_result = self.process_chunks(processor, items, batch_id, Some(tx)).await;
+        
+        // The sender was moved into the chunk loop and is dropped by now, so
+        // the writer drains the channel and returns
+        let export_stats = writer.await
+            .map_err(|e| PipelineError::ExportError(format!("Task join error: {}", e)))??;
+        
+        Ok((batch_result?, export_stats))
+    }
+    
+    /// Process `items` in chunks of `batch_size`, one chunk at a time, so only
+    /// one chunk's tasks are spawned at once and each chunk is checkpointed
+    /// before the next starts.
+    ///
+    /// With `prefetch_chunks`, the next chunks' cache lookups run while a
+    /// chunk is processed. They only read the cache, so they take no API
+    /// permits, and their results are used by the chunk's own run, so cards
+    /// still reach `sink` one chunk after another.
+    async fn process_chunks(
+        &self,
+        processor: &BatchProcessor,
+        items: Vec<VocabularyItem>,
+        batch_id: &BatchId,
+        sink: Option<mpsc::Sender<(VocabularyItem, Stage1Result, Stage2Result)>>,
+    ) -> Result<BatchResult> {
+        processor.start_progress(items.len());
+        let mut chunks = chunk_items(items, self.config.batch_size);
+        let chunk_count = chunks.len();
+        let mut results = Vec::with_capacity(chunk_count);
+        // Lookups for chunks `index + 1..next_prefetch`, in chunk order
+        let mut prefetches = VecDeque::new();
+        // Lookups a failed, aborted or drained batch never reached stop with it
+        let mut prefetch_tasks = AbortOnDrop::default();
+        let mut next_prefetch = 1;
+        
+        for index in 0..chunk_count {
+            if processor.is_draining() {
+                let left: usize = chunks[index..].iter().map(Vec::len).sum();
+                info!("Draining: {} items in chunks {}-{} left pending", left, index + 1, chunk_count);
+                results.push(BatchResult { left_pending: left, ..Default::default() });
+                break;
+            }
+            let chunk = std::mem::take(&mut chunks[index]);
+            let prefetched = match prefetches.pop_front() {
+                Some(lookup) => lookup.await.unwrap_or_else(|e| {
+                    warn!("Prefetch for chunk {} failed: {}", index + 1, e);
+                    Prefetched::new()
+                }),
+                None => Prefetched::new(),
+            };
+            while next_prefetch < chunk_count && next_prefetch <= index + self.config.prefetch_chunks {
+                let ahead = chunks[next_prefetch].clone();
+                let lookup = tokio::spawn(processor.prefetch(ahead));
+                prefetch_tasks.push(&lookup);
+                prefetches.push_back(lookup);
+                next_prefetch += 1;
+            }
             
-            items.push(VocabularyItem {
-                id: None,
-                position,
-                term,
-                word_type,
-                created_at: chrono::Utc::now(),
-                updated_at: chrono::Utc::now(),
-            });
+            info!("Processing chunk {}/{} ({} items, {} prefetched)", index + 1, chunk_count, chunk.len(), prefetched.len());
+            let mut result = processor
+                .process_batch_prefetched(chunk, batch_id, sink.clone(), prefetched)
+                .await?;
+            if sink.is_some() && self.config.max_buffered_results.is_some() {
+                // Its cards are on disk, where linking them now would change nothing
+                result.spill();
+            }
+            let aborted = result.aborted.is_some();
+            // An aborted or drained chunk checkpointed where it stopped
+            if !aborted && result.left_pending == 0 {
+                if let Some(position) = result.last_completed {
+                    processor.checkpoint_chunk(batch_id, position, &result).await?;
+                }
+            }
+            results.push(result);
+            if aborted {
+                // Later chunks never started, so their items are still pending too
+                let left: usize = chunks[index + 1..].iter().map(Vec::len).sum();
+                results.push(BatchResult { left_pending: left, ..Default::default() });
+                break;
+            }
         }
         
-        if items.is_empty() {
-            return Err(PipelineError::InvalidFormat(
-                "CSV file contains no valid vocabulary items".to_string()
-            ));
-        }
+        Ok(BatchResult::merge(results))
+    }
+    
+    /// Queue `items` as a new batch and process them without exporting,
+    /// e.g. to measure throughput.
+    pub async fn process_items(&self, mut items: Vec<VocabularyItem>) -> Result<BatchResult> {
+        let batch_id = self.enqueue(&mut items).await?;
+        self.process_chunks(&self.batch_processor, items, &batch_id, None).await
+    }
+    
+    pub async fn load_csv(&self, path: &Path) -> Result<Vec<VocabularyItem>> {
+        info!("Loading vocabulary from CSV: {:?}", path);
+        
+        let mut items = read_vocabulary_csv(path, self.config.csv_comment)?;
+        self.normalize_terms(&mut items);
+        self.estimate_difficulty(&mut items);
+        
+        info!("Loaded {} vocabulary items", items.len());
+        Ok(items)
+    }
+    
+    /// Load a JSON lines file with one vocabulary record per line.
+    pub async fn load_jsonl(&self, path: &Path) -> Result<Vec<VocabularyItem>> {
+        info!("Loading vocabulary from JSONL: {:?}", path);
+        
+        let mut items = read_vocabulary_jsonl(path)?;
+        self.normalize_terms(&mut items);
+        self.estimate_difficulty(&mut items);
         
         info!("Loaded {} vocabulary items", items.len());
         Ok(items)
     }
     
+    /// Load several input files of the configured format into one batch;
+    /// see [`read_vocabulary_files`].
+    pub async fn load_csvs(&self, paths: &[PathBuf]) -> Result<MergedInput> {
+        let mut merged = read_vocabulary_files(paths, self.config.input_format, self.config.csv_comment)?;
+        self.normalize_terms(&mut merged.items);
+        self.estimate_difficulty(&mut merged.items);
+        
+        for file in &merged.files {
+            info!("Loaded {} vocabulary items from {:?}", file.items, file.path);
+        }
+        Ok(merged)
+    }
+    
+    fn normalize_terms(&self, items: &mut [VocabularyItem]) {
+        let Some(normalizer) = &self.config.term_normalizer else {
+            return;
+        };
+        
+        for item in items {
+            let original = item.term.clone();
+            // apply keeps the original term in the item's metadata
+            if normalizer.apply(item) {
+                info!("Item {}: normalized {} to {}", item.position, original, item.term);
+            }
+        }
+    }
+    
+    /// Neither input format carries a difficulty label, so every loaded
+    /// item gets an estimate from its (normalized) term. Items already in
+    /// the database keep the level stored with them.
+    fn estimate_difficulty(&self, items: &mut [VocabularyItem]) {
+        for item in items {
+            self.difficulty.apply(item, None);
+        }
+    }
+    
+    /// The most prompt tokens `items` can be sent with, per stage, were none
+    /// of them cached.
+    pub fn estimate_tokens(&self, items: &[VocabularyItem]) -> TokenEstimate {
+        self.tokens.estimate_batch(items)
+    }
+    
     async fn update_metrics(&self, batch_result: &BatchResult) {
-        for _ in 0..batch_result.successful.len() {
+        for _ in 0..batch_result.successful_count() {
             self.metrics_collector.record_item_processed(true, batch_result.processing_time);
         }
         
@@ -229,93 +1181,630 @@ impl Pipeline {
             self.metrics_collector.record_item_processed(false, batch_result.processing_time);
         }
         
-        // Cache metrics
-        for _ in 0..batch_result.cache_hits {
-            self.metrics_collector.record_cache_hit();
+        // Cache hits and misses are recorded live by the batch processor
+        self.metrics_collector.record_batch_completed();
+    }
+    
+    pub async fn get_batch_status(&self, batch_id: &BatchId) -> Result<BatchStatus> {
+        let progress = self.queue_repo.get_batch_progress(batch_id).await?;
+        Ok(BatchStatus::from(progress))
+    }
+    
+    /// The `limit` most recent batches, optionally only those labelled `label`.
+    pub async fn list_batches(&self, label: Option<&str>, limit: usize) -> Result<Vec<BatchInfo>> {
+        let batches = self.queue_repo.list_batches(label, limit as i64).await?;
+        Ok(batches.into_iter().map(BatchInfo::from).collect())
+    }
+    
+    /// Where card statistics for an export to `output_path` go, if anywhere.
+    /// `output_path` is the resolved file, so a run into a directory gets
+    /// statistics beside its own batch file.
+    fn stats_path(&self, output_path: &Path) -> Option<PathBuf> {
+        self.config.stats_path.clone()
+            .or_else(|| self.config.write_stats.then(|| default_stats_path(output_path)))
+    }
+    
+    /// The exporter for cards from `batch_id` to `output_path`, tagging
+    /// suspended cards and leeches as their stored card state says.
+    async fn exporter(&self, batch_id: &BatchId, output_path: &Path) -> Result<TsvExporter> {
+        let mut exporter = TsvExporter::new()
+            .with_headers(self.config.include_headers)
+            .with_threads(self.config.export_threads)
+            .with_comparison_columns(self.config.comparison_columns)
+            .with_memory_palace(self.config.memory_palace)
+            .with_sort(self.config.sort_by)
+            .with_stage2_mode(self.config.stage2_mode)
+            .with_max_field_chars(self.config.max_field_chars)
+            .with_extra_tags(self.config.extra_tags.clone())
+            .with_stats_path(self.stats_path(output_path))
+            .with_skip_on_error(self.config.skip_export_errors)
+            .with_formula_guard(self.config.formula_guard.clone())
+            .with_line_ending(self.config.line_ending)
+            .with_bom(self.config.write_bom)
+            .with_card_states(self.card_states.flagged().await?);
+        if self.config.auto_tags {
+            let today = chrono::Local::now().date_naive();
+            exporter = exporter.with_batch_tags(batch_id.clone(), self.config.batch_label.clone(), today);
+        }
+        let exporter = self.config.field_char_limits.iter()
+            .fold(exporter, |exporter, (field, &max)| exporter.with_field_char_limit(field.clone(), max));
+        Ok(match &self.config.anki_preset {
+            Some(preset) => exporter.with_anki_preset(preset.clone()),
+            None => exporter,
+        })
+    }
+    
+    /// [`Self::exporter`]'s counterpart for the mnemonic deck, with the same
+    /// run tags and card states.
+    async fn mnemonic_exporter(&self, batch_id: &BatchId, output_path: &Path) -> Result<MnemonicExporter> {
+        let mut exporter = MnemonicExporter::new()
+            .with_headers(self.config.include_headers)
+            .with_stats_path(self.stats_path(output_path))
+            .with_extra_tags(self.config.extra_tags.clone())
+            .with_card_states(self.card_states.flagged().await?);
+        if self.config.auto_tags {
+            let today = chrono::Local::now().date_naive();
+            exporter = exporter.with_batch_tags(batch_id.clone(), self.config.batch_label.clone(), today);
+        }
+        Ok(exporter)
+    }
+    
+    /// Export the cards of a finished batch again, read from the cache, e.g.
+    /// in another `format`, without reprocessing anything.
+    ///
+    /// Each card's Stage 1 analysis comes from the cache too; run with
+    /// [`PipelineConfig::cache_only`] to fail rather than call the API when
+    /// one has been pruned since.
+    pub async fn reexport_batch(&self, batch_id: &BatchId, format: ExportFormat, output_path: &Path) -> Result<ExportStats> {
+        let cached = self.cache_repo
+            .get_stage2_results_for_batch_in(batch_id, &self.config.namespace)
+            .await?;
+        if cached.is_empty() {
+            return Err(PipelineError::ExportError(format!(
+                "Batch {} has no cached cards to export", batch_id
+            )));
+        }
+        info!("Re-exporting {} cards from batch {} as {:?}", cached.len(), batch_id, format);
+        
+        let api_client = &self.api_client;
+        let cache_manager = &self.cache_manager;
+        let model = &self.config.models().stage1;
+        let mut results = Vec::with_capacity(cached.len());
+        for (item, mut stage2) in cached {
+            // Entries cached before phrases were tagged
+            stage2.tag_if_phrase(&item);
+            let item_ref = &item;
+            let stage1 = cache_manager.get_or_compute_stage1(item_ref, || async move {
+                let (result, tokens) = api_client.process_stage1_with_usage(item_ref).await?;
+                let request_hash = cache_manager.request_hash(CacheType::Stage1, item_ref);
+                Ok((result, request_hash, tokens as i32, model.clone()))
+            }).await?;
+            results.push((item, stage1, stage2));
         }
-        for _ in 0..(batch_result.total_processed - batch_result.cache_hits) {
-            self.metrics_collector.record_cache_miss();
+        
+        match format {
+            ExportFormat::Tsv => self.exporter(batch_id, output_path).await?.export_to(&results, output_path).await,
+            ExportFormat::Json => JsonExporter::new()
+                .with_card_states(self.card_states.flagged().await?)
+                .export_to(&results, output_path)
+                .await,
+            ExportFormat::Mnemonics => self.mnemonic_exporter(batch_id, output_path).await?.export_to(&results, output_path).await,
         }
     }
     
-    pub async fn get_batch_status(&self, batch_id: i32) -> Result<BatchStatus> {
-        let stats = self.queue_repo.get_batch_status(batch_id).await?;
-        Ok(BatchStatus::from(stats))
+    /// How the cards of batch `new` differ from those of batch `old`, e.g.
+    /// after reprocessing the same words with another prompt. Each batch's
+    /// cards are the ones it recorded on its queue rows, and they are
+    /// matched by term, so the batches may list them in any order.
+    pub async fn diff_batches(&self, old: &BatchId, new: &BatchId) -> Result<BatchDiff> {
+        let mut cards = Vec::with_capacity(2);
+        for batch_id in [old, new] {
+            let cached = self.cache_repo
+                .get_stage2_results_for_batch_in(batch_id, &self.config.namespace)
+                .await?;
+            if cached.is_empty() {
+                return Err(PipelineError::ExportError(format!(
+                    "Batch {} has no cached cards to compare", batch_id
+                )));
+            }
+            cards.push(cached);
+        }
+        
+        Ok(BatchDiff::between(&cards[0], &cards[1]))
     }
     
-    pub async fn list_batches(&self) -> Result<Vec<BatchInfo>> {
-        let batches = self.queue_repo.list_batches(10, 0).await?;
-        Ok(batches.into_iter().map(BatchInfo::from).collect())
+    /// Report how much of `items` is already cached, without warming anything.
+    pub async fn probe_cache(&self, items: &[VocabularyItem], stage1_only: bool) -> Result<CacheWarmupStats> {
+        Ok(self.cache_manager.probe_cache(items, stage1_only).await?)
     }
     
-    pub async fn warm_cache(&self, items: &[VocabularyItem]) -> Result<usize> {
+    /// Compute and cache whatever `items` are missing, only Stage 1 with
+    /// `options.stage1_only`, returning how many items this run had to call
+    /// the API for.
+    ///
+    /// A checkpoint is saved after every `options.checkpoint_interval`
+    /// items, so with `options.resume` a warm of the same items that was
+    /// interrupted, e.g. by an API error, continues where it stopped instead
+    /// of looking every item up again. Nothing is carried over but the
+    /// position; what is cached is looked up afresh.
+    pub async fn warm_cache(&self, items: &[VocabularyItem], options: WarmupOptions) -> Result<usize> {
         info!("Warming cache for {} items", items.len());
-        let warmed = self.cache_manager.warm_cache(items).await?;
+        let warm_key = self.fill_key(items, options.stage1_only);
+        
+        let checkpoint = if options.resume {
+            self.cache_repo.get_warm_checkpoint(&warm_key).await?
+        } else {
+            None
+        };
+        let start = match checkpoint {
+            Some((next_index, _)) if next_index <= items.len() => {
+                info!("Resuming cache warm at item {} of {}", next_index, items.len());
+                next_index
+            }
+            _ => 0,
+        };
+        
+        let interval = options.checkpoint_interval.max(1);
+        let mut warmed = 0;
+        for chunk_start in (start..items.len()).step_by(interval) {
+            let chunk_end = (chunk_start + interval).min(items.len());
+            let called: Vec<bool> = futures::stream::iter(&items[chunk_start..chunk_end])
+                .map(|item| self.fill_cache(item, options.stage1_only))
+                .buffer_unordered(self.config.max_concurrent.max(1))
+                .collect::<Vec<_>>()
+                .await
+                .into_iter()
+                .collect::<Result<_>>()?;
+            warmed += called.into_iter().filter(|&called| called).count();
+            
+            if chunk_end < items.len() {
+                self.cache_repo.save_warm_checkpoint(&warm_key, chunk_end, &serde_json::Value::Null).await?;
+            }
+        }
+        self.cache_repo.clear_warm_checkpoint(&warm_key).await?;
+        
         info!("Cache warmed with {} items", warmed);
         Ok(warmed)
     }
     
-    pub async fn get_cache_stats(&self) -> Result<CacheStats> {
-        let stats = self.cache_repo.get_cache_stats().await?;
-        Ok(CacheStats {
-            total_entries: stats.total_entries,
-            stage1_entries: stats.stage1_entries,
-            stage2_entries: stats.stage2_entries,
-            total_size_bytes: stats.total_size_bytes,
-            cache_hit_rate: self.metrics_collector.get_cache_hit_rate(),
-        })
+    /// Checkpoint key of a warm over `items`, kept apart from the checkpoints
+    /// of [`CacheManager::warm_cache_for_batch_with_options`], which hold
+    /// counts
+    fn fill_key(&self, items: &[VocabularyItem], stage1_only: bool) -> String {
+        format!("fill-{}", self.cache_manager.warm_key(items, stage1_only))
     }
-}
-
+    
+    /// Look `item` up, computing and caching each stage that is missing;
+    /// Stage 2 is left alone with `stage1_only`. Returns whether the API
+    /// was called.
+    async fn fill_cache(&self, item: &VocabularyItem, stage1_only: bool) -> Result<bool> {
+        let api_client = &self.api_client;
+        let cache_manager = &self.cache_manager;
+        let models = self.config.models();
+        let stage2_mode = self.config.stage2_mode;
+        let called = &AtomicBool::new(false);
+        
+        let stage1 = cache_manager.get_or_compute_stage1(item, || async move {
+            called.store(true, Ordering::Relaxed);
+            let (result, tokens) = api_client.process_stage1_with_usage(item).await?;
+            let request_hash = cache_manager.request_hash(CacheType::Stage1, item);
+            Ok((result, request_hash, tokens as i32, models.stage1))
+        }).await?;
+        
+        if !stage1_only {
+            let stage1 = &stage1;
+            cache_manager.get_or_compute_stage2(item, stage1, || async move {
+                called.store(true, Ordering::Relaxed);
+                let (result, tokens) = api_client.process_stage2_with_usage(item, stage1, stage2_mode).await?;
+                let request_hash = cache_manager.request_hash(CacheType::Stage2, item);
+                Ok((result, request_hash, tokens as i32, models.stage2))
+            }).await?;
+        }
+        
+        Ok(called.load(Ordering::Relaxed))
+    }
+    
+    /// Write every cache entry to `path` as JSON lines.
+    pub async fn export_cache(&self, path: &Path) -> Result<usize> {
+        use std::io::{BufWriter, Write};
+        
+        info!("Exporting cache to {:?}", path);
+        let mut writer = BufWriter::new(File::create(path)?);
+        let count = self.cache_repo.export_all(&mut |entry| {
+            serde_json::to_writer(&mut writer, &entry)?;
+            writer.write_all(b"\n")?;
+            Ok(())
+        }).await?;
+        writer.flush()?;
+        
+        info!("Exported {} cache entries", count);
+        Ok(count)
+    }
+    
+    /// Restore cache entries from a JSON lines backup.
+    ///
+    /// Entries are committed in chunks so an interrupted import keeps its
+    /// progress; malformed lines are logged and skipped.
+    pub async fn import_cache(&self, path: &Path, overwrite: bool) -> Result<CacheImportStats> {
+        use std::io::{BufRead, BufReader};
+        
+        const IMPORT_CHUNK_SIZE: usize = 500;
+        
+        info!("Importing cache from {:?}", path);
+        let reader = BufReader::new(File::open(path)?);
+        let mut stats = CacheImportStats::default();
+        let mut chunk = Vec::with_capacity(IMPORT_CHUNK_SIZE);
+        
+        for (line_number, line) in reader.lines().enumerate() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            
+            match serde_json::from_str::<CacheEntry>(&line) {
+                Ok(entry) => chunk.push(entry),
+                Err(e) => {
+                    warn!("Skipping malformed cache entry on line {}: {}", line_number + 1, e);
+                    stats.skipped += 1;
+                    continue;
+                }
+            }
+            
+            if chunk.len() == IMPORT_CHUNK_SIZE {
+                let imported = self.cache_repo.import_all(&chunk, overwrite).await?;
+                stats.imported += imported.imported;
+                stats.skipped += imported.skipped;
+                chunk.clear();
+            }
+        }
+        
+        if !chunk.is_empty() {
+            let imported = self.cache_repo.import_all(&chunk, overwrite).await?;
+            stats.imported += imported.imported;
+            stats.skipped += imported.skipped;
+        }
+        
+        info!("Imported {} cache entries, skipped {}", stats.imported, stats.skipped);
+        Ok(stats)
+    }
+    
+    /// Cache entries created at least `older_than` ago with at most
+    /// `max_access` reads.
+    pub async fn find_cold_cache_entries(
+        &self,
+        older_than: Duration,
+        max_access: i32,
+    ) -> Result<Vec<CacheEntry>> {
+        let min_age = chrono_duration(older_than)?;
+        Ok(self.cache_repo.find_cold_entries(min_age, max_access).await?)
+    }
+    
+    /// Delete cached results of `cache_type`, or both stages, returning how
+    /// many were removed. With a namespace configured only its entries go;
+    /// otherwise every namespace's do.
+    pub async fn clear_cache(&self, cache_type: Option<CacheType>) -> Result<i64> {
+        let removed = if self.config.namespace.is_empty() {
+            self.cache_manager.clear_cache(cache_type).await?
+        } else {
+            self.cache_manager.clear_namespace(cache_type).await?
+        };
+        info!("Cleared {} cache entries", removed);
+        Ok(removed)
+    }
+    
+    /// Delete the entries `model` computed, in this pipeline's namespace
+    /// unless it is empty, returning how many were removed.
+    pub async fn clear_cache_by_model(&self, cache_type: Option<CacheType>, model: &str) -> Result<i64> {
+        let namespace = Some(self.config.namespace.as_str()).filter(|namespace| !namespace.is_empty());
+        let removed = self.cache_repo.clear_cache_by_model_in(cache_type, model, namespace).await?;
+        info!("Cleared {} cache entries from model {}", removed, model);
+        Ok(removed)
+    }
+    
+    /// Delete cold cache entries, returning how many were removed.
+    pub async fn prune_cold_cache_entries(&self, older_than: Duration, max_access: i32) -> Result<u64> {
+        let min_age = chrono_duration(older_than)?;
+        let removed = self.cache_repo.prune_cold_entries(min_age, max_access).await?;
+        info!("Pruned {} cold cache entries", removed);
+        Ok(removed)
+    }
+    
+    /// The cached card for `term`. Nothing is computed, so a term that
+    /// isn't cached is an error.
+    ///
+    /// With `diff`, Stage 2 is recomputed instead, and Stage 1 too if it
+    /// isn't cached, and the changed fields are returned alongside the fresh
+    /// card. The fresh card only replaces the cached one with `persist`.
+    pub async fn inspect_term(
+        &self,
+        term: &str,
+        diff: bool,
+        persist: bool,
+    ) -> Result<(Stage2Result, Option<Stage2Diff>)> {
+        let item = VocabularyItem {
+            id: None,
+            position: 1,
+            term: term.to_string(),
+            word_type: None,
+            source: String::new(),
+            created_at: chrono::Utc::now(),
+            updated_at: chrono::Utc::now(),
+        };
+        
+        if !diff {
+            let (_, stage2) = self.cache_manager.get_cached_results(&item).await?
+                .ok_or_else(|| PipelineError::CacheError(format!("Nothing cached for {}", term)))?;
+            return Ok((stage2, None));
+        }
+        
+        let api_client = &self.api_client;
+        let cache_manager = &self.cache_manager;
+        let models = self.config.models();
+        let stage2_mode = self.config.stage2_mode;
+        let item = &item;
+        
+        let stage1 = cache_manager.get_or_compute_stage1(item, || async move {
+            let (result, tokens) = api_client.process_stage1_with_usage(item).await?;
+            let request_hash = cache_manager.request_hash(CacheType::Stage1, item);
+            Ok((result, request_hash, tokens as i32, models.stage1))
+        }).await?;
+        
+        let stage1 = &stage1;
+        let inspected = cache_manager.compute_and_diff_stage2(item, stage1, persist, || async move {
+            let (result, tokens) = api_client.process_stage2_with_usage(item, stage1, stage2_mode).await?;
+            let request_hash = cache_manager.request_hash(CacheType::Stage2, item);
+            Ok((result, request_hash, tokens as i32, models.stage2))
+        }).await?;
+        Ok(inspected)
+    }
+    
+    /// The last `limit` API calls recorded with `--audit`, of `batch_id`
+    /// only if given, oldest first.
+    pub async fn api_calls(&self, batch_id: Option<&BatchId>, limit: usize) -> Result<Vec<ApiCallRecord>> {
+        let limit = i64::try_from(limit).unwrap_or(i64::MAX);
+        Ok(self.api_call_log.recent(batch_id, limit).await?)
+    }
+    
+    /// Mark the card of `term` suspended or not for later exports, which tag
+    /// it [`SUSPENDED_TAG`](crate::export::SUSPENDED_TAG). `leech` also sets
+    /// or clears its leech mark; `None` leaves that as it is.
+    pub async fn set_suspended(&self, term: &str, suspended: bool, leech: Option<bool>) -> Result<CardState> {
+        Ok(self.card_states.set_suspended(term, suspended, leech).await?)
+    }
+    
+    /// The keys `term` is cached under with this configuration and what is
+    /// stored at each, e.g. to find out why a cache hit returned the wrong
+    /// card. Looking entries up here doesn't count as reading them.
+    pub async fn cache_keys(&self, term: &str, word_type: Option<String>) -> Result<CacheKeyReport> {
+        let item = VocabularyItem {
+            id: None,
+            position: 1,
+            term: term.to_string(),
+            word_type,
+            source: String::new(),
+            created_at: chrono::Utc::now(),
+            updated_at: chrono::Utc::now(),
+        };
+        let (stage1_key, stage2_key) = self.cache_manager.cache_keys(&item);
+        
+        Ok(CacheKeyReport {
+            stage1_entry: self.cache_manager.get_entry(CacheType::Stage1, &stage1_key).await?,
+            stage2_entry: self.cache_manager.get_entry(CacheType::Stage2, &stage2_key).await?,
+            stage1_key,
+            stage2_key,
+        })
+    }
+    
+    /// Move cached entries to the keys the configured normalization produces,
+    /// so a key-scheme change doesn't orphan results already paid for.
+    pub async fn migrate_cache_keys(&self, dry_run: bool) -> Result<CacheMigrationStats> {
+        info!("Migrating cache keys to {:?}", self.config.key_normalization);
+        let stats = self.cache_repo.migrate_keys(self.config.key_normalization, dry_run).await?;
+        Ok(stats)
+    }
+    
+    /// Serve the health/metrics endpoints backed by this pipeline's components.
+    #[cfg(feature = "server")]
+    pub async fn serve(&self, addr: std::net::SocketAddr) -> Result<()> {
+        crate::server::serve(addr, crate::server::ServerState {
+            health_checker: Arc::clone(&self.health_checker),
+            metrics_collector: Arc::clone(&self.metrics_collector),
+        }).await
+    }
+    
+    /// Serve the read-only card browser over this pipeline's queue and cache.
+    #[cfg(feature = "server")]
+    pub async fn browse(&self, addr: std::net::SocketAddr) -> Result<()> {
+        crate::browse::serve(addr, crate::browse::BrowseState {
+            cache_repo: Arc::clone(&self.cache_repo),
+            queue_repo: Arc::clone(&self.queue_repo),
+            namespace: self.config.namespace.clone(),
+        }).await
+    }
+    
+    pub async fn get_cache_stats(&self) -> Result<CacheStats> {
+        let stats = self.cache_repo.get_cache_stats().await?;
+        Ok(CacheStats {
+            total_entries: stats.total_entries,
+            stage1_entries: stats.stage1_entries,
+            stage2_entries: stats.stage2_entries,
+            total_size_bytes: stats.total_size_bytes,
+            cache_hit_rate: self.metrics_collector.get_cache_hit_rate(),
+        })
+    }
+    
+    /// Record the current cache stats in the history `cache_growth` reads.
+    pub async fn snapshot_cache_stats(&self) -> Result<CacheStatsSnapshot> {
+        Ok(self.cache_repo.snapshot_stats().await?)
+    }
+    
+    /// Cache stats snapshots of the last `days` days, oldest first.
+    pub async fn cache_growth(&self, days: u32) -> Result<Vec<CacheStatsSnapshot>> {
+        let since = chrono::Utc::now() - chrono::Duration::days(days.into());
+        Ok(self.cache_repo.stats_history(since).await?)
+    }
+}
+
+/// Spawn the controller that resizes `batch_processor`'s item semaphore
+/// from `metrics`, within the bounds `config` sets.
+fn start_adaptive_concurrency(
+    config: &PipelineConfig,
+    batch_processor: &BatchProcessor,
+    metrics: &Arc<MetricsCollector>,
+) -> tokio::task::JoinHandle<()> {
+    let controller = Arc::new(AdaptiveConcurrencyController::new(
+        batch_processor.semaphore(),
+        Arc::clone(metrics),
+        AdaptiveConcurrencyConfig {
+            min_concurrency: config.min_concurrency,
+            max_concurrency: config.max_concurrency,
+            target_error_rate: config.target_error_rate,
+            ..Default::default()
+        },
+    ));
+    controller.spawn()
+}
+
+fn chrono_duration(duration: Duration) -> Result<chrono::Duration> {
+    chrono::Duration::from_std(duration)
+        .map_err(|_| PipelineError::ConfigError(format!("Duration out of range: {:?}", duration)))
+}
+
+/// Create `cache_dir` if needed and check that it can be written to.
+///
+/// Done up front so a bad path fails with a clear message instead of a
+/// Python traceback on the first API call.
+pub fn prepare_cache_dir(cache_dir: &Path) -> Result<()> {
+    std::fs::create_dir_all(cache_dir).map_err(|e| PipelineError::ConfigError(
+        format!("Cannot create cache directory {}: {}", cache_dir.display(), e)
+    ))?;
+    
+    probe_write(cache_dir).map_err(|e| PipelineError::ConfigError(
+        format!("Cache directory {} is not writable: {}", cache_dir.display(), e)
+    ))?;
+    
+    Ok(())
+}
+
+/// Create and remove a probe file in `dir`.
+fn probe_write(dir: &Path) -> std::io::Result<()> {
+    let probe = dir.join(format!(".write-test-{}", std::process::id()));
+    File::create(&probe)?;
+    std::fs::remove_file(&probe)
+}
+
 #[derive(Debug, Clone)]
 pub struct ProcessingResult {
-    pub batch_id: i32,
+    pub batch_id: BatchId,
     pub total_items: usize,
     pub successful_items: usize,
     pub failed_items: usize,
+    /// Uncached items passed over in offline mode
+    pub skipped_items: usize,
     pub cache_hits: usize,
+    /// Items not started because the run was drained for a shutdown or
+    /// aborted; they are still pending, for a resume
+    pub left_pending: usize,
     pub export_stats: ExportStats,
+    pub error_report: Option<PathBuf>,
+    pub failures_by_category: std::collections::BTreeMap<String, usize>,
+    /// Every failed item, as written to the error report
+    pub failures: Vec<FailureRecord>,
+    /// Items loaded per input file, when the batch came from CSVs
+    pub input_files: Vec<InputFileSummary>,
+    /// Items a resumed batch had finished before this run, so weren't
+    /// processed again
+    pub already_processed: usize,
+    /// Where the cards were written, with a directory `--output` resolved to
+    /// the file created in it; `None` if there was nothing to do
+    pub output_path: Option<PathBuf>,
+    /// What this run added to the pipeline's running metrics
+    pub metrics: PipelineMetrics,
+    pub started_at: chrono::DateTime<chrono::Utc>,
+    pub finished_at: chrono::DateTime<chrono::Utc>,
     pub processing_time: std::time::Duration,
 }
 
+impl ProcessingResult {
+    /// The result of a run that found all `count` items of `batch_id`
+    /// already done: nothing processed, exported or failed.
+    fn already_processed(batch_id: BatchId, count: usize, start_time: std::time::Instant) -> Self {
+        let processing_time = start_time.elapsed();
+        let finished_at = chrono::Utc::now();
+        let started_at = finished_at - chrono::Duration::from_std(processing_time).unwrap_or_default();
+        
+        Self {
+            batch_id,
+            total_items: 0,
+            successful_items: 0,
+            failed_items: 0,
+            skipped_items: 0,
+            cache_hits: 0,
+            left_pending: 0,
+            export_stats: ExportStats::default(),
+            error_report: None,
+            failures_by_category: Default::default(),
+            failures: Vec::new(),
+            input_files: Vec::new(),
+            already_processed: count,
+            output_path: None,
+            metrics: PipelineMetrics::default(),
+            started_at,
+            finished_at,
+            processing_time,
+        }
+    }
+    
+    /// Whether the run had nothing to do because everything was done before.
+    pub fn nothing_to_do(&self) -> bool {
+        self.total_items == 0 && self.already_processed > 0
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct BatchStatus {
-    pub batch_id: i32,
+    pub batch_id: BatchId,
     pub total_items: usize,
     pub completed_items: usize,
     pub failed_items: usize,
+    /// Deliberately not processed; counts toward completion, not failure
+    pub skipped_items: usize,
     pub in_progress: bool,
     pub created_at: chrono::DateTime<chrono::Utc>,
 }
 
-impl From<flashcard_core::models::BatchStats> for BatchStatus {
-    fn from(stats: flashcard_core::models::BatchStats) -> Self {
+impl From<flashcard_core::models::BatchProgress> for BatchStatus {
+    fn from(progress: flashcard_core::models::BatchProgress) -> Self {
+        let count = |items: i32| usize::try_from(items).unwrap_or(0);
         Self {
-            batch_id: stats.batch_id,
-            total_items: stats.total_items,
-            completed_items: stats.completed_items,
-            failed_items: stats.failed_items,
-            in_progress: stats.completed_items < stats.total_items,
-            created_at: stats.created_at,
+            batch_id: progress.batch_id,
+            total_items: count(progress.total_items),
+            completed_items: count(progress.completed_items),
+            failed_items: count(progress.failed_items),
+            skipped_items: count(progress.skipped_items),
+            in_progress: progress.completed_items + progress.skipped_items < progress.total_items,
+            created_at: progress.start_time,
         }
     }
 }
 
 #[derive(Debug, Clone)]
 pub struct BatchInfo {
-    pub batch_id: i32,
+    pub batch_id: BatchId,
     pub total_items: usize,
     pub status: String,
+    pub label: Option<String>,
     pub created_at: chrono::DateTime<chrono::Utc>,
 }
 
-impl From<(i32, usize, chrono::DateTime<chrono::Utc>)> for BatchInfo {
-    fn from((batch_id, total_items, created_at): (i32, usize, chrono::DateTime<chrono::Utc>)) -> Self {
+impl From<flashcard_core::models::BatchSummary> for BatchInfo {
+    fn from(summary: flashcard_core::models::BatchSummary) -> Self {
         Self {
-            batch_id,
-            total_items,
-            status: "Created".to_string(),
-            created_at,
+            batch_id: summary.batch_id,
+            total_items: summary.total_items.max(0) as usize,
+            status: summary.status,
+            label: summary.label,
+            created_at: summary.start_time,
         }
     }
 }
@@ -327,4 +1816,1191 @@ pub struct CacheStats {
     pub stage2_entries: usize,
     pub total_size_bytes: i64,
     pub cache_hit_rate: f64,
-}
\ No newline at end of file
+}
+
+/// Where [`Pipeline::cache_keys`] found a term's results
+#[derive(Debug, Clone)]
+pub struct CacheKeyReport {
+    pub stage1_key: String,
+    pub stage1_entry: Option<CacheEntry>,
+    pub stage2_key: String,
+    pub stage2_entry: Option<CacheEntry>,
+}
+
+/// Split `items` into runs of at most `batch_size`, keeping their order.
+/// A `batch_size` of 0 keeps everything in one chunk.
+fn chunk_items(items: Vec<VocabularyItem>, batch_size: usize) -> Vec<Vec<VocabularyItem>> {
+    if batch_size == 0 || items.len() <= batch_size {
+        return vec![items];
+    }
+    
+    let mut chunks = Vec::with_capacity(items.len().div_ceil(batch_size));
+    let mut items = items.into_iter().peekable();
+    while items.peek().is_some() {
+        chunks.push(items.by_ref().take(batch_size).collect());
+    }
+    chunks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::batch_processor::FailureStage;
+    use crate::fixtures::{test_config, test_pipeline, test_pipeline_with};
+    use flashcard_core::models::{BatchProgress, BatchSummary, ProcessingCheckpoint, ProcessingStage, ProcessingStatus, QueueItem};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::collections::HashMap;
+    
+    /// Rejects every call, as the API does once a key is revoked
+    #[derive(Default)]
+    struct RejectingClient {
+        calls: AtomicUsize,
+    }
+    
+    impl RejectingClient {
+        fn reject(&self) -> PipelineError {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            PipelineError::Core(flashcard_core::errors::PipelineError::Api {
+                message: "invalid API key".to_string(),
+                status_code: Some(401),
+            })
+        }
+    }
+    
+    #[async_trait::async_trait]
+    impl ApiClient for RejectingClient {
+        async fn process_stage1(&self, _item: &VocabularyItem) -> Result<Stage1Result> {
+            Err(self.reject())
+        }
+        
+        async fn process_stage2(&self, _item: &VocabularyItem, _stage1: &Stage1Result) -> Result<Stage2Result> {
+            Err(self.reject())
+        }
+        
+        async fn health_check(&self) -> Result<()> {
+            Ok(())
+        }
+    }
+    
+    #[tokio::test]
+    async fn test_failure_counts_a_retry_on_its_own_queue_row() {
+        let (_dir, pipeline) = test_pipeline_with(Arc::new(RejectingClient::default()), |config| PipelineConfig {
+            max_retries: 3,
+            ..config
+        }).await;
+        let mut items = crate::bench::synthetic_items(2);
+        let batch_id = pipeline.enqueue(&mut items).await.unwrap();
+        
+        let result = pipeline.batch_processor.process_batch(items, &batch_id).await.unwrap();
+        
+        assert_eq!(result.failed.len(), 2);
+        assert!(result.failed.iter().all(|failure| failure.retry_count == 1));
+        // Back to pending with one retry counted each, for a resume
+        let rows = pipeline.queue_repo.get_incomplete_items(&batch_id).await.unwrap();
+        assert_eq!(rows.len(), 2);
+        assert!(rows.iter().all(|row| row.status == ProcessingStatus::Pending && row.retry_count == 1));
+    }
+    
+    #[tokio::test]
+    async fn test_consecutive_failures_abort_batch() {
+        let client = Arc::new(RejectingClient::default());
+        let (_dir, pipeline) = test_pipeline_with(client.clone(), |config| PipelineConfig {
+            max_concurrent: 1,
+            // Small chunks, so the count has to carry across them
+            batch_size: 2,
+            max_consecutive_failures: Some(3),
+            ..config
+        }).await;
+        
+        let result = pipeline.process_items(crate::bench::synthetic_items(50)).await.unwrap();
+        
+        assert_eq!(result.aborted.as_deref(), Some("3 items in a row failed"));
+        // The next item may start before the third failure is seen
+        let calls = client.calls.load(Ordering::SeqCst);
+        assert!((3..=4).contains(&calls), "{} calls", calls);
+        assert_eq!(result.failed.len(), calls);
+        assert_eq!(pipeline.metrics_collector.get_metrics().api_errors, calls);
+        assert!(result.successful.is_empty());
+        // Both the rest of the aborted chunk and the chunks after it
+        assert_eq!(result.total_processed, calls);
+        assert_eq!(result.left_pending, 50 - calls);
+    }
+    
+    #[tokio::test]
+    async fn test_aborted_run_still_records_cache_stats() {
+        let (dir, pipeline) = test_pipeline_with(Arc::new(RejectingClient::default()), |config| PipelineConfig {
+            max_concurrent: 1,
+            max_consecutive_failures: Some(2),
+            record_cache_stats: true,
+            ..config
+        }).await;
+        let input = dir.path().join("input.csv");
+        std::fs::write(&input, "position,term,type\n1,학교,noun\n2,바다,noun\n3,사과,noun\n").unwrap();
+        
+        let result = pipeline.process_csv_file(&input, &dir.path().join("output.tsv"), None).await;
+        
+        assert!(matches!(result, Err(PipelineError::ApiError(_))), "{:?}", result.map(|r| r.batch_id));
+        assert_eq!(pipeline.cache_growth(1).await.unwrap().len(), 1);
+    }
+    
+    /// The real queue, counting batched status writes
+    struct CountingQueue {
+        inner: Arc<dyn QueueRepository>,
+        status_writes: AtomicUsize,
+        /// Positions checkpointed, in order
+        checkpoints: parking_lot::Mutex<Vec<i64>>,
+    }
+    
+    impl CountingQueue {
+        fn new(inner: Arc<dyn QueueRepository>) -> Self {
+            Self {
+                inner,
+                status_writes: AtomicUsize::new(0),
+                checkpoints: parking_lot::Mutex::new(Vec::new()),
+            }
+        }
+    }
+    
+    #[async_trait::async_trait]
+    impl QueueRepository for CountingQueue {
+        async fn enqueue_batch(
+            &self,
+            vocabulary_ids: Vec<i64>,
+            batch_id: &BatchId,
+            max_retries: i32,
+            label: Option<&str>,
+        ) -> flashcard_core::Result<i64> {
+            self.inner.enqueue_batch(vocabulary_ids, batch_id, max_retries, label).await
+        }
+        
+        async fn get_next_pending(&self, batch_id: Option<&BatchId>) -> flashcard_core::Result<Option<QueueItem>> {
+            self.inner.get_next_pending(batch_id).await
+        }
+        
+        async fn get_incomplete_items(&self, batch_id: &BatchId) -> flashcard_core::Result<Vec<QueueItem>> {
+            self.inner.get_incomplete_items(batch_id).await
+        }
+        
+        async fn count_pending(&self) -> flashcard_core::Result<i64> {
+            self.inner.count_pending().await
+        }
+        
+        async fn update_status(
+            &self,
+            item_id: i64,
+            status: ProcessingStatus,
+            error_message: Option<String>,
+        ) -> flashcard_core::Result<()> {
+            self.inner.update_status(item_id, status, error_message).await
+        }
+        
+        async fn update_status_batch(&self, updates: &[(i64, ProcessingStatus)]) -> flashcard_core::Result<u64> {
+            self.status_writes.fetch_add(1, Ordering::SeqCst);
+            self.inner.update_status_batch(updates).await
+        }
+        
+        async fn record_cache_keys(&self, keys: &[(i64, String)]) -> flashcard_core::Result<u64> {
+            self.inner.record_cache_keys(keys).await
+        }
+        
+        async fn complete_stage(&self, item_id: i64) -> flashcard_core::Result<ProcessingStage> {
+            self.inner.complete_stage(item_id).await
+        }
+        
+        async fn increment_retry(&self, item_id: i64) -> flashcard_core::Result<bool> {
+            self.inner.increment_retry(item_id).await
+        }
+        
+        async fn list_batches(&self, label: Option<&str>, limit: i64) -> flashcard_core::Result<Vec<BatchSummary>> {
+            self.inner.list_batches(label, limit).await
+        }
+        
+        async fn list_unstarted_batches(&self, limit: i64) -> flashcard_core::Result<Vec<BatchSummary>> {
+            self.inner.list_unstarted_batches(limit).await
+        }
+        
+        async fn get_batch_progress(&self, batch_id: &BatchId) -> flashcard_core::Result<BatchProgress> {
+            self.inner.get_batch_progress(batch_id).await
+        }
+        
+        async fn save_checkpoint(
+            &self,
+            batch_id: &BatchId,
+            last_processed_id: i64,
+            stage: ProcessingStage,
+            checkpoint_data: serde_json::Value,
+        ) -> flashcard_core::Result<()> {
+            self.checkpoints.lock().push(last_processed_id);
+            self.inner.save_checkpoint(batch_id, last_processed_id, stage, checkpoint_data).await
+        }
+        
+        async fn get_latest_checkpoint(&self, batch_id: &BatchId) -> flashcard_core::Result<Option<ProcessingCheckpoint>> {
+            self.inner.get_latest_checkpoint(batch_id).await
+        }
+    }
+    
+    #[tokio::test]
+    async fn test_batched_status_writes_for_1000_items() {
+        let (_dir, pipeline) = test_pipeline(|config| config).await;
+        let queue = Arc::new(CountingQueue::new(Arc::clone(&pipeline.queue_repo)));
+        // Flushed by size alone; the timer's first tick comes before any
+        // item has run
+        let processor = crate::batch_processor::BatchProcessor::new(
+            Arc::new(crate::python_bridge::MockApiClient),
+            Arc::clone(&pipeline.cache_manager),
+            queue.clone(),
+            Arc::new(MetricsCollector::new()),
+            4,
+        )
+        .with_status_batching(300, Duration::from_secs(3600));
+        let mut items = crate::bench::synthetic_items(1000);
+        let batch_id = pipeline.enqueue(&mut items).await.unwrap();
+        
+        let result = processor.process_batch(items, &batch_id).await.unwrap();
+        
+        assert_eq!(result.successful_count(), 1000);
+        let progress = pipeline.queue_repo.get_batch_progress(&batch_id).await.unwrap();
+        assert_eq!(progress.completed_items, 1000);
+        
+        // Three writes of 300 transitions and the last hundred in the final
+        // flush, rather than one per stage of each item. The few items in
+        // flight at a flush move a boundary by no more than the permits
+        assert_eq!(queue.status_writes.load(Ordering::SeqCst), 4);
+    }
+    
+    #[tokio::test]
+    async fn test_each_chunk_is_checkpointed() {
+        let (_dir, pipeline) = test_pipeline(|config| PipelineConfig {
+            batch_size: 10,
+            ..config
+        }).await;
+        let queue = Arc::new(CountingQueue::new(Arc::clone(&pipeline.queue_repo)));
+        let processor = crate::batch_processor::BatchProcessor::new(
+            Arc::new(crate::python_bridge::MockApiClient),
+            Arc::clone(&pipeline.cache_manager),
+            queue.clone(),
+            Arc::new(MetricsCollector::new()),
+            4,
+        );
+        let mut items = crate::bench::synthetic_items(25);
+        let batch_id = pipeline.enqueue(&mut items).await.unwrap();
+        
+        let result = pipeline.process_chunks(&processor, items, &batch_id, None).await.unwrap();
+        
+        assert_eq!(result.successful_count(), 25);
+        assert_eq!(*queue.checkpoints.lock(), [10, 20, 25]);
+        let checkpoint = pipeline.queue_repo.get_latest_checkpoint(&batch_id).await.unwrap().unwrap();
+        assert_eq!(checkpoint.last_processed_id, 25);
+    }
+    
+    #[tokio::test]
+    async fn test_failed_item_is_not_checkpointed_as_completed() {
+        let mut items = crate::bench::synthetic_items(2);
+        let (_dir, pipeline) = test_pipeline_with(Arc::new(FailsOnTerm::new(&items[1].term)), |config| config).await;
+        let batch_id = pipeline.enqueue(&mut items).await.unwrap();
+        
+        let result = pipeline.process_chunks(&pipeline.batch_processor, items, &batch_id, None).await.unwrap();
+        
+        assert_eq!(result.failed.len(), 1);
+        assert_eq!(result.last_completed, Some(1));
+        let checkpoint = pipeline.queue_repo.get_latest_checkpoint(&batch_id).await.unwrap().unwrap();
+        assert_eq!(checkpoint.last_processed_id, 1);
+    }
+    
+    #[tokio::test]
+    async fn test_fully_cached_item_skips_both_stages() {
+        let (_dir, pipeline) = test_pipeline(|config| config).await;
+        let items = crate::bench::synthetic_items(1);
+        pipeline.process_items(items.clone()).await.unwrap();
+        
+        // Any compute call would be counted, and fail
+        let client = Arc::new(RejectingClient::default());
+        let statuses = crate::batch_processor::StatusBuffer::new();
+        let result = crate::batch_processor::BatchProcessor::process_single_item(
+            &items[0],
+            client.clone(),
+            Arc::clone(&pipeline.cache_manager),
+            statuses.item(Some(1)),
+            &MetricsCollector::new(),
+            &RetryPolicy::default(),
+            &crate::concurrency::ApiLimiter::unlimited(),
+            None,
+            false,
+            QualityGate::default(),
+            Stage2Mode::default(),
+            &ModelSelection::default(),
+        ).await;
+        
+        assert!(matches!(result, Ok((_, _, true))));
+        assert_eq!(client.calls.load(Ordering::SeqCst), 0);
+        assert!(statuses.is_empty());
+    }
+    
+    /// Run `items` through `process_single_item` all at once, with API
+    /// calls going through `api`
+    async fn process_concurrently(
+        pipeline: &Pipeline,
+        items: &[VocabularyItem],
+        api: &crate::concurrency::ApiLimiter,
+    ) -> Vec<std::result::Result<(Stage1Result, Stage2Result, bool), crate::batch_processor::ItemFailure>> {
+        let client: Arc<dyn ApiClient> = Arc::new(
+            crate::python_bridge::LatencyMockClient::new(Duration::from_millis(10)),
+        );
+        let statuses = crate::batch_processor::StatusBuffer::new();
+        let metrics = MetricsCollector::new();
+        let retry_policy = RetryPolicy::default();
+        let models = ModelSelection::default();
+        
+        futures::future::join_all(items.iter().map(|item| {
+            crate::batch_processor::BatchProcessor::process_single_item(
+                item,
+                Arc::clone(&client),
+                Arc::clone(&pipeline.cache_manager),
+                statuses.item(None),
+                &metrics,
+                &retry_policy,
+                api,
+                None,
+                false,
+                QualityGate::default(),
+                Stage2Mode::default(),
+                &models,
+            )
+        })).await
+    }
+    
+    #[tokio::test]
+    async fn test_cache_hits_take_no_api_permits() {
+        let (_dir, pipeline) = test_pipeline(|config| config).await;
+        let items = crate::bench::synthetic_items(120);
+        let (cached, uncached) = items.split_at(100);
+        pipeline.process_items(cached.to_vec()).await.unwrap();
+        let api = crate::concurrency::ApiLimiter::new(5);
+        
+        // All at once, and every one a cache hit, so none reaches the limiter
+        let results = process_concurrently(&pipeline, cached, &api).await;
+        assert!(results.iter().all(|result| matches!(result, Ok((_, _, true)))));
+        assert_eq!(api.peak(), 0);
+        
+        // Misses are held to the API limit
+        let results = process_concurrently(&pipeline, uncached, &api).await;
+        assert!(results.iter().all(|result| matches!(result, Ok((_, _, false)))));
+        assert_eq!(api.peak(), 5);
+    }
+    
+    /// Answers Stage 1 with an analysis missing its meaning, keywords and
+    /// IPA, and counts Stage 2 calls
+    #[derive(Default)]
+    struct HollowClient {
+        stage2_calls: AtomicUsize,
+    }
+    
+    #[async_trait::async_trait]
+    impl ApiClient for HollowClient {
+        async fn process_stage1(&self, item: &VocabularyItem) -> Result<Stage1Result> {
+            let mut stage1 = crate::python_bridge::MockApiClient.process_stage1(item).await?;
+            stage1.primary_meaning.clear();
+            stage1.korean_keywords.clear();
+            stage1.ipa.clear();
+            Ok(stage1)
+        }
+        
+        async fn process_stage2(&self, item: &VocabularyItem, stage1: &Stage1Result) -> Result<Stage2Result> {
+            self.stage2_calls.fetch_add(1, Ordering::SeqCst);
+            crate::python_bridge::MockApiClient.process_stage2(item, stage1).await
+        }
+        
+        async fn health_check(&self) -> Result<()> {
+            Ok(())
+        }
+    }
+    
+    #[tokio::test]
+    async fn test_low_quality_stage1_is_quarantined() {
+        let client = Arc::new(HollowClient::default());
+        let (dir, pipeline) = test_pipeline_with(client.clone(), |config| config).await;
+        let input = dir.path().join("words.csv");
+        std::fs::write(&input, "position,term,type\n1,학교,noun\n").unwrap();
+        let output = dir.path().join("cards.tsv");
+        
+        let processed = pipeline.process_csv_file(&input, &output, None).await.unwrap();
+        
+        assert_eq!((processed.successful_items, processed.failed_items), (0, 1));
+        assert_eq!(processed.export_stats.cards_exported, 0);
+        assert_eq!(client.stage2_calls.load(Ordering::SeqCst), 0);
+        
+        // Quarantined on the first failure, so a resume has nothing to retry
+        let resumed = pipeline.process_csv_file(&input, &output, Some(&processed.batch_id)).await.unwrap();
+        assert!(resumed.nothing_to_do());
+    }
+    
+    #[tokio::test]
+    async fn test_concurrency_above_pool_size() {
+        let (_dir, pipeline) = test_pipeline(|config| PipelineConfig {
+            max_concurrent: MAX_CONNECTIONS as usize * 10,
+            db_concurrency: Some(2),
+            batch_size: 0,
+            ..config
+        }).await;
+        
+        let result = pipeline.process_items(crate::bench::synthetic_items(200)).await.unwrap();
+        
+        // Items wait for a connection slot instead of timing out on the pool
+        assert!(result.failed.is_empty(), "{:?}", result.failed);
+        assert_eq!(result.successful.len(), 200);
+        // Cache queries and queue writes together never took more slots
+        assert_eq!(pipeline.db_limiter.peak(), 2);
+    }
+    
+    #[tokio::test]
+    async fn test_diff_batches_matches_terms() {
+        let (dir, pipeline) = test_pipeline(|config| config).await;
+        let process = |name: &str, rows: &str| {
+            let input = dir.path().join(name);
+            std::fs::write(&input, format!("position,term,type\n{}", rows)).unwrap();
+            let output = input.with_extension("tsv");
+            let pipeline = &pipeline;
+            async move { pipeline.process_csv_file(&input, &output, None).await.unwrap().batch_id }
+        };
+        
+        let old = process("old.csv", "1,학교,noun\n2,바다,noun\n").await;
+        // Reordered, with one term dropped and one added
+        let new = process("new.csv", "1,사과,noun\n2,학교,noun\n").await;
+        
+        let diff = pipeline.diff_batches(&old, &new).await.unwrap();
+        
+        assert_eq!(diff.unchanged, 1);
+        assert!(diff.changed.is_empty());
+        assert_eq!(diff.added, vec!["사과"]);
+        assert_eq!(diff.removed, vec!["바다"]);
+    }
+    
+    /// The mock client's cards, worded differently
+    struct RewordingClient;
+    
+    #[async_trait::async_trait]
+    impl ApiClient for RewordingClient {
+        async fn process_stage1(&self, item: &VocabularyItem) -> Result<Stage1Result> {
+            crate::python_bridge::MockApiClient.process_stage1(item).await
+        }
+        
+        async fn process_stage2(&self, item: &VocabularyItem, stage1: &Stage1Result) -> Result<Stage2Result> {
+            let mut card = crate::python_bridge::MockApiClient.process_stage2(item, stage1).await?;
+            card.back.primary_field = "Reworded back".to_string();
+            Ok(card)
+        }
+        
+        async fn health_check(&self) -> Result<()> {
+            Ok(())
+        }
+    }
+    
+    #[tokio::test]
+    async fn test_diff_batches_sees_cards_change_between_runs() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = test_config(dir.path());
+        let input = dir.path().join("input.csv");
+        std::fs::write(&input, "position,term,type\n1,학교,noun\n2,바다,noun\n").unwrap();
+        
+        let first = Pipeline::with_api_client(config.clone(), Arc::new(crate::python_bridge::MockApiClient))
+            .await
+            .unwrap();
+        let old = first.process_csv_file(&input, &dir.path().join("old.tsv"), None).await.unwrap().batch_id;
+        
+        // The same words under another key scheme, so nothing is cached,
+        // with a client whose cards read differently
+        let config = PipelineConfig { pin_model_in_key: true, ..config };
+        let second = Pipeline::with_api_client(config, Arc::new(RewordingClient)).await.unwrap();
+        let new = second.process_csv_file(&input, &dir.path().join("new.tsv"), None).await.unwrap().batch_id;
+        
+        let diff = second.diff_batches(&old, &new).await.unwrap();
+        
+        assert_eq!(diff.unchanged, 0);
+        assert_eq!(diff.changed.len(), 2);
+        assert!(diff.added.is_empty() && diff.removed.is_empty());
+        
+        // Each batch still reads its own cards
+        assert_eq!(second.diff_batches(&old, &old).await.unwrap().unchanged, 2);
+    }
+    
+    #[tokio::test]
+    async fn test_cache_keys_match_item_key() {
+        let (dir, pipeline) = test_pipeline(|config| config).await;
+        let input = dir.path().join("input.csv");
+        std::fs::write(&input, "position,term,type\n1,학교,noun\n").unwrap();
+        pipeline.process_csv_file(&input, &dir.path().join("output.tsv"), None).await.unwrap();
+        
+        let item = VocabularyItem {
+            id: None,
+            position: 1,
+            term: "학교".to_string(),
+            word_type: Some("noun".to_string()),
+            source: String::new(),
+            created_at: chrono::Utc::now(),
+            updated_at: chrono::Utc::now(),
+        };
+        let report = pipeline.cache_keys("학교", Some("noun".to_string())).await.unwrap();
+        
+        assert_eq!(report.stage1_key, Stage1Result::generate_cache_key(&item));
+        assert!(report.stage1_key.ends_with(&item.generate_cache_key()));
+        assert_eq!(report.stage2_key, Stage2Result::generate_cache_key(&item, &report.stage1_key));
+        let stage1_entry = report.stage1_entry.unwrap();
+        assert_eq!(stage1_entry.cache_key, report.stage1_key);
+        assert!(report.stage2_entry.is_some());
+        
+        // Inspecting isn't a read
+        let again = pipeline.cache_keys("학교", Some("noun".to_string())).await.unwrap();
+        assert_eq!(again.stage1_entry.unwrap().access_count, stage1_entry.access_count);
+        
+        let missing = pipeline.cache_keys("바다", None).await.unwrap();
+        assert!(missing.stage1_entry.is_none() && missing.stage2_entry.is_none());
+    }
+    
+    #[tokio::test]
+    async fn test_prefetched_chunks_keep_their_order() {
+        let (_dir, pipeline) = test_pipeline(|config| PipelineConfig {
+            batch_size: 2,
+            prefetch_chunks: 2,
+            ..config
+        }).await;
+        let items = crate::bench::synthetic_items(7);
+        
+        // Every other item is cached beforehand
+        let cached: Vec<_> = items.iter().step_by(2).cloned().collect();
+        pipeline.process_items(cached).await.unwrap();
+        let prefetched = pipeline.batch_processor.prefetch(items.clone()).await;
+        let mut positions: Vec<i32> = prefetched.into_keys().collect();
+        positions.sort();
+        assert_eq!(positions, [1, 3, 5, 7]);
+        
+        let result = pipeline.process_items(items).await.unwrap();
+        assert_eq!(result.successful.len(), 7);
+        assert_eq!(result.cache_hits, 4);
+        // Items finish in any order within a chunk, but never ahead of it
+        let chunks: Vec<Vec<i32>> = result.successful
+            .chunks(2)
+            .map(|chunk| {
+                let mut positions: Vec<i32> = chunk.iter().map(|(item, _, _)| item.position).collect();
+                positions.sort();
+                positions
+            })
+            .collect();
+        assert_eq!(chunks, [vec![1, 2], vec![3, 4], vec![5, 6], vec![7]]);
+    }
+    
+    #[tokio::test]
+    async fn test_phrase_exports_whole_and_tagged() {
+        let (dir, pipeline) = test_pipeline(|config| config).await;
+        let input = dir.path().join("input.csv");
+        let output = dir.path().join("output.tsv");
+        std::fs::write(&input, "position,term,type\n1,잘 지내세요,phrase\n2,학교,noun\n").unwrap();
+        
+        let batch_id = pipeline.process_csv_file(&input, &output, None).await.unwrap().batch_id;
+        let reexported = dir.path().join("reexport.tsv");
+        pipeline.reexport_batch(&batch_id, ExportFormat::Tsv, &reexported).await.unwrap();
+        
+        for path in [&output, &reexported] {
+            let contents = std::fs::read_to_string(path).unwrap();
+            let mut lines = contents.lines().map(|line| line.split('\t').collect::<Vec<_>>());
+            let header = lines.next().unwrap();
+            let tags = header.iter().position(|&column| column == "Tags").unwrap();
+            let mut rows: Vec<Vec<&str>> = lines.collect();
+            rows.sort_by_key(|fields| fields[0]);
+            
+            assert!(rows.iter().all(|fields| fields.len() == header.len()));
+            assert_eq!(rows[0][1], "잘 지내세요");
+            let phrase_tagged = |fields: &[&str]| fields[tags].split(", ").any(|tag| tag == flashcard_core::models::PHRASE_TAG);
+            assert!(phrase_tagged(&rows[0]));
+            assert!(!phrase_tagged(&rows[1]));
+        }
+    }
+    
+    #[tokio::test]
+    async fn test_suspended_term_is_tagged_on_reexport() {
+        let (dir, pipeline) = test_pipeline(|config| config).await;
+        let input = dir.path().join("input.csv");
+        std::fs::write(&input, "position,term,type\n1,학교,noun\n2,바다,noun\n").unwrap();
+        let batch_id = pipeline.process_csv_file(&input, &dir.path().join("output.tsv"), None).await.unwrap().batch_id;
+        let reexported = dir.path().join("reexport.tsv");
+        // Term and Tags columns, found by header
+        let tags_by_term = |path: &Path| {
+            let contents = std::fs::read_to_string(path).unwrap();
+            let mut lines = contents.lines().map(|line| line.split('\t').collect::<Vec<_>>());
+            let header = lines.next().unwrap();
+            let column = |name: &str| header.iter().position(|&column| column == name).unwrap();
+            let (term, tags) = (column("Term"), column("Tags"));
+            let mut tags: Vec<(String, String)> = lines
+                .map(|fields| (fields[term].to_string(), fields[tags].to_string()))
+                .collect();
+            tags.sort();
+            tags
+        };
+        
+        // Suspended by its decomposed spelling, as some keyboards type it
+        pipeline.set_suspended("\u{1112}\u{1161}\u{11A8}\u{1100}\u{116D}", true, Some(true)).await.unwrap();
+        pipeline.reexport_batch(&batch_id, ExportFormat::Tsv, &reexported).await.unwrap();
+        assert_eq!(tags_by_term(&reexported), [
+            ("바다".to_string(), String::new()),
+            ("학교".to_string(), "suspended, leech".to_string()),
+        ]);
+        
+        let json = dir.path().join("reexport.json");
+        pipeline.reexport_batch(&batch_id, ExportFormat::Json, &json).await.unwrap();
+        let cards: Vec<(VocabularyItem, Stage1Result, Stage2Result)> =
+            serde_json::from_str(&std::fs::read_to_string(&json).unwrap()).unwrap();
+        for (item, _, stage2) in &cards {
+            let suspended = stage2.front.thematic_tags.iter().any(|tag| tag == crate::export::SUSPENDED_TAG);
+            assert_eq!(suspended, item.term == "학교", "{}", item.term);
+        }
+        
+        pipeline.set_suspended("학교", false, Some(false)).await.unwrap();
+        pipeline.reexport_batch(&batch_id, ExportFormat::Tsv, &reexported).await.unwrap();
+        assert!(tags_by_term(&reexported).iter().all(|(_, tags)| tags.is_empty()));
+    }
+    
+    #[tokio::test]
+    async fn test_audit_logs_each_api_call() {
+        let (dir, pipeline) = test_pipeline(|config| PipelineConfig {
+            audit_api_calls: true,
+            stage2_model: Some("anthropic/claude-3-haiku".to_string()),
+            ..config
+        }).await;
+        let input = dir.path().join("input.csv");
+        std::fs::write(&input, "position,term,type\n1,학교,noun\n").unwrap();
+        let output = dir.path().join("output.tsv");
+        let batch_id = pipeline.process_csv_file(&input, &output, None).await.unwrap().batch_id;
+        
+        let calls = pipeline.api_calls(Some(&batch_id), 10).await.unwrap();
+        
+        let logged: Vec<_> = calls.iter()
+            .map(|call| (call.term.as_str(), call.stage.clone(), call.model.as_str(), call.success))
+            .collect();
+        assert_eq!(logged, vec![
+            ("학교", CacheType::Stage1, DEFAULT_MODEL, true),
+            ("학교", CacheType::Stage2, "anthropic/claude-3-haiku", true),
+        ]);
+        assert!(calls.iter().all(|call| call.batch_id.as_ref() == Some(&batch_id)));
+        assert!(calls.iter().all(|call| call.error_message.is_none()));
+        
+        // A re-run served from the cache makes no calls to log
+        pipeline.process_csv_file(&input, &output, None).await.unwrap();
+        assert_eq!(pipeline.api_calls(None, 10).await.unwrap().len(), 2);
+    }
+    
+    /// Answers like the mock, tracking how many batches have a call in
+    /// flight at once
+    #[derive(Default)]
+    struct BatchOverlapProbe {
+        /// Batch of each term
+        batch_of: HashMap<String, usize>,
+        in_flight: parking_lot::Mutex<HashMap<usize, usize>>,
+        most_batches: AtomicUsize,
+    }
+    
+    impl BatchOverlapProbe {
+        async fn call<T>(&self, term: &str, call: impl std::future::Future<Output = T>) -> T {
+            let batch = self.batch_of[term];
+            {
+                let mut in_flight = self.in_flight.lock();
+                *in_flight.entry(batch).or_default() += 1;
+                self.most_batches.fetch_max(in_flight.len(), Ordering::SeqCst);
+            }
+            // Long enough for every batch allowed to start to get a call in
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            let result = call.await;
+            
+            let mut in_flight = self.in_flight.lock();
+            if let Some(calls) = in_flight.get_mut(&batch) {
+                *calls -= 1;
+                if *calls == 0 {
+                    in_flight.remove(&batch);
+                }
+            }
+            result
+        }
+    }
+    
+    #[async_trait::async_trait]
+    impl ApiClient for BatchOverlapProbe {
+        async fn process_stage1(&self, item: &VocabularyItem) -> Result<Stage1Result> {
+            self.call(&item.term, crate::python_bridge::MockApiClient.process_stage1(item)).await
+        }
+        
+        async fn process_stage2(&self, item: &VocabularyItem, stage1: &Stage1Result) -> Result<Stage2Result> {
+            self.call(&item.term, crate::python_bridge::MockApiClient.process_stage2(item, stage1)).await
+        }
+        
+        async fn health_check(&self) -> Result<()> {
+            Ok(())
+        }
+    }
+    
+    #[tokio::test]
+    async fn test_queued_batches_run_two_at_a_time() {
+        let mut batches: Vec<Vec<VocabularyItem>> = crate::bench::synthetic_items(6)
+            .chunks(2)
+            .map(<[_]>::to_vec)
+            .collect();
+        let probe = Arc::new(BatchOverlapProbe {
+            batch_of: batches.iter()
+                .enumerate()
+                .flat_map(|(batch, items)| items.iter().map(move |item| (item.term.clone(), batch)))
+                .collect(),
+            ..Default::default()
+        });
+        let (dir, pipeline) = test_pipeline_with(probe.clone(), |config| PipelineConfig {
+            max_concurrent_batches: 2,
+            ..config
+        }).await;
+        let mut queued = Vec::new();
+        for items in &mut batches {
+            queued.push(pipeline.enqueue(items).await.unwrap());
+        }
+        let output_dir = dir.path().join("exports");
+        
+        let outcomes = pipeline.process_queued_batches(&output_dir).await.unwrap();
+        
+        // The third batch waited for a slot
+        assert!(probe.most_batches.load(Ordering::SeqCst) <= 2);
+        
+        let mut finished: Vec<_> = outcomes.iter().map(|(batch_id, _)| batch_id.clone()).collect();
+        finished.sort();
+        queued.sort();
+        assert_eq!(finished, queued);
+        for (batch_id, result) in &outcomes {
+            let result = result.as_ref().unwrap();
+            assert_eq!(&result.batch_id, batch_id);
+            assert_eq!(result.successful_items, 2);
+            assert_eq!(result.failed_items, 0);
+            assert!(result.output_path.as_ref().unwrap().starts_with(&output_dir));
+            
+            let status = pipeline.get_batch_status(batch_id).await.unwrap();
+            assert_eq!(status.completed_items, 2);
+            assert!(!status.in_progress);
+        }
+        // Each batch got its own file
+        assert_eq!(std::fs::read_dir(&output_dir).unwrap().count(), 3);
+        
+        // Nothing is pending any more
+        assert!(pipeline.process_queued_batches(&output_dir).await.unwrap().is_empty());
+    }
+    
+    /// Fails Stage 1 of one term and answers the rest like the mock,
+    /// counting Stage 1 calls
+    struct FailsOnTerm {
+        term: String,
+        calls: AtomicUsize,
+    }
+    
+    impl FailsOnTerm {
+        fn new(term: &str) -> Self {
+            Self { term: term.to_string(), calls: AtomicUsize::new(0) }
+        }
+    }
+    
+    #[async_trait::async_trait]
+    impl ApiClient for FailsOnTerm {
+        async fn process_stage1(&self, item: &VocabularyItem) -> Result<Stage1Result> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            if item.term == self.term {
+                return Err(PipelineError::ApiError("connection reset".to_string()));
+            }
+            crate::python_bridge::MockApiClient.process_stage1(item).await
+        }
+        
+        async fn process_stage2(&self, item: &VocabularyItem, stage1: &Stage1Result) -> Result<Stage2Result> {
+            crate::python_bridge::MockApiClient.process_stage2(item, stage1).await
+        }
+        
+        async fn health_check(&self) -> Result<()> {
+            Ok(())
+        }
+    }
+    
+    #[tokio::test]
+    async fn test_interrupted_warm_resumes_at_its_checkpoint() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = PipelineConfig { max_concurrent: 1, ..test_config(dir.path()) };
+        let items = crate::bench::synthetic_items(5);
+        let options = WarmupOptions { checkpoint_interval: 2, ..Default::default() };
+        
+        let broken = Pipeline::with_api_client(config.clone(), Arc::new(FailsOnTerm::new(&items[3].term)))
+            .await
+            .unwrap();
+        assert!(broken.warm_cache(&items, options).await.is_err());
+        let warm_key = broken.fill_key(&items, false);
+        let checkpoint = broken.cache_repo.get_warm_checkpoint(&warm_key).await.unwrap();
+        assert_eq!(checkpoint.map(|(next_index, _)| next_index), Some(2));
+        
+        let client = Arc::new(FailsOnTerm::new("none"));
+        let fixed = Pipeline::with_api_client(config, client.clone()).await.unwrap();
+        let resumed = fixed.warm_cache(&items, WarmupOptions { resume: true, ..options }).await.unwrap();
+        
+        // Item 2 was cached before the failure; 3 and 4 weren't
+        assert_eq!(resumed, 2);
+        assert_eq!(client.calls.load(Ordering::SeqCst), 2);
+        assert!(fixed.cache_repo.get_warm_checkpoint(&warm_key).await.unwrap().is_none());
+        
+        // Everything is cached now
+        let coverage = fixed.probe_cache(&items, false).await.unwrap();
+        assert_eq!(coverage.stage2_cached, 5);
+        assert_eq!(fixed.warm_cache(&items, options).await.unwrap(), 0);
+    }
+    
+    /// Panics on one term, as a broken bridge call might, and answers the
+    /// rest like the mock
+    struct PanickingClient {
+        term: String,
+    }
+    
+    #[async_trait::async_trait]
+    impl ApiClient for PanickingClient {
+        async fn process_stage1(&self, item: &VocabularyItem) -> Result<Stage1Result> {
+            if item.term == self.term {
+                panic!("GIL released twice");
+            }
+            crate::python_bridge::MockApiClient.process_stage1(item).await
+        }
+        
+        async fn process_stage2(&self, item: &VocabularyItem, stage1: &Stage1Result) -> Result<Stage2Result> {
+            crate::python_bridge::MockApiClient.process_stage2(item, stage1).await
+        }
+        
+        async fn health_check(&self) -> Result<()> {
+            Ok(())
+        }
+    }
+    
+    #[tokio::test]
+    async fn test_panicking_item_fails_alone() {
+        let items = crate::bench::synthetic_items(5);
+        let client = Arc::new(PanickingClient { term: items[2].term.clone() });
+        let (_dir, pipeline) = test_pipeline_with(client, |config| config).await;
+        
+        let result = pipeline.process_items(items.clone()).await.unwrap();
+        
+        assert_eq!(result.successful.len(), 4);
+        assert_eq!(result.failed.len(), 1);
+        let failure = &result.failed[0];
+        assert_eq!((failure.term.as_str(), failure.position), (items[2].term.as_str(), items[2].position));
+        assert_eq!(failure.category, "panic");
+        assert!(failure.message.contains("GIL released twice"), "{}", failure.message);
+        // Our own failure, which the adaptive controller shouldn't back off for
+        assert_eq!(pipeline.metrics_collector.get_metrics().api_errors, 0);
+    }
+    
+    #[tokio::test]
+    async fn test_reexport_batch_to_json() {
+        let (dir, pipeline) = test_pipeline(|config| config).await;
+        let input = dir.path().join("words.csv");
+        std::fs::write(&input, "position,term,type\n1,학교,noun\n2,바다,noun\n3,사과,noun\n").unwrap();
+        
+        let processed = pipeline.process_csv_file(&input, &dir.path().join("cards.tsv"), None).await.unwrap();
+        assert_eq!(processed.successful_items, 3);
+        
+        let json = dir.path().join("cards.json");
+        let stats = pipeline.reexport_batch(&processed.batch_id, ExportFormat::Json, &json).await.unwrap();
+        
+        assert_eq!(stats.cards_exported, processed.export_stats.cards_exported);
+        let cards: Vec<serde_json::Value> = serde_json::from_slice(&std::fs::read(&json).unwrap()).unwrap();
+        assert_eq!(cards.len(), 3);
+    }
+    
+    #[tokio::test]
+    async fn test_empty_input_is_an_error() {
+        let (dir, pipeline) = test_pipeline(|config| config).await;
+        let input = dir.path().join("words.csv");
+        std::fs::write(&input, "position,term,type\n").unwrap();
+        
+        let err = pipeline.process_csv_file(&input, &dir.path().join("cards.tsv"), None).await.unwrap_err();
+        
+        assert!(matches!(err, PipelineError::InvalidFormat(_)));
+    }
+    
+    #[tokio::test]
+    async fn test_resuming_finished_batch_has_nothing_to_do() {
+        let (dir, pipeline) = test_pipeline(|config| config).await;
+        let input = dir.path().join("words.csv");
+        std::fs::write(&input, "position,term,type\n1,학교,noun\n2,바다,noun\n").unwrap();
+        let output = dir.path().join("cards.tsv");
+        let processed = pipeline.process_csv_file(&input, &output, None).await.unwrap();
+        assert!(!processed.nothing_to_do());
+        let exported = std::fs::read(&output).unwrap();
+        
+        let resumed = pipeline.process_csv_file(&input, &output, Some(&processed.batch_id)).await.unwrap();
+        
+        assert!(resumed.nothing_to_do());
+        assert_eq!(resumed.already_processed, 2);
+        assert_eq!((resumed.total_items, resumed.successful_items, resumed.failed_items), (0, 0, 0));
+        assert_eq!(resumed.export_stats.cards_exported, 0);
+        // The earlier export isn't overwritten with an empty one
+        assert_eq!(std::fs::read(&output).unwrap(), exported);
+    }
+    
+    #[tokio::test]
+    async fn test_retry_count_survives_restart() {
+        let dir = tempfile::tempdir().unwrap();
+        let input = dir.path().join("words.csv");
+        std::fs::write(&input, "position,term,type\n1,학교,noun\n").unwrap();
+        let output = dir.path().join("cards.tsv");
+        let config = PipelineConfig { max_retries: 2, ..test_config(dir.path()) };
+        
+        // Two failures, persisting a retry count of 2
+        let client = Arc::new(RejectingClient::default());
+        let pipeline = Pipeline::with_api_client(config.clone(), client.clone()).await.unwrap();
+        let first = pipeline.process_csv_file(&input, &output, None).await.unwrap();
+        let batch_id = first.batch_id;
+        let second = pipeline.process_csv_file(&input, &output, Some(&batch_id)).await.unwrap();
+        assert_eq!((first.failed_items, second.failed_items), (1, 1));
+        drop(pipeline);
+        
+        // After a restart the third failure spends the budget rather than
+        // counting from 0 again
+        let client = Arc::new(RejectingClient::default());
+        let pipeline = Pipeline::with_api_client(config, client.clone()).await.unwrap();
+        let third = pipeline.process_csv_file(&input, &output, Some(&batch_id)).await.unwrap();
+        assert_eq!(third.failed_items, 1);
+        
+        // so the quarantined item isn't tried again
+        let resumed = pipeline.process_csv_file(&input, &output, Some(&batch_id)).await.unwrap();
+        assert!(resumed.nothing_to_do());
+        assert_eq!(client.calls.load(Ordering::SeqCst), 1);
+    }
+    
+    #[tokio::test]
+    async fn test_queue_finds_batch_by_pipeline_id() {
+        let (dir, pipeline) = test_pipeline(|config| config).await;
+        let input = dir.path().join("words.csv");
+        std::fs::write(&input, "position,term,type\n1,학교,noun\n2,바다,noun\n").unwrap();
+        let result = pipeline.process_csv_file(&input, &dir.path().join("cards.tsv"), None).await.unwrap();
+        
+        // As printed in the summary and typed back in on the command line
+        let batch_id: BatchId = result.batch_id.to_string().parse().unwrap();
+        
+        let progress = pipeline.queue_repo.get_batch_progress(&batch_id).await.unwrap();
+        assert_eq!(progress.batch_id, result.batch_id);
+        assert_eq!((progress.total_items, progress.completed_items), (2, 2));
+        let listed = pipeline.queue_repo.list_batches(None, 10).await.unwrap();
+        assert!(listed.iter().any(|batch| batch.batch_id == batch_id && batch.total_items == 2));
+        assert_eq!(pipeline.get_batch_status(&batch_id).await.unwrap().completed_items, 2);
+        assert!(pipeline.vocab_repo.list_incomplete_in_batch(&batch_id).await.unwrap().is_empty());
+    }
+    
+    #[tokio::test]
+    async fn test_list_batches_newest_first_up_to_limit() {
+        let (_dir, pipeline) = test_pipeline(|config| config).await;
+        let mut batch_ids = Vec::new();
+        for mut items in crate::bench::synthetic_items(6).chunks(2).map(<[_]>::to_vec) {
+            batch_ids.push(pipeline.enqueue(&mut items).await.unwrap());
+        }
+        
+        let listed = pipeline.list_batches(None, 2).await.unwrap();
+        
+        let listed_ids: Vec<&BatchId> = listed.iter().map(|batch| &batch.batch_id).collect();
+        assert_eq!(listed_ids, vec![&batch_ids[2], &batch_ids[1]]);
+        assert!(listed.iter().all(|batch| batch.total_items == 2));
+        assert_eq!(pipeline.list_batches(None, 10).await.unwrap().len(), 3);
+        assert!(pipeline.list_batches(Some("unused"), 10).await.unwrap().is_empty());
+    }
+    
+    #[tokio::test]
+    async fn test_output_directory_gets_batch_named_file() {
+        let (dir, pipeline) = test_pipeline(|config| config).await;
+        let input = dir.path().join("words.csv");
+        std::fs::write(&input, "position,term,type\n1,학교,noun\n2,바다,noun\n").unwrap();
+        // Doesn't exist yet, so only the trailing separator marks it a directory
+        let exports = dir.path().join("exports");
+        let output = PathBuf::from(format!("{}{}", exports.display(), std::path::MAIN_SEPARATOR));
+        
+        let result = pipeline.process_csv_file(&input, &output, None).await.unwrap();
+        
+        let written = result.output_path.unwrap();
+        assert_eq!(written.parent(), Some(exports.as_path()));
+        let name = written.file_name().unwrap().to_str().unwrap();
+        let prefix = format!("batch_{}_", result.batch_id);
+        let timestamp = name.strip_prefix(&prefix).and_then(|rest| rest.strip_suffix(".tsv")).unwrap();
+        assert!(chrono::NaiveDateTime::parse_from_str(timestamp, "%Y%m%d_%H%M%S").is_ok(), "{}", name);
+        assert!(std::fs::read_to_string(&written).unwrap().contains("학교"));
+    }
+    
+    #[test]
+    fn test_batch_size_chunks_and_merges_totals() {
+        let items: Vec<VocabularyItem> = (1..=25)
+            .map(|position| VocabularyItem {
+                id: None,
+                position,
+                term: format!("단어{}", position),
+                word_type: None,
+                source: String::new(),
+                created_at: chrono::Utc::now(),
+                updated_at: chrono::Utc::now(),
+            })
+            .collect();
+        
+        let chunks = chunk_items(items, 10);
+        let sizes: Vec<usize> = chunks.iter().map(Vec::len).collect();
+        assert_eq!(sizes, vec![10, 10, 5]);
+        assert_eq!(chunks[2][0].position, 21);
+        assert_eq!(chunk_items(chunks[0].clone(), 0).len(), 1);
+        
+        // One failure and one cache hit per chunk
+        let results = chunks.iter().map(|chunk| BatchResult {
+            failed: vec![FailureRecord {
+                term: chunk[0].term.clone(),
+                position: chunk[0].position,
+                stage: FailureStage::Stage1,
+                category: "api".to_string(),
+                message: "boom".to_string(),
+                retry_count: 0,
+            }],
+            total_processed: chunk.len(),
+            cache_hits: 1,
+            processing_time: Duration::from_secs(2),
+            ..Default::default()
+        });
+        let merged = BatchResult::merge(results);
+        
+        assert_eq!(merged.total_processed, 25);
+        assert_eq!(merged.failed.len(), 3);
+        assert_eq!(merged.cache_hits, 3);
+        assert_eq!(merged.processing_time, Duration::from_secs(6));
+        assert_eq!(merged.failed[2].position, 21);
+    }
+    
+    #[tokio::test]
+    async fn test_buffer_cap_spills_streamed_cards() {
+        let (dir, pipeline) = test_pipeline(|config| {
+            let config = PipelineConfig {
+                stream_export: true,
+                max_buffered_results: Some(4),
+                batch_size: 15,
+                ..config
+            };
+            config.validate().unwrap();
+            config
+        }).await;
+        let mut items = crate::bench::synthetic_items(50);
+        let batch_id = pipeline.enqueue(&mut items).await.unwrap();
+        let output = dir.path().join("output.tsv");
+        
+        let (result, export_stats) = pipeline
+            .process_streaming(&pipeline.batch_processor, items, &batch_id, &output)
+            .await
+            .unwrap();
+        
+        assert!(result.peak_buffered <= 4, "held {} cards at once", result.peak_buffered);
+        assert_eq!(result.successful_count(), 50);
+        assert_eq!(result.total_processed, 50);
+        assert!(result.successful.is_empty());
+        assert_eq!(export_stats.cards_exported, 50);
+        let contents = std::fs::read_to_string(&output).unwrap();
+        let mut positions: Vec<i32> = contents.lines().skip(1)
+            .map(|line| line.split('\t').next().unwrap().parse().unwrap())
+            .collect();
+        positions.sort();
+        assert_eq!(positions, (1..=50).collect::<Vec<_>>());
+        
+        let not_streamed = PipelineConfig { max_buffered_results: Some(4), ..Default::default() };
+        assert!(not_streamed.validate().is_err());
+    }
+    
+    /// [`MockApiClient`](crate::python_bridge::MockApiClient) that holds
+    /// each Stage 1 call at `started` until the test joins it, then until
+    /// `release` is cancelled
+    struct GatedClient {
+        started: Arc<tokio::sync::Barrier>,
+        release: CancellationToken,
+    }
+    
+    #[async_trait::async_trait]
+    impl ApiClient for GatedClient {
+        async fn process_stage1(&self, item: &VocabularyItem) -> Result<Stage1Result> {
+            self.started.wait().await;
+            self.release.cancelled().await;
+            crate::python_bridge::MockApiClient.process_stage1(item).await
+        }
+        
+        async fn process_stage2(&self, item: &VocabularyItem, stage1: &Stage1Result) -> Result<Stage2Result> {
+            crate::python_bridge::MockApiClient.process_stage2(item, stage1).await
+        }
+        
+        async fn health_check(&self) -> Result<()> {
+            Ok(())
+        }
+    }
+    
+    #[tokio::test]
+    async fn test_drain_mid_batch_exports_finished_items() {
+        // Both permits' calls and the test
+        let started = Arc::new(tokio::sync::Barrier::new(3));
+        let release = CancellationToken::new();
+        let client = Arc::new(GatedClient { started: Arc::clone(&started), release: release.clone() });
+        let (dir, pipeline) = test_pipeline_with(client, |config| PipelineConfig {
+            max_concurrent: 2,
+            batch_size: 4,
+            shutdown_grace_period: Duration::from_secs(60),
+            ..config
+        }).await;
+        let input = dir.path().join("input.csv");
+        let output = dir.path().join("output.tsv");
+        std::fs::write(&input, "position,term,type\n1,학교,noun\n2,바다,noun\n3,하늘,noun\n4,나무,noun\n5,사과,noun\n6,물,noun\n").unwrap();
+        
+        // A signal once the first two items are in flight, which only
+        // finish after it
+        let shutdown = pipeline.shutdown_coordinator();
+        tokio::spawn(async move {
+            started.wait().await;
+            shutdown.request();
+            release.cancel();
+        });
+        let result = pipeline.process_csv_file(&input, &output, None).await;
+        
+        // Ok is what `main` exits 0 on
+        let result = result.unwrap();
+        assert_eq!((result.successful_items, result.failed_items), (2, 0));
+        assert_eq!(result.left_pending, 4);
+        assert!(!pipeline.cancellation_token().is_cancelled());
+        
+        let contents = std::fs::read_to_string(&output).unwrap();
+        let mut terms: Vec<&str> = contents.lines().skip(1).map(|line| line.split('\t').nth(1).unwrap()).collect();
+        terms.sort();
+        assert_eq!(terms, vec!["바다", "학교"]);
+        
+        let pending = pipeline.vocab_repo.list_incomplete_in_batch(&result.batch_id).await.unwrap();
+        let mut positions: Vec<i32> = pending.iter().map(|item| item.position).collect();
+        positions.sort();
+        assert_eq!(positions, vec![3, 4, 5, 6]);
+    }
+    
+    #[test]
+    fn test_prepare_cache_dir_creates_missing_directory() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache_dir = dir.path().join("nested").join(".cache");
+        
+        prepare_cache_dir(&cache_dir).unwrap();
+        
+        assert!(cache_dir.is_dir());
+        assert_eq!(std::fs::read_dir(&cache_dir).unwrap().count(), 0);
+    }
+    
+    #[test]
+    fn test_prepare_cache_dir_rejects_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let not_a_dir = dir.path().join("cache");
+        std::fs::write(&not_a_dir, "").unwrap();
+        
+        let err = prepare_cache_dir(&not_a_dir).unwrap_err();
+        assert!(matches!(err, PipelineError::ConfigError(_)));
+    }
+    
+    #[tokio::test]
+    async fn test_malformed_frequency_list_reports_the_line() {
+        let dir = tempfile::tempdir().unwrap();
+        let frequency_list = dir.path().join("frequency.txt");
+        std::fs::write(&frequency_list, "물\n사람\t5120\n").unwrap();
+        let config = PipelineConfig { frequency_list: Some(frequency_list), ..test_config(dir.path()) };
+        
+        let err = Pipeline::with_api_client(config, Arc::new(crate::python_bridge::MockApiClient)).await.err().unwrap();
+        assert!(matches!(&err, PipelineError::ConfigError(message) if message.contains("line 2")), "{}", err);
+    }
+}
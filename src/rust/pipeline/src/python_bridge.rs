@@ -3,58 +3,184 @@ use pyo3::prelude::*;
 #[cfg(feature = "python")]
 use pyo3_asyncio::tokio::future_into_py;
 use async_trait::async_trait;
-use flashcard_core::models::{VocabularyItem, Stage1Result, Stage2Result, FlashcardContent};
+use flashcard_core::models::{VocabularyItem, Stage1Result, Stage2Result, Stage2Mode};
+#[cfg(any(test, feature = "mock"))]
+use flashcard_core::models::FlashcardContent;
 use crate::errors::{PipelineError, Result};
-use std::path::Path;
+use flashcard_core::errors::PipelineError as CoreError;
+use std::path::{Path, PathBuf};
 use parking_lot::RwLock;
 use std::sync::Arc;
+use std::time::Duration;
 use tracing::{info, debug, error, instrument};
 
+/// Wait used when a rate-limit error doesn't say how long to back off
+const DEFAULT_RETRY_AFTER_SECS: u64 = 60;
+
+/// Where the Python client keeps its response cache when none is configured
+pub const DEFAULT_CACHE_DIR: &str = ".cache";
+
+/// Model the Python orchestrator uses when none is configured
+pub const DEFAULT_MODEL: &str = "anthropic/claude-3.5-sonnet";
+
+/// Which model the orchestrator runs for each stage
+#[derive(Debug, Clone, PartialEq)]
+pub struct ModelSelection {
+    pub stage1: String,
+    pub stage2: String,
+}
+
+impl ModelSelection {
+    /// The same model for both stages
+    pub fn single(model: impl Into<String>) -> Self {
+        let model = model.into();
+        Self {
+            stage1: model.clone(),
+            stage2: model,
+        }
+    }
+}
+
+impl Default for ModelSelection {
+    fn default() -> Self {
+        Self::single(DEFAULT_MODEL)
+    }
+}
+
+/// Prompt template files the orchestrator uses instead of its built-in
+/// ones, per stage
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PromptPaths {
+    pub stage1: Option<PathBuf>,
+    pub stage2: Option<PathBuf>,
+}
+
+impl PromptPaths {
+    /// A hash of each stage's custom prompt, for
+    /// [`CacheManager::with_prompt_versions`](flashcard_core::cache_manager::CacheManager::with_prompt_versions),
+    /// or `None` where the built-in prompt is used. Hashing the contents
+    /// rather than the path means editing a prompt in place invalidates its
+    /// cached results too.
+    pub fn versions(&self) -> Result<(Option<String>, Option<String>)> {
+        Ok((prompt_version(self.stage1.as_deref())?, prompt_version(self.stage2.as_deref())?))
+    }
+}
+
+fn prompt_version(path: Option<&Path>) -> Result<Option<String>> {
+    use sha2::{Digest, Sha256};
+    
+    path.map(|path| {
+        let prompt = std::fs::read(path).map_err(|e| PipelineError::ConfigError(format!(
+            "Can't read prompt {}: {}", path.display(), e
+        )))?;
+        Ok(format!("{:x}", Sha256::digest(&prompt)))
+    })
+    .transpose()
+}
+
 #[async_trait]
 pub trait ApiClient: Send + Sync {
     async fn process_stage1(&self, item: &VocabularyItem) -> Result<Stage1Result>;
     async fn process_stage2(&self, item: &VocabularyItem, stage1: &Stage1Result) -> Result<Stage2Result>;
     async fn health_check(&self) -> Result<()>;
+    
+    /// Like `process_stage1`, also returning the tokens the call used.
+    /// Clients that can't report usage return 0.
+    async fn process_stage1_with_usage(&self, item: &VocabularyItem) -> Result<(Stage1Result, usize)> {
+        Ok((self.process_stage1(item).await?, 0))
+    }
+    
+    /// Like `process_stage2`, also returning the tokens the call used.
+    ///
+    /// In [`Stage2Mode::Minimal`] only the essential fields are kept. Clients
+    /// that can't ask for less generate the full card and have the rest
+    /// cleared.
+    async fn process_stage2_with_usage(
+        &self,
+        item: &VocabularyItem,
+        stage1: &Stage1Result,
+        mode: Stage2Mode,
+    ) -> Result<(Stage2Result, usize)> {
+        let mut card = self.process_stage2(item, stage1).await?;
+        card.restrict_to(mode);
+        Ok((card, 0))
+    }
+}
+
+/// `total_tokens` from the usage half of an orchestrator `(result, usage)` tuple.
+#[cfg(feature = "python")]
+fn extract_total_tokens(result: &PyAny) -> usize {
+    result.get_item(1)
+        .and_then(|usage| usage.getattr("total_tokens"))
+        .and_then(|tokens| tokens.extract())
+        .unwrap_or(0)
+}
+
+/// Module the bridge imports before its first call
+#[cfg(feature = "python")]
+const PYTHON_MODULE: &str = "flashcard_pipeline";
+
+/// How often the bridge tries to import the Python module before giving
+/// up, for environments where it only becomes importable shortly after
+/// startup, e.g. while a container's virtualenv is still being activated
+#[cfg(feature = "python")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ImportRetry {
+    /// Imports tried in all, including the first
+    pub attempts: u32,
+    /// Wait between attempts
+    pub delay: Duration,
+}
+
+#[cfg(feature = "python")]
+impl Default for ImportRetry {
+    fn default() -> Self {
+        Self {
+            attempts: 3,
+            delay: Duration::from_millis(500),
+        }
+    }
 }
 
 #[cfg(feature = "python")]
 pub struct PythonBridge {
     initialized: Arc<RwLock<bool>>,
+    cache_dir: PathBuf,
+    models: ModelSelection,
+    prompts: PromptPaths,
+    import_retry: ImportRetry,
 }
 
 #[cfg(feature = "python")]
 impl PythonBridge {
-    pub fn new() -> Result<Self> {
+    pub fn new(cache_dir: PathBuf) -> Result<Self> {
         Ok(Self {
             initialized: Arc::new(RwLock::new(false)),
+            cache_dir,
+            models: ModelSelection::default(),
+            prompts: PromptPaths::default(),
+            import_retry: ImportRetry::default(),
         })
     }
     
-    fn ensure_initialized(&self) -> Result<()> {
-        let mut initialized = self.initialized.write();
-        if !*initialized {
-            pyo3::prepare_freethreaded_python();
-            Python::with_gil(|py| {
-                // Add Python module path
-                let sys = py.import("sys")?;
-                let path = sys.getattr("path")?;
-                
-                // Get the absolute path to the Python source
-                let python_path = std::env::current_dir()?
-                    .join("src")
-                    .join("python");
-                    
-                path.call_method1("insert", (0, python_path.to_str().unwrap()))?;
-                
-                // Import and verify the module
-                py.import("flashcard_pipeline")?;
-                
-                Ok::<(), PipelineError>(())
-            })?;
-            *initialized = true;
-            info!("Python bridge initialized successfully");
-        }
-        Ok(())
+    /// Pass these models to the orchestrator instead of its default.
+    pub fn with_models(mut self, models: ModelSelection) -> Self {
+        self.models = models;
+        self
+    }
+    
+    /// Have the orchestrator load these prompt templates instead of its
+    /// built-in ones.
+    pub fn with_prompts(mut self, prompts: PromptPaths) -> Self {
+        self.prompts = prompts;
+        self
+    }
+    
+    /// Retry a failed import of the Python module this way instead of
+    /// the default
+    pub fn with_import_retry(mut self, import_retry: ImportRetry) -> Self {
+        self.import_retry = import_retry;
+        self
     }
     
     async fn call_python_async<F, R>(&self, func: F) -> Result<R>
@@ -62,31 +188,124 @@ impl PythonBridge {
         F: FnOnce(Python) -> PyResult<R> + Send + 'static,
         R: Send + 'static,
     {
-        self.ensure_initialized()?;
+        let initialized = Arc::clone(&self.initialized);
+        let python_path = std::env::current_dir()?.join("src").join("python");
+        let import_retry = self.import_retry;
         
+        // Initializing may sleep between import attempts, so it runs on the
+        // blocking pool along with the call
         tokio::task::spawn_blocking(move || {
-            Python::with_gil(|py| func(py).map_err(PipelineError::from))
+            initialize(&initialized, &python_path, PYTHON_MODULE, import_retry)?;
+            Python::with_gil(|py| func(py).map_err(|e| map_python_error(py, e)))
         })
         .await
         .map_err(|e| PipelineError::PythonError(format!("Task join error: {}", e)))?
     }
 }
 
+/// Import `module` from `python_path` unless `initialized` says it already
+/// was. The flag is only set once an import succeeds, so a bridge whose
+/// imports all failed tries again on its next call.
+#[cfg(feature = "python")]
+fn initialize(initialized: &RwLock<bool>, python_path: &Path, module: &str, retry: ImportRetry) -> Result<()> {
+    let mut initialized = initialized.write();
+    if *initialized {
+        return Ok(());
+    }
+    
+    pyo3::prepare_freethreaded_python();
+    import_with_retry(python_path, module, retry)?;
+    *initialized = true;
+    info!("Python bridge initialized successfully");
+    Ok(())
+}
+
+/// Put `python_path` first on `sys.path` and import `module`, trying up to
+/// `retry.attempts` times. The error after the last attempt carries its
+/// Python traceback.
+#[cfg(feature = "python")]
+fn import_with_retry(python_path: &Path, module: &str, retry: ImportRetry) -> Result<()> {
+    let attempts = retry.attempts.max(1);
+    let mut attempt = 1;
+    loop {
+        let imported = Python::with_gil(|py| -> PyResult<()> {
+            let path = py.import("sys")?.getattr("path")?;
+            let entry = python_path.to_string_lossy();
+            if !path.contains(entry.as_ref())? {
+                path.call_method1("insert", (0, entry.as_ref()))?;
+            }
+            // Forget directory listings cached by a failed attempt, so
+            // packages installed since then are found
+            py.import("importlib")?.call_method0("invalidate_caches")?;
+            py.import(module)?;
+            Ok(())
+        });
+        
+        match imported {
+            Ok(()) => return Ok(()),
+            Err(err) if attempt < attempts => {
+                tracing::warn!(
+                    "Importing {} failed (attempt {} of {}), retrying in {:?}: {}",
+                    module, attempt, attempts, retry.delay, err
+                );
+                std::thread::sleep(retry.delay);
+                attempt += 1;
+            }
+            Err(err) => {
+                let traceback = Python::with_gil(|py| format_traceback(py, &err));
+                return Err(PipelineError::PythonError(format!(
+                    "Failed to import {} after {} attempt(s):\n{}", module, attempts, traceback
+                )));
+            }
+        }
+    }
+}
+
+/// `err` as Python prints it, traceback included
+#[cfg(feature = "python")]
+fn format_traceback(py: Python, err: &PyErr) -> String {
+    let formatted = py.import("traceback")
+        .and_then(|traceback| {
+            traceback.call_method1("format_exception", (err.get_type(py), err.value(py), err.traceback(py)))
+        })
+        .and_then(|lines| lines.extract::<Vec<String>>());
+    
+    match formatted {
+        Ok(lines) => lines.concat(),
+        Err(_) => err.to_string(),
+    }
+}
+
 #[cfg(feature = "python")]
 #[async_trait]
 impl ApiClient for PythonBridge {
-    #[instrument(skip(self, item), fields(term = %item.term))]
     async fn process_stage1(&self, item: &VocabularyItem) -> Result<Stage1Result> {
+        Ok(self.process_stage1_with_usage(item).await?.0)
+    }
+    
+    async fn process_stage2(&self, item: &VocabularyItem, stage1: &Stage1Result) -> Result<Stage2Result> {
+        Ok(self.process_stage2_with_usage(item, stage1, Stage2Mode::Full).await?.0)
+    }
+    
+    #[instrument(skip(self, item), fields(term = %item.term))]
+    async fn process_stage1_with_usage(&self, item: &VocabularyItem) -> Result<(Stage1Result, usize)> {
         debug!("Processing stage 1 for term: {}", item.term);
         
         let item_clone = item.clone();
-        self.call_python_async(move |py| {
+        let cache_dir = self.cache_dir.to_string_lossy().into_owned();
+        let model = self.models.stage1.clone();
+        let prompt_path = self.prompts.stage1.as_ref().map(|path| path.to_string_lossy().into_owned());
+        let (json_str, tokens) = self.call_python_async(move |py| {
             let module = py.import("flashcard_pipeline.api_client")?;
             let orchestrator_class = module.getattr("PipelineOrchestrator")?;
             
             // Create orchestrator instance
             let kwargs = pyo3::types::PyDict::new(py);
-            kwargs.set_item("cache_dir", ".cache")?;
+            kwargs.set_item("cache_dir", &cache_dir)?;
+            kwargs.set_item("model", &model)?;
+            if let Some(prompt_path) = &prompt_path {
+                kwargs.set_item("prompt_path", prompt_path)?;
+            }
             let orchestrator = orchestrator_class.call((), Some(kwargs))?;
             
             // Create VocabularyItem dict
@@ -104,6 +323,7 @@ impl ApiClient for PythonBridge {
             
             // Extract the stage1_result from tuple (stage1_result, usage)
             let stage1_result = result.get_item(0)?;
+            let tokens = extract_total_tokens(result);
             
             // Convert to JSON string for deserialization
             let json_module = py.import("json")?;
@@ -111,24 +331,42 @@ impl ApiClient for PythonBridge {
                 .call_method1("dumps", (stage1_result.call_method0("dict")?,))?
                 .extract()?;
             
-            serde_json::from_str(&json_str).map_err(PyErr::from)
-        }).await
+            Ok((json_str, tokens))
+        }).await?;
+        
+        let stage1_result = flashcard_core::json::from_str(
+            &json_str,
+            &format!("Stage 1 result for '{}'", item.term),
+        )?;
+        Ok((stage1_result, tokens))
     }
     
     #[instrument(skip(self, item, stage1), fields(term = %item.term))]
-    async fn process_stage2(&self, item: &VocabularyItem, stage1: &Stage1Result) -> Result<Stage2Result> {
+    async fn process_stage2_with_usage(
+        &self,
+        item: &VocabularyItem,
+        stage1: &Stage1Result,
+        mode: Stage2Mode,
+    ) -> Result<(Stage2Result, usize)> {
         debug!("Processing stage 2 for term: {}", item.term);
         
         let item_clone = item.clone();
         let stage1_clone = stage1.clone();
+        let cache_dir = self.cache_dir.to_string_lossy().into_owned();
+        let model = self.models.stage2.clone();
+        let prompt_path = self.prompts.stage2.as_ref().map(|path| path.to_string_lossy().into_owned());
         
-        self.call_python_async(move |py| {
+        let (json_str, tokens) = self.call_python_async(move |py| {
             let module = py.import("flashcard_pipeline.api_client")?;
             let orchestrator_class = module.getattr("PipelineOrchestrator")?;
             
             // Create orchestrator instance
             let kwargs = pyo3::types::PyDict::new(py);
-            kwargs.set_item("cache_dir", ".cache")?;
+            kwargs.set_item("cache_dir", &cache_dir)?;
+            kwargs.set_item("model", &model)?;
+            if let Some(prompt_path) = &prompt_path {
+                kwargs.set_item("prompt_path", prompt_path)?;
+            }
             let orchestrator = orchestrator_class.call((), Some(kwargs))?;
             
             // Create VocabularyItem dict
@@ -156,14 +394,23 @@ impl ApiClient for PythonBridge {
             
             // Extract the stage2_result from tuple (stage2_result, usage)
             let stage2_result = result.get_item(0)?;
+            let tokens = extract_total_tokens(result);
             
             // Convert to JSON string for deserialization
             let json_str: String = json_module
                 .call_method1("dumps", (stage2_result.call_method0("dict")?,))?
                 .extract()?;
             
-            serde_json::from_str(&json_str).map_err(PyErr::from)
-        }).await
+            Ok((json_str, tokens))
+        }).await?;
+        
+        // The orchestrator always generates full cards
+        let mut stage2_result: Stage2Result = flashcard_core::json::from_str(
+            &json_str,
+            &format!("Stage 2 result for '{}'", item.term),
+        )?;
+        stage2_result.restrict_to(mode);
+        Ok((stage2_result, tokens))
     }
     
     async fn health_check(&self) -> Result<()> {
@@ -182,11 +429,114 @@ impl ApiClient for PythonBridge {
     }
 }
 
-// Mock implementation for testing without Python
-#[cfg(not(feature = "python"))]
+/// Convert a Python exception into a typed error, keeping the HTTP status and
+/// `retry_after` carried by the orchestrator's `ApiError`/`RateLimitError` so
+/// retry decisions can tell a 503 from a 400.
+#[cfg(feature = "python")]
+fn map_python_error(py: Python, err: PyErr) -> PipelineError {
+    let value = err.value(py);
+    let type_name = value
+        .get_type()
+        .name()
+        .map(|n| n.to_string())
+        .unwrap_or_default();
+    let message = value
+        .getattr("message")
+        .and_then(|m| m.extract::<String>())
+        .unwrap_or_else(|_| value.to_string());
+    let status_code = value
+        .getattr("status_code")
+        .and_then(|c| c.extract::<Option<u16>>())
+        .ok()
+        .flatten();
+    let retry_after = value
+        .getattr("retry_after")
+        .and_then(|r| r.extract::<Option<u64>>())
+        .ok()
+        .flatten();
+    
+    match classify_python_error(&type_name, &message, status_code, retry_after) {
+        Some(mapped) => mapped,
+        None => PipelineError::from(err),
+    }
+}
+
+/// Map an orchestrator exception to the core API error variants.
+///
+/// Returns `None` for exceptions that are not API failures, which are left
+/// as generic Python errors.
+pub fn classify_python_error(
+    type_name: &str,
+    message: &str,
+    status_code: Option<u16>,
+    retry_after: Option<u64>,
+) -> Option<PipelineError> {
+    match (type_name, status_code) {
+        ("RateLimitError", _) | (_, Some(429)) => Some(PipelineError::Core(CoreError::RateLimit {
+            retry_after: retry_after.unwrap_or(DEFAULT_RETRY_AFTER_SECS),
+        })),
+        ("ApiError" | "AuthenticationError", _) | (_, Some(_)) => Some(PipelineError::Core(CoreError::Api {
+            message: message.to_string(),
+            status_code,
+        })),
+        _ => None,
+    }
+}
+
+/// Parse the string form of a Python exception (`"ApiError: ... status_code=503"`).
+///
+/// Used when only the formatted error is available, e.g. from a traceback
+/// captured by the orchestrator.
+pub fn parse_python_error(error: &str) -> PipelineError {
+    let (type_name, message) = match error.split_once(": ") {
+        Some((name, rest)) => (name.rsplit('.').next().unwrap_or(name).trim(), rest.trim()),
+        None => ("", error.trim()),
+    };
+    
+    let status_code = extract_number(message, &["status_code=", "status code ", "HTTP "])
+        .and_then(|n| u16::try_from(n).ok());
+    let retry_after = extract_number(message, &["retry_after=", "retry after "]);
+    
+    classify_python_error(type_name, message, status_code, retry_after)
+        .unwrap_or_else(|| PipelineError::PythonError(error.to_string()))
+}
+
+fn extract_number(text: &str, markers: &[&str]) -> Option<u64> {
+    markers.iter().find_map(|marker| {
+        let start = text.find(marker)? + marker.len();
+        let digits: String = text[start..]
+            .chars()
+            .take_while(|c| c.is_ascii_digit())
+            .collect();
+        digits.parse().ok()
+    })
+}
+
+/// Stands in for the API in offline runs. Every call reports a cache miss,
+/// so only items already in the cache produce cards.
+pub struct OfflineApiClient;
+
+#[async_trait]
+impl ApiClient for OfflineApiClient {
+    async fn process_stage1(&self, item: &VocabularyItem) -> Result<Stage1Result> {
+        Err(PipelineError::NotCached(format!("stage 1 for {}", item.term)))
+    }
+    
+    async fn process_stage2(&self, item: &VocabularyItem, _stage1: &Stage1Result) -> Result<Stage2Result> {
+        Err(PipelineError::NotCached(format!("stage 2 for {}", item.term)))
+    }
+    
+    async fn health_check(&self) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// Canned responses for tests and benchmarks, available to other builds
+/// with the `mock` feature.
+#[cfg(any(test, feature = "mock"))]
 pub struct MockApiClient;
 
-#[cfg(not(feature = "python"))]
+#[cfg(any(test, feature = "mock"))]
 #[async_trait]
 impl ApiClient for MockApiClient {
     async fn process_stage1(&self, item: &VocabularyItem) -> Result<Stage1Result> {
@@ -222,6 +572,7 @@ impl ApiClient for MockApiClient {
                 secondary_field: Some("Mock secondary".to_string()),
                 tertiary_field: None,
                 example_sentence: None,
+                example_translation: None,
                 pronunciation_guide: None,
                 image_prompt: None,
                 mnemonic_aid: None,
@@ -239,6 +590,7 @@ impl ApiClient for MockApiClient {
                 secondary_field: None,
                 tertiary_field: None,
                 example_sentence: None,
+                example_translation: None,
                 pronunciation_guide: None,
                 image_prompt: None,
                 mnemonic_aid: None,
@@ -262,15 +614,197 @@ impl ApiClient for MockApiClient {
     }
 }
 
+/// [`MockApiClient`] that waits before answering, like a real API would,
+/// so throughput can be measured without spending tokens.
+#[cfg(any(test, feature = "mock"))]
+pub struct LatencyMockClient {
+    stage1_latency: Duration,
+    stage2_latency: Duration,
+    jitter: Duration,
+}
+
+#[cfg(any(test, feature = "mock"))]
+impl LatencyMockClient {
+    /// Every call takes `latency`.
+    pub fn new(latency: Duration) -> Self {
+        Self {
+            stage1_latency: latency,
+            stage2_latency: latency,
+            jitter: Duration::ZERO,
+        }
+    }
+    
+    /// Separate latencies for Stage 1 and Stage 2 calls.
+    pub fn with_stage_latencies(mut self, stage1: Duration, stage2: Duration) -> Self {
+        self.stage1_latency = stage1;
+        self.stage2_latency = stage2;
+        self
+    }
+    
+    /// Add a random delay below `jitter` to every call.
+    pub fn with_jitter(mut self, jitter: Duration) -> Self {
+        self.jitter = jitter;
+        self
+    }
+    
+    async fn wait(&self, latency: Duration) {
+        tokio::time::sleep(latency + crate::retry::jitter(self.jitter)).await;
+    }
+}
+
+#[cfg(any(test, feature = "mock"))]
+#[async_trait]
+impl ApiClient for LatencyMockClient {
+    async fn process_stage1(&self, item: &VocabularyItem) -> Result<Stage1Result> {
+        self.wait(self.stage1_latency).await;
+        MockApiClient.process_stage1(item).await
+    }
+    
+    async fn process_stage2(&self, item: &VocabularyItem, stage1: &Stage1Result) -> Result<Stage2Result> {
+        self.wait(self.stage2_latency).await;
+        MockApiClient.process_stage2(item, stage1).await
+    }
+    
+    async fn health_check(&self) -> Result<()> {
+        Ok(())
+    }
+}
+
 pub fn create_api_client() -> Result<Box<dyn ApiClient>> {
+    create_api_client_with_cache_dir(Path::new(DEFAULT_CACHE_DIR))
+}
+
+/// Create the API client, pointing the Python response cache at `cache_dir`.
+pub fn create_api_client_with_cache_dir(cache_dir: &Path) -> Result<Box<dyn ApiClient>> {
+    create_configured_api_client(cache_dir, ModelSelection::default(), PromptPaths::default())
+}
+
+/// Create the API client with its response cache in `cache_dir`, running
+/// `models` for each stage.
+pub fn create_configured_api_client(
+    cache_dir: &Path,
+    models: ModelSelection,
+    prompts: PromptPaths,
+) -> Result<Box<dyn ApiClient>> {
     #[cfg(feature = "python")]
     {
-        Ok(Box::new(PythonBridge::new()?))
+        Ok(Box::new(PythonBridge::new(cache_dir.to_path_buf())?.with_models(models).with_prompts(prompts)))
     }
     
-    #[cfg(not(feature = "python"))]
+    #[cfg(all(not(feature = "python"), feature = "mock"))]
     {
         info!("Using mock API client (Python feature disabled)");
+        let _ = (cache_dir, models, prompts);
         Ok(Box::new(MockApiClient))
     }
-}
\ No newline at end of file
+    
+    // Without either, there is nothing to answer calls, and a build that
+    // quietly returned canned cards would look like it worked
+    #[cfg(all(not(feature = "python"), not(feature = "mock")))]
+    {
+        let _ = (cache_dir, models, prompts);
+        Err(PipelineError::ConfigError(
+            "built without the python feature, so there is no API client; enable python, or mock for canned responses".to_string(),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_parse_rate_limit_error() {
+        let err = parse_python_error("RateLimitError: Rate limit exceeded (retry_after=30)");
+        assert!(matches!(err, PipelineError::Core(CoreError::RateLimit { retry_after: 30 })));
+        assert!(err.is_retryable());
+    }
+    
+    #[test]
+    fn test_parse_server_error_is_retryable() {
+        let err = parse_python_error(
+            "flashcard_pipeline.exceptions.ApiError: Service unavailable (status_code=503)"
+        );
+        match &err {
+            PipelineError::Core(CoreError::Api { status_code, .. }) => {
+                assert_eq!(*status_code, Some(503));
+            }
+            other => panic!("unexpected variant: {:?}", other),
+        }
+        assert!(err.is_retryable());
+    }
+    
+    #[test]
+    fn test_parse_client_error_is_fatal() {
+        let err = parse_python_error("ApiError: Invalid request body (status_code=400)");
+        match &err {
+            PipelineError::Core(CoreError::Api { message, status_code }) => {
+                assert_eq!(*status_code, Some(400));
+                assert!(message.contains("Invalid request body"));
+            }
+            other => panic!("unexpected variant: {:?}", other),
+        }
+        assert!(!err.is_retryable());
+    }
+    
+    #[test]
+    fn test_parse_unrelated_error_stays_python_error() {
+        let err = parse_python_error("KeyError: 'term'");
+        assert!(matches!(err, PipelineError::PythonError(_)));
+    }
+    
+    #[cfg(feature = "python")]
+    #[test]
+    fn test_import_retries_until_module_loads() {
+        let dir = tempfile::tempdir().unwrap();
+        let marker = dir.path().join("attempted");
+        // Fails the first time, as if its dependencies weren't installed yet
+        std::fs::write(dir.path().join("flaky_bridge_module.py"), format!(
+            "import os\n\
+             if not os.path.exists({marker:?}):\n    \
+                 open({marker:?}, 'w').close()\n    \
+                 raise ImportError('virtualenv not ready')\n",
+            marker = marker.to_str().unwrap(),
+        )).unwrap();
+        let retry = ImportRetry { attempts: 3, delay: Duration::from_millis(10) };
+        
+        let initialized = RwLock::new(false);
+        initialize(&initialized, dir.path(), "flaky_bridge_module", retry).unwrap();
+        assert!(*initialized.read());
+        assert!(marker.exists());
+        
+        // Out of attempts, the flag stays unset and the traceback is kept
+        let initialized = RwLock::new(false);
+        let err = initialize(&initialized, dir.path(), "missing_bridge_module", retry).unwrap_err();
+        assert!(!*initialized.read());
+        let message = err.to_string();
+        assert!(message.contains("after 3 attempt(s)"), "{}", message);
+        assert!(message.contains("ModuleNotFoundError"), "{}", message);
+    }
+    
+    #[test]
+    fn test_prompt_versions_follow_file_contents() {
+        let dir = tempfile::tempdir().unwrap();
+        let stage1 = dir.path().join("stage1.txt");
+        let stage2 = dir.path().join("stage2.txt");
+        std::fs::write(&stage1, "Analyze {term}").unwrap();
+        std::fs::write(&stage2, "Analyze {term}").unwrap();
+        
+        assert_eq!(PromptPaths::default().versions().unwrap(), (None, None));
+        
+        let prompts = PromptPaths { stage1: Some(stage1.clone()), stage2: None };
+        let (before, stage2_version) = prompts.versions().unwrap();
+        assert!(before.is_some());
+        assert_eq!(stage2_version, None);
+        
+        // Same contents, same version, wherever the file lives
+        let moved = PromptPaths { stage1: Some(stage2.clone()), stage2: None };
+        assert_eq!(moved.versions().unwrap().0, before);
+        
+        std::fs::write(&stage1, "Analyze {term} in depth").unwrap();
+        assert_ne!(prompts.versions().unwrap().0, before);
+        
+        let missing = PromptPaths { stage1: None, stage2: Some(dir.path().join("missing.txt")) };
+        assert!(matches!(missing.versions(), Err(PipelineError::ConfigError(_))));
+    }
+}
@@ -0,0 +1,71 @@
+use flashcard_core::models::Stage1Result;
+use serde::{Deserialize, Serialize};
+
+/// Minimums a Stage 1 analysis has to meet before Stage 2 runs on it.
+///
+/// A thin analysis usually means the model wasn't sure of the term, and the
+/// card built from it would be just as thin, so the item is quarantined for
+/// review instead. The checks are heuristics on the fields' content, not on
+/// whether they are right.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct QualityGate {
+    /// Check Stage 1 results at all
+    pub enabled: bool,
+    /// Shortest acceptable primary meaning, in characters
+    pub min_primary_meaning_chars: usize,
+    /// Fewest non-blank Korean keywords
+    pub min_keywords: usize,
+    /// Reject analyses without an IPA transcription
+    pub require_ipa: bool,
+}
+
+impl Default for QualityGate {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            min_primary_meaning_chars: 1,
+            min_keywords: 1,
+            require_ipa: true,
+        }
+    }
+}
+
+impl QualityGate {
+    /// Every way `stage1` falls short, e.g. `"empty primary meaning, no IPA"`,
+    /// or `None` if it passes or the gate is off.
+    pub fn check(&self, stage1: &Stage1Result) -> Option<String> {
+        if !self.enabled {
+            return None;
+        }
+        
+        let mut problems = Vec::new();
+        
+        let meaning_chars = stage1.primary_meaning.trim().chars().count();
+        if meaning_chars == 0 && self.min_primary_meaning_chars > 0 {
+            problems.push("empty primary meaning".to_string());
+        } else if meaning_chars < self.min_primary_meaning_chars {
+            problems.push(format!(
+                "primary meaning under {} characters",
+                self.min_primary_meaning_chars
+            ));
+        }
+        
+        let keywords = stage1.korean_keywords.iter()
+            .filter(|keyword| !keyword.trim().is_empty())
+            .count();
+        if keywords < self.min_keywords {
+            problems.push(format!("{} of {} keywords", keywords, self.min_keywords));
+        }
+        
+        if self.require_ipa && stage1.ipa.trim().is_empty() {
+            problems.push("no IPA".to_string());
+        }
+        
+        if problems.is_empty() {
+            None
+        } else {
+            Some(problems.join(", "))
+        }
+    }
+}
@@ -0,0 +1,168 @@
+//! Per-run summary written alongside the export.
+
+use crate::batch_processor::FailureRecord;
+use crate::errors::Result;
+use crate::export::ExportStats;
+use crate::monitoring::PipelineMetrics;
+use crate::pipeline::ProcessingResult;
+use chrono::{DateTime, Utc};
+use flashcard_core::models::BatchId;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// Bumped whenever a field is renamed, removed or changes type
+pub const REPORT_VERSION: u32 = 2;
+
+/// Everything about one processed batch, for archiving next to its export.
+///
+/// Unlike the Prometheus metrics this describes a single run and carries the
+/// batch id and timestamps, so a report file makes sense on its own.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RunReport {
+    pub report_version: u32,
+    pub batch_id: BatchId,
+    pub label: Option<String>,
+    pub started_at: DateTime<Utc>,
+    pub finished_at: DateTime<Utc>,
+    pub duration_secs: f64,
+    pub total_items: usize,
+    pub successful_items: usize,
+    pub failed_items: usize,
+    pub skipped_items: usize,
+    /// Items a resumed batch had finished before this run
+    #[serde(default)]
+    pub already_processed: usize,
+    pub cache_hits: usize,
+    pub cache_hit_rate: f64,
+    pub api_calls: usize,
+    pub tokens_used: usize,
+    pub estimated_cost: f64,
+    pub export: ExportStats,
+    pub failures_by_category: BTreeMap<String, usize>,
+    pub failures: Vec<FailureRecord>,
+    pub error_report: Option<PathBuf>,
+}
+
+/// `part` as a percentage of `whole`, or `None` when `whole` is zero (an
+/// empty batch, or one where everything was skipped).
+pub fn percentage(part: usize, whole: usize) -> Option<f64> {
+    (whole > 0).then(|| part as f64 / whole as f64 * 100.0)
+}
+
+/// [`percentage`] for display, e.g. `"42.5%"`, or `"N/A"` when `whole` is zero.
+pub fn format_percentage(part: usize, whole: usize) -> String {
+    match percentage(part, whole) {
+        Some(percent) => format!("{:.1}%", percent),
+        None => "N/A".to_string(),
+    }
+}
+
+impl RunReport {
+    pub fn new(result: &ProcessingResult, metrics: &PipelineMetrics, label: Option<String>) -> Self {
+        let cache_hit_rate = if result.total_items == 0 {
+            0.0
+        } else {
+            result.cache_hits as f64 / result.total_items as f64
+        };
+        
+        Self {
+            report_version: REPORT_VERSION,
+            batch_id: result.batch_id.clone(),
+            label,
+            started_at: result.started_at,
+            finished_at: result.finished_at,
+            duration_secs: result.processing_time.as_secs_f64(),
+            total_items: result.total_items,
+            successful_items: result.successful_items,
+            failed_items: result.failed_items,
+            skipped_items: result.skipped_items,
+            already_processed: result.already_processed,
+            cache_hits: result.cache_hits,
+            cache_hit_rate,
+            api_calls: metrics.api_calls,
+            tokens_used: metrics.api_tokens_used,
+            estimated_cost: metrics.estimated_cost,
+            export: result.export_stats.clone(),
+            failures_by_category: result.failures_by_category.clone(),
+            failures: result.failures.clone(),
+            error_report: result.error_report.clone(),
+        }
+    }
+    
+    /// Write the report as pretty-printed JSON, creating parent directories.
+    pub fn write(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::batch_processor::FailureStage;
+    
+    #[test]
+    fn test_report_round_trip() {
+        let finished_at = Utc::now();
+        let report = RunReport {
+            report_version: REPORT_VERSION,
+            batch_id: BatchId::new("42"),
+            label: Some("acme".to_string()),
+            started_at: finished_at - chrono::Duration::seconds(90),
+            finished_at,
+            duration_secs: 90.0,
+            total_items: 3,
+            successful_items: 2,
+            failed_items: 1,
+            skipped_items: 0,
+            already_processed: 0,
+            cache_hits: 1,
+            cache_hit_rate: 1.0 / 3.0,
+            api_calls: 4,
+            tokens_used: 5200,
+            estimated_cost: 0.052,
+            export: ExportStats {
+                cards_exported: 2,
+                ..Default::default()
+            },
+            failures_by_category: BTreeMap::from([("api".to_string(), 1)]),
+            failures: vec![FailureRecord {
+                term: "사과".to_string(),
+                position: 3,
+                stage: FailureStage::Stage2,
+                category: "api".to_string(),
+                message: "API error: 503".to_string(),
+                retry_count: 0,
+            }],
+            error_report: Some(PathBuf::from("output.errors.csv")),
+        };
+        
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("reports").join("report.json");
+        report.write(&path).unwrap();
+        
+        let json = std::fs::read_to_string(&path).unwrap();
+        let restored: RunReport = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored.report_version, REPORT_VERSION);
+        assert_eq!(restored.batch_id, report.batch_id);
+        assert_eq!(restored.started_at, report.started_at);
+        assert_eq!(restored.failures[0].term, "사과");
+        assert_eq!(restored.failures[0].stage, FailureStage::Stage2);
+        assert_eq!(serde_json::to_string_pretty(&restored).unwrap(), json);
+    }
+    
+    #[test]
+    fn test_percentage_of_empty_batch() {
+        assert_eq!(percentage(0, 0), None);
+        assert_eq!(percentage(5, 0), None);
+        assert_eq!(percentage(1, 4), Some(25.0));
+        
+        assert_eq!(format_percentage(0, 0), "N/A");
+        assert_eq!(format_percentage(1, 3), "33.3%");
+        assert_eq!(format_percentage(3, 3), "100.0%");
+    }
+}
@@ -0,0 +1,294 @@
+use crate::errors::{PipelineError, Result};
+use crate::monitoring::MetricsCollector;
+use std::collections::hash_map::RandomState;
+use std::future::Future;
+use std::hash::{BuildHasher, Hasher};
+use std::sync::{Arc, OnceLock};
+use std::time::Duration;
+use tokio::time::Instant;
+use tracing::warn;
+
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    /// Retries after the first attempt, as the queue counts them
+    pub max_retries: u32,
+    /// Delay before the first retry, doubled on each one after
+    pub base_delay: Duration,
+    /// Cap on the exponential delay
+    pub max_delay: Duration,
+    /// Upper bound on the random delay added to every wait
+    pub max_jitter: Duration,
+    /// Wait for the server's `retry_after` on rate limits instead of backing off
+    pub honor_retry_after: bool,
+    /// Wall-clock time one item may spend on attempts and waits, across both
+    /// stages and all retries; `None` leaves only `max_retries`
+    pub per_item_budget: Option<Duration>,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: 3,
+            base_delay: Duration::from_secs(1),
+            max_delay: Duration::from_secs(60),
+            max_jitter: Duration::from_millis(250),
+            honor_retry_after: true,
+            per_item_budget: None,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// How long to wait after the zero-based `attempt` failed with `error`.
+    ///
+    /// A rate limit that names its own wait gets exactly that, plus jitter so
+    /// concurrent items don't all retry in the same instant.
+    pub fn delay_for(&self, attempt: u32, error: &PipelineError) -> Duration {
+        let delay = match error.retry_after() {
+            Some(retry_after) if self.honor_retry_after => retry_after,
+            _ => self.backoff(attempt),
+        };
+        delay + jitter(self.max_jitter)
+    }
+    
+    fn backoff(&self, attempt: u32) -> Duration {
+        self.base_delay
+            .saturating_mul(2u32.saturating_pow(attempt))
+            .min(self.max_delay)
+    }
+    
+    /// The [`per_item_budget`](Self::per_item_budget) for an item about to
+    /// be processed.
+    pub fn item_budget(&self) -> Option<ItemBudget> {
+        self.per_item_budget.map(ItemBudget::new)
+    }
+}
+
+/// An item's [`RetryPolicy::per_item_budget`], counting down from when its
+/// first API call got a permit, so time spent queued behind other items'
+/// calls isn't charged to it. Clones share the clock.
+#[derive(Debug, Clone)]
+pub struct ItemBudget {
+    budget: Duration,
+    deadline: Arc<OnceLock<Instant>>,
+}
+
+impl ItemBudget {
+    pub fn new(budget: Duration) -> Self {
+        Self { budget, deadline: Arc::new(OnceLock::new()) }
+    }
+    
+    /// When the budget runs out, starting the clock if nothing has yet.
+    fn deadline(&self) -> Instant {
+        *self.deadline.get_or_init(|| Instant::now() + self.budget)
+    }
+    
+    /// Whether waiting `delay` from now would run past the budget.
+    fn overrun_by(&self, delay: Duration) -> bool {
+        Instant::now() + delay >= self.deadline()
+    }
+    
+    fn exhausted(&self) -> PipelineError {
+        PipelineError::Timeout { budget: self.budget }
+    }
+}
+
+/// Run `operation` until it succeeds, fails with a non-retryable error or
+/// runs out of retries. Every rate-limit response is counted in `metrics`,
+/// including ones a later attempt recovers from.
+pub async fn with_retry<T, F, Fut>(
+    policy: &RetryPolicy,
+    metrics: &MetricsCollector,
+    operation: F,
+) -> Result<T>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T>>,
+{
+    with_retry_within(policy, metrics, None, operation).await
+}
+
+/// Like [`with_retry`], also giving up with [`PipelineError::Timeout`] once
+/// `budget` is spent, even with retries left: a retry whose wait would end
+/// past the deadline isn't waited for. Wrap each attempt in [`within`] to
+/// cut off one still running at the deadline.
+pub async fn with_retry_within<T, F, Fut>(
+    policy: &RetryPolicy,
+    metrics: &MetricsCollector,
+    budget: Option<&ItemBudget>,
+    mut operation: F,
+) -> Result<T>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T>>,
+{
+    let mut attempt = 0;
+    
+    loop {
+        let error = match operation().await {
+            Ok(value) => return Ok(value),
+            Err(error) => error,
+        };
+        
+        if error.is_rate_limit() {
+            metrics.record_rate_limit();
+        }
+        if attempt >= policy.max_retries || !error.is_retryable() {
+            return Err(error);
+        }
+        
+        let delay = policy.delay_for(attempt, &error);
+        if let Some(budget) = budget.filter(|budget| budget.overrun_by(delay)) {
+            warn!("Attempt {} failed ({}) with too little of the item's budget left to retry", attempt + 1, error);
+            return Err(budget.exhausted());
+        }
+        warn!("Attempt {} failed ({}), retrying in {:?}", attempt + 1, error, delay);
+        tokio::time::sleep(delay).await;
+        attempt += 1;
+    }
+}
+
+/// Run `call`, cutting it off with [`PipelineError::Timeout`] at `budget`'s
+/// deadline. The clock starts when `call` is first polled at the latest, so
+/// wrapped inside an API permit, time queued for the permit is free.
+pub async fn within<T, Fut>(budget: Option<&ItemBudget>, call: Fut) -> Result<T>
+where
+    Fut: Future<Output = Result<T>>,
+{
+    match budget {
+        Some(budget) => tokio::time::timeout_at(budget.deadline(), call)
+            .await
+            .unwrap_or_else(|_| Err(budget.exhausted())),
+        None => call.await,
+    }
+}
+
+/// A random duration below `max`, seeded from the std hasher's random keys.
+pub(crate) fn jitter(max: Duration) -> Duration {
+    let max_nanos = max.as_nanos() as u64;
+    if max_nanos == 0 {
+        return Duration::ZERO;
+    }
+    
+    let random = RandomState::new().build_hasher().finish();
+    Duration::from_nanos(random % max_nanos)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    
+    #[test]
+    fn test_backoff_doubles_up_to_cap() {
+        let policy = RetryPolicy {
+            max_jitter: Duration::ZERO,
+            max_delay: Duration::from_secs(5),
+            ..Default::default()
+        };
+        let error = PipelineError::ApiError("503".to_string());
+        
+        assert_eq!(policy.delay_for(0, &error), Duration::from_secs(1));
+        assert_eq!(policy.delay_for(2, &error), Duration::from_secs(4));
+        assert_eq!(policy.delay_for(5, &error), Duration::from_secs(5));
+    }
+    
+    #[test]
+    fn test_retry_after_replaces_backoff() {
+        let policy = RetryPolicy {
+            max_jitter: Duration::ZERO,
+            ..Default::default()
+        };
+        let error = PipelineError::RateLimitExceeded(7);
+        assert_eq!(policy.delay_for(3, &error), Duration::from_secs(7));
+        
+        let ignoring = RetryPolicy { honor_retry_after: false, ..policy };
+        assert_eq!(ignoring.delay_for(3, &error), Duration::from_secs(8));
+    }
+    
+    #[tokio::test(start_paused = true)]
+    async fn test_rate_limited_call_waits_retry_after_then_succeeds() {
+        let policy = RetryPolicy::default();
+        let metrics = MetricsCollector::new();
+        let calls = AtomicUsize::new(0);
+        
+        let started = tokio::time::Instant::now();
+        let result = with_retry(&policy, &metrics, || async {
+            if calls.fetch_add(1, Ordering::SeqCst) == 0 {
+                Err(PipelineError::RateLimitExceeded(2))
+            } else {
+                Ok("card")
+            }
+        }).await;
+        let waited = started.elapsed();
+        
+        assert_eq!(result.unwrap(), "card");
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+        assert!(waited >= Duration::from_secs(2));
+        assert!(waited < Duration::from_secs(2) + policy.max_jitter + Duration::from_millis(1));
+        assert_eq!(metrics.get_metrics().rate_limit_hits, 1);
+    }
+    
+    #[tokio::test(start_paused = true)]
+    async fn test_item_budget_stops_retries_early() {
+        let policy = RetryPolicy {
+            max_retries: 10,
+            max_jitter: Duration::ZERO,
+            per_item_budget: Some(Duration::from_secs(10)),
+            ..Default::default()
+        };
+        let metrics = MetricsCollector::new();
+        let calls = AtomicUsize::new(0);
+        
+        // Each call takes 3s to fail: attempts end at 3s and 7s after waits
+        // of 1s and 2s, and the third is cut off at the 10s deadline
+        let started = tokio::time::Instant::now();
+        let budget = policy.item_budget();
+        let result: Result<()> = with_retry_within(&policy, &metrics, budget.as_ref(), || within(budget.as_ref(), async {
+            calls.fetch_add(1, Ordering::SeqCst);
+            tokio::time::sleep(Duration::from_secs(3)).await;
+            Err(PipelineError::ApiError("503".to_string()))
+        })).await;
+        
+        assert!(matches!(result, Err(PipelineError::Timeout { budget }) if budget == Duration::from_secs(10)));
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+        assert_eq!(started.elapsed(), Duration::from_secs(10));
+        
+        // A wait that would end past the deadline isn't started
+        let policy = RetryPolicy { base_delay: Duration::from_secs(20), ..policy };
+        let calls = AtomicUsize::new(0);
+        let started = tokio::time::Instant::now();
+        let budget = policy.item_budget();
+        let result: Result<()> = with_retry_within(&policy, &metrics, budget.as_ref(), || within(budget.as_ref(), async {
+            calls.fetch_add(1, Ordering::SeqCst);
+            Err(PipelineError::ApiError("503".to_string()))
+        })).await;
+        
+        assert!(matches!(result, Err(PipelineError::Timeout { .. })));
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+        assert_eq!(started.elapsed(), Duration::ZERO);
+    }
+    
+    #[tokio::test(start_paused = true)]
+    async fn test_item_budget_starts_once_the_permit_is_held() {
+        let limiter = crate::concurrency::ApiLimiter::new(1);
+        let budget = ItemBudget::new(Duration::from_secs(10));
+        
+        // Another item's call holds the only permit for 30s
+        let busy = tokio::spawn({
+            let limiter = limiter.clone();
+            async move { limiter.call(tokio::time::sleep(Duration::from_secs(30))).await }
+        });
+        tokio::task::yield_now().await;
+        
+        let started = tokio::time::Instant::now();
+        let result = limiter.call(within(Some(&budget), async {
+            tokio::time::sleep(Duration::from_secs(5)).await;
+            Ok(())
+        })).await;
+        
+        assert!(result.is_ok());
+        assert_eq!(started.elapsed(), Duration::from_secs(35));
+        busy.await.unwrap();
+    }
+}
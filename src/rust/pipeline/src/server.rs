@@ -0,0 +1,155 @@
+use crate::errors::{PipelineError, Result};
+use crate::monitoring::{MetricsCollector, HealthChecker};
+use axum::{
+    extract::State,
+    http::{header, StatusCode},
+    response::IntoResponse,
+    routing::get,
+    Json, Router,
+};
+use std::net::SocketAddr;
+use std::sync::Arc;
+use tracing::{info, error};
+
+/// Components shared with the running `Pipeline`, so the endpoints report on
+/// in-progress batches rather than a separate copy of the metrics.
+#[derive(Clone)]
+pub struct ServerState {
+    pub health_checker: Arc<HealthChecker>,
+    pub metrics_collector: Arc<MetricsCollector>,
+}
+
+pub fn router(state: ServerState) -> Router {
+    Router::new()
+        .route("/health", get(health))
+        .route("/readyz", get(readyz))
+        .route("/metrics", get(metrics))
+        .with_state(state)
+}
+
+/// Serve `/health`, `/readyz` and `/metrics` on `addr` until the task is dropped.
+pub async fn serve(addr: SocketAddr, state: ServerState) -> Result<()> {
+    let listener = tokio::net::TcpListener::bind(addr).await?;
+    
+    info!("Health server listening on {}", addr);
+    axum::serve(listener, router(state))
+        .await
+        .map_err(PipelineError::IoError)
+}
+
+async fn health(State(state): State<ServerState>) -> impl IntoResponse {
+    match state.health_checker.check_health().await {
+        Ok(health) => {
+            let code = if health.healthy {
+                StatusCode::OK
+            } else {
+                StatusCode::SERVICE_UNAVAILABLE
+            };
+            (code, Json(health)).into_response()
+        }
+        Err(e) => {
+            error!("Health check failed: {}", e);
+            (
+                StatusCode::SERVICE_UNAVAILABLE,
+                Json(serde_json::json!({ "healthy": false, "error": e.to_string() })),
+            )
+                .into_response()
+        }
+    }
+}
+
+/// Ready once the database answers; API degradation doesn't stop us taking work.
+async fn readyz(State(state): State<ServerState>) -> impl IntoResponse {
+    match state.health_checker.check_health().await {
+        Ok(health) if health.database_status.is_healthy() => (StatusCode::OK, "ready"),
+        _ => (StatusCode::SERVICE_UNAVAILABLE, "not ready"),
+    }
+}
+
+async fn metrics(State(state): State<ServerState>) -> impl IntoResponse {
+    let mut body = state.metrics_collector.get_metrics().to_prometheus_format();
+    match state.health_checker.queue_depth().await {
+        Ok(depth) => {
+            body.push_str("# HELP pipeline_queue_depth Items waiting in the processing queue\n");
+            body.push_str("# TYPE pipeline_queue_depth gauge\n");
+            body.push_str(&format!("pipeline_queue_depth {}\n", depth));
+        }
+        Err(e) => error!("Could not read queue depth: {}", e),
+    }
+    (
+        [(header::CONTENT_TYPE, "text/plain; version=0.0.4")],
+        body,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::test_pipeline;
+    use crate::monitoring::QueueDepthThresholds;
+    use crate::pipeline::PipelineConfig;
+    use axum::body::{to_bytes, Body};
+    use axum::http::Request;
+    use tower::ServiceExt;
+    
+    /// The endpoints over a pipeline with two items queued, whose queue is
+    /// unhealthy above `fail_depth` pending items
+    async fn test_router(fail_depth: Option<i64>) -> (tempfile::TempDir, Router) {
+        let (dir, pipeline) = test_pipeline(|config| PipelineConfig {
+            // Only the database, cache and queue are checked then
+            cache_only: true,
+            queue_thresholds: QueueDepthThresholds { warn_depth: None, fail_depth },
+            ..config
+        }).await;
+        pipeline.enqueue(&mut crate::bench::synthetic_items(2)).await.unwrap();
+        let state = ServerState {
+            health_checker: Arc::clone(&pipeline.health_checker),
+            metrics_collector: Arc::clone(&pipeline.metrics_collector),
+        };
+        (dir, router(state))
+    }
+    
+    async fn get(router: Router, path: &str) -> (StatusCode, String) {
+        let response = router.oneshot(Request::get(path).body(Body::empty()).unwrap()).await.unwrap();
+        let status = response.status();
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        (status, String::from_utf8(body.to_vec()).unwrap())
+    }
+    
+    #[tokio::test]
+    async fn test_health_answers_503_once_the_queue_backs_up() {
+        let (_healthy_dir, healthy) = test_router(None).await;
+        let (_backed_up_dir, backed_up) = test_router(Some(1)).await;
+        
+        let (status, body) = get(healthy, "/health").await;
+        assert_eq!(status, StatusCode::OK);
+        let health: serde_json::Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(health["healthy"], true);
+        assert_eq!(health["queue_depth"], 2);
+        
+        let (status, body) = get(backed_up, "/health").await;
+        assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+        let health: serde_json::Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(health["healthy"], false);
+    }
+    
+    #[tokio::test]
+    async fn test_readyz_ignores_the_queue_backlog() {
+        let (_dir, router) = test_router(Some(1)).await;
+        
+        let (status, body) = get(router, "/readyz").await;
+        
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body, "ready");
+    }
+    
+    #[tokio::test]
+    async fn test_metrics_report_queue_depth() {
+        let (_dir, router) = test_router(None).await;
+        
+        let (status, body) = get(router, "/metrics").await;
+        
+        assert_eq!(status, StatusCode::OK);
+        assert!(body.lines().any(|line| line == "pipeline_queue_depth 2"), "{}", body);
+    }
+}
@@ -0,0 +1,94 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio_util::sync::CancellationToken;
+use tracing::warn;
+
+/// How long items in flight may take to finish after a shutdown signal
+/// before they are interrupted
+pub const DEFAULT_SHUTDOWN_GRACE_PERIOD: Duration = Duration::from_secs(30);
+
+/// Turns SIGINT and SIGTERM into the pipeline's two ways of stopping.
+///
+/// The first signal drains: no new item or batch starts, items in flight
+/// finish and are exported, and the run ends as it would have otherwise,
+/// with the rest left pending for a resume. A second signal, or the grace
+/// period running out, cancels the abort token instead, which cuts the
+/// items in flight off and checkpoints them as pending.
+#[derive(Clone)]
+pub struct ShutdownCoordinator {
+    drain: CancellationToken,
+    abort: CancellationToken,
+    grace_period: Duration,
+    requests: Arc<AtomicUsize>,
+}
+
+impl ShutdownCoordinator {
+    /// Cancel `drain` on the first request and `abort` on the second, or
+    /// `grace_period` after the first.
+    pub fn new(drain: CancellationToken, abort: CancellationToken, grace_period: Duration) -> Self {
+        Self {
+            drain,
+            abort,
+            grace_period,
+            requests: Arc::new(AtomicUsize::new(0)),
+        }
+    }
+    
+    /// Handle one shutdown request, as if a signal had arrived.
+    pub fn request(&self) {
+        if self.requests.fetch_add(1, Ordering::SeqCst) > 0 {
+            warn!("Stopping now, writing checkpoint...");
+            self.abort.cancel();
+            return;
+        }
+        
+        warn!(
+            "Shutting down: finishing items in flight for up to {}; signal again to stop now",
+            humantime::format_duration(self.grace_period)
+        );
+        self.drain.cancel();
+        
+        let abort = self.abort.clone();
+        let grace_period = self.grace_period;
+        tokio::spawn(async move {
+            tokio::select! {
+                _ = tokio::time::sleep(grace_period) => {
+                    warn!("Grace period over, writing checkpoint...");
+                    abort.cancel();
+                }
+                _ = abort.cancelled() => {}
+            }
+        });
+    }
+    
+    /// Call [`request`](Self::request) on every SIGINT, and on Unix every
+    /// SIGTERM, for the rest of the process.
+    pub fn install(&self) {
+        let coordinator = self.clone();
+        tokio::spawn(async move {
+            while tokio::signal::ctrl_c().await.is_ok() {
+                coordinator.request();
+            }
+        });
+        
+        #[cfg(unix)]
+        {
+            use tokio::signal::unix::{signal, SignalKind};
+            
+            let coordinator = self.clone();
+            tokio::spawn(async move {
+                let mut terminate = match signal(SignalKind::terminate()) {
+                    Ok(terminate) => terminate,
+                    Err(e) => {
+                        warn!("Not listening for SIGTERM: {}", e);
+                        return;
+                    }
+                };
+                while terminate.recv().await.is_some() {
+                    coordinator.request();
+                }
+            });
+        }
+    }
+}
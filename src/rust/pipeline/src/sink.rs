@@ -0,0 +1,217 @@
+//! Write targets for exported cards.
+//!
+//! Exporters write through an [`OutputSink`] rather than opening a path
+//! themselves, so the same exporter can fill a file, stream to stdout or
+//! upload to S3. [`open_sink`] picks the sink for an `--output` value.
+
+use crate::errors::{PipelineError, Result};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use flashcard_core::models::BatchId;
+use std::path::{Path, PathBuf};
+use tokio::io::AsyncWriteExt;
+
+/// The `--output` value that means stdout
+pub const STDOUT_PATH: &str = "-";
+
+/// Somewhere exported bytes go.
+///
+/// Each `write_all` is handed to the target as it arrives, so a streaming
+/// export keeps every card written before a crash. Call
+/// [`finish`](Self::finish) once at the end to flush and close.
+#[async_trait]
+pub trait OutputSink: Send {
+    async fn write_all(&mut self, data: &[u8]) -> Result<()>;
+    
+    /// Write `line` followed by a newline.
+    async fn write_line(&mut self, line: &str) -> Result<()> {
+        let mut data = Vec::with_capacity(line.len() + 1);
+        data.extend_from_slice(line.as_bytes());
+        data.push(b'\n');
+        self.write_all(&data).await
+    }
+    
+    async fn finish(&mut self) -> Result<()>;
+}
+
+/// Whether `path` is the `-` that stands for stdout.
+pub fn is_stdout(path: &Path) -> bool {
+    path.as_os_str() == STDOUT_PATH
+}
+
+/// Whether an `--output` value names a directory to put files in: it ends
+/// with a path separator or is an existing directory.
+pub fn is_output_dir(path: &Path) -> bool {
+    if is_stdout(path) || path.to_str().is_some_and(|path| path.starts_with("s3://")) {
+        return false;
+    }
+    path.to_str().is_some_and(|path| path.ends_with(std::path::is_separator)) || path.is_dir()
+}
+
+/// A file in output directory `dir` named after `batch_id` and `at`, e.g.
+/// `exports/batch_12_20261016_093000.tsv`, so runs never overwrite each
+/// other's output.
+pub fn batch_output_path(dir: &Path, batch_id: &BatchId, at: DateTime<Utc>, extension: &str) -> PathBuf {
+    dir.join(format!("batch_{}_{}.{}", batch_id, at.format("%Y%m%d_%H%M%S"), extension))
+}
+
+/// Open the sink for an output path: `-` is stdout, `s3://bucket/key` an S3
+/// object (with the `s3` feature), anything else a local file.
+pub async fn open_sink(path: &Path) -> Result<Box<dyn OutputSink>> {
+    if is_stdout(path) {
+        return Ok(Box::new(StdoutSink::new()));
+    }
+    
+    if let Some(location) = path.to_str().and_then(|path| path.strip_prefix("s3://")) {
+        #[cfg(feature = "s3")]
+        return Ok(Box::new(S3Sink::parse(location).await?));
+        
+        #[cfg(not(feature = "s3"))]
+        return Err(PipelineError::ConfigError(format!(
+            "Can't write to s3://{}: built without the `s3` feature", location
+        )));
+    }
+    
+    Ok(Box::new(FileSink::create(path).await?))
+}
+
+/// A local file, created (with its parent directories) on open
+pub struct FileSink {
+    path: PathBuf,
+    file: tokio::fs::File,
+}
+
+impl FileSink {
+    pub async fn create(path: &Path) -> Result<Self> {
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+        
+        Ok(Self {
+            path: path.to_owned(),
+            file: tokio::fs::File::create(path).await?,
+        })
+    }
+    
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+#[async_trait]
+impl OutputSink for FileSink {
+    async fn write_all(&mut self, data: &[u8]) -> Result<()> {
+        self.file.write_all(data).await?;
+        Ok(())
+    }
+    
+    async fn finish(&mut self) -> Result<()> {
+        self.file.flush().await?;
+        self.file.sync_all().await?;
+        Ok(())
+    }
+}
+
+/// The process's stdout
+pub struct StdoutSink {
+    stdout: tokio::io::Stdout,
+}
+
+impl StdoutSink {
+    pub fn new() -> Self {
+        Self { stdout: tokio::io::stdout() }
+    }
+}
+
+impl Default for StdoutSink {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl OutputSink for StdoutSink {
+    async fn write_all(&mut self, data: &[u8]) -> Result<()> {
+        self.stdout.write_all(data).await?;
+        self.stdout.flush().await?;
+        Ok(())
+    }
+    
+    async fn finish(&mut self) -> Result<()> {
+        self.stdout.flush().await?;
+        Ok(())
+    }
+}
+
+/// Collects the output in memory
+#[async_trait]
+impl OutputSink for Vec<u8> {
+    async fn write_all(&mut self, data: &[u8]) -> Result<()> {
+        self.extend_from_slice(data);
+        Ok(())
+    }
+    
+    async fn finish(&mut self) -> Result<()> {
+        Ok(())
+    }
+}
+
+/// An S3 object. The body is buffered in memory and uploaded in one
+/// `PutObject` on [`finish`](OutputSink::finish), so nothing reaches the
+/// bucket if the export fails part way.
+#[cfg(feature = "s3")]
+pub struct S3Sink {
+    client: aws_sdk_s3::Client,
+    bucket: String,
+    key: String,
+    body: Vec<u8>,
+}
+
+#[cfg(feature = "s3")]
+impl S3Sink {
+    /// Credentials and region come from the usual AWS environment.
+    pub async fn new(bucket: impl Into<String>, key: impl Into<String>) -> Self {
+        let config = aws_config::load_defaults(aws_config::BehaviorVersion::latest()).await;
+        Self {
+            client: aws_sdk_s3::Client::new(&config),
+            bucket: bucket.into(),
+            key: key.into(),
+            body: Vec::new(),
+        }
+    }
+    
+    /// `location` is `bucket/key`, i.e. an `s3://` URL without the scheme.
+    async fn parse(location: &str) -> Result<Self> {
+        match location.split_once('/') {
+            Some((bucket, key)) if !bucket.is_empty() && !key.is_empty() => {
+                Ok(Self::new(bucket, key).await)
+            }
+            _ => Err(PipelineError::ConfigError(format!(
+                "Expected s3://bucket/key, got s3://{}", location
+            ))),
+        }
+    }
+}
+
+#[cfg(feature = "s3")]
+#[async_trait]
+impl OutputSink for S3Sink {
+    async fn write_all(&mut self, data: &[u8]) -> Result<()> {
+        self.body.extend_from_slice(data);
+        Ok(())
+    }
+    
+    async fn finish(&mut self) -> Result<()> {
+        let body = std::mem::take(&mut self.body);
+        self.client.put_object()
+            .bucket(&self.bucket)
+            .key(&self.key)
+            .body(aws_sdk_s3::primitives::ByteStream::from(body))
+            .send()
+            .await
+            .map_err(|e| PipelineError::ExportError(format!(
+                "Upload to s3://{}/{} failed: {}", self.bucket, self.key, e
+            )))?;
+        Ok(())
+    }
+}
@@ -0,0 +1,333 @@
+//! Token estimates for items before they are sent, e.g. to price a run.
+
+use crate::errors::{PipelineError, Result};
+use crate::monitoring::ApiStage;
+use crate::pipeline::PipelineConfig;
+use crate::python_bridge::ModelSelection;
+use flashcard_core::models::VocabularyItem;
+use serde::Serialize;
+use std::path::Path;
+#[cfg(feature = "tokenizer")]
+use std::sync::Arc;
+
+/// Typical size of the Stage 1 analysis passed on to Stage 2
+pub const STAGE1_RESULT_TOKENS: usize = 400;
+
+/// Tokens per Hangul syllable. BPE vocabularies hold the common syllables
+/// whole and split rarer ones, e.g. 떡 or 볶, into two or three byte-level
+/// pieces; 1.2 is the average over typical vocabulary.
+const HANGUL_SYLLABLE_TOKENS: f64 = 1.2;
+/// ASCII letters and digits per token within a word
+const ASCII_CHARS_PER_TOKEN: f64 = 6.0;
+/// UTF-8 bytes per token for scripts the approximation knows nothing about
+const OTHER_BYTES_PER_TOKEN: f64 = 2.0;
+
+/// How text is turned into a token count
+#[derive(Clone)]
+enum Tokenizer {
+    /// Character-class heuristic tuned to `cl100k_base`
+    Approximate,
+    /// The model's own BPE, or the closest public one
+    #[cfg(feature = "tokenizer")]
+    Bpe(Arc<tiktoken_rs::CoreBPE>),
+}
+
+impl Tokenizer {
+    /// The tokenizer `model` uses, e.g. `openai/gpt-4o`. Models without a
+    /// public one, such as Claude, are counted with `cl100k_base`.
+    #[cfg(feature = "tokenizer")]
+    fn for_model(model: &str) -> Self {
+        let name = model.rsplit('/').next().unwrap_or(model);
+        let bpe = tiktoken_rs::get_bpe_from_model(name).or_else(|_| tiktoken_rs::cl100k_base());
+        match bpe {
+            Ok(bpe) => Tokenizer::Bpe(Arc::new(bpe)),
+            Err(e) => {
+                tracing::warn!("No tokenizer for {}, approximating token counts: {}", model, e);
+                Tokenizer::Approximate
+            }
+        }
+    }
+    
+    #[cfg(not(feature = "tokenizer"))]
+    fn for_model(_model: &str) -> Self {
+        Tokenizer::Approximate
+    }
+    
+    fn count(&self, text: &str) -> usize {
+        match self {
+            Tokenizer::Approximate => approximate_tokens(text),
+            #[cfg(feature = "tokenizer")]
+            Tokenizer::Bpe(bpe) => bpe.encode_ordinary(text).len(),
+        }
+    }
+}
+
+/// Approximate the `cl100k_base` token count of `text`.
+///
+/// A space is folded into the token after it, as BPE vocabularies do. Each
+/// ASCII word costs a token per six letters or digits, each punctuation
+/// mark one, and each Hangul syllable [`HANGUL_SYLLABLE_TOKENS`], so Korean
+/// costs several times more per character than English.
+pub fn approximate_tokens(text: &str) -> usize {
+    let ascii_run = |len: usize| (len as f64 / ASCII_CHARS_PER_TOKEN).ceil();
+    let mut tokens = 0.0;
+    
+    for word in text.split_whitespace() {
+        let mut run = 0;
+        for c in word.chars() {
+            if c.is_ascii_alphanumeric() {
+                run += 1;
+                continue;
+            }
+            tokens += ascii_run(run);
+            run = 0;
+            tokens += if is_hangul_syllable(c) {
+                HANGUL_SYLLABLE_TOKENS
+            } else if c.is_ascii() {
+                1.0
+            } else {
+                (c.len_utf8() as f64 / OTHER_BYTES_PER_TOKEN).max(1.0)
+            };
+        }
+        tokens += ascii_run(run);
+    }
+    
+    tokens.ceil() as usize
+}
+
+fn is_hangul_syllable(c: char) -> bool {
+    ('\u{AC00}'..='\u{D7A3}').contains(&c)
+}
+
+/// The message the orchestrator sends `stage` for `item` without a custom
+/// template, as `Stage1Request`/`Stage2Request` build it: the item as JSON,
+/// with Python's `json.dumps` separators. The instructions themselves live
+/// in the OpenRouter preset the request names, so they aren't counted.
+/// Stage 2's `stage1_result` is left empty; see [`STAGE1_RESULT_TOKENS`].
+fn built_in_message(item: &VocabularyItem, stage: ApiStage) -> String {
+    let term = serde_json::Value::from(item.term.as_str());
+    match stage {
+        ApiStage::Stage1 => format!(
+            "{{\"position\": {}, \"term\": {}, \"type\": {}}}",
+            item.position,
+            term,
+            serde_json::Value::from(item.word_type.as_deref()),
+        ),
+        ApiStage::Stage2 => format!(
+            "{{\"position\": {}, \"term\": {}, \"stage1_result\": {{}}}}",
+            item.position,
+            term,
+        ),
+    }
+}
+
+/// Estimated prompt tokens of one stage's requests
+#[derive(Clone)]
+struct StageTokens {
+    tokenizer: Tokenizer,
+    /// A custom prompt template's own tokens, sent with every item; `None`
+    /// for the built-in message
+    template: Option<usize>,
+}
+
+/// Tokens the items of a batch will be sent with, per stage
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct TokenEstimate {
+    pub stage1: usize,
+    pub stage2: usize,
+}
+
+impl TokenEstimate {
+    pub fn total(&self) -> usize {
+        self.stage1 + self.stage2
+    }
+}
+
+/// Estimates the prompt tokens each item is sent with, before any API call.
+///
+/// Counts are exact for the text itself when the `tokenizer` feature is
+/// on and the model's tokenizer is public; otherwise they come from
+/// [`approximate_tokens`]. Stage 2's Stage 1 analysis is estimated either
+/// way, and a custom template is counted before its placeholders are
+/// filled in, so treat the result as a budget rather than a bill.
+#[derive(Clone)]
+pub struct TokenEstimator {
+    stage1: StageTokens,
+    stage2: StageTokens,
+}
+
+impl Default for TokenEstimator {
+    fn default() -> Self {
+        Self::for_models(&ModelSelection::default())
+    }
+}
+
+impl TokenEstimator {
+    /// Count with each stage's model's tokenizer, and the built-in prompts.
+    pub fn for_models(models: &ModelSelection) -> Self {
+        Self {
+            stage1: StageTokens {
+                tokenizer: Tokenizer::for_model(&models.stage1),
+                template: None,
+            },
+            stage2: StageTokens {
+                tokenizer: Tokenizer::for_model(&models.stage2),
+                template: None,
+            },
+        }
+    }
+    
+    /// The estimator for the models and prompt templates `config` runs.
+    pub fn from_config(config: &PipelineConfig) -> Result<Self> {
+        let mut estimator = Self::for_models(&config.models());
+        let prompts = config.prompts();
+        for (stage, path) in [(ApiStage::Stage1, &prompts.stage1), (ApiStage::Stage2, &prompts.stage2)] {
+            if let Some(path) = path {
+                estimator = estimator.with_prompt_template(stage, &read_template(path)?);
+            }
+        }
+        Ok(estimator)
+    }
+    
+    /// Count `template` as `stage`'s prompt instead of the built-in one.
+    pub fn with_prompt_template(mut self, stage: ApiStage, template: &str) -> Self {
+        let stage = self.stage_mut(stage);
+        stage.template = Some(stage.tokenizer.count(template));
+        self
+    }
+    
+    /// Tokens `text` comes to with `stage`'s tokenizer.
+    pub fn count(&self, stage: ApiStage, text: &str) -> usize {
+        self.stage(stage).tokenizer.count(text)
+    }
+    
+    /// Prompt tokens `item` will be sent to `stage` with: the built-in
+    /// message, or a custom template plus the term and its word type, and
+    /// for Stage 2 the Stage 1 analysis.
+    pub fn estimate(&self, item: &VocabularyItem, stage: ApiStage) -> usize {
+        let tokens = self.stage(stage);
+        let mut estimate = match tokens.template {
+            None => tokens.tokenizer.count(&built_in_message(item, stage)),
+            Some(template) => {
+                let word_type = item.word_type.as_deref().map_or(0, |word_type| tokens.tokenizer.count(word_type));
+                template + tokens.tokenizer.count(&item.term) + word_type
+            }
+        };
+        if stage == ApiStage::Stage2 {
+            estimate += STAGE1_RESULT_TOKENS;
+        }
+        estimate
+    }
+    
+    /// Prompt tokens for every item of a batch, per stage. Items that turn
+    /// out cached cost nothing, so this is the most a batch can use.
+    pub fn estimate_batch(&self, items: &[VocabularyItem]) -> TokenEstimate {
+        items.iter().fold(TokenEstimate::default(), |total, item| TokenEstimate {
+            stage1: total.stage1 + self.estimate(item, ApiStage::Stage1),
+            stage2: total.stage2 + self.estimate(item, ApiStage::Stage2),
+        })
+    }
+    
+    fn stage(&self, stage: ApiStage) -> &StageTokens {
+        match stage {
+            ApiStage::Stage1 => &self.stage1,
+            ApiStage::Stage2 => &self.stage2,
+        }
+    }
+    
+    fn stage_mut(&mut self, stage: ApiStage) -> &mut StageTokens {
+        match stage {
+            ApiStage::Stage1 => &mut self.stage1,
+            ApiStage::Stage2 => &mut self.stage2,
+        }
+    }
+}
+
+fn read_template(path: &Path) -> Result<String> {
+    std::fs::read_to_string(path).map_err(|e| PipelineError::ConfigError(format!(
+        "Can't read prompt template {}: {}", path.display(), e
+    )))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    /// `cl100k_base` counts, as tiktoken reports them
+    const KNOWN_COUNTS: &[(&str, usize)] = &[
+        ("학교", 4),
+        ("사과", 2),
+        ("떡볶이", 6),
+        ("안녕하세요", 5),
+        ("감사합니다", 4),
+        ("잘 지내세요", 5),
+        ("한국어를 공부하고 있어요", 13),
+        ("school", 1),
+        ("How are you doing today?", 6),
+        ("The quick brown fox jumps over the lazy dog.", 10),
+        ("{\"term\": \"학교\"}", 9),
+    ];
+    
+    fn within_tolerance(estimated: usize, known: usize) -> bool {
+        // Two tokens either way for short strings, a quarter for long ones
+        let tolerance = (known / 4).max(2);
+        estimated.abs_diff(known) <= tolerance
+    }
+    
+    #[test]
+    fn test_approximation_tracks_known_counts() {
+        for &(text, known) in KNOWN_COUNTS {
+            let estimated = approximate_tokens(text);
+            assert!(within_tolerance(estimated, known), "{:?}: estimated {}, known {}", text, estimated, known);
+        }
+        
+        // Korean costs more per character than English
+        assert!(approximate_tokens("안녕하세요") > approximate_tokens("hello"));
+        assert_eq!(approximate_tokens("   "), 0);
+    }
+    
+    #[cfg(feature = "tokenizer")]
+    #[test]
+    fn test_bpe_matches_known_counts() {
+        let estimator = TokenEstimator::for_models(&ModelSelection::single("openai/gpt-4"));
+        for &(text, known) in KNOWN_COUNTS {
+            assert_eq!(estimator.count(ApiStage::Stage1, text), known, "{:?}", text);
+        }
+    }
+    
+    #[test]
+    fn test_estimate_adds_template_and_stage1_analysis() {
+        let item = crate::bench::synthetic_items(1).remove(0);
+        let estimator = TokenEstimator::default()
+            .with_prompt_template(ApiStage::Stage1, "Analyze the Korean term");
+        let term_tokens = estimator.count(ApiStage::Stage1, &item.term)
+            + item.word_type.as_deref().map_or(0, |word_type| estimator.count(ApiStage::Stage1, word_type));
+        
+        let stage1 = estimator.estimate(&item, ApiStage::Stage1);
+        let stage2 = estimator.estimate(&item, ApiStage::Stage2);
+        
+        assert_eq!(stage1, estimator.count(ApiStage::Stage1, "Analyze the Korean term") + term_tokens);
+        // Stage 2 still sends the built-in message
+        let message = format!("{{\"position\": 1, \"term\": \"{}\", \"stage1_result\": {{}}}}", item.term);
+        assert_eq!(stage2, estimator.count(ApiStage::Stage2, &message) + STAGE1_RESULT_TOKENS);
+        
+        let batch = estimator.estimate_batch(&[item.clone(), item]);
+        assert_eq!(batch, TokenEstimate { stage1: 2 * stage1, stage2: 2 * stage2 });
+    }
+    
+    #[test]
+    fn test_built_in_prompt_is_counted_from_the_request() {
+        let mut item = crate::bench::synthetic_items(1).remove(0);
+        item.term = "학교".to_string();
+        let estimator = TokenEstimator::default();
+        
+        let message = r#"{"position": 1, "term": "학교", "type": "noun"}"#;
+        assert_eq!(built_in_message(&item, ApiStage::Stage1), message);
+        assert_eq!(estimator.estimate(&item, ApiStage::Stage1), estimator.count(ApiStage::Stage1, message));
+        
+        // A longer term costs more, with nothing fixed added on top
+        item.term = "한국어를 공부하고 있어요".to_string();
+        assert!(estimator.estimate(&item, ApiStage::Stage1) > estimator.count(ApiStage::Stage1, message));
+        assert!(estimator.estimate(&item, ApiStage::Stage1) < 50);
+    }
+}
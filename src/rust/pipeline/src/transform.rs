@@ -0,0 +1,239 @@
+//! Post-processing applied to generated cards before they are exported.
+
+use crate::errors::{PipelineError, Result};
+use flashcard_core::models::{FlashcardContent, Stage1Result, Stage2Result, VocabularyItem};
+use std::sync::Arc;
+
+/// A rewrite of one face of a card, e.g. stripping markup or adding a tag.
+///
+/// Implement this to register your own step in a [`TransformChain`]. An
+/// error aborts the export and is reported with the transform's name.
+pub trait CardTransform: Send + Sync {
+    /// Short name used in error messages and logs
+    fn name(&self) -> &str;
+    
+    fn apply(&self, content: &mut FlashcardContent) -> Result<()>;
+}
+
+/// Transforms run over both faces of every card, in the order they were added.
+#[derive(Clone, Default)]
+pub struct TransformChain {
+    transforms: Vec<Arc<dyn CardTransform>>,
+}
+
+impl TransformChain {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    
+    /// Append `transform` to the end of the chain.
+    pub fn with<T: CardTransform + 'static>(mut self, transform: T) -> Self {
+        self.transforms.push(Arc::new(transform));
+        self
+    }
+    
+    pub fn is_empty(&self) -> bool {
+        self.transforms.is_empty()
+    }
+    
+    /// Names of the transforms, in the order they run
+    pub fn names(&self) -> Vec<&str> {
+        self.transforms.iter().map(|transform| transform.name()).collect()
+    }
+    
+    /// Run every transform over the front and then the back of `card`.
+    pub fn apply(&self, item: &VocabularyItem, card: &mut Stage2Result) -> Result<()> {
+        for transform in &self.transforms {
+            for content in [&mut card.front, &mut card.back] {
+                transform.apply(content).map_err(|e| PipelineError::TransformFailed {
+                    transform: transform.name().to_string(),
+                    term: item.term.clone(),
+                    message: e.to_string(),
+                })?;
+            }
+        }
+        Ok(())
+    }
+    
+    /// [`apply`](Self::apply) to every card, stopping at the first failure.
+    pub fn apply_all(&self, cards: &mut [(VocabularyItem, Stage1Result, Stage2Result)]) -> Result<()> {
+        for (item, _, card) in cards {
+            self.apply(item, card)?;
+        }
+        Ok(())
+    }
+}
+
+/// Removes HTML tags and decodes the common entities in every text field.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct HtmlStrip;
+
+impl CardTransform for HtmlStrip {
+    fn name(&self) -> &str {
+        "html-strip"
+    }
+    
+    fn apply(&self, content: &mut FlashcardContent) -> Result<()> {
+        for_each_text_field(content, |text| *text = strip_html(text));
+        Ok(())
+    }
+}
+
+/// Trims leading and trailing whitespace from every text field.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TrimWhitespace;
+
+impl CardTransform for TrimWhitespace {
+    fn name(&self) -> &str {
+        "trim-whitespace"
+    }
+    
+    fn apply(&self, content: &mut FlashcardContent) -> Result<()> {
+        for_each_text_field(content, |text| {
+            let trimmed = text.trim();
+            if trimmed.len() != text.len() {
+                *text = trimmed.to_string();
+            }
+        });
+        Ok(())
+    }
+}
+
+/// Adds a thematic tag to every card, e.g. a deck or source marker.
+#[derive(Debug, Clone)]
+pub struct AppendTag(pub String);
+
+impl CardTransform for AppendTag {
+    fn name(&self) -> &str {
+        "append-tag"
+    }
+    
+    fn apply(&self, content: &mut FlashcardContent) -> Result<()> {
+        if self.0.trim().is_empty() {
+            return Err(PipelineError::ConfigError("tag is empty".to_string()));
+        }
+        if !content.thematic_tags.contains(&self.0) {
+            content.thematic_tags.push(self.0.clone());
+        }
+        Ok(())
+    }
+}
+
+/// Clears every generated field except the primary fields and the
+/// pronunciation guide, matching what Stage 2 produces in
+/// [`Stage2Mode::Minimal`](flashcard_core::models::Stage2Mode). Tags are kept.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct EssentialFieldsOnly;
+
+impl CardTransform for EssentialFieldsOnly {
+    fn name(&self) -> &str {
+        "essential-fields-only"
+    }
+    
+    fn apply(&self, content: &mut FlashcardContent) -> Result<()> {
+        content.keep_essential_fields();
+        Ok(())
+    }
+}
+
+fn for_each_text_field(content: &mut FlashcardContent, mut f: impl FnMut(&mut String)) {
+    f(&mut content.primary_field);
+    
+    let optional = [
+        &mut content.secondary_field,
+        &mut content.tertiary_field,
+        &mut content.example_sentence,
+        &mut content.example_translation,
+        &mut content.pronunciation_guide,
+        &mut content.image_prompt,
+        &mut content.mnemonic_aid,
+        &mut content.grammar_notes,
+        &mut content.cultural_notes,
+        &mut content.usage_notes,
+        &mut content.style_register,
+    ];
+    for text in optional.into_iter().flatten() {
+        f(text);
+    }
+}
+
+fn strip_html(text: &str) -> String {
+    if !text.contains('<') && !text.contains('&') {
+        return text.to_string();
+    }
+    
+    let mut stripped = String::with_capacity(text.len());
+    let mut in_tag = false;
+    for c in text.chars() {
+        match c {
+            '<' => in_tag = true,
+            '>' if in_tag => in_tag = false,
+            _ if !in_tag => stripped.push(c),
+            _ => {}
+        }
+    }
+    
+    stripped
+        .replace("&nbsp;", " ")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&amp;", "&")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures;
+    
+    fn card(front: &str, back: &str) -> (VocabularyItem, Stage2Result) {
+        fixtures::card(1, front, back)
+    }
+    
+    struct Fails;
+    
+    impl CardTransform for Fails {
+        fn name(&self) -> &str {
+            "fails"
+        }
+        
+        fn apply(&self, _content: &mut FlashcardContent) -> Result<()> {
+            Err(PipelineError::InvalidFormat("no good".to_string()))
+        }
+    }
+    
+    #[test]
+    fn test_transforms_run_in_order() {
+        let (item, mut stage2) = card("사과", "  <b>apple</b> &amp; pear ");
+        stage2.back.example_sentence = Some("<i>I eat</i>".to_string());
+        
+        let chain = TransformChain::new()
+            .with(HtmlStrip)
+            .with(TrimWhitespace)
+            .with(AppendTag("imported".to_string()));
+        assert_eq!(chain.names(), ["html-strip", "trim-whitespace", "append-tag"]);
+        
+        chain.apply(&item, &mut stage2).unwrap();
+        assert_eq!(stage2.back.primary_field, "apple & pear");
+        assert_eq!(stage2.back.example_sentence.as_deref(), Some("I eat"));
+        assert_eq!(stage2.front.thematic_tags, ["imported"]);
+        
+        // Running again doesn't duplicate the tag
+        chain.apply(&item, &mut stage2).unwrap();
+        assert_eq!(stage2.front.thematic_tags, ["imported"]);
+    }
+    
+    #[test]
+    fn test_failure_names_the_transform() {
+        let (item, mut stage2) = card("사과", "apple");
+        let chain = TransformChain::new().with(TrimWhitespace).with(Fails);
+        
+        let err = chain.apply(&item, &mut stage2).unwrap_err();
+        assert!(matches!(
+            &err,
+            PipelineError::TransformFailed { transform, term, .. } if transform == "fails" && term == "사과"
+        ));
+        assert!(err.to_string().contains("no good"));
+    }
+}